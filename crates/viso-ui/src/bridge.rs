@@ -7,6 +7,8 @@
 //! **Outbound** (WASM → native): we call `window.ipc.postMessage(json)` to
 //! send [`UiAction`]s back to the engine.
 
+use std::collections::HashSet;
+
 use dioxus::signals::{Signal, Writable};
 use serde_json::Value;
 use wasm_bindgen::prelude::*;
@@ -412,6 +414,166 @@ pub fn register_load_status_listener(mut status_sig: Signal<Option<Value>>) {
     on_status.forget();
 }
 
+/// Register a listener for per-chain sequence tracks from the native
+/// engine.
+pub fn register_sequences_listener(mut sequences_sig: Signal<Option<Value>>) {
+    let on_sequences = Closure::<dyn FnMut(web_sys::CustomEvent)>::new(
+        move |evt: web_sys::CustomEvent| {
+            if let Some(json_str) = evt.detail().as_string() {
+                if let Ok(val) = serde_json::from_str::<Value>(&json_str) {
+                    sequences_sig.set(Some(val));
+                }
+            }
+        },
+    );
+    web_sys::window()
+        .expect("no global window")
+        .add_event_listener_with_callback(
+            "viso-sequences",
+            on_sequences.as_ref().unchecked_ref(),
+        )
+        .expect("failed to add viso-sequences listener");
+    on_sequences.forget();
+}
+
+/// Register a listener for the loaded structure's assemblies, unit cell
+/// and active symmetry view.
+pub fn register_symmetry_listener(mut symmetry_sig: Signal<Option<Value>>) {
+    let on_symmetry = Closure::<dyn FnMut(web_sys::CustomEvent)>::new(
+        move |evt: web_sys::CustomEvent| {
+            if let Some(json_str) = evt.detail().as_string() {
+                if let Ok(val) = serde_json::from_str::<Value>(&json_str) {
+                    symmetry_sig.set(Some(val));
+                }
+            }
+        },
+    );
+    web_sys::window()
+        .expect("no global window")
+        .add_event_listener_with_callback(
+            "viso-symmetry",
+            on_symmetry.as_ref().unchecked_ref(),
+        )
+        .expect("failed to add viso-symmetry listener");
+    on_symmetry.forget();
+}
+
+/// Send a `set_symmetry_view` action. `mode` is `"asymmetric"`,
+/// `"assembly"` (with `assembly_id`) or `"mates"` (with `radius` in Å).
+pub fn send_set_symmetry_view(
+    mode: &str,
+    assembly_id: Option<&str>,
+    radius: f64,
+) {
+    let msg = serde_json::json!({
+        "action": "set_symmetry_view",
+        "mode": mode,
+        "assembly_id": assembly_id,
+        "radius": radius,
+    });
+    post_message(&msg.to_string());
+}
+
+/// Send a `show_unit_cell` action to the native engine.
+pub fn send_show_unit_cell(show: bool) {
+    let msg = serde_json::json!({ "action": "show_unit_cell", "show": show });
+    post_message(&msg.to_string());
+}
+
+/// Register a listener for engine change events. Applies selection
+/// diffs to `selected_sig` and tracks the hovered residue (flat residue
+/// index) in `hovered_sig`.
+pub fn register_events_listener(
+    mut selected_sig: Signal<HashSet<i64>>,
+    mut hovered_sig: Signal<Option<i64>>,
+) {
+    let on_events = Closure::<dyn FnMut(web_sys::CustomEvent)>::new(
+        move |evt: web_sys::CustomEvent| {
+            let Some(json_str) = evt.detail().as_string() else {
+                return;
+            };
+            let Ok(Value::Array(events)) =
+                serde_json::from_str::<Value>(&json_str)
+            else {
+                return;
+            };
+            for event in &events {
+                match event.get("type").and_then(Value::as_str) {
+                    Some("selection_changed") => {
+                        apply_selection_diff(event, &mut selected_sig.write());
+                    }
+                    Some("hover_changed") => {
+                        let residue = event
+                            .get("target")
+                            .and_then(|t| t.get("residue"))
+                            .and_then(Value::as_i64);
+                        hovered_sig.set(residue);
+                    }
+                    _ => {}
+                }
+            }
+        },
+    );
+    web_sys::window()
+        .expect("no global window")
+        .add_event_listener_with_callback(
+            "viso-events",
+            on_events.as_ref().unchecked_ref(),
+        )
+        .expect("failed to add viso-events listener");
+    on_events.forget();
+}
+
+/// Apply a `selection_changed` event's `removed` / `added` indices.
+fn apply_selection_diff(event: &Value, selected: &mut HashSet<i64>) {
+    let indices = |key: &str| -> Vec<i64> {
+        event
+            .get(key)
+            .and_then(Value::as_array)
+            .map(|a| a.iter().filter_map(Value::as_i64).collect())
+            .unwrap_or_default()
+    };
+    for i in indices("removed") {
+        selected.remove(&i);
+    }
+    selected.extend(indices("added"));
+}
+
+/// Send a `select_residue` action to the native engine. `extend`
+/// toggles the residue instead of replacing the selection.
+pub fn send_select_residue(index: i64, extend: bool) {
+    let msg = serde_json::json!({
+        "action": "select_residue",
+        "index": index,
+        "extend": extend,
+    });
+    post_message(&msg.to_string());
+}
+
+/// Ask the native engine to recompute DSSP secondary structure at the
+/// displayed coordinates.
+pub fn send_compute_secondary_structure() {
+    let msg = serde_json::json!({ "action": "compute_secondary_structure" });
+    post_message(&msg.to_string());
+}
+
+/// Ask the native engine to compute per-residue solvent-accessible
+/// surface area (feeds the SASA color scheme).
+pub fn send_compute_sasa() {
+    let msg = serde_json::json!({ "action": "compute_sasa" });
+    post_message(&msg.to_string());
+}
+
+/// Ask the native engine to surface just the selected residues, with
+/// the rest of their entities still shaping the surface.
+pub fn send_surface_selection(kind: &str) {
+    let msg = serde_json::json!({
+        "action": "surface_selection",
+        "kind": kind,
+    });
+    post_message(&msg.to_string());
+}
+
 /// Call `window.ipc.postMessage(json)` to send a message to the native
 /// wry IPC handler.
 fn post_message(json: &str) {
@@ -5,9 +5,13 @@ use serde_json::Value;
 
 use crate::bridge;
 
-/// Load panel: PDB fetch form + local file browser + status line.
+/// Load panel: PDB fetch form + local file browser + assembly selector
+/// + status line.
 #[component]
-pub fn LoadPanel(load_status: Signal<Option<Value>>) -> Element {
+pub fn LoadPanel(
+    load_status: Signal<Option<Value>>,
+    symmetry: Signal<Option<Value>>,
+) -> Element {
     let mut pdb_id = use_signal(|| String::new());
     let mut source = use_signal(|| "rcsb".to_string());
 
@@ -92,12 +96,158 @@ pub fn LoadPanel(load_status: Signal<Option<Value>>) -> Element {
                 }
             }
 
+            // ── Assembly ──
+            {render_assembly(symmetry.read().as_ref())}
+
             // ── Status line ──
             {render_status(&status)}
         }
     }
 }
 
+/// Default crystal-mate search radius in Å.
+const DEFAULT_MATES_RADIUS: f64 = 8.0;
+
+/// Render the assembly selector (asymmetric unit, each biological
+/// assembly, crystal mates), the mates radius and the unit-cell toggle.
+/// Hidden when the loaded file has neither assemblies nor a cell.
+fn render_assembly(symmetry: Option<&Value>) -> Element {
+    let Some(sym) = symmetry else {
+        return rsx! {};
+    };
+    let assemblies: Vec<(String, String)> = sym
+        .get("assemblies")
+        .and_then(Value::as_array)
+        .map(|list| {
+            list.iter()
+                .filter_map(|a| {
+                    let id = a.get("id")?.as_str()?.to_owned();
+                    let copies =
+                        a.get("copies").and_then(Value::as_u64).unwrap_or(1);
+                    let label =
+                        a.get("details").and_then(Value::as_str).map_or_else(
+                            || format!("Assembly {id} ({copies}×)"),
+                            |details| {
+                                format!("Assembly {id} ({details}, {copies}×)")
+                            },
+                        );
+                    Some((id, label))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let has_cell = sym.get("cell").is_some_and(|c| !c.is_null());
+    let has_mates = sym
+        .get("has_crystal_symmetry")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if assemblies.is_empty() && !has_cell {
+        return rsx! {};
+    }
+
+    let view = sym.get("view");
+    let mode = view
+        .and_then(|v| v.get("mode"))
+        .and_then(Value::as_str)
+        .unwrap_or("asymmetric");
+    let current = match mode {
+        "assembly" => format!(
+            "assembly:{}",
+            view.and_then(|v| v.get("assembly_id"))
+                .and_then(Value::as_str)
+                .unwrap_or("")
+        ),
+        other => other.to_owned(),
+    };
+    let radius = view
+        .and_then(|v| v.get("radius"))
+        .and_then(Value::as_f64)
+        .unwrap_or(DEFAULT_MATES_RADIUS);
+    let show_cell = sym
+        .get("show_unit_cell")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let cell_label =
+        sym.get("space_group").and_then(Value::as_str).map_or_else(
+            || "Unit cell".to_owned(),
+            |sg| format!("Unit cell ({sg})"),
+        );
+
+    let mut choices = vec![("asymmetric".to_owned(), "Asymmetric unit".into())];
+    choices.extend(
+        assemblies
+            .into_iter()
+            .map(|(id, label)| (format!("assembly:{id}"), label)),
+    );
+    if has_mates {
+        choices.push(("mates".to_owned(), "Crystal mates".into()));
+    }
+
+    rsx! {
+        div { class: "load-section",
+            div { class: "load-section-title", "Assembly" }
+            div { class: "field-row",
+                select {
+                    class: "entity-option-select",
+                    value: "{current}",
+                    onchange: move |evt: Event<FormData>| {
+                        let value = evt.value();
+                        match value.strip_prefix("assembly:") {
+                            Some(id) => bridge::send_set_symmetry_view(
+                                "assembly",
+                                Some(id),
+                                radius,
+                            ),
+                            None => bridge::send_set_symmetry_view(
+                                &value, None, radius,
+                            ),
+                        }
+                    },
+                    for (value, label) in choices.iter() {
+                        option {
+                            value: "{value}",
+                            selected: *value == current,
+                            "{label}"
+                        }
+                    }
+                }
+            }
+            if mode == "mates" {
+                div { class: "field-row",
+                    label { class: "field-label", "Radius (Å)" }
+                    input {
+                        r#type: "number",
+                        class: "pdb-input",
+                        min: "0",
+                        max: "50",
+                        step: "1",
+                        value: "{radius}",
+                        onchange: move |evt: Event<FormData>| {
+                            if let Ok(r) = evt.value().parse::<f64>() {
+                                bridge::send_set_symmetry_view(
+                                    "mates", None, r,
+                                );
+                            }
+                        },
+                    }
+                }
+            }
+            if has_cell {
+                div { class: "field-row",
+                    label { class: "field-label", "{cell_label}" }
+                    input {
+                        r#type: "checkbox",
+                        checked: show_cell,
+                        onchange: move |evt: Event<FormData>| {
+                            bridge::send_show_unit_cell(evt.value() == "true");
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Render the status message line.
 fn render_status(status: &Option<Value>) -> Element {
     let Some(val) = status else {
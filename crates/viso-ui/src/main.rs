@@ -10,6 +10,7 @@ mod bridge;
 mod load_ui;
 mod scene_ui;
 mod schema_ui;
+mod sequence_ui;
 
 use std::collections::HashSet;
 
@@ -28,6 +29,13 @@ fn app() -> Element {
     let load_status: Signal<Option<Value>> = use_signal(|| None);
     let scene_entities: Signal<Option<Value>> = use_signal(|| None);
     let density_maps: Signal<Option<Value>> = use_signal(|| None);
+    let sequences: Signal<Option<Value>> = use_signal(|| None);
+    let symmetry: Signal<Option<Value>> = use_signal(|| None);
+
+    // Residue selection and hover (flat residue indices), kept in sync
+    // from engine event diffs.
+    let selected_residues: Signal<HashSet<i64>> = use_signal(HashSet::new);
+    let hovered_residue: Signal<Option<i64>> = use_signal(|| None);
 
     // Per-entity expanded state — lives at app level so it survives
     // tab switches (ScenePanel unmounts/remounts when switching tabs).
@@ -49,6 +57,9 @@ fn app() -> Element {
         bridge::register_load_status_listener(load_status);
         bridge::register_scene_entities_listener(scene_entities);
         bridge::register_density_maps_listener(density_maps);
+        bridge::register_sequences_listener(sequences);
+        bridge::register_symmetry_listener(symmetry);
+        bridge::register_events_listener(selected_residues, hovered_residue);
         bridge::register_panel_size_listener(panel_size);
 
         // The host pushes orientation via a 'viso-orientation' custom event.
@@ -180,6 +191,11 @@ fn app() -> Element {
                                 onclick: move |_| top_tab.set("scene".into()),
                                 "Scene"
                             }
+                            button {
+                                class: if current_tab == "sequence" { "top-tab active" } else { "top-tab" },
+                                onclick: move |_| top_tab.set("sequence".into()),
+                                "Sequence"
+                            }
                             button {
                                 class: if current_tab == "options" { "top-tab active" } else { "top-tab" },
                                 onclick: move |_| top_tab.set("options".into()),
@@ -215,8 +231,18 @@ fn app() -> Element {
                                 options: options,
                             }
                         },
+                        "sequence" => rsx! {
+                            sequence_ui::SequencePanel {
+                                sequences: sequences,
+                                selected: selected_residues,
+                                hovered: hovered_residue,
+                            }
+                        },
                         _ => rsx! {
-                            load_ui::LoadPanel { load_status: load_status }
+                            load_ui::LoadPanel {
+                                load_status: load_status,
+                                symmetry: symmetry,
+                            }
                         },
                     }
                 }
@@ -119,6 +119,17 @@ fn global_appearance_section(options: &Option<Value>) -> Element {
     let show_sidechains = display_bool(opts, "show_sidechains", true);
     let surface_kind = display_str(opts, "surface_kind", "none").to_owned();
     let surface_opacity = display_f64(opts, "surface_opacity", 0.35);
+    let surface_coloring =
+        display_str(opts, "surface_coloring", "entity").to_owned();
+    let potential_range = display_f64(opts, "surface_potential_range", 5.0);
+    let probe_radius = display_f64(opts, "surface_probe_radius", 1.4);
+    let default_resolution = match surface_kind.as_str() {
+        "ses" | "sas" | "vdw" => 0.5,
+        _ => 1.0,
+    };
+    let resolution =
+        display_f64(opts, "surface_resolution", default_resolution);
+    let surface_cutaway = display_bool(opts, "surface_cutaway", false);
     let show_cavities = display_bool(opts, "show_cavities", false);
     let helix_style = display_str(opts, "helix_style", "ribbon").to_owned();
     let sheet_style = display_str(opts, "sheet_style", "ribbon").to_owned();
@@ -150,14 +161,29 @@ fn global_appearance_section(options: &Option<Value>) -> Element {
                   ("b_factor", "B-Factor"),
                   ("hydrophobicity", "Hydrophobicity"),
                   ("score", "Score"), ("score_relative", "Score (Rel)"),
+                  ("rmsf", "RMSF"), ("sasa", "SASA"),
                   ("solid", "Solid")],
             )}
             {global_select(
                 "Surface", "surface_kind", &surface_kind,
-                &[("none", "None"), ("gaussian", "Gaussian"), ("ses", "SES")],
+                &[("none", "None"), ("gaussian", "Gaussian"), ("ses", "SES"),
+                  ("sas", "SAS"), ("vdw", "vdW"), ("dots", "Dots")],
             )}
             if surface_kind != "none" {
                 {global_slider("Opacity", "surface_opacity", surface_opacity, 0.0, 1.0, 0.01)}
+                if surface_kind == "ses" || surface_kind == "sas" {
+                    {global_slider("Probe Å", "surface_probe_radius", probe_radius, 0.5, 3.0, 0.1)}
+                }
+                {global_slider("Resolution Å", "surface_resolution", resolution, 0.2, 2.0, 0.05)}
+                {global_toggle("Cutaway", "surface_cutaway", surface_cutaway)}
+                {global_select(
+                    "Surface Color", "surface_coloring", &surface_coloring,
+                    &[("entity", "Entity"), ("residue", "Residue"),
+                      ("electrostatic", "Electrostatic")],
+                )}
+                if surface_coloring == "electrostatic" {
+                    {global_slider("±kT/e", "surface_potential_range", potential_range, 0.5, 20.0, 0.5)}
+                }
             }
             {global_toggle("Cavities", "show_cavities", show_cavities)}
             {global_select(
@@ -463,6 +489,10 @@ fn entity_row(
     let has_surface_ovr = ovr
         .and_then(|o| o.get("surface_kind"))
         .is_some_and(|v| !v.is_null());
+    let surface_coloring = ovr
+        .and_then(|o| o.get("surface_coloring"))
+        .and_then(Value::as_str)
+        .map(str::to_owned);
     let has_hbond_ovr = ovr
         .and_then(|o| o.get("show_hbonds"))
         .is_some_and(|v| !v.is_null());
@@ -634,10 +664,18 @@ fn entity_row(
                 {entity_appearance_select(
                     id, "Surface", "surface_kind", &surface_kind,
                     has_surface_ovr,
-                    &[("none", "None"), ("gaussian", "Gaussian"), ("ses", "SES")],
+                    &[("none", "None"), ("gaussian", "Gaussian"), ("ses", "SES"),
+                  ("sas", "SAS"), ("vdw", "vdW"), ("dots", "Dots")],
                 )}
                 if surface_kind != "none" {
                     {entity_opacity_slider(id, entity)}
+                    {entity_appearance_select(
+                        id, "Surface Color", "surface_coloring",
+                        surface_coloring.as_deref().unwrap_or("entity"),
+                        surface_coloring.is_some(),
+                        &[("entity", "Entity"), ("residue", "Residue"),
+                          ("electrostatic", "Electrostatic")],
+                    )}
                 }
                 // Cartoon sub-options (helix/sheet/sidechains)
                 if is_cartoon_capable && is_protein && drawing_mode == "cartoon" {
@@ -883,6 +921,39 @@ fn density_row(map_val: &Value) -> Element {
         .get("opacity")
         .and_then(Value::as_f64)
         .unwrap_or(0.35);
+    let style = map_val
+        .get("style")
+        .and_then(Value::as_str)
+        .unwrap_or("surface");
+    let difference = map_val
+        .get("difference")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let potential = map_val
+        .get("potential")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let zone = map_val.get("zone").and_then(Value::as_str).unwrap_or("off");
+    let zone_radius = map_val
+        .get("zone_radius")
+        .and_then(Value::as_f64)
+        .unwrap_or(10.0);
+    let zone_anchor = map_val
+        .get("zone_anchor")
+        .and_then(Value::as_str)
+        .unwrap_or("camera");
+    let volume_palette = map_val
+        .get("volume_palette")
+        .and_then(Value::as_str)
+        .unwrap_or("viridis");
+    let volume_low = map_val
+        .get("volume_low")
+        .and_then(Value::as_f64)
+        .unwrap_or(1.0);
+    let volume_high = map_val
+        .get("volume_high")
+        .and_then(Value::as_f64)
+        .unwrap_or(8.0);
 
     let opacity_class = if visible { "" } else { " entity-hidden" };
     // Slider range: 0 to dmax (no negatives)
@@ -951,6 +1022,96 @@ fn density_row(map_val: &Value) -> Element {
             {density_slider_row(id, "G", "color_g", cg, 0.0, 1.0, 0.01)}
             {density_slider_row(id, "B", "color_b", cb, 0.0, 1.0, 0.01)}
             {density_slider_row(id, "A", "opacity", opacity, 0.0, 1.0, 0.01)}
+            {density_select_row(id, "Style", "style", style, &[
+                ("surface", "Surface"),
+                ("mesh", "Mesh"),
+                ("volume", "Volume"),
+            ])}
+            if style == "volume" {
+                {density_select_row(id, "Palette", "volume_palette", volume_palette, &[
+                    ("viridis", "Viridis"),
+                    ("plasma", "Plasma"),
+                    ("blues", "Blues"),
+                    ("greens", "Greens"),
+                    ("cool_warm", "Cool-warm"),
+                ])}
+                {density_slider_row(id, "Low σ", "volume_low", volume_low, -2.0, 10.0, 0.1)}
+                {density_slider_row(id, "High σ", "volume_high", volume_high, 0.0, 20.0, 0.1)}
+            }
+            div { class: "entity-option-row",
+                label { class: "entity-option-label", "Difference" }
+                input {
+                    r#type: "checkbox",
+                    checked: difference,
+                    onchange: move |evt: Event<FormData>| {
+                        bridge::send_set_density_option(
+                            id,
+                            "difference",
+                            &Value::Bool(evt.value() == "true"),
+                        );
+                    },
+                }
+            }
+            div { class: "entity-option-row",
+                label { class: "entity-option-label", "Surface Potential" }
+                input {
+                    r#type: "checkbox",
+                    checked: potential,
+                    onchange: move |evt: Event<FormData>| {
+                        bridge::send_set_density_option(
+                            id,
+                            "potential",
+                            &Value::Bool(evt.value() == "true"),
+                        );
+                    },
+                }
+            }
+            {density_select_row(id, "Zone", "zone", zone, &[
+                ("off", "Whole map"),
+                ("box", "Box"),
+                ("sphere", "Sphere"),
+            ])}
+            if zone != "off" {
+                {density_slider_row(id, "Radius", "zone_radius", zone_radius, 2.0, 40.0, 0.5)}
+                {density_select_row(id, "Around", "zone_anchor", zone_anchor, &[
+                    ("camera", "View center"),
+                    ("selection", "Selection"),
+                ])}
+            }
+        }
+    }
+}
+
+/// Density control row: label + select over `(value, label)` choices.
+fn density_select_row(
+    id: u64,
+    label: &str,
+    field: &str,
+    current: &str,
+    choices: &[(&str, &str)],
+) -> Element {
+    let field = field.to_owned();
+    rsx! {
+        div { class: "entity-option-row",
+            label { class: "entity-option-label", "{label}" }
+            select {
+                class: "entity-option-select",
+                value: "{current}",
+                onchange: move |evt: Event<FormData>| {
+                    bridge::send_set_density_option(
+                        id,
+                        &field,
+                        &Value::String(evt.value()),
+                    );
+                },
+                for (value, text) in choices.iter().copied() {
+                    option {
+                        value: "{value}",
+                        selected: value == current,
+                        "{text}"
+                    }
+                }
+            }
         }
     }
 }
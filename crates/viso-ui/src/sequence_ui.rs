@@ -0,0 +1,256 @@
+//! Sequence panel: one-letter sequence track per protein chain, linked
+//! to the 3D selection.
+
+use std::collections::HashSet;
+
+use dioxus::prelude::*;
+use serde_json::Value;
+
+use crate::bridge;
+
+/// Residues per numbering tick.
+const TICK_INTERVAL: usize = 10;
+
+/// Sequence panel.
+///
+/// Clicking a residue replaces the selection (shift-click toggles it);
+/// dragging across residues extends the selection. The residue hovered
+/// in the 3D view is outlined and scrolled into view.
+#[component]
+pub fn SequencePanel(
+    sequences: Signal<Option<Value>>,
+    selected: Signal<HashSet<i64>>,
+    hovered: Signal<Option<i64>>,
+) -> Element {
+    // Flat residue indices visited by the current drag, so re-entering
+    // a residue doesn't toggle it back off.
+    let mut drag: Signal<Option<HashSet<i64>>> = use_signal(|| None);
+
+    use_effect(move || {
+        if let Some(index) = *hovered.read() {
+            let js = format!(
+                "document.getElementById('seq-res-{index}')?.\
+                 scrollIntoView({{block: 'nearest', inline: 'nearest'}})"
+            );
+            let _ = js_sys::eval(&js);
+        }
+    });
+
+    let data = sequences.read();
+    let chains = data.as_ref().and_then(Value::as_array);
+
+    rsx! {
+        div {
+            class: "sequence-panel",
+            onpointerup: move |_| drag.set(None),
+            onpointerleave: move |_| drag.set(None),
+            div { class: "sequence-toolbar",
+                button {
+                    class: "fetch-btn",
+                    title: "Recompute secondary structure from the displayed coordinates",
+                    onclick: move |_| {
+                        bridge::send_compute_secondary_structure();
+                    },
+                    "Compute DSSP"
+                }
+                button {
+                    class: "fetch-btn",
+                    title: "Compute solvent-accessible surface area per residue",
+                    onclick: move |_| {
+                        bridge::send_compute_sasa();
+                    },
+                    "Compute SASA"
+                }
+                button {
+                    class: "fetch-btn",
+                    title: "Show the molecular surface of the selected residues only",
+                    onclick: move |_| {
+                        bridge::send_surface_selection("ses");
+                    },
+                    "Surface Selection"
+                }
+            }
+            if let Some(items) = chains {
+                if items.is_empty() {
+                    div { class: "scene-empty", "No protein chains in scene" }
+                } else {
+                    for chain in items.iter() {
+                        {chain_track(chain, selected, hovered, drag)}
+                    }
+                }
+            } else {
+                div { class: "scene-empty", "Waiting for scene data..." }
+            }
+        }
+    }
+}
+
+/// One chain: header plus a wrapped track of residue cells.
+fn chain_track(
+    chain: &Value,
+    selected: Signal<HashSet<i64>>,
+    hovered: Signal<Option<i64>>,
+    drag: Signal<Option<HashSet<i64>>>,
+) -> Element {
+    let entity_id = chain.get("entity_id").and_then(Value::as_u64).unwrap_or(0);
+    let chain_id = chain.get("chain_id").and_then(Value::as_str).unwrap_or("?");
+    let label = chain.get("label").and_then(Value::as_str).unwrap_or("");
+    let sequence = chain.get("sequence").and_then(Value::as_str).unwrap_or("");
+    let ss: Vec<char> = chain
+        .get("ss")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .chars()
+        .collect();
+    let dssp: Vec<char> = chain
+        .get("dssp")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .chars()
+        .collect();
+    let numbers: Vec<&str> = chain
+        .get("numbers")
+        .and_then(Value::as_array)
+        .map(|a| a.iter().map(|n| n.as_str().unwrap_or("")).collect())
+        .unwrap_or_default();
+    let colors: Vec<Option<String>> = chain
+        .get("colors")
+        .and_then(Value::as_array)
+        .map(|a| a.iter().map(css_color).collect())
+        .unwrap_or_default();
+    let first_index = chain.get("first_index").and_then(Value::as_i64);
+
+    let residues: Vec<ResidueCell> = sequence
+        .chars()
+        .enumerate()
+        .map(|(i, code)| ResidueCell {
+            code,
+            number: numbers.get(i).copied().unwrap_or("").to_owned(),
+            ss: ss.get(i).copied().unwrap_or('C'),
+            dssp: dssp.get(i).copied(),
+            color: colors.get(i).cloned().flatten(),
+            index: first_index.map(|f| f + i as i64),
+            tick: i % TICK_INTERVAL == 0,
+        })
+        .collect();
+    let hidden = first_index.is_none();
+    let class = if hidden {
+        "sequence-chain chain-hidden"
+    } else {
+        "sequence-chain"
+    };
+
+    rsx! {
+        div { class: "{class}", key: "{entity_id}",
+            div { class: "sequence-chain-header",
+                span { class: "sequence-chain-id", "{chain_id}" }
+                span { class: "sequence-chain-label", "{label}" }
+                span { class: "entity-subtitle", "{residues.len()} residues" }
+            }
+            div { class: "sequence-track",
+                for cell in residues.into_iter() {
+                    {residue_cell(&cell, selected, hovered, drag)}
+                }
+            }
+        }
+    }
+}
+
+/// Display data for one residue.
+struct ResidueCell {
+    code: char,
+    number: String,
+    ss: char,
+    /// Eight-state DSSP letter, once computed.
+    dssp: Option<char>,
+    color: Option<String>,
+    /// Flat residue index; `None` when the chain's entity is hidden.
+    index: Option<i64>,
+    /// Show the author number above this residue.
+    tick: bool,
+}
+
+fn residue_cell(
+    cell: &ResidueCell,
+    selected: Signal<HashSet<i64>>,
+    hovered: Signal<Option<i64>>,
+    mut drag: Signal<Option<HashSet<i64>>>,
+) -> Element {
+    let is_selected = cell.index.is_some_and(|i| selected.read().contains(&i));
+    let is_hovered = cell.index.is_some() && *hovered.read() == cell.index;
+    let mut class = String::from("sequence-residue");
+    if is_selected {
+        class.push_str(" selected");
+    }
+    if is_hovered {
+        class.push_str(" hovered");
+    }
+    let ss_class = match cell.ss {
+        'H' => "ss-glyph helix",
+        'E' => "ss-glyph sheet",
+        _ => "ss-glyph coil",
+    };
+    let style = cell
+        .color
+        .as_deref()
+        .map(|c| format!("background: {c};"))
+        .unwrap_or_default();
+    let id = cell
+        .index
+        .map(|i| format!("seq-res-{i}"))
+        .unwrap_or_default();
+    let code = cell.code;
+    let title = cell.dssp.map_or_else(
+        || format!("{code}{}", cell.number),
+        |dssp| format!("{code}{} ({dssp})", cell.number),
+    );
+    let tick = if cell.tick {
+        cell.number.clone()
+    } else {
+        String::new()
+    };
+    let index = cell.index;
+
+    rsx! {
+        div {
+            class: "{class}",
+            id: "{id}",
+            title: "{title}",
+            onpointerdown: move |evt: PointerEvent| {
+                let Some(i) = index else { return };
+                evt.prevent_default();
+                let extend = evt.modifiers().shift();
+                bridge::send_select_residue(i, extend);
+                drag.set(Some(HashSet::from([i])));
+            },
+            onpointerenter: move |_| {
+                let Some(i) = index else { return };
+                let mut state = drag.write();
+                if let Some(visited) = state.as_mut() {
+                    if visited.insert(i) && !selected.read().contains(&i) {
+                        bridge::send_select_residue(i, true);
+                    }
+                }
+            },
+            span { class: "sequence-tick", "{tick}" }
+            span { class: "{ss_class}" }
+            span { class: "sequence-code", style: "{style}", "{code}" }
+        }
+    }
+}
+
+/// `[r, g, b]` in 0..1 → CSS `rgb()`.
+fn css_color(value: &Value) -> Option<String> {
+    let rgb = value.as_array()?;
+    let channel = |i: usize| {
+        rgb.get(i)
+            .and_then(Value::as_f64)
+            .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+    };
+    Some(format!(
+        "rgb({}, {}, {})",
+        channel(0)?,
+        channel(1)?,
+        channel(2)?
+    ))
+}
@@ -20,11 +20,16 @@ struct EntityAnimationState {
     runner: AnimationRunner,
     start: Vec<Vec3>,
     target: Vec<Vec3>,
+    /// Whether completion is queued for [`StructureAnimator::take_finished`].
+    reports_finish: bool,
 }
 
 /// Drives per-entity position interpolation.
 pub(crate) struct StructureAnimator {
     runners: FxHashMap<EntityId, EntityAnimationState>,
+    /// Entities whose runner completed since the last
+    /// [`Self::take_finished`].
+    finished: Vec<EntityId>,
 }
 
 impl StructureAnimator {
@@ -32,6 +37,7 @@ impl StructureAnimator {
     pub(crate) fn new() -> Self {
         Self {
             runners: FxHashMap::default(),
+            finished: Vec::new(),
         }
     }
 
@@ -82,10 +88,17 @@ impl StructureAnimator {
                 runner,
                 start,
                 target,
+                reports_finish: transition.reports_finish,
             },
         );
     }
 
+    /// Stop any animation running on `entity_id`, leaving its
+    /// positions where they are.
+    pub(crate) fn cancel_entity(&mut self, entity_id: EntityId) {
+        let _ = self.runners.remove(&entity_id);
+    }
+
     /// Advance animations for the current frame and write interpolated
     /// positions into `positions`. Returns `true` if any entity's
     /// position buffer was written.
@@ -103,7 +116,7 @@ impl StructureAnimator {
             let t = state.runner.progress(now);
             if t >= 1.0 {
                 positions.set(eid, state.target.clone());
-                completed.push(eid);
+                completed.push((eid, state.reports_finish));
                 any_written = true;
                 continue;
             }
@@ -117,12 +130,21 @@ impl StructureAnimator {
             positions.set(eid, lerped);
             any_written = true;
         }
-        for eid in completed {
+        for (eid, reports_finish) in completed {
             let _ = self.runners.remove(&eid);
+            if reports_finish {
+                self.finished.push(eid);
+            }
         }
         any_written
     }
 
+    /// Drain the entities whose animation completed since the last
+    /// call.
+    pub(crate) fn take_finished(&mut self) -> Vec<EntityId> {
+        std::mem::take(&mut self.finished)
+    }
+
     /// Whether sidechains should be drawn this frame. Returns `false`
     /// if any currently-running entity is in a phase that hides
     /// sidechains.
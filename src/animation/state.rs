@@ -2,19 +2,13 @@
 //! and pending per-entity transitions.
 
 use std::collections::HashMap;
-use std::path::Path;
 
-use molex::adapters::dcd::dcd_file_to_frames;
 use web_time::Instant;
 
 use super::StructureAnimator;
 use crate::animation::transition::Transition;
-use crate::engine::annotations::EntityAnnotations;
 use crate::engine::positions::EntityPositions;
-use crate::engine::scene::Scene;
-use crate::engine::trajectory::{
-    pick_trajectory_target, TrajectoryFrame, TrajectoryPlayer,
-};
+use crate::engine::trajectory::{TrajectoryFrame, TrajectoryPlayer};
 
 /// Grouped animation fields.
 pub(crate) struct AnimationState {
@@ -87,7 +81,45 @@ impl AnimationState {
         player.tick(now)
     }
 
-    /// Create a trajectory player from parsed DCD frames.
+    /// Hand a trajectory frame that carries a transition to the
+    /// animator, moving each entity from its displayed positions to the
+    /// frame's. Returns `false` when the frame has no transition; it is
+    /// then the caller's to apply, and any step still running on its
+    /// entities is stopped so it cannot overwrite the frame.
+    pub(crate) fn animate_trajectory_frame(
+        &mut self,
+        frame: &TrajectoryFrame,
+        positions: &EntityPositions,
+    ) -> bool {
+        let Some(transition) = &frame.transition else {
+            for update in &frame.entities {
+                self.animator.cancel_entity(update.entity);
+            }
+            return false;
+        };
+        for update in &frame.entities {
+            let Some(start) = positions.get(update.entity) else {
+                continue;
+            };
+            let mut target = start.to_vec();
+            for (&idx, &pos) in
+                update.atom_indices.iter().zip(&update.positions)
+            {
+                if let Some(slot) = target.get_mut(idx as usize) {
+                    *slot = pos;
+                }
+            }
+            self.animator.animate_entity(
+                update.entity,
+                start.to_vec(),
+                target,
+                transition,
+            );
+        }
+        true
+    }
+
+    /// Install a trajectory player built from decoded frames.
     pub(crate) fn load_trajectory(
         &mut self,
         player: TrajectoryPlayer,
@@ -101,63 +133,4 @@ impl AnimationState {
              ~{duration_secs:.1}s at 30fps",
         );
     }
-
-    /// Open a DCD trajectory file, bind it to the first visible protein
-    /// entity in `scene`, and begin playback. Logs and returns silently
-    /// on any failure (file open, no visible protein, atom-count
-    /// mismatch).
-    pub(crate) fn load_trajectory_from_path(
-        &mut self,
-        path: &Path,
-        scene: &Scene,
-        annotations: &EntityAnnotations,
-    ) {
-        let (header, frames) = match dcd_file_to_frames(path) {
-            Ok(r) => r,
-            Err(e) => {
-                log::error!("Failed to load DCD trajectory: {e}");
-                return;
-            }
-        };
-
-        let Some((entity_id, atom_count, atom_index_map)) =
-            pick_trajectory_target(scene, annotations)
-        else {
-            log::error!("No protein structure loaded — cannot play trajectory");
-            return;
-        };
-
-        if (header.num_atoms as usize) < atom_count {
-            log::error!(
-                "DCD atom count ({}) is less than protein atom count ({})",
-                header.num_atoms,
-                atom_count,
-            );
-            return;
-        }
-
-        let num_atoms = header.num_atoms as usize;
-        let num_frames = frames.len();
-        let player =
-            TrajectoryPlayer::new(frames, num_atoms, entity_id, atom_index_map);
-        self.load_trajectory(player, num_frames, num_atoms);
-    }
-
-    /// Toggle trajectory playback (play/pause). No-op if no trajectory
-    /// loaded.
-    pub(crate) fn toggle_trajectory(&mut self) {
-        if let Some(ref mut player) = self.trajectory_player {
-            player.toggle_playback();
-            let state = if player.is_playing() {
-                "playing"
-            } else {
-                "paused"
-            };
-            log::info!(
-                "Trajectory {state} (frame {}/{})",
-                player.current_frame(),
-                player.total_frames()
-            );
-        }
-    }
 }
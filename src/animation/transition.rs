@@ -42,6 +42,9 @@ pub struct Transition {
     /// Whether to suppress initial sidechain GPU uploads.
     /// Used by multi-phase behaviors that hide sidechains in phase 1.
     pub suppress_initial_sidechains: bool,
+    /// Whether completion is reported to the host as a
+    /// `TransitionFinished` event.
+    pub(crate) reports_finish: bool,
 }
 
 impl Transition {
@@ -66,6 +69,7 @@ impl Transition {
             name: "snap",
             allows_size_change: true,
             suppress_initial_sidechains: false,
+            reports_finish: true,
         }
     }
 
@@ -84,6 +88,7 @@ impl Transition {
             name: "smooth",
             allows_size_change: false,
             suppress_initial_sidechains: false,
+            reports_finish: true,
         }
     }
 
@@ -118,6 +123,7 @@ impl Transition {
             name: "collapse-expand",
             allows_size_change: true,
             suppress_initial_sidechains: true,
+            reports_finish: true,
         }
     }
 
@@ -146,6 +152,7 @@ impl Transition {
             name: "backbone-then-expand",
             allows_size_change: false,
             suppress_initial_sidechains: true,
+            reports_finish: true,
         }
     }
 
@@ -167,6 +174,26 @@ impl Transition {
             name: "cascade",
             allows_size_change: false,
             suppress_initial_sidechains: false,
+            reports_finish: true,
+        }
+    }
+
+    /// Linear step from one trajectory frame to the next over the
+    /// playback frame interval. Runs back to back during playback, so
+    /// completion is not reported.
+    pub(crate) fn trajectory_frame(interval: Duration) -> Self {
+        Self {
+            phases: vec![AnimationPhase {
+                easing: EasingFunction::Linear,
+                duration: interval,
+                lerp_start: 0.0,
+                lerp_end: 1.0,
+                include_sidechains: true,
+            }],
+            name: "trajectory-frame",
+            allows_size_change: false,
+            suppress_initial_sidechains: false,
+            reports_finish: false,
         }
     }
 
@@ -199,6 +226,7 @@ impl Transition {
             name: "linear",
             allows_size_change: false,
             suppress_initial_sidechains: false,
+            reports_finish: true,
         }
     }
 }
@@ -219,6 +247,7 @@ impl std::fmt::Debug for Transition {
                 "suppress_initial_sidechains",
                 &self.suppress_initial_sidechains,
             )
+            .field("reports_finish", &self.reports_finish)
             .finish()
     }
 }
@@ -260,6 +260,14 @@ impl PanelController {
         }
     }
 
+    /// Drain engine change events and push them to the webview.
+    pub(crate) fn push_events(&self, engine: &mut VisoEngine) {
+        let host = PanelHost {
+            webview: self.webview.as_ref(),
+        };
+        dispatch::push_events(engine, &host);
+    }
+
     /// Push the current entity list to the webview.
     pub(crate) fn push_scene_entities(&self, engine: &VisoEngine) {
         let host = PanelHost {
@@ -275,6 +283,14 @@ impl PanelController {
         };
         dispatch::push_density_maps(engine, &host);
     }
+
+    /// Push the loaded structure's assemblies and symmetry view.
+    fn push_symmetry(&self, engine: &VisoEngine) {
+        let host = PanelHost {
+            webview: self.webview.as_ref(),
+        };
+        dispatch::push_symmetry(engine, &host);
+    }
 }
 
 // ── Load handlers ────────────────────────────────────────────────────────
@@ -292,6 +308,7 @@ impl PanelController {
             Ok(()) => {
                 self.push_scene_entities(engine);
                 self.push_density_maps(engine);
+                self.push_symmetry(engine);
                 if let Some(ref wv) = self.webview {
                     let name =
                         std::path::Path::new(path).file_name().map_or_else(
@@ -323,9 +340,13 @@ impl PanelController {
         let dialog = rfd::FileDialog::new()
             .add_filter("Structure", &["cif", "pdb", "ent", "bcif"])
             .add_filter("Density Map", &["mrc", "map", "ccp4"])
+            .add_filter("Trajectory", &["dcd", "xtc", "trr", "xyz"])
             .add_filter(
                 "All Supported",
-                &["cif", "pdb", "ent", "bcif", "mrc", "map", "ccp4"],
+                &[
+                    "cif", "pdb", "ent", "bcif", "mrc", "map", "ccp4", "dcd",
+                    "xtc", "trr", "xyz",
+                ],
             )
             .set_title("Open File");
 
@@ -401,25 +422,30 @@ impl PanelController {
 
 // ── Helpers (free functions) ─────────────────────────────────────────────
 
-/// Parse a file (structure or density) and load it into the engine.
+/// Parse a file (structure, density or trajectory) and load it into the
+/// engine.
 fn parse_and_load(
     app: &mut crate::app::VisoApp,
     engine: &mut VisoEngine,
     path: &str,
 ) -> Result<(), String> {
     use crate::bridge;
+    use crate::engine::density_formats;
 
     let ext = std::path::Path::new(path)
         .extension()
         .map_or("", |e| e.to_str().unwrap_or(""));
 
-    if bridge::is_density_extension(ext) {
-        let map = molex::adapters::mrc::mrc_file_to_density(
-            std::path::Path::new(path),
-        )
-        .map_err(|e| format!("Density parse error: {e}"))?;
+    if bridge::is_density_extension(&density_formats::file_extension(
+        std::path::Path::new(path),
+    )) {
+        let map = density_formats::read_density(std::path::Path::new(path))
+            .map_err(|e| format!("Density parse error: {e}"))?;
         let _ = engine.density_mut().load(map);
         Ok(())
+    } else if bridge::is_trajectory_extension(ext) {
+        engine.load_trajectory(std::path::Path::new(path));
+        Ok(())
     } else {
         use molex::adapters::pdb::structure_file_to_entities;
 
@@ -430,6 +456,9 @@ fn parse_and_load(
         }
 
         let _ = app.replace_scene(engine, entities);
+        engine.set_structure_symmetry(crate::StructureSymmetry::from_file(
+            std::path::Path::new(path),
+        ));
         Ok(())
     }
 }
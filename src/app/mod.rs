@@ -25,11 +25,14 @@ pub mod gui;
 #[cfg(all(feature = "web", target_arch = "wasm32"))]
 pub mod web;
 
+mod superpose;
+
 use std::collections::HashMap;
 use std::sync::Arc;
 
 use molex::ops::edit::AssemblyEdit;
 use molex::{Assembly, MoleculeEntity, MoleculeType, SSType};
+pub use superpose::{ResidueMatching, SuperposeResult, SuperposeSelection};
 
 use crate::animation::transition::Transition;
 use crate::error::VisoError;
@@ -337,6 +340,70 @@ impl VisoApp {
         engine.sync_now();
     }
 
+    /// Superpose entity `mobile` onto entity `reference`: fit the
+    /// paired CA atoms chosen by `selection` (least-squares rotation
+    /// plus translation), move every mobile atom by that rigid
+    /// transform, and publish the result with a smooth transition.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VisoError::Superpose`] if either entity is missing or
+    /// not a protein, or fewer than three CA pairs are found.
+    pub fn superpose(
+        &mut self,
+        engine: &mut VisoEngine,
+        mobile: u32,
+        reference: u32,
+        selection: &SuperposeSelection,
+    ) -> Result<SuperposeResult, VisoError> {
+        let find = |id: u32| {
+            self.assembly
+                .entities()
+                .iter()
+                .find(|e| e.id().raw() == id)
+                .map(|e| MoleculeEntity::clone(e))
+                .ok_or_else(|| {
+                    VisoError::Superpose(format!("entity {id} not found"))
+                })
+        };
+        let mut mobile_entity = find(mobile)?;
+        let reference_entity = find(reference)?;
+
+        let mobile_cas = superpose::ca_residues(&mobile_entity);
+        let reference_cas = superpose::ca_residues(&reference_entity);
+        let pairs = superpose::pair_residues(
+            &mobile_cas,
+            &reference_cas,
+            selection.matching,
+            selection.residues.as_deref(),
+        );
+        if pairs.len() < superpose::MIN_PAIRS {
+            return Err(VisoError::Superpose(format!(
+                "{} CA pairs between entities {mobile} and {reference}, need \
+                 at least {}",
+                pairs.len(),
+                superpose::MIN_PAIRS,
+            )));
+        }
+        let (from, onto): (Vec<_>, Vec<_>) = pairs
+            .iter()
+            .map(|&(m, r)| (mobile_cas[m].ca, reference_cas[r].ca))
+            .unzip();
+        let fit =
+            crate::util::superpose::fit(&from, &onto).ok_or_else(|| {
+                VisoError::Superpose("degenerate CA pairing".to_owned())
+            })?;
+
+        for atom in mobile_entity.atom_set_mut() {
+            atom.position = fit.apply(atom.position);
+        }
+        self.update_entity(engine, mobile_entity, Transition::smooth())?;
+        Ok(SuperposeResult {
+            rmsd: fit.rmsd,
+            aligned_pairs: pairs.len(),
+        })
+    }
+
     /// Set visibility for a specific entity. For ambient types
     /// (water, ion, solvent), also syncs the corresponding display
     /// option so the renderer safety net stays consistent.
@@ -0,0 +1,234 @@
+//! Residue pairing for [`VisoApp::superpose`](super::VisoApp::superpose).
+//!
+//! Superposition fits the mobile entity's CA atoms onto the reference
+//! entity's. The CA pairs come either from equal author residue numbers
+//! (same construct, different conformation) or from a global sequence
+//! alignment of the two residue-name strings (homologs, constructs with
+//! renumbered or trimmed termini).
+
+use glam::Vec3;
+use molex::MoleculeEntity;
+use rustc_hash::FxHashMap;
+
+/// Needleman-Wunsch scores for the residue-name alignment.
+const MATCH_SCORE: i32 = 2;
+const MISMATCH_SCORE: i32 = -1;
+const GAP_SCORE: i32 = -2;
+
+/// Fewest CA pairs that determine a rotation.
+pub(super) const MIN_PAIRS: usize = 3;
+
+/// How mobile residues are paired with reference residues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResidueMatching {
+    /// Pair residues with equal author residue number and insertion
+    /// code.
+    #[default]
+    ResidueNumber,
+    /// Pair residues aligned by a global sequence alignment.
+    SequenceAlignment,
+}
+
+/// Which residues drive a superposition.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SuperposeSelection {
+    /// How residues of the two entities are paired.
+    pub matching: ResidueMatching,
+    /// Restrict the fit to these mobile-entity author residue numbers.
+    /// `None` fits over every paired residue.
+    pub residues: Option<Vec<i32>>,
+}
+
+/// Outcome of a superposition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SuperposeResult {
+    /// CA RMSD over the aligned pairs after the fit, in Ångström.
+    pub rmsd: f32,
+    /// Number of CA pairs the fit used.
+    pub aligned_pairs: usize,
+}
+
+/// One protein residue as seen by the pairing step.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct CaResidue {
+    /// Author residue number (label number when absent).
+    pub(super) number: i32,
+    /// Insertion code.
+    pub(super) ins_code: Option<u8>,
+    /// 3-letter residue name.
+    pub(super) name: [u8; 3],
+    /// CA position.
+    pub(super) ca: Vec3,
+}
+
+/// Residues of a protein entity that carry a CA atom. Empty for
+/// non-protein entities.
+pub(super) fn ca_residues(entity: &MoleculeEntity) -> Vec<CaResidue> {
+    let Some(protein) = entity.as_protein() else {
+        return Vec::new();
+    };
+    let atoms = entity.atom_set();
+    protein
+        .residues
+        .iter()
+        .filter_map(|residue| {
+            let ca = atoms
+                .get(residue.atom_range.clone())?
+                .iter()
+                .find(|a| a.name == *b"CA  ")?;
+            Some(CaResidue {
+                number: residue.auth_seq_id.unwrap_or(residue.label_seq_id),
+                ins_code: residue.ins_code,
+                name: residue.name,
+                ca: ca.position,
+            })
+        })
+        .collect()
+}
+
+/// Index pairs `(mobile, reference)` under `matching`, restricted to
+/// the mobile residue numbers in `only` when given.
+pub(super) fn pair_residues(
+    mobile: &[CaResidue],
+    reference: &[CaResidue],
+    matching: ResidueMatching,
+    only: Option<&[i32]>,
+) -> Vec<(usize, usize)> {
+    let pairs = match matching {
+        ResidueMatching::ResidueNumber => pair_by_number(mobile, reference),
+        ResidueMatching::SequenceAlignment => {
+            pair_by_alignment(mobile, reference)
+        }
+    };
+    match only {
+        Some(numbers) => pairs
+            .into_iter()
+            .filter(|&(m, _)| numbers.contains(&mobile[m].number))
+            .collect(),
+        None => pairs,
+    }
+}
+
+fn pair_by_number(
+    mobile: &[CaResidue],
+    reference: &[CaResidue],
+) -> Vec<(usize, usize)> {
+    let by_number: FxHashMap<(i32, Option<u8>), usize> = reference
+        .iter()
+        .enumerate()
+        .map(|(r, rr)| ((rr.number, rr.ins_code), r))
+        .collect();
+    mobile
+        .iter()
+        .enumerate()
+        .filter_map(|(m, mr)| {
+            by_number.get(&(mr.number, mr.ins_code)).map(|&r| (m, r))
+        })
+        .collect()
+}
+
+/// Global (Needleman-Wunsch) alignment of the residue-name strings;
+/// every aligned column (match or mismatch) becomes a pair.
+fn pair_by_alignment(
+    mobile: &[CaResidue],
+    reference: &[CaResidue],
+) -> Vec<(usize, usize)> {
+    let (n, m) = (mobile.len(), reference.len());
+    let width = m + 1;
+    let mut score = vec![0i32; (n + 1) * width];
+    for i in 1..=n {
+        score[i * width] = i as i32 * GAP_SCORE;
+    }
+    for (j, cell) in score.iter_mut().enumerate().take(width) {
+        *cell = j as i32 * GAP_SCORE;
+    }
+    let substitution = |i: usize, j: usize| {
+        if mobile[i - 1].name == reference[j - 1].name {
+            MATCH_SCORE
+        } else {
+            MISMATCH_SCORE
+        }
+    };
+    for i in 1..=n {
+        for j in 1..=m {
+            let diagonal = score[(i - 1) * width + j - 1] + substitution(i, j);
+            let up = score[(i - 1) * width + j] + GAP_SCORE;
+            let left = score[i * width + j - 1] + GAP_SCORE;
+            score[i * width + j] = diagonal.max(up).max(left);
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (n, m);
+    while i > 0 && j > 0 {
+        let here = score[i * width + j];
+        if here == score[(i - 1) * width + j - 1] + substitution(i, j) {
+            pairs.push((i - 1, j - 1));
+            i -= 1;
+            j -= 1;
+        } else if here == score[(i - 1) * width + j] + GAP_SCORE {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+    pairs.reverse();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(names: &[&[u8; 3]], first: i32) -> Vec<CaResidue> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, &&name)| CaResidue {
+                number: first + i as i32,
+                ins_code: None,
+                name,
+                ca: Vec3::new(i as f32 * 3.8, 0.0, 0.0),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn residue_number_pairs_overlap_only() {
+        let mobile = chain(&[b"ALA", b"GLY", b"SER", b"LYS"], 10);
+        let reference = chain(&[b"GLY", b"SER"], 11);
+        let pairs = pair_residues(
+            &mobile,
+            &reference,
+            ResidueMatching::ResidueNumber,
+            None,
+        );
+        assert_eq!(pairs, vec![(1, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn alignment_skips_insertions() {
+        let mobile = chain(&[b"ALA", b"GLY", b"TRP", b"SER", b"LYS"], 1);
+        let reference = chain(&[b"ALA", b"GLY", b"SER", b"LYS"], 101);
+        let pairs = pair_residues(
+            &mobile,
+            &reference,
+            ResidueMatching::SequenceAlignment,
+            None,
+        );
+        assert_eq!(pairs, vec![(0, 0), (1, 1), (3, 2), (4, 3)]);
+    }
+
+    #[test]
+    fn selection_restricts_mobile_residues() {
+        let mobile = chain(&[b"ALA", b"GLY", b"SER"], 1);
+        let reference = chain(&[b"ALA", b"GLY", b"SER"], 1);
+        let pairs = pair_residues(
+            &mobile,
+            &reference,
+            ResidueMatching::ResidueNumber,
+            Some(&[1, 3]),
+        );
+        assert_eq!(pairs, vec![(0, 0), (2, 2)]);
+    }
+}
@@ -214,6 +214,9 @@ fn create_app_and_engine(
     }
     let mut engine = VisoEngine::new(context, VisoOptions::default())?;
     app.publish(&mut engine);
+    engine.set_structure_symmetry(path.and_then(|p| {
+        crate::StructureSymmetry::from_file(std::path::Path::new(p))
+    }));
     Ok((app, engine))
 }
 
@@ -274,7 +277,10 @@ impl ViewerShell {
         }
 
         #[cfg(feature = "gui")]
-        self.panel.push_stats_if_due(now, engine);
+        {
+            self.panel.push_stats_if_due(now, engine);
+            self.panel.push_events(engine);
+        }
 
         let Some(w) = &self.window else { return };
 
@@ -252,8 +252,12 @@ fn install_ipc_bridge(
                 let mut a = app_for_load.borrow_mut();
                 let mut e = eng.borrow_mut();
                 let _ids = a.replace_scene(&mut e, entities);
+                e.set_structure_symmetry(crate::StructureSymmetry::from_bytes(
+                    &bytes, &hint,
+                ));
                 push_load_status("loaded", "Structure loaded");
                 push_scene_entities(&e);
+                dispatch::push_symmetry(&e, &WebHost);
             }
             Ok(bridge::ParsedFile::Density(map)) => {
                 let mut e = eng.borrow_mut();
@@ -534,8 +538,12 @@ async fn fetch_and_load(
             let mut a = app.borrow_mut();
             let mut eng = engine.borrow_mut();
             let _ids = a.replace_scene(&mut eng, entities);
+            eng.set_structure_symmetry(crate::StructureSymmetry::from_bytes(
+                &bytes, "cif",
+            ));
             push_load_status("loaded", &format!("Loaded {id}"));
             push_scene_entities(&eng);
+            dispatch::push_symmetry(&eng, &WebHost);
         }
         Err(msg) => {
             push_load_status("error", &msg);
@@ -692,6 +700,7 @@ fn request_animation_frame_loop(
                 Ok(()) => {}
                 Err(e) => log::error!("render error: {e:?}"),
             }
+            dispatch::push_events(&mut eng, &WebHost);
 
             let mut count = frame_counter.borrow_mut();
             *count += 1;
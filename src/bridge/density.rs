@@ -0,0 +1,63 @@
+//! Density map summaries for the viso-ui density panel.
+
+use crate::engine::density_store::{
+    DensityStyle, ZoneAnchor, ZoneShape, DEFAULT_ZONE_RADIUS,
+};
+use crate::VisoEngine;
+
+/// Build a JSON-serializable summary of all density maps for the viso-ui
+/// panel.
+pub(crate) fn density_summaries(engine: &VisoEngine) -> Vec<serde_json::Value> {
+    engine
+        .density
+        .all_entries()
+        .map(|(id, entry)| {
+            serde_json::json!({
+                "id": id,
+                "visible": entry.visible,
+                "threshold": entry.threshold,
+                "dmin": entry.map.dmin,
+                "dmax": entry.map.dmax,
+                "color": [entry.color[0], entry.color[1], entry.color[2]],
+                "opacity": entry.opacity,
+                "style": match entry.style {
+                    DensityStyle::Surface => "surface",
+                    DensityStyle::Mesh => "mesh",
+                    DensityStyle::Volume => "volume",
+                },
+                "difference": entry.difference,
+                "potential": engine
+                    .density
+                    .potential()
+                    .is_some_and(|(potential, _)| potential == id),
+                "levels": entry
+                    .extra_levels
+                    .iter()
+                    .map(|l| {
+                        serde_json::json!({
+                            "threshold": l.threshold,
+                            "color": l.color,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+                "zone": match entry.zone.map(|z| z.shape) {
+                    None => "off",
+                    Some(ZoneShape::Box) => "box",
+                    Some(ZoneShape::Sphere) => "sphere",
+                },
+                "zone_radius": entry.zone.map_or(
+                    DEFAULT_ZONE_RADIUS,
+                    |z| z.radius,
+                ),
+                "zone_anchor": match entry.zone.map(|z| z.anchor) {
+                    Some(ZoneAnchor::Selection) => "selection",
+                    _ => "camera",
+                },
+                "volume_palette": entry.transfer.palette,
+                "volume_low": entry.transfer.low_sigma,
+                "volume_high": entry.transfer.high_sigma,
+                "volume_opacity": entry.transfer.opacity,
+            })
+        })
+        .collect()
+}
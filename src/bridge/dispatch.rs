@@ -11,7 +11,12 @@
 //! on native, an iframe `eval` on web.
 
 use crate::bridge::{self, UiAction};
+use crate::engine::command::CommandOutcome;
+use crate::engine::density_store::{DensityStyle, ZoneAnchor, ZoneShape};
+use crate::engine::events::VisoEvent;
+use crate::engine::surface::SurfaceKind;
 use crate::options::VisoOptions;
+use crate::renderer::geometry::isosurface::density::ContourLevel;
 use crate::VisoEngine;
 
 /// State-push surface a host (native or web) provides to the dispatcher.
@@ -44,8 +49,17 @@ pub(crate) fn dispatch_engine_action(
             None
         }
         UiAction::Command(cmd) => {
-            let _ = engine.execute(cmd);
-            push_scene_entities(engine, host);
+            // Selection / camera-only outcomes reach viso-ui as event
+            // diffs via `push_events`; only visibility changes need a
+            // full summary push.
+            match engine.execute(cmd) {
+                CommandOutcome::NoEffect | CommandOutcome::SelectionChanged => {
+                }
+                CommandOutcome::PlaybackChanged => {
+                    push_trajectory(engine, host);
+                }
+                _ => push_scene_entities(engine, host),
+            }
             None
         }
         UiAction::ClearEntityAppearance { entity_id } => {
@@ -67,22 +81,25 @@ pub(crate) fn dispatch_engine_action(
         UiAction::SetEntitySurface { entity_id, kind } => {
             if let Some(eid) = engine.entity_id(entity_id) {
                 let default_color = [0.7, 0.7, 0.7, 0.35];
+                let kind = serde_json::from_value(serde_json::json!(kind))
+                    .ok()
+                    .and_then(SurfaceKind::from_option);
                 let mut view = engine.annotations_mut();
-                match kind.as_str() {
-                    "gaussian" => {
-                        view.add_gaussian_surface(eid, default_color);
-                    }
-                    "ses" => {
-                        view.add_ses_surface(eid, default_color);
-                    }
-                    _ => {
-                        view.remove_surface(eid);
-                    }
+                match kind {
+                    Some(kind) => view.add_surface(eid, kind, default_color),
+                    None => view.remove_surface(eid),
                 }
             }
             push_scene_entities(engine, host);
             None
         }
+        UiAction::SurfaceSelection { kind } => {
+            if let Ok(kind) = serde_json::from_value(serde_json::json!(kind)) {
+                let _ = engine.surface_selection(kind);
+            }
+            push_scene_entities(engine, host);
+            None
+        }
         UiAction::SetSurfaceOption {
             entity_id,
             field,
@@ -123,6 +140,45 @@ pub(crate) fn dispatch_engine_action(
             push_density_maps(engine, host);
             None
         }
+        UiAction::AnalyzeTrajectory { reference_frame } => {
+            let _ = engine.analyze_trajectory(reference_frame);
+            push_trajectory_analysis(engine, host);
+            // RMSF coloring / putty radius show up in the scene.
+            push_scene_entities(engine, host);
+            None
+        }
+        UiAction::ComputeSecondaryStructure => {
+            engine.compute_secondary_structure();
+            push_scene_entities(engine, host);
+            None
+        }
+        UiAction::ComputeSasa => {
+            let residues = engine.compute_sasa();
+            host.push("sasa", &bridge::sasa_summary(&residues).to_string());
+            // SASA coloring shows up in the scene.
+            push_scene_entities(engine, host);
+            None
+        }
+        UiAction::AnalyzeInterface { a, b } => {
+            let interface = engine.analyze_interface(a, b);
+            let json = bridge::interface_summary(interface.as_ref());
+            host.push("interface", &json.to_string());
+            None
+        }
+        UiAction::SetSymmetryView { view } => {
+            if let Err(e) = engine.set_symmetry_view(view) {
+                log::warn!("{e}");
+            }
+            push_symmetry(engine, host);
+            // Assembly views hide chains outside the assembly.
+            push_scene_entities(engine, host);
+            None
+        }
+        UiAction::ShowUnitCell { show } => {
+            engine.set_show_unit_cell(show);
+            push_symmetry(engine, host);
+            None
+        }
         // Platform-specific — return to caller.
         passthrough @ (UiAction::TogglePanel
         | UiAction::ResizePanel { .. }
@@ -141,11 +197,69 @@ pub(crate) fn push_options(engine: &VisoEngine, host: &dyn UiHost) {
     host.push("options", &json);
 }
 
-/// Serialize and push the current scene entity summaries.
+/// Serialize and push the current scene entity summaries, followed by
+/// the sequence tracks (their colors and pickable ranges follow the
+/// same display and visibility changes).
 pub(crate) fn push_scene_entities(engine: &VisoEngine, host: &dyn UiHost) {
     let summaries = bridge::entity_summaries(engine);
     let json = serde_json::to_string(&summaries).unwrap_or_default();
     host.push("scene_entities", &json);
+    push_sequences(engine, host);
+}
+
+/// Serialize and push the per-chain sequence tracks.
+pub(crate) fn push_sequences(engine: &VisoEngine, host: &dyn UiHost) {
+    let sequences = bridge::sequence_summaries(engine);
+    let json = serde_json::to_string(&sequences).unwrap_or_default();
+    host.push("sequences", &json);
+}
+
+/// Drain the engine's queued change events and push them as a diff.
+/// Re-pushes the full entity summaries only when an event changes a
+/// field they report (focus, entity list).
+pub(crate) fn push_events(engine: &mut VisoEngine, host: &dyn UiHost) {
+    let events = engine.drain_events();
+    if events.is_empty() {
+        return;
+    }
+    let payload = bridge::event_payloads(&events);
+    let json = serde_json::to_string(&payload).unwrap_or_default();
+    host.push("events", &json);
+    if bridge::events_touch_entity_summaries(&events) {
+        push_scene_entities(engine, host);
+    }
+    if events
+        .iter()
+        .any(|e| matches!(e, VisoEvent::TrajectoryFrameChanged { .. }))
+    {
+        push_trajectory(engine, host);
+        if engine.trajectory_analysis().is_some() {
+            push_trajectory_displacement(engine, host);
+        }
+    }
+}
+
+/// Serialize and push the trajectory playback state.
+pub(crate) fn push_trajectory(engine: &VisoEngine, host: &dyn UiHost) {
+    let json = bridge::trajectory_summary(engine).to_string();
+    host.push("trajectory", &json);
+}
+
+/// Serialize and push the trajectory analysis series, followed by the
+/// displayed frame's displacement.
+pub(crate) fn push_trajectory_analysis(engine: &VisoEngine, host: &dyn UiHost) {
+    let json = bridge::trajectory_analysis_summary(engine).to_string();
+    host.push("trajectory_analysis", &json);
+    push_trajectory_displacement(engine, host);
+}
+
+/// Serialize and push the displayed frame's per-residue displacement.
+pub(crate) fn push_trajectory_displacement(
+    engine: &VisoEngine,
+    host: &dyn UiHost,
+) {
+    let json = bridge::trajectory_displacement_summary(engine).to_string();
+    host.push("trajectory_displacement", &json);
 }
 
 /// Serialize and push the current density map summaries.
@@ -155,6 +269,13 @@ pub(crate) fn push_density_maps(engine: &VisoEngine, host: &dyn UiHost) {
     host.push("density_maps", &json);
 }
 
+/// Serialize and push the structure's assemblies, unit cell and active
+/// symmetry view.
+pub(crate) fn push_symmetry(engine: &VisoEngine, host: &dyn UiHost) {
+    let json = bridge::symmetry_summary(engine).to_string();
+    host.push("symmetry", &json);
+}
+
 // ── Engine mutators ─────────────────────────────────────────────────────
 
 /// Apply a `SetOption` patch by serializing options, mutating the JSON,
@@ -243,6 +364,127 @@ fn apply_density_option(
                 engine.density_mut().set_color(id, color);
             }
         }
+        "style" => match value.as_str() {
+            Some("surface") => {
+                engine.density_mut().set_style(id, DensityStyle::Surface);
+            }
+            Some("mesh") => {
+                engine.density_mut().set_style(id, DensityStyle::Mesh);
+            }
+            Some("volume") => {
+                engine.density_mut().set_style(id, DensityStyle::Volume);
+            }
+            _ => log::warn!("Invalid density style: {value}"),
+        },
+        "difference" => {
+            if let Some(v) = value.as_bool() {
+                engine.density_mut().set_difference(id, v);
+            }
+        }
+        "potential" => {
+            if let Some(v) = value.as_bool() {
+                let current = engine.density.potential().map(|(id, _)| id);
+                let potential = if v {
+                    Some(id)
+                } else {
+                    current.filter(|&current| current != id)
+                };
+                engine.density_mut().set_potential(potential);
+            }
+        }
+        "levels" => {
+            let Some(items) = value.as_array() else {
+                log::warn!("density levels must be an array: {value}");
+                return;
+            };
+            let levels = items
+                .iter()
+                .filter_map(|item| {
+                    let threshold = item.get("threshold")?.as_f64()? as f32;
+                    let rgb = item.get("color")?.as_array()?;
+                    let channel = |i: usize| {
+                        rgb.get(i).and_then(serde_json::Value::as_f64)
+                    };
+                    Some(ContourLevel {
+                        threshold,
+                        color: [
+                            channel(0)? as f32,
+                            channel(1)? as f32,
+                            channel(2)? as f32,
+                        ],
+                    })
+                })
+                .collect();
+            engine.density_mut().set_extra_levels(id, levels);
+        }
+        "zone" | "zone_radius" | "zone_anchor" => {
+            let current = engine.density.get(id).and_then(|e| e.zone);
+            let mut zone = current.unwrap_or_default();
+            let enabled = match (field, value.as_str()) {
+                ("zone", Some("off")) => false,
+                ("zone", Some("box")) => {
+                    zone.shape = ZoneShape::Box;
+                    true
+                }
+                ("zone", Some("sphere")) => {
+                    zone.shape = ZoneShape::Sphere;
+                    true
+                }
+                ("zone_anchor", Some("camera")) => {
+                    zone.anchor = ZoneAnchor::Camera;
+                    current.is_some()
+                }
+                ("zone_anchor", Some("selection")) => {
+                    zone.anchor = ZoneAnchor::Selection;
+                    current.is_some()
+                }
+                ("zone_radius", _) => {
+                    let Some(v) = value.as_f64() else { return };
+                    zone.radius = v as f32;
+                    current.is_some()
+                }
+                _ => {
+                    log::warn!("Invalid density {field} value: {value}");
+                    return;
+                }
+            };
+            engine.density_mut().set_zone(id, enabled.then_some(zone));
+        }
+        "volume_palette" | "volume_low" | "volume_high" | "volume_opacity" => {
+            let Some(mut transfer) =
+                engine.density.get(id).map(|e| e.transfer.clone())
+            else {
+                return;
+            };
+            let parsed = match field {
+                "volume_palette" => serde_json::from_value(value.clone())
+                    .ok()
+                    .map(|preset| transfer.palette = preset),
+                "volume_low" => {
+                    value.as_f64().map(|v| transfer.low_sigma = v as f32)
+                }
+                "volume_high" => {
+                    value.as_f64().map(|v| transfer.high_sigma = v as f32)
+                }
+                _ => value.as_array().map(|points| {
+                    transfer.opacity = points
+                        .iter()
+                        .filter_map(|p| {
+                            let p = p.as_array()?;
+                            Some([
+                                p.first()?.as_f64()? as f32,
+                                p.get(1)?.as_f64()? as f32,
+                            ])
+                        })
+                        .collect();
+                }),
+            };
+            if parsed.is_none() {
+                log::warn!("Invalid density {field} value: {value}");
+                return;
+            }
+            engine.density_mut().set_transfer(id, transfer);
+        }
         _ => log::warn!("Unknown density field: {field}"),
     }
 }
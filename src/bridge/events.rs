@@ -0,0 +1,82 @@
+//! Engine event payloads pushed to viso-ui.
+
+use crate::engine::events::VisoEvent;
+use crate::engine::focus::Focus;
+
+/// Serialize drained [`VisoEvent`]s into the JSON diff payload pushed
+/// to viso-ui under the `events` key.
+pub(crate) fn event_payloads(events: &[VisoEvent]) -> Vec<serde_json::Value> {
+    use crate::renderer::picking::PickTarget;
+
+    events
+        .iter()
+        .map(|event| match event {
+            VisoEvent::SelectionChanged { added, removed } => {
+                serde_json::json!({
+                    "type": "selection_changed",
+                    "added": added,
+                    "removed": removed,
+                })
+            }
+            VisoEvent::HoverChanged { target } => {
+                let target = match *target {
+                    PickTarget::None => serde_json::json!(null),
+                    PickTarget::Residue(r) => {
+                        serde_json::json!({ "residue": r })
+                    }
+                    PickTarget::Atom {
+                        entity_id,
+                        atom_idx,
+                    } => serde_json::json!({
+                        "entity_id": entity_id,
+                        "atom_idx": atom_idx,
+                    }),
+                };
+                serde_json::json!({
+                    "type": "hover_changed",
+                    "target": target,
+                })
+            }
+            VisoEvent::FocusChanged { focus } => {
+                let entity = match focus {
+                    Focus::Session => None,
+                    Focus::Entity(eid) => Some(eid.raw()),
+                };
+                serde_json::json!({
+                    "type": "focus_changed",
+                    "entity_id": entity,
+                })
+            }
+            VisoEvent::TransitionFinished { entity_id } => serde_json::json!({
+                "type": "transition_finished",
+                "entity_id": entity_id,
+            }),
+            VisoEvent::TrajectoryFrameChanged { frame, total } => {
+                serde_json::json!({
+                    "type": "trajectory_frame_changed",
+                    "frame": frame,
+                    "total": total,
+                })
+            }
+            VisoEvent::DensityMeshReady => {
+                serde_json::json!({ "type": "density_mesh_ready" })
+            }
+            VisoEvent::SceneSynced { generation } => serde_json::json!({
+                "type": "scene_synced",
+                "generation": generation,
+            }),
+        })
+        .collect()
+}
+
+/// Whether any event in `events` changes a field reported by
+/// [`entity_summaries`](super::entity_summaries) (focus flag, entity list), so
+/// the host should re-push the full summaries alongside the diff.
+pub(crate) fn events_touch_entity_summaries(events: &[VisoEvent]) -> bool {
+    events.iter().any(|e| {
+        matches!(
+            e,
+            VisoEvent::FocusChanged { .. } | VisoEvent::SceneSynced { .. }
+        )
+    })
+}
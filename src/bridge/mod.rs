@@ -5,10 +5,30 @@
 //! (wasm) hosts inject into viso-ui.
 
 use crate::engine::command::VisoCommand;
+use crate::engine::density_formats;
 use crate::engine::focus::Focus;
+use crate::engine::residue_address::{self, ResidueAddress};
+use crate::engine::symmetry::SymmetryView;
+use crate::engine::trajectory::{PlaybackMode, TrajectorySmoothing};
 use crate::VisoEngine;
 
+mod density;
 pub(crate) mod dispatch;
+mod events;
+mod sasa;
+mod sequence;
+mod symmetry;
+mod trajectory;
+
+pub(crate) use density::density_summaries;
+pub(crate) use events::{event_payloads, events_touch_entity_summaries};
+pub(crate) use sasa::{interface_summary, sasa_summary};
+pub(crate) use sequence::sequence_summaries;
+pub(crate) use symmetry::symmetry_summary;
+pub(crate) use trajectory::{
+    trajectory_analysis_summary, trajectory_displacement_summary,
+    trajectory_summary,
+};
 
 // ── Panel layout model ──────────────────────────────────────────────────
 
@@ -125,7 +145,13 @@ pub enum UiAction {
     SetEntitySurface {
         /// Entity ID.
         entity_id: u32,
-        /// Surface kind: `"none"`, `"gaussian"`, or `"ses"`.
+        /// Surface kind: `"none"`, `"gaussian"`, `"ses"`, `"sas"`,
+        /// `"vdw"` or `"dots"`.
+        kind: String,
+    },
+    /// Surface just the selected residues of their entities.
+    SurfaceSelection {
+        /// Surface kind, as for [`SetEntitySurface`](Self::SetEntitySurface).
         kind: String,
     },
     /// Set a molecular surface display parameter.
@@ -151,6 +177,33 @@ pub enum UiAction {
         /// New JSON value (null clears the override for that field).
         value: serde_json::Value,
     },
+    /// Run trajectory analysis against a reference frame.
+    AnalyzeTrajectory {
+        /// Frame every other frame is superposed onto.
+        reference_frame: usize,
+    },
+    /// Recompute DSSP secondary structure at the displayed coordinates.
+    ComputeSecondaryStructure,
+    /// Compute per-residue solvent-accessible surface area.
+    ComputeSasa,
+    /// Measure the surface buried between two entities.
+    AnalyzeInterface {
+        /// Raw id of the first entity.
+        a: u32,
+        /// Raw id of the second entity.
+        b: u32,
+    },
+    /// Switch between the asymmetric unit, a biological assembly, and
+    /// crystal symmetry mates.
+    SetSymmetryView {
+        /// View to display.
+        view: SymmetryView,
+    },
+    /// Show or hide the unit-cell box.
+    ShowUnitCell {
+        /// Whether the box is drawn.
+        show: bool,
+    },
     /// An engine command to forward via `engine.execute()`.
     Command(VisoCommand),
 }
@@ -210,6 +263,10 @@ pub(crate) fn parse_action(msg: &serde_json::Value) -> Option<UiAction> {
             let kind = msg.get("kind")?.as_str()?.to_owned();
             Some(UiAction::SetEntitySurface { entity_id, kind })
         }
+        "surface_selection" => {
+            let kind = msg.get("kind")?.as_str()?.to_owned();
+            Some(UiAction::SurfaceSelection { kind })
+        }
         "set_surface_option" => {
             let entity_id = msg.get("entity_id")?.as_u64()? as u32;
             let field = msg.get("field")?.as_str()?.to_owned();
@@ -234,6 +291,121 @@ pub(crate) fn parse_action(msg: &serde_json::Value) -> Option<UiAction> {
             let id = msg.get("id")?.as_u64()? as u32;
             Some(UiAction::ToggleDensityVisibility { id })
         }
+        "analyze_trajectory" => {
+            let reference_frame = msg
+                .get("reference_frame")
+                .and_then(serde_json::Value::as_u64)
+                .unwrap_or(0) as usize;
+            Some(UiAction::AnalyzeTrajectory { reference_frame })
+        }
+        "compute_secondary_structure" => {
+            Some(UiAction::ComputeSecondaryStructure)
+        }
+        "compute_sasa" => Some(UiAction::ComputeSasa),
+        "analyze_interface" => {
+            let (a, b) = sasa::parse_entity_pair(msg)?;
+            Some(UiAction::AnalyzeInterface { a, b })
+        }
+        "select_interface" => {
+            let (a, b) = sasa::parse_entity_pair(msg)?;
+            let extend = msg
+                .get("extend")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false);
+            Some(UiAction::Command(VisoCommand::SelectInterface {
+                a,
+                b,
+                extend,
+            }))
+        }
+        "set_symmetry_view" => {
+            let view = match msg.get("mode")?.as_str()? {
+                "asymmetric" => SymmetryView::AsymmetricUnit,
+                "assembly" => SymmetryView::Assembly(
+                    msg.get("assembly_id")?.as_str()?.to_owned(),
+                ),
+                "mates" => SymmetryView::CrystalMates {
+                    radius: msg
+                        .get("radius")
+                        .and_then(serde_json::Value::as_f64)
+                        .unwrap_or(8.0) as f32,
+                },
+                _ => return None,
+            };
+            Some(UiAction::SetSymmetryView { view })
+        }
+        "show_unit_cell" => {
+            let show = msg.get("show")?.as_bool()?;
+            Some(UiAction::ShowUnitCell { show })
+        }
+        "select_residue" => {
+            let extend = msg
+                .get("extend")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false);
+            if let Some(address) = msg.get("address") {
+                let address = parse_residue_address(address)?;
+                return Some(UiAction::Command(VisoCommand::SelectResidueAt {
+                    address,
+                    extend,
+                }));
+            }
+            let index = msg.get("index")?.as_i64()? as i32;
+            Some(UiAction::Command(VisoCommand::SelectResidue {
+                index,
+                extend,
+            }))
+        }
+        "toggle_trajectory" => {
+            Some(UiAction::Command(VisoCommand::ToggleTrajectory))
+        }
+        "seek_trajectory" => {
+            let frame = msg.get("frame")?.as_u64()? as usize;
+            Some(UiAction::Command(VisoCommand::SeekTrajectory { frame }))
+        }
+        "step_trajectory" => {
+            let delta = msg.get("delta")?.as_i64()? as i32;
+            Some(UiAction::Command(VisoCommand::StepTrajectory { delta }))
+        }
+        "set_trajectory_fps" => {
+            let fps = msg.get("fps")?.as_f64()? as f32;
+            Some(UiAction::Command(VisoCommand::SetTrajectoryFps { fps }))
+        }
+        "set_trajectory_mode" => {
+            let mode = match msg.get("mode")?.as_str()? {
+                "loop" => PlaybackMode::Loop,
+                "once" => PlaybackMode::Once,
+                "ping_pong" => PlaybackMode::PingPong,
+                _ => return None,
+            };
+            Some(UiAction::Command(VisoCommand::SetTrajectoryMode { mode }))
+        }
+        "set_trajectory_range" => {
+            let start = msg.get("start")?.as_u64()? as usize;
+            let end = msg
+                .get("end")
+                .and_then(serde_json::Value::as_u64)
+                .map(|e| e as usize);
+            Some(UiAction::Command(VisoCommand::SetTrajectoryRange {
+                start,
+                end,
+            }))
+        }
+        "set_trajectory_smoothing" => {
+            let smoothing = TrajectorySmoothing {
+                interpolate: msg
+                    .get("interpolate")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(false),
+                window: msg
+                    .get("window")
+                    .and_then(serde_json::Value::as_u64)
+                    .map_or(0, |w| w as usize),
+            };
+            Some(UiAction::Command(VisoCommand::SetTrajectorySmoothing {
+                smoothing,
+            }))
+        }
         "set_entity_appearance" | "set_entity_option" => {
             let entity_id = msg.get("entity_id")?.as_u64()? as u32;
             let field = msg.get("field")?.as_str()?.to_owned();
@@ -248,6 +420,41 @@ pub(crate) fn parse_action(msg: &serde_json::Value) -> Option<UiAction> {
     }
 }
 
+/// Parse a `{chain_id, auth_seq_id, ins_code?, entity_id?}` residue
+/// address.
+fn parse_residue_address(value: &serde_json::Value) -> Option<ResidueAddress> {
+    let single_char = |key: &str| {
+        value
+            .get(key)
+            .and_then(serde_json::Value::as_str)
+            .and_then(|s| s.chars().next())
+    };
+    Some(ResidueAddress {
+        entity_id: value
+            .get("entity_id")
+            .and_then(serde_json::Value::as_u64)
+            .map(|id| id as u32),
+        chain_id: single_char("chain_id")?,
+        auth_seq_id: value.get("auth_seq_id")?.as_i64()? as i32,
+        ins_code: single_char("ins_code"),
+    })
+}
+
+/// JSON form of a residue address, with its flat residue index
+/// (`null` while the entity is hidden).
+fn residue_address_json(
+    engine: &VisoEngine,
+    address: &ResidueAddress,
+) -> serde_json::Value {
+    serde_json::json!({
+        "entity_id": address.entity_id,
+        "chain_id": address.chain_id.to_string(),
+        "auth_seq_id": address.auth_seq_id,
+        "ins_code": address.ins_code.map(String::from),
+        "index": engine.residue_index(address),
+    })
+}
+
 // ── JS escaping ──────────────────────────────────────────────────────────
 
 /// Escape a string for safe embedding in a JavaScript single-quoted
@@ -325,6 +532,16 @@ pub(crate) fn entity_summaries(engine: &VisoEngine) -> Vec<serde_json::Value> {
                 "sheet_style": resolved_display.sheet_style(),
                 "has_overrides": has_overrides,
             });
+            let residue_count = entity.residues().map_or(0, <[_]>::len);
+            if residue_count > 0 {
+                let address = |local| {
+                    residue_address::address_of(entity, local)
+                        .map(|a| residue_address_json(engine, &a))
+                };
+                entry["first_residue"] = serde_json::json!(address(0));
+                entry["last_residue"] =
+                    serde_json::json!(address(residue_count - 1));
+            }
             if let Some(ovr_val) = ovr {
                 if let Ok(ovr_json) = serde_json::to_value(ovr_val) {
                     entry["appearance_overrides"] = ovr_json;
@@ -338,24 +555,28 @@ pub(crate) fn entity_summaries(engine: &VisoEngine) -> Vec<serde_json::Value> {
 /// Effective surface kind string for an entity, accounting for per-entity
 /// overrides (including invisible opt-outs) and global fallback.
 fn effective_surface_kind(engine: &VisoEngine, raw: u32) -> &'static str {
-    use crate::engine::surface::SurfaceKind;
     use crate::options::SurfaceKindOption;
 
-    if let Some(eid) = engine.entity_id(raw) {
-        if let Some(s) = engine.annotations.surfaces.get(&eid) {
-            if !s.visible {
-                return "none";
-            }
-            return match s.kind {
-                SurfaceKind::Gaussian => "gaussian",
-                SurfaceKind::Ses => "ses",
-            };
-        }
-    }
-    // No per-entity entry — fall back to global
-    match engine.options.display.surface_kind() {
+    let kind = engine
+        .entity_id(raw)
+        .and_then(|eid| engine.annotations.surfaces.get(&eid))
+        .map_or_else(
+            // No per-entity entry — fall back to global
+            || engine.options.display.surface_kind(),
+            |s| {
+                if s.visible {
+                    s.kind.option()
+                } else {
+                    SurfaceKindOption::None
+                }
+            },
+        );
+    match kind {
         SurfaceKindOption::Gaussian => "gaussian",
         SurfaceKindOption::Ses => "ses",
+        SurfaceKindOption::Sas => "sas",
+        SurfaceKindOption::Vdw => "vdw",
+        SurfaceKindOption::Dots => "dots",
         SurfaceKindOption::None => "none",
     }
 }
@@ -374,28 +595,6 @@ fn effective_surface_color(engine: &VisoEngine, raw: u32) -> serde_json::Value {
     serde_json::json!(null)
 }
 
-// ── Density summaries ────────────────────────────────────────────────────
-
-/// Build a JSON-serializable summary of all density maps for the viso-ui
-/// panel.
-pub(crate) fn density_summaries(engine: &VisoEngine) -> Vec<serde_json::Value> {
-    engine
-        .density
-        .all_entries()
-        .map(|(id, entry)| {
-            serde_json::json!({
-                "id": id,
-                "visible": entry.visible,
-                "threshold": entry.threshold,
-                "dmin": entry.map.dmin,
-                "dmax": entry.map.dmax,
-                "color": [entry.color[0], entry.color[1], entry.color[2]],
-                "opacity": entry.opacity,
-            })
-        })
-        .collect()
-}
-
 // ── File parsing ─────────────────────────────────────────────────────────
 
 /// Result of parsing a file — either a structure or a density map.
@@ -403,15 +602,24 @@ pub(crate) fn density_summaries(engine: &VisoEngine) -> Vec<serde_json::Value> {
 pub(crate) enum ParsedFile {
     /// Molecular structure (protein, ligand, etc.).
     Structure(Vec<molex::MoleculeEntity>),
-    /// Electron density map (CCP4/MRC format).
+    /// Volumetric map (MRC / CCP4, cube, DX, DSN6 / BRIX).
     Density(molex::entity::surface::Density),
 }
 
 /// Returns `true` if the extension indicates a density map format.
+/// Compressed maps pass their inner extension too (`"map.gz"`, see
+/// [`density_formats::file_extension`]).
 pub(crate) fn is_density_extension(ext: &str) -> bool {
+    density_formats::is_density_format(ext)
+}
+
+/// Returns `true` if the extension indicates a trajectory-only format.
+/// Multi-model PDB / mmCIF ensembles can also be played as
+/// trajectories but load as structures by default.
+pub(crate) fn is_trajectory_extension(ext: &str) -> bool {
     let lower = ext.to_ascii_lowercase();
     let trimmed = lower.trim_start_matches('.');
-    matches!(trimmed, "mrc" | "map" | "ccp4")
+    matches!(trimmed, "dcd" | "xtc" | "trr" | "xyz")
 }
 
 /// Parse a file from in-memory bytes, auto-detecting structure vs density
@@ -427,8 +635,8 @@ pub(crate) fn parse_file_bytes(
     let hint = format_hint.to_ascii_lowercase();
     let hint = hint.trim_start_matches('.');
     match hint {
-        "mrc" | "map" | "ccp4" => {
-            let map = molex::adapters::mrc::mrc_to_density(bytes)
+        _ if hint == "gz" || is_density_extension(hint) => {
+            let map = density_formats::parse_density(bytes, hint)
                 .map_err(|e| format!("Density parse error: {e}"))?;
             Ok(ParsedFile::Density(map))
         }
@@ -453,7 +661,7 @@ pub(crate) fn parse_file_bytes(
         }
         other => Err(format!(
             "Unsupported format '{other}'. Use 'cif', 'pdb', 'bcif', 'mrc', \
-             'map', or 'ccp4'."
+             'map', 'ccp4', 'cube', 'dx', 'dsn6', 'brix', or a gzipped map."
         )),
     }
 }
@@ -504,6 +712,14 @@ pub(crate) const BRIDGE_JS: &str = r"
     makePush('orientation', 'viso-orientation');
     makePush('panel_size', 'viso-panel-size');
     makePush('density_maps', 'viso-density-maps');
+    makePush('events', 'viso-events');
+    makePush('trajectory', 'viso-trajectory');
+    makePush('trajectory_analysis', 'viso-trajectory-analysis');
+    makePush('trajectory_displacement', 'viso-trajectory-displacement');
+    makePush('sequences', 'viso-sequences');
+    makePush('symmetry', 'viso-symmetry');
+    makePush('sasa', 'viso-sasa');
+    makePush('interface', 'viso-interface');
 
     // Allow late listeners (e.g. dioxus WASM) to replay any values
     // that were pushed before they registered.
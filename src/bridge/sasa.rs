@@ -0,0 +1,56 @@
+//! SASA and interface analysis payloads for viso-ui.
+
+use crate::engine::sasa::{Exposure, InterfaceAnalysis, ResidueSasa};
+
+/// The `"a"` and `"b"` entity ids of an interface message.
+pub(super) fn parse_entity_pair(msg: &serde_json::Value) -> Option<(u32, u32)> {
+    let a = msg.get("a")?.as_u64()? as u32;
+    let b = msg.get("b")?.as_u64()? as u32;
+    Some((a, b))
+}
+
+/// Build the JSON per-residue SASA list.
+pub(crate) fn sasa_summary(residues: &[ResidueSasa]) -> serde_json::Value {
+    residues
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "entity_id": r.entity_id,
+                "residue": r.residue,
+                "area": r.area,
+                "relative": r.relative,
+                "exposure": r.exposure().map(|e| match e {
+                    Exposure::Buried => "buried",
+                    Exposure::Intermediate => "intermediate",
+                    Exposure::Exposed => "exposed",
+                }),
+            })
+        })
+        .collect()
+}
+
+/// Build the JSON interface analysis, or `null` when there is none.
+pub(crate) fn interface_summary(
+    interface: Option<&InterfaceAnalysis>,
+) -> serde_json::Value {
+    let Some(interface) = interface else {
+        return serde_json::Value::Null;
+    };
+    let residues: Vec<serde_json::Value> = interface
+        .residues
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "entity_id": r.entity_id,
+                "residue": r.residue,
+                "index": r.index,
+                "buried_area": r.buried_area,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "entities": interface.entities,
+        "buried_area": interface.buried_area,
+        "residues": residues,
+    })
+}
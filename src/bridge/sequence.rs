@@ -0,0 +1,171 @@
+//! Sequence panel tracks: one-letter sequences, author numbering,
+//! secondary structure and residue colors per protein chain.
+
+use std::ops::Range;
+
+use molex::entity::molecule::id::EntityId;
+use molex::entity::molecule::polymer::Residue;
+
+use crate::engine::residue_address;
+use crate::VisoEngine;
+
+/// Build the per-chain sequence tracks for the viso-ui sequence panel:
+/// one-letter codes, author numbering, secondary structure and the
+/// per-residue cartoon colors of every protein entity.
+///
+/// `first_index` is the flat residue index of the chain's first
+/// residue, as used by picking and `SelectResidue`. Hidden entities
+/// have no pickable residues and report `null`. `dssp` carries the
+/// eight-state DSSP letters once they've been computed.
+pub(crate) fn sequence_summaries(
+    engine: &VisoEngine,
+) -> Vec<serde_json::Value> {
+    use molex::SSType;
+
+    let scene = &engine.scene;
+    let ranges = scene.protein_residue_ranges(&engine.annotations);
+
+    scene
+        .current
+        .entities()
+        .iter()
+        .filter_map(|entity| {
+            let protein = entity.as_protein()?;
+            let eid = entity.id();
+            let state = scene.entity_state.get(&eid)?;
+            let ss = state
+                .ss_override
+                .as_deref()
+                .unwrap_or(&state.topology.ss_types);
+            let sequence: String =
+                protein.residues.iter().map(|r| one_letter(r.name)).collect();
+            let numbers = residue_numbers(&protein.residues);
+            let ss: String = (0..protein.residues.len())
+                .map(|i| match ss.get(i) {
+                    Some(SSType::Helix) => 'H',
+                    Some(SSType::Sheet) => 'E',
+                    _ => 'C',
+                })
+                .collect();
+            let dssp: Option<String> = engine
+                .annotations
+                .computed_ss
+                .get(&eid)
+                .map(|codes| codes.iter().map(|c| c.letter()).collect());
+            Some(serde_json::json!({
+                "entity_id": eid.raw(),
+                "chain_id": entity.pdb_chain_id().map(|c| (c as char).to_string()),
+                "label": entity.label(),
+                "sequence": sequence,
+                "numbers": numbers,
+                "ss": ss,
+                "dssp": dssp,
+                "colors": state.per_residue_colors,
+                "first_index": first_index(&ranges, eid),
+            }))
+        })
+        .collect()
+}
+
+/// Author residue numbers as printed, with any insertion code
+/// appended (`"52A"`).
+fn residue_numbers(residues: &[Residue]) -> Vec<String> {
+    residues
+        .iter()
+        .map(|r| {
+            let (number, ins_code) = residue_address::residue_number(r);
+            ins_code
+                .map_or_else(|| number.to_string(), |c| format!("{number}{c}"))
+        })
+        .collect()
+}
+
+/// Flat residue index of `entity`'s first residue, or `None` while it
+/// is hidden (absent from `ranges`).
+fn first_index(
+    ranges: &[(EntityId, Range<u32>)],
+    entity: EntityId,
+) -> Option<u32> {
+    ranges
+        .iter()
+        .find(|(e, _)| *e == entity)
+        .map(|(_, r)| r.start)
+}
+
+/// One-letter code for a 3-letter residue name; `X` for anything
+/// non-standard.
+fn one_letter(name: [u8; 3]) -> char {
+    match &name {
+        b"ALA" => 'A',
+        b"ARG" => 'R',
+        b"ASN" => 'N',
+        b"ASP" => 'D',
+        b"CYS" => 'C',
+        b"GLN" => 'Q',
+        b"GLU" => 'E',
+        b"GLY" => 'G',
+        b"HIS" => 'H',
+        b"ILE" => 'I',
+        b"LEU" => 'L',
+        b"LYS" => 'K',
+        b"MET" | b"MSE" => 'M',
+        b"PHE" => 'F',
+        b"PRO" => 'P',
+        b"SER" => 'S',
+        b"THR" => 'T',
+        b"TRP" => 'W',
+        b"TYR" => 'Y',
+        b"VAL" => 'V',
+        b"SEC" => 'U',
+        b"PYL" => 'O',
+        _ => 'X',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use molex::entity::molecule::id::EntityIdAllocator;
+
+    use super::*;
+
+    fn residue(name: [u8; 3], auth: i32, ins: Option<u8>) -> Residue {
+        Residue {
+            name,
+            label_seq_id: auth,
+            auth_seq_id: Some(auth),
+            auth_comp_id: None,
+            ins_code: ins,
+            atom_range: 0..0,
+            variants: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn one_letter_codes() {
+        assert_eq!(one_letter(*b"TRP"), 'W');
+        assert_eq!(one_letter(*b"MSE"), 'M');
+        assert_eq!(one_letter(*b"SEC"), 'U');
+        assert_eq!(one_letter(*b"HOH"), 'X');
+    }
+
+    #[test]
+    fn numbers_carry_insertion_codes() {
+        let residues = [
+            residue(*b"ALA", 52, None),
+            residue(*b"GLY", 52, Some(b'A')),
+            residue(*b"SER", -1, None),
+        ];
+        assert_eq!(residue_numbers(&residues), ["52", "52A", "-1"]);
+    }
+
+    #[test]
+    fn first_index_follows_the_flat_ranges() {
+        let mut alloc = EntityIdAllocator::new();
+        let (a, b, hidden) =
+            (alloc.allocate(), alloc.allocate(), alloc.allocate());
+        let ranges = [(a, 0..120), (b, 120..200)];
+        assert_eq!(first_index(&ranges, a), Some(0));
+        assert_eq!(first_index(&ranges, b), Some(120));
+        assert_eq!(first_index(&ranges, hidden), None);
+    }
+}
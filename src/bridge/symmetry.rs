@@ -0,0 +1,53 @@
+//! Symmetry summary for the viso-ui Load panel.
+
+use crate::engine::symmetry::SymmetryView;
+use crate::VisoEngine;
+
+/// Build a JSON summary of the loaded structure's assemblies, unit cell
+/// and the active symmetry view for the viso-ui Load panel.
+pub(crate) fn symmetry_summary(engine: &VisoEngine) -> serde_json::Value {
+    let view = match engine.symmetry_view() {
+        SymmetryView::AsymmetricUnit => {
+            serde_json::json!({ "mode": "asymmetric" })
+        }
+        SymmetryView::Assembly(id) => {
+            serde_json::json!({ "mode": "assembly", "assembly_id": id })
+        }
+        SymmetryView::CrystalMates { radius } => {
+            serde_json::json!({ "mode": "mates", "radius": radius })
+        }
+    };
+    let Some(sym) = engine.structure_symmetry() else {
+        return serde_json::json!({
+            "assemblies": [],
+            "cell": null,
+            "space_group": null,
+            "has_crystal_symmetry": false,
+            "view": view,
+            "show_unit_cell": engine.show_unit_cell(),
+        });
+    };
+    let assemblies: Vec<serde_json::Value> = sym
+        .assemblies
+        .iter()
+        .map(|a| {
+            serde_json::json!({
+                "id": a.id,
+                "details": a.details,
+                "chains": a.chains(),
+                "copies": a.operators().len(),
+            })
+        })
+        .collect();
+    let cell = sym
+        .cell
+        .map(|c| serde_json::json!([c.a, c.b, c.c, c.alpha, c.beta, c.gamma]));
+    serde_json::json!({
+        "assemblies": assemblies,
+        "cell": cell,
+        "space_group": sym.space_group,
+        "has_crystal_symmetry": sym.has_crystal_symmetry(),
+        "view": view,
+        "show_unit_cell": engine.show_unit_cell(),
+    })
+}
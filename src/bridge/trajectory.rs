@@ -0,0 +1,86 @@
+//! Trajectory playback state and analysis payloads for the viso-ui
+//! timeline.
+
+use crate::engine::trajectory::{PlaybackMode, ResidueMetric};
+use crate::VisoEngine;
+
+/// Build the JSON trajectory playback state for the viso-ui timeline,
+/// or `null` when no trajectory is loaded.
+pub(crate) fn trajectory_summary(engine: &VisoEngine) -> serde_json::Value {
+    let Some(status) = engine.trajectory_status() else {
+        return serde_json::Value::Null;
+    };
+    let mode = match status.mode {
+        PlaybackMode::Loop => "loop",
+        PlaybackMode::Once => "once",
+        PlaybackMode::PingPong => "ping_pong",
+    };
+    serde_json::json!({
+        "current_frame": status.current_frame,
+        "frame_count": status.frame_count,
+        "playing": status.playing,
+        "fps": status.fps,
+        "mode": mode,
+        "range_start": status.range_start,
+        "range_end": status.range_end,
+        "interpolate": status.smoothing.interpolate,
+        "smoothing_window": status.smoothing.window,
+        "mapping": engine.trajectory_mapping_report().map(|r| {
+            serde_json::json!({
+                "trajectory_atoms": r.trajectory_atoms,
+                "mapped_atoms": r.mapped_atoms,
+                "unmatched_trajectory_atoms": r.unmatched_trajectory_atoms,
+                "unmatched_entity_atoms": r.unmatched_entity_atoms(),
+                "invalid_entries": r.invalid_entries,
+            })
+        }),
+    })
+}
+
+/// Build the JSON trajectory analysis series (RMSD, radius of
+/// gyration, RMSF), or `null` when no analysis has been run.
+pub(crate) fn trajectory_analysis_summary(
+    engine: &VisoEngine,
+) -> serde_json::Value {
+    let Some(analysis) = engine.trajectory_analysis() else {
+        return serde_json::Value::Null;
+    };
+    serde_json::json!({
+        "reference_frame": analysis.reference_frame,
+        "rmsd": analysis.rmsd,
+        "radius_of_gyration": analysis.radius_of_gyration,
+        "rmsf": residue_metrics_json(&analysis.rmsf),
+    })
+}
+
+/// Build the JSON per-residue displacement of the displayed trajectory
+/// frame, or `null` when no analysis has been run.
+pub(crate) fn trajectory_displacement_summary(
+    engine: &VisoEngine,
+) -> serde_json::Value {
+    let Some(frame) = engine.trajectory_current_frame() else {
+        return serde_json::Value::Null;
+    };
+    engine.trajectory_displacement(frame).map_or(
+        serde_json::Value::Null,
+        |values| {
+            serde_json::json!({
+                "frame": frame,
+                "values": residue_metrics_json(&values),
+            })
+        },
+    )
+}
+
+fn residue_metrics_json(metrics: &[ResidueMetric]) -> Vec<serde_json::Value> {
+    metrics
+        .iter()
+        .map(|m| {
+            serde_json::json!({
+                "entity_id": m.entity_id,
+                "residue": m.residue,
+                "value": m.value,
+            })
+        })
+        .collect()
+}
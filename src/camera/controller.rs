@@ -4,6 +4,7 @@ use wgpu::util::DeviceExt;
 use crate::camera::core::{Camera, CameraUniform};
 use crate::camera::frustum::Frustum;
 use crate::gpu::RenderContext;
+use crate::renderer::model_copies::ModelInstance;
 
 /// Speed of camera animation (higher = faster, 1.0 = instant)
 const CAMERA_ANIMATION_SPEED: f32 = 3.0;
@@ -30,9 +31,11 @@ pub(crate) struct CameraController {
     pub(crate) uniform: CameraUniform,
     /// GPU buffer holding the camera uniform.
     pub(crate) buffer: wgpu::Buffer,
-    /// Bind group layout for the camera uniform.
+    /// Bind group layout for the camera uniform and the model copies
+    /// geometry is drawn with.
     pub(crate) layout: wgpu::BindGroupLayout,
-    /// Bind group for the camera uniform.
+    /// Bind group for the camera uniform, drawing everything once,
+    /// untransformed.
     pub(crate) bind_group: wgpu::BindGroup,
 
     /// When Some, the camera spins around the captured axis (camera up at
@@ -48,7 +51,7 @@ pub(crate) struct CameraController {
 
 impl CameraController {
     /// Create the GPU buffer, bind group layout, and bind group for the camera
-    /// uniform.
+    /// uniform. The bind group draws a single untransformed copy.
     fn create_gpu_resources(
         context: &RenderContext,
         uniform: &CameraUniform,
@@ -64,38 +67,75 @@ impl CameraController {
             },
         );
 
+        let copies = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
         let layout = context.device.create_bind_group_layout(
             &wgpu::BindGroupLayoutDescriptor {
                 label: Some("Camera Bind Group Layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX
-                        | wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX
+                            | wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
                     },
-                    count: None,
-                }],
+                    copies(1),
+                    copies(2),
+                ],
             },
         );
 
-        let bind_group =
-            context
-                .device
-                .create_bind_group(&wgpu::BindGroupDescriptor {
-                    layout: &layout,
-                    entries: &[wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: buffer.as_entire_binding(),
-                    }],
-                    label: Some("Camera Bind Group"),
-                });
+        let storage = |label, contents: &[u8]| {
+            context.device.create_buffer_init(
+                &wgpu::util::BufferInitDescriptor {
+                    label: Some(label),
+                    contents,
+                    usage: wgpu::BufferUsages::STORAGE,
+                },
+            )
+        };
+        let instances = storage(
+            "Camera Identity Copy",
+            bytemuck::bytes_of(&ModelInstance::IDENTITY),
+        );
+        let runs = storage(
+            "Camera Identity Copy Runs",
+            bytemuck::bytes_of(&[0_u32, 1]),
+        );
+        let bind_group = create_bind_group(
+            &context.device,
+            &layout,
+            &buffer,
+            [instances.as_entire_binding(), runs.as_entire_binding()],
+        );
 
         (buffer, layout, bind_group)
     }
 
+    /// A bind group of the camera uniform drawing geometry with the
+    /// given model copies: `ModelInstance`s at binding 1 and the
+    /// `[first, count]` runs indexed by pick ID at binding 2.
+    pub(crate) fn copies_bind_group(
+        &self,
+        device: &wgpu::Device,
+        copies: [wgpu::BindingResource<'_>; 2],
+    ) -> wgpu::BindGroup {
+        create_bind_group(device, &self.layout, &self.buffer, copies)
+    }
+
     /// Create a new camera controller with default orbital parameters and GPU
     /// resources.
     pub(crate) fn new(context: &RenderContext) -> Self {
@@ -500,3 +540,30 @@ impl CameraController {
         Frustum::from_view_projection(self.camera.build_matrix())
     }
 }
+
+/// Camera bind group over `camera` and the two model-copy buffers.
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    camera: &wgpu::Buffer,
+    [instances, runs]: [wgpu::BindingResource<'_>; 2],
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: instances,
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: runs,
+            },
+        ],
+        label: Some("Camera Bind Group"),
+    })
+}
@@ -22,7 +22,7 @@ pub(crate) struct Camera {
 
 /// GPU uniform buffer holding the view-projection matrix and camera metadata.
 ///
-/// Layout matches the WGSL `CameraUniform` struct (112 bytes, std140).
+/// Layout matches the WGSL `CameraUniform` struct (128 bytes, std140).
 /// Padding is handled automatically by encase.
 #[derive(Debug, Copy, Clone, ShaderType)]
 pub(crate) struct CameraUniform {
@@ -42,8 +42,8 @@ pub(crate) struct CameraUniform {
     pub(crate) debug_mode: u32,
     /// Wall-clock elapsed time in seconds (for shader animations).
     pub(crate) time: f32,
-    /// Padding to maintain 16-byte alignment.
-    pub(crate) _pad: f32,
+    /// Look-at target: the focus point surface cutaways cut at.
+    pub(crate) focus: Vec3,
 }
 
 impl Camera {
@@ -92,7 +92,7 @@ impl CameraUniform {
             hovered_residue: -1,
             debug_mode: 0,
             time: 0.0,
-            _pad: 0.0,
+            focus: Vec3::ZERO,
         }
     }
 
@@ -102,6 +102,7 @@ impl CameraUniform {
         self.position = camera.eye;
         self.aspect = camera.aspect;
         self.forward = (camera.target - camera.eye).normalize();
+        self.focus = camera.target;
         self.fovy = camera.fovy;
     }
 }
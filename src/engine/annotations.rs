@@ -43,7 +43,8 @@ use super::surface_regen::{regenerate_surfaces, SurfaceRegen};
 use super::VisoEngine;
 use crate::animation::transition::Transition;
 use crate::options::overrides::RenderInvalidation;
-use crate::options::{DisplayOverrides, DrawingMode, VisoOptions};
+use crate::options::{DisplayOverrides, DrawingMode, SsSource, VisoOptions};
+use crate::util::dssp::{self, DsspCode};
 
 /// Per-entity user-authored state that isn't derived from the
 /// [`Assembly`](molex::Assembly).
@@ -59,8 +60,17 @@ pub(crate) struct EntityAnnotations {
     pub(crate) appearance: FxHashMap<EntityId, DisplayOverrides>,
     /// Per-entity scores (for color-by-score visualization).
     pub(crate) scores: FxHashMap<EntityId, Vec<f64>>,
+    /// Per-entity trajectory RMSF in cartoon residue order (for the
+    /// RMSF color scheme and putty tube radius).
+    pub(crate) rmsf: FxHashMap<EntityId, Vec<f32>>,
+    /// Per-entity relative solvent accessibility in cartoon residue
+    /// order (for the SASA color scheme).
+    pub(crate) sasa: FxHashMap<EntityId, Vec<f32>>,
     /// Per-entity SS overrides (from puzzle annotations).
     pub(crate) ss_overrides: FxHashMap<EntityId, Vec<SSType>>,
+    /// Per-entity DSSP assignment computed by the viewer, in cartoon
+    /// residue order. Rendered when `ss_source` is `Computed`.
+    pub(crate) computed_ss: FxHashMap<EntityId, Vec<DsspCode>>,
     /// Per-entity molecular surfaces.
     pub(crate) surfaces: FxHashMap<EntityId, EntitySurface>,
 }
@@ -99,13 +109,36 @@ impl EntityAnnotations {
         self.appearance.get(&id)
     }
 
+    /// Secondary structure to render for `id` in place of the
+    /// assembly's: a host override wins, then the computed DSSP when
+    /// `options` prefers it. `None` renders the assembly's.
+    #[must_use]
+    pub(crate) fn ss_override_for(
+        &self,
+        options: &VisoOptions,
+        id: EntityId,
+    ) -> Option<Vec<SSType>> {
+        if let Some(ss) = self.ss_overrides.get(&id) {
+            return Some(ss.clone());
+        }
+        if options.geometry.ss_source != SsSource::Computed {
+            return None;
+        }
+        self.computed_ss
+            .get(&id)
+            .map(|codes| dssp::to_ss_types(codes))
+    }
+
     /// Drop entries for entities no longer present in the assembly.
     pub(crate) fn retain_entities(&mut self, keep: impl Fn(EntityId) -> bool) {
         self.visibility.retain(|&id, _| keep(id));
         self.behaviors.retain(|&id, _| keep(id));
         self.appearance.retain(|&id, _| keep(id));
         self.scores.retain(|&id, _| keep(id));
+        self.rmsf.retain(|&id, _| keep(id));
+        self.sasa.retain(|&id, _| keep(id));
         self.ss_overrides.retain(|&id, _| keep(id));
+        self.computed_ss.retain(|&id, _| keep(id));
         self.surfaces.retain(|&id, _| keep(id));
     }
 
@@ -179,7 +212,10 @@ impl EntityAnnotations {
         self.behaviors.clear();
         self.appearance.clear();
         self.scores.clear();
+        self.rmsf.clear();
+        self.sasa.clear();
         self.ss_overrides.clear();
+        self.computed_ss.clear();
         self.surfaces.clear();
     }
 }
@@ -196,26 +232,26 @@ impl EntityAnnotations {
 ///
 /// Surface mutations also live here (rather than in a parallel handle
 /// that would conflict on `&mut EntityAnnotations`); they need the
-/// extra read borrows of [`DensityStore`], [`VisoOptions`], and
-/// [`SurfaceRegen`] (plus a reborrow of `&Scene` from the held
-/// `&mut Scene`) to call [`regenerate_surfaces`].
+/// extra read borrows of [`DensityStore`] and [`VisoOptions`], the
+/// [`SurfaceRegen`] dispatch state (plus a reborrow of `&Scene` from
+/// the held `&mut Scene`) to call [`regenerate_surfaces`].
 pub(crate) struct AnnotationsScene<'a> {
     annotations: &'a mut EntityAnnotations,
     scene: &'a mut Scene,
     density: &'a DensityStore,
     options: &'a VisoOptions,
-    regen: &'a SurfaceRegen,
+    regen: &'a mut SurfaceRegen,
 }
 
 impl<'a> AnnotationsScene<'a> {
     /// Construct the view from the two disjoint `&mut` fields plus the
-    /// three immutable borrows surface regeneration needs.
+    /// borrows surface regeneration needs.
     pub(crate) fn new(
         annotations: &'a mut EntityAnnotations,
         scene: &'a mut Scene,
         density: &'a DensityStore,
         options: &'a VisoOptions,
-        regen: &'a SurfaceRegen,
+        regen: &'a mut SurfaceRegen,
     ) -> Self {
         Self {
             annotations,
@@ -266,6 +302,41 @@ impl<'a> AnnotationsScene<'a> {
         self.bump_for(eid);
     }
 
+    /// Record (or clear, with `None`) per-residue RMSF for `eid`.
+    pub(crate) fn set_per_residue_rmsf(
+        &mut self,
+        eid: EntityId,
+        rmsf: Option<Vec<f32>>,
+    ) {
+        match rmsf {
+            Some(r) => {
+                let _ = self.annotations.rmsf.insert(eid, r);
+            }
+            None => {
+                let _ = self.annotations.rmsf.remove(&eid);
+            }
+        }
+        self.bump_for(eid);
+    }
+
+    /// Record (or clear, with `None`) per-residue relative SASA for
+    /// `eid`.
+    pub(crate) fn set_per_residue_sasa(
+        &mut self,
+        eid: EntityId,
+        sasa: Option<Vec<f32>>,
+    ) {
+        match sasa {
+            Some(s) => {
+                let _ = self.annotations.sasa.insert(eid, s);
+            }
+            None => {
+                let _ = self.annotations.sasa.remove(&eid);
+            }
+        }
+        self.bump_for(eid);
+    }
+
     /// Record an SS override for `eid`.
     pub(crate) fn set_ss_override(&mut self, eid: EntityId, ss: Vec<SSType>) {
         let _ = self.annotations.ss_overrides.insert(eid, ss.clone());
@@ -315,49 +386,36 @@ impl<'a> AnnotationsScene<'a> {
         }
     }
 
-    /// Trigger a regeneration of all isosurface meshes (density,
-    /// entity surfaces, cavities) on the background thread.
-    fn regenerate_surfaces(&self) {
+    /// Regenerate the isosurface meshes (density, entity surfaces,
+    /// cavities) whose inputs changed, on the background thread.
+    fn regenerate_surfaces(&mut self) {
         regenerate_surfaces(
             &*self.scene,
             &*self.annotations,
             self.density,
             self.options,
-            self.regen,
+            &mut *self.regen,
         );
     }
 
-    /// Add a Gaussian surface for an entity.
-    pub(crate) fn add_gaussian_surface(
+    /// Add a molecular surface of `kind` for an entity.
+    pub(crate) fn add_surface(
         &mut self,
         entity_id: EntityId,
+        kind: SurfaceKind,
         color: [f32; 4],
     ) {
-        self.annotations.set_entity_surface(
-            entity_id,
-            EntitySurface {
-                kind: SurfaceKind::Gaussian,
-                color,
-                ..Default::default()
-            },
-        );
-        self.regenerate_surfaces();
+        self.set_surfaces([(entity_id, EntitySurface::new(kind, color))]);
     }
 
-    /// Add a solvent-excluded (Connolly) surface for an entity.
-    pub(crate) fn add_ses_surface(
+    /// Replace (or insert) the surfaces of several entities at once.
+    pub(crate) fn set_surfaces(
         &mut self,
-        entity_id: EntityId,
-        color: [f32; 4],
+        surfaces: impl IntoIterator<Item = (EntityId, EntitySurface)>,
     ) {
-        self.annotations.set_entity_surface(
-            entity_id,
-            EntitySurface {
-                kind: SurfaceKind::Ses,
-                color,
-                ..Default::default()
-            },
-        );
+        for (entity_id, surface) in surfaces {
+            self.annotations.set_entity_surface(entity_id, surface);
+        }
         self.regenerate_surfaces();
     }
 
@@ -429,7 +487,7 @@ impl VisoEngine {
             &mut self.scene,
             &self.density,
             &self.options,
-            &self.surface_regen,
+            &mut self.surface_regen,
         )
     }
 
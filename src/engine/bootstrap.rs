@@ -12,6 +12,7 @@ use web_time::Instant;
 
 use super::annotations::EntityAnnotations;
 use super::density_store::DensityStore;
+use super::events::EventQueue;
 use super::scene::Scene;
 use super::surface_regen::SurfaceRegen;
 use super::{ConstraintSpecs, VisoEngine};
@@ -127,6 +128,7 @@ fn init_gpu_pipeline(
         &layouts,
         &mut shader_composer,
         &post_process.backface_depth_view,
+        &post_process.depth_view,
     )?;
     let pick = PickingSystem::new(
         context,
@@ -181,6 +183,7 @@ impl VisoEngine {
                 last_cull_camera_eye: Vec3::ZERO,
                 shader_composer: bootstrap.shader_composer,
                 density_rx,
+                copies: crate::renderer::model_copies::ModelCopies::default(),
             },
             camera_controller: bootstrap.camera_controller,
             constraints: ConstraintSpecs {
@@ -195,6 +198,8 @@ impl VisoEngine {
             scene: Scene::new(),
             annotations: EntityAnnotations::default(),
             surface_regen: SurfaceRegen::new(density_tx),
+            symmetry: super::symmetry::SymmetryState::default(),
+            events: EventQueue::new(),
         })
     }
 }
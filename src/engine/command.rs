@@ -8,6 +8,9 @@
 use glam::{Vec2, Vec3};
 use molex::MoleculeType;
 
+use super::residue_address::{ResidueAddress, ResidueRef};
+use super::trajectory::{PlaybackMode, TrajectorySmoothing};
+
 // ── Constraint payload types ────────────────────────────────────────────
 
 /// Type of constraint band for color coding.
@@ -27,12 +30,24 @@ pub enum BandType {
 /// Structural reference to a specific atom.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AtomRef {
-    /// 0-based flat residue index.
-    pub residue: u32,
+    /// Owning residue, by flat index or author-numbering address.
+    pub residue: ResidueRef,
     /// PDB atom name ("CA", "CB", "N", etc.).
     pub atom_name: String,
 }
 
+impl AtomRef {
+    /// Reference atom `atom_name` of `residue` (a flat index or a
+    /// [`ResidueAddress`]).
+    #[must_use]
+    pub fn new(residue: impl Into<ResidueRef>, atom_name: &str) -> Self {
+        Self {
+            residue: residue.into(),
+            atom_name: atom_name.to_owned(),
+        }
+    }
+}
+
 /// One end of a band constraint.
 #[derive(Debug, Clone, PartialEq)]
 pub enum BandTarget {
@@ -174,6 +189,45 @@ pub enum VisoCommand {
     /// Toggle trajectory playback (play / pause).
     ToggleTrajectory,
 
+    /// Jump to a trajectory frame (clamped to the playback window).
+    SeekTrajectory {
+        /// Target frame index.
+        frame: usize,
+    },
+
+    /// Pause and step the trajectory by `delta` frames.
+    StepTrajectory {
+        /// Frames to move (negative steps backwards).
+        delta: i32,
+    },
+
+    /// Set the trajectory playback rate.
+    SetTrajectoryFps {
+        /// Frames per second.
+        fps: f32,
+    },
+
+    /// Set what playback does at the end of the frame window.
+    SetTrajectoryMode {
+        /// Loop, play once, or ping-pong.
+        mode: PlaybackMode,
+    },
+
+    /// Restrict trajectory playback to a frame window.
+    SetTrajectoryRange {
+        /// First frame of the window (inclusive).
+        start: usize,
+        /// Last frame of the window (inclusive); `None` = last frame.
+        end: Option<usize>,
+    },
+
+    /// Configure sub-frame interpolation and moving-average smoothing
+    /// for the loaded trajectory.
+    SetTrajectorySmoothing {
+        /// New filter settings.
+        smoothing: TrajectorySmoothing,
+    },
+
     // ── Selection ───────────────────────────────────────────────────
     /// Clear the current residue selection.
     ClearSelection,
@@ -186,6 +240,14 @@ pub enum VisoCommand {
         extend: bool,
     },
 
+    /// Select a single residue by chain and author residue number.
+    SelectResidueAt {
+        /// Residue to select.
+        address: ResidueAddress,
+        /// If true, add to / toggle in the existing selection (shift-click).
+        extend: bool,
+    },
+
     /// Select all residues in the same secondary-structure segment.
     SelectSegment {
         /// Any residue in the target segment.
@@ -202,6 +264,17 @@ pub enum VisoCommand {
         extend: bool,
     },
 
+    /// Select the residues of two entities that bury surface against
+    /// each other (see [`super::VisoEngine::analyze_interface`]).
+    SelectInterface {
+        /// Raw id of the first entity.
+        a: u32,
+        /// Raw id of the second entity.
+        b: u32,
+        /// If true, add to the existing selection.
+        extend: bool,
+    },
+
     // ── Entity management ─────────────────────────────────────────
     /// Focus a specific entity by ID and fit the camera to it.
     FocusEntity {
@@ -256,6 +329,8 @@ pub enum CommandOutcome {
     VisibilityChanged,
     /// The focus target changed.
     FocusChanged,
+    /// Trajectory playback state (frame, rate, mode, window) changed.
+    PlaybackChanged,
     /// A command requiring upstream coordination (e.g.
     /// [`VisoCommand::RemoveEntity`]) was dispatched directly to
     /// [`super::VisoEngine::execute`] instead of routing through
@@ -4,12 +4,14 @@
 //! [`super::positions::EntityPositions`].
 //!
 //! Each frame's resolution pass builds a [`ConstraintContext`] once
-//! (O(visible cartoon proteins)) and then resolves every band + the
-//! pull against it. Atom lookups inside the context are O(log n) to
-//! find the owning entity (binary search on the cartoon-residue range
-//! table) plus O(1) for the sidechain name-to-atom-index lookup
+//! (O(visible proteins)) and then resolves every band + the pull
+//! against it. Atom lookups inside the context are O(log n) to find
+//! the owning entity (binary search on the flat residue range table)
+//! plus O(1) for the sidechain name-to-atom-index lookup
 //! ([`SidechainLayout::atom_index`](crate::renderer::entity_topology::SidechainLayout::atom_index)).
 
+use std::ops::Range;
+
 use glam::{UVec2, Vec2, Vec3};
 use molex::entity::molecule::id::EntityId;
 
@@ -18,10 +20,11 @@ use super::command::{
     AtomRef, BandInfo, BandTarget, PullInfo, ResolvedBand, ResolvedPull,
 };
 use super::entity_view::EntityView;
+use super::residue_address::{self, ResidueRef};
 use super::scene::Scene;
 use super::{ConstraintSpecs, VisoEngine};
 use crate::camera::controller::CameraController;
-use crate::options::{DrawingMode, VisoOptions};
+use crate::options::VisoOptions;
 use crate::renderer::GpuPipeline;
 
 /// Pre-computed per-frame cache for constraint resolution.
@@ -33,23 +36,11 @@ use crate::renderer::GpuPipeline;
 /// residues) per frame.
 pub(super) struct ConstraintContext<'a> {
     scene: &'a Scene,
-    /// Cartoon-mode protein entities in assembly order, with their
-    /// flat residue ranges. `AtomRef.residue` is a flat index across
-    /// these entities (matching
-    /// [`VisoEngine::concatenated_cartoon_ss`]); binary-search this
-    /// table to locate the owning entity.
-    cartoon_ranges: Vec<CartoonRange>,
-}
-
-/// One cartoon-mode protein entity's flat residue range in assembly
-/// order.
-struct CartoonRange {
-    /// Flat residue index where this entity's residues start.
-    start: u32,
-    /// Flat residue index where this entity's residues end (exclusive).
-    end: u32,
-    /// Owning entity id.
-    entity: EntityId,
+    /// Flat residue ranges of every visible protein entity in assembly
+    /// order (picking indexing), which both [`ResidueRef`] variants
+    /// resolve against; binary-search this table to locate the owning
+    /// entity of a [`ResidueRef::Index`].
+    residue_ranges: Vec<(EntityId, Range<u32>)>,
 }
 
 impl<'a> ConstraintContext<'a> {
@@ -57,52 +48,54 @@ impl<'a> ConstraintContext<'a> {
         scene: &'a Scene,
         annotations: &'a EntityAnnotations,
     ) -> Self {
-        let mut cartoon_ranges = Vec::new();
-        let mut cursor: u32 = 0;
-        for (_, eid, state) in scene.visible_entities(annotations) {
-            if state.topology.is_protein()
-                && state.drawing_mode == DrawingMode::Cartoon
-            {
-                let count = state.topology.residue_atom_ranges.len() as u32;
-                cartoon_ranges.push(CartoonRange {
-                    start: cursor,
-                    end: cursor + count,
-                    entity: eid,
-                });
-                cursor += count;
-            }
-        }
         Self {
             scene,
-            cartoon_ranges,
+            residue_ranges: scene.protein_residue_ranges(annotations),
+        }
+    }
+
+    /// Owning entity, entity-local index and flat (picking) index of
+    /// `residue`; `None` when it isn't a residue of a visible protein.
+    fn locate(&self, residue: &ResidueRef) -> Option<(EntityId, u32, u32)> {
+        match residue {
+            ResidueRef::Index(index) => {
+                let at = self
+                    .residue_ranges
+                    .partition_point(|(_, range)| range.end <= *index);
+                let (entity, range) = self
+                    .residue_ranges
+                    .get(at)
+                    .filter(|(_, range)| range.contains(index))?;
+                Some((*entity, index - range.start, *index))
+            }
+            ResidueRef::Address(address) => {
+                let (entity, local) =
+                    residue_address::locate(self.scene, address)?;
+                let flat = residue_address::flat_index(
+                    &self.residue_ranges,
+                    entity,
+                    local,
+                )?;
+                Some((entity, local as u32, flat))
+            }
         }
     }
 
-    /// Resolve an [`AtomRef`] to world-space. Binary-searches the
-    /// cartoon-residue range table for the owning entity, then looks
-    /// up the atom by name (O(1) via
+    /// Resolve an [`AtomRef`] to world-space. Locates the owning
+    /// entity, then looks up the atom by name (O(1) via
     /// [`SidechainLayout::atom_index`](crate::renderer::entity_topology::SidechainLayout::atom_index)
     /// for sidechain atoms, O(1) range-indexed for backbone N/CA/C).
     fn resolve_atom_ref(&self, atom: &AtomRef) -> Option<Vec3> {
-        let range = self
-            .cartoon_ranges
-            .binary_search_by(|r| {
-                use std::cmp::Ordering;
-                if atom.residue < r.start {
-                    Ordering::Greater
-                } else if atom.residue >= r.end {
-                    Ordering::Less
-                } else {
-                    Ordering::Equal
-                }
-            })
-            .ok()
-            .map(|i| &self.cartoon_ranges[i])?;
-        let state = self.scene.entity_state.get(&range.entity)?;
-        let positions = self.scene.positions.get(range.entity)?;
-        let local_residue = atom.residue - range.start;
+        let (entity, local_residue, _) = self.locate(&atom.residue)?;
+        let state = self.scene.entity_state.get(&entity)?;
+        let positions = self.scene.positions.get(entity)?;
         resolve_atom_in_entity(state, positions, local_residue, &atom.atom_name)
     }
+
+    /// Flat (picking) residue index of `residue`.
+    fn flat_residue(&self, residue: &ResidueRef) -> Option<u32> {
+        self.locate(residue).map(|(_, _, flat)| flat)
+    }
 }
 
 /// Resolve a single band spec to world-space endpoint positions.
@@ -123,7 +116,7 @@ fn resolve_band(
         is_disabled: band.is_disabled,
         strength: band.strength,
         target_length: band.target_length,
-        residue_idx: band.anchor_a.residue,
+        residue_idx: ctx.flat_residue(&band.anchor_a.residue)?,
         is_space_pull,
         band_type: band.band_type,
         from_script: band.from_script,
@@ -147,7 +140,7 @@ fn resolve_pull(
     Some(ResolvedPull {
         atom_pos,
         target_pos,
-        residue_idx: pull.atom.residue,
+        residue_idx: ctx.flat_residue(&pull.atom.residue)?,
     })
 }
 
@@ -166,7 +159,7 @@ pub(super) fn resolve_atom_ref_pub(
 }
 
 /// Full breakdown of which atom a screen-space pick resolves to inside
-/// a cartoon-mode residue.
+/// a picked protein residue.
 ///
 /// Used by drag dispatchers that need the owning entity id and the
 /// entity-local residue index in addition to the atom name (e.g.
@@ -193,12 +186,8 @@ pub(super) fn picked_residue_atom(
     screen_pos: Vec2,
 ) -> Option<PickedResidueAtom> {
     let ctx = ConstraintContext::new(scene, annotations);
-    let range = ctx
-        .cartoon_ranges
-        .iter()
-        .find(|r| residue >= r.start && residue < r.end)?;
-    let entity_id = range.entity.raw();
-    let local_residue = residue - range.start;
+    let (entity, local_residue, _) = ctx.locate(&ResidueRef::Index(residue))?;
+    let entity_id = entity.raw();
     let atom_name = closest_atom_in_residue(
         scene,
         annotations,
@@ -227,13 +216,9 @@ pub(super) fn closest_atom_in_residue(
     screen_pos: Vec2,
 ) -> Option<String> {
     let ctx = ConstraintContext::new(scene, annotations);
-    let range = ctx
-        .cartoon_ranges
-        .iter()
-        .find(|r| residue >= r.start && residue < r.end)?;
-    let state = scene.entity_state.get(&range.entity)?;
-    let positions = scene.positions.get(range.entity)?;
-    let local_residue = residue - range.start;
+    let (entity, local_residue, _) = ctx.locate(&ResidueRef::Index(residue))?;
+    let state = scene.entity_state.get(&entity)?;
+    let positions = scene.positions.get(entity)?;
 
     let mut best: Option<(f32, String)> = None;
     let mut consider = |name: &str, pos: Vec3| {
@@ -5,6 +5,9 @@
 //! frame against the current camera. They live here rather than in
 //! [`super::sync`] because they're not Assembly-sync logic; they're
 //! rendering decisions driven by camera position + animator state.
+//! Chain-level frustum and occlusion culling of the backbone and
+//! impostor passes happens on the render side, in
+//! [`crate::renderer::culling`].
 
 use glam::Vec3;
 
@@ -34,7 +37,10 @@ impl super::VisoEngine {
 
         self.gpu
             .set_last_cull_camera_eye(self.camera_controller.camera.eye);
-        let frustum = self.camera_controller.frustum();
+        // Scene copies put sidechains outside the camera frustum on
+        // screen, so culling is skipped while they are drawn.
+        let frustum = (!self.gpu.copies.is_active())
+            .then(|| self.camera_controller.frustum());
 
         let flat = self.flat_sidechain_state();
         let sheet_offsets = self.gpu.backbone_sheet_offsets();
@@ -53,7 +59,7 @@ impl super::VisoEngine {
         let sc_colors = if self.options.display.sidechain_color_mode()
             == crate::options::SidechainColorMode::Backbone
         {
-            let flat = self.scene.flat_cartoon_colors(&self.annotations);
+            let flat = self.scene.flat_protein_colors(&self.annotations);
             if flat.is_empty() {
                 None
             } else {
@@ -64,7 +70,7 @@ impl super::VisoEngine {
         };
         self.gpu.upload_frustum_culled_sidechains(
             &adjusted.as_view(),
-            &frustum,
+            frustum.as_ref(),
             sc_colors.as_deref(),
         );
     }
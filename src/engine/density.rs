@@ -2,37 +2,43 @@
 
 use std::sync::Arc;
 
+use glam::Vec3;
 use molex::entity::surface::Density;
+use web_time::Instant;
 
 use super::annotations::EntityAnnotations;
-use super::density_store::DensityStore;
+use super::density_store::{DensityStore, DensityStyle, DensityZone};
 use super::scene::Scene;
 use super::surface_regen::{regenerate_surfaces, SurfaceRegen};
 use super::VisoEngine;
 use crate::camera::fit::combined_bounding_sphere;
 use crate::options::VisoOptions;
+use crate::renderer::geometry::isosurface::density::ContourLevel;
+use crate::renderer::geometry::volume::TransferFunction;
 
 /// Disjoint-borrow write view over the density store plus the scene
 /// fields a regeneration needs to read.
 ///
 /// Density mutations always trigger a surface/density mesh regeneration,
-/// which reads the [`Scene`], [`EntityAnnotations`], [`VisoOptions`],
-/// and the [`SurfaceRegen`] sender. Bundling those four shared borrows
-/// alongside `&mut DensityStore` lets every mutator be expressed without
-/// `&mut self` methods on `VisoEngine` fighting over the whole engine.
+/// which reads the [`Scene`], [`EntityAnnotations`] and [`VisoOptions`]
+/// and updates the [`SurfaceRegen`] dispatch state. Bundling those
+/// borrows alongside `&mut DensityStore` lets every mutator be expressed
+/// without `&mut self` methods on `VisoEngine` fighting over the whole engine.
 /// [`VisoEngine::density_mut`] is the constructor.
 pub(crate) struct DensityScene<'a> {
     store: &'a mut DensityStore,
     scene: &'a Scene,
     annotations: &'a EntityAnnotations,
     options: &'a VisoOptions,
-    regen: &'a SurfaceRegen,
+    regen: &'a mut SurfaceRegen,
+    /// Current camera focus, seeding the center of zoned maps.
+    focus: Vec3,
 }
 
 impl DensityScene<'_> {
-    /// Trigger a regeneration of all isosurface meshes (density maps,
-    /// entity surfaces, cavities) on the background thread.
-    fn regenerate(&self) {
+    /// Regenerate the isosurface meshes (density maps, entity surfaces,
+    /// cavities) whose inputs changed, on the background thread.
+    fn regenerate(&mut self) {
         regenerate_surfaces(
             self.scene,
             self.annotations,
@@ -50,6 +56,7 @@ impl DensityScene<'_> {
     pub(crate) fn load(&mut self, map: Density) -> u32 {
         let id = self.store.add(map);
         log::info!("loaded density map id={id}");
+        self.store.seed_focus(self.focus);
         let visible = self
             .scene
             .current
@@ -109,6 +116,51 @@ impl DensityScene<'_> {
         self.store.set_opacity(id, opacity);
         self.regenerate();
     }
+
+    /// Set the contour drawing style (surface or chicken-wire).
+    pub(crate) fn set_style(&mut self, id: u32, style: DensityStyle) {
+        self.store.set_style(id, style);
+        self.regenerate();
+    }
+
+    /// Toggle difference-map contouring (±threshold, green / red).
+    pub(crate) fn set_difference(&mut self, id: u32, difference: bool) {
+        self.store.set_difference(id, difference);
+        self.regenerate();
+    }
+
+    /// Replace the contour levels drawn besides the primary one.
+    pub(crate) fn set_extra_levels(
+        &mut self,
+        id: u32,
+        levels: Vec<ContourLevel>,
+    ) {
+        self.store.set_extra_levels(id, levels);
+        self.regenerate();
+    }
+
+    /// Replace the palette and opacity curve of the volume style.
+    pub(crate) fn set_transfer(&mut self, id: u32, transfer: TransferFunction) {
+        self.store.set_transfer(id, transfer);
+        self.regenerate();
+    }
+
+    /// Mark a map (or none) as the source of electrostatic surface
+    /// coloring; surfaces fall back to the Coulomb approximation
+    /// without one.
+    pub(crate) fn set_potential(&mut self, id: Option<u32>) {
+        log::info!("set_potential_map id={id:?}");
+        self.store.set_potential(id);
+        self.regenerate();
+    }
+
+    /// Set (or clear, with `None`) the density zone of a density map.
+    pub(crate) fn set_zone(&mut self, id: u32, zone: Option<DensityZone>) {
+        log::info!("set_density_zone id={id} zone={zone:?}");
+        self.store.seed_focus(self.focus);
+        self.store.set_zone(id, zone);
+        self.regenerate();
+    }
 }
 
 impl VisoEngine {
@@ -120,7 +172,70 @@ impl VisoEngine {
             scene: &self.scene,
             annotations: &self.annotations,
             options: &self.options,
-            regen: &self.surface_regen,
+            regen: &mut self.surface_regen,
+            focus: self.camera_controller.camera.target,
+        }
+    }
+
+    /// Move density zones with the camera focus (debounced) and the
+    /// selection, re-contouring the maps whose zone moved.
+    pub(super) fn update_density_zones(&mut self) {
+        let mut moved = self
+            .density
+            .follow_focus(self.camera_controller.camera.target, Instant::now());
+
+        let selected = self.gpu.pick.selected_residues();
+        if self.density.has_selection_zone()
+            && selected != self.density.selection_residues()
+        {
+            let sphere = selection_sphere(self, selected);
+            self.density.set_selection(selected, sphere);
+            moved = true;
         }
+
+        if moved {
+            self.density_mut().regenerate();
+        }
+    }
+}
+
+/// Bounding sphere `(center, radius)` of the atoms of the selected
+/// flat residue indices, or `None` for an empty selection.
+fn selection_sphere(
+    engine: &VisoEngine,
+    selected: &[i32],
+) -> Option<(Vec3, f32)> {
+    let ranges = engine.scene.protein_residue_ranges(&engine.annotations);
+    let mut atoms: Vec<Vec3> = Vec::new();
+    for index in selected.iter().filter_map(|&i| u32::try_from(i).ok()) {
+        let Some((eid, range)) =
+            ranges.iter().find(|(_, r)| r.contains(&index))
+        else {
+            continue;
+        };
+        let (Some(state), Some(positions)) = (
+            engine.scene.entity_state.get(eid),
+            engine.scene.positions.get(*eid),
+        ) else {
+            continue;
+        };
+        let local = (index - range.start) as usize;
+        if let Some(atom_range) = state.topology.residue_atom_ranges.get(local)
+        {
+            atoms.extend(
+                positions
+                    .get(atom_range.start as usize..atom_range.end as usize)
+                    .unwrap_or_default(),
+            );
+        }
+    }
+    if atoms.is_empty() {
+        return None;
     }
+    let center = atoms.iter().copied().sum::<Vec3>() / atoms.len() as f32;
+    let radius = atoms
+        .iter()
+        .map(|p| p.distance(center))
+        .fold(0.0_f32, f32::max);
+    Some((center, radius))
 }
@@ -0,0 +1,257 @@
+//! O bricked map readers: DSN6 and BRIX.
+//!
+//! Both store one byte per grid point in 8×8×8 bricks of 512 bytes
+//! after a 512-byte header, bricks and the points inside them x
+//! fastest, and scale each byte back to density as
+//! `(byte - plus) / prod`. The header gives the grid start and extent
+//! in cell sampling units and the cell, so the map is placed exactly
+//! like a CCP4 map with x, y, z axis order.
+//!
+//! DSN6 has a binary header of 16-bit words (big-endian as written by
+//! O; little-endian files are recognised by the word-18 constant 100
+//! reading back wrong). Big-endian DSN6 bricks also have their bytes
+//! swapped in pairs. BRIX has a text header starting with `:-)`.
+
+use molex::entity::surface::{Density, VoxelGrid};
+
+use super::{to_array, with_statistics};
+
+/// Header and brick size in bytes.
+const RECORD: usize = 512;
+
+/// Points per brick edge.
+const BRICK: usize = 8;
+
+/// DSN6 header word that always holds 100.
+const DSN6_CONSTANT_WORD: usize = 18;
+
+/// Decoded header fields shared by both formats.
+struct BrickHeader {
+    start: [i32; 3],
+    extent: [usize; 3],
+    sampling: [usize; 3],
+    cell_dims: [f32; 3],
+    cell_angles: [f32; 3],
+    prod: f32,
+    plus: f32,
+    /// Swap brick bytes in pairs (big-endian DSN6).
+    swap_pairs: bool,
+}
+
+/// Parse a DSN6 or BRIX map, told apart by the BRIX `:-)` signature.
+pub(super) fn parse(bytes: &[u8]) -> Result<Density, String> {
+    let header = if bytes.starts_with(b":-)") {
+        brix_header(bytes)?
+    } else {
+        dsn6_header(bytes)?
+    };
+    if header.extent.contains(&0) || header.sampling.contains(&0) {
+        return Err("bricked map has an empty grid".to_owned());
+    }
+    if header.prod == 0.0 {
+        return Err("bricked map has a zero scale factor".to_owned());
+    }
+    let values = unpack(bytes, &header)?;
+    let [nx, ny, nz] = header.extent;
+    Ok(with_statistics(VoxelGrid {
+        nx,
+        ny,
+        nz,
+        nxstart: header.start[0],
+        nystart: header.start[1],
+        nzstart: header.start[2],
+        mx: header.sampling[0],
+        my: header.sampling[1],
+        mz: header.sampling[2],
+        cell_dims: header.cell_dims,
+        cell_angles: header.cell_angles,
+        origin: [0.0; 3],
+        data: to_array(header.extent, values)?,
+    }))
+}
+
+fn dsn6_header(bytes: &[u8]) -> Result<BrickHeader, String> {
+    let raw = bytes
+        .get(..2 * (DSN6_CONSTANT_WORD + 1))
+        .ok_or("DSN6 header truncated")?;
+    let word = |i: usize, big: bool| {
+        let pair = [raw[2 * i], raw[2 * i + 1]];
+        if big {
+            i16::from_be_bytes(pair)
+        } else {
+            i16::from_le_bytes(pair)
+        }
+    };
+    let big = if word(DSN6_CONSTANT_WORD, true) == 100 {
+        true
+    } else if word(DSN6_CONSTANT_WORD, false) == 100 {
+        false
+    } else {
+        return Err("not a DSN6 map (bad header constant)".to_owned());
+    };
+    let h = |i: usize| word(i, big);
+    let cell_scale = f32::from(h(17)).max(1.0);
+    let count = |i: usize| usize::try_from(h(i)).unwrap_or(0);
+    Ok(BrickHeader {
+        start: [h(0), h(1), h(2)].map(i32::from),
+        extent: [count(3), count(4), count(5)],
+        sampling: [count(6), count(7), count(8)],
+        cell_dims: [h(9), h(10), h(11)].map(|v| f32::from(v) / cell_scale),
+        cell_angles: [h(12), h(13), h(14)].map(|v| f32::from(v) / cell_scale),
+        prod: f32::from(h(15)) / f32::from(h(DSN6_CONSTANT_WORD)),
+        plus: f32::from(h(16)),
+        swap_pairs: big,
+    })
+}
+
+fn brix_header(bytes: &[u8]) -> Result<BrickHeader, String> {
+    let text = String::from_utf8_lossy(&bytes[..bytes.len().min(RECORD)])
+        .to_ascii_lowercase();
+    let fields: Vec<&str> = text.split_whitespace().collect();
+    let numbers = |key: &str, n: usize| -> Result<Vec<f32>, String> {
+        let at = fields
+            .iter()
+            .position(|&f| f == key)
+            .ok_or_else(|| format!("BRIX header has no {key:?}"))?;
+        (1..=n)
+            .map(|i| {
+                fields
+                    .get(at + i)
+                    .and_then(|f| f.parse::<f32>().ok())
+                    .ok_or_else(|| format!("malformed BRIX {key:?}"))
+            })
+            .collect()
+    };
+    let ints = |key: &str| -> Result<[f32; 3], String> {
+        let v = numbers(key, 3)?;
+        Ok([v[0], v[1], v[2]])
+    };
+    let cell = numbers("cell", 6)?;
+    Ok(BrickHeader {
+        start: ints("origin")?.map(|v| v as i32),
+        extent: ints("extent")?.map(|v| v.max(0.0) as usize),
+        sampling: ints("grid")?.map(|v| v.max(0.0) as usize),
+        cell_dims: [cell[0], cell[1], cell[2]],
+        cell_angles: [cell[3], cell[4], cell[5]],
+        prod: numbers("prod", 1)?[0],
+        plus: numbers("plus", 1)?[0],
+        swap_pairs: false,
+    })
+}
+
+/// Unpack the bricks into `[x, y, z]` values, z fastest.
+fn unpack(bytes: &[u8], header: &BrickHeader) -> Result<Vec<f32>, String> {
+    let [nx, ny, nz] = header.extent;
+    let bricks = header.extent.map(|n| n.div_ceil(BRICK));
+    let too_large = || "bricked map too large".to_owned();
+    let brick_count = bricks[0]
+        .checked_mul(bricks[1])
+        .and_then(|n| n.checked_mul(bricks[2]))
+        .ok_or_else(too_large)?;
+    let needed = brick_count
+        .checked_add(1)
+        .and_then(|n| n.checked_mul(RECORD))
+        .ok_or_else(too_large)?;
+    if bytes.len() < needed {
+        return Err(format!(
+            "bricked map truncated: {} bytes, expected {needed}",
+            bytes.len()
+        ));
+    }
+    let voxels = nx
+        .checked_mul(ny)
+        .and_then(|n| n.checked_mul(nz))
+        .ok_or_else(too_large)?;
+    let mut values = vec![0.0; voxels];
+    for b in 0..brick_count {
+        let corner = [
+            b % bricks[0] * BRICK,
+            b / bricks[0] % bricks[1] * BRICK,
+            b / (bricks[0] * bricks[1]) * BRICK,
+        ];
+        let brick = &bytes[(b + 1) * RECORD..][..RECORD];
+        for (i, &byte) in brick.iter().enumerate() {
+            let local = if header.swap_pairs { i ^ 1 } else { i };
+            let x = corner[0] + local % BRICK;
+            let y = corner[1] + local / BRICK % BRICK;
+            let z = corner[2] + local / (BRICK * BRICK);
+            if x < nx && y < ny && z < nz {
+                values[(x * ny + y) * nz + z] =
+                    (f32::from(byte) - header.plus) / header.prod;
+            }
+        }
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    /// A one-brick map whose byte at point `(x, y, z)` is `x + 10 y + 20 z`
+    /// (within a 2×2×3 extent), stored with `swap` pair order.
+    fn brick(swap: bool) -> Vec<u8> {
+        let mut data = vec![0u8; RECORD];
+        for z in 0..3 {
+            for y in 0..2 {
+                for x in 0..2 {
+                    let i = z * 64 + y * 8 + x;
+                    data[i ^ usize::from(swap)] = (x + 10 * y + 20 * z) as u8;
+                }
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn reads_brix() {
+        let mut header = String::from(
+            ":-) origin 4 5 6 extent 2 2 3 grid 40 40 40 cell 20.0 20.0 20.0 \
+             90.0 90.0 90.0 prod 2.0 plus 10 sigma 1.0",
+        )
+        .into_bytes();
+        header.resize(RECORD, b' ');
+        header.extend(brick(false));
+        let map = parse(&header).unwrap();
+        assert_eq!([map.nx, map.ny, map.nz], [2, 2, 3]);
+        assert_eq!([map.nxstart, map.nystart, map.nzstart], [4, 5, 6]);
+        // Byte 1 + 10 + 40 = 51 → (51 - 10) / 2.
+        assert!((map.data[[1, 1, 2]] - 20.5).abs() < 1e-6);
+        // Grid start 4 of 40 samples on a 20 Å cell sits at 2 Å.
+        assert!((map.grid_to_cartesian(0, 0, 0)[0] - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn reads_big_endian_dsn6_with_swapped_pairs() {
+        let words: [i16; 19] = [
+            0, 0, 0, 2, 2, 3, 10, 10, 10, 800, 800, 800, 7200, 7200, 7200, 100,
+            0, 80, 100,
+        ];
+        let mut bytes: Vec<u8> =
+            words.iter().flat_map(|w| w.to_be_bytes()).collect();
+        bytes.resize(RECORD, 0);
+        bytes.extend(brick(true));
+        let map = parse(&bytes).unwrap();
+        assert_eq!(map.cell_dims, [10.0; 3]);
+        assert_eq!(map.cell_angles, [90.0; 3]);
+        assert!((map.data[[1, 0, 2]] - 41.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rejects_overflowing_extents() {
+        let mut header = String::from(
+            ":-) origin 0 0 0 extent 1e30 1e30 1e30 grid 40 40 40 cell 20.0 \
+             20.0 20.0 90.0 90.0 90.0 prod 2.0 plus 10 sigma 1.0",
+        )
+        .into_bytes();
+        header.resize(RECORD, b' ');
+        header.extend(brick(false));
+        assert_eq!(parse(&header).unwrap_err(), "bricked map too large");
+    }
+
+    #[test]
+    fn rejects_unknown_headers() {
+        assert!(parse(&[0u8; 1024]).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! Gaussian cube reader.
+//!
+//! Two comment lines; the atom count and grid origin; one line per
+//! axis with its point count and step vector; one line per atom; then
+//! the values with z fastest. A positive point count means Bohr units,
+//! a negative one Ångström. A negative atom count is followed by a
+//! list of orbital indices, and files with several values per point
+//! (orbitals, or the optional value count after the origin) are read
+//! at their first value.
+
+use glam::Vec3;
+use molex::entity::surface::Density;
+
+use super::grid_from_axes;
+
+/// Bohr radius in Ångström.
+const BOHR_TO_ANGSTROM: f32 = 0.529_177_2;
+
+/// Parse a cube file.
+pub(super) fn parse(text: &str) -> Result<Density, String> {
+    let mut lines = text.lines().skip(2);
+    let mut header = |what: &str| {
+        lines
+            .next()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .ok_or_else(|| format!("cube file truncated before {what}"))
+    };
+
+    let counts = header("the origin")?;
+    let natoms: i64 = number(counts.first(), "atom count")?;
+    let origin = vector(&counts, 1, "origin")?;
+    let mut per_point: usize = match counts.get(4) {
+        Some(n) => number(Some(n), "value count")?,
+        None => 1,
+    };
+
+    let mut dims = [0; 3];
+    let mut axes = [Vec3::ZERO; 3];
+    let mut bohr = true;
+    for axis in 0..3 {
+        let fields = header("the grid axes")?;
+        let n: i64 = number(fields.first(), "axis point count")?;
+        bohr = n > 0;
+        dims[axis] = n.unsigned_abs() as usize;
+        axes[axis] = vector(&fields, 1, "axis step")?;
+    }
+    let scale = if bohr { BOHR_TO_ANGSTROM } else { 1.0 };
+
+    for _ in 0..natoms.unsigned_abs() {
+        let _atom = header("the atoms")?;
+    }
+
+    let mut tokens = lines.flat_map(str::split_whitespace);
+    if natoms < 0 {
+        let orbitals: usize = number(tokens.next().as_ref(), "orbital count")?;
+        for _ in 0..orbitals {
+            let _index = tokens.next();
+        }
+        per_point = orbitals;
+    }
+    let per_point = per_point.max(1);
+
+    let values = tokens
+        .step_by(per_point)
+        .map(|t| {
+            t.parse::<f32>()
+                .map_err(|_| format!("malformed cube value {t:?}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    grid_from_axes(origin * scale, axes.map(|a| a * scale), dims, values)
+}
+
+/// Parse `field` as a number, naming `what` in the error.
+fn number<T: std::str::FromStr>(
+    field: Option<&&str>,
+    what: &str,
+) -> Result<T, String> {
+    field
+        .and_then(|f| f.parse().ok())
+        .ok_or_else(|| format!("malformed cube {what}"))
+}
+
+/// Three numbers starting at `fields[at]`.
+fn vector(fields: &[&str], at: usize, what: &str) -> Result<Vec3, String> {
+    Ok(Vec3::new(
+        number(fields.get(at), what)?,
+        number(fields.get(at + 1), what)?,
+        number(fields.get(at + 2), what)?,
+    ))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::super::tests::assert_close;
+    use super::*;
+
+    #[test]
+    fn reads_bohr_grids_in_angstrom() {
+        let text = "comment\ncomment\n1 0.0 0.0 0.0\n2 1.0 0.0 0.0\n2 0.0 1.0 \
+                    0.0\n2 0.0 0.0 1.0\n8 8.0 0.0 0.0 0.0\n0.0 1.0 2.0 3.0 \
+                    4.0 5.0\n6.0 7.0\n";
+        let map = parse(text).unwrap();
+        assert_eq!([map.nx, map.ny, map.nz], [2, 2, 2]);
+        let far = map.grid_to_cartesian(1, 1, 1);
+        assert!((far[0] - BOHR_TO_ANGSTROM).abs() < 1e-5);
+        assert!((map.data[[1, 0, 1]] - 5.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn orbital_cubes_keep_the_first_value_per_point() {
+        let text = "c\nc\n-1 0.0 0.0 0.0\n-1 1.0 0.0 0.0\n-1 0.0 1.0 0.0\n-2 \
+                    0.0 0.0 1.0\n1 1.0 0.0 0.0 0.0\n2 10 11\n0.5 -0.5 0.25 \
+                    -0.25\n";
+        let map = parse(text).unwrap();
+        // Ångström units: the step is used as is.
+        assert_close(map.grid_to_cartesian(0, 0, 1), [0.0, 0.0, 1.0]);
+        assert!((map.data[[0, 0, 1]] - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rejects_short_value_lists() {
+        let text = "c\nc\n0 0 0 0\n2 1 0 0\n1 0 1 0\n1 0 0 1\n1.0\n";
+        assert!(parse(text).unwrap_err().contains("expected 2"));
+    }
+}
@@ -0,0 +1,106 @@
+//! OpenDX scalar-field reader, as written by APBS.
+//!
+//! The header declares a `gridpositions` object with the point counts,
+//! an `origin` and one `delta` step vector per axis (Ångström),
+//! followed by an `array` object whose values follow with z fastest
+//! until the trailing `attribute` / `object` lines.
+
+use glam::Vec3;
+use molex::entity::surface::Density;
+
+use super::grid_from_axes;
+
+/// Parse a DX file.
+pub(super) fn parse(text: &str) -> Result<Density, String> {
+    let mut dims = None;
+    let mut origin = None;
+    let mut axes = Vec::with_capacity(3);
+    let mut values = Vec::new();
+    let mut in_data = false;
+
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let malformed = || format!("line {}: malformed {line:?}", line_no + 1);
+        if in_data {
+            if fields[0] == "attribute" || fields[0] == "object" {
+                in_data = false;
+            } else {
+                for f in fields {
+                    values.push(f.parse::<f32>().map_err(|_| malformed())?);
+                }
+                continue;
+            }
+        }
+        match fields[0] {
+            "object" if line.contains("gridpositions") => {
+                let at = fields
+                    .iter()
+                    .position(|&f| f == "counts")
+                    .ok_or_else(malformed)?;
+                let count = |i: usize| {
+                    fields.get(at + i).and_then(|f| f.parse::<usize>().ok())
+                };
+                dims = Some([
+                    count(1).ok_or_else(malformed)?,
+                    count(2).ok_or_else(malformed)?,
+                    count(3).ok_or_else(malformed)?,
+                ]);
+            }
+            "object" if line.contains("class array") => {
+                in_data = true;
+            }
+            "origin" => origin = Some(vector(&fields).ok_or_else(malformed)?),
+            "delta" => axes.push(vector(&fields).ok_or_else(malformed)?),
+            _ => {}
+        }
+    }
+
+    let dims = dims.ok_or("DX file has no gridpositions object")?;
+    let origin = origin.ok_or("DX file has no origin")?;
+    let axes: [Vec3; 3] = axes
+        .try_into()
+        .map_err(|_| "DX file needs three delta lines".to_owned())?;
+    grid_from_axes(origin, axes, dims, values)
+}
+
+/// The three numbers after the keyword.
+fn vector(fields: &[&str]) -> Option<Vec3> {
+    let next = |i: usize| fields.get(i)?.parse::<f32>().ok();
+    Some(Vec3::new(next(1)?, next(2)?, next(3)?))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::super::tests::assert_close;
+    use super::*;
+
+    const APBS: &str =
+        "# Data from APBS\nobject 1 class gridpositions counts 2 2 3\norigin \
+         -1.0 -2.0 -3.0\ndelta 0.5 0.0 0.0\ndelta 0.0 0.5 0.0\ndelta 0.0 0.0 \
+         0.5\nobject 2 class gridconnections counts 2 2 3\nobject 3 class \
+         array type double rank 0 items 12 data follows\n1.0 2.0 3.0\n4.0 5.0 \
+         6.0\n7.0 8.0 9.0\n10.0 11.0 12.0\nattribute \"dep\" string \
+         \"positions\"\nobject \"regular positions regular connections\" \
+         class field\ncomponent \"positions\" value 1\n";
+
+    #[test]
+    fn reads_apbs_potentials() {
+        let map = parse(APBS).unwrap();
+        assert_eq!([map.nx, map.ny, map.nz], [2, 2, 3]);
+        assert_close(map.grid_to_cartesian(0, 0, 0), [-1.0, -2.0, -3.0]);
+        assert_close(map.grid_to_cartesian(1, 1, 2), [-0.5, -1.5, -2.0]);
+        // z fastest: (1, 0, 2) is the 1*6 + 0*3 + 2 = 8th value.
+        assert!((map.data[[1, 0, 2]] - 9.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn missing_deltas_are_an_error() {
+        let text = APBS.replace("delta 0.0 0.0 0.5\n", "");
+        assert!(parse(&text).unwrap_err().contains("delta"));
+    }
+}
@@ -0,0 +1,294 @@
+//! Density map readers.
+//!
+//! Every supported format decodes to a molex [`Density`]: a voxel grid
+//! indexed `[x, y, z]` with its origin, cell and sampling set so that
+//! `grid_to_cartesian` lands on the file's grid points, plus the value
+//! statistics used for sigma thresholds. Nothing downstream knows which
+//! format a map came from.
+//!
+//! | Extension                       | Format                            |
+//! |---------------------------------|-----------------------------------|
+//! | `mrc`, `map`, `ccp4`            | MRC / CCP4 (via molex)            |
+//! | `mrc.gz`, `map.gz`, `ccp4.gz`   | gzip-compressed MRC / CCP4        |
+//! | `cube`, `cub`                   | Gaussian cube                     |
+//! | `dx`                            | OpenDX scalar field (APBS)        |
+//! | `dsn6`, `omap`, `brix`          | O DSN6 / BRIX bricked maps        |
+
+mod brick;
+mod cube;
+mod dx;
+
+use std::io::Read;
+use std::path::Path;
+
+use glam::Vec3;
+use molex::entity::surface::{Density, VoxelGrid};
+use ndarray::Array3;
+
+/// MRC / CCP4 extensions, also accepted with a `.gz` suffix.
+const MRC_EXTENSIONS: [&str; 3] = ["mrc", "map", "ccp4"];
+
+/// Offset of the `MAP ` machine stamp in an MRC / CCP4 header.
+const MRC_STAMP_OFFSET: usize = 208;
+
+/// Largest decompressed size accepted from a gzip-compressed map
+/// (2 GiB), so a small crafted file can't exhaust memory.
+const MAX_GUNZIP_BYTES: u64 = 2 << 30;
+
+/// Lower-cased extension of `path`, keeping the inner extension of
+/// gzip-compressed files (`"map.gz"`).
+pub(crate) fn file_extension(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let mut parts = name.rsplit('.');
+    let last = parts.next().unwrap_or_default();
+    match parts.next() {
+        Some(inner) if last == "gz" && name.contains('.') => {
+            format!("{inner}.gz")
+        }
+        _ if name.contains('.') => last.to_owned(),
+        _ => String::new(),
+    }
+}
+
+/// Whether `ext` (see [`file_extension`]; a leading dot is ignored)
+/// names a density format.
+pub(crate) fn is_density_format(ext: &str) -> bool {
+    let ext = ext.trim_start_matches('.').to_ascii_lowercase();
+    let base = ext.strip_suffix(".gz").unwrap_or(&ext);
+    MRC_EXTENSIONS.contains(&base)
+        || (base == ext
+            && matches!(base, "cube" | "cub" | "dx" | "dsn6" | "omap" | "brix"))
+}
+
+/// Read a density map file, choosing the decoder by extension.
+///
+/// # Errors
+///
+/// Returns a message if the extension is unsupported, the file cannot
+/// be read, or decoding fails.
+pub(crate) fn read_density(path: &Path) -> Result<Density, String> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    parse_density(&bytes, &file_extension(path))
+}
+
+/// Decode an in-memory density map. `ext` is the file extension as
+/// from [`file_extension`]; a bare `"gz"` is taken as compressed
+/// MRC / CCP4.
+///
+/// # Errors
+///
+/// Returns a message if the extension is unsupported or decoding fails.
+pub(crate) fn parse_density(
+    bytes: &[u8],
+    ext: &str,
+) -> Result<Density, String> {
+    let ext = ext.trim_start_matches('.').to_ascii_lowercase();
+    match ext.as_str() {
+        "mrc" | "map" | "ccp4" => read_mrc(bytes),
+        "gz" => read_mrc(&gunzip(bytes)?),
+        "cube" | "cub" => cube::parse(&String::from_utf8_lossy(bytes)),
+        "dx" => dx::parse(&String::from_utf8_lossy(bytes)),
+        "dsn6" | "omap" | "brix" => brick::parse(bytes),
+        other => match other.strip_suffix(".gz") {
+            Some(inner) if MRC_EXTENSIONS.contains(&inner) => {
+                read_mrc(&gunzip(bytes)?)
+            }
+            _ => Err(format!("unsupported density format {other:?}")),
+        },
+    }
+}
+
+fn read_mrc(bytes: &[u8]) -> Result<Density, String> {
+    if bytes.get(MRC_STAMP_OFFSET..MRC_STAMP_OFFSET + 4) != Some(b"MAP ") {
+        log::warn!("density map has no MRC 'MAP ' stamp; reading anyway");
+    }
+    molex::adapters::mrc::mrc_to_density(bytes).map_err(|e| e.to_string())
+}
+
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    gunzip_capped(bytes, MAX_GUNZIP_BYTES)
+}
+
+/// Decompress `bytes`, failing once the output passes `limit` bytes.
+fn gunzip_capped(bytes: &[u8], limit: u64) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let _ = flate2::read::GzDecoder::new(bytes)
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut out)
+        .map_err(|e| format!("gzip decode failed: {e}"))?;
+    if out.len() as u64 > limit {
+        return Err("decompressed map too large".to_owned());
+    }
+    Ok(out)
+}
+
+/// Build a map from a grid given by its first point and per-step axis
+/// vectors (Ångström), with `values` in `[x, y, z]` C order (z
+/// fastest).
+///
+/// The grid is stored as a unit cell spanning it: cell edge `i` is
+/// `dims[i]` steps of `axes[i]`, sampled `dims[i]` times. molex places
+/// a cell with `a` along x and `b` in the xy plane, so axes in any
+/// other orientation come out rotated about the origin; that is
+/// logged, since formats written by simulation codes are axis-aligned
+/// in practice.
+fn grid_from_axes(
+    origin: Vec3,
+    axes: [Vec3; 3],
+    dims: [usize; 3],
+    values: Vec<f32>,
+) -> Result<Density, String> {
+    if dims.contains(&0) {
+        return Err(format!("empty grid {dims:?}"));
+    }
+    let lengths = axes.map(Vec3::length);
+    if lengths.iter().any(|&l| l <= f32::EPSILON) {
+        return Err("grid has a zero-length axis".to_owned());
+    }
+    let angle = |u: Vec3, v: Vec3| u.angle_between(v).to_degrees();
+    let grid = VoxelGrid {
+        nx: dims[0],
+        ny: dims[1],
+        nz: dims[2],
+        nxstart: 0,
+        nystart: 0,
+        nzstart: 0,
+        mx: dims[0],
+        my: dims[1],
+        mz: dims[2],
+        cell_dims: [
+            lengths[0] * dims[0] as f32,
+            lengths[1] * dims[1] as f32,
+            lengths[2] * dims[2] as f32,
+        ],
+        cell_angles: [
+            angle(axes[1], axes[2]),
+            angle(axes[0], axes[2]),
+            angle(axes[0], axes[1]),
+        ],
+        origin: origin.to_array(),
+        data: to_array(dims, values)?,
+    };
+    let placed = Vec3::from(grid.grid_to_cartesian_f32(1.0, 1.0, 1.0)) - origin;
+    if placed.distance(axes[0] + axes[1] + axes[2]) > 1e-3 * lengths[0] {
+        log::warn!(
+            "density grid axes are not in standard cell orientation; the map \
+             is drawn rotated about its origin"
+        );
+    }
+    Ok(with_statistics(grid))
+}
+
+/// Shape `values` (z fastest) into the `[x, y, z]` array.
+fn to_array(dims: [usize; 3], values: Vec<f32>) -> Result<Array3<f32>, String> {
+    let expected = dims[0]
+        .checked_mul(dims[1])
+        .and_then(|n| n.checked_mul(dims[2]))
+        .ok_or_else(|| "map too large".to_owned())?;
+    if values.len() != expected {
+        return Err(format!(
+            "expected {expected} grid values, found {}",
+            values.len()
+        ));
+    }
+    Array3::from_shape_vec((dims[0], dims[1], dims[2]), values)
+        .map_err(|e| e.to_string())
+}
+
+/// Wrap a grid with its min / max / mean / RMS deviation, in P1.
+fn with_statistics(grid: VoxelGrid) -> Density {
+    let n = grid.data.len().max(1) as f64;
+    let (mut dmin, mut dmax, mut sum) = (f32::INFINITY, f32::NEG_INFINITY, 0.0);
+    for &v in &grid.data {
+        dmin = dmin.min(v);
+        dmax = dmax.max(v);
+        sum += f64::from(v);
+    }
+    let mean = sum / n;
+    let variance = grid
+        .data
+        .iter()
+        .map(|&v| (f64::from(v) - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    Density {
+        grid,
+        dmin,
+        dmax,
+        dmean: mean as f32,
+        rms: variance.sqrt() as f32,
+        space_group: 1,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    pub(super) fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+        let off = Vec3::from(actual).distance(Vec3::from(expected));
+        assert!(off < 1e-4, "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn compressed_extensions_keep_the_inner_one() {
+        assert_eq!(file_extension(Path::new("/x/emd_1234.map.gz")), "map.gz");
+        assert_eq!(file_extension(Path::new("pot.DX")), "dx");
+        assert_eq!(file_extension(Path::new("noext")), "");
+        assert!(is_density_format("ccp4.gz"));
+        assert!(is_density_format(".cube"));
+        assert!(!is_density_format("pdb.gz"));
+        assert!(!is_density_format("dx.gz"));
+    }
+
+    #[test]
+    fn axis_aligned_grid_places_every_point() {
+        let values = (0..24).map(|v| v as f32).collect();
+        let map = grid_from_axes(
+            Vec3::new(1.0, 2.0, 3.0),
+            [Vec3::X * 0.5, Vec3::Y * 0.25, Vec3::Z],
+            [2, 3, 4],
+            values,
+        )
+        .unwrap();
+        assert_close(map.grid_to_cartesian(1, 2, 3), [1.5, 2.5, 6.0]);
+        // z fastest: point (1, 2, 3) is value 1*12 + 2*4 + 3.
+        assert!((map.data[[1, 2, 3]] - 23.0).abs() < 1e-6);
+        assert!((map.dmean - 11.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn gzip_maps_decompress_before_parsing() {
+        let mut encoder = flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::fast(),
+        );
+        encoder.write_all(b"not an mrc file").unwrap();
+        let compressed = encoder.finish().unwrap();
+        // Decompresses fine, then fails as MRC — not as gzip.
+        let err = parse_density(&compressed, "map.gz").unwrap_err();
+        assert!(!err.contains("gzip"), "{err}");
+        assert!(parse_density(b"junk", "map.gz")
+            .unwrap_err()
+            .contains("gzip"));
+        assert_eq!(
+            gunzip_capped(&compressed, 4).unwrap_err(),
+            "decompressed map too large"
+        );
+        assert_eq!(gunzip_capped(&compressed, 15).unwrap().len(), 15);
+    }
+
+    #[test]
+    fn overflowing_dimensions_are_rejected() {
+        let dims = [usize::MAX / 2, 3, 1];
+        assert_eq!(to_array(dims, Vec::new()).unwrap_err(), "map too large");
+    }
+}
@@ -3,7 +3,15 @@
 //! Density maps are not entities (no atoms, residues, chains). They get
 //! their own store, analogous to `ConstraintSpecs` for bands/pulls.
 
+use std::sync::Arc;
+use std::time::Duration;
+
+use glam::Vec3;
 use molex::entity::surface::Density;
+use web_time::Instant;
+
+use crate::renderer::geometry::isosurface::density::{ContourLevel, CropBox};
+use crate::renderer::geometry::volume::TransferFunction;
 
 /// Default sigma for computing the initial threshold at load time.
 const DEFAULT_SIGMA: f32 = 3.0;
@@ -14,10 +22,82 @@ const DEFAULT_COLOR: [f32; 3] = [0.3, 0.5, 0.8];
 /// Default density opacity.
 const DEFAULT_OPACITY: f32 = 0.35;
 
+/// Positive difference-map contour color (green).
+const DIFFERENCE_POSITIVE_COLOR: [f32; 3] = [0.1, 0.8, 0.2];
+
+/// Negative difference-map contour color (red).
+const DIFFERENCE_NEGATIVE_COLOR: [f32; 3] = [0.9, 0.15, 0.15];
+
+/// How a density map's contours are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DensityStyle {
+    /// Translucent solid isosurface.
+    Surface,
+    /// Chicken-wire: the contour's triangle edges as lines.
+    Mesh,
+    /// Ray-marched volume through a transfer function; no contours.
+    Volume,
+}
+
+/// Default density zone radius in Angstroms (Coot's default).
+pub(crate) const DEFAULT_ZONE_RADIUS: f32 = 10.0;
+
+/// Maps with more voxels than this (256³) start with a camera zone
+/// instead of being meshed whole.
+const ZONE_AUTO_VOXELS: usize = 256 * 256 * 256;
+
+/// How long the view center must stay put before zones re-contour.
+const ZONE_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Fraction of the zone radius the view center must move by before
+/// zones re-contour.
+const ZONE_RECENTER_FRACTION: f32 = 0.2;
+
+/// Shape of the meshed region of a density zone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ZoneShape {
+    /// Axis-aligned cube of half-extent `radius`.
+    Box,
+    /// Sphere of `radius`.
+    Sphere,
+}
+
+/// What a density zone is centered on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ZoneAnchor {
+    /// The camera focus point, followed as the view recenters.
+    Camera,
+    /// The bounding sphere of the selected residues, grown by the
+    /// radius. Falls back to the camera focus with nothing selected.
+    Selection,
+}
+
+/// Region of a density map to mesh, instead of the whole map.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct DensityZone {
+    /// Box or sphere.
+    pub(crate) shape: ZoneShape,
+    /// Camera focus or selection.
+    pub(crate) anchor: ZoneAnchor,
+    /// Radius (half-extent for boxes) in Angstroms.
+    pub(crate) radius: f32,
+}
+
+impl Default for DensityZone {
+    fn default() -> Self {
+        Self {
+            shape: ZoneShape::Box,
+            anchor: ZoneAnchor::Camera,
+            radius: DEFAULT_ZONE_RADIUS,
+        }
+    }
+}
+
 /// A single density map entry with display parameters.
 pub(crate) struct DensityEntry {
-    /// The parsed density map (owns the 3D grid).
-    pub(crate) map: Density,
+    /// The parsed density map, shared with the meshing worker so
+    /// regenerating a contour doesn't copy the grid.
+    pub(crate) map: Arc<Density>,
     /// Raw density threshold for isosurface extraction.
     pub(crate) threshold: f32,
     /// Whether this map is visible.
@@ -28,6 +108,81 @@ pub(crate) struct DensityEntry {
     pub(crate) opacity: f32,
     /// Dirty generation counter (bumped on any parameter change).
     pub(crate) generation: u64,
+    /// Meshed region, or `None` to mesh the whole map.
+    pub(crate) zone: Option<DensityZone>,
+    /// Solid surface or chicken-wire.
+    pub(crate) style: DensityStyle,
+    /// Difference map: also contour the threshold mirrored below the
+    /// mean, in [`DIFFERENCE_NEGATIVE_COLOR`].
+    pub(crate) difference: bool,
+    /// Contour levels drawn besides the primary (`threshold`, `color`)
+    /// one.
+    pub(crate) extra_levels: Vec<ContourLevel>,
+    /// Palette and opacity curve of the volume style.
+    pub(crate) transfer: TransferFunction,
+}
+
+impl DensityEntry {
+    /// Every contour level to mesh: the primary one, its negative
+    /// mirror for difference maps, then the extra levels.
+    pub(crate) fn contours(&self) -> Vec<ContourLevel> {
+        let mut levels = vec![ContourLevel {
+            threshold: self.threshold,
+            color: self.color,
+        }];
+        if self.difference {
+            levels.push(ContourLevel {
+                threshold: 2.0f32.mul_add(self.map.dmean, -self.threshold),
+                color: DIFFERENCE_NEGATIVE_COLOR,
+            });
+        }
+        levels.extend_from_slice(&self.extra_levels);
+        levels
+    }
+}
+
+/// Debounced view center that camera-anchored zones follow.
+struct ZoneFollow {
+    /// Focus the zones were last contoured around.
+    applied: Option<Vec3>,
+    /// Moved focus waiting to settle, and when it was last seen moving.
+    pending: Option<(Vec3, Instant)>,
+}
+
+impl ZoneFollow {
+    /// Feed the current focus. Returns the new zone center once the
+    /// focus has moved at least `min_shift` from the applied one and
+    /// then stayed put for [`ZONE_DEBOUNCE`].
+    fn observe(
+        &mut self,
+        focus: Vec3,
+        min_shift: f32,
+        now: Instant,
+    ) -> Option<Vec3> {
+        let Some(applied) = self.applied else {
+            self.applied = Some(focus);
+            return self.applied;
+        };
+        if focus.distance(applied) < min_shift {
+            self.pending = None;
+            return None;
+        }
+        match self.pending {
+            Some((seen, since))
+                if seen.distance(focus) < min_shift * 0.1
+                    && now.duration_since(since) >= ZONE_DEBOUNCE =>
+            {
+                self.pending = None;
+                self.applied = Some(focus);
+                self.applied
+            }
+            Some((seen, _)) if seen.distance(focus) < min_shift * 0.1 => None,
+            _ => {
+                self.pending = Some((focus, now));
+                None
+            }
+        }
+    }
 }
 
 /// Manages all loaded density maps with dirty tracking.
@@ -37,6 +192,14 @@ pub(crate) struct DensityStore {
     /// Last generation that was rendered (for dirty detection).
     #[allow(dead_code)]
     rendered_generation: u64,
+    /// Debounced camera focus for camera-anchored zones.
+    follow: ZoneFollow,
+    /// Selected residues the selection sphere was resolved for.
+    selection_residues: Vec<i32>,
+    /// Bounding sphere `(center, radius)` of the selection.
+    selection: Option<(Vec3, f32)>,
+    /// Map sampled for electrostatic surface coloring.
+    potential: Option<u32>,
 }
 
 impl DensityStore {
@@ -46,6 +209,13 @@ impl DensityStore {
             entries: Vec::new(),
             next_id: 0,
             rendered_generation: 0,
+            follow: ZoneFollow {
+                applied: None,
+                pending: None,
+            },
+            selection_residues: Vec::new(),
+            selection: None,
+            potential: None,
         }
     }
 
@@ -54,15 +224,28 @@ impl DensityStore {
         let id = self.next_id;
         self.next_id += 1;
         let threshold = map.sigma_level(DEFAULT_SIGMA);
+        let zone = (map.nx * map.ny * map.nz > ZONE_AUTO_VOXELS)
+            .then(DensityZone::default);
+        if zone.is_some() {
+            log::info!(
+                "density map id={id} is large; meshing a \
+                 {DEFAULT_ZONE_RADIUS} Å zone around the view center"
+            );
+        }
         self.entries.push((
             id,
             DensityEntry {
-                map,
+                map: Arc::new(map),
                 threshold,
                 visible: true,
                 color: DEFAULT_COLOR,
                 opacity: DEFAULT_OPACITY,
                 generation: 1,
+                zone,
+                style: DensityStyle::Surface,
+                difference: false,
+                extra_levels: Vec::new(),
+                transfer: TransferFunction::default(),
             },
         ));
         id
@@ -72,6 +255,9 @@ impl DensityStore {
     pub(crate) fn remove(&mut self, id: u32) -> bool {
         let len = self.entries.len();
         self.entries.retain(|(eid, _)| *eid != id);
+        if self.potential == Some(id) {
+            self.potential = None;
+        }
         self.entries.len() != len
     }
 
@@ -123,6 +309,141 @@ impl DensityStore {
         }
     }
 
+    /// Set the contour drawing style of a density map.
+    pub(crate) fn set_style(&mut self, id: u32, style: DensityStyle) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.style = style;
+        }
+    }
+
+    /// Mark a density map as a difference map: contour ±threshold
+    /// around the mean, positive in green and negative in red.
+    /// Turning it off keeps the current color.
+    pub(crate) fn set_difference(&mut self, id: u32, difference: bool) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.difference = difference;
+            if difference {
+                entry.color = DIFFERENCE_POSITIVE_COLOR;
+            }
+        }
+    }
+
+    /// Replace the extra contour levels of a density map.
+    pub(crate) fn set_extra_levels(
+        &mut self,
+        id: u32,
+        levels: Vec<ContourLevel>,
+    ) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.extra_levels = levels;
+        }
+    }
+
+    /// Replace the volume-style transfer function of a density map.
+    pub(crate) fn set_transfer(&mut self, id: u32, transfer: TransferFunction) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.transfer = transfer;
+        }
+    }
+
+    /// Mark a map (or none) as the electrostatic potential source.
+    /// Leaves the map's generation alone: only surfaces colored by it
+    /// change, not its own contours.
+    pub(crate) fn set_potential(&mut self, id: Option<u32>) {
+        self.potential = id.filter(|&id| self.get(id).is_some());
+    }
+
+    /// The map marked as the electrostatic potential source.
+    pub(crate) fn potential(&self) -> Option<(u32, &DensityEntry)> {
+        let id = self.potential?;
+        self.get(id).map(|entry| (id, entry))
+    }
+
+    /// Visible volume-style entries.
+    pub(crate) fn volume_entries(
+        &self,
+    ) -> impl Iterator<Item = (u32, &DensityEntry)> {
+        self.visible_entries()
+            .filter(|(_, e)| e.style == DensityStyle::Volume)
+    }
+
+    /// Set (or clear, with `None`) the density zone of a density map.
+    pub(crate) fn set_zone(&mut self, id: u32, zone: Option<DensityZone>) {
+        if let Some(entry) = self.get_mut(id) {
+            entry.zone = zone.map(|z| DensityZone {
+                radius: z.radius.max(1.0),
+                ..z
+            });
+        }
+    }
+
+    /// Track the camera focus for camera-anchored zones. Returns `true`
+    /// when the zone center moved and the zones need re-contouring.
+    pub(crate) fn follow_focus(&mut self, focus: Vec3, now: Instant) -> bool {
+        let Some(radius) = self
+            .visible_entries()
+            .filter(|(_, e)| self.follows_focus(e))
+            .filter_map(|(_, e)| e.zone.map(|z| z.radius))
+            .reduce(f32::min)
+        else {
+            return false;
+        };
+        self.follow
+            .observe(focus, radius * ZONE_RECENTER_FRACTION, now)
+            .is_some()
+    }
+
+    /// Seed the zone center with `focus` if none is applied yet, so the
+    /// first mesh of a zoned map isn't the whole map.
+    pub(crate) fn seed_focus(&mut self, focus: Vec3) {
+        let _ = self.follow.applied.get_or_insert(focus);
+    }
+
+    /// Whether any visible map is anchored on the selection.
+    pub(crate) fn has_selection_zone(&self) -> bool {
+        self.visible_entries().any(|(_, e)| {
+            e.zone.is_some_and(|z| z.anchor == ZoneAnchor::Selection)
+        })
+    }
+
+    /// Selected residues the selection sphere was resolved for.
+    pub(crate) fn selection_residues(&self) -> &[i32] {
+        &self.selection_residues
+    }
+
+    /// Store the selection's bounding sphere, resolved for `residues`.
+    pub(crate) fn set_selection(
+        &mut self,
+        residues: &[i32],
+        sphere: Option<(Vec3, f32)>,
+    ) {
+        self.selection_residues = residues.to_vec();
+        self.selection = sphere;
+    }
+
+    /// Whether `entry`'s zone is currently centered on the camera focus.
+    fn follows_focus(&self, entry: &DensityEntry) -> bool {
+        entry.zone.is_some_and(|z| {
+            z.anchor == ZoneAnchor::Camera || self.selection.is_none()
+        })
+    }
+
+    /// World-space crop of `entry`, or `None` to mesh the whole map.
+    pub(crate) fn crop(&self, entry: &DensityEntry) -> Option<CropBox> {
+        let zone = entry.zone?;
+        let (center, radius) = match (zone.anchor, self.selection) {
+            (ZoneAnchor::Selection, Some((center, radius))) => {
+                (center, radius + zone.radius)
+            }
+            _ => (self.follow.applied?, zone.radius),
+        };
+        let center = center.to_array();
+        Some(match zone.shape {
+            ZoneShape::Box => CropBox::cube(center, radius),
+            ZoneShape::Sphere => CropBox::sphere(center, radius),
+        })
+    }
+
     /// Whether any density map has been modified since the last render.
     #[allow(dead_code)]
     pub(crate) fn is_dirty(&self) -> bool {
@@ -166,3 +487,38 @@ impl DensityStore {
             .unwrap_or(0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zone_follow_waits_for_the_focus_to_settle() {
+        let mut follow = ZoneFollow {
+            applied: None,
+            pending: None,
+        };
+        let t0 = Instant::now();
+        let origin = Vec3::ZERO;
+        assert_eq!(follow.observe(origin, 2.0, t0), Some(origin));
+
+        // Small drift stays inside the threshold.
+        assert_eq!(follow.observe(Vec3::X, 2.0, t0), None);
+
+        // A recenter only applies once it has held still long enough.
+        let moved = Vec3::new(8.0, 0.0, 0.0);
+        assert_eq!(follow.observe(moved, 2.0, t0), None);
+        let early = t0 + ZONE_DEBOUNCE / 2;
+        assert_eq!(follow.observe(moved, 2.0, early), None);
+        let settled = t0 + ZONE_DEBOUNCE;
+        assert_eq!(follow.observe(moved, 2.0, settled), Some(moved));
+
+        // Still moving: the timer restarts on every step.
+        let a = Vec3::new(20.0, 0.0, 0.0);
+        let b = Vec3::new(24.0, 0.0, 0.0);
+        assert_eq!(follow.observe(a, 2.0, settled), None);
+        let later = settled + ZONE_DEBOUNCE;
+        assert_eq!(follow.observe(b, 2.0, later), None);
+        assert_eq!(follow.observe(b, 2.0, later + ZONE_DEBOUNCE), Some(b));
+    }
+}
@@ -0,0 +1,378 @@
+//! Electrostatic potential for coloring molecular surfaces.
+//!
+//! The potential comes either from a loaded map (an APBS / DelPhi `.dx`
+//! in kT/e, sampled trilinearly) or from a Coulomb sum over approximate
+//! per-atom charges with a distance-dependent dielectric ε = 4r. The
+//! approximation charges the titratable groups at pH 7 (Asp, Glu, Lys,
+//! Arg, the termini and nucleic acid phosphates), ions by element, and
+//! honors any formal charge the file recorded. The C-terminal charge is
+//! spread over the last residue's carboxylate oxygens, so chains
+//! deposited without an OXT atom carry it too. Charges farther than
+//! [`CUTOFF`] from a vertex are left out of its sum, so coloring a
+//! surface visits only the charges in the grid cells around each vertex
+//! instead of every charge in the scene.
+//!
+//! Surface vertices are colored on the diverging blue-white-red palette:
+//! blue for positive, red for negative, saturating at ±`range` kT/e.
+
+use std::sync::Arc;
+
+use glam::Vec3;
+use molex::entity::surface::Density;
+use molex::{Atom, Element, MoleculeEntity, MoleculeType};
+
+use crate::options::{Palette, PalettePreset};
+use crate::renderer::geometry::isosurface::atom_grid::AtomGrid;
+use crate::renderer::geometry::isosurface::IsosurfaceVertex;
+
+/// Coulomb's constant in kcal·Å/(mol·e²).
+const COULOMB: f32 = 332.06;
+
+/// kT at 298 K in kcal/mol.
+const KT: f32 = 0.5925;
+
+/// Closest approach used in the Coulomb sum, keeping vertices that
+/// graze an atom center from blowing up.
+const MIN_DISTANCE: f32 = 1.0;
+
+/// Distance beyond which a charge is left out of the Coulomb sum, in
+/// Å. With ε = 4r a unit charge contributes under 0.7 kT/e here.
+const CUTOFF: f32 = 15.0;
+
+/// Source of the potential sampled at surface vertices.
+pub(crate) enum PotentialField {
+    /// Point charges `(position, charge in e)`.
+    Coulomb(Vec<(Vec3, f32)>),
+    /// A loaded potential map in kT/e, shared with the density store.
+    Map(Arc<Density>),
+}
+
+/// Approximate charge of every atom of `entity`, in atom order.
+#[must_use]
+pub(crate) fn atom_charges(entity: &MoleculeEntity) -> Vec<f32> {
+    let atoms = entity.atom_set();
+    let mut charges: Vec<f32> = atoms
+        .iter()
+        .map(|atom| {
+            if atom.formal_charge != 0 {
+                f32::from(atom.formal_charge)
+            } else if entity.molecule_type() == MoleculeType::Ion {
+                ion_charge(atom.element)
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    let Some(residues) = entity.residues() else {
+        return charges;
+    };
+    let is_protein = entity.as_protein().is_some();
+    for (index, residue) in residues.iter().enumerate() {
+        let range = residue.atom_range.clone();
+        for (atom, charge) in atoms
+            .get(range.clone())
+            .unwrap_or_default()
+            .iter()
+            .zip(charges.get_mut(range).unwrap_or_default())
+        {
+            if atom.formal_charge != 0 {
+                continue;
+            }
+            let name = atom_name(&atom.name);
+            *charge = residue_atom_charge(residue.name, name);
+            if is_protein && index == 0 && name == "N" {
+                *charge += 1.0;
+            }
+        }
+    }
+    if let Some(last) = residues.last().filter(|_| is_protein) {
+        charge_c_terminus(atoms, &mut charges, last.atom_range.clone());
+    }
+    charges
+}
+
+/// Add the C-terminal −1 to the carboxylate oxygens among `range`: O
+/// and OXT when the file has both, O alone when OXT was left out.
+fn charge_c_terminus(
+    atoms: &[Atom],
+    charges: &mut [f32],
+    range: std::ops::Range<usize>,
+) {
+    let oxygens: Vec<usize> = range
+        .filter(|&i| {
+            atoms.get(i).is_some_and(|atom| {
+                atom.formal_charge == 0
+                    && matches!(atom_name(&atom.name), "O" | "OXT")
+            })
+        })
+        .collect();
+    let share = 1.0 / oxygens.len().max(1) as f32;
+    for i in oxygens {
+        if let Some(charge) = charges.get_mut(i) {
+            *charge -= share;
+        }
+    }
+}
+
+/// Trimmed atom name.
+fn atom_name(raw: &[u8; 4]) -> &str {
+    std::str::from_utf8(raw)
+        .unwrap_or("")
+        .trim_matches(|c: char| c == ' ' || c == '\0')
+}
+
+/// Charge of a polymer atom at pH 7, by residue and atom name.
+fn residue_atom_charge(residue: [u8; 3], atom: &str) -> f32 {
+    match (&residue, atom) {
+        (b"ASP", "OD1" | "OD2")
+        | (b"GLU", "OE1" | "OE2")
+        | (_, "OP1" | "OP2" | "O1P" | "O2P") => -0.5,
+        (b"LYS", "NZ") => 1.0,
+        (b"ARG", "NE" | "NH1" | "NH2") => 1.0 / 3.0,
+        _ => 0.0,
+    }
+}
+
+/// Charge of a free ion of `element`.
+const fn ion_charge(element: Element) -> f32 {
+    match element {
+        Element::Na | Element::K => 1.0,
+        Element::Mg
+        | Element::Ca
+        | Element::Zn
+        | Element::Mn
+        | Element::Fe
+        | Element::Co
+        | Element::Ni
+        | Element::Cu => 2.0,
+        Element::Cl | Element::Br | Element::I | Element::F => -1.0,
+        _ => 0.0,
+    }
+}
+
+/// Coulomb potential at `p` in kT/e with dielectric ε = 4r, summed
+/// over the charges within [`CUTOFF`].
+#[must_use]
+pub(crate) fn coulomb_potential<'a>(
+    charges: impl IntoIterator<Item = &'a (Vec3, f32)>,
+    p: Vec3,
+) -> f32 {
+    let sum: f32 = charges
+        .into_iter()
+        .filter_map(|&(position, q)| {
+            let r_sq = position.distance_squared(p);
+            (r_sq <= CUTOFF * CUTOFF)
+                .then(|| q / (4.0 * r_sq.max(MIN_DISTANCE)))
+        })
+        .sum();
+    sum * COULOMB / KT
+}
+
+/// Trilinearly interpolated map value at `p`, or `None` outside the
+/// grid.
+#[must_use]
+pub(crate) fn sample_trilinear(map: &Density, p: Vec3) -> Option<f32> {
+    let g = map.cartesian_to_grid(p.to_array());
+    let dims = [map.nx, map.ny, map.nz];
+    let mut base = [0usize; 3];
+    let mut frac = [0.0f32; 3];
+    for axis in 0..3 {
+        let last = dims[axis].checked_sub(1)? as f32;
+        if !(0.0..=last).contains(&g[axis]) {
+            return None;
+        }
+        // Step back from the last plane so `base + 1` stays in range.
+        let cell = g[axis].floor().min((last - 1.0).max(0.0));
+        base[axis] = cell as usize;
+        frac[axis] = g[axis] - cell;
+    }
+    let at = |dx: usize, dy: usize, dz: usize| {
+        let index = [
+            (base[0] + dx).min(dims[0] - 1),
+            (base[1] + dy).min(dims[1] - 1),
+            (base[2] + dz).min(dims[2] - 1),
+        ];
+        map.data[index]
+    };
+    let lerp = |a: f32, b: f32, t: f32| (b - a).mul_add(t, a);
+    let x00 = lerp(at(0, 0, 0), at(1, 0, 0), frac[0]);
+    let x10 = lerp(at(0, 1, 0), at(1, 1, 0), frac[0]);
+    let x01 = lerp(at(0, 0, 1), at(1, 0, 1), frac[0]);
+    let x11 = lerp(at(0, 1, 1), at(1, 1, 1), frac[0]);
+    let y0 = lerp(x00, x10, frac[1]);
+    let y1 = lerp(x01, x11, frac[1]);
+    Some(lerp(y0, y1, frac[2]))
+}
+
+/// Diverging color of a potential: blue at `+range`, white at zero,
+/// red at `-range`.
+#[must_use]
+pub(crate) fn potential_color(
+    palette: &Palette,
+    value: f32,
+    range: f32,
+) -> [f32; 3] {
+    let t = 0.5 - value / (2.0 * range.max(f32::EPSILON));
+    palette.sample_gradient(t)
+}
+
+/// Recolor surface vertices by the potential at their positions,
+/// keeping each vertex's alpha. Outside a map's grid the potential is
+/// zero.
+pub(crate) fn color_vertices(
+    vertices: &mut [IsosurfaceVertex],
+    field: &PotentialField,
+    range: f32,
+) {
+    let palette = Palette {
+        preset: PalettePreset::BlueWhiteRed,
+        ..Default::default()
+    };
+    let paint = |v: &mut IsosurfaceVertex, value: f32| {
+        let [r, g, b] = potential_color(&palette, value, range);
+        v.color = [r, g, b, v.color[3]];
+    };
+    match field {
+        PotentialField::Coulomb(charges) => {
+            let positions: Vec<Vec3> =
+                charges.iter().map(|&(position, _)| position).collect();
+            let grid = AtomGrid::new(&positions, CUTOFF);
+            for v in vertices {
+                let p = Vec3::from_array(v.position);
+                let near = grid.neighbors(p).map(|i| &charges[i]);
+                paint(v, coulomb_potential(near, p));
+            }
+        }
+        PotentialField::Map(map) => {
+            for v in vertices {
+                let p = Vec3::from_array(v.position);
+                paint(v, sample_trilinear(map, p).unwrap_or(0.0));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::engine::density_formats::parse_density;
+
+    #[test]
+    fn titratable_atoms_carry_charge() {
+        assert!((residue_atom_charge(*b"ASP", "OD1") + 0.5).abs() < 1e-6);
+        assert!((residue_atom_charge(*b"LYS", "NZ") - 1.0).abs() < 1e-6);
+        let arg: f32 = ["NE", "NH1", "NH2"]
+            .iter()
+            .map(|name| residue_atom_charge(*b"ARG", name))
+            .sum();
+        assert!((arg - 1.0).abs() < 1e-6);
+        assert!(residue_atom_charge(*b"ASP", "CG").abs() < 1e-6);
+        assert!(residue_atom_charge(*b"HIS", "NE2").abs() < 1e-6);
+        assert!((ion_charge(Element::Zn) - 2.0).abs() < 1e-6);
+    }
+
+    /// Net charge of the O and OXT atoms of a Gly-Gly peptide, with or
+    /// without the OXT record.
+    fn carboxylate_charge(with_oxt: bool) -> f32 {
+        let mut pdb = String::from(
+            "ATOM      1  N   GLY A   1       0.000   0.000   0.000  1.00  \
+             0.00           N
+ATOM      2  CA  GLY A   1       1.458   0.000   0.000  1.00  0.00           C
+ATOM      3  C   GLY A   1       2.009   1.420   0.000  1.00  0.00           C
+ATOM      4  O   GLY A   1       1.251   2.390   0.000  1.00  0.00           O
+ATOM      5  N   GLY A   2       3.332   1.536   0.000  1.00  0.00           N
+ATOM      6  CA  GLY A   2       3.970   2.846   0.000  1.00  0.00           C
+ATOM      7  C   GLY A   2       5.487   2.705   0.000  1.00  0.00           C
+ATOM      8  O   GLY A   2       6.009   1.593   0.000  1.00  0.00           O
+",
+        );
+        if with_oxt {
+            pdb.push_str(
+                "ATOM      9  OXT GLY A   2       6.175   3.750   0.000  1.00  \
+                 0.00           O\n",
+            );
+        }
+        let entities = molex::adapters::pdb::pdb_str_to_entities(&pdb).unwrap();
+        let protein =
+            entities.iter().find(|e| e.as_protein().is_some()).unwrap();
+        let charges = atom_charges(protein);
+        protein
+            .atom_set()
+            .iter()
+            .zip(&charges)
+            .filter(|(atom, _)| matches!(atom_name(&atom.name), "O" | "OXT"))
+            .map(|(_, q)| q)
+            .sum()
+    }
+
+    #[test]
+    fn c_terminus_is_charged_with_or_without_oxt() {
+        assert!((carboxylate_charge(true) + 1.0).abs() < 1e-6);
+        assert!((carboxylate_charge(false) + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn coulomb_falls_off_with_distance_squared() {
+        let charges = [(Vec3::ZERO, 1.0)];
+        let near = coulomb_potential(&charges, Vec3::new(2.0, 0.0, 0.0));
+        let far = coulomb_potential(&charges, Vec3::new(4.0, 0.0, 0.0));
+        assert!((near / far - 4.0).abs() < 1e-4);
+        // 332.06 / (0.5925 * 4 * 2²) ≈ 35 kT/e at 2 Å.
+        assert!((near - 35.03).abs() < 0.05);
+        let opposite = [(Vec3::ZERO, -1.0)];
+        assert!(coulomb_potential(&opposite, Vec3::X * 2.0) < 0.0);
+    }
+
+    #[test]
+    fn charges_beyond_the_cutoff_are_ignored() {
+        let charges = [(Vec3::ZERO, 1.0), (Vec3::X * (CUTOFF + 5.0), -1.0)];
+        let p = Vec3::X * 3.0;
+        let near_only = coulomb_potential(&charges[..1], p);
+        assert!((coulomb_potential(&charges, p) - near_only).abs() < 1e-6);
+
+        let mut vertices = [IsosurfaceVertex {
+            position: p.to_array(),
+            ..bytemuck::Zeroable::zeroed()
+        }];
+        let field = PotentialField::Coulomb(charges.to_vec());
+        color_vertices(&mut vertices, &field, 100.0);
+        let palette = Palette {
+            preset: PalettePreset::BlueWhiteRed,
+            ..Default::default()
+        };
+        let [r, g, b] = potential_color(&palette, near_only, 100.0);
+        assert_eq!(vertices[0].color[..3], [r, g, b]);
+    }
+
+    #[test]
+    fn trilinear_sampling_is_exact_for_linear_fields() {
+        // 2×2×2 grid of f(x, y, z) = x + 2y + 4z, z fastest.
+        let dx = "object 1 class gridpositions counts 2 2 2\norigin 0 0 \
+                  0\ndelta 1 0 0\ndelta 0 1 0\ndelta 0 0 1\nobject 3 class \
+                  array type double rank 0 items 8 data follows\n0 4 2 6 1 5 \
+                  3 7\n";
+        let map = parse_density(dx.as_bytes(), "dx").unwrap();
+        let at = |x, y, z| sample_trilinear(&map, Vec3::new(x, y, z));
+        assert!((at(0.5, 0.5, 0.5).unwrap() - 3.5).abs() < 1e-4);
+        assert!((at(1.0, 1.0, 1.0).unwrap() - 7.0).abs() < 1e-4);
+        assert!((at(0.25, 0.0, 0.75).unwrap() - 3.25).abs() < 1e-4);
+        assert!(at(1.5, 0.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn potentials_map_onto_diverging_palette() {
+        let palette = Palette {
+            preset: PalettePreset::BlueWhiteRed,
+            ..Default::default()
+        };
+        let white = potential_color(&palette, 0.0, 5.0);
+        let blue = potential_color(&palette, 5.0, 5.0);
+        let red = potential_color(&palette, -20.0, 5.0);
+        assert!(white.iter().all(|&c| c > 0.9));
+        assert!(blue[2] > blue[0]);
+        assert!(red[0] > red[2]);
+        assert_eq!(red, palette.sample_gradient(1.0));
+    }
+}
@@ -0,0 +1,236 @@
+//! Engine → host change notifications.
+//!
+//! Hosts used to poll `selected_residues()`, `hovered_target()`,
+//! `focus()` and `has_trajectory()` every frame to detect changes.
+//! [`EventQueue`] does that diffing once, inside the engine, and
+//! buffers the result as [`VisoEvent`]s that the host drains via
+//! [`crate::VisoEngine::drain_events`].
+//!
+//! Two sources feed the queue:
+//!
+//! - **Observed state** (selection, hover, focus): snapshotted and diffed
+//!   against the previous observation in [`EventQueue::observe`], called once
+//!   per frame and again right before a drain so commands executed between
+//!   frames are reported.
+//! - **Edge-triggered work** (transition finished, trajectory frame applied,
+//!   density mesh uploaded, scene synced): pushed directly at the site where
+//!   the work completes.
+
+use std::collections::VecDeque;
+
+use rustc_hash::FxHashSet;
+
+use crate::engine::focus::Focus;
+use crate::renderer::picking::PickTarget;
+
+/// Upper bound on buffered events. A host that never drains would
+/// otherwise grow the queue by one `TrajectoryFrameChanged` per frame
+/// forever; past this bound the oldest events are dropped.
+const MAX_QUEUED_EVENTS: usize = 1024;
+
+/// A change notification emitted by the engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VisoEvent {
+    /// The residue selection changed.
+    SelectionChanged {
+        /// Flat residue indices newly selected.
+        added: Vec<i32>,
+        /// Flat residue indices no longer selected.
+        removed: Vec<i32>,
+    },
+    /// The pick target under the cursor changed.
+    HoverChanged {
+        /// New hover target ([`PickTarget::None`] when nothing is
+        /// hovered).
+        target: PickTarget,
+    },
+    /// The focus target changed.
+    FocusChanged {
+        /// New focus state.
+        focus: Focus,
+    },
+    /// A structural transition finished animating for an entity.
+    TransitionFinished {
+        /// Raw entity id.
+        entity_id: u32,
+    },
+    /// A trajectory frame was applied to the scene.
+    TrajectoryFrameChanged {
+        /// Index of the frame now displayed.
+        frame: usize,
+        /// Total number of frames in the trajectory.
+        total: usize,
+    },
+    /// A regenerated density / surface mesh was uploaded to the GPU.
+    DensityMeshReady,
+    /// Background mesh generation for an assembly sync completed and
+    /// the result was uploaded.
+    SceneSynced {
+        /// Generation of the assembly snapshot the meshes reflect.
+        generation: u64,
+    },
+}
+
+/// Buffered [`VisoEvent`]s plus the last-observed state they are
+/// diffed against.
+pub(crate) struct EventQueue {
+    events: VecDeque<VisoEvent>,
+    last_selection: Vec<i32>,
+    last_hover: PickTarget,
+    last_focus: Focus,
+}
+
+impl EventQueue {
+    /// Empty queue observing an empty selection, no hover and
+    /// session focus.
+    pub(crate) fn new() -> Self {
+        Self {
+            events: VecDeque::new(),
+            last_selection: Vec::new(),
+            last_hover: PickTarget::None,
+            last_focus: Focus::Session,
+        }
+    }
+
+    /// Queue an edge-triggered event.
+    pub(crate) fn push(&mut self, event: VisoEvent) {
+        if self.events.len() >= MAX_QUEUED_EVENTS {
+            let _ = self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Diff the current selection / hover / focus against the last
+    /// observation and queue an event for each that changed.
+    pub(crate) fn observe(
+        &mut self,
+        selection: &[i32],
+        hover: PickTarget,
+        focus: Focus,
+    ) {
+        if selection != self.last_selection.as_slice() {
+            let (added, removed) =
+                diff_selection(&self.last_selection, selection);
+            self.last_selection = selection.to_vec();
+            if !added.is_empty() || !removed.is_empty() {
+                self.push(VisoEvent::SelectionChanged { added, removed });
+            }
+        }
+        if hover != self.last_hover {
+            self.last_hover = hover;
+            self.push(VisoEvent::HoverChanged { target: hover });
+        }
+        if focus != self.last_focus {
+            self.last_focus = focus;
+            self.push(VisoEvent::FocusChanged { focus });
+        }
+    }
+
+    /// Take every buffered event, oldest first.
+    pub(crate) fn drain(&mut self) -> Vec<VisoEvent> {
+        std::mem::take(&mut self.events).into()
+    }
+}
+
+/// Set difference between two selections: `(added, removed)`, each in
+/// the order the indices appear in their source slice.
+fn diff_selection(previous: &[i32], current: &[i32]) -> (Vec<i32>, Vec<i32>) {
+    let previous_set: FxHashSet<i32> = previous.iter().copied().collect();
+    let current_set: FxHashSet<i32> = current.iter().copied().collect();
+    let added = current
+        .iter()
+        .copied()
+        .filter(|r| !previous_set.contains(r))
+        .collect();
+    let removed = previous
+        .iter()
+        .copied()
+        .filter(|r| !current_set.contains(r))
+        .collect();
+    (added, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_diff_reports_added_and_removed() {
+        let mut q = EventQueue::new();
+        q.observe(&[1, 2, 3], PickTarget::None, Focus::Session);
+        q.observe(&[2, 3, 4], PickTarget::None, Focus::Session);
+        let events = q.drain();
+        assert_eq!(
+            events,
+            vec![
+                VisoEvent::SelectionChanged {
+                    added: vec![1, 2, 3],
+                    removed: vec![],
+                },
+                VisoEvent::SelectionChanged {
+                    added: vec![4],
+                    removed: vec![1],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unchanged_state_emits_nothing() {
+        let mut q = EventQueue::new();
+        q.observe(&[], PickTarget::None, Focus::Session);
+        assert!(q.drain().is_empty());
+    }
+
+    #[test]
+    fn reordered_selection_is_not_a_change() {
+        let mut q = EventQueue::new();
+        q.observe(&[1, 2], PickTarget::None, Focus::Session);
+        let _ = q.drain();
+        q.observe(&[2, 1], PickTarget::None, Focus::Session);
+        assert!(q.drain().is_empty());
+    }
+
+    #[test]
+    fn hover_change_is_reported_once() {
+        let mut q = EventQueue::new();
+        q.observe(&[], PickTarget::Residue(7), Focus::Session);
+        q.observe(&[], PickTarget::Residue(7), Focus::Session);
+        assert_eq!(
+            q.drain(),
+            vec![VisoEvent::HoverChanged {
+                target: PickTarget::Residue(7),
+            }]
+        );
+    }
+
+    #[test]
+    fn queue_is_bounded() {
+        let mut q = EventQueue::new();
+        for _ in 0..(MAX_QUEUED_EVENTS + 10) {
+            q.push(VisoEvent::DensityMeshReady);
+        }
+        assert_eq!(q.drain().len(), MAX_QUEUED_EVENTS);
+    }
+
+    #[test]
+    fn bounded_queue_drops_oldest_first() {
+        let mut q = EventQueue::new();
+        let total = MAX_QUEUED_EVENTS + 10;
+        for frame in 0..total {
+            q.push(VisoEvent::TrajectoryFrameChanged { frame, total });
+        }
+        let events = q.drain();
+        assert_eq!(
+            events.first(),
+            Some(&VisoEvent::TrajectoryFrameChanged { frame: 10, total })
+        );
+        assert_eq!(
+            events.last(),
+            Some(&VisoEvent::TrajectoryFrameChanged {
+                frame: total - 1,
+                total,
+            })
+        );
+    }
+}
@@ -5,16 +5,24 @@ pub(crate) mod command;
 pub(crate) mod constraint;
 mod culling;
 mod density;
+pub(crate) mod density_formats;
 pub(crate) mod density_store;
+pub(crate) mod electrostatics;
 pub(crate) mod entity_view;
+/// Change notifications drained by the host.
+pub(crate) mod events;
 /// Focus state for tab cycling.
 pub(crate) mod focus;
 mod options_apply;
 pub(crate) mod positions;
+pub(crate) mod residue_address;
+pub(crate) mod sasa;
 pub(crate) mod scene;
 pub(crate) mod scene_state;
+pub(crate) mod secondary_structure;
 pub(crate) mod surface;
 pub(crate) mod surface_regen;
+pub(crate) mod symmetry;
 mod sync;
 pub(crate) mod trajectory;
 
@@ -24,6 +32,7 @@ use std::sync::Arc;
 use annotations::EntityAnnotations;
 pub(crate) use bootstrap::FrameTiming;
 use density_store::DensityStore;
+use events::{EventQueue, VisoEvent};
 use focus::Focus;
 use molex::entity::molecule::id::EntityId;
 use molex::{Assembly, MoleculeEntity, MoleculeType};
@@ -100,6 +109,17 @@ pub struct VisoEngine {
     /// lives on [`GpuPipeline`]; main-thread polling happens in
     /// [`GpuPipeline::apply_pending_density_mesh`].
     pub(crate) surface_regen: surface_regen::SurfaceRegen,
+
+    // ── Structure-file symmetry ───────────────────────────────────
+    /// Assembly / crystal-symmetry records of the loaded file, the
+    /// active [`symmetry::SymmetryView`] and the unit-cell toggle.
+    pub(crate) symmetry: symmetry::SymmetryState,
+
+    // ── Host notifications ────────────────────────────────────────
+    /// Buffered change events plus the last-observed selection /
+    /// hover / focus they are diffed against. Drained via
+    /// [`VisoEngine::drain_events`].
+    pub(crate) events: EventQueue,
 }
 
 // ── Frame loop ──
@@ -140,7 +160,12 @@ impl VisoEngine {
             self.resolve_and_render_constraints();
         }
 
-        let _ = self.gpu.apply_pending_density_mesh();
+        self.update_density_zones();
+        self.gpu.sync_density_volumes(&self.density);
+        if self.gpu.apply_pending_density_mesh() {
+            self.events.push(VisoEvent::DensityMeshReady);
+        }
+        self.observe_event_state();
     }
 
     /// Tick animation (both trajectory and structural), submitting any
@@ -149,12 +174,26 @@ impl VisoEngine {
         let now = Instant::now();
         let trajectory_frame = self.animation.advance_trajectory(now);
         if let Some(frame) = trajectory_frame {
-            self.apply_trajectory_frame(&frame);
-            self.submit_animation_frame();
+            self.show_trajectory_frame(&frame);
         }
         if self.animation.tick(now, &mut self.scene.positions) {
             self.submit_animation_frame();
         }
+        for eid in self.animation.animator.take_finished() {
+            self.events.push(VisoEvent::TransitionFinished {
+                entity_id: eid.raw(),
+            });
+        }
+    }
+
+    /// Diff selection / hover / focus against the last observation and
+    /// queue the corresponding [`VisoEvent`]s.
+    fn observe_event_state(&mut self) {
+        self.events.observe(
+            self.gpu.pick.selected_residues(),
+            self.gpu.pick.hovered_target,
+            self.annotations.focus,
+        );
     }
 
     /// Core render — geometry, post-process, picking — targeting the
@@ -167,6 +206,7 @@ impl VisoEngine {
             view,
             &self.camera_controller,
             self.options.display.show_sidechains(),
+            self.options.geometry.occlusion_culling,
         )
     }
 
@@ -267,9 +307,33 @@ impl VisoEngine {
                 CommandOutcome::FocusChanged
             }
             // Playback
-            VisoCommand::ToggleTrajectory => {
-                self.animation.toggle_trajectory();
-                CommandOutcome::NoEffect
+            VisoCommand::ToggleTrajectory => self.control_trajectory(|p| {
+                p.toggle_playback();
+                None
+            }),
+            VisoCommand::SeekTrajectory { frame } => {
+                self.control_trajectory(|p| p.seek(frame))
+            }
+            VisoCommand::StepTrajectory { delta } => {
+                self.control_trajectory(|p| p.step(i64::from(delta)))
+            }
+            VisoCommand::SetTrajectoryFps { fps } => {
+                self.control_trajectory(|p| {
+                    p.set_fps(fps);
+                    None
+                })
+            }
+            VisoCommand::SetTrajectoryMode { mode } => {
+                self.control_trajectory(|p| {
+                    p.set_mode(mode);
+                    None
+                })
+            }
+            VisoCommand::SetTrajectoryRange { start, end } => {
+                self.control_trajectory(|p| p.set_range(start, end))
+            }
+            VisoCommand::SetTrajectorySmoothing { smoothing } => {
+                self.control_trajectory(|p| p.set_smoothing(smoothing))
             }
             // Selection
             VisoCommand::ClearSelection => {
@@ -278,6 +342,17 @@ impl VisoEngine {
             VisoCommand::SelectResidue { index, extend } => selection_outcome(
                 self.gpu.pick.picking.handle_click(index, extend),
             ),
+            VisoCommand::SelectResidueAt { address, extend } => {
+                match self.residue_index(&address) {
+                    Some(index) => selection_outcome(
+                        self.gpu
+                            .pick
+                            .picking
+                            .handle_click(index as i32, extend),
+                    ),
+                    None => CommandOutcome::NoEffect,
+                }
+            }
             VisoCommand::SelectSegment { index, extend } => {
                 let ss = self.concatenated_cartoon_ss();
                 selection_outcome(
@@ -290,6 +365,15 @@ impl VisoEngine {
                     self.gpu.pick.select_chain(index, chains, extend),
                 )
             }
+            VisoCommand::SelectInterface { a, b, extend } => {
+                let Some(interface) = self.analyze_interface(a, b) else {
+                    return CommandOutcome::NoEffect;
+                };
+                selection_outcome(self.gpu.pick.select_residues(
+                    interface.residue_indices().map(|i| i as i32),
+                    extend,
+                ))
+            }
             // Entity management
             VisoCommand::FocusEntity { id } => {
                 if let Some(eid) = self.entity_id(id) {
@@ -467,16 +551,6 @@ impl VisoEngine {
         self.gpu.shutdown();
     }
 
-    /// Load a DCD trajectory file and begin playback against the first
-    /// visible protein entity.
-    pub fn load_trajectory(&mut self, path: &std::path::Path) {
-        self.animation.load_trajectory_from_path(
-            path,
-            &self.scene,
-            &self.annotations,
-        );
-    }
-
     /// Position the camera explicitly from world-space center / eye / up.
     /// Used by puzzle loaders to apply a saved viewpoint.
     pub fn set_camera_pose(
@@ -533,13 +607,14 @@ impl VisoEngine {
     pub(crate) fn reset_scene_local_state(&mut self) {
         self.animation = AnimationState::new();
         self.scene.reset_local_state();
+        self.set_structure_symmetry(None);
         self.annotations.reset();
         surface_regen::regenerate_surfaces(
             &self.scene,
             &self.annotations,
             &self.density,
             &self.options,
-            &self.surface_regen,
+            &mut self.surface_regen,
         );
     }
 
@@ -592,10 +667,18 @@ impl VisoEngine {
         self.active_preset.as_deref()
     }
 
-    /// Whether a trajectory is loaded.
-    #[must_use]
-    pub fn has_trajectory(&self) -> bool {
-        self.animation.trajectory_player.is_some()
+    /// Take every change event queued since the last call, oldest
+    /// first.
+    ///
+    /// Selection, hover and focus changes are diffed against the state
+    /// observed at the previous drain (or frame), so commands executed
+    /// between frames are reported too. Hosts call this once per frame
+    /// instead of polling [`Self::selected_residues`],
+    /// [`Self::hovered_target`], [`Self::focus`] and
+    /// [`Self::has_trajectory`].
+    pub fn drain_events(&mut self) -> Vec<VisoEvent> {
+        self.observe_event_state();
+        self.events.drain()
     }
 
     /// Current focus state.
@@ -609,16 +692,13 @@ impl VisoEngine {
     #[must_use]
     pub fn resolve_atom_position(
         &self,
-        residue: u32,
+        residue: impl Into<residue_address::ResidueRef>,
         atom_name: &str,
     ) -> Option<glam::Vec3> {
         constraint::resolve_atom_ref_pub(
             &self.scene,
             &self.annotations,
-            &command::AtomRef {
-                residue,
-                atom_name: atom_name.to_owned(),
-            },
+            &command::AtomRef::new(residue, atom_name),
         )
     }
 
@@ -642,7 +722,7 @@ impl VisoEngine {
         )
     }
 
-    /// Full breakdown of a cartoon-residue pick: owning entity id +
+    /// Full breakdown of a protein-residue pick: owning entity id +
     /// entity-local residue index + PDB atom name of the heavy atom
     /// projecting closest to `screen_pos`. The host uses this to
     /// classify the pick (backbone vs sidechain, protein vs other)
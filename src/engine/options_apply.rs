@@ -162,7 +162,13 @@ impl VisoEngine {
         if !globals.any() && inv.is_empty() {
             return;
         }
+        let ss_source_changed =
+            self.options.geometry.ss_source != new.geometry.ss_source;
         self.options = new;
+        if ss_source_changed && self.apply_ss_source() {
+            // Color-by-SS follows the new assignment.
+            inv |= RenderInvalidation::RE_COLOR;
+        }
         self.apply_global_invalidation(globals, inv);
     }
 
@@ -235,7 +241,7 @@ impl VisoEngine {
                 &self.annotations,
                 &self.density,
                 &self.options,
-                &self.surface_regen,
+                &mut self.surface_regen,
             );
         }
         // Single final sync. Any mesh / color invalidation needs the
@@ -285,7 +291,7 @@ impl VisoEngine {
                 &self.annotations,
                 &self.density,
                 &self.options,
-                &self.surface_regen,
+                &mut self.surface_regen,
             );
         }
         if inv.contains(RenderInvalidation::RE_MESH)
@@ -0,0 +1,271 @@
+//! Author-numbering residue addresses.
+//!
+//! Picking, selection and constraint specs address residues by a flat
+//! index across every visible protein entity in assembly order. That
+//! index is an artifact of the render order; users know residues by
+//! the chain and author residue number (plus insertion code) printed
+//! in the PDB / mmCIF file. [`ResidueAddress`] carries that identity,
+//! and the engine maps it to and from flat indices against the current
+//! scene.
+
+use std::ops::Range;
+
+use molex::entity::molecule::id::EntityId;
+use molex::entity::molecule::polymer::Residue;
+use molex::MoleculeEntity;
+
+use super::scene::Scene;
+use super::VisoEngine;
+
+/// A residue as numbered in the structure file: chain, author residue
+/// number and insertion code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResidueAddress {
+    /// Raw id of the owning entity. `None` matches the first protein
+    /// entity carrying `chain_id`.
+    pub entity_id: Option<u32>,
+    /// PDB chain identifier.
+    pub chain_id: char,
+    /// Author residue number (`auth_seq_id`; `label_seq_id` when the
+    /// file has no author numbering).
+    pub auth_seq_id: i32,
+    /// Insertion code, `None` when blank.
+    pub ins_code: Option<char>,
+}
+
+impl ResidueAddress {
+    /// Address residue `auth_seq_id` of chain `chain_id`, without an
+    /// insertion code.
+    #[must_use]
+    pub const fn new(chain_id: char, auth_seq_id: i32) -> Self {
+        Self {
+            entity_id: None,
+            chain_id,
+            auth_seq_id,
+            ins_code: None,
+        }
+    }
+
+    /// Set the insertion code.
+    #[must_use]
+    pub const fn with_ins_code(mut self, ins_code: char) -> Self {
+        self.ins_code = Some(ins_code);
+        self
+    }
+
+    /// Restrict the address to one entity (for assemblies where
+    /// several entities share a chain identifier).
+    #[must_use]
+    pub const fn in_entity(mut self, entity_id: u32) -> Self {
+        self.entity_id = Some(entity_id);
+        self
+    }
+
+    /// Whether `entity` is the entity this address points into.
+    fn matches_entity(&self, entity: &MoleculeEntity) -> bool {
+        self.entity_id.is_none_or(|id| id == entity.id().raw())
+            && entity.pdb_chain_id().map(char::from) == Some(self.chain_id)
+    }
+}
+
+impl std::fmt::Display for ResidueAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.chain_id, self.auth_seq_id)?;
+        if let Some(code) = self.ins_code {
+            write!(f, "{code}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A residue given either by flat index or by [`ResidueAddress`].
+///
+/// Both variants live in one index space: the flat residue numbering
+/// picking and selection use, counting the residues of every visible
+/// protein entity in assembly order whatever its drawing mode. An
+/// address resolves to the same index [`VisoEngine::residue_index`]
+/// returns for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResidueRef {
+    /// 0-based flat residue index, as reported by picking.
+    Index(u32),
+    /// Author-numbering address.
+    Address(ResidueAddress),
+}
+
+impl From<u32> for ResidueRef {
+    fn from(index: u32) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl From<ResidueAddress> for ResidueRef {
+    fn from(address: ResidueAddress) -> Self {
+        Self::Address(address)
+    }
+}
+
+/// Author residue number and insertion code of `residue`.
+pub(crate) fn residue_number(residue: &Residue) -> (i32, Option<char>) {
+    (
+        residue.auth_seq_id.unwrap_or(residue.label_seq_id),
+        residue
+            .ins_code
+            .filter(|c| !c.is_ascii_whitespace())
+            .map(char::from),
+    )
+}
+
+/// Index of the residue numbered `auth_seq_id` / `ins_code`.
+fn find_residue(
+    residues: &[Residue],
+    auth_seq_id: i32,
+    ins_code: Option<char>,
+) -> Option<usize> {
+    residues
+        .iter()
+        .position(|r| residue_number(r) == (auth_seq_id, ins_code))
+}
+
+/// Address of entity-local residue `local` of `entity`.
+pub(crate) fn address_of(
+    entity: &MoleculeEntity,
+    local: usize,
+) -> Option<ResidueAddress> {
+    let residue = entity.residues()?.get(local)?;
+    let (auth_seq_id, ins_code) = residue_number(residue);
+    Some(ResidueAddress {
+        entity_id: Some(entity.id().raw()),
+        chain_id: char::from(entity.pdb_chain_id()?),
+        auth_seq_id,
+        ins_code,
+    })
+}
+
+/// Owning entity and entity-local residue index of `address`, or
+/// `None` if no protein entity in `scene` has that residue.
+pub(crate) fn locate(
+    scene: &Scene,
+    address: &ResidueAddress,
+) -> Option<(EntityId, usize)> {
+    scene
+        .current
+        .entities()
+        .iter()
+        .filter(|e| e.as_protein().is_some() && address.matches_entity(e))
+        .find_map(|entity| {
+            let local = find_residue(
+                entity.residues()?,
+                address.auth_seq_id,
+                address.ins_code,
+            )?;
+            Some((entity.id(), local))
+        })
+}
+
+/// Flat index of entity-local residue `local` of `entity`, given the
+/// scene's [`Scene::protein_residue_ranges`].
+pub(crate) fn flat_index(
+    ranges: &[(EntityId, Range<u32>)],
+    entity: EntityId,
+    local: usize,
+) -> Option<u32> {
+    let (_, range) = ranges.iter().find(|(e, _)| *e == entity)?;
+    let index = range.start + local as u32;
+    range.contains(&index).then_some(index)
+}
+
+impl VisoEngine {
+    /// Flat residue index (as used by picking and selection) of
+    /// `address`. `None` if the residue doesn't exist or its entity is
+    /// hidden.
+    #[must_use]
+    pub fn residue_index(&self, address: &ResidueAddress) -> Option<u32> {
+        let (eid, local) = locate(&self.scene, address)?;
+        flat_index(
+            &self.scene.protein_residue_ranges(&self.annotations),
+            eid,
+            local,
+        )
+    }
+
+    /// Author-numbering address of flat residue index `index`.
+    #[must_use]
+    pub fn residue_address(&self, index: u32) -> Option<ResidueAddress> {
+        let (eid, range) = self
+            .scene
+            .protein_residue_ranges(&self.annotations)
+            .into_iter()
+            .find(|(_, r)| r.contains(&index))?;
+        let entity = self
+            .scene
+            .current
+            .entities()
+            .iter()
+            .find(|e| e.id() == eid)?;
+        address_of(entity, (index - range.start) as usize)
+    }
+
+    /// Resolve a [`ResidueRef`] to a flat residue index.
+    #[must_use]
+    pub fn resolve_residue_ref(&self, residue: &ResidueRef) -> Option<u32> {
+        match residue {
+            ResidueRef::Index(index) => Some(*index),
+            ResidueRef::Address(address) => self.residue_index(address),
+        }
+    }
+
+    /// Addresses of the currently selected residues, in selection
+    /// order.
+    #[must_use]
+    pub fn selected_residue_addresses(&self) -> Vec<ResidueAddress> {
+        self.selected_residues()
+            .iter()
+            .filter_map(|&i| u32::try_from(i).ok())
+            .filter_map(|i| self.residue_address(i))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn residue(auth: Option<i32>, label: i32, ins: Option<u8>) -> Residue {
+        Residue {
+            name: *b"ALA",
+            label_seq_id: label,
+            auth_seq_id: auth,
+            auth_comp_id: None,
+            ins_code: ins,
+            atom_range: 0..0,
+            variants: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn insertion_codes_distinguish_residues() {
+        let residues = [
+            residue(Some(52), 1, None),
+            residue(Some(52), 2, Some(b'A')),
+            residue(Some(53), 3, Some(b' ')),
+        ];
+        assert_eq!(find_residue(&residues, 52, None), Some(0));
+        assert_eq!(find_residue(&residues, 52, Some('A')), Some(1));
+        assert_eq!(find_residue(&residues, 53, None), Some(2));
+        assert_eq!(find_residue(&residues, 54, None), None);
+    }
+
+    #[test]
+    fn label_number_stands_in_for_missing_auth() {
+        assert_eq!(residue_number(&residue(None, 7, None)), (7, None));
+        assert_eq!(residue_number(&residue(Some(-3), 7, None)), (-3, None));
+    }
+
+    #[test]
+    fn display_matches_pdb_convention() {
+        let address = ResidueAddress::new('B', 100).with_ins_code('C');
+        assert_eq!(address.to_string(), "B:100C");
+        assert_eq!(ResidueAddress::new('A', -2).to_string(), "A:-2");
+    }
+}
@@ -0,0 +1,427 @@
+//! Solvent-accessible surface area and interface analysis.
+//!
+//! Per-atom SASA follows Shrake and Rupley: each heavy atom's sphere,
+//! grown by the 1.4 Å water probe, is sampled at [`SPHERE_POINTS`]
+//! points, and the atom's area is the fraction of points no neighboring
+//! grown sphere covers. Residue areas are the sums over their atoms;
+//! relative SASA divides by the amino acid's theoretical maximum
+//! (Tien et al. 2013), which the buried / intermediate / exposed
+//! classification and the [`Sasa`](crate::options::ColorScheme::Sasa)
+//! color scheme read.
+//!
+//! The interface between two entities (one per chain) is the area each
+//! residue loses when the partner is added: the entities' SASA alone
+//! minus their SASA together.
+
+use glam::Vec3;
+use molex::entity::molecule::id::EntityId;
+use molex::{Element, MoleculeType};
+
+use super::residue_address::flat_index;
+use super::VisoEngine;
+use crate::options::overrides::RenderInvalidation;
+use crate::renderer::entity_topology::EntityTopology;
+use crate::renderer::geometry::isosurface::atom_grid::AtomGrid;
+use crate::renderer::geometry::isosurface::atom_spheres::fibonacci_sphere;
+
+/// Radius of the water probe in Å.
+const PROBE_RADIUS: f32 = 1.4;
+
+/// Test points per atom sphere. ~1 Å² resolution for a carbon.
+const SPHERE_POINTS: usize = 96;
+
+/// Relative SASA below which a residue counts as buried.
+const BURIED_BELOW: f32 = 0.2;
+
+/// Relative SASA above which a residue counts as exposed.
+const EXPOSED_ABOVE: f32 = 0.5;
+
+/// Area in Å² a residue must lose to a partner to count as part of the
+/// interface.
+const INTERFACE_MIN_BURIED: f32 = 1.0;
+
+/// Solvent exposure class of a residue from its relative SASA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exposure {
+    /// Less than 20 % of the maximum area accessible.
+    Buried,
+    /// Between buried and exposed.
+    Intermediate,
+    /// More than 50 % of the maximum area accessible.
+    Exposed,
+}
+
+impl Exposure {
+    /// Classify a relative SASA.
+    #[must_use]
+    pub fn from_relative(relative: f32) -> Self {
+        if relative < BURIED_BELOW {
+            Self::Buried
+        } else if relative > EXPOSED_ABOVE {
+            Self::Exposed
+        } else {
+            Self::Intermediate
+        }
+    }
+}
+
+/// Solvent-accessible area of one residue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResidueSasa {
+    /// Raw id of the entity the residue belongs to.
+    pub entity_id: u32,
+    /// Entity-local residue index.
+    pub residue: u32,
+    /// Accessible area in Å².
+    pub area: f32,
+    /// Area over the amino acid's theoretical maximum; `None` for
+    /// residues that aren't standard amino acids.
+    pub relative: Option<f32>,
+}
+
+impl ResidueSasa {
+    /// Exposure class, for standard amino acids.
+    #[must_use]
+    pub fn exposure(&self) -> Option<Exposure> {
+        self.relative.map(Exposure::from_relative)
+    }
+}
+
+/// A residue whose accessible area shrinks when the partner is added.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterfaceResidue {
+    /// Raw id of the entity the residue belongs to.
+    pub entity_id: u32,
+    /// Entity-local residue index.
+    pub residue: u32,
+    /// Flat residue index (as used by picking and selection), for
+    /// visible protein residues.
+    pub index: Option<u32>,
+    /// Area in Å² the partner buries.
+    pub buried_area: f32,
+}
+
+/// Buried surface between two entities.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceAnalysis {
+    /// Raw ids of the two entities.
+    pub entities: [u32; 2],
+    /// Total area buried on both sides in Å²; half of it is the
+    /// per-side interface area.
+    pub buried_area: f32,
+    /// Residues of either entity burying at least 1 Å², in entity then
+    /// residue order.
+    pub residues: Vec<InterfaceResidue>,
+}
+
+impl InterfaceAnalysis {
+    /// Flat indices of the visible interface residues.
+    pub fn residue_indices(&self) -> impl Iterator<Item = u32> + '_ {
+        self.residues.iter().filter_map(|r| r.index)
+    }
+}
+
+/// Heavy atoms of one entity, with their residues.
+struct AtomSet {
+    positions: Vec<Vec3>,
+    radii: Vec<f32>,
+    /// Entity-local residue index of each atom.
+    residues: Vec<u32>,
+    /// Residue names, indexed by entity-local residue index.
+    residue_names: Vec<[u8; 3]>,
+}
+
+impl AtomSet {
+    /// Heavy atoms of `topology` at `positions`, or `None` for solvent
+    /// or an entity without heavy atoms.
+    fn new(positions: &[Vec3], topology: &EntityTopology) -> Option<Self> {
+        if matches!(
+            topology.molecule_type,
+            MoleculeType::Water | MoleculeType::Solvent
+        ) {
+            return None;
+        }
+        let mut set = Self {
+            positions: Vec::new(),
+            radii: Vec::new(),
+            residues: Vec::new(),
+            residue_names: topology.residue_names.clone(),
+        };
+        for ((&p, &element), &residue) in positions
+            .iter()
+            .zip(&topology.atom_elements)
+            .zip(&topology.atom_residue_index)
+        {
+            if element != Element::H {
+                set.positions.push(p);
+                set.radii.push(element.vdw_radius());
+                set.residues.push(residue);
+            }
+        }
+        (!set.positions.is_empty()).then_some(set)
+    }
+
+    /// Per-residue sums of the per-atom `areas`.
+    fn residue_areas(&self, areas: &[f32]) -> Vec<f32> {
+        let mut out = vec![0.0; self.residue_names.len()];
+        for (&residue, &area) in self.residues.iter().zip(areas) {
+            if let Some(slot) = out.get_mut(residue as usize) {
+                *slot += area;
+            }
+        }
+        out
+    }
+}
+
+/// Per-atom SASA in Å² of the atoms of `sets` taken together, split
+/// back per set.
+fn joint_sasa(sets: &[&AtomSet]) -> Vec<Vec<f32>> {
+    let positions: Vec<Vec3> = sets
+        .iter()
+        .flat_map(|s| s.positions.iter().copied())
+        .collect();
+    let radii: Vec<f32> =
+        sets.iter().flat_map(|s| s.radii.iter().copied()).collect();
+    let mut areas = atom_sasa(&positions, &radii, PROBE_RADIUS).into_iter();
+    sets.iter()
+        .map(|s| areas.by_ref().take(s.positions.len()).collect())
+        .collect()
+}
+
+/// Shrake-Rupley accessible area in Å² of each atom sphere, grown by
+/// `probe`.
+fn atom_sasa(positions: &[Vec3], radii: &[f32], probe: f32) -> Vec<f32> {
+    let max_reach = radii.iter().copied().fold(0.0, f32::max) + probe;
+    // Two grown spheres overlap only within twice the largest reach.
+    let grid = AtomGrid::new(positions, 2.0 * max_reach);
+    let directions: Vec<Vec3> = fibonacci_sphere(SPHERE_POINTS).collect();
+    positions
+        .iter()
+        .zip(radii)
+        .enumerate()
+        .map(|(atom, (&center, &radius))| {
+            let reach = radius + probe;
+            let occluders: Vec<(Vec3, f32)> = grid
+                .neighbors(center)
+                .filter(|&other| other != atom)
+                .map(|other| (positions[other], radii[other] + probe))
+                .filter(|&(c, r)| c.distance(center) < reach + r)
+                .map(|(c, r)| (c, r * r))
+                .collect();
+            let exposed = directions
+                .iter()
+                .filter(|&&d| {
+                    let p = center + d * reach;
+                    occluders.iter().all(|&(c, r2)| c.distance_squared(p) >= r2)
+                })
+                .count();
+            4.0 * std::f32::consts::PI * reach * reach * exposed as f32
+                / SPHERE_POINTS as f32
+        })
+        .collect()
+}
+
+/// Theoretical maximum SASA in Å² of a standard amino acid (Tien et al.
+/// 2013, Gly-X-Gly tripeptides).
+fn max_asa(name: [u8; 3]) -> Option<f32> {
+    Some(match &name {
+        b"ALA" => 129.0,
+        b"ARG" => 274.0,
+        b"ASN" => 195.0,
+        b"ASP" => 193.0,
+        b"CYS" => 167.0,
+        b"GLN" => 225.0,
+        b"GLU" => 223.0,
+        b"GLY" => 104.0,
+        b"HIS" | b"MET" => 224.0,
+        b"ILE" => 197.0,
+        b"LEU" => 201.0,
+        b"LYS" => 236.0,
+        b"PHE" => 240.0,
+        b"PRO" => 159.0,
+        b"SER" => 155.0,
+        b"THR" => 172.0,
+        b"TRP" => 285.0,
+        b"TYR" => 263.0,
+        b"VAL" => 174.0,
+        _ => return None,
+    })
+}
+
+/// Relative SASA of a protein entity's residues in cartoon residue
+/// order, 0 for residues without a maximum.
+fn cartoon_relative(topology: &EntityTopology, areas: &[f32]) -> Vec<f32> {
+    topology
+        .protein_backbone_layout
+        .iter()
+        .flat_map(|seg| seg.ca.iter())
+        .map(|&ca| {
+            let residue = *topology.atom_residue_index.get(ca)? as usize;
+            let max = max_asa(*topology.residue_names.get(residue)?)?;
+            Some(areas.get(residue)? / max)
+        })
+        .map(|relative| relative.unwrap_or(0.0))
+        .collect()
+}
+
+impl VisoEngine {
+    /// Heavy atoms of every visible non-solvent entity.
+    fn sasa_atom_sets(&self) -> Vec<(EntityId, AtomSet)> {
+        self.scene
+            .visible_entities(&self.annotations)
+            .filter_map(|(entity, eid, state)| {
+                AtomSet::new(&entity.positions(), &state.topology)
+                    .map(|set| (eid, set))
+            })
+            .collect()
+    }
+
+    /// Solvent-accessible area of every residue of the visible
+    /// entities, with the whole visible scene (waters excluded)
+    /// occluding. The relative SASA also feeds the
+    /// [`Sasa`](crate::options::ColorScheme::Sasa) color scheme until
+    /// the next call.
+    pub fn compute_sasa(&mut self) -> Vec<ResidueSasa> {
+        let sets = self.sasa_atom_sets();
+        let refs: Vec<&AtomSet> = sets.iter().map(|(_, s)| s).collect();
+        let atom_areas = joint_sasa(&refs);
+
+        let mut out = Vec::new();
+        let mut cartoon = Vec::new();
+        for ((eid, set), areas) in sets.iter().zip(&atom_areas) {
+            let areas = set.residue_areas(areas);
+            out.extend(areas.iter().enumerate().map(|(residue, &area)| {
+                ResidueSasa {
+                    entity_id: eid.raw(),
+                    residue: residue as u32,
+                    area,
+                    relative: max_asa(set.residue_names[residue])
+                        .map(|max| area / max),
+                }
+            }));
+            if let Some(state) = self.scene.entity_state.get(eid) {
+                if state.topology.is_protein() {
+                    cartoon.push((
+                        *eid,
+                        cartoon_relative(&state.topology, &areas),
+                    ));
+                }
+            }
+        }
+
+        let stale: Vec<EntityId> =
+            self.annotations.sasa.keys().copied().collect();
+        {
+            let mut annotations = self.annotations_mut();
+            for eid in stale {
+                annotations.set_per_residue_sasa(eid, None);
+            }
+            for (eid, values) in cartoon {
+                annotations.set_per_residue_sasa(eid, Some(values));
+            }
+        }
+        self.apply_entity_invalidation(RenderInvalidation::RE_COLOR);
+        out
+    }
+
+    /// Surface buried between entities `a` and `b` (raw ids): the
+    /// per-residue area each loses to the other and the total. Other
+    /// entities are ignored. `None` if either entity is hidden, solvent
+    /// or unknown.
+    #[must_use]
+    pub fn analyze_interface(
+        &self,
+        a: u32,
+        b: u32,
+    ) -> Option<InterfaceAnalysis> {
+        let sets = self.sasa_atom_sets();
+        let find = |raw: u32| sets.iter().find(|(eid, _)| eid.raw() == raw);
+        let (&(eid_a, ref set_a), &(eid_b, ref set_b)) = (find(a)?, find(b)?);
+        if eid_a == eid_b {
+            return None;
+        }
+
+        let together = joint_sasa(&[set_a, set_b]);
+        let ranges = self.scene.protein_residue_ranges(&self.annotations);
+        let mut analysis = InterfaceAnalysis {
+            entities: [a, b],
+            buried_area: 0.0,
+            residues: Vec::new(),
+        };
+        for ((eid, set), together) in
+            [(eid_a, set_a), (eid_b, set_b)].into_iter().zip(&together)
+        {
+            let alone = set.residue_areas(&joint_sasa(&[set])[0]);
+            let together = set.residue_areas(together);
+            for (residue, (alone, together)) in
+                alone.iter().zip(&together).enumerate()
+            {
+                let buried_area = alone - together;
+                analysis.buried_area += buried_area;
+                if buried_area >= INTERFACE_MIN_BURIED {
+                    analysis.residues.push(InterfaceResidue {
+                        entity_id: eid.raw(),
+                        residue: residue as u32,
+                        index: flat_index(&ranges, eid, residue),
+                        buried_area,
+                    });
+                }
+            }
+        }
+        Some(analysis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sphere_area(radius: f32) -> f32 {
+        4.0 * std::f32::consts::PI * radius * radius
+    }
+
+    #[test]
+    fn isolated_atom_is_fully_exposed() {
+        let areas = atom_sasa(&[Vec3::ZERO], &[1.7], PROBE_RADIUS);
+        assert!((areas[0] - sphere_area(1.7 + PROBE_RADIUS)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn contact_buries_area_symmetrically() {
+        let positions = [Vec3::ZERO, Vec3::new(3.0, 0.0, 0.0)];
+        let areas = atom_sasa(&positions, &[1.7, 1.7], PROBE_RADIUS);
+        let full = sphere_area(1.7 + PROBE_RADIUS);
+        assert!(areas[0] < full && areas[0] > 0.5 * full);
+        assert!((areas[0] - areas[1]).abs() < 0.05 * full);
+
+        // Far apart, nothing is buried.
+        let apart = [Vec3::ZERO, Vec3::new(20.0, 0.0, 0.0)];
+        let areas = atom_sasa(&apart, &[1.7, 1.7], PROBE_RADIUS);
+        assert!((areas[0] - full).abs() < 1e-3);
+    }
+
+    #[test]
+    fn joint_sasa_splits_per_set() {
+        let set = |x: f32| AtomSet {
+            positions: vec![Vec3::new(x, 0.0, 0.0)],
+            radii: vec![1.7],
+            residues: vec![0],
+            residue_names: vec![*b"ALA"],
+        };
+        let (a, b) = (set(0.0), set(3.0));
+        let alone = joint_sasa(&[&a]);
+        let together = joint_sasa(&[&a, &b]);
+        assert_eq!(together.len(), 2);
+        assert!(together[0][0] < alone[0][0]);
+        assert_eq!(a.residue_areas(&together[0]).len(), 1);
+    }
+
+    #[test]
+    fn exposure_classes_follow_thresholds() {
+        assert_eq!(Exposure::from_relative(0.05), Exposure::Buried);
+        assert_eq!(Exposure::from_relative(0.3), Exposure::Intermediate);
+        assert_eq!(Exposure::from_relative(0.8), Exposure::Exposed);
+        assert_eq!(max_asa(*b"TRP"), Some(285.0));
+        assert_eq!(max_asa(*b"HOH"), None);
+    }
+}
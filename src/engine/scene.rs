@@ -19,6 +19,7 @@
 //! the opaque [`EntityId`] down. Internal code never walks the
 //! assembly looking up u32s.
 
+use std::ops::Range;
 use std::sync::Arc;
 
 use molex::entity::molecule::id::EntityId;
@@ -29,7 +30,6 @@ use super::annotations::EntityAnnotations;
 use super::entity_view::EntityView;
 use super::positions::EntityPositions;
 use super::scene_state::SceneRenderState;
-use crate::options::DrawingMode;
 
 /// Assembly consumption + derived per-entity state.
 pub(crate) struct Scene {
@@ -147,18 +147,37 @@ impl Scene {
         })
     }
 
-    /// Concatenated per-residue colors across every visible Cartoon-mode
-    /// protein entity, in assembly order. Entities without cached colors
-    /// contribute a default gray block sized to their residue count.
-    pub(crate) fn flat_cartoon_colors(
+    /// Flat residue index range of every visible protein entity, in
+    /// assembly order -- the indexing picking and selection use.
+    pub(crate) fn protein_residue_ranges(
+        &self,
+        annotations: &EntityAnnotations,
+    ) -> Vec<(EntityId, Range<u32>)> {
+        let mut start: u32 = 0;
+        self.visible_entities(annotations)
+            .filter(|(_, _, state)| state.topology.is_protein())
+            .map(|(_, eid, state)| {
+                let end =
+                    start + state.topology.residue_atom_ranges.len() as u32;
+                let range = start..end;
+                start = end;
+                (eid, range)
+            })
+            .collect()
+    }
+
+    /// Concatenated per-residue colors across every visible protein
+    /// entity, in assembly order -- the flat residue numbering of
+    /// [`Self::protein_residue_ranges`], whatever each entity's drawing
+    /// mode. Entities without cached colors contribute a default gray
+    /// block sized to their residue count.
+    pub(crate) fn flat_protein_colors(
         &self,
         annotations: &EntityAnnotations,
     ) -> Vec<[f32; 3]> {
         let mut out = Vec::new();
         for (_, _, state) in self.visible_entities(annotations) {
-            if !state.topology.is_protein()
-                || state.drawing_mode != DrawingMode::Cartoon
-            {
+            if !state.topology.is_protein() {
                 continue;
             }
             if let Some(colors) = &state.per_residue_colors {
@@ -0,0 +1,282 @@
+//! Viewer-side DSSP secondary structure.
+//!
+//! The assembly carries one SS assignment per entity, made when the
+//! structure was loaded or edited, against the reference coordinates.
+//! Trajectory frames and interactive edits move the backbone without
+//! touching it. [`VisoEngine::compute_secondary_structure`] reruns DSSP
+//! ([`crate::util::dssp`]) on the coordinates currently displayed and
+//! stores the result in `EntityAnnotations::computed_ss`. The cartoon
+//! renders it when [`GeometryOptions::ss_source`] is
+//! [`SsSource::Computed`].
+//!
+//! [`GeometryOptions::ss_source`]: crate::options::GeometryOptions::ss_source
+
+use glam::Vec3;
+use molex::entity::molecule::id::EntityId;
+
+use super::VisoEngine;
+use crate::options::overrides::RenderInvalidation;
+use crate::options::SsSource;
+use crate::renderer::entity_topology::EntityTopology;
+use crate::util::dssp::{self, BackboneResidue, DsspCode};
+
+/// DSSP classes of a protein entity at `positions`, in cartoon residue
+/// order. `None` for non-protein entities.
+pub(crate) fn entity_dssp(
+    topology: &EntityTopology,
+    positions: &[Vec3],
+) -> Option<Vec<DsspCode>> {
+    if !topology.is_protein() {
+        return None;
+    }
+    let mut residue = 0;
+    let segments: Vec<Vec<BackboneResidue>> = topology
+        .protein_backbone_chains(positions)
+        .iter()
+        .map(|chain| {
+            (0..chain.residue_count())
+                .map(|i| {
+                    let proline = topology
+                        .residue_names
+                        .get(residue)
+                        .is_some_and(|name| name == b"PRO");
+                    residue += 1;
+                    BackboneResidue {
+                        n: chain.n()[i],
+                        ca: chain.ca()[i],
+                        c: chain.c()[i],
+                        o: chain.o()[i],
+                        proline,
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    Some(dssp::assign(&segments))
+}
+
+impl VisoEngine {
+    /// Run DSSP on every protein entity at its currently displayed
+    /// coordinates (the active trajectory frame, or the edited
+    /// structure) and keep the result.
+    ///
+    /// Cheap enough to call per frame for small proteins, but not run
+    /// automatically for trajectories: hosts call it when they want
+    /// the SS to follow the frame. Assembly edits re-run it on sync
+    /// while `ss_source` is `Computed`.
+    pub fn compute_secondary_structure(&mut self) {
+        self.store_computed_ss();
+        if self.options.geometry.ss_source == SsSource::Computed
+            && self.restamp_ss_overrides()
+        {
+            self.apply_entity_invalidation(
+                RenderInvalidation::RE_MESH | RenderInvalidation::RE_COLOR,
+            );
+        }
+    }
+
+    /// DSSP classes for entity `id` from the last
+    /// [`compute_secondary_structure`](Self::compute_secondary_structure),
+    /// in residue order.
+    #[must_use]
+    pub fn computed_secondary_structure(&self, id: u32) -> Option<&[DsspCode]> {
+        self.annotations
+            .computed_ss
+            .get(&self.entity_id(id)?)
+            .map(Vec::as_slice)
+    }
+
+    /// Follow an `ss_source` change: compute DSSP if it's now the
+    /// source, then restamp. Returns whether any entity's SS changed;
+    /// the caller owns the remesh.
+    pub(crate) fn apply_ss_source(&mut self) -> bool {
+        if self.options.geometry.ss_source == SsSource::Computed {
+            self.store_computed_ss();
+        }
+        self.restamp_ss_overrides()
+    }
+
+    /// Run DSSP on every entity's displayed positions into
+    /// `annotations.computed_ss`.
+    fn store_computed_ss(&mut self) {
+        let computed: Vec<(EntityId, Vec<DsspCode>)> = self
+            .scene
+            .entity_state
+            .iter()
+            .filter_map(|(&id, state)| {
+                let positions = self.scene.positions.get(id)?;
+                Some((id, entity_dssp(&state.topology, positions)?))
+            })
+            .collect();
+        for (id, codes) in computed {
+            let _ = self.annotations.computed_ss.insert(id, codes);
+        }
+    }
+
+    /// Re-resolve every entity's rendered SS (host override, computed
+    /// DSSP, or the assembly's), bumping `mesh_version` on the ones
+    /// that changed. Returns whether any did.
+    fn restamp_ss_overrides(&mut self) -> bool {
+        let ids: Vec<EntityId> =
+            self.scene.entity_state.keys().copied().collect();
+        let mut changed = false;
+        for id in ids {
+            let ss = self.annotations.ss_override_for(&self.options, id);
+            if self
+                .scene
+                .entity_state
+                .get(&id)
+                .is_some_and(|state| state.ss_override == ss)
+            {
+                continue;
+            }
+            let version = self.scene.bump_mesh_version();
+            if let Some(state) = self.scene.entity_state.get_mut(&id) {
+                state.ss_override = ss;
+                state.mesh_version = version;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::fmt::Write;
+
+    use molex::MoleculeEntity;
+
+    use super::*;
+    use crate::engine::entity_view::derive_topology;
+
+    /// Atom bonded to `c` at `length` Å, with angle `angle` at `c` and
+    /// dihedral `torsion` over `a-b-c-new` (degrees).
+    fn place(
+        a: Vec3,
+        b: Vec3,
+        c: Vec3,
+        length: f32,
+        angle: f32,
+        torsion: f32,
+    ) -> Vec3 {
+        let bc = (c - b).normalize();
+        let n = (b - a).cross(bc).normalize();
+        let m = n.cross(bc);
+        let (angle, torsion) = (angle.to_radians(), torsion.to_radians());
+        c - bc * length * angle.cos()
+            + m * length * angle.sin() * torsion.cos()
+            + n * length * angle.sin() * torsion.sin()
+    }
+
+    /// `[N, CA, C, O]` of an ideal backbone with constant `phi`/`psi`.
+    fn backbone(len: usize, phi: f32, psi: f32) -> Vec<[Vec3; 4]> {
+        let mut n = Vec3::ZERO;
+        let mut ca = Vec3::X * 1.458;
+        let mut c = place(Vec3::Y, n, ca, 1.525, 111.2, phi);
+        let mut residues = Vec::with_capacity(len);
+        for _ in 0..len {
+            let next_n = place(n, ca, c, 1.329, 116.2, psi);
+            let o = place(next_n, ca, c, 1.231, 120.5, 180.0);
+            residues.push([n, ca, c, o]);
+            let next_ca = place(ca, c, next_n, 1.458, 121.7, 180.0);
+            let next_c = place(c, next_n, next_ca, 1.525, 111.2, phi);
+            (n, ca, c) = (next_n, next_ca, next_c);
+        }
+        residues
+    }
+
+    /// One chain A protein entity from `(residue number, backbone)`.
+    fn protein(residues: &[(usize, [Vec3; 4])]) -> MoleculeEntity {
+        let mut pdb = String::new();
+        for (i, (seq, atoms)) in residues.iter().enumerate() {
+            for (j, (name, p)) in
+                ["N", "CA", "C", "O"].iter().zip(atoms).enumerate()
+            {
+                let element = &name[..1];
+                let _ = writeln!(
+                    pdb,
+                    "ATOM  {:>5}  {name:<3} ALA A{seq:>4}    \
+                     {:>8.3}{:>8.3}{:>8.3}  1.00  0.00           {element}",
+                    i * 4 + j + 1,
+                    p.x,
+                    p.y,
+                    p.z,
+                );
+            }
+        }
+        molex::adapters::pdb::pdb_str_to_entities(&pdb)
+            .unwrap()
+            .into_iter()
+            .find(|e| e.as_protein().is_some())
+            .unwrap()
+    }
+
+    fn dssp_letters(entity: &MoleculeEntity) -> String {
+        let topology = derive_topology(entity, &[]);
+        let codes = entity_dssp(&topology, &entity.positions()).unwrap();
+        codes.iter().map(|c| c.letter()).collect()
+    }
+
+    /// Number the residues of each backbone run from `first`.
+    fn numbered(
+        first: usize,
+        run: &[[Vec3; 4]],
+    ) -> impl Iterator<Item = (usize, [Vec3; 4])> + '_ {
+        run.iter().enumerate().map(move |(i, r)| (first + i, *r))
+    }
+
+    #[test]
+    fn ideal_helix_is_alpha() {
+        let helix = backbone(14, -57.0, -47.0);
+        let letters =
+            dssp_letters(&protein(&numbered(1, &helix).collect::<Vec<_>>()));
+        assert_eq!(letters, "CHHHHHHHHHHHHC");
+    }
+
+    #[test]
+    fn paired_strands_form_a_sheet() {
+        // The second strand is the first turned end over end about the
+        // sheet normal and laid 4.8 Å alongside it.
+        let strand = backbone(7, -139.0, 135.0);
+        let axis = (strand[6][1] - strand[0][1]).normalize();
+        let co = strand[0][3] - strand[0][2];
+        let side = co.reject_from(axis).normalize();
+        let flip =
+            glam::Quat::from_axis_angle(axis.cross(side), std::f32::consts::PI);
+        let centroid = strand.iter().map(|r| r[1]).sum::<Vec3>() / 7.0;
+        let partner: Vec<[Vec3; 4]> = strand
+            .iter()
+            .map(|r| r.map(|p| centroid + flip * (p - centroid) + side * 4.8))
+            .collect();
+        let residues: Vec<_> =
+            numbered(1, &strand).chain(numbered(20, &partner)).collect();
+        assert_eq!(dssp_letters(&protein(&residues)), "CEEEEECCEEEEEC");
+    }
+
+    #[test]
+    fn codes_follow_cartoon_residue_order() {
+        // A lone strand, then a helix in its own backbone segment: the
+        // helix codes must land on the helix residues.
+        let strand = backbone(7, -139.0, 135.0);
+        let helix: Vec<[Vec3; 4]> = backbone(14, -57.0, -47.0)
+            .into_iter()
+            .map(|r| r.map(|p| p + Vec3::splat(40.0)))
+            .collect();
+        let entity = protein(
+            &numbered(1, &strand)
+                .chain(numbered(30, &helix))
+                .collect::<Vec<_>>(),
+        );
+        let topology = derive_topology(&entity, &[]);
+        let cartoon_residues: usize = topology
+            .protein_backbone_layout
+            .iter()
+            .map(|segment| segment.ca.len())
+            .sum();
+        assert_eq!(topology.protein_backbone_layout.len(), 2);
+        assert_eq!(cartoon_residues, 21);
+        assert_eq!(dssp_letters(&entity), "CCCCCCCCHHHHHHHHHHHHC");
+    }
+}
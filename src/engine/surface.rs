@@ -3,20 +3,58 @@
 //! Surfaces are generated on a background thread through
 //! [`crate::engine::surface_regen::regenerate_surfaces`] and rendered
 //! via the shared `IsosurfaceRenderer`. Multiple entities can each
-//! have surfaces — their meshes are concatenated before upload.
+//! have surfaces — each is meshed and uploaded on its own. A surface
+//! can also cover just some residues or atoms of its entity (a pocket
+//! or a domain), in which case the rest of the entity still shapes it,
+//! as do, for an SES, the nearby atoms of the other visible entities.
 
+use glam::Vec3;
 use molex::entity::molecule::id::EntityId;
+use rustc_hash::FxHashMap;
 
 use super::annotations::EntityAnnotations;
-use crate::options::SurfaceKindOption;
+use super::scene::Scene;
+use super::VisoEngine;
+use crate::options::{DisplayOptions, DisplayOverrides, SurfaceKindOption};
 
 /// Which kind of molecular surface to generate.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) enum SurfaceKind {
     /// Smooth Gaussian blob surface.
     Gaussian,
     /// Solvent-excluded / Connolly surface.
     Ses,
+    /// Solvent-accessible surface (atom spheres grown by the probe).
+    Sas,
+    /// Van der Waals surface (union of atom spheres).
+    Vdw,
+    /// Dots on the exposed van der Waals surface.
+    Dots,
+}
+
+impl SurfaceKind {
+    /// The surface kind of a display option, `None` for no surface.
+    pub(crate) const fn from_option(option: SurfaceKindOption) -> Option<Self> {
+        match option {
+            SurfaceKindOption::None => None,
+            SurfaceKindOption::Gaussian => Some(Self::Gaussian),
+            SurfaceKindOption::Ses => Some(Self::Ses),
+            SurfaceKindOption::Sas => Some(Self::Sas),
+            SurfaceKindOption::Vdw => Some(Self::Vdw),
+            SurfaceKindOption::Dots => Some(Self::Dots),
+        }
+    }
+
+    /// The display option naming this kind.
+    pub(crate) const fn option(self) -> SurfaceKindOption {
+        match self {
+            Self::Gaussian => SurfaceKindOption::Gaussian,
+            Self::Ses => SurfaceKindOption::Ses,
+            Self::Sas => SurfaceKindOption::Sas,
+            Self::Vdw => SurfaceKindOption::Vdw,
+            Self::Dots => SurfaceKindOption::Dots,
+        }
+    }
 }
 
 /// Per-entity surface parameters.
@@ -24,9 +62,10 @@ pub(crate) enum SurfaceKind {
 pub(crate) struct EntitySurface {
     /// Which surface type.
     pub(crate) kind: SurfaceKind,
-    /// Grid resolution in Angstroms (lower = finer).
+    /// Grid resolution in Angstroms (lower = finer); the spacing
+    /// between dots for dot surfaces.
     pub(crate) resolution: f32,
-    /// Probe radius for SES (Angstroms, default 1.4).
+    /// Probe radius for SES and SAS (Angstroms, default 1.4).
     pub(crate) probe_radius: f32,
     /// Gaussian isosurface level (only used for Gaussian kind).
     pub(crate) level: f32,
@@ -34,6 +73,13 @@ pub(crate) struct EntitySurface {
     pub(crate) color: [f32; 4],
     /// Whether this surface is visible.
     pub(crate) visible: bool,
+    /// Entity-local indices of the atoms the surface covers, sorted;
+    /// `None` covers the whole entity. The other atoms still shape the
+    /// surface but get none of it.
+    pub(crate) atoms: Option<Vec<u32>>,
+    /// Whether the part on the camera side of the focus point is cut
+    /// away.
+    pub(crate) cutaway: bool,
 }
 
 impl Default for EntitySurface {
@@ -45,10 +91,111 @@ impl Default for EntitySurface {
             level: 0.5,
             color: [0.7, 0.7, 0.7, 0.35],
             visible: true,
+            atoms: None,
+            cutaway: false,
         }
     }
 }
 
+impl EntitySurface {
+    /// A visible surface of `kind` at the kind's default resolution.
+    pub(crate) fn new(kind: SurfaceKind, color: [f32; 4]) -> Self {
+        Self {
+            kind,
+            resolution: kind.option().default_resolution(),
+            color,
+            ..Default::default()
+        }
+    }
+}
+
+/// The surface to mesh for an entity, or `None` for no surface.
+///
+/// An explicit per-entity surface takes priority over the appearance
+/// override's kind and opacity, which take priority over the global
+/// ones. Probe radius, grid spacing and cutaway resolve the same way
+/// for either kind of surface, the spacing defaulting per kind.
+pub(super) fn resolve_surface(
+    explicit: Option<&EntitySurface>,
+    overrides: Option<&DisplayOverrides>,
+    display: &DisplayOptions,
+) -> Option<EntitySurface> {
+    let mut surface = match explicit {
+        Some(s) if !s.visible => return None,
+        Some(s) => s.clone(),
+        None => {
+            let kind = overrides
+                .and_then(|o| o.surface_kind)
+                .unwrap_or_else(|| display.surface_kind());
+            let opacity = overrides
+                .and_then(|o| o.surface_opacity)
+                .unwrap_or_else(|| display.surface_opacity());
+            EntitySurface::new(
+                SurfaceKind::from_option(kind)?,
+                [0.7, 0.7, 0.7, opacity],
+            )
+        }
+    };
+    surface.probe_radius = overrides
+        .and_then(|o| o.surface_probe_radius)
+        .unwrap_or_else(|| display.surface_probe_radius());
+    surface.resolution = overrides
+        .and_then(|o| o.surface_resolution)
+        .unwrap_or_else(|| display.surface_resolution(surface.kind.option()));
+    surface.cutaway = overrides
+        .and_then(|o| o.surface_cutaway)
+        .unwrap_or_else(|| display.surface_cutaway());
+    Some(surface)
+}
+
+/// Atoms of the visible entities other than `eid` that shape its
+/// `surface`, as `(positions, radii)`. Only a partial SES has any: the
+/// atoms within a probe diameter plus both atoms' radii of the bounding
+/// box of the covered atoms (`own` being the entity's positions and
+/// radii).
+pub(super) fn surface_occluders(
+    scene: &Scene,
+    annotations: &EntityAnnotations,
+    eid: EntityId,
+    surface: &EntitySurface,
+    own: (&[Vec3], &[f32]),
+) -> (Vec<Vec3>, Vec<f32>) {
+    let mut positions = Vec::new();
+    let mut radii = Vec::new();
+    let Some(atoms) = surface
+        .atoms
+        .as_ref()
+        .filter(|_| surface.kind == SurfaceKind::Ses)
+    else {
+        return (positions, radii);
+    };
+    let subset = atoms.iter().filter_map(|&a| {
+        let a = a as usize;
+        Some((*own.0.get(a)?, *own.1.get(a)?))
+    });
+    let Some((lo, hi, widest)) = subset.fold(None, |bounds, (p, r)| {
+        let (lo, hi, widest) = bounds.unwrap_or((p, p, r));
+        Some((lo.min(p), hi.max(p), widest.max(r)))
+    }) else {
+        return (positions, radii);
+    };
+    for se in scene.current.entities() {
+        if se.id() == eid || !annotations.is_visible(se.id()) {
+            continue;
+        }
+        for (p, atom) in se.positions().into_iter().zip(se.atom_set()) {
+            let radius = atom.element.vdw_radius();
+            let reach =
+                Vec3::splat(widest + radius + 2.0 * surface.probe_radius);
+            if p.cmpge(lo - reach).all() && p.cmple(hi + reach).all() {
+                positions.push(p);
+                radii.push(radius);
+            }
+        }
+    }
+    (positions, radii)
+}
+
 // ── EntityAnnotations: surface mutators ──
 
 impl EntityAnnotations {
@@ -105,3 +252,108 @@ impl EntityAnnotations {
         had || global_kind != SurfaceKindOption::None
     }
 }
+
+// ── Selection surfaces ──
+
+impl VisoEngine {
+    /// Surface just the given flat residues. Each protein entity owning
+    /// any of them gets a surface of `kind` over those residues,
+    /// replacing its previous surface; the entity's other atoms still
+    /// shape it (an SES probe cannot roll through them). Returns the
+    /// number of entities surfaced, 0 for [`SurfaceKindOption::None`].
+    pub fn add_residue_surface(
+        &mut self,
+        residues: &[u32],
+        kind: SurfaceKindOption,
+    ) -> usize {
+        let Some(kind) = SurfaceKind::from_option(kind) else {
+            return 0;
+        };
+        let ranges = self.scene.protein_residue_ranges(&self.annotations);
+        let mut atoms: FxHashMap<EntityId, Vec<u32>> = FxHashMap::default();
+        for &index in residues {
+            let Some((eid, range)) =
+                ranges.iter().find(|(_, r)| r.contains(&index))
+            else {
+                continue;
+            };
+            let Some(state) = self.scene.entity_state.get(eid) else {
+                continue;
+            };
+            let local = (index - range.start) as usize;
+            if let Some(atom_range) =
+                state.topology.residue_atom_ranges.get(local)
+            {
+                atoms.entry(*eid).or_default().extend(atom_range.clone());
+            }
+        }
+
+        self.set_atom_surfaces(atoms, kind)
+    }
+
+    /// Surface just the given entity-local atoms of entity `id` with a
+    /// surface of `kind`, replacing its previous surface; the entity's
+    /// other atoms still shape it as for
+    /// [`add_residue_surface`](Self::add_residue_surface). Out-of-range
+    /// atoms are ignored. Returns whether a surface was added.
+    pub fn add_atom_surface(
+        &mut self,
+        id: u32,
+        atoms: &[u32],
+        kind: SurfaceKindOption,
+    ) -> bool {
+        let (Some(kind), Some(eid)) =
+            (SurfaceKind::from_option(kind), self.entity_id(id))
+        else {
+            return false;
+        };
+        let Some(state) = self.scene.entity_state.get(&eid) else {
+            return false;
+        };
+        let count = state.topology.atom_elements.len();
+        let atoms: Vec<u32> = atoms
+            .iter()
+            .copied()
+            .filter(|&atom| (atom as usize) < count)
+            .collect();
+        if atoms.is_empty() {
+            return false;
+        }
+        self.set_atom_surfaces(std::iter::once((eid, atoms)).collect(), kind)
+            > 0
+    }
+
+    /// Give each entity a `kind` surface over its listed atoms. Returns
+    /// the number of entities surfaced.
+    fn set_atom_surfaces(
+        &mut self,
+        atoms: FxHashMap<EntityId, Vec<u32>>,
+        kind: SurfaceKind,
+    ) -> usize {
+        let color = [0.7, 0.7, 0.7, self.options.display.surface_opacity()];
+        let count = atoms.len();
+        self.annotations_mut().set_surfaces(atoms.into_iter().map(
+            |(eid, mut atoms)| {
+                atoms.sort_unstable();
+                atoms.dedup();
+                let surface = EntitySurface {
+                    atoms: Some(atoms),
+                    ..EntitySurface::new(kind, color)
+                };
+                (eid, surface)
+            },
+        ));
+        count
+    }
+
+    /// Surface the selected residues; see
+    /// [`add_residue_surface`](Self::add_residue_surface).
+    pub fn surface_selection(&mut self, kind: SurfaceKindOption) -> usize {
+        let residues: Vec<u32> = self
+            .selected_residues()
+            .iter()
+            .filter_map(|&i| u32::try_from(i).ok())
+            .collect();
+        self.add_residue_surface(&residues, kind)
+    }
+}
@@ -1,75 +1,576 @@
-//! Background regeneration of all isosurface meshes (density maps,
-//! entity surfaces, cavities).
+//! Background regeneration of isosurface meshes (density maps, entity
+//! surfaces, cavities).
 //!
 //! Isosurface meshing is kicked off from several annotation and density
-//! mutation paths; the completed mesh is shipped through an
-//! `mpsc::channel` to the main thread, which drains the receiver each
-//! frame and uploads the latest mesh to the GPU.
+//! mutation paths. Every density map, entity surface and entity cavity
+//! set is its own mesh, identified by a [`SurfaceKey`]. Each call to
+//! [`regenerate_surfaces`] hashes the inputs of every mesh that should
+//! exist and only queues jobs for those whose hash changed, so editing
+//! one surface leaves the others alone.
+//!
+//! Jobs run in order on one long-lived worker thread. Queuing a job for
+//! a key cancels the key's previous job: the worker skips it if it has
+//! not started, and drops its result if it has. Dragging the sigma
+//! slider therefore meshes at most the map's current level plus the one
+//! already in progress. Completed meshes are shipped through an
+//! `mpsc::channel` to the main thread, which swaps each key's GPU
+//! buffers independently.
 //!
 //! This module owns the sender side of that channel through a thin
 //! [`SurfaceRegen`] holder. Decoupling it from `GpuPipeline` (which
 //! keeps the receiver) avoids exposing a worker-bound sender as a
 //! crate-internal API from the renderer back to engine code.
 
-use std::sync::mpsc;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+
+use glam::Vec3;
+use molex::entity::molecule::id::EntityId;
+use molex::entity::surface::Density;
+use rustc_hash::{FxHashMap, FxHasher};
 
 use super::annotations::EntityAnnotations;
-use super::density_store::DensityStore;
+use super::density_store::{DensityStore, DensityStyle};
+use super::electrostatics::{atom_charges, color_vertices, PotentialField};
 use super::scene::Scene;
-use super::surface::{EntitySurface, SurfaceKind};
-use crate::options::{SurfaceKindOption, VisoOptions};
-use crate::renderer::geometry::isosurface::IsosurfaceVertex;
+use super::surface::{
+    resolve_surface, surface_occluders, EntitySurface, SurfaceKind,
+};
+use crate::options::{SurfaceColoring, VisoOptions};
+use crate::renderer::geometry::isosurface::density::{ContourLevel, CropBox};
+use crate::renderer::geometry::isosurface::residue_tags::tag_residues;
+use crate::renderer::geometry::isosurface::surface_patch::restrict_to_atoms;
+use crate::renderer::geometry::isosurface::{
+    isosurface_kind, IsosurfaceVertex, MeshTopology,
+};
 
-/// Worker→main message type carrying a completed isosurface mesh.
-pub(crate) type MeshMessage = (Vec<IsosurfaceVertex>, Vec<u32>);
+/// Which isosurface a mesh is. Also the draw order of the meshes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum SurfaceKey {
+    /// A density map, by its [`DensityStore`] id.
+    Density(u32),
+    /// An entity's molecular surface.
+    Surface(EntityId),
+    /// An entity's internal cavities.
+    Cavities(EntityId),
+}
+
+/// Worker→main message carrying one completed isosurface mesh.
+pub(crate) struct MeshMessage {
+    /// The mesh this replaces.
+    pub(crate) key: SurfaceKey,
+    /// Dispatch order across all keys. A message older than the last
+    /// one applied for its key is stale and must be ignored.
+    pub(crate) generation: u64,
+    /// `(vertices, indices)`, or `None` to remove the mesh.
+    pub(crate) mesh: Option<(Vec<IsosurfaceVertex>, Vec<u32>)>,
+    /// Primitive the indices describe.
+    pub(crate) topology: MeshTopology,
+}
 
-/// Owner of the sender side of the background isosurface-mesh channel.
+/// Inputs of one isosurface mesh.
+enum SurfaceInput {
+    Density {
+        map: Arc<Density>,
+        levels: Vec<ContourLevel>,
+        opacity: f32,
+        crop: Option<CropBox>,
+        wire: bool,
+    },
+    Surface {
+        positions: Vec<Vec3>,
+        radii: Vec<f32>,
+        surface: EntitySurface,
+        /// Atoms of other visible entities around a partial SES, with
+        /// their radii: they shape the surface but get none of it.
+        occluders: (Vec<Vec3>, Vec<f32>),
+        /// Potential to color by and its ±range in kT/e, replacing the
+        /// flat surface color.
+        potential: Option<(Arc<PotentialField>, f32)>,
+        /// Residue pick ID of each atom (empty for entities without
+        /// residues in the pick numbering).
+        residues: Vec<u32>,
+        /// Color each vertex by its residue on the GPU.
+        by_residue: bool,
+    },
+    Cavities {
+        positions: Vec<Vec3>,
+        radii: Vec<f32>,
+    },
+}
+
+impl SurfaceInput {
+    /// Primitive of the generated mesh's indices.
+    const fn topology(&self) -> MeshTopology {
+        match self {
+            Self::Density { wire: true, .. } => MeshTopology::Lines,
+            Self::Surface { surface, .. }
+                if matches!(surface.kind, SurfaceKind::Dots) =>
+            {
+                MeshTopology::Points
+            }
+            _ => MeshTopology::Triangles,
+        }
+    }
+}
+
+/// A queued mesh job.
+struct SurfaceJob {
+    key: SurfaceKey,
+    generation: u64,
+    cancel: Arc<AtomicBool>,
+    input: SurfaceInput,
+}
+
+/// The last job queued for a key.
+struct Dispatched {
+    /// Hash of the job's inputs.
+    hash: u64,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Owner of the sender side of the background isosurface-mesh channel,
+/// plus the per-mesh dispatch state.
 ///
 /// The matching receiver lives on
 /// [`crate::renderer::GpuPipeline`]; the two ends are constructed
 /// together in [`crate::engine::VisoEngine::new`].
 pub(crate) struct SurfaceRegen {
-    /// Sender used by [`regenerate_surfaces`] to ship completed meshes
-    /// back to the main thread.
+    /// Sender used to ship removals straight to the main thread; the
+    /// worker holds a clone for completed meshes.
     pub(crate) tx: mpsc::Sender<MeshMessage>,
+    /// Job queue of the worker thread, `None` if it failed to spawn.
+    jobs: Option<mpsc::Sender<SurfaceJob>>,
+    /// Last dispatch generation handed out.
+    generation: u64,
+    /// Every mesh that currently exists or is being made.
+    dispatched: FxHashMap<SurfaceKey, Dispatched>,
 }
 
 impl SurfaceRegen {
-    /// Wrap an existing sender.
+    /// Wrap an existing sender and start the worker thread.
     pub(crate) fn new(tx: mpsc::Sender<MeshMessage>) -> Self {
-        Self { tx }
+        let (jobs, queue) = mpsc::channel::<SurfaceJob>();
+        let worker_tx = tx.clone();
+        let spawned = std::thread::Builder::new()
+            .name("viso-surface-regen".into())
+            .spawn(move || run_jobs(&queue, &worker_tx));
+        let jobs = match spawned {
+            Ok(_) => Some(jobs),
+            Err(e) => {
+                log::warn!("failed to spawn surface regen thread: {e}");
+                None
+            }
+        };
+        Self {
+            tx,
+            jobs,
+            generation: 0,
+            dispatched: FxHashMap::default(),
+        }
+    }
+
+    fn next_generation(&mut self) -> u64 {
+        self.generation += 1;
+        self.generation
+    }
+
+    /// Reconcile the dispatched meshes with `wanted`, the `(key, input
+    /// hash)` of every mesh that should exist. Removes meshes no longer
+    /// wanted (cancelling their jobs) and returns the keys whose inputs
+    /// changed; [`Self::dispatch`] must follow for each of them.
+    fn plan(&mut self, wanted: &[(SurfaceKey, u64)]) -> Vec<SurfaceKey> {
+        let stale: Vec<SurfaceKey> = self
+            .dispatched
+            .keys()
+            .filter(|key| !wanted.iter().any(|(k, _)| k == *key))
+            .copied()
+            .collect();
+        for key in stale {
+            if let Some(old) = self.dispatched.remove(&key) {
+                old.cancel.store(true, Ordering::Relaxed);
+            }
+            let generation = self.next_generation();
+            let _ = self.tx.send(MeshMessage {
+                key,
+                generation,
+                mesh: None,
+                topology: MeshTopology::Triangles,
+            });
+        }
+        wanted
+            .iter()
+            .filter(|(key, hash)| {
+                self.dispatched.get(key).is_none_or(|d| d.hash != *hash)
+            })
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    /// Queue a job for `key`, cancelling the key's previous one.
+    fn dispatch(&mut self, key: SurfaceKey, hash: u64, input: SurfaceInput) {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let previous = self.dispatched.insert(
+            key,
+            Dispatched {
+                hash,
+                cancel: Arc::clone(&cancel),
+            },
+        );
+        if let Some(previous) = previous {
+            previous.cancel.store(true, Ordering::Relaxed);
+        }
+        let generation = self.next_generation();
+        let Some(jobs) = &self.jobs else {
+            return;
+        };
+        let job = SurfaceJob {
+            key,
+            generation,
+            cancel,
+            input,
+        };
+        if jobs.send(job).is_err() {
+            log::warn!("surface regen worker has exited");
+        }
+    }
+}
+
+/// Worker loop: mesh each job that is still current and ship it.
+fn run_jobs(
+    queue: &mpsc::Receiver<SurfaceJob>,
+    tx: &mpsc::Sender<MeshMessage>,
+) {
+    for job in queue {
+        if job.cancel.load(Ordering::Relaxed) {
+            continue;
+        }
+        let mesh = generate(&job.input, &job.cancel);
+        if job.cancel.load(Ordering::Relaxed) {
+            log::debug!("dropping superseded {:?} mesh", job.key);
+            continue;
+        }
+        log::info!(
+            "{:?} mesh: {} verts, {} triangles",
+            job.key,
+            mesh.0.len(),
+            mesh.1.len() / 3,
+        );
+        let message = MeshMessage {
+            key: job.key,
+            generation: job.generation,
+            mesh: Some(mesh),
+            topology: job.input.topology(),
+        };
+        if tx.send(message).is_err() {
+            log::warn!("surface mesh channel send failed");
+            return;
+        }
+    }
+}
+
+/// Mesh an entity surface over all of `positions`.
+fn mesh_surface(
+    positions: &[Vec3],
+    radii: &[f32],
+    surface: &EntitySurface,
+    cancel: &AtomicBool,
+) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
+    use crate::renderer::geometry::isosurface::{
+        atom_spheres, gaussian_surface, ses,
+    };
+
+    match surface.kind {
+        SurfaceKind::Gaussian => gaussian_surface::generate_gaussian_surface(
+            positions,
+            radii,
+            surface.resolution,
+            surface.level,
+            surface.color,
+            cancel,
+        ),
+        SurfaceKind::Ses => ses::generate_ses(
+            positions,
+            radii,
+            Some(surface.probe_radius),
+            surface.resolution,
+            surface.color,
+            cancel,
+        ),
+        SurfaceKind::Sas | SurfaceKind::Vdw => {
+            let expand = if surface.kind == SurfaceKind::Sas {
+                surface.probe_radius
+            } else {
+                0.0
+            };
+            atom_spheres::generate_sphere_surface(
+                positions,
+                radii,
+                expand,
+                surface.resolution,
+                surface.color,
+                cancel,
+            )
+        }
+        SurfaceKind::Dots => atom_spheres::generate_dots(
+            positions,
+            radii,
+            surface.resolution,
+            surface.color,
+        ),
+    }
+}
+
+/// Run the generator for one mesh. Stops between stages once `cancel`
+/// is set; the partial result is dropped by [`run_jobs`].
+fn generate(
+    input: &SurfaceInput,
+    cancel: &AtomicBool,
+) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
+    use crate::renderer::geometry::isosurface::{cancelled, cavity, density};
+
+    match input {
+        SurfaceInput::Density {
+            map,
+            levels,
+            opacity,
+            crop,
+            wire,
+        } => density::generate_density_contours(
+            map,
+            levels,
+            *opacity,
+            crop.as_ref(),
+            *wire,
+            cancel,
+        ),
+        SurfaceInput::Surface {
+            positions,
+            radii,
+            surface,
+            occluders,
+            potential,
+            residues,
+            by_residue,
+        } => {
+            let shaping = [positions.as_slice(), &occluders.0].concat();
+            let shaping_radii = [radii.as_slice(), &occluders.1].concat();
+            let (mut vertices, mut indices) =
+                mesh_surface(&shaping, &shaping_radii, surface, cancel);
+            if cancelled(cancel) {
+                return (Vec::new(), Vec::new());
+            }
+            if let Some(atoms) = &surface.atoms {
+                let mut include = vec![false; shaping.len()];
+                for &atom in atoms {
+                    if let Some(slot) = include.get_mut(atom as usize) {
+                        *slot = true;
+                    }
+                }
+                (vertices, indices) = restrict_to_atoms(
+                    &vertices,
+                    &indices,
+                    input.topology(),
+                    &shaping,
+                    &include,
+                );
+            }
+            if surface.cutaway {
+                for v in &mut vertices {
+                    v.kind |= isosurface_kind::CUTAWAY;
+                }
+            }
+            if let Some((field, range)) = potential {
+                color_vertices(&mut vertices, field, *range);
+            }
+            if !residues.is_empty() {
+                tag_residues(&mut vertices, positions, residues, *by_residue);
+            }
+            (vertices, indices)
+        }
+        // Cavities are meshed on a 0.6 Å grid — coarser than SES
+        // because cavity detection is topological (flood fill from
+        // grid boundary), so finer voxels can flip whether a thin
+        // SES-wall separates a cavity from the exterior. 0.6 Å was
+        // verified to detect the expected number of cavities on
+        // benchmark structures (e.g. 1bbc has 3).
+        SurfaceInput::Cavities { positions, radii } => {
+            let set = cavity::generate_cavities(
+                positions,
+                radii,
+                Some(1.4),
+                0.6,
+                cancel,
+            );
+            let mut vertices = Vec::new();
+            let mut indices = Vec::new();
+            for mesh in &set.meshes {
+                let base = vertices.len() as u32;
+                vertices.extend(mesh.vertices.iter().copied());
+                indices.extend(mesh.indices.iter().map(|&idx| idx + base));
+            }
+            (vertices, indices)
+        }
+    }
+}
+
+/// Hash of an entity's atoms as a surface generator sees them.
+fn hash_atoms(hasher: &mut FxHasher, positions: &[Vec3], radii: &[f32]) {
+    for p in positions {
+        p.to_array().map(f32::to_bits).hash(hasher);
+    }
+    for r in radii {
+        r.to_bits().hash(hasher);
+    }
+}
+
+/// Hash of an entity surface's mesh inputs. `occluders` are the other
+/// entities' atoms shaping it; `potential` is the hash of the potential
+/// field plus the color range, when colored by it; `residues` are the
+/// atoms' residue pick IDs, which shift when an entity before this one
+/// is shown or hidden.
+fn surface_hash(
+    positions: &[Vec3],
+    radii: &[f32],
+    surface: &EntitySurface,
+    occluders: &(Vec<Vec3>, Vec<f32>),
+    potential: Option<(u64, f32)>,
+    residues: (&[u32], bool),
+) -> u64 {
+    let mut hasher = FxHasher::default();
+    hash_atoms(&mut hasher, positions, radii);
+    hash_atoms(&mut hasher, &occluders.0, &occluders.1);
+    surface.kind.hash(&mut hasher);
+    [surface.resolution, surface.probe_radius, surface.level]
+        .map(f32::to_bits)
+        .hash(&mut hasher);
+    surface.color.map(f32::to_bits).hash(&mut hasher);
+    (&surface.atoms, surface.cutaway).hash(&mut hasher);
+    potential
+        .map(|(field, range)| (field, range.to_bits()))
+        .hash(&mut hasher);
+    residues.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Residue pick ID (flat residue index + 1) of every atom of `eid`, or
+/// nothing if the entity has no residues in the pick numbering.
+fn atom_pick_ids(
+    scene: &Scene,
+    eid: EntityId,
+    residue_ranges: &[(EntityId, Range<u32>)],
+) -> Vec<u32> {
+    let Some((_, range)) = residue_ranges.iter().find(|(id, _)| *id == eid)
+    else {
+        return Vec::new();
+    };
+    scene.entity_state.get(&eid).map_or_else(Vec::new, |state| {
+        state
+            .topology
+            .atom_residue_index
+            .iter()
+            .map(|&residue| range.start + residue + 1)
+            .collect()
+    })
+}
+
+/// The potential electrostatic surfaces are colored by, with a hash of
+/// its inputs: the map marked as the potential source (by id and store
+/// generation), else point charges on the atoms of every visible
+/// entity.
+fn potential_field(
+    scene: &Scene,
+    annotations: &EntityAnnotations,
+    density: &DensityStore,
+) -> (Arc<PotentialField>, u64) {
+    let mut hasher = FxHasher::default();
+    if let Some((id, entry)) = density.potential() {
+        (id, entry.generation).hash(&mut hasher);
+        return (
+            Arc::new(PotentialField::Map(Arc::clone(&entry.map))),
+            hasher.finish(),
+        );
+    }
+    let mut charges = Vec::new();
+    for se in scene.current.entities() {
+        if !annotations.is_visible(se.id()) {
+            continue;
+        }
+        charges.extend(
+            se.positions()
+                .into_iter()
+                .zip(atom_charges(se))
+                .filter(|(_, q)| *q != 0.0),
+        );
     }
+    for (p, q) in &charges {
+        (p.to_array().map(f32::to_bits), q.to_bits()).hash(&mut hasher);
+    }
+    (Arc::new(PotentialField::Coulomb(charges)), hasher.finish())
 }
 
-/// Regenerate all isosurface meshes (density + entity surfaces +
-/// cavities) on a background thread.
+/// Hash of an entity's cavity inputs.
+fn cavity_hash(positions: &[Vec3], radii: &[f32]) -> u64 {
+    let mut hasher = FxHasher::default();
+    hash_atoms(&mut hasher, positions, radii);
+    hasher.finish()
+}
+
+/// Hash of a density map's store generation and zone crop.
+fn density_hash(generation: u64, crop: Option<&CropBox>) -> u64 {
+    let mut hasher = FxHasher::default();
+    generation.hash(&mut hasher);
+    if let Some(crop) = crop {
+        let (center, radius) = crop.sphere.unwrap_or(([0.0; 3], 0.0));
+        for v in crop.min.iter().chain(&crop.max).chain(&center) {
+            v.to_bits().hash(&mut hasher);
+        }
+        radius.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Regenerate the isosurface meshes (density + entity surfaces +
+/// cavities) whose inputs changed, on the background worker.
 ///
 /// Collects atom positions + radii from each entity that has a surface
-/// or cavity rendering enabled, runs the appropriate generator,
-/// concatenates all meshes, and sends the result to the isosurface
-/// mesh channel (shared with density map rendering).
+/// or cavity rendering enabled, hashes every mesh's inputs, queues jobs
+/// for the changed ones and removes meshes that are no longer shown.
+/// Density maps are keyed on their store generation, which every
+/// parameter change bumps, and on their zone's crop, which moves with
+/// the view center. Volume-style maps are ray-marched, not meshed, so
+/// they drop out of the wanted set. Electrostatically colored surfaces
+/// also hash the potential they sample, so moving a charged ligand or
+/// picking another potential map recolors them. Protein surfaces tag
+/// their vertices with residue pick IDs for picking and per-residue
+/// coloring; the colors themselves are read on the GPU, so color
+/// scheme changes need no remesh.
 pub(crate) fn regenerate_surfaces(
     scene: &Scene,
     annotations: &EntityAnnotations,
     density: &DensityStore,
     options: &VisoOptions,
-    regen: &SurfaceRegen,
+    regen: &mut SurfaceRegen,
 ) {
-    use crate::renderer::geometry::isosurface::{
-        cavity, gaussian_surface, ses,
-    };
-
     let all_entities = scene.current.entities();
     let palette = options.display.backbone_palette();
-    let global_kind = options.display.surface_kind();
-    let global_opacity = options.display.surface_opacity();
     let global_show_cavities = options.display.show_cavities();
 
-    // Collect jobs: (positions, radii, surface params with color)
-    let mut jobs: Vec<(Vec<glam::Vec3>, Vec<f32>, EntitySurface)> = Vec::new();
-    // Cavity jobs: (positions, radii). Color is the fixed CAVITY_RGBA
-    // constant — cavities don't pick up per-entity coloring.
-    let mut cavity_jobs: Vec<(Vec<glam::Vec3>, Vec<f32>)> = Vec::new();
+    let mut wanted: Vec<(SurfaceKey, u64)> = Vec::new();
+    let mut inputs: FxHashMap<SurfaceKey, SurfaceInput> = FxHashMap::default();
+    // Resolved on first use; shared by every electrostatic surface.
+    let mut field: Option<(Arc<PotentialField>, u64)> = None;
+    let residue_ranges = scene.protein_residue_ranges(annotations);
+
+    for (id, entry) in density
+        .visible_entries()
+        .filter(|(_, e)| e.style != DensityStyle::Volume)
+    {
+        let hash = density_hash(entry.generation, density.crop(entry).as_ref());
+        wanted.push((SurfaceKey::Density(id), hash));
+    }
 
     for (entity_idx, se) in all_entities.iter().enumerate() {
         let eid = se.id();
@@ -77,22 +578,11 @@ pub(crate) fn regenerate_surfaces(
             continue;
         }
 
-        // Per-entity surface takes priority; fall back to global
-        let base_surface = annotations.surfaces.get(&eid).map_or_else(
-            || match global_kind {
-                SurfaceKindOption::Gaussian => Some(EntitySurface {
-                    kind: SurfaceKind::Gaussian,
-                    color: [0.7, 0.7, 0.7, global_opacity],
-                    ..Default::default()
-                }),
-                SurfaceKindOption::Ses => Some(EntitySurface {
-                    kind: SurfaceKind::Ses,
-                    color: [0.7, 0.7, 0.7, global_opacity],
-                    ..Default::default()
-                }),
-                SurfaceKindOption::None => None,
-            },
-            |s| if s.visible { Some(s.clone()) } else { None },
+        let overrides = annotations.appearance(eid);
+        let base_surface = resolve_surface(
+            annotations.surfaces.get(&eid),
+            overrides,
+            &options.display,
         );
 
         // Skip atoms gathering only when neither the surface nor cavity
@@ -118,109 +608,191 @@ pub(crate) fn regenerate_surfaces(
 
         if let Some(mut surface) = base_surface {
             surface.color = [r, g, b, surface.color[3]];
-            // SES needs a finer grid than Gaussian to resolve atom-level
-            // detail (ChimeraX default is 0.5 Å).
-            if surface.kind == SurfaceKind::Ses {
-                surface.resolution = 0.5;
-            }
-            jobs.push((positions.clone(), radii.clone(), surface));
+            let coloring = overrides
+                .and_then(|o| o.surface_coloring)
+                .unwrap_or_else(|| options.display.surface_coloring());
+            let potential =
+                (coloring == SurfaceColoring::Electrostatic).then(|| {
+                    let range = overrides
+                        .and_then(|o| o.surface_potential_range)
+                        .unwrap_or_else(|| {
+                            options.display.surface_potential_range()
+                        });
+                    let (field, hash) = field
+                        .get_or_insert_with(|| {
+                            potential_field(scene, annotations, density)
+                        })
+                        .clone();
+                    (field, hash, range)
+                });
+            let residues = atom_pick_ids(scene, eid, &residue_ranges);
+            let by_residue = coloring == SurfaceColoring::Residue;
+            let occluders = surface_occluders(
+                scene,
+                annotations,
+                eid,
+                &surface,
+                (&positions, &radii),
+            );
+            let key = SurfaceKey::Surface(eid);
+            let hash = surface_hash(
+                &positions,
+                &radii,
+                &surface,
+                &occluders,
+                potential.as_ref().map(|(_, hash, range)| (*hash, *range)),
+                (&residues, by_residue),
+            );
+            wanted.push((key, hash));
+            let _ = inputs.insert(
+                key,
+                SurfaceInput::Surface {
+                    positions: positions.clone(),
+                    radii: radii.clone(),
+                    surface,
+                    occluders,
+                    potential: potential
+                        .map(|(field, _, range)| (field, range)),
+                    residues,
+                    by_residue,
+                },
+            );
         }
 
         if global_show_cavities {
-            cavity_jobs.push((positions, radii));
+            let key = SurfaceKey::Cavities(eid);
+            wanted.push((key, cavity_hash(&positions, &radii)));
+            let _ =
+                inputs.insert(key, SurfaceInput::Cavities { positions, radii });
         }
     }
 
-    // Also include any visible density maps
-    let density_jobs: Vec<_> = density
-        .visible_entries()
-        .map(|(_id, entry)| {
-            let [r, g, b] = entry.color;
-            (entry.map.clone(), entry.threshold, [r, g, b, entry.opacity])
-        })
-        .collect();
-
-    if jobs.is_empty() && density_jobs.is_empty() && cavity_jobs.is_empty() {
-        // Nothing to generate — send empty mesh to clear renderer
-        let _ = regen.tx.send((Vec::new(), Vec::new()));
-        return;
-    }
-
-    let tx = regen.tx.clone();
-
-    let spawn_result = std::thread::Builder::new()
-        .name("viso-surface-regen".into())
-        .spawn(move || {
-            let mut all_verts: Vec<IsosurfaceVertex> = Vec::new();
-            let mut all_idxs: Vec<u32> = Vec::new();
-
-            // Generate density map meshes first
-            use crate::renderer::geometry::isosurface::density;
-            for (map, threshold, color) in &density_jobs {
-                let (v, i) = density::generate_density_mesh(
-                    map, *threshold, *color, None,
-                );
-                let base = all_verts.len() as u32;
-                all_verts.extend(v);
-                all_idxs.extend(i.iter().map(|&idx| idx + base));
+    for key in regen.plan(&wanted) {
+        let hash = wanted
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map_or(0, |(_, hash)| *hash);
+        let input = match key {
+            SurfaceKey::Density(id) => {
+                density.get(id).map(|entry| SurfaceInput::Density {
+                    map: Arc::clone(&entry.map),
+                    levels: entry.contours(),
+                    opacity: entry.opacity,
+                    crop: density.crop(entry),
+                    wire: entry.style == DensityStyle::Mesh,
+                })
             }
-
-            // Generate entity surface meshes
-            for (positions, radii, surface) in &jobs {
-                let (v, i) = match surface.kind {
-                    SurfaceKind::Gaussian => {
-                        gaussian_surface::generate_gaussian_surface(
-                            positions,
-                            radii,
-                            surface.resolution,
-                            surface.level,
-                            surface.color,
-                        )
-                    }
-                    SurfaceKind::Ses => ses::generate_ses(
-                        positions,
-                        radii,
-                        Some(surface.probe_radius),
-                        surface.resolution,
-                        surface.color,
-                    ),
-                };
-                let base = all_verts.len() as u32;
-                all_verts.extend(v);
-                all_idxs.extend(i.iter().map(|&idx| idx + base));
+            SurfaceKey::Surface(_) | SurfaceKey::Cavities(_) => {
+                inputs.remove(&key)
             }
+        };
+        if let Some(input) = input {
+            regen.dispatch(key, hash, input);
+        }
+    }
+}
 
-            // Generate cavity meshes on a 0.6 Å grid — coarser than SES
-            // because cavity detection is topological (flood fill from
-            // grid boundary), so finer voxels can flip whether a thin
-            // SES-wall separates a cavity from the exterior. 0.6 Å was
-            // verified to detect the expected number of cavities on
-            // benchmark structures (e.g. 1bbc has 3).
-            let mut cavity_count = 0usize;
-            for (positions, radii) in &cavity_jobs {
-                let set =
-                    cavity::generate_cavities(positions, radii, Some(1.4), 0.6);
-                for mesh in &set.meshes {
-                    let base = all_verts.len() as u32;
-                    all_verts.extend(mesh.vertices.iter().copied());
-                    all_idxs.extend(mesh.indices.iter().map(|&idx| idx + base));
-                }
-                cavity_count += set.meshes.len();
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            log::info!(
-                "surface mesh: {} verts, {} triangles ({} cavities)",
-                all_verts.len(),
-                all_idxs.len() / 3,
-                cavity_count,
-            );
+    fn regen() -> (SurfaceRegen, mpsc::Receiver<MeshMessage>) {
+        let (tx, rx) = mpsc::channel();
+        let mut regen = SurfaceRegen::new(tx);
+        // Keep jobs off the worker; only the planning is under test.
+        regen.jobs = None;
+        (regen, rx)
+    }
 
-            if tx.send((all_verts, all_idxs)).is_err() {
-                log::warn!("surface mesh channel send failed");
-            }
-        });
+    fn cavities() -> SurfaceInput {
+        SurfaceInput::Cavities {
+            positions: Vec::new(),
+            radii: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn only_changed_meshes_are_dispatched() {
+        let (mut regen, _rx) = regen();
+        let a = SurfaceKey::Density(0);
+        let b = SurfaceKey::Density(1);
+
+        assert_eq!(regen.plan(&[(a, 1), (b, 1)]), vec![a, b]);
+        regen.dispatch(a, 1, cavities());
+        regen.dispatch(b, 1, cavities());
+
+        assert!(regen.plan(&[(a, 1), (b, 1)]).is_empty());
+        assert_eq!(regen.plan(&[(a, 1), (b, 2)]), vec![b]);
+    }
+
+    fn ses(
+        positions: Vec<Vec3>,
+        atoms: Option<Vec<u32>>,
+        occluders: &[Vec3],
+    ) -> SurfaceInput {
+        SurfaceInput::Surface {
+            radii: vec![1.8; positions.len()],
+            positions,
+            surface: EntitySurface {
+                atoms,
+                ..EntitySurface::new(SurfaceKind::Ses, [1.0; 4])
+            },
+            occluders: (occluders.to_vec(), vec![1.8; occluders.len()]),
+            potential: None,
+            residues: Vec::new(),
+            by_residue: false,
+        }
+    }
+
+    #[test]
+    fn partial_ses_is_shaped_by_occluders() {
+        // Alone, the atom bulges past the midpoint to another entity's
+        // atom at x = 3; shaped by that atom, its surface stops short.
+        let farthest = |input: &SurfaceInput| {
+            let (vertices, _) = generate(input, &AtomicBool::default());
+            let xs = vertices.iter().map(|v| v.position[0]);
+            xs.reduce(f32::max).unwrap_or(f32::NAN)
+        };
+        let alone = ses(vec![Vec3::ZERO], Some(vec![0]), &[]);
+        let shaped = ses(vec![Vec3::ZERO], Some(vec![0]), &[Vec3::X * 3.0]);
+        assert!(farthest(&alone) > 1.7);
+        assert!(farthest(&shaped) < 1.5);
+    }
+
+    #[test]
+    fn cancelled_jobs_stop_meshing() {
+        let input = ses(vec![Vec3::ZERO, Vec3::X * 3.0], None, &[]);
+        let (vertices, _) = generate(&input, &AtomicBool::new(false));
+        assert!(!vertices.is_empty());
+        let (vertices, indices) = generate(&input, &AtomicBool::new(true));
+        assert!(vertices.is_empty() && indices.is_empty());
+    }
+
+    #[test]
+    fn superseded_jobs_are_cancelled() {
+        let (mut regen, _rx) = regen();
+        let key = SurfaceKey::Density(0);
+        regen.dispatch(key, 1, cavities());
+        let first = Arc::clone(&regen.dispatched[&key].cancel);
+        regen.dispatch(key, 2, cavities());
+        assert!(first.load(Ordering::Relaxed));
+        assert!(!regen.dispatched[&key].cancel.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn unwanted_meshes_are_removed() {
+        let (mut regen, rx) = regen();
+        let kept = SurfaceKey::Density(0);
+        let dropped = SurfaceKey::Density(1);
+        regen.dispatch(kept, 1, cavities());
+        regen.dispatch(dropped, 1, cavities());
+        let cancel = Arc::clone(&regen.dispatched[&dropped].cancel);
 
-    if let Err(e) = spawn_result {
-        log::warn!("failed to spawn surface regen thread: {e}");
+        assert!(regen.plan(&[(kept, 1)]).is_empty());
+        assert!(cancel.load(Ordering::Relaxed));
+        let removal = rx.try_recv().ok();
+        assert!(removal.is_some_and(|m| m.key == dropped
+            && m.mesh.is_none()
+            && m.generation == 3));
     }
 }
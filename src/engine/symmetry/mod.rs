@@ -0,0 +1,710 @@
+//! Biological assemblies and crystal symmetry mates.
+//!
+//! A deposited structure is usually the asymmetric unit (ASU): the
+//! biologically relevant oligomer and the crystal packing are both
+//! produced by applying rigid operators to it. [`StructureSymmetry`]
+//! carries those operators as read from the file (see [`parse`]):
+//! `pdbx_struct_assembly_gen` / `REMARK 350` assemblies, plus the unit
+//! cell and space group used to generate symmetry mates.
+//!
+//! The engine never adds entities for the copies — the assembly is
+//! host-owned. Instead, [`VisoEngine::set_symmetry_view`] hands the
+//! renderer the model transforms of the copies and every copy is drawn
+//! from the ASU's GPU buffers as one more instance. Assemblies that
+//! apply different operators to different chains give the entities of
+//! those chains transforms of their own. Copies follow animation and
+//! edits for free, and are not pickable.
+
+mod parse;
+
+use glam::{DMat3, DVec3, Mat4, Vec3};
+use molex::entity::molecule::id::EntityId;
+use molex::MoleculeEntity;
+
+use super::VisoEngine;
+use crate::error::VisoError;
+use crate::renderer::model_copies::CopyTransforms;
+use crate::util::space_group::SymOp;
+
+/// Largest lattice-shift search per axis when looking for mates.
+const MAX_LATTICE_SHIFT: i32 = 4;
+
+/// Crystal unit-cell parameters (Å and degrees).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnitCell {
+    /// Length of the a axis.
+    pub a: f64,
+    /// Length of the b axis.
+    pub b: f64,
+    /// Length of the c axis.
+    pub c: f64,
+    /// Angle between b and c.
+    pub alpha: f64,
+    /// Angle between a and c.
+    pub beta: f64,
+    /// Angle between a and b.
+    pub gamma: f64,
+}
+
+impl UnitCell {
+    /// A cell from its six parameters. `None` for degenerate cells and
+    /// the 1 Å cubic placeholder NMR and EM entries carry.
+    #[must_use]
+    pub fn new(
+        a: f64,
+        b: f64,
+        c: f64,
+        alpha: f64,
+        beta: f64,
+        gamma: f64,
+    ) -> Option<Self> {
+        let cell = Self {
+            a,
+            b,
+            c,
+            alpha,
+            beta,
+            gamma,
+        };
+        let placeholder = [a, b, c].iter().all(|l| (l - 1.0).abs() < 1e-6);
+        (a > 0.0 && b > 0.0 && c > 0.0 && !placeholder && cell.volume() > 0.0)
+            .then_some(cell)
+    }
+
+    /// Cell volume in Å³.
+    #[must_use]
+    pub fn volume(&self) -> f64 {
+        let (ca, cb, cg) = self.cosines();
+        let v2 = 1.0 - ca * ca - cb * cb - cg * cg + 2.0 * ca * cb * cg;
+        self.a * self.b * self.c * v2.max(0.0).sqrt()
+    }
+
+    /// Whether the cell is in the primitive rhombohedral setting
+    /// (a = b = c, α = β = γ ≠ 90°).
+    #[must_use]
+    pub fn is_rhombohedral(&self) -> bool {
+        let eq = |x: f64, y: f64| (x - y).abs() < 1e-3;
+        eq(self.a, self.b)
+            && eq(self.b, self.c)
+            && eq(self.alpha, self.beta)
+            && eq(self.beta, self.gamma)
+            && !eq(self.alpha, 90.0)
+    }
+
+    /// Fractional → Cartesian matrix (PDB convention: a along x, b in
+    /// the xy plane).
+    pub(crate) fn orthogonalization(&self) -> DMat3 {
+        let (ca, cb, cg) = self.cosines();
+        let sg = self.gamma.to_radians().sin();
+        DMat3::from_cols(
+            DVec3::new(self.a, 0.0, 0.0),
+            DVec3::new(self.b * cg, self.b * sg, 0.0),
+            DVec3::new(
+                self.c * cb,
+                self.c * (ca - cb * cg) / sg,
+                self.volume() / (self.a * self.b * sg),
+            ),
+        )
+    }
+
+    /// Cartesian → fractional matrix.
+    pub(crate) fn fractionalization(&self) -> DMat3 {
+        self.orthogonalization().inverse()
+    }
+
+    /// The twelve edges of the cell at the origin, in Cartesian space.
+    #[must_use]
+    pub fn edges(&self) -> [(Vec3, Vec3); 12] {
+        let orth = self.orthogonalization();
+        let corner =
+            |x: f64, y: f64, z: f64| (orth * DVec3::new(x, y, z)).as_vec3();
+        let c = [
+            corner(0.0, 0.0, 0.0),
+            corner(1.0, 0.0, 0.0),
+            corner(0.0, 1.0, 0.0),
+            corner(1.0, 1.0, 0.0),
+            corner(0.0, 0.0, 1.0),
+            corner(1.0, 0.0, 1.0),
+            corner(0.0, 1.0, 1.0),
+            corner(1.0, 1.0, 1.0),
+        ];
+        [
+            (c[0], c[1]),
+            (c[2], c[3]),
+            (c[4], c[5]),
+            (c[6], c[7]),
+            (c[0], c[2]),
+            (c[1], c[3]),
+            (c[4], c[6]),
+            (c[5], c[7]),
+            (c[0], c[4]),
+            (c[1], c[5]),
+            (c[2], c[6]),
+            (c[3], c[7]),
+        ]
+    }
+
+    /// Cartesian transform of fractional operator `op` followed by the
+    /// lattice translation `shift`.
+    fn cartesian(&self, op: &SymOp, shift: DVec3) -> Mat4 {
+        let orth = self.orthogonalization();
+        let rot = orth * op.rotation() * self.fractionalization();
+        let trans = orth * (op.translation_frac() + shift);
+        let mut m = Mat4::from_mat3(rot.as_mat3());
+        m.w_axis = trans.as_vec3().extend(1.0);
+        m
+    }
+
+    fn cosines(&self) -> (f64, f64, f64) {
+        (
+            self.alpha.to_radians().cos(),
+            self.beta.to_radians().cos(),
+            self.gamma.to_radians().cos(),
+        )
+    }
+}
+
+/// One row of an assembly definition: operators applied to a set of
+/// chains.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssemblyGen {
+    /// Author chain identifiers the operators apply to.
+    pub chains: Vec<String>,
+    /// Cartesian operators, identity included when the ASU itself is
+    /// part of the assembly.
+    pub operators: Vec<Mat4>,
+}
+
+/// A biological assembly from `pdbx_struct_assembly` / `REMARK 350`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BioAssembly {
+    /// Assembly identifier (`"1"`, `"2"`, ...).
+    pub id: String,
+    /// Free-text description (`author_defined_assembly`, `DIMERIC`).
+    pub details: Option<String>,
+    /// Generator rows.
+    pub gens: Vec<AssemblyGen>,
+}
+
+impl BioAssembly {
+    /// Every chain named by any generator row.
+    #[must_use]
+    pub fn chains(&self) -> Vec<&str> {
+        let mut chains: Vec<&str> = Vec::new();
+        for chain in self.gens.iter().flat_map(|g| &g.chains) {
+            if !chains.contains(&chain.as_str()) {
+                chains.push(chain);
+            }
+        }
+        chains
+    }
+
+    /// Every distinct operator of any generator row, in file order.
+    #[must_use]
+    pub fn operators(&self) -> Vec<Mat4> {
+        let mut ops: Vec<Mat4> = Vec::new();
+        for op in self.gens.iter().flat_map(|g| &g.operators) {
+            if !ops.iter().any(|o| o.abs_diff_eq(*op, 1e-4)) {
+                ops.push(*op);
+            }
+        }
+        ops
+    }
+
+    /// Every distinct operator applied to `chain` (an author chain id),
+    /// in file order. Empty when the chain isn't part of the assembly.
+    #[must_use]
+    pub fn chain_operators(&self, chain: &str) -> Vec<Mat4> {
+        let mut ops: Vec<Mat4> = Vec::new();
+        let rows = self
+            .gens
+            .iter()
+            .filter(|g| g.chains.iter().any(|c| c == chain));
+        for op in rows.flat_map(|g| &g.operators) {
+            if !ops.iter().any(|o| o.abs_diff_eq(*op, 1e-4)) {
+                ops.push(*op);
+            }
+        }
+        ops
+    }
+}
+
+/// Symmetry information of a loaded structure file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureSymmetry {
+    /// Unit cell, if the file has a real one.
+    pub cell: Option<UnitCell>,
+    /// Hermann–Mauguin space-group symbol as written in the file.
+    pub space_group: Option<String>,
+    /// Biological assemblies, in file order.
+    pub assemblies: Vec<BioAssembly>,
+    /// Space-group operators: the file's own list when it has one,
+    /// otherwise the built-in table entry for `space_group`.
+    symops: Vec<SymOp>,
+}
+
+impl StructureSymmetry {
+    fn new(
+        cell: Option<UnitCell>,
+        space_group: Option<String>,
+        explicit: Vec<SymOp>,
+        assemblies: Vec<BioAssembly>,
+    ) -> Self {
+        let symops = if explicit.is_empty() {
+            parse::table_operators(space_group.as_deref(), cell.as_ref())
+        } else {
+            explicit
+        };
+        Self {
+            cell,
+            space_group,
+            assemblies,
+            symops,
+        }
+    }
+
+    /// Parse the symmetry records of mmCIF text.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VisoError::StructureLoad`] if the text isn't valid CIF.
+    pub fn from_mmcif_str(text: &str) -> Result<Self, VisoError> {
+        parse::from_mmcif_str(text).ok_or_else(|| {
+            VisoError::StructureLoad("invalid mmCIF symmetry records".into())
+        })
+    }
+
+    /// Parse the `CRYST1`, `REMARK 290` and `REMARK 350` records of
+    /// PDB text.
+    #[must_use]
+    pub fn from_pdb_str(text: &str) -> Self {
+        parse::from_pdb_str(text)
+    }
+
+    /// Parse structure bytes with a format hint (`"cif"` or `"pdb"`).
+    /// `None` for other formats (BinaryCIF carries no assembly
+    /// categories in molex's reader) or when the file has neither a
+    /// cell nor an assembly.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8], format_hint: &str) -> Option<Self> {
+        let text = std::str::from_utf8(bytes).ok()?;
+        let hint = format_hint.to_ascii_lowercase();
+        let sym = match hint.trim_start_matches('.') {
+            "cif" | "mmcif" => parse::from_mmcif_str(text)?,
+            "pdb" | "ent" => parse::from_pdb_str(text),
+            _ => return None,
+        };
+        sym.has_content().then_some(sym)
+    }
+
+    /// Read a structure file and parse its symmetry records. See
+    /// [`Self::from_bytes`].
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn from_file(path: &std::path::Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        Self::from_bytes(&std::fs::read(path).ok()?, ext)
+    }
+
+    /// Number of space-group operators (identity included); zero when
+    /// the space group is unknown.
+    #[must_use]
+    pub fn symmetry_operator_count(&self) -> usize {
+        self.symops.len()
+    }
+
+    /// Whether crystal mates can be generated.
+    #[must_use]
+    pub fn has_crystal_symmetry(&self) -> bool {
+        self.cell.is_some() && !self.symops.is_empty()
+    }
+
+    /// Look up an assembly by id.
+    #[must_use]
+    pub fn assembly(&self, id: &str) -> Option<&BioAssembly> {
+        self.assemblies.iter().find(|a| a.id == id)
+    }
+
+    fn has_content(&self) -> bool {
+        self.cell.is_some() || !self.assemblies.is_empty()
+    }
+}
+
+/// Which copies of the asymmetric unit are drawn.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum SymmetryView {
+    /// Just the deposited coordinates.
+    #[default]
+    AsymmetricUnit,
+    /// Biological assembly with the given id.
+    Assembly(String),
+    /// The ASU plus every symmetry mate whose bounding sphere comes
+    /// within `radius` Å of the ASU's.
+    CrystalMates {
+        /// Extra distance between bounding spheres, in Å.
+        radius: f32,
+    },
+}
+
+/// Engine-side symmetry state.
+#[derive(Default)]
+pub(crate) struct SymmetryState {
+    /// Records of the loaded structure file.
+    pub(crate) structure: Option<StructureSymmetry>,
+    /// Active view.
+    pub(crate) view: SymmetryView,
+    /// Whether the unit-cell box is drawn.
+    pub(crate) show_unit_cell: bool,
+    /// Polymer entities hidden because they aren't part of the active
+    /// assembly; made visible again when the view changes.
+    hidden: Vec<EntityId>,
+}
+
+/// Cartesian transforms of every symmetry mate whose bounding sphere
+/// (`center`, `extent`) comes within `radius` of the original's. The
+/// identity is excluded.
+pub(crate) fn crystal_mates(
+    cell: &UnitCell,
+    symops: &[SymOp],
+    center: Vec3,
+    extent: f32,
+    radius: f32,
+) -> Vec<Mat4> {
+    let cutoff = f64::from(2.0 * extent + radius);
+    let center = center.as_dvec3();
+    let frac_center = cell.fractionalization() * center;
+    let reach =
+        |len: f64| ((cutoff / len).ceil() as i32 + 1).min(MAX_LATTICE_SHIFT);
+    let (ka, kb, kc) = (reach(cell.a), reach(cell.b), reach(cell.c));
+    let orth = cell.orthogonalization();
+
+    let mut mates = Vec::new();
+    for op in symops {
+        let image = op.apply(frac_center);
+        // Lattice shift bringing this image closest to the original.
+        let base = (frac_center - image).round();
+        let shifts = (-ka..=ka).flat_map(|i| {
+            (-kb..=kb).flat_map(move |j| {
+                (-kc..=kc).map(move |k| {
+                    base + DVec3::new(f64::from(i), f64::from(j), f64::from(k))
+                })
+            })
+        });
+        for shift in shifts {
+            if op.is_identity() && shift == DVec3::ZERO {
+                continue;
+            }
+            let moved = orth * (image + shift);
+            if moved.distance(center) <= cutoff {
+                mates.push(cell.cartesian(op, shift));
+            }
+        }
+    }
+    mates
+}
+
+/// Author chain id of `entity`: the polymer's own, otherwise the chain
+/// it was read from.
+fn entity_chain(entity: &MoleculeEntity) -> Option<u8> {
+    let auth = match entity {
+        MoleculeEntity::Protein(p) => p.auth_asym_id,
+        MoleculeEntity::NucleicAcid(n) => n.auth_asym_id,
+        _ => None,
+    };
+    auth.or_else(|| entity.pdb_chain_id())
+}
+
+/// Whether `entity` belongs to one of `chains` (author chain ids).
+/// Non-polymers always do.
+fn entity_in_chains(entity: &MoleculeEntity, chains: &[&str]) -> bool {
+    if !matches!(
+        entity,
+        MoleculeEntity::Protein(_) | MoleculeEntity::NucleicAcid(_)
+    ) {
+        return true;
+    }
+    let Some(id) = entity_chain(entity) else {
+        return true;
+    };
+    chains.iter().any(|c| c.len() == 1 && c.as_bytes()[0] == id)
+}
+
+impl VisoEngine {
+    /// Provide the symmetry records of the loaded structure (or `None`
+    /// to clear them). Resets the view to the asymmetric unit and
+    /// hides the unit cell.
+    pub fn set_structure_symmetry(
+        &mut self,
+        symmetry: Option<StructureSymmetry>,
+    ) {
+        self.restore_assembly_visibility();
+        self.symmetry.structure = symmetry;
+        self.symmetry.view = SymmetryView::AsymmetricUnit;
+        self.symmetry.show_unit_cell = false;
+        self.gpu.set_model_copies(CopyTransforms::default());
+        self.gpu.set_unit_cell(None);
+    }
+
+    /// Symmetry records of the loaded structure, if any.
+    #[must_use]
+    pub fn structure_symmetry(&self) -> Option<&StructureSymmetry> {
+        self.symmetry.structure.as_ref()
+    }
+
+    /// The active symmetry view.
+    #[must_use]
+    pub fn symmetry_view(&self) -> &SymmetryView {
+        &self.symmetry.view
+    }
+
+    /// Switch which copies of the asymmetric unit are drawn. Returns
+    /// the number of distinct transforms now drawn, the ASU itself
+    /// included.
+    ///
+    /// Selecting an assembly hides polymer chains it doesn't contain
+    /// until the view changes again, and draws every chain with the
+    /// operators of the generator rows naming it. Crystal mates are
+    /// placed against the coordinates displayed at the time of the call.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VisoError::Symmetry`] if no symmetry records are
+    /// loaded, the assembly id is unknown, or mates are requested for a
+    /// structure without a unit cell and known space group.
+    pub fn set_symmetry_view(
+        &mut self,
+        view: SymmetryView,
+    ) -> Result<usize, VisoError> {
+        let copies = self.copy_transforms(&view)?;
+        self.restore_assembly_visibility();
+        if let SymmetryView::Assembly(id) = &view {
+            self.hide_chains_outside_assembly(id);
+        }
+        let count = copies.shared.len().max(1);
+        self.gpu.set_model_copies(copies);
+        self.symmetry.view = view;
+        Ok(count)
+    }
+
+    /// Whether the unit-cell box is drawn.
+    #[must_use]
+    pub fn show_unit_cell(&self) -> bool {
+        self.symmetry.show_unit_cell
+    }
+
+    /// Show or hide the unit-cell box. No-op without a cell.
+    pub fn set_show_unit_cell(&mut self, show: bool) {
+        self.symmetry.show_unit_cell = show;
+        let cell = self
+            .symmetry
+            .structure
+            .as_ref()
+            .and_then(|s| s.cell)
+            .filter(|_| show);
+        let edges = cell.map(|c| c.edges());
+        self.gpu.set_unit_cell(edges.as_ref().map(|e| &e[..]));
+    }
+
+    /// Model transforms for `view`. None means "draw the ASU once,
+    /// untransformed".
+    fn copy_transforms(
+        &self,
+        view: &SymmetryView,
+    ) -> Result<CopyTransforms, VisoError> {
+        let loaded = || {
+            self.symmetry.structure.as_ref().ok_or_else(|| {
+                VisoError::Symmetry("no symmetry records loaded".into())
+            })
+        };
+        match view {
+            SymmetryView::AsymmetricUnit => Ok(CopyTransforms::default()),
+            SymmetryView::Assembly(id) => {
+                let sym = loaded()?;
+                let assembly = sym.assembly(id).ok_or_else(|| {
+                    VisoError::Symmetry(format!("unknown assembly '{id}'"))
+                })?;
+                Ok(self.assembly_transforms(assembly))
+            }
+            SymmetryView::CrystalMates { radius } => {
+                let sym = loaded()?;
+                let cell =
+                    sym.cell.as_ref().filter(|_| sym.has_crystal_symmetry());
+                let cell = cell.ok_or_else(|| {
+                    VisoError::Symmetry(
+                        "structure has no unit cell or known space group"
+                            .into(),
+                    )
+                })?;
+                let (center, extent) = self.displayed_bounds();
+                let mut shared = vec![Mat4::IDENTITY];
+                shared.extend(crystal_mates(
+                    cell,
+                    &sym.symops,
+                    center,
+                    extent,
+                    radius.max(0.0),
+                ));
+                Ok(CopyTransforms {
+                    shared,
+                    entities: Vec::new(),
+                })
+            }
+        }
+    }
+
+    /// Model transforms of `assembly`: every operator for entities off
+    /// any listed chain, and the operators of its own chain for each
+    /// entity whose chain differs from that.
+    fn assembly_transforms(&self, assembly: &BioAssembly) -> CopyTransforms {
+        let shared = assembly.operators();
+        let entities: Vec<(u32, Vec<Mat4>)> = self
+            .scene
+            .current
+            .entities()
+            .iter()
+            .filter_map(|e| {
+                let chain = char::from(entity_chain(e)?).to_string();
+                let ops = assembly.chain_operators(&chain);
+                (!ops.is_empty() && ops != shared).then(|| (e.id().raw(), ops))
+            })
+            .collect();
+        let identity_only =
+            shared.len() == 1 && shared[0].abs_diff_eq(Mat4::IDENTITY, 1e-4);
+        if identity_only && entities.is_empty() {
+            return CopyTransforms::default();
+        }
+        CopyTransforms { shared, entities }
+    }
+
+    /// Bounding sphere of every displayed atom.
+    fn displayed_bounds(&self) -> (Vec3, f32) {
+        let points: Vec<Vec3> = self
+            .scene
+            .visible_entities(&self.annotations)
+            .filter_map(|(_, id, _)| self.scene.positions.get(id))
+            .flatten()
+            .copied()
+            .collect();
+        if points.is_empty() {
+            return (Vec3::ZERO, 0.0);
+        }
+        let center = points.iter().sum::<Vec3>() / points.len() as f32;
+        let extent = points
+            .iter()
+            .map(|p| p.distance(center))
+            .fold(0.0, f32::max);
+        (center, extent)
+    }
+
+    fn hide_chains_outside_assembly(&mut self, id: &str) {
+        let Some(assembly) = self
+            .symmetry
+            .structure
+            .as_ref()
+            .and_then(|s| s.assembly(id))
+        else {
+            return;
+        };
+        let chains = assembly.chains();
+        let hidden: Vec<EntityId> = self
+            .scene
+            .current
+            .entities()
+            .iter()
+            .filter(|e| {
+                self.annotations.is_visible(e.id())
+                    && !entity_in_chains(e, &chains)
+            })
+            .map(|e| e.id())
+            .collect();
+        let mut view = self.annotations_mut();
+        for &eid in &hidden {
+            view.set_visible(eid, false);
+        }
+        self.symmetry.hidden = hidden;
+    }
+
+    fn restore_assembly_visibility(&mut self) {
+        let hidden = std::mem::take(&mut self.symmetry.hidden);
+        let mut view = self.annotations_mut();
+        for eid in hidden {
+            view.set_visible(eid, true);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use glam::Mat3;
+
+    use super::*;
+    use crate::util::space_group;
+
+    fn ortho_cell() -> UnitCell {
+        UnitCell::new(50.0, 60.0, 70.0, 90.0, 90.0, 90.0).unwrap()
+    }
+
+    #[test]
+    fn orthogonalization_round_trips() {
+        let cell = UnitCell::new(40.0, 50.0, 60.0, 80.0, 95.0, 110.0).unwrap();
+        let p = DVec3::new(3.0, -7.0, 12.5);
+        let back = cell.orthogonalization() * (cell.fractionalization() * p);
+        assert!((back - p).length() < 1e-9);
+        assert!(UnitCell::new(1.0, 1.0, 1.0, 90.0, 90.0, 90.0).is_none());
+    }
+
+    #[test]
+    fn p1_mates_are_lattice_neighbours() {
+        let cell = ortho_cell();
+        let ops = space_group::operators("P 1", false).unwrap();
+        // A point-like ASU reaches only lattice translations within
+        // the radius: the two ±a neighbours at 50 Å.
+        let mates = crystal_mates(&cell, &ops, Vec3::ZERO, 0.0, 55.0);
+        assert_eq!(mates.len(), 2);
+        for m in &mates {
+            let p = m.transform_point3(Vec3::ZERO);
+            assert!((p.length() - 50.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn mates_are_rigid_and_nearby() {
+        let cell = ortho_cell();
+        let ops = space_group::operators("P 21 21 21", false).unwrap();
+        let center = Vec3::new(10.0, 12.0, 20.0);
+        let mates = crystal_mates(&cell, &ops, center, 20.0, 10.0);
+        assert!(!mates.is_empty());
+        for m in &mates {
+            let rot = Mat3::from_mat4(*m);
+            assert!((rot.determinant() - 1.0).abs() < 1e-4);
+            let moved = m.transform_point3(center);
+            assert!(moved.distance(center) <= 50.0 + 1e-3);
+            assert!(!m.abs_diff_eq(Mat4::IDENTITY, 1e-4));
+        }
+    }
+
+    #[test]
+    fn assembly_operator_union() {
+        let shift = Mat4::from_translation(Vec3::X);
+        let assembly = BioAssembly {
+            id: "1".into(),
+            details: None,
+            gens: vec![
+                AssemblyGen {
+                    chains: vec!["A".into()],
+                    operators: vec![Mat4::IDENTITY, shift],
+                },
+                AssemblyGen {
+                    chains: vec!["B".into(), "A".into()],
+                    operators: vec![Mat4::IDENTITY],
+                },
+            ],
+        };
+        assert_eq!(assembly.chains(), vec!["A", "B"]);
+        assert_eq!(assembly.operators(), vec![Mat4::IDENTITY, shift]);
+        assert_eq!(assembly.chain_operators("A"), vec![Mat4::IDENTITY, shift]);
+        assert_eq!(assembly.chain_operators("B"), vec![Mat4::IDENTITY]);
+        assert!(assembly.chain_operators("C").is_empty());
+    }
+}
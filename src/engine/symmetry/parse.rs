@@ -0,0 +1,549 @@
+//! Symmetry records from mmCIF and PDB text.
+//!
+//! molex's structure adapters stop at the coordinates, so the cell,
+//! space group, explicit symmetry operators and biological-assembly
+//! definitions are read here straight from the file:
+//!
+//! - mmCIF: `_cell`, `_symmetry` / `_space_group`, `_space_group_symop` (or
+//!   `_symmetry_equiv`), `_pdbx_struct_assembly`, `_pdbx_struct_assembly_gen`
+//!   and `_pdbx_struct_oper_list`.
+//! - PDB: `CRYST1`, `REMARK 290 SMTRY` and `REMARK 350 BIOMT`.
+
+use std::collections::HashMap;
+
+use glam::{Mat3, Mat4, Vec4};
+use molex::adapters::cif::{Block, Value};
+
+use super::{AssemblyGen, BioAssembly, StructureSymmetry, UnitCell};
+use crate::util::space_group::{self, SymOp};
+
+// ── mmCIF ──────────────────────────────────────────────────────────
+
+/// Parse symmetry records from mmCIF text. `None` if the text isn't
+/// valid CIF.
+pub(super) fn from_mmcif_str(text: &str) -> Option<StructureSymmetry> {
+    let doc = molex::adapters::cif::parse(text).ok()?;
+    let block = doc.blocks.first()?;
+
+    let cell = cif_cell(block);
+    let space_group = [
+        "_symmetry.space_group_name_H-M",
+        "_space_group.name_H-M_alt",
+    ]
+    .iter()
+    .find_map(|tag| block.get(tag).and_then(Value::as_str))
+    .map(str::to_owned);
+    let explicit: Vec<SymOp> = [
+        "_space_group_symop.operation_xyz",
+        "_symmetry_equiv.pos_as_xyz",
+    ]
+    .iter()
+    .find_map(|tag| {
+        let ops: Vec<SymOp> = cif_rows(block, &[tag])
+            .into_iter()
+            .filter_map(|row| SymOp::parse(row[0]?))
+            .collect();
+        (!ops.is_empty()).then_some(ops)
+    })
+    .unwrap_or_default();
+
+    Some(StructureSymmetry::new(
+        cell,
+        space_group,
+        explicit,
+        cif_assemblies(block),
+    ))
+}
+
+/// Rows of a category, whether written as a loop or as single
+/// key-value pairs. Missing or `.`/`?` values are `None`.
+fn cif_rows<'a>(block: &'a Block, tags: &[&str]) -> Vec<Vec<Option<&'a str>>> {
+    let Some(first) = tags.first() else {
+        return Vec::new();
+    };
+    if let Some(lp) = block.find_loop(first) {
+        let columns: Vec<Option<usize>> =
+            tags.iter().map(|t| lp.column_index(t)).collect();
+        let width = lp.tags.len();
+        return (0..lp.nrows())
+            .map(|row| {
+                columns
+                    .iter()
+                    .map(|col| {
+                        col.and_then(|c| lp.values[row * width + c].as_str())
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+    if block.get(first).is_none() {
+        return Vec::new();
+    }
+    vec![tags
+        .iter()
+        .map(|t| block.get(t).and_then(Value::as_str))
+        .collect()]
+}
+
+fn cif_cell(block: &Block) -> Option<UnitCell> {
+    let get = |tag: &str| block.get(tag).and_then(Value::as_f64);
+    UnitCell::new(
+        get("_cell.length_a")?,
+        get("_cell.length_b")?,
+        get("_cell.length_c")?,
+        get("_cell.angle_alpha")?,
+        get("_cell.angle_beta")?,
+        get("_cell.angle_gamma")?,
+    )
+}
+
+fn cif_assemblies(block: &Block) -> Vec<BioAssembly> {
+    let operators = cif_operators(block);
+    let auth_chain = label_to_auth_chains(block);
+
+    let mut assemblies: Vec<BioAssembly> = cif_rows(
+        block,
+        &["_pdbx_struct_assembly.id", "_pdbx_struct_assembly.details"],
+    )
+    .into_iter()
+    .filter_map(|row| {
+        Some(BioAssembly {
+            id: row[0]?.to_owned(),
+            details: row[1].map(str::to_owned),
+            gens: Vec::new(),
+        })
+    })
+    .collect();
+
+    let gen_rows = cif_rows(
+        block,
+        &[
+            "_pdbx_struct_assembly_gen.assembly_id",
+            "_pdbx_struct_assembly_gen.oper_expression",
+            "_pdbx_struct_assembly_gen.asym_id_list",
+        ],
+    );
+    for row in gen_rows {
+        let (Some(id), Some(expr), Some(asyms)) = (row[0], row[1], row[2])
+        else {
+            continue;
+        };
+        let Some(ops) = expand_oper_expression(expr, &operators) else {
+            log::warn!("assembly {id}: unresolved operators '{expr}'");
+            continue;
+        };
+        let mut chains: Vec<String> = Vec::new();
+        for label in asyms.split(',').map(str::trim) {
+            let chain = auth_chain.get(label).map_or(label, String::as_str);
+            if !chains.iter().any(|c| c == chain) {
+                chains.push(chain.to_owned());
+            }
+        }
+        let gen = AssemblyGen {
+            chains,
+            operators: ops,
+        };
+        if let Some(assembly) = assemblies.iter_mut().find(|a| a.id == id) {
+            assembly.gens.push(gen);
+        } else {
+            assemblies.push(BioAssembly {
+                id: id.to_owned(),
+                details: None,
+                gens: vec![gen],
+            });
+        }
+    }
+    assemblies.retain(|a| !a.gens.is_empty());
+    assemblies
+}
+
+/// `_pdbx_struct_oper_list` by operator id.
+fn cif_operators(block: &Block) -> HashMap<String, Mat4> {
+    let mut tags = vec!["_pdbx_struct_oper_list.id".to_owned()];
+    for i in 1..=3 {
+        for j in 1..=3 {
+            tags.push(format!("_pdbx_struct_oper_list.matrix[{i}][{j}]"));
+        }
+        tags.push(format!("_pdbx_struct_oper_list.vector[{i}]"));
+    }
+    let tag_refs: Vec<&str> = tags.iter().map(String::as_str).collect();
+    cif_rows(block, &tag_refs)
+        .into_iter()
+        .filter_map(|row| {
+            let id = row[0]?.to_owned();
+            let num = |k: usize| -> Option<f32> { row[k]?.parse().ok() };
+            // Per row i: three matrix entries, then the vector entry.
+            let mut rows = [[0.0f32; 4]; 3];
+            for (i, r) in rows.iter_mut().enumerate() {
+                for (j, v) in r.iter_mut().enumerate() {
+                    *v = num(1 + i * 4 + j)?;
+                }
+            }
+            Some((id, mat4_from_rows(&rows)))
+        })
+        .collect()
+}
+
+/// Map `label_asym_id` to `auth_asym_id` via `_atom_site`.
+fn label_to_auth_chains(block: &Block) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    let Some(cols) =
+        block.columns(&["_atom_site.label_asym_id", "_atom_site.auth_asym_id"])
+    else {
+        return map;
+    };
+    for row in &cols {
+        if let (Some(label), Some(auth)) = (row[0].as_str(), row[1].as_str()) {
+            if !map.contains_key(label) {
+                let _ = map.insert(label.to_owned(), auth.to_owned());
+            }
+        }
+    }
+    map
+}
+
+/// Expand an `oper_expression` such as `1`, `1,2,5`, `(1-60)` or
+/// `(X0)(1-20)` into operator matrices. Parenthesized groups form a
+/// Cartesian product applied right to left.
+fn expand_oper_expression(
+    expr: &str,
+    operators: &HashMap<String, Mat4>,
+) -> Option<Vec<Mat4>> {
+    let groups: Vec<&str> = if expr.contains('(') {
+        expr.split(['(', ')'])
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+            .collect()
+    } else {
+        vec![expr.trim()]
+    };
+    let mut result = vec![Mat4::IDENTITY];
+    for group in groups {
+        let mut ids = Vec::new();
+        for item in group.split(',').map(str::trim) {
+            match item.split_once('-').and_then(|(a, b)| {
+                Some((
+                    a.trim().parse::<i64>().ok()?,
+                    b.trim().parse::<i64>().ok()?,
+                ))
+            }) {
+                Some((lo, hi)) => ids.extend((lo..=hi).map(|i| i.to_string())),
+                None => ids.push(item.to_owned()),
+            }
+        }
+        let mats: Vec<Mat4> = ids
+            .iter()
+            .map(|id| operators.get(id).copied())
+            .collect::<Option<_>>()?;
+        result = result
+            .iter()
+            .flat_map(|left| mats.iter().map(move |right| *left * *right))
+            .collect();
+    }
+    Some(result)
+}
+
+// ── PDB ────────────────────────────────────────────────────────────
+
+/// Parse symmetry records from PDB text.
+pub(super) fn from_pdb_str(text: &str) -> StructureSymmetry {
+    let mut cell = None;
+    let mut space_group = None;
+    let mut smtry = MatrixRecords::default();
+    let mut assemblies: Vec<BioAssembly> = Vec::new();
+    let mut biomt = MatrixRecords::default();
+    let mut pending_chains: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        if line.starts_with("CRYST1") {
+            cell = pdb_cell(line);
+            space_group = line
+                .get(55..66)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_owned);
+        } else if let Some(rest) = line.strip_prefix("REMARK 290") {
+            smtry.push(rest, "SMTRY");
+        } else if let Some(rest) = line.strip_prefix("REMARK 350") {
+            let rest = rest.trim();
+            if let Some(id) = rest.strip_prefix("BIOMOLECULE:") {
+                flush_gen(&mut assemblies, &mut pending_chains, &mut biomt);
+                assemblies.push(BioAssembly {
+                    id: id.trim().to_owned(),
+                    details: None,
+                    gens: Vec::new(),
+                });
+            } else if let Some(details) = rest
+                .strip_prefix("AUTHOR DETERMINED BIOLOGICAL UNIT:")
+                .or_else(|| {
+                    rest.strip_prefix(
+                        "SOFTWARE DETERMINED QUATERNARY STRUCTURE:",
+                    )
+                })
+            {
+                if let Some(a) =
+                    assemblies.last_mut().filter(|a| a.details.is_none())
+                {
+                    a.details = Some(details.trim().to_owned());
+                }
+            } else if let Some(list) = rest
+                .strip_prefix("APPLY THE FOLLOWING TO CHAINS:")
+                .or_else(|| rest.strip_prefix("AND CHAINS:"))
+            {
+                if rest.starts_with("APPLY") {
+                    flush_gen(&mut assemblies, &mut pending_chains, &mut biomt);
+                }
+                pending_chains.extend(
+                    list.split(',')
+                        .map(str::trim)
+                        .filter(|c| !c.is_empty())
+                        .map(str::to_owned),
+                );
+            } else {
+                biomt.push(rest, "BIOMT");
+            }
+        }
+    }
+    flush_gen(&mut assemblies, &mut pending_chains, &mut biomt);
+    assemblies.retain(|a| !a.gens.is_empty());
+
+    let explicit = cell
+        .as_ref()
+        .map(|c| {
+            smtry
+                .take()
+                .iter()
+                .filter_map(|m| c.cartesian_to_symop(m))
+                .collect()
+        })
+        .unwrap_or_default();
+    StructureSymmetry::new(cell, space_group, explicit, assemblies)
+}
+
+fn pdb_cell(line: &str) -> Option<UnitCell> {
+    let field = |a: usize, b: usize| -> Option<f64> {
+        line.get(a..b.min(line.len()))?.trim().parse().ok()
+    };
+    UnitCell::new(
+        field(6, 15)?,
+        field(15, 24)?,
+        field(24, 33)?,
+        field(33, 40)?,
+        field(40, 47)?,
+        field(47, 54)?,
+    )
+}
+
+/// Close the current `APPLY ... CHAINS` block of the last biomolecule.
+fn flush_gen(
+    assemblies: &mut [BioAssembly],
+    chains: &mut Vec<String>,
+    biomt: &mut MatrixRecords,
+) {
+    let operators = biomt.take();
+    let chains = std::mem::take(chains);
+    if operators.is_empty() || chains.is_empty() {
+        return;
+    }
+    if let Some(assembly) = assemblies.last_mut() {
+        assembly.gens.push(AssemblyGen { chains, operators });
+    }
+}
+
+/// Accumulates the three-line `BIOMTn` / `SMTRYn` matrix records.
+#[derive(Default)]
+struct MatrixRecords {
+    done: Vec<Mat4>,
+    rows: Vec<[f32; 4]>,
+}
+
+impl MatrixRecords {
+    /// Feed one remark body, e.g. `"  BIOMT1   1  1.0 0.0 0.0  0.0"`.
+    fn push(&mut self, rest: &str, key: &str) {
+        let mut fields = rest.split_whitespace();
+        let Some(tag) = fields.next().and_then(|t| t.strip_prefix(key)) else {
+            return;
+        };
+        let Ok(row_index) = tag.parse::<usize>() else {
+            return;
+        };
+        let values: Vec<f32> =
+            fields.skip(1).filter_map(|f| f.parse().ok()).collect();
+        let [a, b, c, t] = values[..] else {
+            return;
+        };
+        if row_index == 1 {
+            self.rows.clear();
+        }
+        self.rows.push([a, b, c, t]);
+        if row_index == 3 && self.rows.len() == 3 {
+            let rows = [self.rows[0], self.rows[1], self.rows[2]];
+            self.done.push(mat4_from_rows(&rows));
+            self.rows.clear();
+        }
+    }
+
+    fn take(&mut self) -> Vec<Mat4> {
+        self.rows.clear();
+        std::mem::take(&mut self.done)
+    }
+}
+
+/// Affine matrix from three `[r0, r1, r2, t]` rows.
+fn mat4_from_rows(rows: &[[f32; 4]; 3]) -> Mat4 {
+    let col =
+        |j: usize, w: f32| Vec4::new(rows[0][j], rows[1][j], rows[2][j], w);
+    Mat4::from_cols(col(0, 0.0), col(1, 0.0), col(2, 0.0), col(3, 1.0))
+}
+
+impl UnitCell {
+    /// Convert a Cartesian operator (PDB `SMTRY`) to exact fractional
+    /// form through this cell.
+    fn cartesian_to_symop(&self, m: &Mat4) -> Option<SymOp> {
+        let rot = Mat3::from_mat4(*m).as_dmat3();
+        let trans = m.w_axis.truncate().as_dvec3();
+        let (orth, frac) = (self.orthogonalization(), self.fractionalization());
+        SymOp::from_fractional(frac * rot * orth, frac * trans)
+    }
+}
+
+/// Space-group operators from `symbol` when a file lists none.
+pub(super) fn table_operators(
+    symbol: Option<&str>,
+    cell: Option<&UnitCell>,
+) -> Vec<SymOp> {
+    let rhombohedral = cell.is_some_and(UnitCell::is_rhombohedral);
+    symbol
+        .and_then(|s| space_group::operators(s, rhombohedral))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+
+    const CIF: &str = "data_TEST
+_cell.length_a 50.0
+_cell.length_b 60.0
+_cell.length_c 70.0
+_cell.angle_alpha 90.0
+_cell.angle_beta 90.0
+_cell.angle_gamma 90.0
+_symmetry.space_group_name_H-M 'P 21 21 21'
+loop_
+_pdbx_struct_assembly.id
+_pdbx_struct_assembly.details
+1 author_defined_assembly
+2 software_defined_assembly
+loop_
+_pdbx_struct_assembly_gen.assembly_id
+_pdbx_struct_assembly_gen.oper_expression
+_pdbx_struct_assembly_gen.asym_id_list
+1 1,2 A,C
+2 '(1)(2)' B
+loop_
+_pdbx_struct_oper_list.id
+_pdbx_struct_oper_list.type
+_pdbx_struct_oper_list.matrix[1][1]
+_pdbx_struct_oper_list.matrix[1][2]
+_pdbx_struct_oper_list.matrix[1][3]
+_pdbx_struct_oper_list.vector[1]
+_pdbx_struct_oper_list.matrix[2][1]
+_pdbx_struct_oper_list.matrix[2][2]
+_pdbx_struct_oper_list.matrix[2][3]
+_pdbx_struct_oper_list.vector[2]
+_pdbx_struct_oper_list.matrix[3][1]
+_pdbx_struct_oper_list.matrix[3][2]
+_pdbx_struct_oper_list.matrix[3][3]
+_pdbx_struct_oper_list.vector[3]
+1 'identity operation' 1 0 0 0 0 1 0 0 0 0 1 0
+2 'crystal symmetry operation' -1 0 0 25 0 -1 0 0 0 0 1 35
+loop_
+_atom_site.group_PDB
+_atom_site.id
+_atom_site.label_asym_id
+_atom_site.auth_asym_id
+ATOM 1 A X
+ATOM 2 B Y
+ATOM 3 C X
+";
+
+    #[test]
+    fn reads_mmcif_assemblies_and_cell() {
+        let sym = from_mmcif_str(CIF).unwrap();
+        let cell = sym.cell.unwrap();
+        assert!((cell.b - 60.0).abs() < 1e-9);
+        assert_eq!(sym.space_group.as_deref(), Some("P 21 21 21"));
+        assert_eq!(sym.symmetry_operator_count(), 4);
+
+        assert_eq!(sym.assemblies.len(), 2);
+        let first = &sym.assemblies[0];
+        assert_eq!(first.details.as_deref(), Some("author_defined_assembly"));
+        // label A and C are both author chain X.
+        assert_eq!(first.gens[0].chains, vec!["X".to_owned()]);
+        assert_eq!(first.gens[0].operators.len(), 2);
+        let p = first.gens[0].operators[1].transform_point3(Vec3::ONE);
+        assert!((p - Vec3::new(24.0, -1.0, 36.0)).length() < 1e-5);
+
+        let product = &sym.assemblies[1].gens[0];
+        assert_eq!(product.chains, vec!["Y".to_owned()]);
+        assert_eq!(product.operators.len(), 1);
+    }
+
+    #[test]
+    fn expands_ranges_and_products() {
+        let ops: HashMap<String, Mat4> = (1..=4)
+            .map(|i| {
+                (i.to_string(), Mat4::from_translation(Vec3::X * i as f32))
+            })
+            .collect();
+        assert_eq!(expand_oper_expression("1-3", &ops).unwrap().len(), 3);
+        assert_eq!(
+            expand_oper_expression("(1,2)(3-4)", &ops).unwrap().len(),
+            4
+        );
+        assert!(expand_oper_expression("(5)", &ops).is_none());
+    }
+
+    const PDB: &str = "\
+CRYST1   50.000   60.000   70.000  90.00  90.00  90.00 P 21 21 21    4
+REMARK 290   SMTRY1   1  1.000000  0.000000  0.000000        0.00000
+REMARK 290   SMTRY2   1  0.000000  1.000000  0.000000        0.00000
+REMARK 290   SMTRY3   1  0.000000  0.000000  1.000000        0.00000
+REMARK 290   SMTRY1   2 -1.000000  0.000000  0.000000       25.00000
+REMARK 290   SMTRY2   2  0.000000 -1.000000  0.000000        0.00000
+REMARK 290   SMTRY3   2  0.000000  0.000000  1.000000       35.00000
+REMARK 350 BIOMOLECULE: 1
+REMARK 350 AUTHOR DETERMINED BIOLOGICAL UNIT: DIMERIC
+REMARK 350 APPLY THE FOLLOWING TO CHAINS: A,
+REMARK 350                    AND CHAINS: B
+REMARK 350   BIOMT1   1  1.000000  0.000000  0.000000        0.00000
+REMARK 350   BIOMT2   1  0.000000  1.000000  0.000000        0.00000
+REMARK 350   BIOMT3   1  0.000000  0.000000  1.000000        0.00000
+REMARK 350   BIOMT1   2 -1.000000  0.000000  0.000000       25.00000
+REMARK 350   BIOMT2   2  0.000000 -1.000000  0.000000        0.00000
+REMARK 350   BIOMT3   2  0.000000  0.000000  1.000000       35.00000
+REMARK 350 BIOMOLECULE: 2
+REMARK 350 APPLY THE FOLLOWING TO CHAINS: C
+REMARK 350   BIOMT1   1  1.000000  0.000000  0.000000        0.00000
+REMARK 350   BIOMT2   1  0.000000  1.000000  0.000000        0.00000
+REMARK 350   BIOMT3   1  0.000000  0.000000  1.000000        0.00000
+";
+
+    #[test]
+    fn reads_pdb_remarks() {
+        let sym = from_pdb_str(PDB);
+        assert_eq!(sym.space_group.as_deref(), Some("P 21 21 21"));
+        // Explicit SMTRY records win over the space-group table.
+        assert_eq!(sym.symmetry_operator_count(), 2);
+        assert_eq!(sym.assemblies.len(), 2);
+        let first = &sym.assemblies[0];
+        assert_eq!(first.details.as_deref(), Some("DIMERIC"));
+        assert_eq!(first.gens[0].chains, vec!["A", "B"]);
+        assert_eq!(first.gens[0].operators.len(), 2);
+        assert_eq!(sym.assemblies[1].gens[0].operators, vec![Mat4::IDENTITY]);
+    }
+}
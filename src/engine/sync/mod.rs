@@ -23,6 +23,7 @@ use std::collections::HashMap;
 use molex::{Assembly, SSType};
 pub(crate) use pipeline::SyncPipeline;
 
+use super::events::VisoEvent;
 use super::trajectory::TrajectoryFrame;
 use super::VisoEngine;
 use crate::animation::transition::Transition;
@@ -60,13 +61,18 @@ impl VisoEngine {
 
     /// Apply any pending scene data from the background `SceneProcessor`.
     pub fn apply_pending_scene(&mut self) {
-        SyncPipeline::apply_pending_scene(
+        let applied = SyncPipeline::apply_pending_scene(
             &mut self.scene,
             &self.annotations,
             &self.options,
             &mut self.gpu,
             &mut self.animation,
         );
+        if applied {
+            self.events.push(VisoEvent::SceneSynced {
+                generation: self.scene.current.generation(),
+            });
+        }
     }
 
     /// Apply any pending animation frame from the background thread.
@@ -18,12 +18,14 @@ use super::super::annotations::EntityAnnotations;
 use super::super::entity_view::{EntityView, RibbonBackbone};
 use super::super::scene::Scene;
 use super::super::scene_state::{BondResolveInput, SceneRenderState};
+use super::super::secondary_structure::entity_dssp;
 use super::super::trajectory::TrajectoryFrame;
 use crate::animation::transition::Transition;
 use crate::animation::AnimationState;
 use crate::options::{
-    DisplayOptions, DrawingMode, GeometryOptions, VisoOptions,
+    DisplayOptions, DrawingMode, GeometryOptions, SsSource, VisoOptions,
 };
+use crate::renderer::geometry::backbone::profile::putty_radius_scales;
 use crate::renderer::gpu_pipeline::SceneChainData;
 use crate::renderer::pipeline::prepared::{
     FullRebuildBody, FullRebuildEntity, PreparedRebuild,
@@ -58,10 +60,18 @@ impl SyncPipeline {
             let id = entity.id();
             let _ = seen.insert(id);
             let ss = assembly.ss_types(id);
-            let ss_override = annotations.ss_overrides.get(&id).cloned();
             let topology = Arc::new(
                 crate::engine::entity_view::derive_topology(entity, ss),
             );
+            let positions = entity.positions();
+            // Edited structures: keep viewer DSSP in step with the new
+            // coordinates while it's the rendered source.
+            if options.geometry.ss_source == SsSource::Computed {
+                if let Some(codes) = entity_dssp(&topology, &positions) {
+                    let _ = annotations.computed_ss.insert(id, codes);
+                }
+            }
+            let ss_override = annotations.ss_override_for(options, id);
             let drawing_mode = annotations.resolved_drawing_mode(
                 options,
                 id,
@@ -87,9 +97,7 @@ impl SyncPipeline {
                     });
                 }
             }
-            scene
-                .positions
-                .insert_from_reference(id, &entity.positions());
+            scene.positions.insert_from_reference(id, &positions);
 
             // New entity? Seed visibility from ambient-type defaults.
             if let std::collections::hash_map::Entry::Vacant(slot) =
@@ -295,12 +303,20 @@ impl SyncPipeline {
                     &backbone_chains,
                     &ss_types,
                     annotations.scores.get(&eid).map(Vec::as_slice),
+                    annotations.rmsf.get(&eid).map(Vec::as_slice),
+                    annotations.sasa.get(&eid).map(Vec::as_slice),
                     &display,
                 )
             } else {
                 None
             };
             state.per_residue_colors.clone_from(&per_residue_colors);
+            let per_residue_radii = state
+                .topology
+                .is_protein()
+                .then(|| annotations.rmsf.get(&eid))
+                .flatten()
+                .map(|rmsf| putty_radius_scales(rmsf));
 
             result.push(FullRebuildEntity {
                 id: eid,
@@ -310,6 +326,7 @@ impl SyncPipeline {
                 positions,
                 ss_override: state.ss_override.clone(),
                 per_residue_colors,
+                per_residue_radii,
             });
         }
         result
@@ -366,15 +383,16 @@ impl SyncPipeline {
     }
 
     /// Apply any pending scene data from the background `SceneProcessor`.
+    /// Returns `true` if a prepared rebuild was consumed.
     pub(crate) fn apply_pending_scene(
         scene: &mut Scene,
         annotations: &EntityAnnotations,
         options: &VisoOptions,
         gpu: &mut GpuPipeline,
         animation: &mut AnimationState,
-    ) {
+    ) -> bool {
         let Some(prepared) = gpu.scene_processor.try_recv_rebuild() else {
-            return;
+            return false;
         };
 
         let entity_transitions = animation.take_pending_transitions();
@@ -403,6 +421,7 @@ impl SyncPipeline {
             animating,
             suppress_sidechains,
         );
+        true
     }
 
     /// Kick off per-entity animation runners using the current
@@ -464,7 +483,7 @@ impl SyncPipeline {
             scene.positions.set(entity.id(), entity.positions());
         }
         Self::ensure_gpu_capacity_and_colors(scene, annotations, gpu);
-        let flat_colors = scene.flat_cartoon_colors(annotations);
+        let flat_colors = scene.flat_protein_colors(annotations);
         if !flat_colors.is_empty() {
             gpu.set_colors_immediate(&flat_colors);
         }
@@ -482,7 +501,7 @@ impl SyncPipeline {
                 &backbone_chains,
             );
         gpu.ensure_residue_capacity(total_residues);
-        let flat_colors = scene.flat_cartoon_colors(annotations);
+        let flat_colors = scene.flat_protein_colors(annotations);
         if !flat_colors.is_empty() {
             gpu.set_target_colors(&flat_colors);
         }
@@ -510,15 +529,16 @@ impl SyncPipeline {
         scene: &mut Scene,
         frame: &TrajectoryFrame,
     ) {
-        let Some(slot) = scene.positions.get_mut(frame.entity) else {
-            return;
-        };
-        for (i, &idx) in frame.atom_indices.iter().enumerate() {
-            let Some(pos) = frame.positions.get(i).copied() else {
+        for update in &frame.entities {
+            let Some(slot) = scene.positions.get_mut(update.entity) else {
                 continue;
             };
-            if let Some(target) = slot.get_mut(idx as usize) {
-                *target = pos;
+            for (&idx, &pos) in
+                update.atom_indices.iter().zip(&update.positions)
+            {
+                if let Some(target) = slot.get_mut(idx as usize) {
+                    *target = pos;
+                }
             }
         }
     }
@@ -581,10 +601,12 @@ impl SyncPipeline {
                 &backbone_chains,
                 &ss_types,
                 annotations.scores.get(&eid).map(Vec::as_slice),
+                annotations.rmsf.get(&eid).map(Vec::as_slice),
+                annotations.sasa.get(&eid).map(Vec::as_slice),
                 &display,
             );
         }
-        let flat = scene.flat_cartoon_colors(annotations);
+        let flat = scene.flat_protein_colors(annotations);
         if !flat.is_empty() {
             gpu.set_colors_immediate(&flat);
         }
@@ -598,6 +620,8 @@ fn per_entity_colors(
     backbone_chains: &[crate::renderer::entity_topology::ProteinBackboneChain],
     ss_types: &[SSType],
     scores: Option<&[f64]>,
+    rmsf: Option<&[f32]>,
+    sasa: Option<&[f32]>,
     display: &DisplayOptions,
 ) -> Option<Vec<[f32; 3]>> {
     if backbone_chains.is_empty() {
@@ -608,6 +632,8 @@ fn per_entity_colors(
         backbone_chains,
         ss_types,
         &scores_slice,
+        rmsf,
+        sasa,
         &display.backbone_color_scheme(),
         &display.backbone_palette(),
         entity_index,
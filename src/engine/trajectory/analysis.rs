@@ -0,0 +1,261 @@
+//! Structural analysis over a loaded trajectory: per-frame RMSD to a
+//! reference frame after optimal superposition, per-residue RMSF,
+//! radius of gyration, and per-residue displacement.
+//!
+//! Every metric is computed over one representative atom per residue
+//! (CA for protein, P for nucleic acid) so the series line up with the
+//! per-residue color and geometry channels. Frames are superposed onto
+//! the reference with [`superpose::fit`] before RMSD, RMSF and
+//! displacement are measured; radius of gyration is fit-invariant.
+
+use glam::{DVec3, Vec3};
+use molex::entity::molecule::id::EntityId;
+
+use crate::util::superpose::{self, RigidFit};
+
+/// One representative atom of one residue, as seen by the trajectory.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Probe {
+    /// Entity the residue belongs to.
+    pub(crate) entity: EntityId,
+    /// Entity-local residue index.
+    pub(crate) residue: u32,
+    /// Trajectory atom index of the representative atom.
+    pub(crate) trajectory_atom: usize,
+}
+
+/// A per-residue value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResidueMetric {
+    /// Raw id of the entity the residue belongs to.
+    pub entity_id: u32,
+    /// Entity-local residue index.
+    pub residue: u32,
+    /// The metric, in Ångström.
+    pub value: f32,
+}
+
+/// Trajectory-wide analysis results.
+#[derive(Debug, Clone)]
+pub struct TrajectoryAnalysis {
+    /// Frame every other frame was superposed onto.
+    pub reference_frame: usize,
+    /// RMSD to the reference frame after superposition, per frame.
+    pub rmsd: Vec<f32>,
+    /// Radius of gyration of the representative atoms, per frame.
+    pub radius_of_gyration: Vec<f32>,
+    /// Root-mean-square fluctuation about the mean superposed
+    /// position, per residue.
+    pub rmsf: Vec<ResidueMetric>,
+    /// Residues the series were computed over (parallel to `rmsf`).
+    probes: Vec<Probe>,
+    /// Representative-atom positions in the reference frame.
+    reference: Vec<Vec3>,
+    /// Superposition of each frame onto the reference.
+    fits: Vec<RigidFit>,
+}
+
+impl TrajectoryAnalysis {
+    /// Per-residue distance between `frame` (superposed) and the
+    /// reference frame. `None` if `frame` is out of range.
+    pub(crate) fn displacement(
+        &self,
+        frames: &[Vec<Vec3>],
+        frame: usize,
+    ) -> Option<Vec<ResidueMetric>> {
+        let fit = self.fits.get(frame)?;
+        let coords = probe_positions(frames, frame, &self.probes);
+        Some(
+            self.probes
+                .iter()
+                .zip(coords.iter().zip(&self.reference))
+                .map(|(probe, (&p, &r))| {
+                    metric(probe, fit.apply(p).distance(r))
+                })
+                .collect(),
+        )
+    }
+
+    /// RMSF values of `entity`'s residues in probe order.
+    pub(crate) fn entity_rmsf(
+        &self,
+        entity: EntityId,
+    ) -> impl Iterator<Item = (u32, f32)> + '_ {
+        self.probes
+            .iter()
+            .zip(&self.rmsf)
+            .filter(move |(probe, _)| probe.entity == entity)
+            .map(|(probe, m)| (probe.residue, m.value))
+    }
+}
+
+/// Analyze `frames` over `probes`, superposing every frame onto
+/// `reference_frame` (clamped to the trajectory). `None` if there are
+/// no frames or no probes.
+pub(crate) fn analyze(
+    frames: &[Vec<Vec3>],
+    probes: Vec<Probe>,
+    reference_frame: usize,
+) -> Option<TrajectoryAnalysis> {
+    if frames.is_empty() || probes.is_empty() {
+        return None;
+    }
+    let reference_frame = reference_frame.min(frames.len() - 1);
+    let reference = probe_positions(frames, reference_frame, &probes);
+
+    let mut rmsd = Vec::with_capacity(frames.len());
+    let mut radius_of_gyration = Vec::with_capacity(frames.len());
+    let mut fits = Vec::with_capacity(frames.len());
+    let mut sum = vec![DVec3::ZERO; probes.len()];
+    let mut sum_sq = vec![0.0f64; probes.len()];
+    for frame in 0..frames.len() {
+        let coords = probe_positions(frames, frame, &probes);
+        let fit = superpose::fit(&coords, &reference)?;
+        radius_of_gyration.push(gyration_radius(&coords));
+        rmsd.push(fit.rmsd);
+        for (i, &p) in coords.iter().enumerate() {
+            let aligned = fit.apply(p).as_dvec3();
+            sum[i] += aligned;
+            sum_sq[i] += aligned.length_squared();
+        }
+        fits.push(fit);
+    }
+
+    let n = frames.len() as f64;
+    let rmsf = probes
+        .iter()
+        .zip(sum.iter().zip(&sum_sq))
+        .map(|(probe, (&s, &sq))| {
+            let mean = s / n;
+            let msf = (sq / n - mean.length_squared()).max(0.0);
+            metric(probe, msf.sqrt() as f32)
+        })
+        .collect();
+
+    Some(TrajectoryAnalysis {
+        reference_frame,
+        rmsd,
+        radius_of_gyration,
+        rmsf,
+        probes,
+        reference,
+        fits,
+    })
+}
+
+/// Representative-atom positions at `frame` (missing atoms read as the
+/// origin, matching the trajectory sampler).
+fn probe_positions(
+    frames: &[Vec<Vec3>],
+    frame: usize,
+    probes: &[Probe],
+) -> Vec<Vec3> {
+    let positions = frames.get(frame).map_or(&[][..], Vec::as_slice);
+    probes
+        .iter()
+        .map(|p| {
+            positions
+                .get(p.trajectory_atom)
+                .copied()
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Unweighted radius of gyration.
+fn gyration_radius(coords: &[Vec3]) -> f32 {
+    if coords.is_empty() {
+        return 0.0;
+    }
+    let n = coords.len() as f32;
+    let center = coords.iter().copied().sum::<Vec3>() / n;
+    let msd = coords
+        .iter()
+        .map(|p| p.distance_squared(center))
+        .sum::<f32>()
+        / n;
+    msd.sqrt()
+}
+
+fn metric(probe: &Probe, value: f32) -> ResidueMetric {
+    ResidueMetric {
+        entity_id: probe.entity.raw(),
+        residue: probe.residue,
+        value,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use glam::Quat;
+    use molex::entity::molecule::id::EntityIdAllocator;
+
+    use super::*;
+
+    fn probes(n: usize) -> Vec<Probe> {
+        let entity = EntityIdAllocator::new().allocate();
+        (0..n)
+            .map(|i| Probe {
+                entity,
+                residue: i as u32,
+                trajectory_atom: i,
+            })
+            .collect()
+    }
+
+    fn base() -> Vec<Vec3> {
+        vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(3.8, 0.0, 0.0),
+            Vec3::new(3.8, 3.8, 0.0),
+            Vec3::new(0.0, 3.8, 3.8),
+        ]
+    }
+
+    #[test]
+    fn rigid_motion_has_zero_rmsd_and_rmsf() {
+        let rot = Quat::from_rotation_z(1.1);
+        let moved: Vec<Vec3> =
+            base().iter().map(|&p| rot * p + Vec3::X * 5.0).collect();
+        let a = analyze(&[base(), moved], probes(4), 0).unwrap();
+        assert!(a.rmsd.iter().all(|&r| r < 1e-3));
+        assert!(a.rmsf.iter().all(|m| m.value < 1e-3));
+        assert!(
+            (a.radius_of_gyration[0] - a.radius_of_gyration[1]).abs() < 1e-4
+        );
+    }
+
+    #[test]
+    fn fluctuating_residue_has_largest_rmsf() {
+        let mut frames = vec![base(); 4];
+        frames[1][3].z += 1.0;
+        frames[3][3].z -= 1.0;
+        let a = analyze(&frames, probes(4), 0).unwrap();
+        let max = a
+            .rmsf
+            .iter()
+            .max_by(|x, y| x.value.total_cmp(&y.value))
+            .unwrap();
+        assert_eq!(max.residue, 3);
+        assert!(a.rmsd[1] > 0.0 && a.rmsd[2] < 1e-4);
+    }
+
+    #[test]
+    fn displacement_measures_distance_from_reference() {
+        let mut frames = vec![base(); 2];
+        frames[1][0].x -= 0.5;
+        let a = analyze(&frames, probes(4), 0).unwrap();
+        let d = a.displacement(&frames, 1).unwrap();
+        assert_eq!(d.len(), 4);
+        assert!(d[0].value > d[2].value);
+        assert!(a.displacement(&frames, 9).is_none());
+    }
+
+    #[test]
+    fn reference_frame_is_clamped() {
+        let a = analyze(&[base(), base()], probes(4), 7).unwrap();
+        assert_eq!(a.reference_frame, 1);
+        assert!(analyze(&[], probes(4), 0).is_none());
+    }
+}
@@ -0,0 +1,161 @@
+//! Temporal filters applied when sampling trajectory frames: a
+//! centered moving-average window to calm thermal noise. The same
+//! settings switch on sub-frame interpolation, which the player hands
+//! to the structure animator as a per-step transition.
+
+use std::collections::VecDeque;
+
+use glam::Vec3;
+
+/// Largest accepted moving-average window, in frames.
+pub(super) const MAX_SMOOTHING_WINDOW: usize = 101;
+
+/// Smoothed frames kept by [`SmoothedFrames`]: the displayed frame and
+/// the one before it.
+const CACHED_FRAMES: usize = 2;
+
+/// Per-trajectory temporal filter settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TrajectorySmoothing {
+    /// Animate between consecutive frames over the frame interval
+    /// instead of snapping from one frame to the next.
+    pub interpolate: bool,
+    /// Moving-average window in frames, centered on the displayed
+    /// frame. `0` or `1` disables smoothing; even values are rounded
+    /// up to the next odd width.
+    pub window: usize,
+}
+
+impl TrajectorySmoothing {
+    /// Half-width of the centered averaging window.
+    pub(super) fn half_window(self) -> usize {
+        self.window.min(MAX_SMOOTHING_WINDOW) / 2
+    }
+}
+
+/// Position of trajectory atom `atom` at frame `frame`, averaged over
+/// frames `frame - half..=frame + half` (clamped to the trajectory).
+pub(super) fn sample(
+    frames: &[Vec<Vec3>],
+    frame: usize,
+    atom: usize,
+    half: usize,
+) -> Vec3 {
+    let at = |f: usize| {
+        frames
+            .get(f)
+            .and_then(|positions| positions.get(atom))
+            .copied()
+            .unwrap_or(Vec3::ZERO)
+    };
+    if half == 0 {
+        return at(frame);
+    }
+    let first = frame.saturating_sub(half);
+    let last = (frame + half).min(frames.len().saturating_sub(1));
+    if last < first {
+        return at(frame);
+    }
+    let sum: Vec3 = (first..=last).map(at).sum();
+    sum / (last - first + 1) as f32
+}
+
+/// Whole frames averaged by [`sample`], keyed by frame and half-window,
+/// so re-sampling a recent frame (a smoothing toggle, a step back)
+/// does not average its window again.
+#[derive(Default)]
+pub(super) struct SmoothedFrames {
+    /// `(frame, half, positions)`, least recently used first.
+    entries: VecDeque<(usize, usize, Vec<Vec3>)>,
+}
+
+impl SmoothedFrames {
+    /// Average frame `frame` of `frames` over `half` frames either side
+    /// unless it is cached, and mark it most recently used. Evicts the
+    /// least recently used frame beyond [`CACHED_FRAMES`].
+    pub(super) fn prepare(
+        &mut self,
+        frames: &[Vec<Vec3>],
+        frame: usize,
+        half: usize,
+    ) {
+        let hit = self
+            .entries
+            .iter()
+            .position(|&(f, h, _)| f == frame && h == half);
+        let entry =
+            hit.and_then(|i| self.entries.remove(i)).unwrap_or_else(|| {
+                let atoms = frames.get(frame).map_or(0, Vec::len);
+                let positions = (0..atoms)
+                    .map(|atom| sample(frames, frame, atom, half))
+                    .collect();
+                (frame, half, positions)
+            });
+        self.entries.push_back(entry);
+        if self.entries.len() > CACHED_FRAMES {
+            let _ = self.entries.pop_front();
+        }
+    }
+
+    /// A frame [`prepare`](Self::prepare)d with the same `half`, or an
+    /// empty slice if it has been evicted since.
+    pub(super) fn get(&self, frame: usize, half: usize) -> &[Vec3] {
+        self.entries
+            .iter()
+            .find(|&&(f, h, _)| f == frame && h == half)
+            .map_or(&[], |(_, _, positions)| positions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames() -> Vec<Vec<Vec3>> {
+        [0.0, 3.0, 0.0, 3.0, 0.0]
+            .iter()
+            .map(|&x| vec![Vec3::new(x, 0.0, 0.0)])
+            .collect()
+    }
+
+    #[test]
+    fn no_window_returns_raw_frame() {
+        assert_eq!(sample(&frames(), 1, 0, 0).x, 3.0);
+    }
+
+    #[test]
+    fn window_averages_neighbors() {
+        assert!((sample(&frames(), 2, 0, 1).x - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn window_is_clamped_at_trajectory_ends() {
+        assert!((sample(&frames(), 0, 0, 1).x - 1.5).abs() < 1e-6);
+        assert!((sample(&frames(), 4, 0, 1).x - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn even_window_rounds_up() {
+        let s = TrajectorySmoothing {
+            interpolate: false,
+            window: 4,
+        };
+        assert_eq!(s.half_window(), 2);
+    }
+
+    #[test]
+    fn smoothed_frames_match_sampling_and_keep_two() {
+        let frames = frames();
+        let mut cache = SmoothedFrames::default();
+        for (frame, half) in [(2, 1), (0, 1), (2, 0)] {
+            cache.prepare(&frames, frame, half);
+        }
+        assert!(cache.get(2, 1).is_empty());
+        assert!((cache.get(0, 1)[0].x - 1.5).abs() < 1e-6);
+        assert_eq!(cache.get(2, 0)[0].x, 0.0);
+        cache.prepare(&frames, 0, 1);
+        cache.prepare(&frames, 2, 1);
+        assert!(cache.get(2, 0).is_empty());
+        assert!((cache.get(2, 1)[0].x - 2.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,127 @@
+//! Trajectory file readers.
+//!
+//! Every supported format decodes to the same [`TrajectoryData`]: a
+//! flat per-frame list of atom positions in Ångström, in file atom
+//! order. The player maps that order onto entity atoms; nothing
+//! downstream knows which format a trajectory came from.
+//!
+//! | Extension            | Format                                      |
+//! |----------------------|---------------------------------------------|
+//! | `dcd`                | CHARMM / NAMD DCD (via molex)               |
+//! | `xtc`                | GROMACS compressed coordinates              |
+//! | `trr`                | GROMACS full-precision trajectory           |
+//! | `xyz`                | Multi-frame XYZ                             |
+//! | `pdb`, `ent`         | Multi-model PDB (one `MODEL` per frame)     |
+//! | `cif`, `mmcif`       | Multi-model mmCIF (`pdbx_PDB_model_num`)    |
+
+mod trr;
+mod xdr;
+mod xtc;
+mod xyz;
+
+use std::path::Path;
+
+use glam::Vec3;
+use molex::MoleculeEntity;
+
+/// GROMACS stores coordinates in nanometres.
+const NM_TO_ANGSTROM: f32 = 10.0;
+
+/// Decoded trajectory: per-frame atom positions in file order.
+pub(crate) struct TrajectoryData {
+    /// Atoms per frame (every frame has exactly this many).
+    pub(crate) num_atoms: usize,
+    /// Frame positions in Ångström.
+    pub(crate) frames: Vec<Vec<Vec3>>,
+}
+
+/// Read a trajectory file, choosing the decoder by extension.
+///
+/// # Errors
+///
+/// Returns a message if the extension is unsupported, the file cannot
+/// be read, or decoding fails.
+pub(crate) fn read_trajectory(path: &Path) -> Result<TrajectoryData, String> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let read_bytes = || {
+        std::fs::read(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))
+    };
+    let data = match ext.as_str() {
+        "dcd" => read_dcd(path)?,
+        "xtc" => xtc::parse(&read_bytes()?)?,
+        "trr" => trr::parse(&read_bytes()?)?,
+        "xyz" => {
+            let bytes = read_bytes()?;
+            xyz::parse(&String::from_utf8_lossy(&bytes))?
+        }
+        "pdb" | "ent" => from_models(
+            &molex::adapters::pdb::pdb_file_to_all_models(path)
+                .map_err(|e| e.to_string())?,
+        )?,
+        "cif" | "mmcif" => from_models(
+            &molex::adapters::cif::mmcif_file_to_all_models(path)
+                .map_err(|e| e.to_string())?,
+        )?,
+        other => {
+            return Err(format!("unsupported trajectory format {other:?}"))
+        }
+    };
+    if data.frames.is_empty() {
+        return Err("trajectory contains no frames".to_owned());
+    }
+    Ok(data)
+}
+
+/// Convert molex's split-axis DCD frames.
+fn read_dcd(path: &Path) -> Result<TrajectoryData, String> {
+    let (header, frames) = molex::adapters::dcd::dcd_file_to_frames(path)
+        .map_err(|e| e.to_string())?;
+    let frames = frames
+        .into_iter()
+        .map(|f| {
+            f.x.iter()
+                .zip(&f.y)
+                .zip(&f.z)
+                .map(|((&x, &y), &z)| Vec3::new(x, y, z))
+                .collect()
+        })
+        .collect();
+    Ok(TrajectoryData {
+        num_atoms: header.num_atoms as usize,
+        frames,
+    })
+}
+
+/// Treat each model of an ensemble as one frame, concatenating every
+/// entity's atoms in model order.
+fn from_models(
+    models: &[Vec<MoleculeEntity>],
+) -> Result<TrajectoryData, String> {
+    let frames: Vec<Vec<Vec3>> = models
+        .iter()
+        .map(|entities| {
+            entities
+                .iter()
+                .flat_map(MoleculeEntity::positions)
+                .collect()
+        })
+        .collect();
+    let num_atoms = frames.first().map_or(0, Vec::len);
+    if let Some((i, f)) = frames
+        .iter()
+        .enumerate()
+        .find(|(_, f)| f.len() != num_atoms)
+    {
+        return Err(format!(
+            "model {} has {} atoms, expected {num_atoms}",
+            i + 1,
+            f.len()
+        ));
+    }
+    Ok(TrajectoryData { num_atoms, frames })
+}
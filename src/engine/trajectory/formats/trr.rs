@@ -0,0 +1,229 @@
+//! GROMACS TRR reader.
+//!
+//! TRR frames carry uncompressed box, virial, pressure, coordinate,
+//! velocity and force blocks, each optional and sized in the frame
+//! header. Reals are single or double precision depending on how the
+//! file was written; the width is inferred from the block sizes.
+//! Frames without coordinates (velocity- or force-only) are skipped.
+//! Coordinates are stored in nanometres and returned in Ångström.
+
+use glam::Vec3;
+
+use super::xdr::XdrReader;
+use super::{TrajectoryData, NM_TO_ANGSTROM};
+
+/// TRR frame magic number.
+const TRR_MAGIC: i32 = 1993;
+
+/// Block sizes (in bytes) declared by a frame header.
+struct FrameHeader {
+    skip_before_box: usize,
+    box_size: usize,
+    vir_size: usize,
+    pres_size: usize,
+    x_size: usize,
+    v_size: usize,
+    f_size: usize,
+    natoms: usize,
+    /// Width of one real in bytes (4 or 8).
+    real_size: usize,
+}
+
+/// Parse every coordinate-bearing frame of a TRR file.
+pub(super) fn parse(bytes: &[u8]) -> Result<TrajectoryData, String> {
+    let mut xdr = XdrReader::new(bytes);
+    let mut frames = Vec::new();
+    let mut num_atoms = None;
+    let mut index = 0;
+    while !xdr.is_at_end() {
+        let frame = read_frame(&mut xdr)
+            .map_err(|e| format!("TRR frame {index}: {e}"))?;
+        index += 1;
+        let Some(frame) = frame else { continue };
+        match num_atoms {
+            None => num_atoms = Some(frame.len()),
+            Some(n) if n != frame.len() => {
+                return Err(format!(
+                    "TRR frame {} has {} atoms, expected {n}",
+                    index - 1,
+                    frame.len()
+                ));
+            }
+            Some(_) => {}
+        }
+        frames.push(frame);
+    }
+    Ok(TrajectoryData {
+        num_atoms: num_atoms.unwrap_or(0),
+        frames,
+    })
+}
+
+/// Read one frame, returning `None` if it carries no coordinates.
+fn read_frame(xdr: &mut XdrReader<'_>) -> Result<Option<Vec<Vec3>>, String> {
+    let header = read_header(xdr)?;
+    xdr.skip(
+        header.skip_before_box
+            + header.box_size
+            + header.vir_size
+            + header.pres_size,
+    )?;
+    let coords = if header.x_size == 0 {
+        None
+    } else {
+        let mut coords = Vec::with_capacity(header.natoms);
+        for _ in 0..header.natoms {
+            let mut v = [0.0f32; 3];
+            for c in &mut v {
+                *c = if header.real_size == 8 {
+                    xdr.read_f64()? as f32
+                } else {
+                    xdr.read_f32()?
+                };
+            }
+            coords.push(Vec3::from(v) * NM_TO_ANGSTROM);
+        }
+        Some(coords)
+    };
+    xdr.skip(header.v_size + header.f_size)?;
+    Ok(coords)
+}
+
+/// Read the frame header: magic, version string, block sizes, atom
+/// count, step, and time / lambda.
+fn read_header(xdr: &mut XdrReader<'_>) -> Result<FrameHeader, String> {
+    let magic = xdr.read_i32()?;
+    if magic != TRR_MAGIC {
+        return Err(format!("bad magic {magic}"));
+    }
+    let _slen = xdr.read_i32()?;
+    let version_len = xdr.read_len()?;
+    let _ = xdr.take_padded(version_len)?;
+
+    // ir, e, box, vir, pres, top, sym, x, v, f sizes; natoms; step;
+    // nre.
+    let mut sizes = [0usize; 13];
+    for s in &mut sizes {
+        *s = xdr.read_len()?;
+    }
+    let [ir, e, box_size, vir_size, pres_size, top, sym, rest @ ..] = sizes;
+    let [x_size, v_size, f_size, natoms, ..] = rest;
+
+    let real_size = infer_real_size(box_size, x_size, v_size, f_size, natoms)?;
+    xdr.skip(2 * real_size)?; // t, lambda
+
+    if x_size != 0 && x_size != natoms * 3 * real_size {
+        return Err(format!(
+            "coordinate block is {x_size} bytes for {natoms} atoms"
+        ));
+    }
+    Ok(FrameHeader {
+        skip_before_box: ir + e + top + sym,
+        box_size,
+        vir_size,
+        pres_size,
+        x_size,
+        v_size,
+        f_size,
+        natoms,
+        real_size,
+    })
+}
+
+/// Infer single vs double precision from whichever block is present.
+fn infer_real_size(
+    box_size: usize,
+    x_size: usize,
+    v_size: usize,
+    f_size: usize,
+    natoms: usize,
+) -> Result<usize, String> {
+    let size = if box_size != 0 {
+        box_size / 9
+    } else {
+        let per_atom = natoms * 3;
+        [x_size, v_size, f_size]
+            .into_iter()
+            .find(|&s| s != 0)
+            .and_then(|s| s.checked_div(per_atom))
+            .unwrap_or(4)
+    };
+    match size {
+        4 | 8 => Ok(size),
+        _ => Err(format!("unsupported real size {size}")),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn be_i32(out: &mut Vec<u8>, v: i32) {
+        out.extend_from_slice(&v.to_be_bytes());
+    }
+
+    /// One TRR frame with a box and coordinates, `real` bytes per
+    /// real, and optionally a velocity block.
+    fn frame(coords: &[[f64; 3]], real: usize, velocities: bool) -> Vec<u8> {
+        let n = coords.len();
+        let put_real = |out: &mut Vec<u8>, v: f64| {
+            if real == 8 {
+                out.extend_from_slice(&v.to_be_bytes());
+            } else {
+                out.extend_from_slice(&(v as f32).to_be_bytes());
+            }
+        };
+        let mut out = Vec::new();
+        be_i32(&mut out, TRR_MAGIC);
+        be_i32(&mut out, 13);
+        be_i32(&mut out, 12);
+        out.extend_from_slice(b"GMX_trn_file");
+        let v_size = if velocities { n * 3 * real } else { 0 };
+        for s in [0, 0, 9 * real, 0, 0, 0, 0, n * 3 * real, v_size, 0, n, 7, 0]
+        {
+            be_i32(&mut out, s as i32);
+        }
+        put_real(&mut out, 1.5);
+        put_real(&mut out, 0.0);
+        for _ in 0..9 {
+            put_real(&mut out, 3.0);
+        }
+        for c in coords.iter().flatten() {
+            put_real(&mut out, *c);
+        }
+        if velocities {
+            for _ in 0..n * 3 {
+                put_real(&mut out, 9.0);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn reads_single_precision_frames() {
+        let mut bytes = frame(&[[0.1, 0.2, 0.3], [1.0, 0.0, 0.0]], 4, true);
+        bytes.extend(frame(&[[0.2, 0.2, 0.3], [1.1, 0.0, 0.0]], 4, false));
+        let data = parse(&bytes).unwrap();
+        assert_eq!(data.num_atoms, 2);
+        assert_eq!(data.frames.len(), 2);
+        assert!(
+            (data.frames[1][1] - Vec3::new(11.0, 0.0, 0.0)).length() < 1e-4
+        );
+    }
+
+    #[test]
+    fn reads_double_precision_frames() {
+        let bytes = frame(&[[0.5, 0.25, 0.125]], 8, false);
+        let data = parse(&bytes).unwrap();
+        assert!(
+            (data.frames[0][0] - Vec3::new(5.0, 2.5, 1.25)).length() < 1e-5
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_frame() {
+        let bytes = frame(&[[0.1, 0.2, 0.3]], 4, false);
+        assert!(parse(&bytes[..bytes.len() - 4]).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+//! Minimal big-endian XDR cursor shared by the GROMACS readers.
+
+/// Read cursor over an in-memory XDR stream.
+pub(super) struct XdrReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> XdrReader<'a> {
+    /// Cursor at the start of `bytes`.
+    pub(super) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Whether every byte has been consumed.
+    pub(super) fn is_at_end(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    /// Take the next `len` raw bytes.
+    pub(super) fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| {
+                format!("unexpected end of file at byte {}", self.pos)
+            })?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    /// Take `len` opaque bytes plus XDR padding to a 4-byte boundary.
+    pub(super) fn take_padded(
+        &mut self,
+        len: usize,
+    ) -> Result<&'a [u8], String> {
+        let data = self.take(len)?;
+        let _ = self.take((4 - len % 4) % 4)?;
+        Ok(data)
+    }
+
+    /// Skip `len` bytes.
+    pub(super) fn skip(&mut self, len: usize) -> Result<(), String> {
+        self.take(len).map(|_| ())
+    }
+
+    /// Read a big-endian `i32`.
+    pub(super) fn read_i32(&mut self) -> Result<i32, String> {
+        let b = self.take(4)?;
+        Ok(i32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Read a big-endian `i32` that must be non-negative.
+    pub(super) fn read_len(&mut self) -> Result<usize, String> {
+        let v = self.read_i32()?;
+        usize::try_from(v).map_err(|_| format!("negative size field {v}"))
+    }
+
+    /// Read a big-endian `f32`.
+    pub(super) fn read_f32(&mut self) -> Result<f32, String> {
+        let b = self.take(4)?;
+        Ok(f32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Read a big-endian `f64`.
+    pub(super) fn read_f64(&mut self) -> Result<f64, String> {
+        let b = self.take(8)?;
+        let mut arr = [0u8; 8];
+        arr.copy_from_slice(b);
+        Ok(f64::from_be_bytes(arr))
+    }
+}
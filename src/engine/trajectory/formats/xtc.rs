@@ -0,0 +1,558 @@
+//! GROMACS XTC reader, including the `xdr3dfcoord` compressed
+//! coordinate decoder.
+//!
+//! Each frame is an XDR header (magic, atom count, step, time, box)
+//! followed by either raw floats (≤ 9 atoms) or a bit-packed stream of
+//! integer coordinates quantized at `precision`. Consecutive atoms close
+//! to each other (water molecules, in particular) are packed as "runs"
+//! of small deltas whose bit width adapts from frame to frame via the
+//! `MAGIC_INTS` table. Coordinates are stored in nanometres and
+//! returned in Ångström.
+
+use glam::Vec3;
+
+use super::xdr::XdrReader;
+use super::{TrajectoryData, NM_TO_ANGSTROM};
+
+/// XTC frame magic number.
+const XTC_MAGIC: i32 = 1995;
+
+/// Bit-width table for the adaptive small-delta encoding: entry `i`
+/// is roughly `2^(i/3)`, so three ints of that size fit in `i` bits.
+const MAGIC_INTS: [u32; 73] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 8, 10, 12, 16, 20, 25, 32, 40, 50, 64, 80, 101,
+    128, 161, 203, 256, 322, 406, 512, 645, 812, 1024, 1290, 1625, 2048, 2580,
+    3250, 4096, 5060, 6501, 8192, 10321, 13003, 16384, 20642, 26007, 32768,
+    41285, 52015, 65536, 82570, 104_031, 131_072, 165_140, 208_063, 262_144,
+    330_280, 416_127, 524_287, 660_561, 832_255, 1_048_576, 1_321_122,
+    1_664_510, 2_097_152, 2_642_245, 3_329_021, 4_194_304, 5_284_491,
+    6_658_042, 8_388_607, 10_568_983, 13_316_085, 16_777_216,
+];
+
+/// First usable index into [`MAGIC_INTS`].
+const FIRST_IDX: usize = 9;
+
+/// Parse every frame of an XTC file.
+pub(super) fn parse(bytes: &[u8]) -> Result<TrajectoryData, String> {
+    let mut xdr = XdrReader::new(bytes);
+    let mut frames = Vec::new();
+    let mut num_atoms = None;
+    while !xdr.is_at_end() {
+        let frame = read_frame(&mut xdr)
+            .map_err(|e| format!("XTC frame {}: {e}", frames.len()))?;
+        match num_atoms {
+            None => num_atoms = Some(frame.len()),
+            Some(n) if n != frame.len() => {
+                return Err(format!(
+                    "XTC frame {} has {} atoms, expected {n}",
+                    frames.len(),
+                    frame.len()
+                ));
+            }
+            Some(_) => {}
+        }
+        frames.push(frame);
+    }
+    Ok(TrajectoryData {
+        num_atoms: num_atoms.unwrap_or(0),
+        frames,
+    })
+}
+
+/// Read one frame: header, box, and coordinates.
+fn read_frame(xdr: &mut XdrReader<'_>) -> Result<Vec<Vec3>, String> {
+    let magic = xdr.read_i32()?;
+    if magic != XTC_MAGIC {
+        return Err(format!("bad magic {magic}"));
+    }
+    let natoms = xdr.read_len()?;
+    let _step = xdr.read_i32()?;
+    let _time = xdr.read_f32()?;
+    xdr.skip(9 * 4)?; // box
+    let lsize = xdr.read_len()?;
+    if lsize != natoms {
+        return Err(format!("atom count mismatch ({lsize} vs {natoms})"));
+    }
+    let coords = if natoms <= 9 {
+        (0..natoms * 3)
+            .map(|_| xdr.read_f32())
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        read_compressed(xdr, natoms)?
+    };
+    Ok(coords
+        .chunks_exact(3)
+        .map(|c| Vec3::new(c[0], c[1], c[2]) * NM_TO_ANGSTROM)
+        .collect())
+}
+
+/// Decode an `xdr3dfcoord` compressed coordinate block into `3 *
+/// natoms` floats (nanometres).
+fn read_compressed(
+    xdr: &mut XdrReader<'_>,
+    natoms: usize,
+) -> Result<Vec<f32>, String> {
+    let precision = xdr.read_f32()?;
+    if precision <= 0.0 || !precision.is_finite() {
+        return Err(format!("invalid precision {precision}"));
+    }
+    let mut minint = [0i32; 3];
+    let mut maxint = [0i32; 3];
+    for v in &mut minint {
+        *v = xdr.read_i32()?;
+    }
+    for v in &mut maxint {
+        *v = xdr.read_i32()?;
+    }
+    let mut sizeint = [0u32; 3];
+    for k in 0..3 {
+        sizeint[k] = maxint[k].wrapping_sub(minint[k]).wrapping_add(1) as u32;
+    }
+    // Large boxes fall back to independent per-axis bit widths.
+    let large = (sizeint[0] | sizeint[1] | sizeint[2]) > 0x00ff_ffff;
+    let bitsizeint = sizeint.map(size_of_int);
+    let bitsize = if large { 0 } else { size_of_ints(&sizeint) };
+
+    let mut smallidx = xdr.read_len()?;
+    if !(FIRST_IDX..MAGIC_INTS.len()).contains(&smallidx) {
+        return Err(format!("invalid smallidx {smallidx}"));
+    }
+    let mut smaller = MAGIC_INTS[FIRST_IDX.max(smallidx - 1)] as i32 / 2;
+    let mut smallnum = MAGIC_INTS[smallidx] as i32 / 2;
+    let mut sizesmall = [MAGIC_INTS[smallidx]; 3];
+
+    let byte_count = xdr.read_len()?;
+    let mut bits = BitReader::new(xdr.take_padded(byte_count)?);
+
+    let inv_precision = 1.0 / precision;
+    let mut out = Vec::with_capacity(natoms * 3);
+    let mut emit = |c: [i32; 3]| {
+        out.extend(c.iter().map(|&v| v as f32 * inv_precision));
+    };
+
+    let mut run: u32 = 0;
+    let mut i = 0;
+    while i < natoms {
+        let mut this = if large {
+            [
+                bits.read(bitsizeint[0])? as i32,
+                bits.read(bitsizeint[1])? as i32,
+                bits.read(bitsizeint[2])? as i32,
+            ]
+        } else {
+            bits.read_ints(bitsize, sizeint)?
+        };
+        i += 1;
+        for k in 0..3 {
+            this[k] = this[k].wrapping_add(minint[k]);
+        }
+        let mut prev = this;
+
+        let mut is_smaller: i32 = 0;
+        if bits.read(1)? == 1 {
+            run = bits.read(5)?;
+            is_smaller = (run % 3) as i32;
+            run -= is_smaller as u32;
+            is_smaller -= 1;
+        }
+        if run > 0 {
+            for k in (0..run).step_by(3) {
+                if i >= natoms {
+                    return Err("run overflows atom count".to_owned());
+                }
+                this = bits.read_ints(smallidx as u32, sizesmall)?;
+                i += 1;
+                for d in 0..3 {
+                    this[d] =
+                        this[d].wrapping_add(prev[d]).wrapping_sub(smallnum);
+                }
+                if k == 0 {
+                    // The encoder swaps the first two atoms of a run
+                    // (better compression for water O-H-H).
+                    std::mem::swap(&mut this, &mut prev);
+                    emit(prev);
+                } else {
+                    prev = this;
+                }
+                emit(this);
+            }
+        } else {
+            emit(this);
+        }
+
+        let next_idx = smallidx as i32 + is_smaller;
+        if !(FIRST_IDX as i32..MAGIC_INTS.len() as i32).contains(&next_idx) {
+            return Err(format!("invalid smallidx {next_idx}"));
+        }
+        smallidx = next_idx as usize;
+        if is_smaller < 0 {
+            smallnum = smaller;
+            smaller = if smallidx > FIRST_IDX {
+                MAGIC_INTS[smallidx - 1] as i32 / 2
+            } else {
+                0
+            };
+        } else if is_smaller > 0 {
+            smaller = smallnum;
+            smallnum = MAGIC_INTS[smallidx] as i32 / 2;
+        }
+        sizesmall = [MAGIC_INTS[smallidx]; 3];
+    }
+    Ok(out)
+}
+
+/// Number of bits needed to represent values in `0..size`.
+fn size_of_int(size: u32) -> u32 {
+    let mut num: u64 = 1;
+    let mut bits = 0;
+    while u64::from(size) >= num && bits < 32 {
+        bits += 1;
+        num <<= 1;
+    }
+    bits
+}
+
+/// Number of bits needed to represent the mixed-radix product of
+/// three values with ranges `sizes`.
+fn size_of_ints(sizes: &[u32; 3]) -> u32 {
+    let mut bytes = [0u32; 32];
+    bytes[0] = 1;
+    let mut num_of_bytes = 1;
+    for &size in sizes {
+        let mut tmp: u64 = 0;
+        let mut bytecnt = 0;
+        while bytecnt < num_of_bytes {
+            tmp += u64::from(bytes[bytecnt]) * u64::from(size);
+            bytes[bytecnt] = (tmp & 0xff) as u32;
+            tmp >>= 8;
+            bytecnt += 1;
+        }
+        while tmp != 0 && bytecnt < bytes.len() {
+            bytes[bytecnt] = (tmp & 0xff) as u32;
+            tmp >>= 8;
+            bytecnt += 1;
+        }
+        num_of_bytes = bytecnt;
+    }
+    let mut num = 1;
+    let mut bits = 0;
+    let top = num_of_bytes - 1;
+    while bytes[top] >= num {
+        bits += 1;
+        num *= 2;
+    }
+    bits + top as u32 * 8
+}
+
+/// MSB-first bit reader over the compressed payload.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    last_bits: u32,
+    last_byte: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            last_bits: 0,
+            last_byte: 0,
+        }
+    }
+
+    fn next_byte(&mut self) -> Result<u32, String> {
+        let b = self
+            .data
+            .get(self.pos)
+            .copied()
+            .ok_or_else(|| "compressed stream truncated".to_owned())?;
+        self.pos += 1;
+        Ok(u32::from(b))
+    }
+
+    /// Read `nbits` (≤ 32) bits as an unsigned integer.
+    fn read(&mut self, mut nbits: u32) -> Result<u32, String> {
+        let mask = ((1u64 << nbits) - 1) as u32;
+        let mut num: u32 = 0;
+        while nbits >= 8 {
+            self.last_byte = (self.last_byte << 8) | self.next_byte()?;
+            num |= (self.last_byte >> self.last_bits) << (nbits - 8);
+            nbits -= 8;
+        }
+        if nbits > 0 {
+            if self.last_bits < nbits {
+                self.last_bits += 8;
+                self.last_byte = (self.last_byte << 8) | self.next_byte()?;
+            }
+            self.last_bits -= nbits;
+            num |= (self.last_byte >> self.last_bits) & ((1 << nbits) - 1);
+        }
+        Ok(num & mask)
+    }
+
+    /// Read three integers packed as one mixed-radix number of
+    /// `nbits` bits with per-component ranges `sizes`.
+    fn read_ints(
+        &mut self,
+        mut nbits: u32,
+        sizes: [u32; 3],
+    ) -> Result<[i32; 3], String> {
+        let mut bytes = [0u32; 32];
+        let mut num_of_bytes = 0;
+        while nbits > 8 {
+            if num_of_bytes >= bytes.len() {
+                return Err("packed integer too wide".to_owned());
+            }
+            bytes[num_of_bytes] = self.read(8)?;
+            num_of_bytes += 1;
+            nbits -= 8;
+        }
+        if nbits > 0 {
+            if num_of_bytes >= bytes.len() {
+                return Err("packed integer too wide".to_owned());
+            }
+            bytes[num_of_bytes] = self.read(nbits)?;
+            num_of_bytes += 1;
+        }
+        let mut nums = [0i32; 3];
+        for i in (1..3).rev() {
+            if sizes[i] == 0 {
+                return Err("zero coordinate range".to_owned());
+            }
+            let size = u64::from(sizes[i]);
+            let mut num: u64 = 0;
+            for j in (0..num_of_bytes).rev() {
+                num = (num << 8) | u64::from(bytes[j]);
+                let p = num / size;
+                bytes[j] = p as u32;
+                num -= p * size;
+            }
+            nums[i] = num as i32;
+        }
+        nums[0] =
+            (bytes[0] | (bytes[1] << 8) | (bytes[2] << 16) | (bytes[3] << 24))
+                as i32;
+        Ok(nums)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    /// MSB-first bit writer mirroring [`BitReader`].
+    #[derive(Default)]
+    struct BitWriter {
+        bytes: Vec<u8>,
+        acc: u64,
+        nacc: u32,
+    }
+
+    impl BitWriter {
+        fn write(&mut self, nbits: u32, value: u64) {
+            for b in (0..nbits).rev() {
+                self.acc = (self.acc << 1) | ((value >> b) & 1);
+                self.nacc += 1;
+                if self.nacc == 8 {
+                    self.bytes.push(self.acc as u8);
+                    self.acc = 0;
+                    self.nacc = 0;
+                }
+            }
+        }
+
+        fn write_ints(&mut self, nbits: u32, sizes: [u32; 3], nums: [u32; 3]) {
+            let value = (u128::from(nums[0]) * u128::from(sizes[1])
+                + u128::from(nums[1]))
+                * u128::from(sizes[2])
+                + u128::from(nums[2]);
+            let mut remaining = nbits;
+            let mut k = 0;
+            while remaining > 0 {
+                let n = remaining.min(8);
+                self.write(n, ((value >> (8 * k)) & 0xff) as u64);
+                remaining -= n;
+                k += 1;
+            }
+        }
+
+        fn finish(mut self) -> Vec<u8> {
+            if self.nacc > 0 {
+                let pad = 8 - self.nacc;
+                self.write(pad, 0);
+            }
+            self.bytes
+        }
+    }
+
+    fn be_i32(out: &mut Vec<u8>, v: i32) {
+        out.extend_from_slice(&v.to_be_bytes());
+    }
+
+    fn be_f32(out: &mut Vec<u8>, v: f32) {
+        out.extend_from_slice(&v.to_be_bytes());
+    }
+
+    /// Encode one compressed frame. `atoms` are integer coordinates
+    /// (already multiplied by `precision`). The pair starting at
+    /// `run_start` is encoded as a two-atom run with the water swap;
+    /// everything else is a plain full-width atom.
+    fn encode_frame(
+        atoms: &[[i32; 3]],
+        precision: f32,
+        run_start: Option<usize>,
+    ) -> Vec<u8> {
+        let natoms = atoms.len() as i32;
+        let mut minint = [i32::MAX; 3];
+        let mut maxint = [i32::MIN; 3];
+        for a in atoms {
+            for k in 0..3 {
+                minint[k] = minint[k].min(a[k]);
+                maxint[k] = maxint[k].max(a[k]);
+            }
+        }
+        let sizeint = [0, 1, 2].map(|k| (maxint[k] - minint[k] + 1) as u32);
+        let bitsize = size_of_ints(&sizeint);
+        let smallidx = 12usize;
+        let smallnum = MAGIC_INTS[smallidx] as i32 / 2;
+        let sizesmall = [MAGIC_INTS[smallidx]; 3];
+
+        let mut w = BitWriter::default();
+        let mut run_active = false;
+        let mut i = 0;
+        while i < atoms.len() {
+            let full =
+                |a: [i32; 3]| [0, 1, 2].map(|k| (a[k] - minint[k]) as u32);
+            if Some(i) == run_start {
+                // Encoder order: the *second* atom is sent at full
+                // width, then the first as a small delta from it.
+                let (first, second) = (atoms[i], atoms[i + 1]);
+                w.write_ints(bitsize, sizeint, full(second));
+                // flag=1, run=3 with is_smaller=0 → run%3==1 → 4.
+                w.write(1, 1);
+                w.write(5, 4);
+                let delta =
+                    [0, 1, 2].map(|k| (first[k] - second[k] + smallnum) as u32);
+                w.write_ints(smallidx as u32, sizesmall, delta);
+                run_active = true;
+                i += 2;
+            } else {
+                w.write_ints(bitsize, sizeint, full(atoms[i]));
+                // The run length is sticky: after a run, reset it to 0
+                // explicitly (value 1 → run 0, is_smaller 0).
+                if run_active {
+                    w.write(1, 1);
+                    w.write(5, 1);
+                    run_active = false;
+                } else {
+                    w.write(1, 0);
+                }
+                i += 1;
+            }
+        }
+        let payload = w.finish();
+
+        let mut out = Vec::new();
+        be_i32(&mut out, XTC_MAGIC);
+        be_i32(&mut out, natoms);
+        be_i32(&mut out, 0);
+        be_f32(&mut out, 0.0);
+        for _ in 0..9 {
+            be_f32(&mut out, 0.0);
+        }
+        be_i32(&mut out, natoms);
+        be_f32(&mut out, precision);
+        for v in minint.iter().chain(maxint.iter()) {
+            be_i32(&mut out, *v);
+        }
+        be_i32(&mut out, smallidx as i32);
+        be_i32(&mut out, payload.len() as i32);
+        out.extend_from_slice(&payload);
+        out.resize(out.len() + (4 - payload.len() % 4) % 4, 0);
+        out
+    }
+
+    fn sample_atoms() -> Vec<[i32; 3]> {
+        (0..12)
+            .map(|i| [i * 1000 - 3000, (i * 37) % 500, 20_000 - i * 150])
+            .collect()
+    }
+
+    #[test]
+    fn decodes_full_width_atoms() {
+        let atoms = sample_atoms();
+        let bytes = encode_frame(&atoms, 1000.0, None);
+        let data = parse(&bytes).unwrap();
+        assert_eq!(data.num_atoms, 12);
+        assert_eq!(data.frames.len(), 1);
+        for (got, want) in data.frames[0].iter().zip(&atoms) {
+            let want =
+                Vec3::new(want[0] as f32, want[1] as f32, want[2] as f32)
+                    / 1000.0
+                    * NM_TO_ANGSTROM;
+            assert!((*got - want).length() < 1e-3, "{got} vs {want}");
+        }
+    }
+
+    #[test]
+    fn decodes_run_with_water_swap() {
+        let mut atoms = sample_atoms();
+        // Atoms 4 and 5 sit within the small-delta range of each other.
+        atoms[5] = [atoms[4][0] + 3, atoms[4][1] - 2, atoms[4][2] + 1];
+        let bytes = encode_frame(&atoms, 1000.0, Some(4));
+        let data = parse(&bytes).unwrap();
+        for (got, want) in data.frames[0].iter().zip(&atoms) {
+            let want =
+                Vec3::new(want[0] as f32, want[1] as f32, want[2] as f32)
+                    / 1000.0
+                    * NM_TO_ANGSTROM;
+            assert!((*got - want).length() < 1e-3, "{got} vs {want}");
+        }
+    }
+
+    #[test]
+    fn run_deltas_near_the_integer_limit_do_not_overflow() {
+        let mut atoms = sample_atoms();
+        for (i, a) in atoms.iter_mut().enumerate() {
+            a[2] = i32::MAX - 3 - (i as i32 - 4).abs() * 150;
+        }
+        // The delta sum overshoots `i32::MAX` before `smallnum` is
+        // taken back off.
+        atoms[5] = [atoms[4][0] + 3, atoms[4][1] - 2, atoms[4][2] + 1];
+        let bytes = encode_frame(&atoms, 1000.0, Some(4));
+        let data = parse(&bytes).unwrap();
+        let frame = &data.frames[0];
+        let step = (frame[5] - frame[4]) * 1000.0 / NM_TO_ANGSTROM;
+        assert!((step.x - 3.0).abs() < 1e-2 && (step.y + 2.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn reads_uncompressed_small_frames() {
+        let mut out = Vec::new();
+        be_i32(&mut out, XTC_MAGIC);
+        be_i32(&mut out, 2);
+        be_i32(&mut out, 0);
+        be_f32(&mut out, 0.0);
+        for _ in 0..9 {
+            be_f32(&mut out, 0.0);
+        }
+        be_i32(&mut out, 2);
+        for v in [0.1, 0.2, 0.3, 1.0, 2.0, 3.0] {
+            be_f32(&mut out, v);
+        }
+        let data = parse(&out).unwrap();
+        assert!(
+            (data.frames[0][1] - Vec3::new(10.0, 20.0, 30.0)).length() < 1e-4
+        );
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut out = Vec::new();
+        be_i32(&mut out, 42);
+        assert!(parse(&out).is_err());
+    }
+}
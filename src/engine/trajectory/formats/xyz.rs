@@ -0,0 +1,89 @@
+//! Multi-frame XYZ reader.
+//!
+//! Each frame is an atom-count line, a free-form comment line, and one
+//! `element x y z` line per atom (Ångström). Frames are concatenated
+//! back to back; blank lines between frames are tolerated.
+
+use glam::Vec3;
+
+use super::TrajectoryData;
+
+/// Parse every frame of an XYZ file.
+pub(super) fn parse(text: &str) -> Result<TrajectoryData, String> {
+    let mut lines = text.lines().enumerate();
+    let mut frames: Vec<Vec<Vec3>> = Vec::new();
+    let mut num_atoms = None;
+
+    while let Some((line_no, line)) = lines.next() {
+        let count_str = line.trim();
+        if count_str.is_empty() {
+            continue;
+        }
+        let count: usize = count_str.parse().map_err(|_| {
+            format!(
+                "line {}: expected atom count, got {count_str:?}",
+                line_no + 1
+            )
+        })?;
+        if let Some(n) = num_atoms {
+            if n != count {
+                return Err(format!(
+                    "frame {} has {count} atoms, expected {n}",
+                    frames.len()
+                ));
+            }
+        }
+        num_atoms = Some(count);
+
+        let _comment = lines.next();
+        let mut frame = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (line_no, line) = lines
+                .next()
+                .ok_or_else(|| format!("frame {} truncated", frames.len()))?;
+            frame.push(parse_atom_line(line).ok_or_else(|| {
+                format!("line {}: malformed atom record", line_no + 1)
+            })?);
+        }
+        frames.push(frame);
+    }
+
+    Ok(TrajectoryData {
+        num_atoms: num_atoms.unwrap_or(0),
+        frames,
+    })
+}
+
+/// Coordinates from an `element x y z [...]` line.
+fn parse_atom_line(line: &str) -> Option<Vec3> {
+    let mut fields = line.split_whitespace().skip(1);
+    let mut next = || fields.next()?.parse::<f32>().ok();
+    Some(Vec3::new(next()?, next()?, next()?))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_consecutive_frames() {
+        let text = "2\nframe 0\nO 0.0 0.0 0.0\nH 0.96 0.0 0.0\n2\nframe 1\nO \
+                    0.1 0.0 0.0\nH 1.06 0.0 0.0 extra\n";
+        let data = parse(text).unwrap();
+        assert_eq!(data.num_atoms, 2);
+        assert_eq!(data.frames.len(), 2);
+        assert!((data.frames[1][1].x - 1.06).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rejects_changing_atom_count() {
+        let text = "1\n\nC 0 0 0\n2\n\nC 0 0 0\nC 1 0 0\n";
+        assert!(parse(text).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_frame() {
+        assert!(parse("3\ncomment\nC 0 0 0\n").is_err());
+    }
+}
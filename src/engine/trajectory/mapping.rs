@@ -0,0 +1,293 @@
+//! Trajectory atom order → entity atom mapping.
+//!
+//! Trajectory formats without topology (DCD, XTC, TRR, XYZ) identify
+//! atoms only by position in the frame. By default that order is
+//! matched against every entity's atoms concatenated in assembly
+//! order, which is what a simulation started from the loaded structure
+//! produces. Hosts whose topology differs (reordered chains, stripped
+//! solvent, extra ions) pass an explicit
+//! [`TrajectoryAtomMapping::Explicit`] list instead.
+//!
+//! Either way, building the mapping yields a
+//! [`TrajectoryMappingReport`] listing what could not be matched, so a
+//! mismatched topology is visible rather than silently scrambling
+//! coordinates.
+
+use molex::entity::molecule::id::EntityId;
+
+/// One explicit trajectory-atom → entity-atom correspondence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtomMapEntry {
+    /// Index of the atom in each trajectory frame.
+    pub trajectory_atom: usize,
+    /// Raw id of the entity the atom belongs to.
+    pub entity_id: u32,
+    /// Entity-local atom index.
+    pub atom_index: u32,
+}
+
+/// How trajectory atoms are matched to entity atoms.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TrajectoryAtomMapping {
+    /// Trajectory atom `i` is the `i`-th atom of all entities
+    /// concatenated in assembly order.
+    #[default]
+    AssemblyOrder,
+    /// Only the listed atoms are driven by the trajectory.
+    Explicit(Vec<AtomMapEntry>),
+}
+
+/// Per-entity mapping coverage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityMappingReport {
+    /// Raw entity id.
+    pub entity_id: u32,
+    /// Atoms in the entity.
+    pub atom_count: usize,
+    /// Entity atoms driven by the trajectory.
+    pub mapped_atoms: usize,
+}
+
+impl EntityMappingReport {
+    /// Entity atoms the trajectory does not move.
+    #[must_use]
+    pub const fn unmatched_atoms(&self) -> usize {
+        self.atom_count - self.mapped_atoms
+    }
+}
+
+/// Validation report produced when a trajectory is bound to the scene.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TrajectoryMappingReport {
+    /// Atoms per trajectory frame.
+    pub trajectory_atoms: usize,
+    /// Trajectory atoms mapped onto some entity atom.
+    pub mapped_atoms: usize,
+    /// Trajectory atoms with no entity counterpart.
+    pub unmatched_trajectory_atoms: usize,
+    /// Coverage of every entity in the assembly.
+    pub entities: Vec<EntityMappingReport>,
+    /// Explicit entries dropped because the trajectory atom, entity or
+    /// entity atom does not exist, or the entity atom was already
+    /// mapped.
+    pub invalid_entries: usize,
+}
+
+impl TrajectoryMappingReport {
+    /// Entity atoms (across all entities) the trajectory does not move.
+    #[must_use]
+    pub fn unmatched_entity_atoms(&self) -> usize {
+        self.entities
+            .iter()
+            .map(EntityMappingReport::unmatched_atoms)
+            .sum()
+    }
+
+    /// Whether every trajectory atom and every entity atom is mapped
+    /// and no explicit entry was rejected.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.unmatched_trajectory_atoms == 0
+            && self.unmatched_entity_atoms() == 0
+            && self.invalid_entries == 0
+    }
+}
+
+/// Atoms of one entity driven by the trajectory.
+pub(crate) struct EntityAtomMap {
+    /// The entity being driven.
+    pub(crate) entity: EntityId,
+    /// Entity-local atom indices (parallel to `trajectory_atoms`).
+    pub(crate) atom_indices: Vec<u32>,
+    /// Trajectory atom index feeding each entry of `atom_indices`.
+    pub(crate) trajectory_atoms: Vec<usize>,
+}
+
+/// Resolved mapping for every entity the trajectory moves. Entities
+/// with no mapped atoms are omitted.
+pub(crate) struct TrajectoryMap {
+    pub(crate) entities: Vec<EntityAtomMap>,
+}
+
+impl TrajectoryMap {
+    /// Resolve `mapping` against `entities` (`(id, atom_count)` in
+    /// assembly order) for a trajectory with `trajectory_atoms` atoms.
+    pub(crate) fn build(
+        entities: &[(EntityId, usize)],
+        trajectory_atoms: usize,
+        mapping: &TrajectoryAtomMapping,
+    ) -> (Self, TrajectoryMappingReport) {
+        let (maps, invalid_entries) = match mapping {
+            TrajectoryAtomMapping::AssemblyOrder => {
+                (assembly_order(entities, trajectory_atoms), 0)
+            }
+            TrajectoryAtomMapping::Explicit(entries) => {
+                explicit(entities, trajectory_atoms, entries)
+            }
+        };
+
+        let mapped_atoms: usize =
+            maps.iter().map(|m| m.atom_indices.len()).sum();
+        let report = TrajectoryMappingReport {
+            trajectory_atoms,
+            mapped_atoms,
+            unmatched_trajectory_atoms: trajectory_atoms
+                .saturating_sub(mapped_atoms),
+            entities: entities
+                .iter()
+                .map(|&(id, atom_count)| EntityMappingReport {
+                    entity_id: id.raw(),
+                    atom_count,
+                    mapped_atoms: maps
+                        .iter()
+                        .find(|m| m.entity == id)
+                        .map_or(0, |m| m.atom_indices.len()),
+                })
+                .collect(),
+            invalid_entries,
+        };
+        let entities = maps
+            .into_iter()
+            .filter(|m| !m.atom_indices.is_empty())
+            .collect();
+        (Self { entities }, report)
+    }
+}
+
+/// Sequential mapping: walk entities in order, consuming trajectory
+/// atoms until either side runs out.
+fn assembly_order(
+    entities: &[(EntityId, usize)],
+    trajectory_atoms: usize,
+) -> Vec<EntityAtomMap> {
+    let mut offset = 0;
+    entities
+        .iter()
+        .map(|&(entity, atom_count)| {
+            let take = atom_count.min(trajectory_atoms.saturating_sub(offset));
+            let map = EntityAtomMap {
+                entity,
+                atom_indices: (0..take as u32).collect(),
+                trajectory_atoms: (offset..offset + take).collect(),
+            };
+            offset += take;
+            map
+        })
+        .collect()
+}
+
+/// Explicit mapping: keep every entry that points at an existing
+/// trajectory atom and entity atom; count the rest as invalid. Each
+/// trajectory atom and each entity atom may be mapped at most once.
+fn explicit(
+    entities: &[(EntityId, usize)],
+    trajectory_atoms: usize,
+    entries: &[AtomMapEntry],
+) -> (Vec<EntityAtomMap>, usize) {
+    let mut maps: Vec<EntityAtomMap> = entities
+        .iter()
+        .map(|&(entity, _)| EntityAtomMap {
+            entity,
+            atom_indices: Vec::new(),
+            trajectory_atoms: Vec::new(),
+        })
+        .collect();
+    let mut entity_seen: Vec<Vec<bool>> =
+        entities.iter().map(|&(_, n)| vec![false; n]).collect();
+    let mut trajectory_seen = vec![false; trajectory_atoms];
+    let mut invalid = 0;
+
+    for entry in entries {
+        let slot = entities
+            .iter()
+            .position(|(id, _)| id.raw() == entry.entity_id)
+            .filter(|&e| (entry.atom_index as usize) < entities[e].1);
+        let fresh = slot.is_some_and(|e| {
+            !entity_seen[e][entry.atom_index as usize]
+                && trajectory_seen
+                    .get(entry.trajectory_atom)
+                    .is_some_and(|seen| !seen)
+        });
+        let (Some(e), true) = (slot, fresh) else {
+            invalid += 1;
+            continue;
+        };
+        entity_seen[e][entry.atom_index as usize] = true;
+        trajectory_seen[entry.trajectory_atom] = true;
+        maps[e].atom_indices.push(entry.atom_index);
+        maps[e].trajectory_atoms.push(entry.trajectory_atom);
+    }
+    (maps, invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use molex::entity::molecule::id::EntityIdAllocator;
+
+    use super::*;
+
+    fn entities(counts: &[usize]) -> Vec<(EntityId, usize)> {
+        let mut alloc = EntityIdAllocator::new();
+        counts.iter().map(|&n| (alloc.allocate(), n)).collect()
+    }
+
+    #[test]
+    fn assembly_order_spans_entities() {
+        let ents = entities(&[3, 2]);
+        let (map, report) =
+            TrajectoryMap::build(&ents, 5, &TrajectoryAtomMapping::default());
+        assert_eq!(map.entities.len(), 2);
+        assert_eq!(map.entities[1].trajectory_atoms, vec![3, 4]);
+        assert_eq!(map.entities[1].atom_indices, vec![0, 1]);
+        assert!(report.is_complete());
+    }
+
+    #[test]
+    fn short_trajectory_reports_unmatched_entity_atoms() {
+        let ents = entities(&[3, 2]);
+        let (map, report) =
+            TrajectoryMap::build(&ents, 4, &TrajectoryAtomMapping::default());
+        assert_eq!(map.entities[1].atom_indices, vec![0]);
+        assert_eq!(report.entities[1].unmatched_atoms(), 1);
+        assert_eq!(report.unmatched_trajectory_atoms, 0);
+        assert!(!report.is_complete());
+    }
+
+    #[test]
+    fn long_trajectory_reports_unmatched_trajectory_atoms() {
+        let ents = entities(&[2]);
+        let (_, report) =
+            TrajectoryMap::build(&ents, 10, &TrajectoryAtomMapping::default());
+        assert_eq!(report.mapped_atoms, 2);
+        assert_eq!(report.unmatched_trajectory_atoms, 8);
+    }
+
+    #[test]
+    fn explicit_mapping_rejects_invalid_and_duplicate_entries() {
+        let ents = entities(&[2, 2]);
+        let second = ents[1].0.raw();
+        let entry = |t, a| AtomMapEntry {
+            trajectory_atom: t,
+            entity_id: second,
+            atom_index: a,
+        };
+        let mapping = TrajectoryAtomMapping::Explicit(vec![
+            entry(0, 1),
+            entry(1, 1), // entity atom already mapped
+            entry(9, 0), // trajectory atom out of range
+            entry(2, 5), // entity atom out of range
+            AtomMapEntry {
+                trajectory_atom: 3,
+                entity_id: 999,
+                atom_index: 0,
+            },
+        ]);
+        let (map, report) = TrajectoryMap::build(&ents, 4, &mapping);
+        assert_eq!(map.entities.len(), 1);
+        assert_eq!(map.entities[0].entity, ents[1].0);
+        assert_eq!(map.entities[0].trajectory_atoms, vec![0]);
+        assert_eq!(report.invalid_entries, 4);
+        assert_eq!(report.unmatched_trajectory_atoms, 3);
+        assert_eq!(report.unmatched_entity_atoms(), 3);
+    }
+}
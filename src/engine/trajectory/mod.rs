@@ -0,0 +1,374 @@
+//! Trajectory playback.
+//!
+//! Trajectories are decoded by the readers in `formats` (DCD, XTC,
+//! TRR, XYZ, multi-model PDB / mmCIF) into one shared per-frame
+//! position list. At load time that list is mapped onto the atoms of
+//! every entity in the assembly (see [`mapping`]), and
+//! [`TrajectoryPlayer::tick`] returns a [`TrajectoryFrame`] containing
+//! the per-atom position updates the engine applies to
+//! [`crate::engine::positions::EntityPositions`] for each entity.
+//!
+//! The host drives playback through the `*Trajectory` variants of
+//! [`VisoCommand`](super::command::VisoCommand) and reads it back via
+//! [`VisoEngine::trajectory_status`]. [`VisoEngine::analyze_trajectory`]
+//! runs the structural analyses in `analysis` (RMSD, RMSF, radius of
+//! gyration, displacement) over the loaded frames.
+
+mod analysis;
+mod filter;
+mod formats;
+mod mapping;
+mod player;
+
+use analysis::Probe;
+pub use analysis::{ResidueMetric, TrajectoryAnalysis};
+pub use filter::TrajectorySmoothing;
+pub(crate) use formats::read_trajectory;
+use glam::Vec3;
+use mapping::TrajectoryMap;
+pub use mapping::{
+    AtomMapEntry, EntityMappingReport, TrajectoryAtomMapping,
+    TrajectoryMappingReport,
+};
+use molex::entity::molecule::id::EntityId;
+pub(crate) use player::TrajectoryPlayer;
+pub use player::{PlaybackMode, TrajectoryStatus};
+use rustc_hash::FxHashMap;
+
+use super::command::CommandOutcome;
+use super::events::VisoEvent;
+use super::scene::Scene;
+use super::VisoEngine;
+use crate::animation::transition::Transition;
+use crate::options::overrides::RenderInvalidation;
+
+/// One trajectory frame's position updates, for every entity the
+/// trajectory drives.
+pub(crate) struct TrajectoryFrame {
+    /// Per-entity updates (entities with no mapped atoms are absent).
+    pub(crate) entities: Vec<EntityFrame>,
+    /// Whether the displayed frame index changed (as opposed to a
+    /// filter re-sample).
+    pub(crate) advanced: bool,
+    /// How to move the structure onto this frame: `Some` when playback
+    /// interpolates between adjacent frames, `None` to snap.
+    pub(crate) transition: Option<Transition>,
+}
+
+/// Per-entity trajectory frame update: which entity-local atom
+/// indices to overwrite and the new [`Vec3`] at each.
+pub(crate) struct EntityFrame {
+    /// The entity this update belongs to.
+    pub(crate) entity: EntityId,
+    /// Replacement positions — parallel to `atom_indices`.
+    pub(crate) positions: Vec<Vec3>,
+    /// Entity-local atom indices to overwrite (parallel to
+    /// `positions`). An atom not in this list keeps its previous
+    /// position.
+    pub(crate) atom_indices: Vec<u32>,
+}
+
+// ---------------------------------------------------------------------------
+// Engine surface
+// ---------------------------------------------------------------------------
+
+impl VisoEngine {
+    /// Load a trajectory file (DCD, XTC, TRR, XYZ, or a multi-model
+    /// PDB / mmCIF ensemble) and begin playback, matching trajectory
+    /// atoms to all entities in assembly order. Failures are logged.
+    pub fn load_trajectory(&mut self, path: &std::path::Path) {
+        let _ = self.load_trajectory_with_mapping(
+            path,
+            &TrajectoryAtomMapping::AssemblyOrder,
+        );
+    }
+
+    /// Load a trajectory file with an explicit atom mapping and begin
+    /// playback. Returns the mapping validation report, or `None` if
+    /// the file could not be read or no atom could be mapped (the
+    /// reason is logged).
+    pub fn load_trajectory_with_mapping(
+        &mut self,
+        path: &std::path::Path,
+        mapping: &TrajectoryAtomMapping,
+    ) -> Option<TrajectoryMappingReport> {
+        let data = read_trajectory(path)
+            .map_err(|e| log::error!("Failed to load trajectory: {e}"))
+            .ok()?;
+        let entities: Vec<(EntityId, usize)> = self
+            .scene
+            .current
+            .entities()
+            .iter()
+            .map(|e| (e.id(), e.atom_count()))
+            .collect();
+        let (map, report) =
+            TrajectoryMap::build(&entities, data.num_atoms, mapping);
+        if report.mapped_atoms == 0 {
+            log::error!(
+                "No trajectory atoms could be mapped onto the loaded structure"
+            );
+            return None;
+        }
+        log_mapping_report(&report);
+        if self.clear_trajectory_rmsf() {
+            self.apply_entity_invalidation(
+                RenderInvalidation::RE_MESH | RenderInvalidation::RE_COLOR,
+            );
+        }
+        let num_frames = data.frames.len();
+        let player = TrajectoryPlayer::new(data.frames, map, report.clone());
+        self.animation
+            .load_trajectory(player, num_frames, data.num_atoms);
+        Some(report)
+    }
+
+    /// Mapping validation report of the loaded trajectory.
+    #[must_use]
+    pub fn trajectory_mapping_report(
+        &self,
+    ) -> Option<&TrajectoryMappingReport> {
+        self.animation
+            .trajectory_player
+            .as_ref()
+            .map(TrajectoryPlayer::mapping_report)
+    }
+
+    /// Analyze the loaded trajectory against `reference_frame`
+    /// (clamped): per-frame RMSD after superposition and radius of
+    /// gyration, and per-residue RMSF, over one atom per residue (CA
+    /// or P). The RMSF also feeds the
+    /// [`Rmsf`](crate::options::ColorScheme::Rmsf) color scheme and the
+    /// [`Putty`](crate::options::CartoonStyle::Putty) cartoon style.
+    /// Returns `None` if no trajectory is loaded or no residue is
+    /// driven by it.
+    pub fn analyze_trajectory(
+        &mut self,
+        reference_frame: usize,
+    ) -> Option<&TrajectoryAnalysis> {
+        let player = self.animation.trajectory_player.as_ref()?;
+        let probes = residue_probes(&self.scene, player.map());
+        let Some(result) =
+            analysis::analyze(player.frames(), probes, reference_frame)
+        else {
+            log::warn!("Trajectory analysis: no residues driven by trajectory");
+            return None;
+        };
+
+        let rmsf = cartoon_rmsf(&self.scene, &result);
+        let _ = self.clear_trajectory_rmsf();
+        {
+            let mut annotations = self.annotations_mut();
+            for (eid, values) in rmsf {
+                annotations.set_per_residue_rmsf(eid, Some(values));
+            }
+        }
+        self.apply_entity_invalidation(
+            RenderInvalidation::RE_MESH | RenderInvalidation::RE_COLOR,
+        );
+        let player = self.animation.trajectory_player.as_mut()?;
+        Some(player.set_analysis(result))
+    }
+
+    /// Most recent [`analyze_trajectory`](Self::analyze_trajectory)
+    /// result for the loaded trajectory.
+    #[must_use]
+    pub fn trajectory_analysis(&self) -> Option<&TrajectoryAnalysis> {
+        self.animation
+            .trajectory_player
+            .as_ref()
+            .and_then(TrajectoryPlayer::analysis)
+    }
+
+    /// Per-residue distance of `frame` (superposed) from the analysis
+    /// reference frame. `None` until
+    /// [`analyze_trajectory`](Self::analyze_trajectory) has run, or if
+    /// `frame` is out of range.
+    #[must_use]
+    pub fn trajectory_displacement(
+        &self,
+        frame: usize,
+    ) -> Option<Vec<ResidueMetric>> {
+        let player = self.animation.trajectory_player.as_ref()?;
+        player.analysis()?.displacement(player.frames(), frame)
+    }
+
+    /// Drop RMSF left over from an earlier trajectory analysis.
+    /// Returns whether any entity had some (the caller re-renders).
+    fn clear_trajectory_rmsf(&mut self) -> bool {
+        let stale: Vec<EntityId> =
+            self.annotations.rmsf.keys().copied().collect();
+        let mut annotations = self.annotations_mut();
+        for &eid in &stale {
+            annotations.set_per_residue_rmsf(eid, None);
+        }
+        !stale.is_empty()
+    }
+
+    /// Whether a trajectory is loaded.
+    #[must_use]
+    pub fn has_trajectory(&self) -> bool {
+        self.animation.trajectory_player.is_some()
+    }
+
+    /// Playback state of the loaded trajectory, or `None` if no
+    /// trajectory is loaded.
+    #[must_use]
+    pub fn trajectory_status(&self) -> Option<TrajectoryStatus> {
+        self.animation
+            .trajectory_player
+            .as_ref()
+            .map(TrajectoryPlayer::status)
+    }
+
+    /// Total number of frames in the loaded trajectory.
+    #[must_use]
+    pub fn trajectory_frame_count(&self) -> Option<usize> {
+        self.trajectory_status().map(|s| s.frame_count)
+    }
+
+    /// Index of the trajectory frame currently displayed.
+    #[must_use]
+    pub fn trajectory_current_frame(&self) -> Option<usize> {
+        self.trajectory_status().map(|s| s.current_frame)
+    }
+
+    /// Show a trajectory frame — through the structure animator when it
+    /// carries a transition, otherwise by applying it and queueing a
+    /// background remesh — and notify the host if the frame index
+    /// changed.
+    pub(crate) fn show_trajectory_frame(&mut self, frame: &TrajectoryFrame) {
+        if !self
+            .animation
+            .animate_trajectory_frame(frame, &self.scene.positions)
+        {
+            self.apply_trajectory_frame(frame);
+            self.submit_animation_frame();
+        }
+        if !frame.advanced {
+            return;
+        }
+        if let Some(status) = self.trajectory_status() {
+            self.events.push(VisoEvent::TrajectoryFrameChanged {
+                frame: status.current_frame,
+                total: status.frame_count,
+            });
+        }
+    }
+
+    /// Run `control` against the loaded player and display any frame
+    /// it returns. No-ops (returning
+    /// [`CommandOutcome::NoEffect`]) when no trajectory is loaded.
+    pub(crate) fn control_trajectory(
+        &mut self,
+        control: impl FnOnce(&mut TrajectoryPlayer) -> Option<TrajectoryFrame>,
+    ) -> CommandOutcome {
+        let Some(player) = self.animation.trajectory_player.as_mut() else {
+            return CommandOutcome::NoEffect;
+        };
+        if let Some(frame) = control(player) {
+            self.show_trajectory_frame(&frame);
+        }
+        CommandOutcome::PlaybackChanged
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Loader
+// ---------------------------------------------------------------------------
+
+/// One probe per residue whose representative atom (CA for protein,
+/// P for nucleic acid) is driven by the trajectory, in cartoon residue
+/// order.
+fn residue_probes(scene: &Scene, map: &TrajectoryMap) -> Vec<Probe> {
+    let mut probes = Vec::new();
+    for m in &map.entities {
+        let Some(state) = scene.entity_state.get(&m.entity) else {
+            continue;
+        };
+        let topology = &state.topology;
+        let lookup: FxHashMap<u32, usize> = m
+            .atom_indices
+            .iter()
+            .copied()
+            .zip(m.trajectory_atoms.iter().copied())
+            .collect();
+        let representatives = topology
+            .protein_backbone_layout
+            .iter()
+            .flat_map(|seg| seg.ca.iter())
+            .chain(topology.na_backbone_chain_layout.iter().flatten());
+        for &atom in representatives {
+            let (Some(&trajectory_atom), Some(&residue)) = (
+                lookup.get(&(atom as u32)),
+                topology.atom_residue_index.get(atom),
+            ) else {
+                continue;
+            };
+            probes.push(Probe {
+                entity: m.entity,
+                residue,
+                trajectory_atom,
+            });
+        }
+    }
+    probes
+}
+
+/// Per-entity RMSF laid out in cartoon residue order (one value per
+/// CA in `protein_backbone_layout`), for protein entities the analysis
+/// covered. Residues the trajectory does not drive read as zero.
+fn cartoon_rmsf(
+    scene: &Scene,
+    result: &TrajectoryAnalysis,
+) -> Vec<(EntityId, Vec<f32>)> {
+    let mut out = Vec::new();
+    for (&eid, state) in &scene.entity_state {
+        let topology = &state.topology;
+        if !topology.is_protein() {
+            continue;
+        }
+        let by_residue: FxHashMap<u32, f32> = result.entity_rmsf(eid).collect();
+        if by_residue.is_empty() {
+            continue;
+        }
+        let values = topology
+            .protein_backbone_layout
+            .iter()
+            .flat_map(|seg| seg.ca.iter())
+            .map(|&ca| {
+                topology
+                    .atom_residue_index
+                    .get(ca)
+                    .and_then(|r| by_residue.get(r))
+                    .copied()
+                    .unwrap_or(0.0)
+            })
+            .collect();
+        out.push((eid, values));
+    }
+    out
+}
+
+/// Warn about atoms the trajectory cannot drive.
+fn log_mapping_report(report: &TrajectoryMappingReport) {
+    if report.is_complete() {
+        return;
+    }
+    log::warn!(
+        "Trajectory mapping incomplete: {}/{} trajectory atoms mapped, {} \
+         entity atoms unmatched, {} invalid explicit entries",
+        report.mapped_atoms,
+        report.trajectory_atoms,
+        report.unmatched_entity_atoms(),
+        report.invalid_entries,
+    );
+    for entity in report.entities.iter().filter(|e| e.unmatched_atoms() > 0) {
+        log::warn!(
+            "  entity {}: {}/{} atoms driven by trajectory",
+            entity.entity_id,
+            entity.mapped_atoms,
+            entity.atom_count,
+        );
+    }
+}
@@ -0,0 +1,476 @@
+//! Frame sequencer for a loaded trajectory: play / pause, seek, step,
+//! frame rate, playback mode, and a frame-range window.
+
+use std::ops::Range;
+use std::time::Duration;
+
+use glam::Vec3;
+use web_time::Instant;
+
+use super::analysis::TrajectoryAnalysis;
+use super::filter::{
+    SmoothedFrames, TrajectorySmoothing, MAX_SMOOTHING_WINDOW,
+};
+use super::mapping::{TrajectoryMap, TrajectoryMappingReport};
+use super::{EntityFrame, TrajectoryFrame};
+use crate::animation::transition::Transition;
+
+/// Default playback rate in frames per second.
+const DEFAULT_FPS: f32 = 30.0;
+
+/// Playback rate bounds accepted by [`TrajectoryPlayer::set_fps`].
+const FPS_RANGE: (f32, f32) = (0.1, 240.0);
+
+/// What happens when playback reaches the end of the frame window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaybackMode {
+    /// Wrap back to the first frame of the window.
+    #[default]
+    Loop,
+    /// Stop on the last frame of the window.
+    Once,
+    /// Reverse direction at each end of the window.
+    PingPong,
+}
+
+/// Snapshot of trajectory playback state, for timeline UIs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrajectoryStatus {
+    /// Index of the frame currently displayed.
+    pub current_frame: usize,
+    /// Total number of frames in the trajectory.
+    pub frame_count: usize,
+    /// Whether playback is advancing.
+    pub playing: bool,
+    /// Playback rate in frames per second.
+    pub fps: f32,
+    /// End-of-window behavior.
+    pub mode: PlaybackMode,
+    /// First frame of the playback window (inclusive).
+    pub range_start: usize,
+    /// Last frame of the playback window (inclusive).
+    pub range_end: usize,
+    /// Temporal filter settings.
+    pub smoothing: TrajectorySmoothing,
+}
+
+/// Auto-advancing trajectory frame sequencer. Each frame drives every
+/// entity covered by its [`TrajectoryMap`].
+pub(crate) struct TrajectoryPlayer {
+    /// Per-frame atom positions in trajectory file order.
+    frames: Vec<Vec<Vec3>>,
+    current_frame: usize,
+    last_advance: Instant,
+    frame_duration: Duration,
+    playing: bool,
+    mode: PlaybackMode,
+    /// `true` while a [`PlaybackMode::PingPong`] pass runs backwards.
+    reversed: bool,
+    /// Playback window (half-open), always a non-empty sub-range of
+    /// `0..frames.len()` when frames exist.
+    window: Range<usize>,
+    /// Sub-frame interpolation and moving-average settings.
+    smoothing: TrajectorySmoothing,
+    /// Averaged positions of the frames being displayed.
+    smoothed: SmoothedFrames,
+    /// Trajectory atom → entity atom correspondence.
+    map: TrajectoryMap,
+    /// Validation report produced when `map` was built.
+    report: TrajectoryMappingReport,
+    /// Most recent structural analysis, if one has been run.
+    analysis: Option<TrajectoryAnalysis>,
+}
+
+impl TrajectoryPlayer {
+    /// New player over pre-decoded frames.
+    pub(crate) fn new(
+        frames: Vec<Vec<Vec3>>,
+        map: TrajectoryMap,
+        report: TrajectoryMappingReport,
+    ) -> Self {
+        let window = 0..frames.len();
+        Self {
+            frames,
+            current_frame: 0,
+            last_advance: Instant::now(),
+            frame_duration: Duration::from_secs_f32(1.0 / DEFAULT_FPS),
+            playing: true,
+            mode: PlaybackMode::Loop,
+            reversed: false,
+            window,
+            smoothing: TrajectorySmoothing::default(),
+            smoothed: SmoothedFrames::default(),
+            map,
+            report,
+            analysis: None,
+        }
+    }
+
+    /// Advance time and return the frame update to display, if any.
+    /// With interpolation enabled, a step to an adjacent frame carries
+    /// a [`Transition`] spanning the frame interval, so the animator
+    /// moves the structure there at display rate instead of snapping.
+    pub(crate) fn tick(&mut self, now: Instant) -> Option<TrajectoryFrame> {
+        if !self.playing || self.window.is_empty() {
+            return None;
+        }
+        if now.saturating_duration_since(self.last_advance)
+            < self.frame_duration
+        {
+            return None;
+        }
+        self.last_advance = now;
+        let previous = self.current_frame;
+        if !self.advance() {
+            self.playing = false;
+            return None;
+        }
+        let mut frame = self.frame_update(true);
+        if self.smoothing.interpolate
+            && previous.abs_diff(self.current_frame) == 1
+        {
+            frame.transition =
+                Some(Transition::trajectory_frame(self.frame_duration));
+        }
+        Some(frame)
+    }
+
+    /// The frame (and ping-pong direction) the next advance moves to,
+    /// or `None` when a [`PlaybackMode::Once`] pass has ended.
+    fn peek_next(&self) -> Option<(usize, bool)> {
+        let (start, last) = (self.window.start, self.window.end - 1);
+        let current = self.current_frame;
+        match self.mode {
+            PlaybackMode::Loop => {
+                Some((if current >= last { start } else { current + 1 }, false))
+            }
+            PlaybackMode::Once => {
+                (current < last).then_some((current + 1, false))
+            }
+            PlaybackMode::PingPong => {
+                if start == last {
+                    return Some((current, false));
+                }
+                let reversed = if self.reversed && current <= start {
+                    false
+                } else if !self.reversed && current >= last {
+                    true
+                } else {
+                    self.reversed
+                };
+                let next = if reversed { current - 1 } else { current + 1 };
+                Some((next, reversed))
+            }
+        }
+    }
+
+    /// Move `current_frame` one step according to the playback mode.
+    /// Returns `false` when a [`PlaybackMode::Once`] pass has ended.
+    fn advance(&mut self) -> bool {
+        let Some((next, reversed)) = self.peek_next() else {
+            return false;
+        };
+        self.current_frame = next;
+        self.reversed = reversed;
+        true
+    }
+
+    /// Position updates for every mapped entity at the current frame,
+    /// applied without a transition. `advanced` records whether the
+    /// displayed frame index changed.
+    fn frame_update(&mut self, advanced: bool) -> TrajectoryFrame {
+        let half = self.smoothing.half_window();
+        self.smoothed
+            .prepare(&self.frames, self.current_frame, half);
+        let positions = self.smoothed.get(self.current_frame, half);
+        let at =
+            |atom: usize| positions.get(atom).copied().unwrap_or(Vec3::ZERO);
+        let entities = self
+            .map
+            .entities
+            .iter()
+            .map(|m| EntityFrame {
+                entity: m.entity,
+                positions: m
+                    .trajectory_atoms
+                    .iter()
+                    .map(|&atom| at(atom))
+                    .collect(),
+                atom_indices: m.atom_indices.clone(),
+            })
+            .collect();
+        TrajectoryFrame {
+            entities,
+            advanced,
+            transition: None,
+        }
+    }
+
+    /// Toggle between playing and paused states. Resuming a finished
+    /// [`PlaybackMode::Once`] pass rewinds to the start of the window.
+    pub(crate) fn toggle_playback(&mut self) {
+        self.playing = !self.playing;
+        if self.playing {
+            if self.mode == PlaybackMode::Once
+                && self.current_frame + 1 >= self.window.end
+            {
+                self.current_frame = self.window.start;
+            }
+            self.last_advance = Instant::now();
+        }
+        let state = if self.playing { "playing" } else { "paused" };
+        log::info!(
+            "Trajectory {state} (frame {}/{})",
+            self.current_frame,
+            self.frames.len()
+        );
+    }
+
+    /// Jump to `frame` (clamped into the playback window) and return
+    /// its update. Playback state is unchanged.
+    pub(crate) fn seek(&mut self, frame: usize) -> Option<TrajectoryFrame> {
+        if self.window.is_empty() {
+            return None;
+        }
+        self.current_frame =
+            frame.clamp(self.window.start, self.window.end - 1);
+        self.last_advance = Instant::now();
+        Some(self.frame_update(true))
+    }
+
+    /// Pause and move `delta` frames (negative steps backwards),
+    /// clamped to the playback window.
+    pub(crate) fn step(&mut self, delta: i64) -> Option<TrajectoryFrame> {
+        self.playing = false;
+        let target = (self.current_frame as i64 + delta).max(0) as usize;
+        self.seek(target)
+    }
+
+    /// Set the playback rate, clamped to a sane range.
+    pub(crate) fn set_fps(&mut self, fps: f32) {
+        let fps = if fps.is_finite() { fps } else { DEFAULT_FPS };
+        self.frame_duration =
+            Duration::from_secs_f32(1.0 / fps.clamp(FPS_RANGE.0, FPS_RANGE.1));
+    }
+
+    /// Set the end-of-window behavior.
+    pub(crate) fn set_mode(&mut self, mode: PlaybackMode) {
+        self.mode = mode;
+        self.reversed = false;
+    }
+
+    /// Restrict playback to frames `start..=end` (`None` = last
+    /// frame). Both bounds are clamped to the trajectory; if the
+    /// current frame falls outside the new window, returns the update
+    /// for the window's first frame.
+    pub(crate) fn set_range(
+        &mut self,
+        start: usize,
+        end: Option<usize>,
+    ) -> Option<TrajectoryFrame> {
+        let total = self.frames.len();
+        if total == 0 {
+            return None;
+        }
+        let last = end.unwrap_or(total - 1).min(total - 1);
+        let first = start.min(last);
+        self.window = first..last + 1;
+        self.reversed = false;
+        if self.window.contains(&self.current_frame) {
+            None
+        } else {
+            self.seek(first)
+        }
+    }
+
+    /// Replace the temporal filter settings and return the current
+    /// frame re-sampled under them.
+    pub(crate) fn set_smoothing(
+        &mut self,
+        smoothing: TrajectorySmoothing,
+    ) -> Option<TrajectoryFrame> {
+        self.smoothing = TrajectorySmoothing {
+            window: smoothing.window.min(MAX_SMOOTHING_WINDOW),
+            ..smoothing
+        };
+        (!self.window.is_empty()).then(|| self.frame_update(false))
+    }
+
+    /// Mapping validation report.
+    pub(crate) fn mapping_report(&self) -> &TrajectoryMappingReport {
+        &self.report
+    }
+
+    /// Raw per-frame positions in trajectory file order.
+    pub(crate) fn frames(&self) -> &[Vec<Vec3>] {
+        &self.frames
+    }
+
+    /// Trajectory atom → entity atom correspondence.
+    pub(crate) fn map(&self) -> &TrajectoryMap {
+        &self.map
+    }
+
+    /// Most recent structural analysis.
+    pub(crate) fn analysis(&self) -> Option<&TrajectoryAnalysis> {
+        self.analysis.as_ref()
+    }
+
+    /// Store a structural analysis, replacing any earlier one.
+    pub(crate) fn set_analysis(
+        &mut self,
+        analysis: TrajectoryAnalysis,
+    ) -> &TrajectoryAnalysis {
+        self.analysis.insert(analysis)
+    }
+
+    /// Playback state snapshot.
+    pub(crate) fn status(&self) -> TrajectoryStatus {
+        TrajectoryStatus {
+            current_frame: self.current_frame,
+            frame_count: self.frames.len(),
+            playing: self.playing,
+            fps: 1.0 / self.frame_duration.as_secs_f32(),
+            mode: self.mode,
+            range_start: self.window.start,
+            range_end: self.window.end.saturating_sub(1),
+            smoothing: self.smoothing,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use molex::entity::molecule::id::EntityIdAllocator;
+
+    use super::super::mapping::TrajectoryAtomMapping;
+    use super::*;
+    use crate::animation::AnimationState;
+    use crate::engine::positions::EntityPositions;
+
+    fn player(frame_count: usize) -> TrajectoryPlayer {
+        let frames = (0..frame_count)
+            .map(|i| vec![Vec3::new(i as f32, 0.0, 0.0)])
+            .collect();
+        let entity = EntityIdAllocator::new().allocate();
+        let (map, report) = TrajectoryMap::build(
+            &[(entity, 1)],
+            1,
+            &TrajectoryAtomMapping::AssemblyOrder,
+        );
+        TrajectoryPlayer::new(frames, map, report)
+    }
+
+    fn advance_n(p: &mut TrajectoryPlayer, n: usize) -> Vec<usize> {
+        (0..n)
+            .map(|_| {
+                let _ = p.advance();
+                p.current_frame
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loop_wraps_to_window_start() {
+        let mut p = player(3);
+        assert_eq!(advance_n(&mut p, 4), vec![1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn once_stops_on_last_frame() {
+        let mut p = player(3);
+        p.set_mode(PlaybackMode::Once);
+        let _ = advance_n(&mut p, 2);
+        assert!(!p.advance());
+        assert_eq!(p.current_frame, 2);
+    }
+
+    #[test]
+    fn ping_pong_reverses_at_both_ends() {
+        let mut p = player(3);
+        p.set_mode(PlaybackMode::PingPong);
+        assert_eq!(advance_n(&mut p, 6), vec![1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn range_restricts_playback_and_seek() {
+        let mut p = player(10);
+        let update = p.set_range(4, Some(6));
+        assert!(update.is_some());
+        assert_eq!(p.current_frame, 4);
+        assert_eq!(advance_n(&mut p, 3), vec![5, 6, 4]);
+        let _ = p.seek(9);
+        assert_eq!(p.current_frame, 6);
+    }
+
+    #[test]
+    fn step_pauses_and_clamps() {
+        let mut p = player(5);
+        let frame = p.step(-3);
+        assert!(!p.playing);
+        assert_eq!(p.current_frame, 0);
+        assert_eq!(frame.map(|f| f.entities[0].positions[0].x), Some(0.0));
+        let _ = p.step(10);
+        assert_eq!(p.current_frame, 4);
+    }
+
+    fn interpolating(frame_count: usize) -> TrajectoryPlayer {
+        let mut p = player(frame_count);
+        let _ = p.set_smoothing(TrajectorySmoothing {
+            interpolate: true,
+            window: 0,
+        });
+        p.set_fps(1.0);
+        p
+    }
+
+    #[test]
+    fn interpolated_step_runs_through_the_animator() {
+        let mut p = interpolating(3);
+        let start = p.last_advance;
+        assert!(p.tick(start + Duration::from_millis(500)).is_none());
+        let frame = p.tick(start + Duration::from_secs(1)).unwrap();
+        assert_eq!(
+            frame.transition.as_ref().map(Transition::total_duration),
+            Some(Duration::from_secs(1))
+        );
+
+        let entity = frame.entities[0].entity;
+        let mut positions = EntityPositions::new();
+        positions.set(entity, vec![Vec3::ZERO]);
+        let mut animation = AnimationState::new();
+        assert!(animation.animate_trajectory_frame(&frame, &positions));
+        let x = |positions: &EntityPositions| {
+            positions.get(entity).map_or(f32::NAN, |p| p[0].x)
+        };
+        assert_eq!(x(&positions), 0.0);
+
+        let now = Instant::now();
+        assert!(
+            animation.tick(now + Duration::from_millis(500), &mut positions)
+        );
+        assert!((x(&positions) - 0.5).abs() < 0.05);
+        assert!(animation.tick(now + Duration::from_secs(2), &mut positions));
+        assert_eq!(x(&positions), 1.0);
+        assert!(animation.animator.take_finished().is_empty());
+    }
+
+    #[test]
+    fn loop_seam_snaps() {
+        let mut p = interpolating(3);
+        let _ = p.seek(2);
+        let frame = p.tick(p.last_advance + Duration::from_secs(1));
+        assert_eq!(p.current_frame, 0);
+        assert!(frame.is_some_and(|f| f.transition.is_none()));
+    }
+
+    #[test]
+    fn fps_is_clamped() {
+        let mut p = player(1);
+        p.set_fps(0.0);
+        assert!((p.status().fps - FPS_RANGE.0).abs() < 1e-3);
+        p.set_fps(60.0);
+        assert!((p.status().fps - 60.0).abs() < 1e-2);
+    }
+}
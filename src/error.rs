@@ -21,6 +21,10 @@ pub enum VisoError {
     Viewer(String),
     /// Shader compilation or composition failure.
     Shader(String),
+    /// Structural superposition could not be computed.
+    Superpose(String),
+    /// A symmetry view could not be built from the loaded records.
+    Symmetry(String),
 }
 
 impl fmt::Display for VisoError {
@@ -39,6 +43,8 @@ impl fmt::Display for VisoError {
             }
             Self::Viewer(msg) => write!(f, "viewer error: {msg}"),
             Self::Shader(msg) => write!(f, "shader error: {msg}"),
+            Self::Superpose(msg) => write!(f, "superposition error: {msg}"),
+            Self::Symmetry(msg) => write!(f, "symmetry error: {msg}"),
         }
     }
 }
@@ -147,6 +147,28 @@ impl DynamicBuffer {
         reallocated
     }
 
+    /// Overwrite `data.len()` bytes at byte `offset` of the current
+    /// contents, leaving the rest untouched. Never grows the buffer.
+    ///
+    /// Returns `false` (writing nothing) if the range runs past the
+    /// data length or is not 4-byte aligned.
+    pub(crate) fn write_range(
+        &self,
+        queue: &wgpu::Queue,
+        offset: usize,
+        data: &[u8],
+    ) -> bool {
+        let aligned = (offset | data.len())
+            .is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT as usize);
+        if !aligned || offset + data.len() > self.len {
+            return false;
+        }
+        if !data.is_empty() {
+            queue.write_buffer(&self.buffer, offset as u64, data);
+        }
+        true
+    }
+
     /// Replace the backing buffer with one of `new_capacity` bytes.
     fn reallocate(&mut self, device: &wgpu::Device, new_capacity: usize) {
         self.buffer = device.create_buffer(&wgpu::BufferDescriptor {
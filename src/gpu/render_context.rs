@@ -41,6 +41,13 @@ impl std::error::Error for RenderContextError {
     }
 }
 
+/// Features used when the adapter has them: indirect draws with a
+/// non-zero first instance (culled impostor groups) and multi-draw
+/// indirect (culled chain lists in one call).
+const OPTIONAL_FEATURES: wgpu::Features =
+    wgpu::Features::INDIRECT_FIRST_INSTANCE
+        .union(wgpu::Features::MULTI_DRAW_INDIRECT);
+
 /// Owns the core wgpu resources: device, queue, surface, and configuration.
 pub struct RenderContext {
     /// The wgpu logical device.
@@ -91,7 +98,7 @@ impl RenderContext {
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: Some("Primary Device"),
-                required_features: wgpu::Features::empty(),
+                required_features: adapter.features() & OPTIONAL_FEATURES,
                 required_limits: wgpu::Limits::default(),
                 ..Default::default()
             })
@@ -20,13 +20,18 @@ pub(crate) enum Shader {
     BackboneTube,
     Capsule,
     Sphere,
+    Coarse,
     Cone,
     Polygon,
     PickingMesh,
     PickingCapsule,
     PickingSphere,
+    PickingSurface,
     Isosurface,
     BackfaceDepth,
+    HiZBuild,
+    OcclusionCull,
+    DensityVolume,
 }
 
 /// Expands each `Variant => "path"` into a match arm returning
@@ -64,13 +69,18 @@ shader_registry! {
     BackboneTube   => "raster/mesh/backbone_tube.wgsl",
     Capsule        => "raster/impostor/capsule.wgsl",
     Sphere         => "raster/impostor/sphere.wgsl",
+    Coarse         => "raster/impostor/coarse.wgsl",
     Cone           => "raster/impostor/cone.wgsl",
     Polygon        => "raster/impostor/polygon.wgsl",
     PickingMesh    => "utility/picking_mesh.wgsl",
     PickingCapsule => "utility/picking_capsule.wgsl",
     PickingSphere  => "utility/picking_sphere.wgsl",
+    PickingSurface => "utility/picking_surface.wgsl",
     Isosurface     => "raster/mesh/isosurface.wgsl",
     BackfaceDepth  => "raster/mesh/backface_depth.wgsl",
+    HiZBuild       => "utility/hiz_build.wgsl",
+    OcclusionCull  => "utility/occlusion_cull.wgsl",
+    DensityVolume  => "raster/volume/density_volume.wgsl",
 }
 
 /// Shared shader modules registered with naga-oil for `#import` support.
@@ -99,6 +109,10 @@ const MODULE_PATHS: &[(&str, &str)] = &[
         "modules/camera.wgsl",
         include_str!("../shaders/modules/camera.wgsl"),
     ),
+    (
+        "modules/model_copies.wgsl",
+        include_str!("../shaders/modules/model_copies.wgsl"),
+    ),
     (
         "modules/lighting.wgsl",
         include_str!("../shaders/modules/lighting.wgsl"),
@@ -47,7 +47,7 @@ pub use animation::transition::Transition;
 #[cfg(feature = "viewer")]
 pub use app::viewer::{Viewer, ViewerBuilder};
 #[cfg(any(feature = "viewer", feature = "web"))]
-pub use app::VisoApp;
+pub use app::{ResidueMatching, SuperposeResult, SuperposeSelection, VisoApp};
 #[cfg(feature = "gui")]
 pub use bridge::UiAction;
 pub use engine::command::{
@@ -55,7 +55,20 @@ pub use engine::command::{
     VisoCommand,
 };
 pub use engine::constraint::PickedResidueAtom;
+pub use engine::events::VisoEvent;
 pub use engine::focus::Focus;
+pub use engine::residue_address::{ResidueAddress, ResidueRef};
+pub use engine::sasa::{
+    Exposure, InterfaceAnalysis, InterfaceResidue, ResidueSasa,
+};
+pub use engine::symmetry::{
+    AssemblyGen, BioAssembly, StructureSymmetry, SymmetryView, UnitCell,
+};
+pub use engine::trajectory::{
+    AtomMapEntry, EntityMappingReport, PlaybackMode, ResidueMetric,
+    TrajectoryAnalysis, TrajectoryAtomMapping, TrajectoryMappingReport,
+    TrajectorySmoothing, TrajectoryStatus,
+};
 pub use engine::VisoEngine;
 pub use error::VisoError;
 pub use gpu::render_context::RenderContext;
@@ -67,5 +80,7 @@ pub use molex;
 pub use options::{DisplayOverrides, DrawingMode, HelixStyle, SheetStyle};
 // Picking output
 pub use renderer::picking::PickTarget;
+// Secondary structure
+pub use util::dssp::DsspCode;
 #[cfg(all(feature = "web", target_arch = "wasm32"))]
 pub use wasm_bindgen_rayon::init_thread_pool;
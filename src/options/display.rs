@@ -232,6 +232,11 @@ pub enum ColorScheme {
     Score,
     /// Relative score (5th/95th percentile normalized).
     ScoreRelative,
+    /// Trajectory root-mean-square fluctuation, normalized to the
+    /// entity's most mobile residue.
+    Rmsf,
+    /// Relative solvent accessibility, buried to exposed.
+    Sasa,
     /// Single uniform color (uses first color from palette stops).
     Solid,
 }
@@ -310,6 +315,53 @@ pub enum SurfaceKindOption {
     Gaussian,
     /// Solvent-excluded / Connolly surface.
     Ses,
+    /// Solvent-accessible surface: atom spheres grown by the probe
+    /// radius, traced by the probe's center.
+    Sas,
+    /// Van der Waals surface: the union of atom spheres.
+    Vdw,
+    /// Dots on the exposed parts of the van der Waals spheres.
+    Dots,
+}
+
+impl SurfaceKindOption {
+    /// Default grid spacing in Å, or dot spacing for [`Self::Dots`].
+    /// Sphere-union surfaces need a finer grid than Gaussian blobs to
+    /// resolve atom-level detail (ChimeraX uses 0.5 Å).
+    #[must_use]
+    pub const fn default_resolution(self) -> f32 {
+        match self {
+            Self::Ses | Self::Sas | Self::Vdw => 0.5,
+            Self::None | Self::Gaussian | Self::Dots => 1.0,
+        }
+    }
+}
+
+/// What colors a molecular surface.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Default,
+    JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SurfaceColoring {
+    /// One flat color per entity, matching its backbone palette color.
+    #[default]
+    Entity,
+    /// Electrostatic potential on a blue-white-red scale. Sampled from
+    /// the density map marked as the potential source, else from a
+    /// Coulomb approximation over per-residue charges.
+    Electrostatic,
+    /// Each point takes the color scheme's color of the residue of its
+    /// nearest atom, following scheme changes live. Entities without
+    /// residues keep their entity color.
+    Residue,
 }
 
 /// Surface presentation mode.
@@ -434,6 +486,45 @@ impl DisplayOptions {
             .unwrap_or_else(default_surface_opacity)
     }
 
+    /// Surface coloring mode, resolved.
+    #[must_use]
+    pub fn surface_coloring(&self) -> SurfaceColoring {
+        self.overrides.surface_coloring.unwrap_or_default()
+    }
+
+    /// Electrostatic color range in kT/e, resolved. Potentials at
+    /// `±range` get the fully saturated blue and red.
+    #[must_use]
+    pub fn surface_potential_range(&self) -> f32 {
+        self.overrides
+            .surface_potential_range
+            .unwrap_or(DEFAULT_POTENTIAL_RANGE)
+    }
+
+    /// Solvent probe radius in Å for SES and SAS surfaces, resolved.
+    #[must_use]
+    pub fn surface_probe_radius(&self) -> f32 {
+        self.overrides
+            .surface_probe_radius
+            .unwrap_or(DEFAULT_PROBE_RADIUS)
+    }
+
+    /// Surface grid spacing in Å for `kind`, resolved against the
+    /// kind's default.
+    #[must_use]
+    pub fn surface_resolution(&self, kind: SurfaceKindOption) -> f32 {
+        self.overrides
+            .surface_resolution
+            .unwrap_or_else(|| kind.default_resolution())
+    }
+
+    /// Whether surfaces are cut away on the camera side of the focus
+    /// point, resolved.
+    #[must_use]
+    pub fn surface_cutaway(&self) -> bool {
+        self.overrides.surface_cutaway.unwrap_or(false)
+    }
+
     /// Whether to render internal cavity meshes, resolved.
     #[must_use]
     pub fn show_cavities(&self) -> bool {
@@ -508,6 +599,12 @@ impl DisplayOptions {
     }
 }
 
+/// Default electrostatic color range in kT/e.
+const DEFAULT_POTENTIAL_RANGE: f32 = 5.0;
+
+/// Radius of a water molecule in Å.
+const DEFAULT_PROBE_RADIUS: f32 = 1.4;
+
 /// Default surface opacity for serde deserialization.
 fn default_surface_opacity() -> f32 {
     0.35
@@ -21,18 +21,49 @@ pub enum CartoonStyle {
     Tube,
     /// Cylindrical helices, flat sheets with arrows, round coils.
     Cylindrical,
+    /// Round tubes whose radius follows a per-residue magnitude
+    /// (trajectory RMSF); uniform tubes when none is set.
+    Putty,
     /// Custom -- user controls all per-SS parameters directly.
     Custom,
 }
 
+/// Where cartoon secondary structure comes from.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Default,
+    JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SsSource {
+    /// The assignment carried by the assembly (file records or the
+    /// loader's own assignment).
+    #[default]
+    File,
+    /// DSSP computed by the viewer from the displayed coordinates;
+    /// follows trajectory frames and edits when recomputed.
+    Computed,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[schemars(title = "Geometry", inline)]
 #[serde(default)]
+#[allow(clippy::struct_excessive_bools)]
 /// Geometry detail options for molecular rendering primitives.
 pub struct GeometryOptions {
     /// Cartoon rendering style preset.
     #[schemars(title = "Cartoon Style", extend("x-group" = "Style"))]
     pub cartoon_style: CartoonStyle,
+    /// Secondary-structure source for the cartoon. Host-supplied SS
+    /// overrides take priority over either.
+    #[schemars(title = "SS Source", extend("x-group" = "Style"))]
+    pub ss_source: SsSource,
     /// Whether to draw arrow heads at the C-terminal end of beta sheets.
     #[schemars(title = "Sheet Arrows", extend("x-group" = "Sheet"))]
     pub sheet_arrows: bool,
@@ -83,6 +114,28 @@ pub struct GeometryOptions {
     /// cost).
     #[schemars(title = "Cross-Section Detail", range(min = 4, max = 16), extend("step" = 2), extend("x-group" = "Quality"))]
     pub cross_section_verts: usize,
+    /// Draw entities that are rigid copies of an earlier entity (capsid
+    /// and filament subunits) as instances of one shared backbone mesh.
+    #[schemars(title = "Instance Copies", extend("x-group" = "Quality"))]
+    pub instance_copies: bool,
+    /// Replace the cartoon of far-away chains with one sphere per
+    /// residue, and of the farthest chains with one blob per chain.
+    /// Off by default, since it changes how large structures look.
+    #[schemars(title = "Coarse LOD", extend("x-group" = "Quality"))]
+    pub coarse_lod: bool,
+    /// Camera distance in angstroms at which a chain starts fading from
+    /// cartoon to residue spheres.
+    #[schemars(title = "Residue Sphere Distance", range(min = 200.0, max = 5000.0), extend("step" = 50.0), extend("x-group" = "Quality"))]
+    pub coarse_residue_distance: f32,
+    /// Camera distance in angstroms at which a chain starts fading from
+    /// residue spheres to a single blob.
+    #[schemars(title = "Chain Blob Distance", range(min = 400.0, max = 20000.0), extend("step" = 100.0), extend("x-group" = "Quality"))]
+    pub coarse_blob_distance: f32,
+    /// Skip chains hidden behind nearer geometry, tested on the GPU
+    /// against the previous frame's depth. Newly uncovered chains show
+    /// up one frame late.
+    #[schemars(title = "Occlusion Culling", extend("x-group" = "Quality"))]
+    pub occlusion_culling: bool,
 
     /// Solvent sphere radius in angstroms.
     #[schemars(skip)]
@@ -184,6 +237,19 @@ pub struct ChainLod {
     pub cross_section_verts: usize,
 }
 
+impl ChainLod {
+    /// A chain that is not tessellated at all: it is far enough away to
+    /// be drawn only by its coarse representation.
+    #[must_use]
+    pub const fn is_coarse(self) -> bool {
+        self.segments_per_residue == 0
+    }
+}
+
+/// LOD tier of a chain drawn only by its coarse representation (residue
+/// spheres or chain blob); its cartoon is not tessellated.
+pub const COARSE_LOD_TIER: u8 = 4;
+
 /// Scale user detail settings down for an LOD tier.
 ///
 /// Only `spr` (segments per residue) is reduced -- `csv` (cross-section
@@ -196,12 +262,14 @@ pub struct ChainLod {
 /// - Tier 1: spr 50%
 /// - Tier 2: spr 25%
 /// - Tier 3: spr 12.5%
+/// - [`COARSE_LOD_TIER`]: spr 0 (not tessellated)
 #[must_use]
 pub fn lod_scaled(max_spr: usize, max_csv: usize, tier: u8) -> ChainLod {
     let segments_per_residue = match tier {
         0 => max_spr,
         1 => (max_spr / 2).max(4),
         2 => (max_spr / 4).max(4),
+        COARSE_LOD_TIER.. => 0,
         _ => (max_spr / 8).max(4),
     };
     ChainLod {
@@ -251,10 +319,102 @@ pub fn select_chain_lod_tier(chain_center: Vec3, camera_eye: Vec3) -> u8 {
     select_lod_tier(distance, 0.0)
 }
 
+/// Fraction of a coarse tier's start distance over which it fades in.
+const COARSE_FADE_FRACTION: f32 = 0.2;
+
+/// Camera-distance window of one coarse representation: invisible
+/// before `start`, dithered in up to `opaque`, and replaced by the next
+/// tier from `end` on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoarseWindow {
+    /// Distance at which the representation starts to appear.
+    pub start: f32,
+    /// Distance from which it is fully opaque.
+    pub opaque: f32,
+    /// Distance from which it is no longer drawn.
+    pub end: f32,
+}
+
+impl CoarseWindow {
+    /// Opacity at camera distance `distance`.
+    #[must_use]
+    pub fn opacity(&self, distance: f32) -> f32 {
+        if distance < self.start || distance >= self.end {
+            return 0.0;
+        }
+        ((distance - self.start) / (self.opaque - self.start)).clamp(0.0, 1.0)
+    }
+}
+
+/// Distance windows of the two coarse tiers. Each tier fades in over
+/// the previous one, which stops drawing once it is fully opaque, so a
+/// chain never pops between representations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoarseTiers {
+    /// One sphere per residue.
+    pub residues: CoarseWindow,
+    /// One blob per chain.
+    pub blobs: CoarseWindow,
+}
+
+impl CoarseTiers {
+    /// Distance from which a chain's cartoon is no longer drawn or
+    /// tessellated.
+    #[must_use]
+    pub const fn cartoon_cutoff(&self) -> f32 {
+        self.residues.opaque
+    }
+}
+
+impl GeometryOptions {
+    /// Coarse tier windows, or `None` when coarse LOD is disabled.
+    ///
+    /// The blob tier starts no earlier than the residue tier turns
+    /// opaque, so the residue spheres always fully cover the cartoon
+    /// before they themselves fade out.
+    #[must_use]
+    pub fn coarse_tiers(&self) -> Option<CoarseTiers> {
+        if !self.coarse_lod {
+            return None;
+        }
+        let fade = |start: f32| start * (1.0 + COARSE_FADE_FRACTION);
+        let residue_start = self.coarse_residue_distance.max(0.0);
+        let residue_opaque = fade(residue_start);
+        let blob_start = self.coarse_blob_distance.max(residue_opaque);
+        let blob_opaque = fade(blob_start);
+        Some(CoarseTiers {
+            residues: CoarseWindow {
+                start: residue_start,
+                opaque: residue_opaque,
+                end: blob_opaque,
+            },
+            blobs: CoarseWindow {
+                start: blob_start,
+                opaque: blob_opaque,
+                end: f32::INFINITY,
+            },
+        })
+    }
+
+    /// LOD tier of a chain `distance` angstroms from the camera:
+    /// [`COARSE_LOD_TIER`] past the cartoon cutoff, otherwise the
+    /// spline-density tier from [`select_lod_tier`].
+    #[must_use]
+    pub fn chain_lod_tier(&self, distance: f32) -> u8 {
+        match self.coarse_tiers() {
+            Some(tiers) if distance >= tiers.cartoon_cutoff() => {
+                COARSE_LOD_TIER
+            }
+            _ => select_lod_tier(distance, 0.0),
+        }
+    }
+}
+
 impl Default for GeometryOptions {
     fn default() -> Self {
         Self {
             cartoon_style: CartoonStyle::default(),
+            ss_source: SsSource::default(),
             sheet_arrows: true,
             helix_width: 1.4,
             helix_thickness: 0.25,
@@ -270,6 +430,11 @@ impl Default for GeometryOptions {
             na_roundness: 0.0,
             segments_per_residue: 32,
             cross_section_verts: 16,
+            instance_copies: true,
+            coarse_lod: false,
+            coarse_residue_distance: 600.0,
+            coarse_blob_distance: 1500.0,
+            occlusion_culling: false,
             solvent_radius: 0.15,
             ligand_sphere_radius: 0.3,
             ligand_bond_radius: 0.12,
@@ -296,7 +461,7 @@ impl GeometryOptions {
                 coil_roundness: 1.0,
                 ..self.clone()
             },
-            CartoonStyle::Tube => Self {
+            CartoonStyle::Tube | CartoonStyle::Putty => Self {
                 helix_width: 0.4,
                 helix_thickness: 0.4,
                 helix_roundness: 1.0,
@@ -367,3 +532,58 @@ impl GeometryOptions {
         }
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coarse_tiers_cross_fade_without_gaps() {
+        let geo = GeometryOptions {
+            coarse_lod: true,
+            ..GeometryOptions::default()
+        };
+        let tiers = geo.coarse_tiers().unwrap();
+        let near = tiers.residues.start - 1.0;
+        assert_eq!(tiers.residues.opacity(near), 0.0);
+        assert_eq!(geo.chain_lod_tier(near), 3);
+
+        // Halfway through the fade both cartoon and spheres draw.
+        let mid = (tiers.residues.start + tiers.residues.opaque) * 0.5;
+        assert!((tiers.residues.opacity(mid) - 0.5).abs() < 1e-4);
+        assert!(mid < tiers.cartoon_cutoff());
+
+        // Spheres are opaque before the cartoon is dropped, and stay
+        // drawn until the blob is opaque.
+        let cutoff = tiers.cartoon_cutoff();
+        assert_eq!(tiers.residues.opacity(cutoff), 1.0);
+        assert_eq!(geo.chain_lod_tier(cutoff), COARSE_LOD_TIER);
+        assert!(tiers.blobs.start >= cutoff);
+        assert_eq!(tiers.residues.end, tiers.blobs.opaque);
+        assert_eq!(tiers.blobs.opacity(tiers.blobs.opaque), 1.0);
+        assert_eq!(tiers.residues.opacity(tiers.blobs.opaque), 0.0);
+    }
+
+    #[test]
+    fn blob_tier_never_starts_before_spheres_are_opaque() {
+        let geo = GeometryOptions {
+            coarse_lod: true,
+            coarse_residue_distance: 1000.0,
+            coarse_blob_distance: 900.0,
+            ..GeometryOptions::default()
+        };
+        let tiers = geo.coarse_tiers().unwrap();
+        assert_eq!(tiers.blobs.start, tiers.residues.opaque);
+    }
+
+    #[test]
+    fn disabled_coarse_lod_keeps_spline_tiers() {
+        let geo = GeometryOptions::default();
+        assert!(!geo.coarse_lod);
+        assert!(geo.coarse_tiers().is_none());
+        assert_eq!(geo.chain_lod_tier(1.0e6), 3);
+        assert!(lod_scaled(32, 16, COARSE_LOD_TIER).is_coarse());
+        assert!(!lod_scaled(32, 16, 3).is_coarse());
+    }
+}
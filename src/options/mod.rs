@@ -31,12 +31,13 @@ pub use debug::DebugOptions;
 pub use display::{
     BackboneColorMode, BondOptions, BondSource, BondStyle, BondTypeOptions,
     ColorScheme, DisplayOptions, DrawingMode, HelixStyle, LipidMode,
-    NaColorMode, PresentMode, SheetStyle, SidechainColorMode,
+    NaColorMode, PresentMode, SheetStyle, SidechainColorMode, SurfaceColoring,
     SurfaceKindOption,
 };
 pub use geometry::{
     lod_params, lod_scaled, select_chain_lod_tier, select_lod_tier,
-    CartoonStyle, ChainLod, GeometryOptions,
+    CartoonStyle, ChainLod, CoarseTiers, CoarseWindow, GeometryOptions,
+    SsSource, COARSE_LOD_TIER,
 };
 pub use lighting::LightingOptions;
 pub use overrides::DisplayOverrides;
@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 
 use super::display::{
     BondStyle, ColorScheme, DrawingMode, HelixStyle, LipidMode, NaColorMode,
-    SheetStyle, SidechainColorMode, SurfaceKindOption,
+    SheetStyle, SidechainColorMode, SurfaceColoring, SurfaceKindOption,
 };
 use super::geometry::GeometryOptions;
 use super::palette::{PaletteMode, PalettePreset};
@@ -33,12 +33,27 @@ pub struct DisplayOverrides {
     /// Whether to render amino acid sidechains.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub show_sidechains: Option<bool>,
-    /// Molecular surface type (None / Gaussian / SES).
+    /// Molecular surface type (None / Gaussian / SES / SAS / vdW / dots).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub surface_kind: Option<SurfaceKindOption>,
     /// Surface opacity (alpha channel, 0.0–1.0).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub surface_opacity: Option<f32>,
+    /// What colors the surface (entity color / electrostatic potential).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub surface_coloring: Option<SurfaceColoring>,
+    /// Electrostatic color range in kT/e.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub surface_potential_range: Option<f32>,
+    /// Solvent probe radius in Å for SES and SAS surfaces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub surface_probe_radius: Option<f32>,
+    /// Surface grid spacing in Å (dot spacing for dot surfaces).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub surface_resolution: Option<f32>,
+    /// Cut away the surface on the camera side of the focus point.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub surface_cutaway: Option<bool>,
     /// Whether to render internal cavity meshes.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub show_cavities: Option<bool>,
@@ -160,6 +175,11 @@ impl DisplayOverrides {
             show_sidechains: _,
             surface_kind: _,
             surface_opacity: _,
+            surface_coloring: _,
+            surface_potential_range: _,
+            surface_probe_radius: _,
+            surface_resolution: _,
+            surface_cutaway: _,
             show_cavities: _,
             helix_style: _,
             sheet_style: _,
@@ -183,6 +203,17 @@ impl DisplayOverrides {
             show_sidechains: self.show_sidechains.or(base.show_sidechains),
             surface_kind: self.surface_kind.or(base.surface_kind),
             surface_opacity: self.surface_opacity.or(base.surface_opacity),
+            surface_coloring: self.surface_coloring.or(base.surface_coloring),
+            surface_potential_range: self
+                .surface_potential_range
+                .or(base.surface_potential_range),
+            surface_probe_radius: self
+                .surface_probe_radius
+                .or(base.surface_probe_radius),
+            surface_resolution: self
+                .surface_resolution
+                .or(base.surface_resolution),
+            surface_cutaway: self.surface_cutaway.or(base.surface_cutaway),
             show_cavities: self.show_cavities.or(base.show_cavities),
             helix_style: self.helix_style.or(base.helix_style),
             sheet_style: self.sheet_style.or(base.sheet_style),
@@ -234,6 +265,11 @@ impl DisplayOverrides {
             show_sidechains: _,
             surface_kind: _,
             surface_opacity: _,
+            surface_coloring: _,
+            surface_potential_range: _,
+            surface_probe_radius: _,
+            surface_resolution: _,
+            surface_cutaway: _,
             show_cavities: _,
             helix_style: _,
             sheet_style: _,
@@ -283,6 +319,11 @@ impl DisplayOverrides {
         // Surface changes: regen surface mesh + sync.
         if self.surface_kind != new.surface_kind
             || self.surface_opacity != new.surface_opacity
+            || self.surface_coloring != new.surface_coloring
+            || self.surface_potential_range != new.surface_potential_range
+            || self.surface_probe_radius != new.surface_probe_radius
+            || self.surface_resolution != new.surface_resolution
+            || self.surface_cutaway != new.surface_cutaway
             || self.show_cavities != new.show_cavities
         {
             inv |= RenderInvalidation::RE_SURFACE;
@@ -352,6 +393,22 @@ impl DisplayOverrides {
             "surface_opacity" => {
                 self.surface_opacity = value.as_f64().map(|v| v as f32);
             }
+            "surface_coloring" => {
+                self.surface_coloring =
+                    serde_json::from_value(value.clone()).ok();
+            }
+            "surface_potential_range" => {
+                self.surface_potential_range = value.as_f64().map(|v| v as f32);
+            }
+            "surface_probe_radius" => {
+                self.surface_probe_radius = value.as_f64().map(|v| v as f32);
+            }
+            "surface_resolution" => {
+                self.surface_resolution = value.as_f64().map(|v| v as f32);
+            }
+            "surface_cutaway" => {
+                self.surface_cutaway = value.as_bool();
+            }
             "show_hbonds" => {
                 self.show_hbonds = value.as_bool();
             }
@@ -378,6 +435,11 @@ impl DisplayOverrides {
             && self.show_sidechains.is_none()
             && self.surface_kind.is_none()
             && self.surface_opacity.is_none()
+            && self.surface_coloring.is_none()
+            && self.surface_potential_range.is_none()
+            && self.surface_probe_radius.is_none()
+            && self.surface_resolution.is_none()
+            && self.surface_cutaway.is_none()
             && self.show_cavities.is_none()
             && self.helix_style.is_none()
             && self.sheet_style.is_none()
@@ -602,6 +664,43 @@ backbone_palette_preset = "viridis"
         assert!(!inv.contains(RenderInvalidation::RE_MESH));
     }
 
+    #[test]
+    fn diff_surface_coloring_sets_re_surface() {
+        let a = DisplayOverrides::default();
+        let mut b = DisplayOverrides::default();
+        assert!(b
+            .apply_json_field(
+                "surface_coloring",
+                &serde_json::json!("electrostatic"),
+            )
+            .is_ok());
+        assert_eq!(b.surface_coloring, Some(SurfaceColoring::Electrostatic));
+        let inv = a.diff(&b);
+        assert!(inv.contains(RenderInvalidation::RE_SURFACE));
+        assert!(!inv.contains(RenderInvalidation::RE_MESH));
+    }
+
+    #[test]
+    fn diff_surface_geometry_sets_re_surface() {
+        let a = DisplayOverrides::default();
+        let mut b = DisplayOverrides::default();
+        assert!(b
+            .apply_json_field("surface_probe_radius", &serde_json::json!(2.0))
+            .is_ok());
+        assert!(b
+            .apply_json_field("surface_resolution", &serde_json::json!(0.4))
+            .is_ok());
+        assert!(b
+            .apply_json_field("surface_cutaway", &serde_json::json!(true))
+            .is_ok());
+        assert_eq!(b.surface_probe_radius, Some(2.0));
+        assert_eq!(b.surface_cutaway, Some(true));
+        let inv = a.diff(&b);
+        assert!(inv.contains(RenderInvalidation::RE_SURFACE));
+        assert!(!inv.contains(RenderInvalidation::RE_MESH));
+        assert!(!b.is_empty());
+    }
+
     #[test]
     fn diff_helix_style_sets_lod_and_mesh() {
         let a = DisplayOverrides::default();
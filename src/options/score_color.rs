@@ -7,6 +7,10 @@
 //! Score modes:
 //! - **Absolute** (`score`): Fixed REU thresholds (-4 to +4).
 //! - **Relative** (`score_relative`): 5th/95th percentile normalization.
+//!
+//! RMSF mode (`rmsf`) maps trajectory fluctuation to `[0, 1]` against the
+//! entity's most mobile residue. SASA mode (`sasa`) samples the palette
+//! at the relative solvent accessibility, clamped to `[0, 1]`.
 
 /// Absolute energy thresholds in REU.
 ///
@@ -49,6 +53,8 @@ pub(crate) fn compute_per_residue_colors_styled(
     backbone_chains: &[crate::renderer::entity_topology::ProteinBackboneChain],
     ss_types: &[molex::SSType],
     per_residue_scores: &[Option<&[f64]>],
+    per_residue_rmsf: Option<&[f32]>,
+    per_residue_sasa: Option<&[f32]>,
     scheme: &super::ColorScheme,
     palette: &super::palette::Palette,
     entity_index: usize,
@@ -101,6 +107,22 @@ pub(crate) fn compute_per_residue_colors_styled(
                 ),
             }
         }
+        super::ColorScheme::Rmsf => {
+            per_residue_rmsf.filter(|r| !r.is_empty()).map_or_else(
+                || vec![[0.5, 0.5, 0.5]; residue_count],
+                |r| per_residue_rmsf_colors(r, palette),
+            )
+        }
+        super::ColorScheme::Sasa => {
+            per_residue_sasa.filter(|s| !s.is_empty()).map_or_else(
+                || vec![[0.5, 0.5, 0.5]; residue_count],
+                |s| {
+                    s.iter()
+                        .map(|&v| palette.sample(v.clamp(0.0, 1.0)))
+                        .collect()
+                },
+            )
+        }
         super::ColorScheme::Solid => {
             let color = palette
                 .resolved_stops()
@@ -127,6 +149,24 @@ fn per_residue_score_colors_with_palette(
         .collect()
 }
 
+/// RMSF colors using a palette, normalized to the largest value.
+fn per_residue_rmsf_colors(
+    rmsf: &[f32],
+    palette: &super::palette::Palette,
+) -> Vec<[f32; 3]> {
+    let max = rmsf.iter().copied().fold(0.0f32, f32::max);
+    rmsf.iter()
+        .map(|&v| {
+            let t = if max < 1e-6 {
+                0.0
+            } else {
+                (v / max).clamp(0.0, 1.0)
+            };
+            palette.sample(t)
+        })
+        .collect()
+}
+
 /// Relative score colors using a palette.
 fn per_residue_score_colors_relative_with_palette(
     scores: &[f64],
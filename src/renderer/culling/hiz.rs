@@ -0,0 +1,419 @@
+//! Hierarchical depth (Hi-Z) pyramid and the occlusion test against it.
+//!
+//! After the geometry pass, the depth buffer is reduced into a mip
+//! chain where each texel holds the farthest depth of the block below
+//! it (level 0 is half the render resolution). Next frame, before the
+//! geometry pass, a compute pass projects each indirect slot's bounding
+//! sphere with the view-projection the pyramid was built with, reads
+//! the level where the sphere covers at most 2x2 texels, and zeroes the
+//! slot's instance count if the sphere's nearest depth lies behind all
+//! of them.
+//!
+//! The test runs against last frame's view, so geometry uncovered by a
+//! camera move shows up one frame late, and spheres reaching past last
+//! frame's screen edges are never culled.
+
+use glam::Mat4;
+
+use super::indirect::{ArgList, IndirectDraws};
+use crate::error::VisoError;
+use crate::gpu::{RenderContext, Shader, ShaderComposer};
+
+/// Threads per workgroup of the pyramid build (each axis).
+const BUILD_WORKGROUP: u32 = 8;
+
+/// Threads per workgroup of the occlusion test.
+const CULL_WORKGROUP: u32 = 64;
+
+/// Occlusion test uniforms. Must match the WGSL `CullParams` struct.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct CullParams {
+    view_proj: [[f32; 4]; 4],
+    /// Render resolution the depth buffer had.
+    screen_size: [f32; 2],
+    mip_count: u32,
+    slot_count: u32,
+    /// Words per argument slot.
+    stride: u32,
+    _pad: [u32; 3],
+}
+
+/// The pyramid texture with a full view for the test and one view per
+/// level for the build.
+struct Pyramid {
+    /// Render resolution the pyramid was sized for.
+    screen: (u32, u32),
+    /// Level 0 size: half the render resolution, rounded up.
+    base: (u32, u32),
+    view: wgpu::TextureView,
+    levels: Vec<wgpu::TextureView>,
+}
+
+impl Pyramid {
+    fn new(device: &wgpu::Device, screen: (u32, u32)) -> Self {
+        let base = (screen.0.div_ceil(2).max(1), screen.1.div_ceil(2).max(1));
+        let mip_count = base.0.max(base.1).ilog2() + 1;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Hi-Z Pyramid"),
+            size: wgpu::Extent3d {
+                width: base.0,
+                height: base.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: mip_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R32Float,
+            usage: wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let levels = (0..mip_count)
+            .map(|level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Hi-Z Level"),
+                    base_mip_level: level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        Self {
+            screen,
+            base,
+            view: texture.create_view(&Default::default()),
+            levels,
+        }
+    }
+
+    /// Size of mip `level`.
+    fn level_size(&self, level: usize) -> (u32, u32) {
+        ((self.base.0 >> level).max(1), (self.base.1 >> level).max(1))
+    }
+}
+
+/// Hi-Z pyramid plus the compute pipelines that build and test it.
+pub(crate) struct HiZ {
+    base_pipeline: wgpu::ComputePipeline,
+    level_pipeline: wgpu::ComputePipeline,
+    cull_pipeline: wgpu::ComputePipeline,
+    base_layout: wgpu::BindGroupLayout,
+    level_layout: wgpu::BindGroupLayout,
+    cull_layout: wgpu::BindGroupLayout,
+    /// One uniform buffer per argument list.
+    params: [wgpu::Buffer; 2],
+    pyramid: Option<Pyramid>,
+    /// View-projection of the frame the pyramid holds; `None` until a
+    /// frame has built it, and after occlusion culling was switched off.
+    view_proj: Option<Mat4>,
+}
+
+fn storage_entry(binding: u32, read_only: bool) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+fn texture_entry(
+    binding: u32,
+    sample_type: wgpu::TextureSampleType,
+) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Texture {
+            sample_type,
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+fn level_target_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::StorageTexture {
+            access: wgpu::StorageTextureAccess::WriteOnly,
+            format: wgpu::TextureFormat::R32Float,
+            view_dimension: wgpu::TextureViewDimension::D2,
+        },
+        count: None,
+    }
+}
+
+fn compute_pipeline(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+    module: &wgpu::ShaderModule,
+    entry_point: &str,
+) -> wgpu::ComputePipeline {
+    let pipeline_layout =
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(&format!("{label} Layout")),
+            bind_group_layouts: &[layout],
+            push_constant_ranges: &[],
+        });
+    device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some(label),
+        layout: Some(&pipeline_layout),
+        module,
+        entry_point: Some(entry_point),
+        compilation_options: Default::default(),
+        cache: None,
+    })
+}
+
+impl HiZ {
+    pub(crate) fn new(
+        context: &RenderContext,
+        shader_composer: &mut ShaderComposer,
+    ) -> Result<Self, VisoError> {
+        let device = &context.device;
+        let unfilterable = wgpu::TextureSampleType::Float { filterable: false };
+        let base_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Hi-Z Base Layout"),
+                entries: &[
+                    texture_entry(0, wgpu::TextureSampleType::Depth),
+                    level_target_entry(2),
+                ],
+            });
+        let level_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Hi-Z Level Layout"),
+                entries: &[
+                    texture_entry(1, unfilterable),
+                    level_target_entry(2),
+                ],
+            });
+        let cull_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Occlusion Cull Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    storage_entry(1, true),
+                    storage_entry(2, false),
+                    texture_entry(3, unfilterable),
+                ],
+            });
+
+        let build = shader_composer.compose(device, Shader::HiZBuild)?;
+        let cull = shader_composer.compose(device, Shader::OcclusionCull)?;
+        let params = std::array::from_fn(|_| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Occlusion Cull Params"),
+                size: size_of::<CullParams>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+
+        Ok(Self {
+            base_pipeline: compute_pipeline(
+                device,
+                "Hi-Z Base",
+                &base_layout,
+                &build,
+                "build_base",
+            ),
+            level_pipeline: compute_pipeline(
+                device,
+                "Hi-Z Level",
+                &level_layout,
+                &build,
+                "build_level",
+            ),
+            cull_pipeline: compute_pipeline(
+                device,
+                "Occlusion Cull",
+                &cull_layout,
+                &cull,
+                "cull",
+            ),
+            base_layout,
+            level_layout,
+            cull_layout,
+            params,
+            pyramid: None,
+            view_proj: None,
+        })
+    }
+
+    /// Forget the pyramid so a later [`Self::occlude`] does nothing
+    /// until [`Self::build`] runs again.
+    pub(crate) fn invalidate(&mut self) {
+        self.view_proj = None;
+    }
+
+    /// Encode the pyramid build from `depth`, the depth buffer of a
+    /// frame drawn with `view_proj`. Call after the geometry pass.
+    pub(crate) fn build(
+        &mut self,
+        context: &RenderContext,
+        encoder: &mut wgpu::CommandEncoder,
+        depth: &wgpu::TextureView,
+        view_proj: Mat4,
+    ) {
+        let device = &context.device;
+        let screen = (context.render_width(), context.render_height());
+        if self.pyramid.as_ref().is_none_or(|p| p.screen != screen) {
+            self.pyramid = Some(Pyramid::new(device, screen));
+        }
+        let Some(pyramid) = &self.pyramid else {
+            return;
+        };
+
+        let mut pass =
+            encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Hi-Z Build"),
+                timestamp_writes: None,
+            });
+        for (level, target) in pyramid.levels.iter().enumerate() {
+            let (layout, source) = if level == 0 {
+                (&self.base_layout, (0, depth))
+            } else {
+                (&self.level_layout, (1, &pyramid.levels[level - 1]))
+            };
+            let bind_group =
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Hi-Z Level Bind Group"),
+                    layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: source.0,
+                            resource: wgpu::BindingResource::TextureView(
+                                source.1,
+                            ),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::TextureView(
+                                target,
+                            ),
+                        },
+                    ],
+                });
+            pass.set_pipeline(if level == 0 {
+                &self.base_pipeline
+            } else {
+                &self.level_pipeline
+            });
+            pass.set_bind_group(0, &bind_group, &[]);
+            let (width, height) = pyramid.level_size(level);
+            pass.dispatch_workgroups(
+                width.div_ceil(BUILD_WORKGROUP),
+                height.div_ceil(BUILD_WORKGROUP),
+                1,
+            );
+        }
+        self.view_proj = Some(view_proj);
+    }
+
+    /// Encode the occlusion test of this frame's uploaded slots against
+    /// the last built pyramid. Call before the geometry pass.
+    pub(crate) fn occlude(
+        &self,
+        context: &RenderContext,
+        encoder: &mut wgpu::CommandEncoder,
+        draws: &IndirectDraws,
+    ) {
+        let (Some(view_proj), Some(pyramid)) = (self.view_proj, &self.pyramid)
+        else {
+            return;
+        };
+        let lists: Vec<(&ArgList, &wgpu::Buffer)> = draws
+            .occludable()
+            .into_iter()
+            .zip(&self.params)
+            .filter(|(list, _)| list.len() > 0)
+            .collect();
+        if lists.is_empty() {
+            return;
+        }
+
+        let mut pass =
+            encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Occlusion Cull"),
+                timestamp_writes: None,
+            });
+        pass.set_pipeline(&self.cull_pipeline);
+        for (list, params) in lists {
+            let uniform = CullParams {
+                view_proj: view_proj.to_cols_array_2d(),
+                screen_size: [pyramid.screen.0 as f32, pyramid.screen.1 as f32],
+                mip_count: pyramid.levels.len() as u32,
+                slot_count: list.len(),
+                stride: list.stride(),
+                _pad: [0; 3],
+            };
+            context
+                .queue
+                .write_buffer(params, 0, bytemuck::bytes_of(&uniform));
+            let bind_group =
+                context
+                    .device
+                    .create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("Occlusion Cull Bind Group"),
+                        layout: &self.cull_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: params.as_entire_binding(),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: list
+                                    .bounds_buffer()
+                                    .as_entire_binding(),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 2,
+                                resource: list
+                                    .args_buffer()
+                                    .as_entire_binding(),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 3,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &pyramid.view,
+                                ),
+                            },
+                        ],
+                    });
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(list.len().div_ceil(CULL_WORKGROUP), 1, 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cull_params_match_wgsl_layout() {
+        // mat4x4 + vec2 + three u32, rounded up to 16-byte alignment.
+        assert_eq!(size_of::<CullParams>(), 96);
+    }
+}
@@ -0,0 +1,263 @@
+//! Indirect draw argument lists: one slot per culled chain and pass.
+
+use std::ops::Range;
+
+use crate::gpu::dynamic_buffer::DynamicBuffer;
+
+/// Consecutive slots of one list, drawn together.
+pub(crate) type Slots = Range<u32>;
+
+/// Words in a `DrawIndexedIndirectArgs`.
+const INDEXED_WORDS: usize = 5;
+
+/// Words in a `DrawIndirectArgs`.
+const DIRECT_WORDS: usize = 4;
+
+/// Bounds of a slot the occlusion pass must never cull (negative
+/// radius).
+const ALWAYS_VISIBLE: [f32; 4] = [0.0, 0.0, 0.0, -1.0];
+
+/// One list of indirect draw arguments, plus each slot's world-space
+/// bounding sphere for the occlusion pass.
+pub(crate) struct ArgList {
+    /// Packed arguments, `stride` words per slot.
+    words: Vec<u32>,
+    spheres: Vec<[f32; 4]>,
+    stride: usize,
+    args: DynamicBuffer,
+    bounds: DynamicBuffer,
+}
+
+impl ArgList {
+    fn new(device: &wgpu::Device, label: &str, stride: usize) -> Self {
+        Self {
+            words: Vec::new(),
+            spheres: Vec::new(),
+            stride,
+            args: DynamicBuffer::new(
+                device,
+                &format!("{label} Indirect Args"),
+                0,
+                wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::STORAGE,
+            ),
+            bounds: DynamicBuffer::new(
+                device,
+                &format!("{label} Indirect Bounds"),
+                0,
+                wgpu::BufferUsages::STORAGE,
+            ),
+        }
+    }
+
+    fn push(&mut self, args: &[u32], sphere: [f32; 4]) {
+        self.words.extend_from_slice(args);
+        self.spheres.push(sphere);
+    }
+
+    /// Number of queued slots.
+    pub(crate) fn len(&self) -> u32 {
+        self.spheres.len() as u32
+    }
+
+    /// Words per slot; the instance count is always word 1.
+    pub(crate) fn stride(&self) -> u32 {
+        self.stride as u32
+    }
+
+    /// Argument buffer, written by the occlusion pass.
+    pub(crate) fn args_buffer(&self) -> &wgpu::Buffer {
+        self.args.buffer()
+    }
+
+    /// Per-slot `[x, y, z, r]` world-space bounds.
+    pub(crate) fn bounds_buffer(&self) -> &wgpu::Buffer {
+        self.bounds.buffer()
+    }
+
+    fn offset(&self, slot: u32) -> wgpu::BufferAddress {
+        (slot as usize * self.stride * size_of::<u32>()) as wgpu::BufferAddress
+    }
+
+    fn slot(&self, slot: u32) -> &[u32] {
+        let start = slot as usize * self.stride;
+        &self.words[start..start + self.stride]
+    }
+
+    fn clear(&mut self) {
+        self.words.clear();
+        self.spheres.clear();
+    }
+
+    fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let _ = self.args.write(device, queue, &self.words);
+        let _ = self.bounds.write(device, queue, &self.spheres);
+    }
+}
+
+/// Frame-local indirect draws of every culled pass.
+///
+/// Indexed draws (cartoon tube and ribbon) and instanced impostor draws
+/// live in separate lists, since their argument layouts differ. Slots
+/// are queued in draw order, so each pass's visible chains form one
+/// contiguous [`Slots`] range, drawn with a single multi-draw where the
+/// device supports it.
+pub(crate) struct IndirectDraws {
+    indexed: ArgList,
+    direct: ArgList,
+    multi_draw: bool,
+    /// Indirect draws may start past instance 0. Without it, instanced
+    /// slots are drawn directly from the CPU copy (frustum culled, never
+    /// occlusion culled).
+    first_instance: bool,
+}
+
+impl IndirectDraws {
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let features = device.features();
+        Self {
+            indexed: ArgList::new(device, "Indexed", INDEXED_WORDS),
+            direct: ArgList::new(device, "Instanced", DIRECT_WORDS),
+            multi_draw: features.contains(wgpu::Features::MULTI_DRAW_INDIRECT),
+            first_instance: features
+                .contains(wgpu::Features::INDIRECT_FIRST_INSTANCE),
+        }
+    }
+
+    /// Drop last frame's slots.
+    pub(crate) fn clear(&mut self) {
+        self.indexed.clear();
+        self.direct.clear();
+    }
+
+    /// Slot the next [`Self::push_indexed`] lands in.
+    pub(crate) fn indexed_len(&self) -> u32 {
+        self.indexed.len()
+    }
+
+    /// Slot the next [`Self::push_instances`] lands in.
+    pub(crate) fn direct_len(&self) -> u32 {
+        self.direct.len()
+    }
+
+    /// Queue an indexed draw of `indices` with `instances` instances,
+    /// bounded by `sphere` (`[x, y, z, r]` in world space).
+    pub(crate) fn push_indexed(
+        &mut self,
+        indices: Range<u32>,
+        instances: u32,
+        sphere: [f32; 4],
+    ) {
+        self.indexed.push(
+            &[indices.len() as u32, instances, indices.start, 0, 0],
+            sphere,
+        );
+    }
+
+    /// Queue an instanced draw, bounded by `sphere`, or by nothing the
+    /// occlusion pass may cull when `None`.
+    pub(crate) fn push_instances(
+        &mut self,
+        vertices: u32,
+        instances: Range<u32>,
+        sphere: Option<[f32; 4]>,
+    ) {
+        self.direct.push(
+            &[vertices, instances.len() as u32, 0, instances.start],
+            sphere.unwrap_or(ALWAYS_VISIBLE),
+        );
+    }
+
+    /// Upload this frame's slots.
+    pub(crate) fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        self.indexed.upload(device, queue);
+        self.direct.upload(device, queue);
+    }
+
+    /// Lists the occlusion pass may cull.
+    pub(crate) fn occludable(&self) -> Vec<&ArgList> {
+        if self.first_instance {
+            vec![&self.indexed, &self.direct]
+        } else {
+            vec![&self.indexed]
+        }
+    }
+
+    /// Draw indexed `slots`. Pipeline, bind groups, vertex and index
+    /// buffers must already be set.
+    pub(crate) fn draw_indexed(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        slots: &Slots,
+    ) {
+        if slots.is_empty() {
+            return;
+        }
+        let buffer = self.indexed.args_buffer();
+        if self.multi_draw {
+            render_pass.multi_draw_indexed_indirect(
+                buffer,
+                self.indexed.offset(slots.start),
+                slots.len() as u32,
+            );
+        } else {
+            for slot in slots.clone() {
+                render_pass
+                    .draw_indexed_indirect(buffer, self.indexed.offset(slot));
+            }
+        }
+    }
+
+    /// Draw instanced `slots`. Pipeline and bind groups must already be
+    /// set.
+    pub(crate) fn draw(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        slots: &Slots,
+    ) {
+        if slots.is_empty() {
+            return;
+        }
+        if !self.first_instance {
+            for slot in slots.clone() {
+                let &[vertices, count, _, first] = self.direct.slot(slot)
+                else {
+                    continue;
+                };
+                render_pass.draw(0..vertices, first..first + count);
+            }
+            return;
+        }
+        let buffer = self.direct.args_buffer();
+        if self.multi_draw {
+            render_pass.multi_draw_indirect(
+                buffer,
+                self.direct.offset(slots.start),
+                slots.len() as u32,
+            );
+        } else {
+            for slot in slots.clone() {
+                render_pass.draw_indirect(buffer, self.direct.offset(slot));
+            }
+        }
+    }
+
+    /// `(label, used_bytes, allocated_bytes)` for debug overlay.
+    pub(crate) fn buffer_info(&self) -> Vec<(&'static str, usize, usize)> {
+        vec![
+            (
+                "Indirect Indexed Args",
+                self.indexed.args.len(),
+                self.indexed.args.capacity(),
+            ),
+            (
+                "Indirect Instanced Args",
+                self.direct.args.len(),
+                self.direct.args.capacity(),
+            ),
+        ]
+    }
+}
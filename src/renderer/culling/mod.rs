@@ -0,0 +1,240 @@
+//! Chain-level visibility for the cartoon and impostor passes.
+//!
+//! Before the geometry pass, every chain (or group of impostor
+//! instances) whose bounding sphere intersects the view frustum is
+//! queued as one indirect draw slot per pass in [`IndirectDraws`].
+//! With occlusion culling on, [`HiZ`] then tests each slot's sphere
+//! against a depth pyramid built from the previous frame and zeroes the
+//! instance count of hidden ones, so the CPU never waits on the GPU for
+//! the result.
+
+mod hiz;
+mod indirect;
+
+use std::ops::Range;
+
+use glam::{Mat4, Vec3};
+pub(crate) use hiz::HiZ;
+pub(crate) use indirect::{IndirectDraws, Slots};
+
+use crate::camera::frustum::Frustum;
+use crate::renderer::impostor::Bounded;
+
+/// Most instances in one impostor culling group. Keeps groups of
+/// unrelated molecules (waters, ions) small enough to cull usefully.
+const MAX_GROUP_INSTANCES: usize = 256;
+
+/// Per-draw visibility inputs for geometry drawn as instanced copies.
+/// Bounds are given in the frame they are stored in (the prototype's
+/// frame for instanced entity copies) and tested at every copy.
+pub(crate) struct ChainCull {
+    /// World-space view frustum.
+    frustum: Frustum,
+    /// World-space camera eye, for the coarse LOD distance.
+    eye: Vec3,
+    /// Model-to-world transform of every copy.
+    models: Vec<Mat4>,
+    /// Instances each draw is issued with.
+    pub(crate) copies: u32,
+}
+
+impl ChainCull {
+    /// Culling inputs for geometry drawn `copies` times under a camera
+    /// with `view_proj` at `eye`, each copy placed by one of `models`.
+    pub(crate) fn new(
+        view_proj: Mat4,
+        eye: Vec3,
+        models: Vec<Mat4>,
+        copies: u32,
+    ) -> Self {
+        Self {
+            frustum: Frustum::from_view_projection(view_proj),
+            eye,
+            models,
+            copies,
+        }
+    }
+
+    /// Each copy of a model-frame bounding sphere, in world space.
+    fn images(
+        &self,
+        center: Vec3,
+        radius: f32,
+    ) -> impl Iterator<Item = (Vec3, f32)> + '_ {
+        self.models.iter().map(move |model| {
+            let scale = model
+                .x_axis
+                .truncate()
+                .length()
+                .max(model.y_axis.truncate().length())
+                .max(model.z_axis.truncate().length());
+            (model.transform_point3(center), radius * scale)
+        })
+    }
+
+    /// Whether any copy of a model-frame bounding sphere intersects the
+    /// frustum.
+    pub(crate) fn is_visible(&self, center: Vec3, radius: f32) -> bool {
+        self.images(center, radius)
+            .any(|(c, r)| self.frustum.intersects_sphere(c, r))
+    }
+
+    /// Distance from the eye to the nearest copy of a model-frame
+    /// point.
+    pub(crate) fn distance(&self, center: Vec3) -> f32 {
+        self.images(center, 0.0)
+            .map(|(c, _)| c.distance(self.eye))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// A world-space sphere around every copy of a model-frame bounding
+    /// sphere, as `[x, y, z, r]`. Radii grow with each transform's
+    /// largest axis scale.
+    pub(crate) fn world_sphere(&self, center: Vec3, radius: f32) -> [f32; 4] {
+        let images: Vec<(Vec3, f32)> = self.images(center, radius).collect();
+        let (min, max) = images.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), &(c, r)| (min.min(c - r), max.max(c + r)),
+        );
+        let c = (min + max) * 0.5;
+        let r = images
+            .iter()
+            .map(|&(image, r)| image.distance(c) + r)
+            .fold(0.0_f32, f32::max);
+        [c.x, c.y, c.z, r]
+    }
+}
+
+/// Consecutive impostor instances culled as one: a chain's bases, or a
+/// run of a molecule's atoms.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct InstanceGroup {
+    /// Instance range in the pass's buffer.
+    pub(crate) instances: Range<u32>,
+    /// Bounding sphere center around every instance in the range.
+    pub(crate) center: Vec3,
+    /// Bounding sphere radius.
+    pub(crate) radius: f32,
+}
+
+impl InstanceGroup {
+    /// The same group after `by` instances were placed before it.
+    pub(crate) fn offset(&self, by: u32) -> Self {
+        Self {
+            instances: self.instances.start + by..self.instances.end + by,
+            ..self.clone()
+        }
+    }
+}
+
+/// Split `instances` into culling groups of consecutive instances,
+/// starting a new group wherever `key` changes or the current group is
+/// full.
+pub(crate) fn group_instances<T: Bounded, K: PartialEq>(
+    instances: &[T],
+    key: impl Fn(usize) -> K,
+) -> Vec<InstanceGroup> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for end in 1..=instances.len() {
+        let split = end == instances.len()
+            || end - start == MAX_GROUP_INSTANCES
+            || key(end) != key(start);
+        if split {
+            let (center, radius) = enclosing_sphere(&instances[start..end]);
+            groups.push(InstanceGroup {
+                instances: start as u32..end as u32,
+                center,
+                radius,
+            });
+            start = end;
+        }
+    }
+    groups
+}
+
+/// A sphere around the bounding spheres of `instances`, centered on
+/// their bounding box.
+fn enclosing_sphere<T: Bounded>(instances: &[T]) -> (Vec3, f32) {
+    let spheres: Vec<(Vec3, f32)> =
+        instances.iter().map(Bounded::bounding_sphere).collect();
+    let (min, max) = spheres.iter().fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), &(c, r)| (min.min(c - r), max.max(c + r)),
+    );
+    let center = (min + max) * 0.5;
+    let radius = spheres
+        .iter()
+        .map(|&(c, r)| c.distance(center) + r)
+        .fold(0.0_f32, f32::max);
+    (center, radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::impostor::SphereInstance;
+
+    fn sphere(x: f32, radius: f32) -> SphereInstance {
+        SphereInstance {
+            center: [x, 0.0, 0.0, radius],
+            color: [1.0; 4],
+        }
+    }
+
+    #[test]
+    fn groups_split_on_key_change_and_size() {
+        let instances: Vec<SphereInstance> =
+            (0..600).map(|i| sphere(i as f32, 0.5)).collect();
+        let groups = group_instances(&instances, |i| i < 10);
+        let ranges: Vec<Range<u32>> =
+            groups.iter().map(|g| g.instances.clone()).collect();
+        assert_eq!(ranges, vec![0..10, 10..266, 266..522, 522..600]);
+
+        let first = &groups[0];
+        assert!((first.center.x - 4.5).abs() < 1e-5);
+        assert!((first.radius - 5.0).abs() < 1e-5);
+        assert!(group_instances::<SphereInstance, ()>(&[], |_| ()).is_empty());
+    }
+
+    #[test]
+    fn group_offset_shifts_only_the_range() {
+        let group = InstanceGroup {
+            instances: 2..5,
+            center: Vec3::ONE,
+            radius: 3.0,
+        };
+        let moved = group.offset(10);
+        assert_eq!(moved.instances, 12..15);
+        assert_eq!(moved.center, Vec3::ONE);
+    }
+
+    #[test]
+    fn world_sphere_follows_the_model_transform() {
+        let model = Mat4::from_scale_rotation_translation(
+            Vec3::splat(2.0),
+            glam::Quat::from_rotation_z(1.0),
+            Vec3::new(10.0, 0.0, 0.0),
+        );
+        let cull = ChainCull::new(Mat4::IDENTITY, Vec3::ZERO, vec![model], 1);
+        let [x, y, z, r] = cull.world_sphere(Vec3::ZERO, 1.5);
+        assert!((Vec3::new(x, y, z) - Vec3::X * 10.0).length() < 1e-5);
+        assert!((r - 3.0).abs() < 1e-5);
+        assert!((cull.distance(Vec3::ZERO) - 10.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn culling_covers_every_copy() {
+        let models = vec![
+            Mat4::from_translation(Vec3::X * -10.0),
+            Mat4::from_translation(Vec3::X * 4.0),
+        ];
+        let cull = ChainCull::new(Mat4::IDENTITY, Vec3::ZERO, models, 2);
+        let [x, y, z, r] = cull.world_sphere(Vec3::ZERO, 1.0);
+        assert_eq!([x, y, z, r], [-3.0, 0.0, 0.0, 8.0]);
+        assert!((cull.distance(Vec3::ZERO) - 4.0).abs() < 1e-5);
+        // Only the second copy lands in the unit clip volume.
+        assert!(cull.is_visible(Vec3::X * -4.0, 0.5));
+        assert!(!cull.is_visible(Vec3::X * 20.0, 0.5));
+    }
+}
@@ -1,7 +1,11 @@
 /// Bind groups shared across all molecular draw calls.
 pub(crate) struct DrawBindGroups<'a> {
-    /// Camera uniform bind group (view-projection, position, etc.).
+    /// Camera uniform bind group (view-projection, position, etc.) and
+    /// the model copies geometry is drawn with.
     pub(crate) camera: &'a wgpu::BindGroup,
+    /// Instances per draw: the most copies any geometry has under
+    /// `camera`.
+    pub(crate) copies: u32,
     /// Lighting uniform bind group.
     pub(crate) lighting: &'a wgpu::BindGroup,
     /// Selection state storage buffer bind group.
@@ -97,7 +97,8 @@ impl ProteinBackboneIndices {
 /// index `i` of [`n`](Self::n), [`ca`](Self::ca), [`c`](Self::c), and
 /// [`o`](Self::o) refer to the same residue, and all four are guaranteed
 /// equal length (enforced in [`ProteinBackboneIndices::resolve`]). Fields
-/// are private so the only construction path is that fallible resolve.
+/// are private so the only construction paths are that fallible resolve
+/// and [`slice`](Self::slice) of a resolved chain.
 #[derive(Clone, Default)]
 pub(crate) struct ProteinBackboneChain {
     n: Vec<Vec3>,
@@ -129,6 +130,21 @@ impl ProteinBackboneChain {
     pub(crate) fn residue_count(&self) -> usize {
         self.ca.len()
     }
+
+    /// `[N, CA, C, O]` of residue `i`.
+    pub(crate) fn residue(&self, i: usize) -> [Vec3; 4] {
+        [self.n[i], self.ca[i], self.c[i], self.o[i]]
+    }
+
+    /// The contiguous run of `residues` as a chain of its own.
+    pub(crate) fn slice(&self, residues: Range<usize>) -> Self {
+        Self {
+            n: self.n[residues.clone()].to_vec(),
+            ca: self.ca[residues.clone()].to_vec(),
+            c: self.c[residues.clone()].to_vec(),
+            o: self.o[residues].to_vec(),
+        }
+    }
 }
 
 /// Resolved P-atom positions for one continuous nucleic-acid backbone
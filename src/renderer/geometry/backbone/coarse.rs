@@ -0,0 +1,356 @@
+//! Coarse-grained stand-ins for far-away chains.
+//!
+//! Past [`CoarseTiers::cartoon_cutoff`] a chain's cartoon is neither
+//! tessellated nor drawn. The chain is shown as one sphere per residue
+//! (at the CA or P atom) instead, and farther still as a single blob
+//! sized by the chain's radius of gyration. Every instance carries its
+//! chain's center and its tier's distance window. The shader derives
+//! the cross-fade from those each frame, so nothing is re-uploaded as
+//! the camera moves.
+
+use glam::Vec3;
+
+use super::BackboneRenderer;
+use crate::error::VisoError;
+use crate::gpu::dynamic_buffer::TypedBuffer;
+use crate::gpu::{RenderContext, Shader, ShaderComposer};
+use crate::options::{CoarseTiers, CoarseWindow};
+use crate::renderer::culling::{IndirectDraws, Slots};
+use crate::renderer::{pipeline_util, PipelineLayouts};
+
+/// Residue sphere radius, about a residue's van der Waals extent.
+pub(super) const RESIDUE_SPHERE_RADIUS: f32 = 2.4;
+
+/// Smallest chain blob radius, so short peptides stay visible.
+const MIN_BLOB_RADIUS: f32 = 4.0;
+
+/// Radius of the uniform sphere with a given radius of gyration:
+/// `Rg = sqrt(3/5) * R`.
+const GYRATION_TO_RADIUS: f32 = 1.290_994_4;
+
+/// `residue_idx` of a chain blob, which stands for no single residue.
+pub(crate) const BLOB_RESIDUE: u32 = u32::MAX;
+
+/// Per-instance data of the coarse pass.
+/// Must match the WGSL `CoarseInstance` struct layout.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct CoarseInstance {
+    /// xyz = position, w = radius
+    pub(crate) center: [f32; 4],
+    /// Baked RGB color.
+    pub(crate) color: [f32; 3],
+    /// Flat residue index, or [`BLOB_RESIDUE`].
+    pub(crate) residue_idx: u32,
+    /// xyz = center of the chain whose camera distance drives the fade,
+    /// w = the chain's first flat residue index (picks its model copies)
+    pub(crate) anchor: [f32; 4],
+    /// `(start, opaque, end, unused)` of the tier's distance window.
+    pub(crate) window: [f32; 4],
+}
+
+fn window(w: &CoarseWindow) -> [f32; 4] {
+    [w.start, w.opaque, w.end, 0.0]
+}
+
+/// Coarse instances of one chain: a sphere per control point, then the
+/// chain blob.
+///
+/// `colors` is parallel with `points`; `first_residue` is the flat
+/// residue index of `points[0]` (carried in the anchor's w), and
+/// `center` the chain's bounding center (the fade anchor).
+pub(crate) fn chain_instances(
+    points: &[Vec3],
+    colors: &[[f32; 3]],
+    first_residue: u32,
+    center: Vec3,
+    tiers: &CoarseTiers,
+) -> Vec<CoarseInstance> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let anchor = [center.x, center.y, center.z, first_residue as f32];
+    let residues = window(&tiers.residues);
+    let mut out: Vec<CoarseInstance> = points
+        .iter()
+        .zip(colors)
+        .enumerate()
+        .map(|(i, (p, &color))| CoarseInstance {
+            center: [p.x, p.y, p.z, RESIDUE_SPHERE_RADIUS],
+            color,
+            residue_idx: first_residue + i as u32,
+            anchor,
+            window: residues,
+        })
+        .collect();
+
+    let (blob_center, blob_radius) = blob(points);
+    let n = colors.len().max(1) as f32;
+    let mean = colors.iter().fold([0.0; 3], |acc, c| {
+        [acc[0] + c[0] / n, acc[1] + c[1] / n, acc[2] + c[2] / n]
+    });
+    out.push(CoarseInstance {
+        center: [blob_center.x, blob_center.y, blob_center.z, blob_radius],
+        color: mean,
+        residue_idx: BLOB_RESIDUE,
+        anchor,
+        window: window(&tiers.blobs),
+    });
+    out
+}
+
+/// Center and radius of the sphere standing in for a chain: the
+/// centroid, and the uniform sphere with the chain's radius of
+/// gyration.
+fn blob(points: &[Vec3]) -> (Vec3, f32) {
+    let n = points.len() as f32;
+    let center = points.iter().copied().sum::<Vec3>() / n;
+    let rg_sq = points
+        .iter()
+        .map(|p| p.distance_squared(center))
+        .sum::<f32>()
+        / n;
+    (
+        center,
+        (rg_sq.sqrt() * GYRATION_TO_RADIUS).max(MIN_BLOB_RADIUS),
+    )
+}
+
+// ==================== RENDERER ====================
+
+/// Impostor pass drawing the coarse instances of whole chains.
+///
+/// Bind groups follow the impostor convention, except that group 3
+/// also carries the instanced copies' colors so copies keep their own
+/// colors once coarsened.
+pub(super) struct CoarsePass {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    instances: TypedBuffer<CoarseInstance>,
+    pub(super) bind_group: wgpu::BindGroup,
+}
+
+impl CoarsePass {
+    pub(super) fn new(
+        context: &RenderContext,
+        layouts: &PipelineLayouts,
+        instance_colors: &wgpu::Buffer,
+        shader_composer: &mut ShaderComposer,
+    ) -> Result<Self, VisoError> {
+        let device = &context.device;
+        let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX
+                | wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Coarse Layout"),
+                entries: &[storage_entry(0), storage_entry(1)],
+            });
+        let instances = TypedBuffer::new_with_data(
+            device,
+            "Coarse Buffer",
+            &[bytemuck::Zeroable::zeroed()],
+            wgpu::BufferUsages::STORAGE,
+        );
+        let bind_group =
+            create_bind_group(device, &layout, &instances, instance_colors);
+
+        let shader = shader_composer.compose(device, Shader::Coarse)?;
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Coarse Pipeline Layout"),
+                bind_group_layouts: &[
+                    &layouts.camera,
+                    &layouts.lighting,
+                    &layouts.selection,
+                    &layout,
+                ],
+                push_constant_ranges: &[],
+            });
+        let pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Coarse Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    targets: &pipeline_util::hdr_fragment_targets(),
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: Some(pipeline_util::depth_stencil_state()),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+        Ok(Self {
+            pipeline,
+            layout,
+            instances,
+            bind_group,
+        })
+    }
+
+    /// Upload raw [`CoarseInstance`] bytes and rebind.
+    pub(super) fn write(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        instance_colors: &wgpu::Buffer,
+    ) {
+        let zeroed: CoarseInstance = bytemuck::Zeroable::zeroed();
+        let data = if bytes.is_empty() {
+            bytemuck::bytes_of(&zeroed)
+        } else {
+            bytes
+        };
+        let _ = self.instances.write_bytes(device, queue, data);
+        self.bind(device, instance_colors);
+    }
+
+    /// Recreate the bind group, e.g. after the instance color buffer was
+    /// reallocated.
+    pub(super) fn bind(
+        &mut self,
+        device: &wgpu::Device,
+        instance_colors: &wgpu::Buffer,
+    ) {
+        self.bind_group = create_bind_group(
+            device,
+            &self.layout,
+            &self.instances,
+            instance_colors,
+        );
+    }
+
+    /// Draw queued indirect slots. Groups 0-2 must already be bound and
+    /// group 3 set to [`Self::bind_group`].
+    pub(super) fn draw_slots(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        indirect: &IndirectDraws,
+        slots: &Slots,
+    ) {
+        if slots.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        indirect.draw(render_pass, slots);
+    }
+
+    /// `(label, used_bytes, allocated_bytes)` for debug overlay.
+    pub(super) fn buffer_info(&self) -> (&'static str, usize, usize) {
+        (
+            "Backbone Coarse",
+            self.instances.len_bytes(),
+            self.instances.capacity_bytes(),
+        )
+    }
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    instances: &TypedBuffer<CoarseInstance>,
+    instance_colors: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Coarse Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: instances.buffer().as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: instance_colors.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+impl BackboneRenderer {
+    /// Set the coarse LOD windows used to pick each chain's
+    /// representation at draw time.
+    pub(crate) fn set_coarse_tiers(&mut self, tiers: Option<CoarseTiers>) {
+        self.coarse_tiers = tiers;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::options::GeometryOptions;
+
+    fn tiers() -> CoarseTiers {
+        GeometryOptions {
+            coarse_lod: true,
+            ..GeometryOptions::default()
+        }
+        .coarse_tiers()
+        .unwrap()
+    }
+
+    #[test]
+    fn one_sphere_per_residue_then_one_blob() {
+        let tiers = tiers();
+        let points: Vec<Vec3> = (0..10)
+            .map(|i| Vec3::new(i as f32 * 3.8, 0.0, 0.0))
+            .collect();
+        let colors = vec![[1.0, 0.0, 0.0]; 5]
+            .into_iter()
+            .chain(vec![[0.0, 0.0, 1.0]; 5])
+            .collect::<Vec<_>>();
+        let center = Vec3::new(17.1, 0.0, 0.0);
+        let out = chain_instances(&points, &colors, 40, center, &tiers);
+
+        assert_eq!(out.len(), 11);
+        assert_eq!(out[0].residue_idx, 40);
+        assert_eq!(out[9].residue_idx, 49);
+        assert!(out[..10]
+            .iter()
+            .all(|c| c.window[0] == tiers.residues.start));
+
+        let blob = out[10];
+        assert_eq!(blob.residue_idx, BLOB_RESIDUE);
+        assert_eq!(blob.window[0], tiers.blobs.start);
+        assert!((blob.center[0] - 17.1).abs() < 1e-4);
+        assert!((blob.color[0] - 0.5).abs() < 1e-6);
+        assert!((blob.color[2] - 0.5).abs() < 1e-6);
+        // n evenly spaced points: Rg^2 = spacing^2 * (n^2 - 1) / 12.
+        let rg = (3.8_f32 * 3.8 * 99.0 / 12.0).sqrt();
+        assert!((blob.center[3] - rg * GYRATION_TO_RADIUS).abs() < 1e-3);
+    }
+
+    #[test]
+    fn tiny_chains_keep_a_visible_blob() {
+        let tiers = tiers();
+        let out = chain_instances(
+            &[Vec3::ZERO, Vec3::X],
+            &[[1.0; 3]; 2],
+            0,
+            Vec3::ZERO,
+            &tiers,
+        );
+        assert_eq!(out[2].center[3], MIN_BLOB_RADIUS);
+        assert!(chain_instances(&[], &[], 0, Vec3::ZERO, &tiers).is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+//! View-frustum culling of backbone chains.
+//!
+//! Each frame the chains in view are queued as indirect draws, one slot
+//! per chain and pass: cartoon tube and ribbon index ranges for the
+//! chains nearer than the cartoon cutoff, coarse stand-ins for those far
+//! enough to show them.
+
+use std::ops::Range;
+
+use super::{coarse, BackboneRenderer, ChainRange};
+use crate::renderer::culling::{ChainCull, IndirectDraws, Slots};
+use crate::renderer::draw_context::DrawBindGroups;
+
+/// Indirect slots of one backbone draw, one slot per visible chain.
+#[derive(Default)]
+pub(crate) struct ChainSlots {
+    tube: Slots,
+    ribbon: Slots,
+    coarse: Slots,
+}
+
+impl BackboneRenderer {
+    /// Queue the chains in view of `cull` for [`Self::draw_culled`].
+    pub(crate) fn plan(
+        &self,
+        indirect: &mut IndirectDraws,
+        cull: &ChainCull,
+    ) -> ChainSlots {
+        self.plan_chains(indirect, &self.chain_ranges, cull)
+    }
+
+    /// Queue the visible chains of `ranges`: cartoon for those nearer
+    /// than the cartoon cutoff, coarse stand-ins for those far enough
+    /// to show them. Each pass's slots are queued together so they form
+    /// one contiguous range.
+    pub(super) fn plan_chains(
+        &self,
+        indirect: &mut IndirectDraws,
+        ranges: &[ChainRange],
+        cull: &ChainCull,
+    ) -> ChainSlots {
+        let visible: Vec<(&ChainRange, f32)> = ranges
+            .iter()
+            .filter(|range| {
+                cull.is_visible(range.bounding_center, range.bounding_radius)
+            })
+            .map(|range| (range, cull.distance(range.bounding_center)))
+            .collect();
+        let sphere = |range: &ChainRange, pad: f32| {
+            cull.world_sphere(
+                range.bounding_center,
+                range.bounding_radius + pad,
+            )
+        };
+        let cartoon: Vec<&ChainRange> = visible
+            .iter()
+            .filter(|(_, distance)| {
+                self.coarse_tiers
+                    .is_none_or(|t| *distance < t.cartoon_cutoff())
+            })
+            .map(|(range, _)| *range)
+            .collect();
+
+        let mut push_indexed = |indices: fn(&ChainRange) -> Range<u32>| {
+            let start = indirect.indexed_len();
+            for range in &cartoon {
+                if !indices(range).is_empty() {
+                    indirect.push_indexed(
+                        indices(range),
+                        cull.copies,
+                        sphere(range, 0.0),
+                    );
+                }
+            }
+            start..indirect.indexed_len()
+        };
+        let tube = push_indexed(ChainRange::tube);
+        let ribbon = push_indexed(ChainRange::ribbon);
+
+        let start = indirect.direct_len();
+        if let Some(tiers) = self.coarse_tiers {
+            for (range, distance) in &visible {
+                if *distance < tiers.residues.start || range.coarse().is_empty()
+                {
+                    continue;
+                }
+                indirect.push_instances(
+                    6 * cull.copies,
+                    range.coarse(),
+                    Some(sphere(range, coarse::RESIDUE_SPHERE_RADIUS)),
+                );
+            }
+        }
+        ChainSlots {
+            tube,
+            ribbon,
+            coarse: start..indirect.direct_len(),
+        }
+    }
+
+    /// Draw the chains queued by [`Self::plan`]; everything when no
+    /// chain ranges are known.
+    pub(crate) fn draw_culled<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        bind_groups: &DrawBindGroups<'a>,
+        indirect: &IndirectDraws,
+        slots: &ChainSlots,
+    ) {
+        let Some(color) = bind_groups.color else {
+            return;
+        };
+        render_pass.set_bind_group(0, bind_groups.camera, &[]);
+        render_pass.set_bind_group(1, bind_groups.lighting, &[]);
+        render_pass.set_bind_group(2, bind_groups.selection, &[]);
+
+        if self.chain_ranges.is_empty() {
+            // No chain range data -- fall back to full draw
+            render_pass.set_bind_group(3, color, &[]);
+            let vb = self.vertex_buffer.buffer();
+            let copies = bind_groups.copies;
+            self.tube_pass.draw_indexed(render_pass, vb, copies);
+            self.ribbon_pass.draw_indexed(render_pass, vb, copies);
+            return;
+        }
+        self.draw_chains(render_pass, indirect, slots, color);
+    }
+
+    /// Draw queued cartoon slots (with `color` at group 3), then coarse
+    /// ones. Groups 0-2 must already be bound.
+    pub(super) fn draw_chains<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        indirect: &IndirectDraws,
+        slots: &ChainSlots,
+        color: &'a wgpu::BindGroup,
+    ) {
+        let vb = self.vertex_buffer.buffer();
+        render_pass.set_bind_group(3, color, &[]);
+        self.tube_pass
+            .draw_slots(render_pass, vb, indirect, &slots.tube);
+        self.ribbon_pass
+            .draw_slots(render_pass, vb, indirect, &slots.ribbon);
+        if !slots.coarse.is_empty() {
+            render_pass.set_bind_group(3, &self.coarse.bind_group, &[]);
+            self.coarse.draw_slots(render_pass, indirect, &slots.coarse);
+        }
+    }
+}
@@ -7,6 +7,7 @@ use glam::Vec3;
 use super::profile::{cap_offset, extrude_cross_section, CrossSectionProfile};
 use super::spline::SplinePoint;
 use super::BackboneVertex;
+use crate::options::ChainLod;
 
 /// Mesh generation parameters that always travel together.
 pub(super) struct MeshParams {
@@ -15,6 +16,16 @@ pub(super) struct MeshParams {
     pub(super) segments_per_residue: usize,
 }
 
+impl MeshParams {
+    /// The detail level these parameters tessellate at.
+    pub(super) fn lod(&self) -> ChainLod {
+        ChainLod {
+            segments_per_residue: self.segments_per_residue,
+            cross_section_verts: self.cross_section_verts,
+        }
+    }
+}
+
 /// Extrude cross-sections and generate partitioned indices + end caps.
 pub(super) fn extrude_and_index(
     frames: &[SplinePoint],
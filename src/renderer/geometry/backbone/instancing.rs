@@ -0,0 +1,198 @@
+//! Rigid entity copies drawn with a prototype's backbone mesh.
+//!
+//! Copies reuse their prototype's chain ranges: every copy of one
+//! prototype is an instance of a single draw, whose camera bind group
+//! holds each copy's transform (its entity's scene copies times the copy
+//! transform) and residue offset. Copies read their residue colors from
+//! a separate storage buffer bound at group 3.
+
+use std::ops::Range;
+
+use glam::{Mat4, Vec3};
+
+use super::culling::ChainSlots;
+use super::BackboneRenderer;
+use crate::renderer::culling::IndirectDraws;
+use crate::renderer::draw_context::DrawBindGroups;
+use crate::renderer::model_copies::PrototypeDraw;
+use crate::renderer::pipeline::prepared::BackboneInstance;
+
+/// Storage buffer + bind group holding instanced copies' colors.
+pub(super) struct InstanceColors {
+    layout: wgpu::BindGroupLayout,
+    pub(super) buffer: wgpu::Buffer,
+    pub(super) bind_group: wgpu::BindGroup,
+    /// Capacity in colors.
+    pub(super) capacity: usize,
+}
+
+impl InstanceColors {
+    pub(super) fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let (buffer, bind_group) = Self::allocate(device, layout, 1);
+        Self {
+            layout: layout.clone(),
+            buffer,
+            bind_group,
+            capacity: 1,
+        }
+    }
+
+    fn allocate(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        capacity: usize,
+    ) -> (wgpu::Buffer, wgpu::BindGroup) {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Backbone Instance Colors"),
+            size: (capacity * size_of::<[f32; 4]>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Backbone Instance Color Bind Group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+        (buffer, bind_group)
+    }
+
+    /// Upload `colors`, zero-filling the rest of the buffer so stale
+    /// entries read as "no color".
+    fn write(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        colors: &[[f32; 4]],
+    ) {
+        if colors.len() > self.capacity {
+            self.capacity = colors.len().next_power_of_two();
+            let (buffer, bind_group) =
+                Self::allocate(device, &self.layout, self.capacity);
+            self.buffer = buffer;
+            self.bind_group = bind_group;
+        }
+        let mut padded = colors.to_vec();
+        padded.resize(self.capacity, [0.0; 4]);
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&padded));
+    }
+}
+
+impl BackboneRenderer {
+    /// Queue the chains of every prototype of rigid entity copies for
+    /// [`Self::draw_instances`].
+    pub(crate) fn plan_instances(
+        &self,
+        indirect: &mut IndirectDraws,
+        draws: &[PrototypeDraw<'_>],
+    ) -> Vec<ChainSlots> {
+        draws
+            .iter()
+            .map(|draw| {
+                self.chain_ranges
+                    .get(draw.chains.clone())
+                    .map_or_else(ChainSlots::default, |ranges| {
+                        self.plan_chains(indirect, ranges, &draw.cull)
+                    })
+            })
+            .collect()
+    }
+
+    /// Draw the rigid entity copies queued by [`Self::plan_instances`]:
+    /// each prototype's chains once, with an instance per copy read
+    /// from its camera bind group's model copies. `slots` is parallel
+    /// with `draws`.
+    pub(crate) fn draw_instances<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        bind_groups: &DrawBindGroups<'a>,
+        indirect: &IndirectDraws,
+        draws: &[PrototypeDraw<'a>],
+        slots: &[ChainSlots],
+    ) {
+        if draws.is_empty() {
+            return;
+        }
+        render_pass.set_bind_group(1, bind_groups.lighting, &[]);
+        render_pass.set_bind_group(2, bind_groups.selection, &[]);
+
+        for (draw, slots) in draws.iter().zip(slots) {
+            render_pass.set_bind_group(0, draw.camera, &[]);
+            self.draw_chains(
+                render_pass,
+                indirect,
+                slots,
+                &self.instance_colors.bind_group,
+            );
+        }
+    }
+
+    /// Upload the per-residue colors of instanced copies (flat residue
+    /// index, alpha 0 where the baked vertex color applies).
+    pub(crate) fn set_instance_colors(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        colors: &[[f32; 4]],
+    ) {
+        self.instance_colors.write(device, queue, colors);
+        self.coarse.bind(device, &self.instance_colors.buffer);
+    }
+
+    /// Distance from `eye` to every chain, the nearest over all places
+    /// it is drawn: each of the scene's model copies `models`, with
+    /// instanced copies counting for their prototype's chains.
+    pub(crate) fn chain_distances(
+        &self,
+        eye: Vec3,
+        models: &[Mat4],
+    ) -> Vec<f32> {
+        let nearest = |center: Vec3| {
+            models
+                .iter()
+                .map(|model| model.transform_point3(center).distance(eye))
+                .fold(f32::INFINITY, f32::min)
+        };
+        let mut distances: Vec<f32> = self
+            .chain_ranges
+            .iter()
+            .map(|r| nearest(r.bounding_center))
+            .collect();
+        for instance in &self.instances {
+            for i in instance.chains.clone() {
+                let (Some(range), Some(d)) =
+                    (self.chain_ranges.get(i), distances.get(i).copied())
+                else {
+                    continue;
+                };
+                let center =
+                    instance.model.transform_point3(range.bounding_center);
+                distances[i] = d.min(nearest(center));
+            }
+        }
+        distances
+    }
+
+    pub(crate) fn instances(&self) -> &[BackboneInstance] {
+        &self.instances
+    }
+
+    /// Tube and ribbon index ranges covering a prototype's span of
+    /// chain ranges (contiguous in the index buffers).
+    pub(crate) fn prototype_index_ranges(
+        &self,
+        chains: Range<usize>,
+    ) -> Option<(Range<u32>, Range<u32>)> {
+        let ranges = self.chain_ranges.get(chains)?;
+        let (first, last) = (ranges.first()?, ranges.last()?);
+        Some((
+            first.tube().start..last.tube().end,
+            first.ribbon().start..last.ribbon().end,
+        ))
+    }
+}
@@ -7,15 +7,21 @@ mod protein;
 use glam::Vec3;
 use molex::SSType;
 use nucleic::generate_na_chain_mesh;
-use protein::generate_protein_chain_mesh;
+use protein::{generate_protein_chain_mesh, patch_protein_chain, WindowPatch};
 
 use super::arrows::apply_sheet_arrows;
+use super::coarse::chain_instances;
 use super::index::MeshParams;
 use super::profile::{
     resolve_na_profile, resolve_profile, CrossSectionProfile,
 };
+use super::remesh::{
+    dirty_span, find_nucleic_acid, find_protein, reuse_chain, ChainHistory,
+    ChainInputs, RemeshWindow,
+};
 use super::BackboneMeshOutput;
-use crate::options::{ChainLod, GeometryOptions};
+use crate::options::{CartoonStyle, ChainLod, GeometryOptions};
+use crate::renderer::entity_topology::ProteinBackboneChain;
 use crate::renderer::geometry::nucleic_acid::NA_DEFAULT_COLOR;
 
 /// Per-chain index range and bounding sphere for frustum culling.
@@ -25,10 +31,14 @@ use crate::renderer::geometry::nucleic_acid::NA_DEFAULT_COLOR;
 /// construction path is [`ChainRange::new`], which asserts the two
 /// invariants every consumer relies on: `start <= end` and triangle
 /// alignment (`% 3 == 0`, since indices come in triples).
+///
+/// `coarse` spans the chain's residue spheres and blob in the coarse
+/// instance buffer; it is empty when coarse LOD is off.
 #[derive(Clone, Debug)]
 pub(crate) struct ChainRange {
     tube: std::ops::Range<u32>,
     ribbon: std::ops::Range<u32>,
+    coarse: std::ops::Range<u32>,
     pub(crate) bounding_center: Vec3,
     pub(crate) bounding_radius: f32,
 }
@@ -57,11 +67,18 @@ impl ChainRange {
         Self {
             tube,
             ribbon,
+            coarse: 0..0,
             bounding_center,
             bounding_radius,
         }
     }
 
+    /// Attach the chain's span of coarse instances.
+    #[must_use]
+    pub(crate) fn with_coarse(self, coarse: std::ops::Range<u32>) -> Self {
+        Self { coarse, ..self }
+    }
+
     /// Half-open index span for the tube (round cross-section) pass.
     pub(crate) fn tube(&self) -> std::ops::Range<u32> {
         self.tube.clone()
@@ -71,21 +88,43 @@ impl ChainRange {
     pub(crate) fn ribbon(&self) -> std::ops::Range<u32> {
         self.ribbon.clone()
     }
+
+    /// Half-open instance span in the coarse pass.
+    pub(crate) fn coarse(&self) -> std::ops::Range<u32> {
+        self.coarse.clone()
+    }
 }
 
 /// Generate unified backbone mesh from protein and nucleic acid chains.
+///
+/// `per_residue_radii` scales each protein residue's cross-section when
+/// the cartoon style is [`CartoonStyle::Putty`]; other styles ignore it.
+///
+/// With `prev`, the same chains' previous mesh, unchanged chains are
+/// copied over and locally edited protein chains are patched (see
+/// [`super::remesh`]); the output's `dirty` spans record what differs.
 pub(crate) fn generate_mesh_colored(
-    protein: &[crate::renderer::entity_topology::ProteinBackboneChain],
+    protein: &[ProteinBackboneChain],
     na: &[crate::renderer::entity_topology::NaBackboneChain],
     ss_override: Option<&[SSType]>,
     per_residue_colors: Option<&[[f32; 3]]>,
+    per_residue_radii: Option<&[f32]>,
     geo: &GeometryOptions,
     per_chain_lod: Option<&[ChainLod]>,
     na_residue_colors: Option<&[[f32; 3]]>,
     na_seeds: Option<&[Option<Vec3>]>,
     na_guide_dirs: Option<&[Vec3]>,
+    prev: Option<&BackboneMeshOutput>,
 ) -> BackboneMeshOutput {
     let mut out = BackboneMeshOutput::default();
+    let putty =
+        per_residue_radii.filter(|_| geo.cartoon_style == CartoonStyle::Putty);
+    let putty_scale = |residue: usize| {
+        putty.and_then(|r| r.get(residue)).copied().unwrap_or(1.0)
+    };
+    // Chains at `ChainLod::is_coarse` detail get no cartoon triangles,
+    // only their coarse stand-ins.
+    let coarse_tiers = geo.coarse_tiers();
 
     // Protein block. The color slice is whole-assembly-indexed, so it
     // keys off `global_residue_idx`; `residue_offset` is unused here.
@@ -96,7 +135,7 @@ pub(crate) fn generate_mesh_colored(
         &mut out,
         0,
         |atoms| atoms.ca().len(),
-        |atoms, _chain_idx, global_residue_idx, _residue_offset, params| {
+        |atoms, chain_idx, global_residue_idx, _residue_offset, params| {
             let n_residues = atoms.ca().len();
             let chain_slice = ss_override.and_then(|o| {
                 let start = global_residue_idx as usize;
@@ -120,12 +159,16 @@ pub(crate) fn generate_mesh_colored(
                             c.get(global_residue_idx as usize + i).copied()
                         })
                         .unwrap_or_else(|| ss_types[i].color());
-                    resolve_profile(
+                    let mut profile = resolve_profile(
                         ss_types[i],
                         global_residue_idx + i as u32,
                         color,
                         geo,
-                    )
+                    );
+                    let scale = putty_scale(global_residue_idx as usize + i);
+                    profile.width *= scale;
+                    profile.thickness *= scale;
+                    profile
                 })
                 .collect();
 
@@ -135,7 +178,8 @@ pub(crate) fn generate_mesh_colored(
 
             // Widest the extruded ribbon/tube can sit off the CA spline:
             // the largest configured half-width/thickness, scaled by the
-            // x1.5 sheet-arrow shoulder, plus Catmull-Rom overshoot.
+            // x1.5 sheet-arrow shoulder and the chain's largest putty
+            // scale, plus Catmull-Rom overshoot.
             let max_extent = geo
                 .sheet_width
                 .max(geo.helix_width)
@@ -143,19 +187,34 @@ pub(crate) fn generate_mesh_colored(
                 .max(geo.sheet_thickness)
                 .max(geo.helix_thickness)
                 .max(geo.coil_thickness)
-                * 1.5;
+                * 1.5
+                * (0..n_residues)
+                    .map(|i| putty_scale(global_residue_idx as usize + i))
+                    .fold(1.0, f32::max);
             let (center, radius) = bounding_sphere(
                 atoms.ca(),
                 max_extent + SPLINE_OVERSHOOT_SLACK,
             );
 
-            let chain_mesh = generate_protein_chain_mesh(
+            let colors = profile_colors(&profiles);
+            let mut chain_mesh = protein_chain(
+                prev,
+                chain_idx,
                 atoms,
-                &ss_types,
-                &profiles,
+                ss_types,
+                profiles,
                 global_residue_idx,
                 params,
             );
+            if let Some(tiers) = &coarse_tiers {
+                chain_mesh.coarse = chain_instances(
+                    atoms.ca(),
+                    &colors,
+                    global_residue_idx,
+                    center,
+                    tiers,
+                );
+            }
             (chain_mesh, center, radius)
         },
     );
@@ -205,13 +264,24 @@ pub(crate) fn generate_mesh_colored(
                     g.get(residue_offset..residue_offset + n_residues)
                 })
                 .unwrap_or(&[]);
-            let chain_mesh = generate_na_chain_mesh(
-                points,
-                &profiles,
-                params,
+            let colors = profile_colors(&profiles);
+            let inputs = ChainInputs::NucleicAcid {
+                index: chain_idx,
+                points: points.to_vec(),
+                profiles,
                 seed,
-                chain_guides,
-            );
+                guides: chain_guides.to_vec(),
+            };
+            let mut chain_mesh = na_chain(prev, inputs, params);
+            if let Some(tiers) = &coarse_tiers {
+                chain_mesh.coarse = chain_instances(
+                    points,
+                    &colors,
+                    global_residue_idx,
+                    center,
+                    tiers,
+                );
+            }
             (chain_mesh, center, radius)
         },
     );
@@ -289,6 +359,174 @@ fn process_chains<C>(
     global_residue_idx
 }
 
+/// Tessellate protein chain `index`, reusing its mesh in `prev` when no
+/// residue moved and patching only the moved window when a few did.
+fn protein_chain(
+    prev: Option<&BackboneMeshOutput>,
+    index: usize,
+    atoms: &ProteinBackboneChain,
+    ss_types: Vec<SSType>,
+    profiles: Vec<CrossSectionProfile>,
+    global_residue_base: u32,
+    params: &MeshParams,
+) -> BackboneMeshOutput {
+    let lod = params.lod();
+    let cached = prev.and_then(|prev| {
+        let slot = find_protein(prev, index)?;
+        let history = &prev.history[slot];
+        let ChainInputs::Protein {
+            atoms: old_atoms,
+            ss_types: old_ss,
+            profiles: old_profiles,
+            frames,
+            ..
+        } = &history.inputs
+        else {
+            return None;
+        };
+        let same = history.lod == lod
+            && *old_ss == ss_types
+            && *old_profiles == profiles
+            && old_atoms.residue_count() == atoms.residue_count();
+        same.then_some((prev, slot, old_atoms, frames))
+    });
+    if let Some((prev, slot, old_atoms, frames)) = cached {
+        let Some(dirty) = dirty_span(old_atoms, atoms) else {
+            return reuse_chain(prev, slot, params.base_vertex)
+                .unwrap_or_default();
+        };
+        let window = RemeshWindow::new(dirty, &ss_types);
+        if let Some(mut chain) = reuse_chain(prev, slot, params.base_vertex) {
+            let patch = patch_protein_chain(
+                atoms,
+                &ss_types,
+                &profiles,
+                global_residue_base,
+                params,
+                &window,
+                frames,
+                &chain.vertices,
+            );
+            if let Some(patch) = patch {
+                apply_window_patch(&mut chain, patch, atoms, params);
+                return chain;
+            }
+        }
+    }
+
+    if params.segments_per_residue == 0 {
+        return BackboneMeshOutput::default();
+    }
+    let (mut chain, frames) = generate_protein_chain_mesh(
+        atoms,
+        &ss_types,
+        &profiles,
+        global_residue_base,
+        params,
+    );
+    let inputs = ChainInputs::Protein {
+        index,
+        atoms: atoms.clone(),
+        ss_types,
+        profiles,
+        frames,
+    };
+    chain.history = vec![ChainHistory::new(inputs, lod, &chain)];
+    chain.dirty.push(0..chain.vertices.len() as u32);
+    chain
+}
+
+/// Write a re-tessellated window into a reused chain mesh and its
+/// history.
+fn apply_window_patch(
+    chain: &mut BackboneMeshOutput,
+    patch: WindowPatch,
+    atoms: &ProteinBackboneChain,
+    params: &MeshParams,
+) {
+    let csv = params.cross_section_verts;
+    let vertices = patch.rings.start * csv..patch.rings.end * csv;
+    chain.vertices[vertices.clone()].copy_from_slice(&patch.vertices);
+    chain.dirty.push(vertices.start as u32..vertices.end as u32);
+
+    chain
+        .sheet_offsets
+        .retain(|so| !patch.residues.contains(&so.residue_idx));
+    chain.sheet_offsets.extend(patch.sheet_offsets);
+    chain.sheet_offsets.sort_by_key(|so| so.residue_idx);
+
+    for history in &mut chain.history {
+        if let ChainInputs::Protein {
+            atoms: old_atoms,
+            frames,
+            ..
+        } = &mut history.inputs
+        {
+            history.sheet_offsets = 0..chain.sheet_offsets.len();
+            old_atoms.clone_from(atoms);
+            frames.rmf[patch.rings.clone()].copy_from_slice(&patch.frames.rmf);
+            frames.normals[patch.rings.clone()]
+                .copy_from_slice(&patch.frames.normals);
+        }
+    }
+}
+
+/// Tessellate a nucleic-acid chain, reusing its mesh in `prev` when
+/// none of its inputs changed.
+fn na_chain(
+    prev: Option<&BackboneMeshOutput>,
+    inputs: ChainInputs,
+    params: &MeshParams,
+) -> BackboneMeshOutput {
+    let ChainInputs::NucleicAcid {
+        index,
+        points,
+        profiles,
+        seed,
+        guides,
+    } = &inputs
+    else {
+        return BackboneMeshOutput::default();
+    };
+    let lod = params.lod();
+    let reused = prev.and_then(|prev| {
+        let slot = find_nucleic_acid(prev, *index)?;
+        let history = &prev.history[slot];
+        let ChainInputs::NucleicAcid {
+            points: old_points,
+            profiles: old_profiles,
+            seed: old_seed,
+            guides: old_guides,
+            ..
+        } = &history.inputs
+        else {
+            return None;
+        };
+        let same = history.lod == lod
+            && old_points == points
+            && old_profiles == profiles
+            && old_seed == seed
+            && old_guides == guides;
+        same.then(|| reuse_chain(prev, slot, params.base_vertex))
+            .flatten()
+    });
+    if let Some(chain) = reused {
+        return chain;
+    }
+    if params.segments_per_residue == 0 {
+        return BackboneMeshOutput::default();
+    }
+    let mut chain =
+        generate_na_chain_mesh(points, profiles, params, *seed, guides);
+    chain.history = vec![ChainHistory::new(inputs, lod, &chain)];
+    chain.dirty.push(0..chain.vertices.len() as u32);
+    chain
+}
+
+fn profile_colors(profiles: &[CrossSectionProfile]) -> Vec<[f32; 3]> {
+    profiles.iter().map(|p| p.color).collect()
+}
+
 /// Catmull-Rom interpolation can bow outside the CA control hull at
 /// sharp turns; this bounds that overshoot for the culling sphere so a
 /// chain isn't culled while its extruded curve is still on-screen.
@@ -378,4 +616,114 @@ mod tests {
         );
         assert_eq!(after_na, 12);
     }
+
+    fn wavy_chain(residues: usize, bump: Option<(usize, Vec3)>) -> Vec<Vec3> {
+        let mut positions = Vec::with_capacity(residues * 4);
+        for i in 0..residues {
+            let t = i as f32;
+            let ca = Vec3::new(3.8 * t, (t * 0.6).sin(), (t * 0.6).cos());
+            let shift = bump
+                .filter(|&(at, _)| at == i)
+                .map_or(Vec3::ZERO, |(_, by)| by);
+            for offset in [
+                Vec3::new(-1.2, 0.6, 0.0),
+                Vec3::ZERO,
+                Vec3::new(1.3, -0.5, 0.2),
+                Vec3::new(1.6, -1.6, 0.4),
+            ] {
+                positions.push(ca + offset + shift);
+            }
+        }
+        positions
+    }
+
+    fn bytes(vertices: &[super::super::BackboneVertex]) -> &[u8] {
+        bytemuck::cast_slice(vertices)
+    }
+
+    fn all_dirty(mesh: &BackboneMeshOutput) -> bool {
+        mesh.dirty.len() == 1
+            && mesh.dirty[0] == (0..mesh.vertices.len() as u32)
+    }
+
+    fn mesh(
+        positions: &[Vec3],
+        prev: Option<&BackboneMeshOutput>,
+    ) -> BackboneMeshOutput {
+        let residues = positions.len() / 4;
+        let layout = crate::renderer::entity_topology::ProteinBackboneIndices {
+            n: (0..residues).map(|r| r * 4).collect(),
+            ca: (0..residues).map(|r| r * 4 + 1).collect(),
+            c: (0..residues).map(|r| r * 4 + 2).collect(),
+            o: (0..residues).map(|r| r * 4 + 3).collect(),
+        };
+        generate_mesh_colored(
+            &[layout.resolve(positions)],
+            &[],
+            None,
+            None,
+            None,
+            &GeometryOptions::default(),
+            None,
+            None,
+            None,
+            None,
+            prev,
+        )
+    }
+
+    #[test]
+    fn moving_one_residue_patches_only_its_window() {
+        let original = mesh(&wavy_chain(40, None), None);
+        assert!(all_dirty(&original));
+
+        let same = mesh(&wavy_chain(40, None), Some(&original));
+        assert!(same.dirty.is_empty());
+        assert!(bytes(&same.vertices) == bytes(&original.vertices));
+
+        let bumped_positions =
+            wavy_chain(40, Some((20, Vec3::new(0.0, 0.3, 0.0))));
+        let bumped = mesh(&bumped_positions, Some(&original));
+        assert_eq!(bumped.dirty.len(), 1);
+        let dirty =
+            bumped.dirty[0].start as usize..bumped.dirty[0].end as usize;
+        assert!(!dirty.is_empty() && dirty.len() < original.vertices.len() / 2);
+        assert_eq!(bumped.tube_indices, original.tube_indices);
+        assert_eq!(bumped.ribbon_indices, original.ribbon_indices);
+        assert!(
+            bytes(&bumped.vertices[..dirty.start])
+                == bytes(&original.vertices[..dirty.start])
+        );
+        assert!(
+            bytes(&bumped.vertices[dirty.end..])
+                == bytes(&original.vertices[dirty.end..])
+        );
+        let moved = bumped.vertices[dirty.clone()]
+            .iter()
+            .zip(&original.vertices[dirty])
+            .any(|(a, b)| a.position != b.position);
+        assert!(moved);
+
+        // Moving back lands on the original mesh.
+        let restored = mesh(&wavy_chain(40, None), Some(&bumped));
+        let worst = restored
+            .vertices
+            .iter()
+            .zip(&original.vertices)
+            .map(|(a, b)| {
+                Vec3::from(a.position).distance(Vec3::from(b.position))
+            })
+            .fold(0.0_f32, f32::max);
+        assert!(worst < 1e-3, "restored mesh is off by {worst}");
+    }
+
+    #[test]
+    fn edits_at_a_chain_end_rebuild_the_chain() {
+        let original = mesh(&wavy_chain(40, None), None);
+        let bumped = mesh(
+            &wavy_chain(40, Some((1, Vec3::new(0.0, 0.3, 0.0)))),
+            Some(&original),
+        );
+        assert!(all_dirty(&bumped));
+    }
 }
@@ -1,103 +1,272 @@
 //! Per-chain protein backbone mesh: SS-aware spline, sheet geometry,
 //! and RMF/radial/sheet normal blending.
 
+use std::ops::Range;
+
 use glam::Vec3;
 
 use super::super::curve::{cubic_bspline, sliding_window_centroids};
 use super::super::index::{extrude_and_index, MeshParams};
 use super::super::path::{
     compute_sheet_geometry, interpolate_per_residue_normals, SheetGeometry,
+    SheetOffset,
+};
+use super::super::profile::{
+    extrude_cross_section, interpolate_profiles, CrossSectionProfile,
+};
+use super::super::remesh::{
+    absorb_twist, rings_match, ChainFrames, RemeshWindow,
 };
-use super::super::profile::{interpolate_profiles, CrossSectionProfile};
 use super::super::spline::{
     build_traces, helix_aware_spline, rmf_frames, SplinePoint,
 };
-use super::super::BackboneMeshOutput;
+use super::super::{BackboneMeshOutput, BackboneVertex};
+use crate::renderer::entity_topology::ProteinBackboneChain;
 use crate::util::geom::central_difference_tangents;
 
+/// A protein chain's spline samples and everything sampled along them,
+/// ready for framing.
+struct ChainSpline {
+    /// First residue's peptide-plane normal, the chain-roll seed.
+    seed: Option<Vec3>,
+    sheet_offsets: Vec<SheetOffset>,
+    points: Vec<Vec3>,
+    tangents: Vec<Vec3>,
+    helix_centers: Vec<Vec3>,
+    sheet_normals: Vec<Vec3>,
+    profiles: Vec<CrossSectionProfile>,
+}
+
+impl ChainSpline {
+    /// Spline a chain, or `None` when it is too short to tessellate.
+    fn new(
+        atoms: &ProteinBackboneChain,
+        ss_types: &[molex::SSType],
+        profiles: &[CrossSectionProfile],
+        global_residue_base: u32,
+        spr: usize,
+    ) -> Option<Self> {
+        let n = atoms.ca().len();
+        if n < 2 {
+            return None;
+        }
+
+        let SheetGeometry {
+            flat_ca,
+            normals: sheet_normals,
+            offsets: sheet_offsets,
+        } = compute_sheet_geometry(atoms, ss_types, global_residue_base);
+
+        let points = helix_aware_spline(&flat_ca, ss_types, spr);
+        let total = points.len();
+        if total < 2 {
+            return None;
+        }
+
+        let helix_centers = sliding_window_centroids(atoms.ca());
+        Some(Self {
+            seed: sheet_normals.first().copied(),
+            sheet_offsets,
+            tangents: central_difference_tangents(&points),
+            helix_centers: cubic_bspline(&helix_centers, spr),
+            sheet_normals: interpolate_per_residue_normals(
+                &sheet_normals,
+                total,
+                n,
+            ),
+            profiles: interpolate_profiles(profiles, total, n),
+            points,
+        })
+    }
+
+    /// Final frames of samples `samples`, from rotation-minimizing
+    /// `rmf` frames over the same samples.
+    fn final_frames(
+        &self,
+        rmf: &[SplinePoint],
+        samples: Range<usize>,
+        hemisphere: Option<Vec3>,
+    ) -> Vec<SplinePoint> {
+        compute_final_frames(
+            rmf,
+            &self.helix_centers[samples.clone()],
+            &self.sheet_normals[samples.clone()],
+            &self.profiles[samples],
+            hemisphere,
+        )
+    }
+}
+
 /// Generate mesh for a single protein chain (with SS detection, sheet
 /// geometry, and RMF/radial/sheet normal blending). Takes the SoA
 /// backbone-atom view directly from the topology -- no interleaved
 /// stride shuffling.
+///
+/// Also returns the chain's per-sample frames, which
+/// [`patch_protein_chain`] continues from.
 pub(super) fn generate_protein_chain_mesh(
-    atoms: &crate::renderer::entity_topology::ProteinBackboneChain,
+    atoms: &ProteinBackboneChain,
     ss_types: &[molex::SSType],
     profiles: &[CrossSectionProfile],
     global_residue_base: u32,
     params: &MeshParams,
-) -> BackboneMeshOutput {
+) -> (BackboneMeshOutput, ChainFrames) {
     let n = atoms.ca().len();
-    if n < 2 {
-        return BackboneMeshOutput::default();
-    }
-
-    let SheetGeometry {
-        flat_ca,
-        normals: sheet_normals,
-        offsets: sheet_offsets,
-    } = compute_sheet_geometry(atoms, ss_types, global_residue_base);
-
-    let spr = params.segments_per_residue;
-    let spline_points = helix_aware_spline(&flat_ca, ss_types, spr);
-    let total = spline_points.len();
-    if total < 2 {
-        return BackboneMeshOutput::default();
-    }
-
-    let tangents = central_difference_tangents(&spline_points);
-
-    let helix_centers = sliding_window_centroids(atoms.ca());
-    let spline_helix_centers = cubic_bspline(&helix_centers, spr);
-
-    let traces = build_traces(&spline_points, &tangents);
+    let Some(spline) = ChainSpline::new(
+        atoms,
+        ss_types,
+        profiles,
+        global_residue_base,
+        params.segments_per_residue,
+    ) else {
+        return (BackboneMeshOutput::default(), ChainFrames::default());
+    };
+    let total = spline.points.len();
+
+    let traces = build_traces(&spline.points, &spline.tangents);
     // Seed the RMF roll from the first residue's peptide-plane normal so
     // the whole chain's roll is fixed by backbone geometry rather than a
     // world axis. `compute_rmf` projects this perpendicular to the first
     // tangent and falls back to an axis only if it is zero/absent.
-    let frames = rmf_frames(&traces, sheet_normals.first().copied());
-
-    let spline_sheet_normals =
-        interpolate_per_residue_normals(&sheet_normals, total, n);
-    let spline_profiles = interpolate_profiles(profiles, total, n);
-
-    let final_frames = compute_final_frames(
-        &frames,
-        &spline_helix_centers,
-        &spline_sheet_normals,
-        &spline_profiles,
-    );
+    let frames = rmf_frames(&traces, spline.seed);
+    let final_frames = spline.final_frames(&frames, 0..total, None);
 
     if super::super::sheet_trace::enabled() {
         super::super::sheet_trace::trace_final_frames(
             global_residue_base,
             n,
-            &tangents,
+            &spline.tangents,
             &frames,
-            &spline_sheet_normals,
+            &spline.sheet_normals,
             &final_frames,
-            &spline_profiles,
+            &spline.profiles,
         );
     }
 
     let (verts, tube_inds, ribbon_inds) =
-        extrude_and_index(&final_frames, &spline_profiles, params);
-
-    BackboneMeshOutput {
-        vertices: verts,
-        tube_indices: tube_inds,
-        ribbon_indices: ribbon_inds,
-        sheet_offsets,
-        ..Default::default()
+        extrude_and_index(&final_frames, &spline.profiles, params);
+
+    (
+        BackboneMeshOutput {
+            vertices: verts,
+            tube_indices: tube_inds,
+            ribbon_indices: ribbon_inds,
+            sheet_offsets: spline.sheet_offsets,
+            ..Default::default()
+        },
+        ChainFrames::from_frames(&frames, &final_frames),
+    )
+}
+
+/// Re-tessellated rings of a chain window.
+pub(super) struct WindowPatch {
+    /// Spline samples (ring indices) rewritten.
+    pub(super) rings: Range<usize>,
+    /// `rings.len() * cross_section_verts` ring vertices.
+    pub(super) vertices: Vec<BackboneVertex>,
+    /// Frames of the rewritten samples.
+    pub(super) frames: ChainFrames,
+    /// Global indices of the window's write residues.
+    pub(super) residues: Range<u32>,
+    /// Sheet offsets of those residues.
+    pub(super) sheet_offsets: Vec<SheetOffset>,
+}
+
+/// Re-tessellate the write window of a protein chain whose previous
+/// tessellation had per-sample `prev_frames` and ring vertices
+/// `prev_vertices` (same LOD, same secondary structure).
+///
+/// Returns `None` when the window reaches a chain end or its boundary
+/// rings do not line up with the cached ones; the caller then rebuilds
+/// the whole chain.
+pub(super) fn patch_protein_chain(
+    atoms: &ProteinBackboneChain,
+    ss_types: &[molex::SSType],
+    profiles: &[CrossSectionProfile],
+    global_residue_base: u32,
+    params: &MeshParams,
+    window: &RemeshWindow,
+    prev_frames: &ChainFrames,
+    prev_vertices: &[BackboneVertex],
+) -> Option<WindowPatch> {
+    let spr = params.segments_per_residue;
+    let csv = params.cross_section_verts;
+    let total = prev_frames.rmf.len();
+    if window.touches_end(atoms.residue_count()) || spr == 0 {
+        return None;
+    }
+    let generate = window.generate.clone();
+    let spline = ChainSpline::new(
+        &atoms.slice(generate.clone()),
+        ss_types.get(generate.clone())?,
+        profiles.get(generate.clone())?,
+        global_residue_base + generate.start as u32,
+        spr,
+    )?;
+
+    // Rewritten samples, plus the unwritten boundary sample on each
+    // side (chain sample indices), and their window-local offset.
+    let rings = window.write.start * spr..window.write.end * spr;
+    let (before, after) = (rings.start - 1, rings.end);
+    let local = before.checked_sub(generate.start * spr)?;
+    let samples = local..local + (after - before) + 1;
+    if after >= total || samples.end > spline.points.len() {
+        return None;
     }
+
+    let traces = build_traces(
+        &spline.points[samples.clone()],
+        &spline.tangents[samples.clone()],
+    );
+    let mut frames = rmf_frames(&traces, Some(prev_frames.rmf[before]));
+    absorb_twist(&mut frames, prev_frames.rmf[after]);
+    let final_frames = spline.final_frames(
+        &frames,
+        samples.clone(),
+        Some(prev_frames.normals[before]),
+    );
+
+    let mut vertices = Vec::with_capacity((samples.len()) * csv);
+    for (frame, profile) in final_frames.iter().zip(&spline.profiles[samples]) {
+        extrude_cross_section(frame, profile, csv, &mut vertices);
+    }
+    let ring = |i: usize| i * csv..(i + 1) * csv;
+    let last = final_frames.len() - 1;
+    if !rings_match(&vertices[ring(0)], prev_vertices.get(ring(before))?)
+        || !rings_match(&vertices[ring(last)], prev_vertices.get(ring(after))?)
+    {
+        return None;
+    }
+
+    let write = global_residue_base + window.write.start as u32
+        ..global_residue_base + window.write.end as u32;
+    Some(WindowPatch {
+        rings,
+        vertices: vertices[csv..last * csv].to_vec(),
+        frames: ChainFrames::from_frames(
+            &frames[1..last],
+            &final_frames[1..last],
+        ),
+        sheet_offsets: spline
+            .sheet_offsets
+            .into_iter()
+            .filter(|so| write.contains(&so.residue_idx))
+            .collect(),
+        residues: write,
+    })
 }
 
 // ==================== NORMAL BLENDING (protein only) ====================
 
+/// Blend RMF, helix-radial and sheet normals into each sample's final
+/// frame. `hemisphere` is the normal the first frame is aligned with,
+/// when continuing a chain from a cached frame.
 fn compute_final_frames(
     rmf_frames: &[SplinePoint],
     helix_centers: &[Vec3],
     sheet_normals: &[Vec3],
     profiles: &[CrossSectionProfile],
+    hemisphere: Option<Vec3>,
 ) -> Vec<SplinePoint> {
     let total_spline = rmf_frames.len();
     let mut result: Vec<SplinePoint> = Vec::with_capacity(total_spline);
@@ -185,8 +354,9 @@ fn compute_final_frames(
         // geometrically free, so force each frame into the previous
         // frame's hemisphere: consecutive samples are densely spaced, so
         // a sign opposition between neighbors is always spurious.
-        let normal = match result.last() {
-            Some(prev) if normal.dot(prev.normal) < 0.0 => -normal,
+        let prev = result.last().map(|f| f.normal).or(hemisphere);
+        let normal = match prev {
+            Some(prev) if normal.dot(prev) < 0.0 => -normal,
             _ => normal,
         };
 
@@ -248,6 +418,7 @@ mod tests {
             &helix_centers,
             &sheet_normals,
             &profiles,
+            None,
         );
 
         for i in 0..result.len() - 1 {
@@ -296,6 +467,7 @@ mod tests {
             &helix_centers,
             &sheet_normals,
             &profiles,
+            None,
         );
 
         for i in 1..result.len() {
@@ -334,6 +506,7 @@ mod tests {
             &helix_centers,
             &sheet_normals,
             &profiles,
+            None,
         );
 
         assert!(
@@ -5,15 +5,22 @@
 //! - **Ribbon pass** (no culling): flat cross-sections
 
 pub(crate) mod arrows;
+pub(crate) mod coarse;
+mod culling;
 pub(crate) mod curve;
 pub(crate) mod index;
+mod instancing;
 pub(crate) mod mesh;
 pub(crate) mod path;
 pub(crate) mod profile;
+pub(crate) mod remesh;
 pub(crate) mod sheet_trace;
 pub(crate) mod spline;
 
+use coarse::{CoarseInstance, CoarsePass};
+pub(crate) use culling::ChainSlots;
 use glam::Vec3;
+use instancing::InstanceColors;
 pub(crate) use mesh::ChainRange;
 use molex::SSType;
 pub(crate) use path::SheetOffset;
@@ -26,6 +33,14 @@ pub(crate) struct BackboneMeshOutput {
     pub(crate) ribbon_indices: Vec<u32>,
     pub(crate) sheet_offsets: Vec<SheetOffset>,
     pub(crate) chain_ranges: Vec<ChainRange>,
+    /// Coarse stand-ins of every chain, spanned by `chain_ranges`.
+    pub(crate) coarse: Vec<CoarseInstance>,
+    /// What each chain was built from, parallel with `chain_ranges`;
+    /// the next frame's incremental remesh diffs against it.
+    pub(crate) history: Vec<remesh::ChainHistory>,
+    /// Vertex spans that differ from the previous mesh the generator
+    /// was given (every vertex without one).
+    pub(crate) dirty: Vec<Range<u32>>,
 }
 
 impl BackboneMeshOutput {
@@ -38,33 +53,55 @@ impl BackboneMeshOutput {
     ) {
         let tube_index_start = self.tube_indices.len() as u32;
         let ribbon_index_start = self.ribbon_indices.len() as u32;
-
+        let coarse_start = self.coarse.len() as u32;
+        let vertex_start = self.vertices.len() as u32;
+        let sheet_start = self.sheet_offsets.len();
+
+        self.dirty.extend(
+            chain
+                .dirty
+                .iter()
+                .map(|r| r.start + vertex_start..r.end + vertex_start),
+        );
+        self.history.extend(chain.history.into_iter().map(|h| {
+            remesh::ChainHistory {
+                vertices: h.vertices.start + vertex_start
+                    ..h.vertices.end + vertex_start,
+                sheet_offsets: h.sheet_offsets.start + sheet_start
+                    ..h.sheet_offsets.end + sheet_start,
+                ..h
+            }
+        }));
         self.vertices.extend(chain.vertices);
         self.tube_indices.extend(chain.tube_indices);
         self.ribbon_indices.extend(chain.ribbon_indices);
         self.sheet_offsets.extend(chain.sheet_offsets);
-
-        self.chain_ranges.push(ChainRange::new(
-            tube_index_start..self.tube_indices.len() as u32,
-            ribbon_index_start..self.ribbon_indices.len() as u32,
-            bounding_center,
-            bounding_radius,
-        ));
+        self.coarse.extend(chain.coarse);
+
+        self.chain_ranges.push(
+            ChainRange::new(
+                tube_index_start..self.tube_indices.len() as u32,
+                ribbon_index_start..self.ribbon_indices.len() as u32,
+                bounding_center,
+                bounding_radius,
+            )
+            .with_coarse(coarse_start..self.coarse.len() as u32),
+        );
     }
 }
 
 use std::hash::{Hash, Hasher};
+use std::ops::Range;
 
 use rustc_hash::FxHasher;
 
-use crate::camera::frustum::Frustum;
 use crate::error::VisoError;
 use crate::gpu::dynamic_buffer::DynamicBuffer;
 use crate::gpu::{RenderContext, Shader, ShaderComposer};
-use crate::options::{ChainLod, GeometryOptions};
-use crate::renderer::draw_context::DrawBindGroups;
+use crate::options::{ChainLod, CoarseTiers, GeometryOptions};
 use crate::renderer::entity_topology::{NaBackboneChain, ProteinBackboneChain};
 use crate::renderer::mesh::{create_mesh_pipeline, MeshPass, MeshPipelineDef};
+use crate::renderer::pipeline::prepared::BackboneInstance;
 use crate::util::hash::hash_vec3_slice_summary;
 
 // ==================== VERTEX FORMAT ====================
@@ -128,6 +165,16 @@ pub(crate) struct BackboneRenderer {
     sheet_offsets: Vec<SheetOffset>,
     chain_ranges: Vec<ChainRange>,
     cached_lod_tiers: Vec<u8>,
+    /// Rigid entity copies drawn with a prototype's chain ranges.
+    instances: Vec<BackboneInstance>,
+    /// Per-residue colors of the instanced copies, bound at group 3 for
+    /// instance draws in place of the shared residue color buffer.
+    instance_colors: InstanceColors,
+    /// Residue spheres and chain blobs of far-away chains.
+    coarse: CoarsePass,
+    /// Coarse LOD windows the uploaded mesh was built with; `None`
+    /// draws every chain as cartoon.
+    coarse_tiers: Option<CoarseTiers>,
 }
 
 impl BackboneRenderer {
@@ -165,6 +212,7 @@ impl BackboneRenderer {
                 label: "Backbone Tube",
                 shader: Shader::BackboneTube,
                 cull_mode: Some(wgpu::Face::Back),
+                topology: wgpu::PrimitiveTopology::TriangleList,
                 vertex_layout: vl.clone(),
             },
             mesh_layouts,
@@ -176,12 +224,20 @@ impl BackboneRenderer {
                 label: "Backbone Ribbon",
                 shader: Shader::BackboneTube,
                 cull_mode: None,
+                topology: wgpu::PrimitiveTopology::TriangleList,
                 vertex_layout: vl,
             },
             mesh_layouts,
             shader_composer,
         )?;
 
+        let instance_colors = InstanceColors::new(device, &layouts.color);
+        let coarse = CoarsePass::new(
+            context,
+            layouts,
+            &instance_colors.buffer,
+            shader_composer,
+        )?;
         let tube_pass =
             MeshPass::new(device, "Backbone Tube Index", tube_pipeline, &[]);
         let ribbon_pass = MeshPass::new(
@@ -201,52 +257,13 @@ impl BackboneRenderer {
             sheet_offsets: Vec::new(),
             chain_ranges: Vec::new(),
             cached_lod_tiers: Vec::new(),
+            instances: Vec::new(),
+            instance_colors,
+            coarse,
+            coarse_tiers: None,
         })
     }
 
-    // -- Draw --
-
-    /// Frustum-culled draw: skip chains whose bounding sphere is off-screen.
-    pub(crate) fn draw_culled<'a>(
-        &'a self,
-        render_pass: &mut wgpu::RenderPass<'a>,
-        bind_groups: &DrawBindGroups<'a>,
-        frustum: &Frustum,
-    ) {
-        let Some(color) = bind_groups.color else {
-            return;
-        };
-        render_pass.set_bind_group(0, bind_groups.camera, &[]);
-        render_pass.set_bind_group(1, bind_groups.lighting, &[]);
-        render_pass.set_bind_group(2, bind_groups.selection, &[]);
-        render_pass.set_bind_group(3, color, &[]);
-
-        let vb = self.vertex_buffer.buffer();
-
-        if self.chain_ranges.is_empty() {
-            // No chain range data -- fall back to full draw
-            self.tube_pass.draw_indexed(render_pass, vb);
-            self.ribbon_pass.draw_indexed(render_pass, vb);
-            return;
-        }
-
-        for range in &self.chain_ranges {
-            if !frustum
-                .intersects_sphere(range.bounding_center, range.bounding_radius)
-            {
-                continue;
-            }
-
-            self.tube_pass
-                .draw_indexed_range(render_pass, vb, range.tube());
-            self.ribbon_pass.draw_indexed_range(
-                render_pass,
-                vb,
-                range.ribbon(),
-            );
-        }
-    }
-
     // -- Scene-processor path --
 
     #[allow(clippy::too_many_arguments)]
@@ -261,6 +278,8 @@ impl BackboneRenderer {
         ribbon_index_count: u32,
         sheet_offsets: Vec<SheetOffset>,
         chain_ranges: Vec<ChainRange>,
+        coarse_instances: &[u8],
+        instances: Vec<BackboneInstance>,
         cached_chains: &[ProteinBackboneChain],
         cached_na_chains: &[NaBackboneChain],
     ) {
@@ -283,8 +302,18 @@ impl BackboneRenderer {
             ribbon_indices,
             ribbon_index_count,
         );
+        self.coarse.write(
+            device,
+            queue,
+            coarse_instances,
+            &self.instance_colors.buffer,
+        );
         self.sheet_offsets = sheet_offsets;
         self.chain_ranges = chain_ranges;
+        self.instances = instances;
+        // A full rebuild tessellates every chain at full detail; forget
+        // the per-chain tiers so the next LOD check re-applies them.
+        self.cached_lod_tiers.clear();
         self.cached_chains.clear();
         self.cached_chains.extend_from_slice(cached_chains);
         self.cached_na_chains.clear();
@@ -306,19 +335,38 @@ impl BackboneRenderer {
             combined_hash(&self.cached_chains, &self.cached_na_chains);
     }
 
+    /// Upload an animation frame's backbone. A frame carrying a vertex
+    /// patch only rewrites the changed vertex spans and keeps the
+    /// uploaded indices.
+    ///
+    /// Returns `false` if a patch did not fit the uploaded buffer, in
+    /// which case the vertex buffer no longer matches any frame.
     pub(crate) fn apply_mesh(
         &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         mesh: crate::renderer::pipeline::prepared::BackboneMeshData,
-    ) {
-        if !mesh.vertices.is_empty() {
-            // See `apply_prepared`: a failed buffer write only costs one
-            // stale/blank frame, recovered on the next sync, so the
-            // Result is intentionally not propagated.
-            let _ =
-                self.vertex_buffer
-                    .write_bytes(device, queue, &mesh.vertices);
+    ) -> bool {
+        let mut complete = true;
+        if let Some(patch) = &mesh.vertex_patch {
+            for (offset, bytes) in &patch.writes {
+                complete &=
+                    self.vertex_buffer.write_range(queue, *offset, bytes);
+            }
+        } else {
+            if !mesh.vertices.is_empty() {
+                // See `apply_prepared`: a failed buffer write only costs one
+                // stale/blank frame, recovered on the next sync, so the
+                // Result is intentionally not propagated.
+                let _ = self.vertex_buffer.write_bytes(
+                    device,
+                    queue,
+                    &mesh.vertices,
+                );
+            }
+            // Written even without vertices: when every chain is coarse
+            // the index counts must drop to zero, not keep the last
+            // mesh's.
             self.tube_pass.write_indices_bytes(
                 device,
                 queue,
@@ -332,15 +380,23 @@ impl BackboneRenderer {
                 mesh.ribbon_index_count,
             );
         }
+        self.coarse.write(
+            device,
+            queue,
+            &mesh.coarse_instances,
+            &self.instance_colors.buffer,
+        );
         self.sheet_offsets = mesh.sheet_offsets;
         self.chain_ranges = mesh.chain_ranges;
+        self.instances = mesh.instances;
+        if let Some(colors) = mesh.instance_colors {
+            self.set_instance_colors(device, queue, &colors);
+        }
+        complete
     }
 
     // -- Accessors --
 
-    pub(crate) fn chain_ranges(&self) -> &[ChainRange] {
-        &self.chain_ranges
-    }
     pub(crate) fn cached_lod_tiers(&self) -> &[u8] {
         &self.cached_lod_tiers
     }
@@ -390,6 +446,12 @@ impl BackboneRenderer {
                 self.ribbon_pass.index_buffer_len(),
                 self.ribbon_pass.index_buffer_capacity(),
             ),
+            (
+                "Backbone Instance Colors",
+                self.instance_colors.capacity * size_of::<[f32; 4]>(),
+                self.instance_colors.capacity * size_of::<[f32; 4]>(),
+            ),
+            self.coarse.buffer_info(),
         ]
     }
 
@@ -400,22 +462,26 @@ impl BackboneRenderer {
         na: &[NaBackboneChain],
         ss_override: Option<&[SSType]>,
         per_residue_colors: Option<&[[f32; 3]]>,
+        per_residue_radii: Option<&[f32]>,
         geo: &GeometryOptions,
         per_chain_lod: Option<&[ChainLod]>,
         na_residue_colors: Option<&[[f32; 3]]>,
         na_seeds: Option<&[Option<Vec3>]>,
         na_guide_dirs: Option<&[Vec3]>,
+        prev: Option<&BackboneMeshOutput>,
     ) -> BackboneMeshOutput {
         mesh::generate_mesh_colored(
             protein,
             na,
             ss_override,
             per_residue_colors,
+            per_residue_radii,
             geo,
             per_chain_lod,
             na_residue_colors,
             na_seeds,
             na_guide_dirs,
+            prev,
         )
     }
 }
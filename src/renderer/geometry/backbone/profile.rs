@@ -13,7 +13,7 @@ use crate::options::GeometryOptions;
 // ==================== CROSS-SECTION PROFILE ====================
 
 /// Interpolated per-spline-point geometry parameters.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub(crate) struct CrossSectionProfile {
     pub(crate) width: f32,
     pub(crate) thickness: f32,
@@ -113,6 +113,32 @@ pub(crate) fn resolve_na_profile(
     }
 }
 
+// ==================== PUTTY ====================
+
+/// Bounds on the per-residue putty radius multiplier.
+const PUTTY_SCALE_RANGE: (f32, f32) = (0.25, 4.0);
+
+/// Per-residue putty radius multipliers from a per-residue magnitude
+/// (e.g. trajectory RMSF): each value relative to the mean, clamped to
+/// [`PUTTY_SCALE_RANGE`]. A flat or empty input yields unit scales.
+pub(crate) fn putty_radius_scales(values: &[f32]) -> Vec<f32> {
+    let mean = if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f32>() / values.len() as f32
+    };
+    values
+        .iter()
+        .map(|&v| {
+            if mean < 1e-6 {
+                1.0
+            } else {
+                (v / mean).clamp(PUTTY_SCALE_RANGE.0, PUTTY_SCALE_RANGE.1)
+            }
+        })
+        .collect()
+}
+
 // ==================== PROFILE INTERPOLATION ====================
 
 /// Interpolate per-residue profiles to spline resolution with smooth
@@ -249,6 +275,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn putty_scales_are_relative_to_mean_and_clamped() {
+        let mut values = vec![1.0; 9];
+        values.push(100.0);
+        let s = putty_radius_scales(&values);
+        assert_eq!(s[0], PUTTY_SCALE_RANGE.0);
+        assert_eq!(s[9], PUTTY_SCALE_RANGE.1);
+        let s = putty_radius_scales(&[1.0, 3.0]);
+        assert!((s[0] - 0.5).abs() < 1e-6 && (s[1] - 1.5).abs() < 1e-6);
+        assert!(putty_radius_scales(&[0.0, 0.0]).iter().all(|&v| v == 1.0));
+    }
+
     /// Circular tube: verify normals are radial and center_pos == frame.pos.
     #[test]
     fn circular_tube_normals_are_radial() {
@@ -0,0 +1,349 @@
+//! Incremental remeshing of locally edited backbone chains.
+//!
+//! During interactive editing only a handful of residues move per step.
+//! Rather than re-tessellating every chain on each animation frame, the
+//! generator keeps what each chain was last built from
+//! ([`ChainHistory`]) and diffs the next frame's control atoms against
+//! it:
+//!
+//! - unchanged chains reuse their vertices as-is;
+//! - a protein chain with a moved run of residues re-tessellates only the
+//!   spline segments those residues influence ([`RemeshWindow::write`]),
+//!   splined from a slightly wider run of residues for context
+//!   ([`RemeshWindow::generate`]);
+//! - everything else (LOD or secondary-structure changes, moves near a chain
+//!   end, seams that do not line up) rebuilds the whole chain.
+//!
+//! The window's rotation-minimizing frames start from the cached frame
+//! at the ring just before it and are twisted back onto the cached frame
+//! at the ring just after it, so the roll change an edit introduces
+//! stays inside the window instead of running down the rest of the
+//! chain. Both boundary rings are re-extruded and compared against the
+//! cached vertices; a mismatch beyond [`SEAM_TOLERANCE`] means the
+//! window was too narrow for the edit and the chain is rebuilt whole.
+
+use std::ops::Range;
+
+use glam::{Quat, Vec3};
+use molex::SSType;
+
+use super::path::segment_by_ss;
+use super::profile::CrossSectionProfile;
+use super::spline::SplinePoint;
+use super::{BackboneMeshOutput, BackboneVertex};
+use crate::options::ChainLod;
+use crate::renderer::entity_topology::ProteinBackboneChain;
+
+/// Residues on either side of a moved one whose spline samples can
+/// still change: the dual-Hermite midpoints reach two residues, the
+/// smoothed helix axis (centroid window plus B-spline) four, and the
+/// sample tangents one more sample.
+pub(crate) const SPLINE_PAD: usize = 5;
+
+/// Residues splined past each end of the write window only so the
+/// window's samples see the same neighbors as in the full chain. They
+/// keep the sub-chain's mirrored ghost endpoints out of the write
+/// window.
+pub(crate) const CONTEXT: usize = 5;
+
+/// Largest distance (Å) between a re-extruded boundary ring vertex and
+/// its cached counterpart for the window to count as seamless.
+pub(crate) const SEAM_TOLERANCE: f32 = 0.05;
+
+/// Control-atom displacement (Å) below which a residue counts as
+/// unmoved.
+const MOVE_EPSILON: f32 = 1e-4;
+
+/// Per-spline-sample frame normals of a tessellated protein chain.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ChainFrames {
+    /// Rotation-minimizing frame normal.
+    pub(crate) rmf: Vec<Vec3>,
+    /// Final normal after the helix/sheet blend and hemisphere
+    /// alignment.
+    pub(crate) normals: Vec<Vec3>,
+}
+
+impl ChainFrames {
+    pub(crate) fn from_frames(
+        rmf: &[SplinePoint],
+        normals: &[SplinePoint],
+    ) -> Self {
+        Self {
+            rmf: rmf.iter().map(|f| f.normal).collect(),
+            normals: normals.iter().map(|f| f.normal).collect(),
+        }
+    }
+}
+
+/// Inputs a chain was last tessellated from, keyed by its index within
+/// its polymer block.
+#[derive(Clone)]
+pub(crate) enum ChainInputs {
+    /// A protein chain, patchable window by window.
+    Protein {
+        index: usize,
+        atoms: ProteinBackboneChain,
+        ss_types: Vec<SSType>,
+        profiles: Vec<CrossSectionProfile>,
+        frames: ChainFrames,
+    },
+    /// A nucleic-acid chain, reused whole or rebuilt whole.
+    NucleicAcid {
+        index: usize,
+        points: Vec<Vec3>,
+        profiles: Vec<CrossSectionProfile>,
+        seed: Option<Vec3>,
+        guides: Vec<Vec3>,
+    },
+}
+
+/// What the next frame's incremental remesh needs from one chain's
+/// last tessellation. Parallel with
+/// [`BackboneMeshOutput::chain_ranges`].
+#[derive(Clone)]
+pub(crate) struct ChainHistory {
+    pub(crate) inputs: ChainInputs,
+    pub(crate) lod: ChainLod,
+    /// The chain's vertex span in its mesh.
+    pub(crate) vertices: Range<u32>,
+    /// The chain's span of sheet offsets in its mesh.
+    pub(crate) sheet_offsets: Range<usize>,
+}
+
+impl ChainHistory {
+    /// History of a chain occupying all of its own single-chain mesh.
+    pub(crate) fn new(
+        inputs: ChainInputs,
+        lod: ChainLod,
+        mesh: &BackboneMeshOutput,
+    ) -> Self {
+        Self {
+            inputs,
+            lod,
+            vertices: 0..mesh.vertices.len() as u32,
+            sheet_offsets: 0..mesh.sheet_offsets.len(),
+        }
+    }
+}
+
+/// Index of the chain in `prev` built from protein chain `index`, or
+/// `None` without one.
+pub(crate) fn find_protein(
+    prev: &BackboneMeshOutput,
+    index: usize,
+) -> Option<usize> {
+    prev.history.iter().position(|h| {
+        matches!(h.inputs, ChainInputs::Protein { index: i, .. } if i == index)
+    })
+}
+
+/// Index of the chain in `prev` built from nucleic-acid chain `index`.
+pub(crate) fn find_nucleic_acid(
+    prev: &BackboneMeshOutput,
+    index: usize,
+) -> Option<usize> {
+    prev.history.iter().position(|h| {
+        matches!(
+            h.inputs,
+            ChainInputs::NucleicAcid { index: i, .. } if i == index
+        )
+    })
+}
+
+/// Chain `slot` of `prev` as a single-chain mesh whose vertices start at
+/// `base_vertex`, with its history and no dirty vertices.
+pub(crate) fn reuse_chain(
+    prev: &BackboneMeshOutput,
+    slot: usize,
+    base_vertex: u32,
+) -> Option<BackboneMeshOutput> {
+    let history = prev.history.get(slot)?;
+    let range = prev.chain_ranges.get(slot)?;
+    let old_base = history.vertices.start;
+    let rebase = |idx: &u32| idx - old_base + base_vertex;
+    let vertices = prev
+        .vertices
+        .get(history.vertices.start as usize..history.vertices.end as usize)?
+        .to_vec();
+    let tube_indices = prev
+        .tube_indices
+        .get(range.tube().start as usize..range.tube().end as usize)?
+        .iter()
+        .map(rebase)
+        .collect();
+    let ribbon_indices = prev
+        .ribbon_indices
+        .get(range.ribbon().start as usize..range.ribbon().end as usize)?
+        .iter()
+        .map(rebase)
+        .collect();
+    let sheet_offsets = prev
+        .sheet_offsets
+        .get(history.sheet_offsets.clone())?
+        .to_vec();
+    let mut chain = BackboneMeshOutput {
+        vertices,
+        tube_indices,
+        ribbon_indices,
+        sheet_offsets,
+        ..BackboneMeshOutput::default()
+    };
+    chain.history = vec![ChainHistory::new(
+        history.inputs.clone(),
+        history.lod,
+        &chain,
+    )];
+    Some(chain)
+}
+
+/// The residue span whose control atoms moved from `old` to `new`, or
+/// `None` when nothing moved. Both chains must have the same length.
+pub(crate) fn dirty_span(
+    old: &ProteinBackboneChain,
+    new: &ProteinBackboneChain,
+) -> Option<Range<usize>> {
+    let moved = |i: usize| {
+        old.residue(i)
+            .iter()
+            .zip(new.residue(i))
+            .any(|(a, b)| a.distance_squared(b) > MOVE_EPSILON * MOVE_EPSILON)
+    };
+    let n = new.residue_count();
+    let first = (0..n).find(|&i| moved(i))?;
+    let last = (first..n).rfind(|&i| moved(i))?;
+    Some(first..last + 1)
+}
+
+/// The residues a moved span forces to be re-tessellated, and those
+/// splined to do it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RemeshWindow {
+    /// Residues whose spline samples are rewritten.
+    pub(crate) write: Range<usize>,
+    /// Residues splined to produce them: `write` plus [`CONTEXT`] on
+    /// each side, clamped to the chain.
+    pub(crate) generate: Range<usize>,
+}
+
+impl RemeshWindow {
+    /// Window around the moved residues `dirty` of a chain with
+    /// `ss_types`.
+    ///
+    /// Sheet flattening couples every residue of a strand, so both
+    /// ranges grow to cover any strand they cut into.
+    pub(crate) fn new(dirty: Range<usize>, ss_types: &[SSType]) -> Self {
+        let n = ss_types.len();
+        let write = cover_strands(
+            dirty.start.saturating_sub(SPLINE_PAD)
+                ..(dirty.end + SPLINE_PAD).min(n),
+            ss_types,
+        );
+        let generate = cover_strands(
+            write.start.saturating_sub(CONTEXT)..(write.end + CONTEXT).min(n),
+            ss_types,
+        );
+        Self { write, generate }
+    }
+
+    /// Whether the write window reaches a chain end, where the end caps
+    /// would change too.
+    pub(crate) fn touches_end(&self, residues: usize) -> bool {
+        self.write.start == 0 || self.write.end >= residues
+    }
+}
+
+/// Grow `residues` to cover every sheet segment it overlaps.
+fn cover_strands(residues: Range<usize>, ss_types: &[SSType]) -> Range<usize> {
+    let (start, end) = (residues.start, residues.end);
+    segment_by_ss(ss_types)
+        .iter()
+        .filter(|seg| {
+            seg.ss_type == SSType::Sheet
+                && seg.residues.start < end
+                && start < seg.residues.end
+        })
+        .fold(residues, |r, seg| {
+            r.start.min(seg.residues.start)..r.end.max(seg.residues.end)
+        })
+}
+
+/// Spread a rotation about each tangent over `frames` so the last
+/// frame's normal lands on `target` (projected perpendicular to its
+/// tangent), ramping linearly from no rotation at the first frame.
+pub(crate) fn absorb_twist(frames: &mut [SplinePoint], target: Vec3) {
+    let Some(last) = frames.last() else {
+        return;
+    };
+    let t = last.tangent;
+    let target = (target - t * t.dot(target)).normalize_or_zero();
+    if target == Vec3::ZERO || frames.len() < 2 {
+        return;
+    }
+    let angle = t
+        .dot(last.normal.cross(target))
+        .atan2(last.normal.dot(target));
+    let span = (frames.len() - 1) as f32;
+    for (i, frame) in frames.iter_mut().enumerate() {
+        let turn =
+            Quat::from_axis_angle(frame.tangent, angle * i as f32 / span);
+        frame.normal = (turn * frame.normal).normalize();
+        frame.binormal = frame.tangent.cross(frame.normal).normalize();
+    }
+}
+
+/// Whether two cross-section rings coincide within [`SEAM_TOLERANCE`].
+pub(crate) fn rings_match(a: &[BackboneVertex], b: &[BackboneVertex]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            Vec3::from(a.position).distance(Vec3::from(b.position))
+                <= SEAM_TOLERANCE
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_pad_clamp_and_cover_strands() {
+        let coil = vec![SSType::Coil; 40];
+        let window = RemeshWindow::new(18..20, &coil);
+        assert_eq!(window.write, 13..25);
+        assert_eq!(window.generate, 8..30);
+        assert!(!window.touches_end(40));
+
+        let near_start = RemeshWindow::new(2..3, &coil);
+        assert_eq!(near_start.write, 0..8);
+        assert_eq!(near_start.generate, 0..13);
+        assert!(near_start.touches_end(40));
+
+        // A strand at 24..34 is cut by the padded span and pulled in
+        // whole; the generation window then covers it as well.
+        let mut ss = coil;
+        ss[24..34].fill(SSType::Sheet);
+        let window = RemeshWindow::new(18..20, &ss);
+        assert_eq!(window.write, 13..34);
+        assert_eq!(window.generate, 8..39);
+    }
+
+    #[test]
+    fn twist_is_absorbed_by_the_last_frame() {
+        let mut frames: Vec<SplinePoint> = (0..5)
+            .map(|i| SplinePoint {
+                pos: Vec3::new(0.0, 0.0, i as f32),
+                tangent: Vec3::Z,
+                normal: Vec3::X,
+                binormal: Vec3::Y,
+            })
+            .collect();
+        absorb_twist(&mut frames, Vec3::Y);
+        assert!(frames[0].normal.distance(Vec3::X) < 1e-5);
+        assert!(frames[4].normal.distance(Vec3::Y) < 1e-5);
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        assert!(frames[2].normal.distance(Vec3::new(half, half, 0.0)) < 1e-5);
+        assert!(frames
+            .iter()
+            .all(|f| f.binormal.distance(f.tangent.cross(f.normal)) < 1e-5));
+    }
+}
@@ -14,6 +14,9 @@ use molex::MoleculeType;
 use crate::error::VisoError;
 use crate::gpu::{RenderContext, Shader, ShaderComposer};
 use crate::options::{ColorOptions, DisplayOptions, DrawingMode};
+use crate::renderer::culling::{
+    ChainCull, IndirectDraws, InstanceGroup, Slots,
+};
 use crate::renderer::entity_topology::EntityTopology;
 use crate::renderer::impostor::{
     CapsuleInstance, ImpostorPass, ShaderDef, SphereInstance,
@@ -66,6 +69,10 @@ pub(crate) struct PreparedBallAndStickData<'a> {
     pub(crate) capsule_bytes: &'a [u8],
     /// Number of bond capsule instances.
     pub(crate) capsule_count: u32,
+    /// Culling groups over the spheres.
+    pub(crate) sphere_groups: &'a [InstanceGroup],
+    /// Culling groups over the bond capsules.
+    pub(crate) capsule_groups: &'a [InstanceGroup],
 }
 
 /// Output buffers for instance generation.
@@ -201,16 +208,43 @@ impl BallAndStickRenderer {
             data.capsule_bytes,
             data.capsule_count,
         );
+        self.sphere_pass.set_groups(data.sphere_groups.to_vec());
+        self.bond_pass.set_groups(data.capsule_groups.to_vec());
+    }
+
+    /// Queue the sphere and bond groups in view of `cull`.
+    pub(crate) fn plan(
+        &self,
+        indirect: &mut IndirectDraws,
+        cull: &ChainCull,
+    ) -> [Slots; 2] {
+        [
+            self.sphere_pass.plan(indirect, cull),
+            self.bond_pass.plan(indirect, cull),
+        ]
     }
 
-    /// Draw both spheres and bonds in a single render pass.
+    /// Draw the spheres and bonds queued by [`Self::plan`] in a single
+    /// render pass.
     pub(crate) fn draw<'a>(
         &'a self,
         render_pass: &mut wgpu::RenderPass<'a>,
         bind_groups: &crate::renderer::draw_context::DrawBindGroups<'a>,
+        indirect: &IndirectDraws,
+        slots: &[Slots; 2],
     ) {
-        self.sphere_pass.draw(render_pass, bind_groups);
-        self.bond_pass.draw(render_pass, bind_groups);
+        self.sphere_pass.draw_slots(
+            render_pass,
+            bind_groups,
+            indirect,
+            &slots[0],
+        );
+        self.bond_pass.draw_slots(
+            render_pass,
+            bind_groups,
+            indirect,
+            &slots[1],
+        );
     }
 
     /// Get the sphere instance buffer (visual pass).
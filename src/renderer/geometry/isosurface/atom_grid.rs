@@ -0,0 +1,80 @@
+//! Uniform grid over atom positions for neighbor queries.
+//!
+//! Atoms are bucketed into cubic cells; every atom within one cell
+//! size of a point lies in the 27 cells around it. Used to tag surface
+//! vertices with their nearest atom, to find which dots on an atom
+//! sphere are buried by its neighbors, and for solvent accessibility.
+
+use glam::{IVec3, Vec3};
+use rustc_hash::FxHashMap;
+
+/// Atom indices bucketed by grid cell.
+pub(crate) struct AtomGrid<'a> {
+    positions: &'a [Vec3],
+    cell_size: f32,
+    cells: FxHashMap<IVec3, Vec<u32>>,
+}
+
+impl<'a> AtomGrid<'a> {
+    /// Bucket `positions` into cells of `cell_size` Å.
+    pub(crate) fn new(positions: &'a [Vec3], cell_size: f32) -> Self {
+        let mut grid = Self {
+            positions,
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: FxHashMap::default(),
+        };
+        for (i, &p) in positions.iter().enumerate() {
+            grid.cells
+                .entry(grid.cell_of(p))
+                .or_default()
+                .push(i as u32);
+        }
+        grid
+    }
+
+    fn cell_of(&self, p: Vec3) -> IVec3 {
+        (p / self.cell_size).floor().as_ivec3()
+    }
+
+    /// Indices of the atoms in the 27 cells around `p`: a superset of
+    /// every atom within one cell size of it.
+    pub(crate) fn neighbors(
+        &self,
+        p: Vec3,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let center = self.cell_of(p);
+        (-1..=1)
+            .flat_map(|x| {
+                (-1..=1).flat_map(move |y| {
+                    (-1..=1).map(move |z| IVec3::new(x, y, z))
+                })
+            })
+            .filter_map(move |offset| self.cells.get(&(center + offset)))
+            .flatten()
+            .map(|&i| i as usize)
+    }
+
+    /// Index of the atom nearest `p`, or `None` without atoms. Falls
+    /// back to a full scan when no atom is within one cell size.
+    pub(crate) fn nearest(&self, p: Vec3) -> Option<usize> {
+        let best = self
+            .neighbors(p)
+            .map(|i| (i, self.positions[i].distance_squared(p)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+        match best {
+            Some((i, d)) if d <= self.cell_size * self.cell_size => Some(i),
+            _ => self.scan(p),
+        }
+    }
+
+    /// Nearest atom by brute force.
+    fn scan(&self, p: Vec3) -> Option<usize> {
+        self.positions
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                a.distance_squared(p).total_cmp(&b.distance_squared(p))
+            })
+            .map(|(i, _)| i)
+    }
+}
@@ -0,0 +1,268 @@
+//! Surfaces built directly from atom spheres: van der Waals,
+//! solvent-accessible, and dots.
+//!
+//! The van der Waals surface is the union of atom spheres; the
+//! solvent-accessible surface (SAS) is the same union with every radius
+//! grown by the probe, i.e. the surface traced by the probe's center.
+//! Both are meshed by marching cubes over the union's signed distance
+//! `max(rᵢ - |p - cᵢ|)` (positive inside, matching the density
+//! pipeline) and Taubin-smoothed to soften voxel facets.
+//!
+//! Dot surfaces skip the grid: each atom sphere is sampled on a
+//! Fibonacci spiral and only the dots no other sphere buries are kept,
+//! like PyMOL's dot representation.
+
+use std::sync::atomic::AtomicBool;
+
+use glam::Vec3;
+use molex::analysis::volumetric::GridSpec;
+
+use super::atom_grid::AtomGrid;
+use super::cpu_marching_cubes::extract_isosurface;
+use super::mesh_smooth::taubin_smooth;
+use super::{cancelled, isosurface_kind, IsosurfaceVertex};
+
+/// Taubin iterations applied to sphere-union meshes. Fewer than for
+/// cavities: the creases between spheres are real features.
+const SPHERE_SMOOTHING_ITERATIONS: usize = 4;
+
+/// Generate the union-of-spheres surface of the atoms with every
+/// radius grown by `expand`: the van der Waals surface for 0, the
+/// solvent-accessible surface for the probe radius.
+///
+/// Returns `(vertices, indices)` for an indexed triangle mesh, empty
+/// once `cancel` is set.
+pub(crate) fn generate_sphere_surface(
+    positions: &[Vec3],
+    radii: &[f32],
+    expand: f32,
+    resolution: f32,
+    color: [f32; 4],
+    cancel: &AtomicBool,
+) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
+    let Some((spec, field)) =
+        sphere_union_field(positions, radii, expand, resolution)
+            .filter(|_| !cancelled(cancel))
+    else {
+        return (Vec::new(), Vec::new());
+    };
+    let (mut vertices, indices) = extract_isosurface(
+        &field,
+        spec.dims,
+        0.0,
+        [0, 0, 0],
+        spec.dims,
+        |gx, gy, gz| {
+            [
+                gx.mul_add(spec.spacing[0], spec.origin[0]),
+                gy.mul_add(spec.spacing[1], spec.origin[1]),
+                gz.mul_add(spec.spacing[2], spec.origin[2]),
+            ]
+        },
+        color,
+        cancel,
+    );
+    if cancelled(cancel) {
+        return (Vec::new(), Vec::new());
+    }
+    taubin_smooth(&mut vertices, &indices, SPHERE_SMOOTHING_ITERATIONS);
+    (vertices, indices)
+}
+
+/// Signed distance to the union of the grown spheres on a grid
+/// bracketing them, or `None` without atoms.
+///
+/// Each atom only writes the voxels within its grown radius plus a two
+/// voxel margin; the rest keep a negative floor. The sign is exact
+/// everywhere and the value exact near the surface, which is all
+/// marching cubes needs.
+fn sphere_union_field(
+    positions: &[Vec3],
+    radii: &[f32],
+    expand: f32,
+    resolution: f32,
+) -> Option<(GridSpec, Vec<f32>)> {
+    let resolution = resolution.max(0.1);
+    let max_reach = radii.iter().copied().fold(0.0, f32::max) + expand;
+    let pad = Vec3::splat(2.0f32.mul_add(resolution, max_reach));
+    let (min, max) = bounds(positions)?;
+    let spec = GridSpec::from_bounds(min - pad, max + pad, resolution);
+    let margin = 2.0 * spec.spacing.iter().copied().fold(0.0, f32::max);
+    let mut field = vec![-1.5 * margin; spec.voxel_count()];
+    for (&center, &radius) in positions.iter().zip(radii) {
+        splat_sphere(&spec, &mut field, center, radius + expand, margin);
+    }
+    Some((spec, field))
+}
+
+/// Raise `field` to the sphere's signed distance `reach - |p - center|`
+/// over the voxels within `reach + margin` of its center.
+fn splat_sphere(
+    spec: &GridSpec,
+    field: &mut [f32],
+    center: Vec3,
+    reach: f32,
+    margin: f32,
+) {
+    let origin = Vec3::from_array(spec.origin);
+    let spacing = Vec3::from_array(spec.spacing);
+    let extent = Vec3::splat(reach + margin);
+    let lo = ((center - extent - origin) / spacing)
+        .floor()
+        .max(Vec3::ZERO);
+    let hi = ((center + extent - origin) / spacing).ceil();
+    let [lx, ly, lz] = lo.to_array().map(|v| v as usize);
+    let [hx, hy, hz] = hi.to_array();
+    let hx = (hx as usize).min(spec.dims[0] - 1);
+    let hy = (hy as usize).min(spec.dims[1] - 1);
+    let hz = (hz as usize).min(spec.dims[2] - 1);
+    for ix in lx..=hx {
+        for iy in ly..=hy {
+            for iz in lz..=hz {
+                let p = origin
+                    + spacing * Vec3::new(ix as f32, iy as f32, iz as f32);
+                let value = reach - p.distance(center);
+                let slot = &mut field[spec.lin(ix, iy, iz)];
+                *slot = slot.max(value);
+            }
+        }
+    }
+}
+
+/// Axis-aligned bounds of the atoms, or `None` without atoms.
+fn bounds(positions: &[Vec3]) -> Option<(Vec3, Vec3)> {
+    let first = *positions.first()?;
+    Some(
+        positions
+            .iter()
+            .fold((first, first), |(lo, hi), &p| (lo.min(p), hi.max(p))),
+    )
+}
+
+/// Generate dots on the exposed parts of the van der Waals spheres,
+/// roughly `spacing` Å apart. Every dot is a vertex of kind
+/// [`isosurface_kind::DOT`] with its sphere's outward normal; the
+/// indices are a point list.
+pub(crate) fn generate_dots(
+    positions: &[Vec3],
+    radii: &[f32],
+    spacing: f32,
+    color: [f32; 4],
+) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
+    let max_radius = radii.iter().copied().fold(0.0, f32::max);
+    let grid = AtomGrid::new(positions, max_radius);
+    let spacing = spacing.max(0.1);
+    let mut vertices = Vec::new();
+    for (atom, (&center, &radius)) in positions.iter().zip(radii).enumerate() {
+        let area = 4.0 * std::f32::consts::PI * radius * radius;
+        let count = (area / (spacing * spacing)).round().max(1.0) as usize;
+        for dir in fibonacci_sphere(count) {
+            let p = center + dir * radius;
+            let buried = grid.neighbors(p).any(|other| {
+                other != atom
+                    && positions[other].distance_squared(p)
+                        < radii[other] * radii[other]
+            });
+            if !buried {
+                vertices.push(IsosurfaceVertex {
+                    position: p.to_array(),
+                    normal: dir.to_array(),
+                    color,
+                    kind: isosurface_kind::DOT,
+                    cavity_center: [0.0; 3],
+                    residue: 0,
+                });
+            }
+        }
+    }
+    let indices = (0..vertices.len() as u32).collect();
+    (vertices, indices)
+}
+
+/// `count` near-uniform unit directions on a golden-angle spiral.
+pub(crate) fn fibonacci_sphere(count: usize) -> impl Iterator<Item = Vec3> {
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    (0..count).map(move |i| {
+        let z = 1.0 - (2.0 * i as f32 + 1.0) / count as f32;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let theta = golden_angle * i as f32;
+        Vec3::new(r * theta.cos(), r * theta.sin(), z)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn max_deviation(vertices: &[IsosurfaceVertex], radius: f32) -> f32 {
+        vertices
+            .iter()
+            .map(|v| (Vec3::from_array(v.position).length() - radius).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn cancelled_job_meshes_nothing() {
+        let pos = [Vec3::ZERO];
+        let cancel = AtomicBool::new(true);
+        let (v, i) =
+            generate_sphere_surface(&pos, &[1.5], 0.0, 0.3, [1.0; 4], &cancel);
+        assert!(v.is_empty());
+        assert!(i.is_empty());
+    }
+
+    #[test]
+    fn empty_input_gives_empty_meshes() {
+        let (v, i) = generate_sphere_surface(
+            &[],
+            &[],
+            1.4,
+            0.5,
+            [1.0; 4],
+            &AtomicBool::default(),
+        );
+        assert!(v.is_empty() && i.is_empty());
+        let (v, i) = generate_dots(&[], &[], 1.0, [1.0; 4]);
+        assert!(v.is_empty() && i.is_empty());
+    }
+
+    #[test]
+    fn vdw_and_sas_follow_the_grown_radius() {
+        let pos = [Vec3::ZERO];
+        let (vdw, idx) = generate_sphere_surface(
+            &pos,
+            &[1.5],
+            0.0,
+            0.3,
+            [1.0; 4],
+            &AtomicBool::default(),
+        );
+        assert!(!vdw.is_empty() && idx.len() % 3 == 0);
+        assert!(max_deviation(&vdw, 1.5) < 0.2);
+        let (sas, _) = generate_sphere_surface(
+            &pos,
+            &[1.5],
+            1.4,
+            0.3,
+            [1.0; 4],
+            &AtomicBool::default(),
+        );
+        assert!(max_deviation(&sas, 2.9) < 0.2);
+    }
+
+    #[test]
+    fn buried_dots_are_dropped() {
+        let (lone, _) = generate_dots(&[Vec3::ZERO], &[1.5], 0.5, [1.0; 4]);
+        assert!(max_deviation(&lone, 1.5) < 1e-4);
+
+        let pos = [Vec3::ZERO, Vec3::new(2.0, 0.0, 0.0)];
+        let (pair, indices) = generate_dots(&pos, &[1.5, 1.5], 0.5, [1.0; 4]);
+        assert_eq!(indices.len(), pair.len());
+        assert!(pair.len() < 2 * lone.len());
+        for v in &pair {
+            let p = Vec3::from_array(v.position);
+            assert!(pos.iter().all(|c| c.distance(p) >= 1.5 - 1e-4));
+            assert_eq!(v.kind, isosurface_kind::DOT);
+        }
+    }
+}
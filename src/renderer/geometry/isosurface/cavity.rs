@@ -9,13 +9,15 @@
 //! triangles, and baking cavity-specific vertex attributes
 //! (`CAVITY_RGBA`, `cavity_center`).
 
+use std::sync::atomic::AtomicBool;
+
 use molex::analysis::volumetric::{
     binary_to_sdf, detect_cavities, DetectedCavity,
 };
 
 use super::cpu_marching_cubes::extract_isosurface;
 use super::mesh_smooth::taubin_smooth;
-use super::{isosurface_kind, IsosurfaceVertex};
+use super::{cancelled, isosurface_kind, IsosurfaceVertex};
 
 /// Number of Taubin smoothing iterations applied to each cavity mesh
 /// after marching cubes. Each iteration is one λ pass + one μ pass.
@@ -61,16 +63,22 @@ pub(crate) struct CavitySet {
 /// - `radii`: per-atom van der Waals radii (Angstroms)
 /// - `probe_radius`: solvent probe radius; defaults to 1.4 Å
 /// - `resolution`: grid spacing in Angstroms (lower = finer, typ. 0.5–1.0)
+/// - `cancel`: job cancel flag; once set, no further cavity is meshed
 #[must_use]
 pub(crate) fn generate_cavities(
     positions: &[glam::Vec3],
     radii: &[f32],
     probe_radius: Option<f32>,
     resolution: f32,
+    cancel: &AtomicBool,
 ) -> CavitySet {
     let detected = detect_cavities(positions, radii, probe_radius, resolution);
 
-    let meshes = detected.iter().filter_map(extract_cavity_mesh).collect();
+    let meshes = detected
+        .iter()
+        .take_while(|_| !cancelled(cancel))
+        .filter_map(|cavity| extract_cavity_mesh(cavity, cancel))
+        .collect();
 
     CavitySet { meshes }
 }
@@ -83,7 +91,10 @@ pub(crate) fn generate_cavities(
 /// appearance gets smoothed away on the triangle side after marching
 /// cubes, not by blurring the field — blurring the field would shrink
 /// small cavities below the iso-threshold and lose them entirely.
-fn extract_cavity_mesh(cavity: &DetectedCavity) -> Option<CavityMesh> {
+fn extract_cavity_mesh(
+    cavity: &DetectedCavity,
+    cancel: &AtomicBool,
+) -> Option<CavityMesh> {
     let mut sub_sdf =
         binary_to_sdf(&cavity.sub_mask, cavity.sub_dims, &cavity.spacing);
     for v in &mut sub_sdf {
@@ -104,9 +115,10 @@ fn extract_cavity_mesh(cavity: &DetectedCavity) -> Option<CavityMesh> {
             ]
         },
         CAVITY_RGBA,
+        cancel,
     );
 
-    if vertices.is_empty() || indices.is_empty() {
+    if vertices.is_empty() || indices.is_empty() || cancelled(cancel) {
         return None;
     }
 
@@ -131,14 +143,21 @@ mod tests {
 
     #[test]
     fn generate_cavities_empty_atoms() {
-        let set = generate_cavities(&[], &[], None, 1.0);
+        let set =
+            generate_cavities(&[], &[], None, 1.0, &AtomicBool::default());
         assert!(set.meshes.is_empty());
     }
 
     #[test]
     fn generate_cavities_single_atom_has_none() {
         // A lone atom is a solid blob with no interior voids.
-        let set = generate_cavities(&[Vec3::ZERO], &[1.5], Some(1.4), 0.5);
+        let set = generate_cavities(
+            &[Vec3::ZERO],
+            &[1.5],
+            Some(1.4),
+            0.5,
+            &AtomicBool::default(),
+        );
         assert!(set.meshes.is_empty());
     }
 }
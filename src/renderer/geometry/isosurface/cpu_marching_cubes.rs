@@ -2,10 +2,13 @@
 //!
 //! Pure algorithm — no GPU dependencies. Given a 3D scalar field,
 //! extracts a triangle mesh at a given threshold using the classic
-//! 256-entry lookup table approach.
+//! 256-entry lookup table approach, or the contour lines where that
+//! surface crosses the faces of the grid cells.
+
+use std::sync::atomic::AtomicBool;
 
 use super::tables::{EDGE_TABLE, TRI_TABLE};
-use super::IsosurfaceVertex;
+use super::{cancelled, IsosurfaceVertex};
 
 /// Extract an isosurface from a 3D scalar field using marching cubes.
 ///
@@ -17,7 +20,8 @@ use super::IsosurfaceVertex;
 /// - `grid_to_world`: maps fractional grid coords to world-space
 /// - `color`: uniform color for all vertices
 ///
-/// Returns `(vertices, indices)` for an indexed triangle mesh.
+/// Returns `(vertices, indices)` for an indexed triangle mesh, empty
+/// once `cancel` is set.
 pub(crate) fn extract_isosurface(
     data: &[f32],
     dims: [usize; 3],
@@ -26,10 +30,73 @@ pub(crate) fn extract_isosurface(
     grid_max: [usize; 3],
     grid_to_world: impl Fn(f32, f32, f32) -> [f32; 3],
     color: [f32; 4],
+    cancel: &AtomicBool,
 ) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
+    let (vertices, indices, _) = march(
+        data,
+        dims,
+        threshold,
+        [grid_min, grid_max],
+        &grid_to_world,
+        color,
+        false,
+        cancel,
+    );
+    (vertices, indices)
+}
+
+/// Extract the same surface as [`extract_isosurface`] but return it as
+/// an indexed line list: the segments where it crosses each grid-cell
+/// face, without the triangle diagonals inside the cells. Each segment
+/// appears once.
+pub(crate) fn extract_contour_lines(
+    data: &[f32],
+    dims: [usize; 3],
+    threshold: f32,
+    grid_min: [usize; 3],
+    grid_max: [usize; 3],
+    grid_to_world: impl Fn(f32, f32, f32) -> [f32; 3],
+    color: [f32; 4],
+    cancel: &AtomicBool,
+) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
+    let (vertices, _, lines) = march(
+        data,
+        dims,
+        threshold,
+        [grid_min, grid_max],
+        &grid_to_world,
+        color,
+        true,
+        cancel,
+    );
+    let mut segments: Vec<[u32; 2]> = lines
+        .chunks_exact(2)
+        .map(|l| [l[0].min(l[1]), l[0].max(l[1])])
+        .filter(|[a, b]| a != b)
+        .collect();
+    segments.sort_unstable();
+    segments.dedup();
+    (vertices, segments.into_iter().flatten().collect())
+}
+
+/// Run marching cubes over `bounds` (`[grid_min, grid_max]`), returning
+/// welded and smoothed vertices, triangle indices and, with `lines`,
+/// the cell-face contour segments as index pairs (duplicates kept).
+/// `cancel` is polled once per z slab and before smoothing.
+fn march(
+    data: &[f32],
+    dims: [usize; 3],
+    threshold: f32,
+    bounds: [[usize; 3]; 2],
+    grid_to_world: &impl Fn(f32, f32, f32) -> [f32; 3],
+    color: [f32; 4],
+    lines: bool,
+    cancel: &AtomicBool,
+) -> (Vec<IsosurfaceVertex>, Vec<u32>, Vec<u32>) {
+    let [grid_min, grid_max] = bounds;
     let [nx, ny, nz] = dims;
     if nx < 2 || ny < 2 || nz < 2 {
-        return (Vec::new(), Vec::new());
+        return (Vec::new(), Vec::new(), Vec::new());
     }
 
     // Clamp iteration bounds to valid marching-cubes range (need x+1 etc.)
@@ -41,7 +108,7 @@ pub(crate) fn extract_isosurface(
     let z1 = grid_max[2].min(nz - 1);
 
     if x0 >= x1 || y0 >= y1 || z0 >= z1 {
-        return (Vec::new(), Vec::new());
+        return (Vec::new(), Vec::new(), Vec::new());
     }
 
     // Pre-estimate capacity (heuristic: ~2% of voxels produce triangles)
@@ -49,6 +116,7 @@ pub(crate) fn extract_isosurface(
     let estimate = sub_volume / 50;
     let mut vertices = Vec::with_capacity(estimate);
     let mut indices = Vec::with_capacity(estimate * 3);
+    let mut segments = Vec::new();
 
     // ndarray row-major (C order): shape (nx, ny, nz) → last axis (z)
     // varies fastest in memory.
@@ -56,6 +124,9 @@ pub(crate) fn extract_isosurface(
         |x: usize, y: usize, z: usize| -> usize { x * ny * nz + y * nz + z };
 
     for z in z0..z1 {
+        if cancelled(cancel) {
+            return (Vec::new(), Vec::new(), Vec::new());
+        }
         for y in y0..y1 {
             for x in x0..x1 {
                 let corners = [
@@ -75,15 +146,19 @@ pub(crate) fn extract_isosurface(
                     data,
                     dims,
                     color,
-                    &grid_to_world,
+                    grid_to_world,
                     &mut vertices,
                     &mut indices,
+                    lines.then_some(&mut segments),
                 );
             }
         }
     }
 
-    weld_vertices(&mut vertices, &mut indices);
+    if cancelled(cancel) {
+        return (Vec::new(), Vec::new(), Vec::new());
+    }
+    weld_vertices(&mut vertices, &mut [&mut indices, &mut segments]);
     laplacian_smooth(&mut vertices, &indices, 3, 0.4);
 
     // Flip triangle winding so front faces point toward the camera
@@ -93,7 +168,7 @@ pub(crate) fn extract_isosurface(
         tri.swap(1, 2);
     }
 
-    (vertices, indices)
+    (vertices, indices, segments)
 }
 
 /// Merge coincident vertices and average their normals for smooth
@@ -101,8 +176,12 @@ pub(crate) fn extract_isosurface(
 ///
 /// Uses spatial hashing to find vertices at the same position (within
 /// a small epsilon). Merged vertices get averaged normals, giving
-/// smooth interpolation across adjacent marching-cubes cells.
-fn weld_vertices(vertices: &mut Vec<IsosurfaceVertex>, indices: &mut [u32]) {
+/// smooth interpolation across adjacent marching-cubes cells. Every
+/// index list in `index_lists` is remapped to the welded vertices.
+fn weld_vertices(
+    vertices: &mut Vec<IsosurfaceVertex>,
+    index_lists: &mut [&mut Vec<u32>],
+) {
     use std::collections::HashMap;
 
     if vertices.is_empty() {
@@ -163,7 +242,7 @@ fn weld_vertices(vertices: &mut Vec<IsosurfaceVertex>, indices: &mut [u32]) {
     }
 
     // Remap indices
-    for idx in indices.iter_mut() {
+    for idx in index_lists.iter_mut().flat_map(|list| list.iter_mut()) {
         *idx = remap[*idx as usize];
     }
 
@@ -281,8 +360,28 @@ fn recompute_normals(vertices: &mut [IsosurfaceVertex], indices: &[u32]) {
     }
 }
 
-/// Process a single marching-cubes cell, appending triangles to the
-/// output buffers.
+/// Faces of the cell each of the 12 cube edges lies on, one bit per
+/// face (z = 0, z = 1, y = 0, y = 1, x = 0, x = 1). A triangle edge
+/// between two cube edges that share a face is a contour segment on
+/// that face; any other triangle edge is a diagonal through the cell.
+const EDGE_FACES: [u8; 12] = [
+    0b00_01_01, // 0: z = 0, y = 0
+    0b10_00_01, // 1: z = 0, x = 1
+    0b00_10_01, // 2: z = 0, y = 1
+    0b01_00_01, // 3: z = 0, x = 0
+    0b00_01_10, // 4: z = 1, y = 0
+    0b10_00_10, // 5: z = 1, x = 1
+    0b00_10_10, // 6: z = 1, y = 1
+    0b01_00_10, // 7: z = 1, x = 0
+    0b01_01_00, // 8: y = 0, x = 0
+    0b10_01_00, // 9: y = 0, x = 1
+    0b10_10_00, // 10: y = 1, x = 1
+    0b01_10_00, // 11: y = 1, x = 0
+];
+
+/// Process a single marching-cubes cell, appending triangles (and,
+/// with `lines`, the cell-face segments of their edges) to the output
+/// buffers.
 fn process_cube(
     corners: &[f32; 8],
     origin: [f32; 3],
@@ -293,6 +392,7 @@ fn process_cube(
     grid_to_world: &impl Fn(f32, f32, f32) -> [f32; 3],
     vertices: &mut Vec<IsosurfaceVertex>,
     indices: &mut Vec<u32>,
+    lines: Option<&mut Vec<u32>>,
 ) {
     let cube_index = classify_corners(corners, threshold);
     let edge_bits = EDGE_TABLE[cube_index as usize];
@@ -318,6 +418,7 @@ fn process_cube(
                 color,
                 kind: super::isosurface_kind::SURFACE,
                 cavity_center: [0.0; 3],
+                residue: 0,
             });
             edge_emitted[edge] = base + local_count;
             local_count += 1;
@@ -325,6 +426,18 @@ fn process_cube(
         indices.push(edge_emitted[edge]);
         i += 1;
     }
+
+    if let Some(lines) = lines {
+        for tri in tri_row[..i].chunks_exact(3) {
+            for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])]
+            {
+                let (a, b) = (a as usize, b as usize);
+                if EDGE_FACES[a] & EDGE_FACES[b] != 0 {
+                    lines.extend([edge_emitted[a], edge_emitted[b]]);
+                }
+            }
+        }
+    }
 }
 
 /// Build cube index from corner classification (above/below threshold).
@@ -476,6 +589,7 @@ mod tests {
             dims,
             |x, y, z| [x, y, z],
             [1.0, 1.0, 1.0, 1.0],
+            &AtomicBool::default(),
         );
 
         assert!(!verts.is_empty(), "should produce vertices");
@@ -496,6 +610,7 @@ mod tests {
             dims,
             |x, y, z| [x, y, z],
             [1.0, 1.0, 1.0, 1.0],
+            &AtomicBool::default(),
         );
 
         let mut outward_count = 0;
@@ -520,6 +635,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn contour_lines_skip_cell_diagonals() {
+        // Corners (0, 0, 0) and (1, 0, 0) of a single cell are inside:
+        // the surface is a quad split into two triangles.
+        let dims = [2, 2, 2];
+        let mut data = vec![0.0f32; 8];
+        data[0] = 1.0;
+        data[4] = 1.0;
+        let to_world = |x: f32, y: f32, z: f32| [x, y, z];
+        let (_, triangles) = extract_isosurface(
+            &data,
+            dims,
+            0.5,
+            [0, 0, 0],
+            dims,
+            to_world,
+            [1.0; 4],
+            &AtomicBool::default(),
+        );
+        let mut tri_edges: Vec<[u32; 2]> = triangles
+            .chunks_exact(3)
+            .flat_map(|t| [[t[0], t[1]], [t[1], t[2]], [t[2], t[0]]])
+            .map(|[a, b]| [a.min(b), a.max(b)])
+            .collect();
+        tri_edges.sort_unstable();
+        tri_edges.dedup();
+        assert_eq!(tri_edges.len(), 5);
+
+        let (verts, lines) = extract_contour_lines(
+            &data,
+            dims,
+            0.5,
+            [0, 0, 0],
+            dims,
+            to_world,
+            [1.0; 4],
+            &AtomicBool::default(),
+        );
+        assert_eq!(verts.len(), 4);
+        // The quad's four sides only; the shared diagonal is dropped.
+        let segments: Vec<[u32; 2]> =
+            lines.chunks_exact(2).map(|l| [l[0], l[1]]).collect();
+        assert_eq!(segments.len(), 4);
+        assert!(segments.iter().all(|s| tri_edges.contains(s)));
+    }
+
     #[test]
     fn empty_field_produces_nothing() {
         let dims = [5, 5, 5];
@@ -532,6 +693,7 @@ mod tests {
             dims,
             |x, y, z| [x, y, z],
             [1.0, 0.0, 0.0, 1.0],
+            &AtomicBool::default(),
         );
         assert!(verts.is_empty());
         assert!(indices.is_empty());
@@ -549,6 +711,7 @@ mod tests {
             dims,
             |x, y, z| [x, y, z],
             [1.0, 1.0, 1.0, 1.0],
+            &AtomicBool::default(),
         );
 
         let total = indices.len() / 3;
@@ -2,17 +2,28 @@
 //!
 //! Converts crystallographic density data into triangle mesh vertices
 //! suitable for GPU rendering. Optionally crops to a world-space
-//! bounding box so only density near the structure is meshed.
+//! zone (a box, or a sphere clipped out of its bounding box) so large
+//! maps only mesh the region around the view center.
+//!
+//! A map can be contoured at several levels into one mesh, drawn either
+//! as a solid surface or as "chicken-wire": the contour segments where
+//! the surface crosses each grid-cell face, as a line list.
+
+use std::sync::atomic::AtomicBool;
 
 use molex::entity::surface::Density;
 
-use super::cpu_marching_cubes::extract_isosurface;
-use super::IsosurfaceVertex;
+use super::cpu_marching_cubes::{extract_contour_lines, extract_isosurface};
+use super::{cancelled, isosurface_kind, IsosurfaceVertex};
 
-/// Padding in Angstroms around the structure bounding box when cropping
-/// the density map.
-#[allow(dead_code)]
-const CROP_PADDING: f32 = 5.0;
+/// One contour level of a density map.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ContourLevel {
+    /// Raw density threshold.
+    pub(crate) threshold: f32,
+    /// Mesh color RGB.
+    pub(crate) color: [f32; 3],
+}
 
 /// World-space axis-aligned bounding box for map cropping.
 pub(crate) struct CropBox {
@@ -20,20 +31,172 @@ pub(crate) struct CropBox {
     pub(crate) min: [f32; 3],
     /// Maximum corner (Angstroms).
     pub(crate) max: [f32; 3],
+    /// Optional sphere `(center, radius)` inside the box; triangles or
+    /// lines whose centroid falls outside it are dropped.
+    pub(crate) sphere: Option<([f32; 3], f32)>,
 }
 
 impl CropBox {
-    /// Build a crop box from a bounding sphere (centroid + radius) with
-    /// padding.
+    /// Cube of half-extent `radius` around `center`.
     #[must_use]
-    #[allow(dead_code)]
-    pub(crate) fn from_sphere(center: [f32; 3], radius: f32) -> Self {
-        let r = radius + CROP_PADDING;
+    pub(crate) fn cube(center: [f32; 3], radius: f32) -> Self {
         Self {
-            min: [center[0] - r, center[1] - r, center[2] - r],
-            max: [center[0] + r, center[1] + r, center[2] + r],
+            min: [center[0] - radius, center[1] - radius, center[2] - radius],
+            max: [center[0] + radius, center[1] + radius, center[2] + radius],
+            sphere: None,
         }
     }
+
+    /// Sphere of `radius` around `center`, meshed from its bounding cube.
+    #[must_use]
+    pub(crate) fn sphere(center: [f32; 3], radius: f32) -> Self {
+        Self {
+            sphere: Some((center, radius)),
+            ..Self::cube(center, radius)
+        }
+    }
+
+    /// Grid-index bounds `(min, max)` covering the box, clamped to
+    /// `dims`. All eight corners are mapped since an oblique cell
+    /// stretches the box along more than its diagonal.
+    fn grid_bounds(
+        &self,
+        map: &Density,
+        dims: [usize; 3],
+    ) -> ([usize; 3], [usize; 3]) {
+        let mut lo = [f32::INFINITY; 3];
+        let mut hi = [f32::NEG_INFINITY; 3];
+        for corner in 0..8 {
+            let pick = |axis: usize| {
+                if corner & (1 << axis) == 0 {
+                    self.min[axis]
+                } else {
+                    self.max[axis]
+                }
+            };
+            let g = map.cartesian_to_grid([pick(0), pick(1), pick(2)]);
+            for axis in 0..3 {
+                lo[axis] = lo[axis].min(g[axis]);
+                hi[axis] = hi[axis].max(g[axis]);
+            }
+        }
+        let clamp = |v: f32, axis: usize| (v.max(0.0) as usize).min(dims[axis]);
+        (
+            [
+                clamp(lo[0].floor(), 0),
+                clamp(lo[1].floor(), 1),
+                clamp(lo[2].floor(), 2),
+            ],
+            [
+                clamp(hi[0].ceil() + 1.0, 0),
+                clamp(hi[1].ceil() + 1.0, 1),
+                clamp(hi[2].ceil() + 1.0, 2),
+            ],
+        )
+    }
+}
+
+/// Drop the primitives (`arity` indices each: 3 for triangles, 2 for
+/// lines) whose centroid lies outside the sphere and compact the vertex
+/// list to the ones still referenced.
+fn clip_to_sphere(
+    vertices: &[IsosurfaceVertex],
+    indices: &[u32],
+    arity: usize,
+    center: [f32; 3],
+    radius: f32,
+) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
+    let radius_sq = radius * radius;
+    let mut remap = vec![u32::MAX; vertices.len()];
+    let mut kept_vertices = Vec::new();
+    let mut kept_indices = Vec::new();
+    for prim in indices.chunks_exact(arity) {
+        let Some(corners) = prim
+            .iter()
+            .map(|&i| vertices.get(i as usize).map(|v| v.position))
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+        let dist_sq: f32 = (0..3)
+            .map(|axis| {
+                let c =
+                    corners.iter().map(|p| p[axis]).sum::<f32>() / arity as f32;
+                (c - center[axis]).powi(2)
+            })
+            .sum();
+        if dist_sq > radius_sq {
+            continue;
+        }
+        for &i in prim {
+            let slot = &mut remap[i as usize];
+            if *slot == u32::MAX {
+                *slot = kept_vertices.len() as u32;
+                kept_vertices.push(vertices[i as usize]);
+            }
+            kept_indices.push(*slot);
+        }
+    }
+    (kept_vertices, kept_indices)
+}
+
+/// Contour `map` at every level in `levels` into one mesh.
+///
+/// Levels below the map mean enclose low density (the negative contour
+/// of a difference map), so their normals and winding are flipped to
+/// face out of it. With `wire`, the indices are the cell-face contour
+/// segments as a line list (no triangle diagonals) and the vertices are
+/// tagged [`isosurface_kind::WIRE`]. Meshing stops, leaving an empty
+/// mesh, once `cancel` is set.
+pub(crate) fn generate_density_contours(
+    map: &Density,
+    levels: &[ContourLevel],
+    opacity: f32,
+    crop: Option<&CropBox>,
+    wire: bool,
+    cancel: &AtomicBool,
+) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for level in levels {
+        if cancelled(cancel) {
+            return (Vec::new(), Vec::new());
+        }
+        let [r, g, b] = level.color;
+        let (mut level_vertices, mut level_indices) = generate_density_mesh(
+            map,
+            level.threshold,
+            [r, g, b, opacity],
+            crop,
+            wire,
+            cancel,
+        );
+        if level.threshold < map.dmean {
+            // Lines have no winding; only their normals flip.
+            let winding: &mut [u32] =
+                if wire { &mut [] } else { &mut level_indices };
+            invert(&mut level_vertices, winding);
+        }
+        let base = vertices.len() as u32;
+        vertices.extend(level_vertices);
+        indices.extend(level_indices.into_iter().map(|i| i + base));
+    }
+    if wire {
+        for v in &mut vertices {
+            v.kind = isosurface_kind::WIRE;
+        }
+    }
+    (vertices, indices)
+}
+
+/// Flip normals and triangle winding, turning the surface inside out.
+fn invert(vertices: &mut [IsosurfaceVertex], indices: &mut [u32]) {
+    for v in vertices {
+        v.normal = v.normal.map(|n| -n);
+    }
+    for tri in indices.chunks_exact_mut(3) {
+        tri.swap(1, 2);
+    }
 }
 
 /// Generate a triangle mesh from a density map at the given sigma level.
@@ -41,14 +204,19 @@ impl CropBox {
 /// - `map`: parsed density map (CCP4/MRC format)
 /// - `threshold`: raw density threshold for isosurface extraction
 /// - `color`: uniform RGBA color for all mesh vertices
-/// - `crop`: optional bounding box to restrict meshing (world-space)
+/// - `crop`: optional zone to restrict meshing (world-space)
+/// - `wire`: emit cell-face contour lines instead of triangles
+/// - `cancel`: job cancel flag, polled during marching cubes
 ///
-/// Returns `(vertices, indices)` for an indexed triangle mesh.
+/// Returns `(vertices, indices)` for an indexed triangle mesh, or an
+/// indexed line list with `wire`.
 pub(crate) fn generate_density_mesh(
     map: &Density,
     threshold: f32,
     color: [f32; 4],
     crop: Option<&CropBox>,
+    wire: bool,
+    cancel: &AtomicBool,
 ) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
     let dims = [map.nx, map.ny, map.nz];
 
@@ -99,36 +267,15 @@ pub(crate) fn generate_density_mesh(
     );
 
     // Determine grid-space iteration bounds (full grid or cropped).
-    let (grid_min, grid_max) = crop.map_or(([0, 0, 0], dims), |bbox| {
-        let gmin = map.cartesian_to_grid(bbox.min);
-        let gmax = map.cartesian_to_grid(bbox.max);
-        // Take min/max across both corners since the matrix transform
-        // can swap axis ordering.
-        let lo = [
-            gmin[0].min(gmax[0]),
-            gmin[1].min(gmax[1]),
-            gmin[2].min(gmax[2]),
-        ];
-        let hi = [
-            gmin[0].max(gmax[0]),
-            gmin[1].max(gmax[1]),
-            gmin[2].max(gmax[2]),
-        ];
-        (
-            [
-                (lo[0].floor() as isize).max(0) as usize,
-                (lo[1].floor() as isize).max(0) as usize,
-                (lo[2].floor() as isize).max(0) as usize,
-            ],
-            [
-                ((hi[0].ceil() as usize) + 1).min(dims[0]),
-                ((hi[1].ceil() as usize) + 1).min(dims[1]),
-                ((hi[2].ceil() as usize) + 1).min(dims[2]),
-            ],
-        )
-    });
+    let (grid_min, grid_max) =
+        crop.map_or(([0, 0, 0], dims), |bbox| bbox.grid_bounds(map, dims));
 
-    extract_isosurface(
+    let extract = if wire {
+        extract_contour_lines
+    } else {
+        extract_isosurface
+    };
+    let (vertices, indices) = extract(
         data,
         dims,
         threshold,
@@ -136,5 +283,75 @@ pub(crate) fn generate_density_mesh(
         grid_max,
         |x, y, z| map.grid_to_cartesian_f32(x, y, z),
         color,
-    )
+        cancel,
+    );
+    let arity = if wire { 2 } else { 3 };
+    match crop.and_then(|bbox| bbox.sphere) {
+        Some((center, radius)) => {
+            clip_to_sphere(&vertices, &indices, arity, center, radius)
+        }
+        None => (vertices, indices),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(position: [f32; 3]) -> IsosurfaceVertex {
+        IsosurfaceVertex {
+            position,
+            ..bytemuck::Zeroable::zeroed()
+        }
+    }
+
+    #[test]
+    fn sphere_clip_keeps_only_triangles_inside() {
+        let vertices = vec![
+            vertex([0.0, 0.0, 0.0]),
+            vertex([1.0, 0.0, 0.0]),
+            vertex([0.0, 1.0, 0.0]),
+            vertex([20.0, 0.0, 0.0]),
+            vertex([21.0, 0.0, 0.0]),
+        ];
+        // One triangle at the origin, one far outside sharing vertex 1.
+        let indices = [0, 1, 2, 1, 3, 4];
+        let (kept, kept_indices) =
+            clip_to_sphere(&vertices, &indices, 3, [0.0; 3], 5.0);
+        assert_eq!(kept.len(), 3);
+        assert_eq!(kept_indices, vec![0, 1, 2]);
+        assert_eq!(kept[1].position, [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn sphere_clip_keeps_only_lines_inside() {
+        let vertices = vec![
+            vertex([0.0, 0.0, 0.0]),
+            vertex([1.0, 0.0, 0.0]),
+            vertex([20.0, 0.0, 0.0]),
+        ];
+        // The 1-2 line's midpoint is outside the sphere.
+        let (kept, kept_indices) =
+            clip_to_sphere(&vertices, &[0, 1, 1, 2], 2, [0.0; 3], 5.0);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept_indices, vec![0, 1]);
+    }
+
+    #[test]
+    fn inverting_flips_winding_and_normals() {
+        let mut vertices = vec![vertex([0.0; 3])];
+        vertices[0].normal = [0.0, 0.0, 1.0];
+        let mut indices = vec![0, 1, 2];
+        invert(&mut vertices, &mut indices);
+        assert_eq!(vertices[0].normal, [0.0, 0.0, -1.0]);
+        assert_eq!(indices, vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn sphere_crop_is_bounded_by_its_cube() {
+        let crop = CropBox::sphere([10.0, 0.0, -5.0], 4.0);
+        assert_eq!(crop.min, [6.0, -4.0, -9.0]);
+        assert_eq!(crop.max, [14.0, 4.0, -1.0]);
+        assert_eq!(crop.sphere, Some(([10.0, 0.0, -5.0], 4.0)));
+    }
 }
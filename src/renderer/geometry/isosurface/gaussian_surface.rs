@@ -5,11 +5,13 @@
 //! file only runs marching cubes on the returned field and formats
 //! the result as [`IsosurfaceVertex`].
 
+use std::sync::atomic::AtomicBool;
+
 use glam::Vec3;
 use molex::analysis::volumetric::compute_gaussian_field;
 
 use super::cpu_marching_cubes::extract_isosurface;
-use super::IsosurfaceVertex;
+use super::{cancelled, IsosurfaceVertex};
 
 /// Generate a Gaussian molecular surface from atom positions.
 ///
@@ -18,21 +20,24 @@ use super::IsosurfaceVertex;
 /// - `resolution`: grid spacing in Angstroms (lower = finer, typ. 0.5–2.0)
 /// - `level`: isosurface threshold (default ~0.5)
 /// - `color`: RGBA color for the surface
+/// - `cancel`: job cancel flag, checked between stages
 ///
-/// Returns `(vertices, indices)` for an indexed triangle mesh.
+/// Returns `(vertices, indices)` for an indexed triangle mesh, empty
+/// once `cancel` is set.
 pub(crate) fn generate_gaussian_surface(
     positions: &[Vec3],
     radii: &[f32],
     resolution: f32,
     level: f32,
     color: [f32; 4],
+    cancel: &AtomicBool,
 ) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
     if positions.is_empty() {
         return (Vec::new(), Vec::new());
     }
 
     let grid = compute_gaussian_field(positions, radii, resolution);
-    if grid.data.is_empty() {
+    if grid.data.is_empty() || cancelled(cancel) {
         return (Vec::new(), Vec::new());
     }
 
@@ -53,5 +58,6 @@ pub(crate) fn generate_gaussian_surface(
             ]
         },
         color,
+        cancel,
     )
 }
@@ -164,6 +164,7 @@ mod tests {
             color: [1.0; 4],
             kind: super::super::isosurface_kind::SURFACE,
             cavity_center: [0.0; 3],
+            residue: 0,
         }
     }
 
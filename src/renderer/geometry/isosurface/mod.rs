@@ -2,26 +2,45 @@
 //! surfaces.
 //!
 //! Renders isosurfaces as triangle meshes via marching cubes on the
-//! CPU, uploaded through a `MeshPass` + `DynamicBuffer` vertex buffer.
+//! CPU. Each density map, entity surface and cavity set keeps its own
+//! vertex and index buffers, so one can be replaced without touching
+//! the others.
 //! Integrates with depth, normals, SSAO, and bloom through the standard
 //! dual render target (color + normal).
 
+pub(crate) mod atom_grid;
+pub(crate) mod atom_spheres;
 pub(crate) mod cavity;
 pub(crate) mod cpu_marching_cubes;
 pub(crate) mod density;
 pub(crate) mod gaussian_surface;
 pub(crate) mod mesh_smooth;
+pub(crate) mod residue_tags;
 pub(crate) mod ses;
+pub(crate) mod surface_patch;
 pub(crate) mod tables;
 
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rustc_hash::FxHashMap;
+
+use crate::engine::surface_regen::{MeshMessage, SurfaceKey};
 use crate::error::VisoError;
 use crate::gpu::dynamic_buffer::DynamicBuffer;
 use crate::gpu::pipeline_helpers::texture_2d_unfilterable;
 use crate::gpu::{RenderContext, Shader, ShaderComposer};
 use crate::renderer::draw_context::DrawBindGroups;
-use crate::renderer::mesh::{create_mesh_pipeline, MeshPass, MeshPipelineDef};
+use crate::renderer::mesh::{create_mesh_pipeline, MeshPipelineDef};
 use crate::renderer::PipelineLayouts;
 
+/// Whether a background mesh job has been superseded. Generators poll
+/// this between their stages (field build, marching cubes, smoothing)
+/// and return an empty mesh once it is set, since the result would be
+/// dropped anyway.
+pub(crate) fn cancelled(cancel: &AtomicBool) -> bool {
+    cancel.load(Ordering::Relaxed)
+}
+
 /// Discriminator tagging which kind of isosurface a vertex belongs to.
 ///
 /// Stored as a `u32` in [`IsosurfaceVertex::kind`] so the shared
@@ -38,6 +57,45 @@ pub(crate) mod isosurface_kind {
     /// Internal cavity mesh — gets the cavity-specific pulsing rim and
     /// (eventually) volumetric / depth-absorption shading.
     pub(crate) const CAVITY: u32 = 1;
+    /// Chicken-wire density contour, drawn as lines. Skips the
+    /// thickness absorption, which only makes sense for closed shells.
+    pub(crate) const WIRE: u32 = 2;
+    /// Dot surface, drawn as points. Like wires, skips the thickness
+    /// absorption.
+    pub(crate) const DOT: u32 = 3;
+    /// Flag OR-ed onto any kind: color the vertex by its residue. The
+    /// shader reads the live color of [`IsosurfaceVertex::residue`]
+    /// from the per-residue color buffer, so the surface follows color
+    /// scheme changes and their transitions without a remesh.
+    ///
+    /// [`IsosurfaceVertex::residue`]: super::IsosurfaceVertex::residue
+    pub(crate) const RESIDUE_COLOR: u32 = 1 << 8;
+    /// Flag OR-ed onto any kind: discard the fragment when it lies
+    /// between the camera and the focus point's view plane, opening
+    /// the surface onto what it encloses.
+    pub(crate) const CUTAWAY: u32 = 1 << 9;
+}
+
+/// Primitive a mesh's index list describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MeshTopology {
+    /// Triangle list: closed surfaces and contour shells.
+    Triangles,
+    /// Line list: chicken-wire density contours.
+    Lines,
+    /// Point list: dot surfaces.
+    Points,
+}
+
+impl MeshTopology {
+    /// Indices per primitive.
+    pub(crate) const fn primitive_len(self) -> usize {
+        match self {
+            Self::Triangles => 3,
+            Self::Lines => 2,
+            Self::Points => 1,
+        }
+    }
 }
 
 /// A vertex on the extracted isosurface.
@@ -57,6 +115,10 @@ pub(crate) struct IsosurfaceVertex {
     /// other kinds set this to `[0.0; 3]` and the shader ignores it.
     /// Used by the vertex shader for radial-breath displacement.
     pub(crate) cavity_center: [f32; 3],
+    /// Pick ID of the residue nearest this vertex (its flat residue
+    /// index plus one), or 0 for none. Entity surfaces of proteins set
+    /// it so the surface can be picked and colored per residue.
+    pub(crate) residue: u32,
 }
 
 /// Isosurface vertex buffer layout for wgpu.
@@ -90,6 +152,11 @@ pub(crate) fn isosurface_vertex_layout() -> wgpu::VertexBufferLayout<'static> {
                 offset: 44,
                 shader_location: 4,
             },
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Uint32,
+                offset: 56,
+                shader_location: 5,
+            },
         ],
     }
 }
@@ -102,9 +169,19 @@ pub(crate) fn isosurface_vertex_layout() -> wgpu::VertexBufferLayout<'static> {
 /// z to an external R32Float texture; the main pass samples that
 /// texture (bound as group 2) to compute thickness for Beer-Lambert.
 pub(crate) struct IsosurfaceRenderer {
-    mesh_pass: MeshPass,
-    vertex_buffer: DynamicBuffer,
-    vertex_count: u32,
+    pipeline: wgpu::RenderPipeline,
+    /// Unculled triangle pipeline for cutaway meshes, whose inner walls
+    /// face the camera once the near side is cut.
+    cutaway_pipeline: wgpu::RenderPipeline,
+    /// Line-list pipeline for chicken-wire meshes.
+    wire_pipeline: wgpu::RenderPipeline,
+    /// Point-list pipeline for dot surfaces.
+    dot_pipeline: wgpu::RenderPipeline,
+    /// One entry per non-empty mesh, in [`SurfaceKey`] order.
+    slices: Vec<IsosurfaceSlice>,
+    /// Generation of the last message applied per key (including
+    /// removals), so late results of superseded jobs are ignored.
+    generations: FxHashMap<SurfaceKey, u64>,
     back_face_pipeline: wgpu::RenderPipeline,
     back_face_bind_group_layout: wgpu::BindGroupLayout,
     back_face_bind_group: wgpu::BindGroup,
@@ -136,31 +213,77 @@ impl IsosurfaceRenderer {
                 label: "Isosurface",
                 shader: Shader::Isosurface,
                 cull_mode: Some(wgpu::Face::Back),
+                topology: wgpu::PrimitiveTopology::TriangleList,
                 vertex_layout: isosurface_vertex_layout(),
             },
             &[
                 &layouts.camera,
                 &layouts.lighting,
                 &back_face_bind_group_layout,
+                &layouts.color,
             ],
             shader_composer,
         )?;
 
-        let back_face_pipeline = create_back_face_depth_pipeline(
+        let cutaway_pipeline = create_mesh_pipeline(
             context,
+            &MeshPipelineDef {
+                label: "Isosurface Cutaway",
+                shader: Shader::Isosurface,
+                cull_mode: None,
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                vertex_layout: isosurface_vertex_layout(),
+            },
+            &[
+                &layouts.camera,
+                &layouts.lighting,
+                &back_face_bind_group_layout,
+                &layouts.color,
+            ],
             shader_composer,
-            &layouts.camera,
         )?;
 
-        let mesh_pass =
-            MeshPass::new(&context.device, "Isosurface Indices", pipeline, &[]);
+        let wire_pipeline = create_mesh_pipeline(
+            context,
+            &MeshPipelineDef {
+                label: "Isosurface Wire",
+                shader: Shader::Isosurface,
+                cull_mode: None,
+                topology: wgpu::PrimitiveTopology::LineList,
+                vertex_layout: isosurface_vertex_layout(),
+            },
+            &[
+                &layouts.camera,
+                &layouts.lighting,
+                &back_face_bind_group_layout,
+                &layouts.color,
+            ],
+            shader_composer,
+        )?;
 
-        let vertex_buffer = DynamicBuffer::new(
-            &context.device,
-            "Isosurface Vertices",
-            size_of::<IsosurfaceVertex>() * 1000,
-            wgpu::BufferUsages::VERTEX,
-        );
+        let dot_pipeline = create_mesh_pipeline(
+            context,
+            &MeshPipelineDef {
+                label: "Isosurface Dots",
+                shader: Shader::Isosurface,
+                cull_mode: None,
+                topology: wgpu::PrimitiveTopology::PointList,
+                vertex_layout: isosurface_vertex_layout(),
+            },
+            &[
+                &layouts.camera,
+                &layouts.lighting,
+                &back_face_bind_group_layout,
+                &layouts.color,
+            ],
+            shader_composer,
+        )?;
+
+        let back_face_pipeline = create_back_face_depth_pipeline(
+            context,
+            shader_composer,
+            &layouts.camera,
+        )?;
 
         let back_face_bind_group = create_back_face_bind_group(
             &context.device,
@@ -169,9 +292,12 @@ impl IsosurfaceRenderer {
         );
 
         Ok(Self {
-            mesh_pass,
-            vertex_buffer,
-            vertex_count: 0,
+            pipeline,
+            cutaway_pipeline,
+            wire_pipeline,
+            dot_pipeline,
+            slices: Vec::new(),
+            generations: FxHashMap::default(),
             back_face_pipeline,
             back_face_bind_group_layout,
             back_face_bind_group,
@@ -192,78 +318,206 @@ impl IsosurfaceRenderer {
         );
     }
 
-    /// Upload new isosurface mesh data to the GPU.
+    /// Replace (or remove) one mesh. Returns `false` if the message is
+    /// older than the last one applied for its key and was ignored.
     pub(crate) fn apply_prepared(
         &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        vertices: &[IsosurfaceVertex],
-        indices: &[u32],
-    ) {
-        if vertices.is_empty() || indices.is_empty() {
-            self.vertex_count = 0;
-            self.mesh_pass.write_indices(device, queue, &[]);
-            return;
+        message: MeshMessage,
+    ) -> bool {
+        let MeshMessage {
+            key,
+            generation,
+            mesh,
+            topology,
+        } = message;
+        if self.generations.get(&key).is_some_and(|&g| g >= generation) {
+            return false;
         }
-        let _ = self.vertex_buffer.write(device, queue, vertices);
-        self.vertex_count = vertices.len() as u32;
-        self.mesh_pass.write_indices(device, queue, indices);
+        let _ = self.generations.insert(key, generation);
+
+        let slot = self.slices.binary_search_by_key(&key, |s| s.key);
+        match (mesh.filter(|(v, i)| !v.is_empty() && !i.is_empty()), slot) {
+            (None, Ok(at)) => {
+                let _ = self.slices.remove(at);
+            }
+            (None, Err(_)) => {}
+            (Some((vertices, indices)), Ok(at)) => {
+                let slice = &mut self.slices[at];
+                slice.topology = topology;
+                slice.write(device, queue, &vertices, &indices);
+            }
+            (Some((vertices, indices)), Err(at)) => {
+                let mut slice = IsosurfaceSlice::new(device, key, topology);
+                slice.write(device, queue, &vertices, &indices);
+                self.slices.insert(at, slice);
+            }
+        }
+        true
     }
 
-    /// Draw the isosurface mesh into the given render pass.
+    /// Draw every isosurface mesh into the given render pass: the
+    /// triangle meshes first (closed, then cutaway), then the
+    /// chicken-wire and dot ones.
     pub(crate) fn draw<'a>(
         &'a self,
         render_pass: &mut wgpu::RenderPass<'a>,
         bind_groups: &DrawBindGroups<'a>,
     ) {
-        if self.vertex_count == 0 {
+        let Some(color) = bind_groups.color else {
+            return;
+        };
+        if self.slices.is_empty() {
             return;
         }
         render_pass.set_bind_group(0, bind_groups.camera, &[]);
         render_pass.set_bind_group(1, bind_groups.lighting, &[]);
         render_pass.set_bind_group(2, &self.back_face_bind_group, &[]);
-        self.mesh_pass
-            .draw_indexed(render_pass, self.vertex_buffer.buffer());
+        render_pass.set_bind_group(3, color, &[]);
+        for (pipeline, topology, cutaway) in [
+            (&self.pipeline, MeshTopology::Triangles, false),
+            (&self.cutaway_pipeline, MeshTopology::Triangles, true),
+            (&self.wire_pipeline, MeshTopology::Lines, false),
+            (&self.dot_pipeline, MeshTopology::Points, false),
+        ] {
+            let mut slices = self
+                .slices
+                .iter()
+                .filter(|s| s.topology == topology && s.cutaway == cutaway)
+                .peekable();
+            if slices.peek().is_none() {
+                continue;
+            }
+            render_pass.set_pipeline(pipeline);
+            for slice in slices {
+                slice.draw(render_pass, bind_groups.copies);
+            }
+        }
     }
 
     /// Draw the back-face depth pre-pass.
     ///
     /// Caller is responsible for setting up a render pass with the
-    /// R32Float color attachment and binding the camera bind group.
-    /// Renders all isosurface back-faces (front-face culling) writing
-    /// linear view-space z.
+    /// R32Float color attachment. Renders all isosurface back-faces
+    /// (front-face culling) of `copies` model copies writing linear
+    /// view-space z.
     pub(crate) fn draw_back_face_pass<'a>(
         &'a self,
         render_pass: &mut wgpu::RenderPass<'a>,
         camera_bind_group: &'a wgpu::BindGroup,
+        copies: u32,
     ) {
-        if self.vertex_count == 0 {
+        if self.slices.is_empty() {
             return;
         }
         render_pass.set_pipeline(&self.back_face_pipeline);
         render_pass.set_bind_group(0, camera_bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.vertex_buffer.buffer().slice(..));
-        render_pass.set_index_buffer(
-            self.mesh_pass.index_buffer().slice(..),
-            wgpu::IndexFormat::Uint32,
-        );
-        render_pass.draw_indexed(0..self.mesh_pass.index_count, 0, 0..1);
+        for slice in self
+            .slices
+            .iter()
+            .filter(|s| s.topology == MeshTopology::Triangles)
+        {
+            slice.draw(render_pass, copies);
+        }
+    }
+
+    /// Triangulated entity surface meshes for the picking pass, as `(vertices,
+    /// indices, index count)`. Their vertices carry residue pick IDs.
+    pub(crate) fn pickable_meshes(
+        &self,
+    ) -> impl Iterator<Item = (&wgpu::Buffer, &wgpu::Buffer, u32)> {
+        self.slices
+            .iter()
+            .filter(|s| {
+                matches!(s.key, SurfaceKey::Surface(_))
+                    && s.topology == MeshTopology::Triangles
+            })
+            .map(|s| (s.vertices.buffer(), s.indices.buffer(), s.index_count))
     }
 
     /// GPU buffer sizes: `(label, used_bytes, allocated_bytes)`.
+    /// Summed over every mesh.
     pub(crate) fn buffer_info(&self) -> Vec<(&'static str, usize, usize)> {
+        let sum = |f: fn(&IsosurfaceSlice) -> &DynamicBuffer| {
+            self.slices.iter().map(f).fold((0, 0), |(len, cap), b| {
+                (len + b.len(), cap + b.capacity())
+            })
+        };
+        let (vertex_len, vertex_cap) = sum(|s| &s.vertices);
+        let (index_len, index_cap) = sum(|s| &s.indices);
         vec![
-            (
+            ("Isosurface Vertices", vertex_len, vertex_cap),
+            ("Isosurface Indices", index_len, index_cap),
+        ]
+    }
+}
+
+/// GPU buffers of one isosurface mesh.
+struct IsosurfaceSlice {
+    key: SurfaceKey,
+    /// Primitive the indices describe.
+    topology: MeshTopology,
+    /// Triangle mesh carrying the [`isosurface_kind::CUTAWAY`] flag,
+    /// drawn two-sided. Lines and points are never culled anyway.
+    cutaway: bool,
+    vertices: DynamicBuffer,
+    indices: DynamicBuffer,
+    index_count: u32,
+}
+
+impl IsosurfaceSlice {
+    fn new(
+        device: &wgpu::Device,
+        key: SurfaceKey,
+        topology: MeshTopology,
+    ) -> Self {
+        Self {
+            key,
+            topology,
+            cutaway: false,
+            vertices: DynamicBuffer::new(
+                device,
                 "Isosurface Vertices",
-                self.vertex_buffer.len(),
-                self.vertex_buffer.capacity(),
+                0,
+                wgpu::BufferUsages::VERTEX,
             ),
-            (
+            indices: DynamicBuffer::new(
+                device,
                 "Isosurface Indices",
-                self.mesh_pass.index_buffer_len(),
-                self.mesh_pass.index_buffer_capacity(),
+                0,
+                wgpu::BufferUsages::INDEX,
             ),
-        ]
+            index_count: 0,
+        }
+    }
+
+    fn write(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        vertices: &[IsosurfaceVertex],
+        indices: &[u32],
+    ) {
+        let _ = self.vertices.write(device, queue, vertices);
+        let _ = self.indices.write(device, queue, indices);
+        self.index_count = indices.len() as u32;
+        // The flag is set on every vertex of a mesh or on none.
+        self.cutaway = self.topology == MeshTopology::Triangles
+            && vertices
+                .first()
+                .is_some_and(|v| v.kind & isosurface_kind::CUTAWAY != 0);
+    }
+
+    /// Bind this mesh's buffers and draw `copies` instances of it with
+    /// the current pipeline.
+    fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, copies: u32) {
+        render_pass.set_vertex_buffer(0, self.vertices.buffer().slice(..));
+        render_pass.set_index_buffer(
+            self.indices.buffer().slice(..),
+            wgpu::IndexFormat::Uint32,
+        );
+        render_pass.draw_indexed(0..self.index_count, 0, 0..copies);
     }
 }
 
@@ -0,0 +1,89 @@
+//! Per-vertex residue tags for entity surfaces.
+//!
+//! Each surface vertex takes the residue of its nearest atom, so the
+//! surface can be picked back to residues and colored per residue on
+//! the GPU. The nearest atom is found through a uniform grid of
+//! [`CELL_SIZE`] cells: an atom within one cell size of a vertex lies
+//! in the 27 cells around it, so the search only falls back to a full
+//! scan for vertices farther than that from every atom (coarse
+//! Gaussian blobs around sparse atoms).
+
+use glam::Vec3;
+
+use super::atom_grid::AtomGrid;
+use super::{isosurface_kind, IsosurfaceVertex};
+
+/// Edge length of a grid cell in Å. Larger than a vdW radius plus the
+/// SES probe, so almost every vertex finds its atom in the first shell.
+const CELL_SIZE: f32 = 4.0;
+
+/// Tag every vertex with the residue of its nearest atom.
+///
+/// `residues` is the pick ID of each atom's residue (0 for none), in
+/// the order of `positions`. With `recolor` the vertices also get the
+/// [`isosurface_kind::RESIDUE_COLOR`] flag so the shader colors them
+/// from the per-residue color buffer; their baked color stays as the
+/// fallback for untagged vertices.
+pub(crate) fn tag_residues(
+    vertices: &mut [IsosurfaceVertex],
+    positions: &[Vec3],
+    residues: &[u32],
+    recolor: bool,
+) {
+    let grid = AtomGrid::new(positions, CELL_SIZE);
+    for v in vertices {
+        let nearest = grid.nearest(Vec3::from_array(v.position));
+        v.residue = nearest.and_then(|i| residues.get(i)).copied().unwrap_or(0);
+        if recolor && v.residue != 0 {
+            v.kind |= isosurface_kind::RESIDUE_COLOR;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(position: [f32; 3]) -> IsosurfaceVertex {
+        IsosurfaceVertex {
+            position,
+            ..bytemuck::Zeroable::zeroed()
+        }
+    }
+
+    #[test]
+    fn vertices_take_the_nearest_atoms_residue() {
+        let positions = [Vec3::ZERO, Vec3::new(3.0, 0.0, 0.0)];
+        let mut vertices = vec![
+            vertex([-1.0, 0.5, 0.0]),
+            vertex([3.9, 0.0, -0.5]),
+            // Straddles a cell boundary; still nearer the second atom.
+            vertex([2.0, 0.0, 0.0]),
+        ];
+        tag_residues(&mut vertices, &positions, &[1, 7], false);
+        let tags: Vec<u32> = vertices.iter().map(|v| v.residue).collect();
+        assert_eq!(tags, vec![1, 7, 7]);
+        assert!(vertices.iter().all(|v| v.kind == isosurface_kind::SURFACE));
+    }
+
+    #[test]
+    fn distant_vertices_fall_back_to_a_full_scan() {
+        let positions = [Vec3::ZERO, Vec3::new(50.0, 0.0, 0.0)];
+        let mut vertices = vec![vertex([30.0, 0.0, 0.0])];
+        tag_residues(&mut vertices, &positions, &[1, 2], true);
+        assert_eq!(vertices[0].residue, 2);
+        assert_eq!(
+            vertices[0].kind,
+            isosurface_kind::SURFACE | isosurface_kind::RESIDUE_COLOR
+        );
+    }
+
+    #[test]
+    fn untagged_atoms_keep_the_baked_color() {
+        let positions = [Vec3::ZERO];
+        let mut vertices = vec![vertex([1.0, 0.0, 0.0])];
+        tag_residues(&mut vertices, &positions, &[0], true);
+        assert_eq!(vertices[0].residue, 0);
+        assert_eq!(vertices[0].kind, isosurface_kind::SURFACE);
+    }
+}
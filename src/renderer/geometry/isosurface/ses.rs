@@ -6,29 +6,33 @@
 //! rendering-bound half: marching cubes on the returned SDF and
 //! per-vertex mean-curvature coloring (blue = convex, red = concave).
 
+use std::sync::atomic::AtomicBool;
+
 use glam::Vec3;
 use molex::analysis::volumetric::compute_ses_sdf;
 
 use super::cpu_marching_cubes::extract_isosurface;
-use super::IsosurfaceVertex;
+use super::{cancelled, IsosurfaceVertex};
 
 /// Generate a solvent-excluded (Connolly) surface from atom positions.
 ///
 /// Vertices are colored by mean curvature: blue (convex) → white (flat)
-/// → red (concave). The `color` alpha channel controls opacity.
+/// → red (concave). The `color` alpha channel controls opacity. Returns
+/// an empty mesh once `cancel` is set.
 pub(crate) fn generate_ses(
     positions: &[Vec3],
     radii: &[f32],
     probe_radius: Option<f32>,
     resolution: f32,
     color: [f32; 4],
+    cancel: &AtomicBool,
 ) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
     if positions.is_empty() {
         return (Vec::new(), Vec::new());
     }
 
     let grid = compute_ses_sdf(positions, radii, probe_radius, resolution);
-    if grid.data.is_empty() {
+    if grid.data.is_empty() || cancelled(cancel) {
         return (Vec::new(), Vec::new());
     }
 
@@ -57,7 +61,11 @@ pub(crate) fn generate_ses(
             ]
         },
         color,
+        cancel,
     );
+    if cancelled(cancel) {
+        return (Vec::new(), Vec::new());
+    }
 
     apply_curvature_coloring(&mut verts, &idxs, color[3]);
 
@@ -199,7 +207,8 @@ mod tests {
 
     #[test]
     fn ses_empty_input() {
-        let (v, i) = generate_ses(&[], &[], None, 1.0, [1.0; 4]);
+        let (v, i) =
+            generate_ses(&[], &[], None, 1.0, [1.0; 4], &AtomicBool::default());
         assert!(v.is_empty());
         assert!(i.is_empty());
     }
@@ -208,8 +217,14 @@ mod tests {
     fn ses_single_atom_produces_surface() {
         let pos = vec![Vec3::ZERO];
         let radii = vec![1.5];
-        let (verts, idxs) =
-            generate_ses(&pos, &radii, Some(1.4), 0.5, [1.0; 4]);
+        let (verts, idxs) = generate_ses(
+            &pos,
+            &radii,
+            Some(1.4),
+            0.5,
+            [1.0; 4],
+            &AtomicBool::default(),
+        );
         assert!(!verts.is_empty(), "SES should produce vertices");
         assert!(!idxs.is_empty(), "SES should produce indices");
     }
@@ -219,8 +234,14 @@ mod tests {
         // Two atoms close enough that the probe fills the gap
         let pos = vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(3.0, 0.0, 0.0)];
         let radii = vec![1.5, 1.5];
-        let (verts, idxs) =
-            generate_ses(&pos, &radii, Some(1.4), 0.5, [1.0; 4]);
+        let (verts, idxs) = generate_ses(
+            &pos,
+            &radii,
+            Some(1.4),
+            0.5,
+            [1.0; 4],
+            &AtomicBool::default(),
+        );
         assert!(
             !verts.is_empty(),
             "SES should produce vertices for two atoms"
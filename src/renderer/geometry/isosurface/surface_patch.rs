@@ -0,0 +1,101 @@
+//! Cutting an entity surface down to a subset of its atoms.
+//!
+//! A pocket or domain surface is meshed over the whole entity, so the
+//! atoms left out still shape it (the SES probe cannot roll through
+//! them), and is then cut down to the primitives whose vertices all lie
+//! nearest an atom of the subset. The kept vertices are compacted.
+
+use glam::Vec3;
+
+use super::atom_grid::AtomGrid;
+use super::{IsosurfaceVertex, MeshTopology};
+
+/// Edge length of the nearest-atom grid cells in Å, as for residue
+/// tagging.
+const CELL_SIZE: f32 = 4.0;
+
+/// Keep the primitives of `(vertices, indices)` whose vertices are all
+/// nearest an atom flagged in `include` (parallel with `positions`).
+pub(crate) fn restrict_to_atoms(
+    vertices: &[IsosurfaceVertex],
+    indices: &[u32],
+    topology: MeshTopology,
+    positions: &[Vec3],
+    include: &[bool],
+) -> (Vec<IsosurfaceVertex>, Vec<u32>) {
+    let grid = AtomGrid::new(positions, CELL_SIZE);
+    let keep: Vec<bool> = vertices
+        .iter()
+        .map(|v| {
+            grid.nearest(Vec3::from_array(v.position))
+                .is_some_and(|atom| include.get(atom).copied().unwrap_or(false))
+        })
+        .collect();
+
+    let mut remap = vec![u32::MAX; vertices.len()];
+    let mut out_vertices = Vec::new();
+    let mut out_indices = Vec::new();
+    for primitive in indices.chunks_exact(topology.primitive_len()) {
+        if !primitive.iter().all(|&i| keep[i as usize]) {
+            continue;
+        }
+        for &i in primitive {
+            let slot = &mut remap[i as usize];
+            if *slot == u32::MAX {
+                *slot = out_vertices.len() as u32;
+                out_vertices.push(vertices[i as usize]);
+            }
+            out_indices.push(*slot);
+        }
+    }
+    (out_vertices, out_indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(x: f32) -> IsosurfaceVertex {
+        IsosurfaceVertex {
+            position: [x, 0.0, 0.0],
+            ..bytemuck::Zeroable::zeroed()
+        }
+    }
+
+    #[test]
+    fn keeps_primitives_wholly_on_included_atoms() {
+        let positions = [Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0)];
+        // Vertices 0-2 sit on the first atom, 3-4 on the second.
+        let vertices: Vec<_> = [0.5, -0.5, 1.0, 9.5, 10.5]
+            .into_iter()
+            .map(vertex)
+            .collect();
+        let indices = [0, 1, 2, 2, 3, 4, 3, 4, 1];
+        let (kept, idx) = restrict_to_atoms(
+            &vertices,
+            &indices,
+            MeshTopology::Triangles,
+            &positions,
+            &[true, false],
+        );
+        assert_eq!(idx, vec![0, 1, 2]);
+        assert_eq!(kept.len(), 3);
+        assert_eq!(kept[2].position, [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn compacts_points() {
+        let positions = [Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0)];
+        let vertices: Vec<_> =
+            [0.5, 9.5, 10.5].into_iter().map(vertex).collect();
+        let (kept, idx) = restrict_to_atoms(
+            &vertices,
+            &[0, 1, 2],
+            MeshTopology::Points,
+            &positions,
+            &[false, true],
+        );
+        assert_eq!(idx, vec![0, 1]);
+        assert_eq!(kept[0].position, [9.5, 0.0, 0.0]);
+    }
+}
@@ -23,6 +23,9 @@ pub(crate) mod pull;
 pub(crate) mod sheet_adjust;
 /// Capsule sidechain renderer.
 pub(crate) mod sidechain;
+/// Crystal unit-cell box renderer.
+pub(crate) mod unit_cell;
+pub(crate) mod volume;
 
 pub(crate) use backbone::BackboneRenderer;
 pub(crate) use ball_and_stick::{
@@ -33,3 +36,5 @@ pub(crate) use bond::BondRenderer;
 pub(crate) use nucleic_acid::NucleicAcidRenderer;
 pub(crate) use pull::PullRenderer;
 pub(crate) use sidechain::{SidechainRenderer, SidechainView};
+pub(crate) use unit_cell::UnitCellRenderer;
+pub(crate) use volume::VolumeRenderer;
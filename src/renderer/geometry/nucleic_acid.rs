@@ -8,6 +8,9 @@ use glam::Vec3;
 
 use crate::error::VisoError;
 use crate::gpu::{RenderContext, Shader, ShaderComposer};
+use crate::renderer::culling::{
+    group_instances, ChainCull, IndirectDraws, InstanceGroup, Slots,
+};
 use crate::renderer::entity_topology::ResolvedRing;
 use crate::renderer::impostor::{
     CapsuleInstance, ExtrudedPolygonInstance, ImpostorPass, ShaderDef,
@@ -31,6 +34,15 @@ const STEM_RADIUS: f32 = 0.25;
 /// coincidence is explicit and either can change without the other (T5-NA-B).
 const RING_HALF_THICKNESS: f32 = 0.25;
 
+/// Stem and ring instances of one entity, with per-chain culling
+/// groups over each.
+pub(crate) struct NaBaseInstances {
+    pub(crate) stems: Vec<CapsuleInstance>,
+    pub(crate) rings: Vec<ExtrudedPolygonInstance>,
+    pub(crate) stem_groups: Vec<InstanceGroup>,
+    pub(crate) ring_groups: Vec<InstanceGroup>,
+}
+
 /// Renders DNA/RNA base rings and stem tubes as impostors.
 pub(crate) struct NucleicAcidRenderer {
     stem_pass: ImpostorPass<CapsuleInstance>,
@@ -75,14 +87,38 @@ impl NucleicAcidRenderer {
         })
     }
 
-    /// Draw nucleic acid geometry into the given render pass.
+    /// Queue the stem and ring groups in view of `cull`.
+    pub(crate) fn plan(
+        &self,
+        indirect: &mut IndirectDraws,
+        cull: &ChainCull,
+    ) -> [Slots; 2] {
+        [
+            self.stem_pass.plan(indirect, cull),
+            self.ring_pass.plan(indirect, cull),
+        ]
+    }
+
+    /// Draw the slots queued by [`Self::plan`].
     pub(crate) fn draw<'a>(
         &'a self,
         render_pass: &mut wgpu::RenderPass<'a>,
         bind_groups: &crate::renderer::draw_context::DrawBindGroups<'a>,
+        indirect: &IndirectDraws,
+        slots: &[Slots; 2],
     ) {
-        self.stem_pass.draw(render_pass, bind_groups);
-        self.ring_pass.draw(render_pass, bind_groups);
+        self.stem_pass.draw_slots(
+            render_pass,
+            bind_groups,
+            indirect,
+            &slots[0],
+        );
+        self.ring_pass.draw_slots(
+            render_pass,
+            bind_groups,
+            indirect,
+            &slots[1],
+        );
     }
 
     /// Apply pre-computed instance data (GPU upload only, no CPU generation).
@@ -110,6 +146,8 @@ impl NucleicAcidRenderer {
             &na.ring_instances,
             na.ring_count,
         );
+        self.stem_pass.set_groups(na.stem_groups.clone());
+        self.ring_pass.set_groups(na.ring_groups.clone());
     }
 
     /// GPU buffer sizes: `(label, used_bytes, allocated_bytes)`.
@@ -133,9 +171,12 @@ impl NucleicAcidRenderer {
     /// opposite ways.
     pub(crate) fn generate_instances(
         rings: &[ResolvedRing],
-    ) -> (Vec<CapsuleInstance>, Vec<ExtrudedPolygonInstance>) {
+    ) -> NaBaseInstances {
         let mut stems = Vec::new();
         let mut ring_instances = Vec::new();
+        // Chain of every emitted stem and ring, for the culling groups.
+        let mut stem_chains = Vec::new();
+        let mut ring_chains = Vec::new();
 
         // Per-strand normal coherence: reset at every chain boundary,
         // then flip each base's normal into the prior base's hemisphere.
@@ -176,6 +217,7 @@ impl NucleicAcidRenderer {
                     color_a: [ring.color[0], ring.color[1], ring.color[2], 0.0],
                     color_b: [ring.color[0], ring.color[1], ring.color[2], 0.0],
                 });
+                stem_chains.push(resolved.chain_idx);
             } else {
                 missing_p += 1;
             }
@@ -201,6 +243,7 @@ impl NucleicAcidRenderer {
                     &mut ring_instances,
                 );
             }
+            ring_chains.resize(ring_instances.len(), resolved.chain_idx);
         }
 
         if degenerate_rings > 0 {
@@ -217,7 +260,12 @@ impl NucleicAcidRenderer {
             );
         }
 
-        (stems, ring_instances)
+        NaBaseInstances {
+            stem_groups: group_instances(&stems, |i| stem_chains[i]),
+            ring_groups: group_instances(&ring_instances, |i| ring_chains[i]),
+            stems,
+            rings: ring_instances,
+        }
     }
 }
 
@@ -325,7 +373,7 @@ mod tests {
         reversed.reverse(); // flips the raw Newell sign
 
         let rings = vec![resolved(forward, 0), resolved(reversed.clone(), 0)];
-        let (_stems, polys) = NucleicAcidRenderer::generate_instances(&rings);
+        let polys = NucleicAcidRenderer::generate_instances(&rings).rings;
         assert_eq!(polys.len(), 2, "both hex rings should emit");
 
         let n0 = Vec3::new(
@@ -348,7 +396,7 @@ mod tests {
         // chain keeps its raw (opposite) sign, proving the alignment is
         // per-strand and not a global force.
         let split = vec![resolved(hexagon(0.0), 0), resolved(reversed, 1)];
-        let (_s, polys2) = NucleicAcidRenderer::generate_instances(&split);
+        let polys2 = NucleicAcidRenderer::generate_instances(&split).rings;
         let m0 = Vec3::new(
             polys2[0].normal[0],
             polys2[0].normal[1],
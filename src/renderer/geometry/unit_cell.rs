@@ -0,0 +1,78 @@
+//! Unit-cell box renderer
+//!
+//! Draws the twelve edges of the crystal unit cell as thin capsules,
+//! using the same capsule impostor shader as bands. Always drawn with
+//! the main camera, so the box stays at the origin cell while symmetry
+//! copies are drawn around it.
+
+use glam::Vec3;
+
+use crate::error::VisoError;
+use crate::gpu::{RenderContext, Shader, ShaderComposer};
+use crate::renderer::impostor::{CapsuleInstance, ImpostorPass, ShaderDef};
+
+const EDGE_COLOR: [f32; 3] = [0.85, 0.85, 0.3];
+const EDGE_RADIUS: f32 = 0.15;
+/// Residue index far outside any real scene, so edges never pick up
+/// hover or selection highlighting. Exactly representable in `f32`.
+const NO_RESIDUE: f32 = 1.0e9;
+
+/// Renders the unit-cell box as capsule impostors.
+pub(crate) struct UnitCellRenderer {
+    pass: ImpostorPass<CapsuleInstance>,
+}
+
+impl UnitCellRenderer {
+    /// Create a new unit-cell renderer with no edges.
+    pub(crate) fn new(
+        context: &RenderContext,
+        layouts: &crate::renderer::PipelineLayouts,
+        shader_composer: &mut ShaderComposer,
+    ) -> Result<Self, VisoError> {
+        let pass = ImpostorPass::new(
+            context,
+            &ShaderDef {
+                label: "Unit Cell",
+                shader: Shader::Capsule,
+            },
+            layouts,
+            6,
+            shader_composer,
+        )?;
+        Ok(Self { pass })
+    }
+
+    /// Replace the drawn edges; `None` hides the box.
+    pub(crate) fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        edges: Option<&[(Vec3, Vec3)]>,
+    ) {
+        let instances: Vec<CapsuleInstance> = edges
+            .unwrap_or_default()
+            .iter()
+            .map(|(a, b)| CapsuleInstance {
+                endpoint_a: [a.x, a.y, a.z, EDGE_RADIUS],
+                endpoint_b: [b.x, b.y, b.z, NO_RESIDUE],
+                color_a: [EDGE_COLOR[0], EDGE_COLOR[1], EDGE_COLOR[2], 0.0],
+                color_b: [EDGE_COLOR[0], EDGE_COLOR[1], EDGE_COLOR[2], 0.0],
+            })
+            .collect();
+        let _ = self.pass.write_instances(device, queue, &instances);
+    }
+
+    /// GPU buffer sizes: `(label, used_bytes, allocated_bytes)`.
+    pub(crate) fn buffer_info(&self) -> Vec<(&'static str, usize, usize)> {
+        vec![self.pass.buffer_info("Unit Cell Capsules")]
+    }
+
+    /// Draw the cell edges into the given render pass.
+    pub(crate) fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        bind_groups: &crate::renderer::draw_context::DrawBindGroups<'a>,
+    ) {
+        self.pass.draw(render_pass, bind_groups);
+    }
+}
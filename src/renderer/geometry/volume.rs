@@ -0,0 +1,658 @@
+//! Direct volume rendering of density maps.
+//!
+//! A map in volume style is uploaded once as an `R16Float` 3D texture
+//! (box-averaged down to at most [`MAX_VOLUME_DIM`] texels per axis) and
+//! drawn by ray-marching its grid box after the geometry pass. Each
+//! sample is looked up in a baked transfer function — a palette plus an
+//! opacity curve over a sigma range — and composited front to back.
+//! Rays stop at the depth of the opaque geometry already drawn, read
+//! from the scene depth buffer, so the volume sits correctly around
+//! cartoons and atoms.
+
+use glam::{Mat4, Vec3};
+use molex::entity::surface::Density;
+
+use crate::error::VisoError;
+use crate::gpu::pipeline_helpers::{
+    depth_texture_2d, filtering_sampler, texture_2d,
+};
+use crate::gpu::{RenderContext, Shader, ShaderComposer};
+use crate::options::PalettePreset;
+
+/// Largest texture extent per axis; bigger maps are box-averaged down.
+pub(crate) const MAX_VOLUME_DIM: usize = 256;
+
+/// Entries of the baked transfer-function lookup texture.
+const LUT_SIZE: usize = 256;
+
+/// Hard cap on ray-march steps per pixel, mirrored in
+/// `density_volume.wgsl`.
+const MAX_STEPS: f32 = 768.0;
+
+/// Density-to-color-and-opacity mapping of a volume-rendered map.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TransferFunction {
+    /// Palette sampled as a gradient across the range.
+    pub(crate) palette: PalettePreset,
+    /// Density mapped to the start of the palette and opacity curve,
+    /// in standard deviations above the map mean.
+    pub(crate) low_sigma: f32,
+    /// Density mapped to the end of the palette and opacity curve.
+    pub(crate) high_sigma: f32,
+    /// Opacity curve as `[position, opacity]` points, `position` in
+    /// `[0, 1]` across the range and opacity per Ångström of ray.
+    /// Linearly interpolated; below the range the opacity is zero.
+    pub(crate) opacity: Vec<[f32; 2]>,
+}
+
+impl Default for TransferFunction {
+    fn default() -> Self {
+        Self {
+            palette: PalettePreset::Viridis,
+            low_sigma: 1.0,
+            high_sigma: 8.0,
+            opacity: vec![[0.0, 0.0], [0.25, 0.04], [1.0, 0.5]],
+        }
+    }
+}
+
+impl TransferFunction {
+    /// Bake into `LUT_SIZE` RGBA entries.
+    fn bake(&self) -> Vec<[f32; 4]> {
+        let palette = crate::options::Palette {
+            preset: self.palette.clone(),
+            ..Default::default()
+        };
+        (0..LUT_SIZE)
+            .map(|i| {
+                let t = i as f32 / (LUT_SIZE - 1) as f32;
+                let [r, g, b] = palette.sample_gradient(t);
+                [r, g, b, opacity_at(&self.opacity, t).clamp(0.0, 1.0)]
+            })
+            .collect()
+    }
+}
+
+/// Piecewise-linear opacity curve sampled at `t`, flat past its ends.
+fn opacity_at(points: &[[f32; 2]], t: f32) -> f32 {
+    let Some(first) = points.first() else {
+        return 0.0;
+    };
+    if t <= first[0] {
+        return first[1];
+    }
+    for pair in points.windows(2) {
+        let ([t0, a0], [t1, a1]) = (pair[0], pair[1]);
+        if t <= t1 {
+            let f = if t1 > t0 { (t - t0) / (t1 - t0) } else { 1.0 };
+            return (a1 - a0).mul_add(f, a0);
+        }
+    }
+    points.last().map_or(0.0, |p| p[1])
+}
+
+/// Box-average `data` (C order, `dims` = `[nx, ny, nz]`) by `stride`
+/// along every axis. Returns the texels, z fastest, and their extent.
+fn downsample(
+    data: &[f32],
+    dims: [usize; 3],
+    stride: usize,
+) -> (Vec<half::f16>, [usize; 3]) {
+    let out = dims.map(|n| n.div_ceil(stride));
+    let mut texels = Vec::with_capacity(out[0] * out[1] * out[2]);
+    for x in 0..out[0] {
+        for y in 0..out[1] {
+            for z in 0..out[2] {
+                let mean = block_mean(data, dims, stride, [x, y, z]);
+                texels.push(half::f16::from_f32(mean));
+            }
+        }
+    }
+    (texels, out)
+}
+
+/// Mean of the `stride`-sized block of grid values behind `texel`.
+fn block_mean(
+    data: &[f32],
+    dims: [usize; 3],
+    stride: usize,
+    texel: [usize; 3],
+) -> f32 {
+    let range = |axis: usize| {
+        texel[axis] * stride..((texel[axis] + 1) * stride).min(dims[axis])
+    };
+    let mut sum = 0.0;
+    let mut count = 0.0;
+    for gx in range(0) {
+        for gy in range(1) {
+            let row = (gx * dims[1] + gy) * dims[2];
+            let z = range(2);
+            if let Some(values) = data.get(row + z.start..row + z.end) {
+                sum += values.iter().sum::<f32>();
+                count += values.len() as f32;
+            }
+        }
+    }
+    if count > 0.0 {
+        sum / count
+    } else {
+        0.0
+    }
+}
+
+/// Affine map from texel-grid coordinates to world space, probed from
+/// the map. Texel-grid axes are grid `(z, y, x)`, the order of the
+/// texture's `(u, v, w)` since the data is z-fastest.
+fn texel_grid_to_world(map: &Density) -> Mat4 {
+    let o = Vec3::from(map.grid_to_cartesian_f32(0.0, 0.0, 0.0));
+    let axis = |x, y, z| Vec3::from(map.grid_to_cartesian_f32(x, y, z)) - o;
+    Mat4::from_cols(
+        axis(0.0, 0.0, 1.0).extend(0.0),
+        axis(0.0, 1.0, 0.0).extend(0.0),
+        axis(1.0, 0.0, 0.0).extend(0.0),
+        o.extend(1.0),
+    )
+}
+
+/// Texel-grid box `(min, max)` spanned by the texture. Texel `i` of a
+/// `stride`-averaged axis covers grid `i * stride ..`, so the texture's
+/// `[0, 1]` runs from grid `-0.5` to `stride * n - 0.5`.
+fn texel_box(extent: [usize; 3], stride: usize) -> (Vec3, Vec3) {
+    let span = |n: usize| (stride * n) as f32 - 0.5;
+    (
+        Vec3::splat(-0.5),
+        Vec3::new(span(extent[2]), span(extent[1]), span(extent[0])),
+    )
+}
+
+/// A map to draw: `(id, store generation, map, transfer function)`.
+pub(crate) type VolumeSource<'a> =
+    (u32, u64, &'a Density, &'a TransferFunction);
+
+/// Uniforms of one volume draw, mirrored in `density_volume.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct VolumeUniform {
+    world_to_grid: [[f32; 4]; 4],
+    grid_to_world: [[f32; 4]; 4],
+    box_min: [f32; 3],
+    /// Ray step in Ångström.
+    step: f32,
+    box_max: [f32; 3],
+    /// Gradient sampling offset in texture space.
+    gradient_epsilon: f32,
+    /// Raw density at the start and end of the transfer range.
+    value_range: [f32; 2],
+    /// 1 when the texel-grid axes are left-handed in world space.
+    mirrored: u32,
+    _pad: u32,
+}
+
+/// GPU state of one volume-rendered map.
+struct VolumeSlot {
+    id: u32,
+    /// Store generation the uniforms and transfer function were built at.
+    generation: u64,
+    /// Texel-grid placement, kept to rebuild the uniforms.
+    grid_to_world: Mat4,
+    box_min: Vec3,
+    box_max: Vec3,
+    step: f32,
+    gradient_epsilon: f32,
+    uniform: wgpu::Buffer,
+    lut: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    texture_bytes: usize,
+}
+
+/// Ray-marching renderer for volume-style density maps.
+pub(crate) struct VolumeRenderer {
+    pipeline: wgpu::RenderPipeline,
+    volume_layout: wgpu::BindGroupLayout,
+    depth_layout: wgpu::BindGroupLayout,
+    depth_bind_group: wgpu::BindGroup,
+    sampler: wgpu::Sampler,
+    slots: Vec<VolumeSlot>,
+}
+
+impl VolumeRenderer {
+    /// Create the renderer. `depth_view` is the scene depth buffer the
+    /// rays stop at; rebind it with [`Self::set_depth_view`] on resize.
+    pub(crate) fn new(
+        context: &RenderContext,
+        camera_layout: &wgpu::BindGroupLayout,
+        shader_composer: &mut ShaderComposer,
+        depth_view: &wgpu::TextureView,
+    ) -> Result<Self, VisoError> {
+        let device = &context.device;
+        let volume_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Density Volume Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX
+                            | wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: true,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::D3,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    filtering_sampler(2),
+                    texture_2d(3),
+                ],
+            });
+        let depth_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Density Volume Depth Layout"),
+                entries: &[depth_texture_2d(0)],
+            });
+
+        let shader = shader_composer.compose(device, Shader::DensityVolume)?;
+        let layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Density Volume Pipeline Layout"),
+                bind_group_layouts: &[
+                    camera_layout,
+                    &volume_layout,
+                    &depth_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+        let pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Density Volume"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Rgba16Float,
+                        blend: Some(
+                            wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+                        ),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                // Back faces only, so the box still draws with the
+                // camera inside it.
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    cull_mode: Some(wgpu::Face::Front),
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Density Volume Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let depth_bind_group =
+            create_depth_bind_group(device, &depth_layout, depth_view);
+
+        Ok(Self {
+            pipeline,
+            volume_layout,
+            depth_layout,
+            depth_bind_group,
+            sampler,
+            slots: Vec::new(),
+        })
+    }
+
+    /// Rebind the scene depth buffer after it was recreated.
+    pub(crate) fn set_depth_view(
+        &mut self,
+        device: &wgpu::Device,
+        view: &wgpu::TextureView,
+    ) {
+        self.depth_bind_group =
+            create_depth_bind_group(device, &self.depth_layout, view);
+    }
+
+    /// Bring the GPU volumes in line with `maps`, the `(id, store
+    /// generation, map, transfer function)` of every map to draw. Maps
+    /// are uploaded the first time they appear; later generations only
+    /// rebuild the uniforms and transfer function.
+    pub(crate) fn sync<'a>(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        maps: impl Iterator<Item = VolumeSource<'a>>,
+    ) {
+        let mut keep = Vec::new();
+        for (id, generation, map, transfer) in maps {
+            keep.push(id);
+            let at =
+                if let Some(at) = self.slots.iter().position(|s| s.id == id) {
+                    at
+                } else {
+                    let Some(slot) = self.upload(device, queue, id, map) else {
+                        continue;
+                    };
+                    self.slots.push(slot);
+                    self.slots.len() - 1
+                };
+            let slot = &mut self.slots[at];
+            if slot.generation != generation {
+                slot.generation = generation;
+                write_transfer(queue, slot, map, transfer);
+            }
+        }
+        self.slots.retain(|s| keep.contains(&s.id));
+    }
+
+    /// Upload `map` as a 3D texture and build its bind group.
+    fn upload(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        id: u32,
+        map: &Density,
+    ) -> Option<VolumeSlot> {
+        let Some(data) = map.data.as_slice() else {
+            log::warn!("density map data is not contiguous; skipping volume");
+            return None;
+        };
+        let dims = [map.nx, map.ny, map.nz];
+        let stride = dims
+            .iter()
+            .map(|n| n.div_ceil(MAX_VOLUME_DIM))
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let (texels, extent) = downsample(data, dims, stride);
+        log::info!(
+            "density volume id={id}: grid {dims:?} -> texture {extent:?} \
+             (stride {stride})"
+        );
+
+        let size = wgpu::Extent3d {
+            width: extent[2] as u32,
+            height: extent[1] as u32,
+            depth_or_array_layers: extent[0] as u32,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Density Volume"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D3,
+            format: wgpu::TextureFormat::R16Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &f16_bytes(&texels),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(size.width * 2),
+                rows_per_image: Some(size.height),
+            },
+            size,
+        );
+
+        let lut = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Density Volume Transfer"),
+            size: wgpu::Extent3d {
+                width: LUT_SIZE as u32,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba16Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let uniform = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Density Volume Uniform"),
+            size: size_of::<VolumeUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let volume_view = texture.create_view(&Default::default());
+        let lut_view = lut.create_view(&Default::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Density Volume Bind Group"),
+            layout: &self.volume_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&volume_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&lut_view),
+                },
+            ],
+        });
+
+        let voxel = map.voxel_size();
+        let min_voxel = voxel[0].min(voxel[1]).min(voxel[2]) * stride as f32;
+        let grid_to_world = texel_grid_to_world(map);
+        let (box_min, box_max) = texel_box(extent, stride);
+        // Half a texel per step, but never more steps than the cap
+        // across the box diagonal.
+        let span = grid_to_world
+            .transform_point3(box_min)
+            .distance(grid_to_world.transform_point3(box_max));
+        let step = (min_voxel * 0.5).max(span / MAX_STEPS).max(0.05);
+        let gradient_epsilon =
+            1.0 / extent.iter().copied().max().unwrap_or(1) as f32;
+
+        Some(VolumeSlot {
+            id,
+            generation: 0,
+            grid_to_world,
+            box_min,
+            box_max,
+            step,
+            gradient_epsilon,
+            uniform,
+            lut,
+            bind_group,
+            texture_bytes: texels.len() * 2,
+        })
+    }
+
+    /// Ray-march every volume into `color_view` (the HDR scene color,
+    /// loaded, not cleared) with the main camera.
+    pub(crate) fn encode(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        color_view: &wgpu::TextureView,
+        camera: &wgpu::BindGroup,
+    ) {
+        if self.slots.is_empty() {
+            return;
+        }
+        let mut rp = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("density volume pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: color_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            ..Default::default()
+        });
+        rp.set_pipeline(&self.pipeline);
+        rp.set_bind_group(0, camera, &[]);
+        rp.set_bind_group(2, &self.depth_bind_group, &[]);
+        for slot in &self.slots {
+            rp.set_bind_group(1, &slot.bind_group, &[]);
+            rp.draw(0..36, 0..1);
+        }
+    }
+
+    /// GPU buffer sizes: `(label, used_bytes, allocated_bytes)`.
+    pub(crate) fn buffer_info(&self) -> Vec<(&'static str, usize, usize)> {
+        let bytes: usize = self.slots.iter().map(|s| s.texture_bytes).sum();
+        vec![("Density Volumes", bytes, bytes)]
+    }
+}
+
+/// Write the uniforms and baked transfer function of `slot`.
+fn write_transfer(
+    queue: &wgpu::Queue,
+    slot: &VolumeSlot,
+    map: &Density,
+    transfer: &TransferFunction,
+) {
+    let uniform = VolumeUniform {
+        world_to_grid: slot.grid_to_world.inverse().to_cols_array_2d(),
+        grid_to_world: slot.grid_to_world.to_cols_array_2d(),
+        box_min: slot.box_min.to_array(),
+        step: slot.step,
+        box_max: slot.box_max.to_array(),
+        gradient_epsilon: slot.gradient_epsilon,
+        value_range: [
+            map.sigma_level(transfer.low_sigma),
+            map.sigma_level(transfer.high_sigma),
+        ],
+        mirrored: u32::from(slot.grid_to_world.determinant() < 0.0),
+        _pad: 0,
+    };
+    queue.write_buffer(&slot.uniform, 0, bytemuck::bytes_of(&uniform));
+
+    let lut: Vec<half::f16> = transfer
+        .bake()
+        .into_iter()
+        .flatten()
+        .map(half::f16::from_f32)
+        .collect();
+    queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture: &slot.lut,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &f16_bytes(&lut),
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(LUT_SIZE as u32 * 8),
+            rows_per_image: Some(1),
+        },
+        wgpu::Extent3d {
+            width: LUT_SIZE as u32,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+/// Little-endian bytes of half-float texels, as the GPU expects them.
+fn f16_bytes(values: &[half::f16]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn create_depth_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    view: &wgpu::TextureView,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Density Volume Depth Bind Group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(view),
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downsampling_averages_each_block() {
+        // 4×2×2 grid, value = x.
+        let dims = [4, 2, 2];
+        let data: Vec<f32> = (0..16).map(|i| (i / 4) as f32).collect();
+        let (texels, extent) = downsample(&data, dims, 2);
+        assert_eq!(extent, [2, 1, 1]);
+        let values: Vec<f32> = texels.iter().map(|t| t.to_f32()).collect();
+        assert_eq!(values, vec![0.5, 2.5]);
+    }
+
+    #[test]
+    fn texel_box_puts_grid_points_at_texel_centers() {
+        let (min, max) = texel_box([2, 4, 8], 1);
+        let uvw = |g: Vec3| (g - min) / (max - min);
+        // Grid point 0 is the first texel center on every axis.
+        assert_eq!(uvw(Vec3::ZERO), Vec3::new(0.5 / 8.0, 0.5 / 4.0, 0.5 / 2.0));
+        // Texture u follows grid z; the last point is the last center.
+        assert!((uvw(Vec3::new(7.0, 0.0, 0.0)).x - 7.5 / 8.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn strided_box_covers_the_whole_grid() {
+        // 5 points averaged by 2 → 3 texels spanning grid -0.5 .. 5.5.
+        let (min, max) = texel_box([3, 3, 3], 2);
+        assert_eq!(min, Vec3::splat(-0.5));
+        assert_eq!(max, Vec3::splat(5.5));
+    }
+
+    #[test]
+    fn opacity_curve_interpolates_and_clamps() {
+        let curve = [[0.2, 0.0], [0.6, 0.4]];
+        assert!(opacity_at(&curve, 0.0).abs() < 1e-6);
+        assert!((opacity_at(&curve, 0.4) - 0.2).abs() < 1e-6);
+        assert!((opacity_at(&curve, 1.0) - 0.4).abs() < 1e-6);
+        assert!(opacity_at(&[], 0.5).abs() < 1e-6);
+    }
+}
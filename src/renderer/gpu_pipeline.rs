@@ -6,13 +6,15 @@ use glam::{Mat4, Vec3};
 
 use crate::camera::controller::CameraController;
 use crate::camera::core::Camera;
+use crate::engine::density_store::DensityStore;
 use crate::engine::positions::EntityPositions;
+use crate::engine::surface_regen::MeshMessage;
 use crate::gpu::lighting::Lighting;
 use crate::gpu::{RenderContext, ShaderComposer};
 use crate::options::{GeometryOptions, LightingOptions, VisoOptions};
 use crate::renderer::draw_context::DrawBindGroups;
-use crate::renderer::geometry::isosurface::IsosurfaceVertex;
 use crate::renderer::geometry::{PreparedBallAndStickData, SidechainView};
+use crate::renderer::model_copies::{CopyTransforms, ModelCopies};
 use crate::renderer::picking::PickingSystem;
 use crate::renderer::pipeline::prepared::{
     AnimationFrameBody, PreparedRebuild,
@@ -60,19 +62,48 @@ pub(crate) struct GpuPipeline {
     /// Receiver for background-extracted isosurface meshes (density
     /// maps, entity surfaces, cavities). The matching sender lives on
     /// [`crate::engine::surface_regen::SurfaceRegen`].
-    pub(crate) density_rx: mpsc::Receiver<(Vec<IsosurfaceVertex>, Vec<u32>)>,
+    pub(crate) density_rx: mpsc::Receiver<MeshMessage>,
+    /// Rigid copies of the scene drawn for symmetry views.
+    pub(crate) copies: ModelCopies,
 }
 
 impl GpuPipeline {
     /// Core render -- geometry, post-process, picking -- targeting the given
     /// view. Returns the encoder so the caller can submit it.
+    ///
+    /// With `occlusion_culling`, the culled draws are first tested
+    /// against the depth pyramid of the previous frame, and this frame's
+    /// depth is reduced into a new pyramid after the geometry pass.
     pub(crate) fn render_to_view(
         &mut self,
         view: &wgpu::TextureView,
         camera: &CameraController,
         show_sidechains: bool,
+        occlusion_culling: bool,
     ) -> wgpu::CommandEncoder {
         let mut encoder = self.context.create_encoder();
+        self.copies.prepare(
+            &self.context.device,
+            camera,
+            self.renderers.backbone.instances(),
+        );
+        let draw = self.copies.draws(
+            &camera.bind_group,
+            camera.uniform.view_proj,
+            camera.camera.eye,
+        );
+        let plan = self.renderers.plan_draws(
+            &self.context.device,
+            &self.context.queue,
+            &draw,
+        );
+        if occlusion_culling {
+            self.renderers.hiz.occlude(
+                &self.context,
+                &mut encoder,
+                &self.renderers.indirect,
+            );
+        }
 
         // Geometry pass
         let input = GeometryPassInput {
@@ -83,22 +114,38 @@ impl GpuPipeline {
         };
         let bind_groups = DrawBindGroups {
             camera: &camera.bind_group,
+            copies: 1,
             lighting: &self.lighting.bind_group,
             selection: &self.pick.selection.bind_group,
             color: Some(&self.pick.residue_colors.bind_group),
         };
-        let frustum = camera.frustum();
         self.renderers.encode_isosurface_backface_pass(
             &mut encoder,
             &self.post_process.backface_depth_view,
-            &camera.bind_group,
+            &draw,
         );
         self.renderers.encode_geometry_pass(
             &mut encoder,
             &input,
             &bind_groups,
-            &frustum,
+            &draw,
+            &plan,
+        );
+        self.renderers.volume.encode(
+            &mut encoder,
+            self.post_process.color_view(),
+            &camera.bind_group,
         );
+        if occlusion_culling {
+            self.renderers.hiz.build(
+                &self.context,
+                &mut encoder,
+                &self.post_process.depth_view,
+                camera.uniform.view_proj,
+            );
+        } else {
+            self.renderers.hiz.invalidate();
+        }
 
         // Post-processing: SSAO -> bloom -> composite -> FXAA
         let cam = &camera.camera;
@@ -115,8 +162,11 @@ impl GpuPipeline {
         );
 
         // GPU Picking pass
-        let picking_geometry =
-            self.pick.build_geometry(&self.renderers, show_sidechains);
+        let picking_geometry = self.pick.build_geometry(
+            &self.renderers,
+            &self.copies,
+            show_sidechains,
+        );
         self.pick.picking.render(
             &mut encoder,
             &camera.bind_group,
@@ -135,6 +185,10 @@ impl GpuPipeline {
             &self.context.device,
             &self.post_process.backface_depth_view,
         );
+        self.renderers.volume.set_depth_view(
+            &self.context.device,
+            &self.post_process.depth_view,
+        );
         self.pick
             .picking
             .resize(&self.context.device, width, height);
@@ -148,6 +202,13 @@ impl GpuPipeline {
         suppress_sidechains: bool,
         scene: &SceneChainData<'_>,
     ) {
+        if let Some(colors) = &prepared.backbone.instance_colors {
+            self.renderers.backbone.set_instance_colors(
+                &self.context.device,
+                &self.context.queue,
+                colors,
+            );
+        }
         if animating {
             self.renderers
                 .backbone
@@ -163,9 +224,12 @@ impl GpuPipeline {
                 prepared.backbone.ribbon_index_count,
                 prepared.backbone.sheet_offsets.clone(),
                 prepared.backbone.chain_ranges.clone(),
+                &prepared.backbone.coarse_instances,
+                prepared.backbone.instances.clone(),
                 scene.backbone_chains,
                 scene.na_chains,
             );
+            self.scene_processor.invalidate_frames();
             if !suppress_sidechains {
                 let _ = self.renderers.sidechain.apply_prepared(
                     &self.context.device,
@@ -188,6 +252,8 @@ impl GpuPipeline {
                 sphere_count: prepared.bns.sphere_count,
                 capsule_bytes: &prepared.bns.capsule_instances,
                 capsule_count: prepared.bns.capsule_count,
+                sphere_groups: &prepared.bns.sphere_groups,
+                capsule_groups: &prepared.bns.capsule_groups,
             },
         );
         self.renderers.nucleic_acid.apply_prepared(
@@ -195,6 +261,7 @@ impl GpuPipeline {
             &self.context.queue,
             &prepared.na,
         );
+        self.copies.set_pick_ids(&prepared.pick_map);
         self.pick.pick_map = Some(prepared.pick_map.clone());
         self.pick.groups.rebuild_all(
             &self.pick.picking,
@@ -212,11 +279,16 @@ impl GpuPipeline {
             return false;
         };
 
-        self.renderers.backbone.apply_mesh(
+        let complete = self.renderers.backbone.apply_mesh(
             &self.context.device,
             &self.context.queue,
             prepared.backbone,
         );
+        if complete {
+            self.scene_processor.mark_frame_applied(prepared.sequence);
+        } else {
+            self.scene_processor.invalidate_frames();
+        }
 
         if let Some(ref instances) = prepared.sidechain_instances {
             let reallocated = self.renderers.sidechain.apply_prepared(
@@ -267,6 +339,7 @@ impl GpuPipeline {
     /// buffer limit, then each chain is further scaled by its distance tier.
     /// For very large structures (>50 K residues) this per-chain scaling is
     /// critical -- without it the vertex buffer can exceed GPU limits.
+    /// Chains past the coarse LOD cutoff are not tessellated at all.
     pub(crate) fn submit_lod_remesh(
         &self,
         camera_eye: Vec3,
@@ -280,7 +353,7 @@ impl GpuPipeline {
         if self.scene_processor.is_rebuild_pending() {
             return;
         }
-        use crate::options::{lod_scaled, select_chain_lod_tier};
+        use crate::options::lod_scaled;
 
         // Use clamped geometry as the base for LOD scaling
         let total_residues =
@@ -298,14 +371,9 @@ impl GpuPipeline {
         let max_csv = base_geo.cross_section_verts;
 
         let per_chain_lod: Vec<crate::options::ChainLod> = self
-            .renderers
-            .backbone
-            .chain_ranges()
-            .iter()
-            .map(|r| {
-                let tier = select_chain_lod_tier(r.bounding_center, camera_eye);
-                lod_scaled(max_spr, max_csv, tier)
-            })
+            .chain_lod_tiers(camera_eye, geometry)
+            .into_iter()
+            .map(|tier| lod_scaled(max_spr, max_csv, tier))
             .collect();
 
         self.scene_processor
@@ -329,21 +397,13 @@ impl GpuPipeline {
         geometry: &GeometryOptions,
         positions: &EntityPositions,
     ) {
+        self.renderers
+            .backbone
+            .set_coarse_tiers(geometry.coarse_tiers());
         if self.scene_processor.is_rebuild_pending() {
             return;
         }
-        let per_chain_tiers: Vec<u8> = self
-            .renderers
-            .backbone
-            .chain_ranges()
-            .iter()
-            .map(|r| {
-                crate::options::select_chain_lod_tier(
-                    r.bounding_center,
-                    camera_eye,
-                )
-            })
-            .collect();
+        let per_chain_tiers = self.chain_lod_tiers(camera_eye, geometry);
         if per_chain_tiers != self.renderers.backbone.cached_lod_tiers() {
             self.renderers
                 .backbone
@@ -352,6 +412,22 @@ impl GpuPipeline {
         }
     }
 
+    /// LOD tier of every backbone chain from its nearest drawn copy
+    /// (scene copies and instanced entity copies included), so a
+    /// prototype stays tessellated while any of its copies is close.
+    fn chain_lod_tiers(
+        &self,
+        camera_eye: Vec3,
+        geometry: &GeometryOptions,
+    ) -> Vec<u8> {
+        self.renderers
+            .backbone
+            .chain_distances(camera_eye, &self.copies.scene_models())
+            .into_iter()
+            .map(|d| geometry.chain_lod_tier(d))
+            .collect()
+    }
+
     /// Push lighting options to the GPU uniform.
     pub(crate) fn apply_lighting(&mut self, lo: &LightingOptions) {
         self.lighting.apply_options(lo, &self.context.queue);
@@ -369,30 +445,33 @@ impl GpuPipeline {
         self.lighting.update_gpu(&self.context.queue);
     }
 
-    /// Poll for pending density mesh data and upload to GPU.
+    /// Poll for pending isosurface meshes and upload them to the GPU.
     ///
-    /// Drains all queued results and only applies the latest one,
-    /// so rapid slider changes don't queue up stale meshes.
+    /// Drains all queued results. Each replaces only its own mesh, and
+    /// results older than one already applied for the same surface are
+    /// ignored.
     pub(crate) fn apply_pending_density_mesh(&mut self) -> bool {
-        let mut latest = None;
-        while let Ok(data) = self.density_rx.try_recv() {
-            latest = Some(data);
+        let mut applied = false;
+        while let Ok(message) = self.density_rx.try_recv() {
+            applied |= self.renderers.isosurface.apply_prepared(
+                &self.context.device,
+                &self.context.queue,
+                message,
+            );
         }
-        let Some((vertices, indices)) = latest else {
-            return false;
-        };
-        log::info!(
-            "applying density mesh: {} verts, {} indices",
-            vertices.len(),
-            indices.len()
-        );
-        self.renderers.isosurface.apply_prepared(
+        applied
+    }
+
+    /// Bring the ray-marched density volumes in line with the
+    /// volume-style maps of `density`.
+    pub(crate) fn sync_density_volumes(&mut self, density: &DensityStore) {
+        self.renderers.volume.sync(
             &self.context.device,
             &self.context.queue,
-            &vertices,
-            &indices,
+            density
+                .volume_entries()
+                .map(|(id, e)| (id, e.generation, &*e.map, &e.transfer)),
         );
-        true
     }
 
     /// Stop the background scene processor thread.
@@ -429,14 +508,14 @@ impl GpuPipeline {
     pub(crate) fn upload_frustum_culled_sidechains(
         &mut self,
         view: &SidechainView,
-        frustum: &crate::camera::frustum::Frustum,
+        frustum: Option<&crate::camera::frustum::Frustum>,
         per_residue_colors: Option<&[[f32; 3]]>,
     ) {
         self.renderers.sidechain.update_with_frustum(
             &self.context.device,
             &self.context.queue,
             view,
-            Some(frustum),
+            frustum,
             per_residue_colors,
         );
         self.pick.groups.rebuild_capsule(
@@ -446,6 +525,23 @@ impl GpuPipeline {
         );
     }
 
+    /// Replace the scene-copy transforms (none = draw the scene once).
+    pub(crate) fn set_model_copies(&mut self, transforms: CopyTransforms) {
+        self.copies.set(transforms);
+        // Sidechain culling depends on whether copies are drawn; force a
+        // re-cull on the next frame.
+        self.last_cull_camera_eye = Vec3::splat(f32::INFINITY);
+    }
+
+    /// Show the unit-cell box with the given edges, or hide it.
+    pub(crate) fn set_unit_cell(&mut self, edges: Option<&[(Vec3, Vec3)]>) {
+        self.renderers.unit_cell.update(
+            &self.context.device,
+            &self.context.queue,
+            edges,
+        );
+    }
+
     /// Record the camera eye at which frustum culling last ran. Used
     /// by the engine to gate re-culling on camera motion.
     pub(crate) fn set_last_cull_camera_eye(&mut self, eye: Vec3) {
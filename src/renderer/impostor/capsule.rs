@@ -12,3 +12,13 @@ pub(crate) struct CapsuleInstance {
     /// Color at endpoint B (RGB), w unused
     pub(crate) color_b: [f32; 4],
 }
+
+impl super::Bounded for CapsuleInstance {
+    fn bounding_sphere(&self) -> (glam::Vec3, f32) {
+        let [ax, ay, az, radius] = self.endpoint_a;
+        let [bx, by, bz, _] = self.endpoint_b;
+        let a = glam::Vec3::new(ax, ay, az);
+        let b = glam::Vec3::new(bx, by, bz);
+        ((a + b) * 0.5, a.distance(b) * 0.5 + radius)
+    }
+}
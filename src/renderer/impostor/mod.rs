@@ -13,14 +13,24 @@ pub(crate) mod sphere;
 use bytemuck::{Pod, Zeroable};
 pub(crate) use capsule::CapsuleInstance;
 pub(crate) use cone::ConeInstance;
+use glam::Vec3;
 pub(crate) use polygon::ExtrudedPolygonInstance;
 pub(crate) use sphere::SphereInstance;
 
 use crate::error::VisoError;
 use crate::gpu::dynamic_buffer::TypedBuffer;
 use crate::gpu::{RenderContext, Shader, ShaderComposer};
+use crate::renderer::culling::{
+    ChainCull, IndirectDraws, InstanceGroup, Slots,
+};
 use crate::renderer::{pipeline_util, PipelineLayouts};
 
+/// Instance types with a model-space bounding sphere, for culling.
+pub(crate) trait Bounded {
+    /// `(center, radius)` enclosing everything the instance draws.
+    fn bounding_sphere(&self) -> (Vec3, f32);
+}
+
 /// Shader identity for an impostor pass.
 pub(crate) struct ShaderDef {
     pub(crate) label: &'static str,
@@ -30,10 +40,13 @@ pub(crate) struct ShaderDef {
 /// A single impostor draw pass: pipeline + typed storage buffer + bind group.
 ///
 /// All impostor shaders use the same bind group layout convention:
-/// - group(0): camera uniform
+/// - group(0): camera uniform + model copies
 /// - group(1): lighting uniform + textures
 /// - group(2): selection storage
 /// - group(3): storage buffer (instances)
+///
+/// Since the instance index picks the impostor, model copies repeat the
+/// vertex range instead: vertex `v` draws copy `v / vertices_per_instance`.
 pub(crate) struct ImpostorPass<T: Pod + Zeroable> {
     pipeline: wgpu::RenderPipeline,
     instance_buffer: TypedBuffer<T>,
@@ -41,6 +54,8 @@ pub(crate) struct ImpostorPass<T: Pod + Zeroable> {
     bind_group: wgpu::BindGroup,
     pub(crate) instance_count: u32,
     vertices_per_instance: u32,
+    /// Culling groups covering the instances; empty = drawn whole.
+    groups: Vec<InstanceGroup>,
 }
 
 impl<T: Pod + Zeroable> ImpostorPass<T> {
@@ -83,6 +98,7 @@ impl<T: Pod + Zeroable> ImpostorPass<T> {
             bind_group,
             instance_count: 0,
             vertices_per_instance,
+            groups: Vec::new(),
         })
     }
 
@@ -246,7 +262,63 @@ impl<T: Pod + Zeroable> ImpostorPass<T> {
         render_pass.set_bind_group(1, bind_groups.lighting, &[]);
         render_pass.set_bind_group(2, bind_groups.selection, &[]);
         render_pass.set_bind_group(3, &self.bind_group, &[]);
-        render_pass.draw(0..self.vertices_per_instance, 0..self.instance_count);
+        render_pass.draw(
+            0..self.vertices_per_instance * bind_groups.copies,
+            0..self.instance_count,
+        );
+    }
+
+    /// Replace the culling groups of the current instances.
+    pub(crate) fn set_groups(&mut self, groups: Vec<InstanceGroup>) {
+        self.groups = groups;
+    }
+
+    /// Queue one indirect slot per group in view, or one for the whole
+    /// buffer when the pass has no groups.
+    pub(crate) fn plan(
+        &self,
+        indirect: &mut IndirectDraws,
+        cull: &ChainCull,
+    ) -> Slots {
+        let start = indirect.direct_len();
+        let vertices = self.vertices_per_instance * cull.copies;
+        if self.groups.is_empty() {
+            if self.instance_count > 0 {
+                indirect.push_instances(vertices, 0..self.instance_count, None);
+            }
+        } else {
+            for group in &self.groups {
+                if cull.is_visible(group.center, group.radius) {
+                    indirect.push_instances(
+                        vertices,
+                        group.instances.clone(),
+                        Some(cull.world_sphere(group.center, group.radius)),
+                    );
+                }
+            }
+        }
+        start..indirect.direct_len()
+    }
+
+    /// Draw slots queued by [`Self::plan`].
+    ///
+    /// Sets the pipeline and bind groups 0–3, then draws.
+    pub(crate) fn draw_slots<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        bind_groups: &super::draw_context::DrawBindGroups<'a>,
+        indirect: &IndirectDraws,
+        slots: &Slots,
+    ) {
+        if slots.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, bind_groups.camera, &[]);
+        render_pass.set_bind_group(1, bind_groups.lighting, &[]);
+        render_pass.set_bind_group(2, bind_groups.selection, &[]);
+        render_pass.set_bind_group(3, &self.bind_group, &[]);
+        indirect.draw(render_pass, slots);
     }
 
     /// The underlying `wgpu::Buffer` (for picking bind groups, etc.).
@@ -25,3 +25,15 @@ pub(crate) struct ExtrudedPolygonInstance {
     /// xyz = RGB, w = unused
     pub(crate) color: [f32; 4],
 }
+
+impl super::Bounded for ExtrudedPolygonInstance {
+    fn bounding_sphere(&self) -> (glam::Vec3, f32) {
+        let centroid = glam::Vec3::new(self.v2[3], self.v3[3], self.v4[3]);
+        let half_thickness = self.v1[3];
+        let radius = [self.v0, self.v1, self.v2, self.v3, self.v4, self.v5]
+            .iter()
+            .map(|v| centroid.distance(glam::Vec3::new(v[0], v[1], v[2])))
+            .fold(0.0_f32, f32::max);
+        (centroid, radius + half_thickness)
+    }
+}
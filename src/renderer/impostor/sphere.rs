@@ -8,3 +8,10 @@ pub(crate) struct SphereInstance {
     /// xyz = RGB color, w = entity_id (packed as float)
     pub(crate) color: [f32; 4],
 }
+
+impl super::Bounded for SphereInstance {
+    fn bounding_sphere(&self) -> (glam::Vec3, f32) {
+        let [x, y, z, radius] = self.center;
+        (glam::Vec3::new(x, y, z), radius)
+    }
+}
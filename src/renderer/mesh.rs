@@ -11,6 +11,7 @@
 use crate::error::VisoError;
 use crate::gpu::dynamic_buffer::DynamicBuffer;
 use crate::gpu::{RenderContext, Shader, ShaderComposer};
+use crate::renderer::culling::{IndirectDraws, Slots};
 use crate::renderer::pipeline_util;
 
 /// Description of an indexed-mesh render pipeline.
@@ -18,6 +19,7 @@ pub(crate) struct MeshPipelineDef {
     pub(crate) label: &'static str,
     pub(crate) shader: Shader,
     pub(crate) cull_mode: Option<wgpu::Face>,
+    pub(crate) topology: wgpu::PrimitiveTopology,
     pub(crate) vertex_layout: wgpu::VertexBufferLayout<'static>,
 }
 
@@ -57,7 +59,7 @@ pub(crate) fn create_mesh_pipeline(
                 compilation_options: Default::default(),
             }),
             primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
+                topology: def.topology,
                 cull_mode: def.cull_mode,
                 ..Default::default()
             },
@@ -107,13 +109,15 @@ impl MeshPass {
         }
     }
 
-    /// Set pipeline, vertex buffer, index buffer, and draw.
+    /// Set pipeline, vertex buffer, index buffer, and draw `copies`
+    /// instances.
     ///
     /// Caller must set bind groups before calling this.
     pub(crate) fn draw_indexed<'a>(
         &'a self,
         render_pass: &mut wgpu::RenderPass<'a>,
         vertex_buffer: &'a wgpu::Buffer,
+        copies: u32,
     ) {
         if self.index_count == 0 {
             return;
@@ -124,19 +128,20 @@ impl MeshPass {
             self.index_buffer.buffer().slice(..),
             wgpu::IndexFormat::Uint32,
         );
-        render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..copies);
     }
 
-    /// Draw a sub-range of the index buffer (for frustum culling).
+    /// Draw indirect slots of the index buffer (for chain culling).
     ///
     /// Caller must set bind groups before calling this.
-    pub(crate) fn draw_indexed_range<'a>(
+    pub(crate) fn draw_slots<'a>(
         &'a self,
         render_pass: &mut wgpu::RenderPass<'a>,
         vertex_buffer: &'a wgpu::Buffer,
-        index_range: std::ops::Range<u32>,
+        indirect: &IndirectDraws,
+        slots: &Slots,
     ) {
-        if index_range.is_empty() {
+        if slots.is_empty() {
             return;
         }
         render_pass.set_pipeline(&self.pipeline);
@@ -145,20 +150,7 @@ impl MeshPass {
             self.index_buffer.buffer().slice(..),
             wgpu::IndexFormat::Uint32,
         );
-        render_pass.draw_indexed(index_range, 0, 0..1);
-    }
-
-    /// Write typed index data.
-    pub(crate) fn write_indices(
-        &mut self,
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        indices: &[u32],
-    ) {
-        if !indices.is_empty() {
-            let _ = self.index_buffer.write(device, queue, indices);
-        }
-        self.index_count = indices.len() as u32;
+        indirect.draw_indexed(render_pass, slots);
     }
 
     /// Write raw index bytes (from scene processor).
@@ -3,6 +3,8 @@
 //! Contains molecular renderers (tubes, ribbons, sidechains, ball-and-stick,
 //! nucleic acids) and post-processing effects (SSAO, bloom, FXAA).
 
+/// Per-chain frustum and occlusion culling through indirect draws.
+pub(crate) mod culling;
 /// Bind groups shared across all molecular draw calls.
 pub(crate) mod draw_context;
 /// Render-ready per-entity contract -- [`EntityTopology`] plus the
@@ -22,6 +24,8 @@ pub(crate) mod geometry;
 pub(crate) mod impostor;
 /// Shared indexed-mesh draw-pass abstraction.
 pub(crate) mod mesh;
+/// Rigidly transformed copies of the scene (symmetry, assemblies).
+pub(crate) mod model_copies;
 /// GPU-based object picking and selection management.
 pub(crate) mod picking;
 /// Background mesh generation pipeline (scene -> GPU-ready buffers).
@@ -30,13 +34,16 @@ pub(crate) mod pipeline_util;
 /// Post-processing effects (SSAO, bloom, FXAA).
 pub(crate) mod postprocess;
 
+use self::culling::{HiZ, IndirectDraws, Slots};
 use self::draw_context::DrawBindGroups;
+use self::geometry::backbone::ChainSlots;
 use self::geometry::isosurface::IsosurfaceRenderer;
 use self::geometry::{
     BackboneRenderer, BallAndStickRenderer, BandRenderer, BondRenderer,
     NucleicAcidRenderer, PullRenderer, SidechainRenderer, SidechainView,
+    UnitCellRenderer, VolumeRenderer,
 };
-use crate::camera::frustum::Frustum;
+use self::model_copies::SceneDraw;
 use crate::gpu::{RenderContext, ShaderComposer};
 
 /// Bind group layouts shared by all molecular geometry pipelines.
@@ -66,6 +73,15 @@ pub(crate) struct GeometryPassInput<'a> {
     pub(crate) show_sidechains: bool,
 }
 
+/// Indirect slots of the scene, from [`Renderers::plan_draws`].
+pub(crate) struct ScenePlan {
+    backbone: ChainSlots,
+    /// Parallel with the scene draw's backbone prototypes.
+    backbone_instances: Vec<ChainSlots>,
+    nucleic_acid: [Slots; 2],
+    ball_and_stick: [Slots; 2],
+}
+
 // ---------------------------------------------------------------------------
 // Renderers
 // ---------------------------------------------------------------------------
@@ -80,6 +96,13 @@ pub(crate) struct Renderers {
     pub(crate) ball_and_stick: BallAndStickRenderer,
     pub(crate) nucleic_acid: NucleicAcidRenderer,
     pub(crate) isosurface: IsosurfaceRenderer,
+    pub(crate) unit_cell: UnitCellRenderer,
+    /// Ray-marched density maps, drawn after the geometry pass.
+    pub(crate) volume: VolumeRenderer,
+    /// This frame's culled draws of the backbone and impostor passes.
+    pub(crate) indirect: IndirectDraws,
+    /// Occlusion test against last frame's depth.
+    pub(crate) hiz: HiZ,
 }
 
 impl Renderers {
@@ -92,6 +115,7 @@ impl Renderers {
         layouts: &PipelineLayouts,
         shader_composer: &mut ShaderComposer,
         backface_depth_view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
     ) -> Result<Self, crate::error::VisoError> {
         let backbone =
             BackboneRenderer::new(context, layouts, &[], &[], shader_composer)?;
@@ -120,6 +144,16 @@ impl Renderers {
             shader_composer,
             backface_depth_view,
         )?;
+        let unit_cell =
+            UnitCellRenderer::new(context, layouts, shader_composer)?;
+        let volume = VolumeRenderer::new(
+            context,
+            &layouts.camera,
+            shader_composer,
+            depth_view,
+        )?;
+        let indirect = IndirectDraws::new(&context.device);
+        let hiz = HiZ::new(context, shader_composer)?;
         Ok(Self {
             backbone,
             sidechain,
@@ -129,20 +163,47 @@ impl Renderers {
             ball_and_stick,
             nucleic_acid,
             isosurface,
+            unit_cell,
+            volume,
+            indirect,
+            hiz,
         })
     }
 
+    /// Frustum-cull every copy of the scene in `draw` into this frame's
+    /// indirect slots and upload them.
+    pub(crate) fn plan_draws(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        draw: &SceneDraw<'_>,
+    ) -> ScenePlan {
+        self.indirect.clear();
+        let indirect = &mut self.indirect;
+        let plan = ScenePlan {
+            backbone: self.backbone.plan(indirect, &draw.cull),
+            backbone_instances: self
+                .backbone
+                .plan_instances(indirect, &draw.prototypes),
+            nucleic_acid: self.nucleic_acid.plan(indirect, &draw.cull),
+            ball_and_stick: self.ball_and_stick.plan(indirect, &draw.cull),
+        };
+        self.indirect.upload(device, queue);
+        plan
+    }
+
     /// Encode the isosurface back-face depth pre-pass.
     ///
     /// Renders all isosurface back-faces (front-face culling) into the
     /// R32Float `backface_depth_view`, writing linear view-space z. The
     /// main isosurface fragment shader samples this texture to compute
-    /// thickness for Beer-Lambert absorption.
+    /// thickness for Beer-Lambert absorption. Every model copy of
+    /// `draw` is drawn.
     pub(crate) fn encode_isosurface_backface_pass(
         &self,
         encoder: &mut wgpu::CommandEncoder,
         backface_depth_view: &wgpu::TextureView,
-        camera_bind_group: &wgpu::BindGroup,
+        draw: &SceneDraw<'_>,
     ) {
         let mut rp = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("isosurface backface depth pass"),
@@ -163,17 +224,26 @@ impl Renderers {
             depth_stencil_attachment: None,
             ..Default::default()
         });
-        self.isosurface
-            .draw_back_face_pass(&mut rp, camera_bind_group);
+        self.isosurface.draw_back_face_pass(
+            &mut rp,
+            draw.camera,
+            draw.cull.copies,
+        );
     }
 
     /// Encode the main geometry render pass.
+    ///
+    /// The scene is drawn with the model copies of `draw`'s camera
+    /// bind group, one instance per copy, from the indirect slots of
+    /// `plan`; overlays that belong to the world rather than the scene
+    /// (the unit cell) are drawn once with `bind_groups`.
     pub(crate) fn encode_geometry_pass(
         &self,
         encoder: &mut wgpu::CommandEncoder,
         input: &GeometryPassInput<'_>,
         bind_groups: &DrawBindGroups<'_>,
-        frustum: &Frustum,
+        draw: &SceneDraw<'_>,
+        plan: &ScenePlan,
     ) {
         let mut rp = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("main render pass"),
@@ -210,18 +280,51 @@ impl Renderers {
             ..Default::default()
         });
 
-        self.backbone.draw_culled(&mut rp, bind_groups, frustum);
+        let scene_groups = DrawBindGroups {
+            camera: draw.camera,
+            copies: draw.cull.copies,
+            ..*bind_groups
+        };
+        self.draw_scene(&mut rp, input, &scene_groups, draw, plan);
+        self.unit_cell.draw(&mut rp, bind_groups);
+    }
+
+    /// Draw every scene renderer with the given bind groups.
+    fn draw_scene<'a>(
+        &'a self,
+        rp: &mut wgpu::RenderPass<'a>,
+        input: &GeometryPassInput<'_>,
+        bind_groups: &DrawBindGroups<'a>,
+        draw: &SceneDraw<'a>,
+        plan: &ScenePlan,
+    ) {
+        let indirect = &self.indirect;
+        self.backbone
+            .draw_culled(rp, bind_groups, indirect, &plan.backbone);
+        self.backbone.draw_instances(
+            rp,
+            bind_groups,
+            indirect,
+            &draw.prototypes,
+            &plan.backbone_instances,
+        );
 
         if input.show_sidechains {
-            self.sidechain.draw(&mut rp, bind_groups);
+            self.sidechain.draw(rp, bind_groups);
         }
 
-        self.ball_and_stick.draw(&mut rp, bind_groups);
-        self.nucleic_acid.draw(&mut rp, bind_groups);
-        self.bond.draw(&mut rp, bind_groups);
-        self.band.draw(&mut rp, bind_groups);
-        self.pull.draw(&mut rp, bind_groups);
-        self.isosurface.draw(&mut rp, bind_groups);
+        self.ball_and_stick.draw(
+            rp,
+            bind_groups,
+            indirect,
+            &plan.ball_and_stick,
+        );
+        self.nucleic_acid
+            .draw(rp, bind_groups, indirect, &plan.nucleic_acid);
+        self.bond.draw(rp, bind_groups);
+        self.band.draw(rp, bind_groups);
+        self.pull.draw(rp, bind_groups);
+        self.isosurface.draw(rp, bind_groups);
     }
 
     /// GPU buffer sizes across all renderers.
@@ -237,6 +340,9 @@ impl Renderers {
         stats.extend(self.pull.buffer_info());
         stats.extend(self.nucleic_acid.buffer_info());
         stats.extend(self.isosurface.buffer_info());
+        stats.extend(self.unit_cell.buffer_info());
+        stats.extend(self.volume.buffer_info());
+        stats.extend(self.indirect.buffer_info());
         stats
     }
 }
@@ -0,0 +1,507 @@
+//! Rigid copies of the scene (symmetry mates, assembly operators) and
+//! of single entities (instanced backbone meshes), drawn as instances.
+//!
+//! Every copy transform lives in one storage buffer of
+//! `ModelInstance`s, bound next to the camera uniform. Geometry is
+//! drawn with one instance per copy, and the vertex shader reads the
+//! `[first, count]` run of transforms its pick ID's entity is drawn
+//! with, so an assembly whose operators differ per chain is still drawn
+//! in one pass; instances past the end of a run are dropped.
+//!
+//! The backbone instances of one prototype share a run: for every
+//! instance, the scene transforms of its entity times the instance
+//! transform, with the instance's residue offset. The prototype's mesh
+//! is then drawn once with an instance per entry. A second run with one
+//! entry per instance places them in the untransformed scene for
+//! picking, where the instance index picks the entry.
+
+use std::ops::Range;
+
+use glam::{Mat4, Vec3};
+use rustc_hash::FxHashMap;
+use wgpu::util::DeviceExt;
+
+use crate::camera::controller::CameraController;
+use crate::renderer::culling::ChainCull;
+use crate::renderer::picking::PickMap;
+use crate::renderer::pipeline::prepared::BackboneInstance;
+
+/// The single untransformed copy.
+const IDENTITY_RUN: &[Mat4] = &[Mat4::IDENTITY];
+
+/// One drawn copy, laid out as the shaders' `ModelInstance` (80 bytes).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct ModelInstance {
+    model: [f32; 16],
+    residue_offset: u32,
+    _pad: [u32; 3],
+}
+
+impl ModelInstance {
+    /// The untransformed copy.
+    #[rustfmt::skip]
+    pub(crate) const IDENTITY: Self = Self {
+        model: [
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ],
+        residue_offset: 0,
+        _pad: [0; 3],
+    };
+
+    fn new(model: Mat4, residue_offset: u32) -> Self {
+        Self {
+            model: model.to_cols_array(),
+            residue_offset,
+            _pad: [0; 3],
+        }
+    }
+}
+
+/// The transforms the scene is drawn with.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct CopyTransforms {
+    /// Copies of every entity not listed in `entities`. Empty draws the
+    /// scene once, untransformed.
+    pub(crate) shared: Vec<Mat4>,
+    /// Entities drawn with copies of their own, by raw entity id. An
+    /// empty list hides the entity.
+    pub(crate) entities: Vec<(u32, Vec<Mat4>)>,
+}
+
+impl CopyTransforms {
+    /// The copies of entities without their own.
+    fn shared(&self) -> &[Mat4] {
+        if self.shared.is_empty() {
+            IDENTITY_RUN
+        } else {
+            &self.shared
+        }
+    }
+
+    /// The copies `entity` is drawn with.
+    fn of(&self, entity: u32) -> &[Mat4] {
+        self.entities
+            .iter()
+            .find(|(id, _)| *id == entity)
+            .map_or_else(|| self.shared(), |(_, models)| models)
+    }
+}
+
+/// CPU side of the copy buffers.
+#[derive(Debug, Default, PartialEq)]
+struct CopyLayout {
+    /// Every run of copies, back to back.
+    instances: Vec<ModelInstance>,
+    /// Scene run of every pick ID; entry 0 covers geometry without one.
+    scene_runs: Vec<[u32; 2]>,
+    /// Runs of the backbone instances, one entry per prototype.
+    prototypes: Vec<PrototypeRuns>,
+}
+
+/// Copies of the backbone instances of one prototype.
+#[derive(Debug, PartialEq)]
+struct PrototypeRuns {
+    /// The prototype's span of the backbone chain ranges.
+    chains: Range<usize>,
+    /// World transform of every copy of every instance.
+    models: Vec<Mat4>,
+    /// Run of those copies.
+    run: [u32; 2],
+    /// Run placing each instance in the untransformed scene.
+    picking: [u32; 2],
+}
+
+impl CopyLayout {
+    fn new(
+        transforms: &CopyTransforms,
+        pick_ids: &[(u32, Range<u32>)],
+        backbone: &[BackboneInstance],
+    ) -> Self {
+        let untransformed = |models: &[Mat4]| {
+            models
+                .iter()
+                .map(|model| ModelInstance::new(*model, 0))
+                .collect::<Vec<_>>()
+        };
+        let mut layout = Self::default();
+        let shared = layout.push(untransformed(transforms.shared()));
+        let mut entity_runs = FxHashMap::default();
+        for (entity, models) in &transforms.entities {
+            let run = layout.push(untransformed(models));
+            let _ = entity_runs.insert(*entity, run);
+        }
+
+        layout.scene_runs.push(shared);
+        for (entity, ids) in pick_ids {
+            let Some(run) = entity_runs.get(entity) else {
+                continue;
+            };
+            let end = ids.end as usize;
+            if layout.scene_runs.len() < end {
+                layout.scene_runs.resize(end, shared);
+            }
+            layout.scene_runs[ids.start as usize..end].fill(*run);
+        }
+
+        for (chains, members) in prototypes(backbone) {
+            let copies: Vec<ModelInstance> = members
+                .iter()
+                .flat_map(|instance| {
+                    transforms.of(instance.entity).iter().map(|model| {
+                        ModelInstance::new(
+                            *model * instance.model,
+                            instance.residue_offset,
+                        )
+                    })
+                })
+                .collect();
+            let models = copies
+                .iter()
+                .map(|copy| Mat4::from_cols_array(&copy.model))
+                .collect();
+            let run = layout.push(copies);
+            let picking = layout.push(
+                members
+                    .iter()
+                    .map(|instance| {
+                        ModelInstance::new(
+                            instance.model,
+                            instance.residue_offset,
+                        )
+                    })
+                    .collect(),
+            );
+            layout.prototypes.push(PrototypeRuns {
+                chains,
+                models,
+                run,
+                picking,
+            });
+        }
+        layout
+    }
+
+    /// Append a run, returning its `[first, count]`.
+    fn push(&mut self, run: Vec<ModelInstance>) -> [u32; 2] {
+        if run.is_empty() {
+            return [0, 0];
+        }
+        let first = self.instances.len() as u32;
+        self.instances.extend(run);
+        [first, self.instances.len() as u32 - first]
+    }
+}
+
+/// The backbone instances of each prototype (keyed by its chain span),
+/// in order of first appearance.
+fn prototypes(
+    instances: &[BackboneInstance],
+) -> Vec<(Range<usize>, Vec<&BackboneInstance>)> {
+    let mut groups: Vec<(Range<usize>, Vec<&BackboneInstance>)> = Vec::new();
+    for instance in instances {
+        match groups
+            .iter_mut()
+            .find(|(chains, _)| *chains == instance.chains)
+        {
+            Some((_, members)) => members.push(instance),
+            None => groups.push((instance.chains.clone(), vec![instance])),
+        }
+    }
+    groups
+}
+
+/// Camera bind groups of one prototype's instances.
+struct PrototypeCopies {
+    runs: PrototypeRuns,
+    /// Draws every copy of every instance.
+    bind_group: wgpu::BindGroup,
+    /// Draws each instance once, untransformed.
+    picking: wgpu::BindGroup,
+}
+
+/// Camera bind groups over the uploaded copy buffers.
+struct CopyBindGroups {
+    scene: wgpu::BindGroup,
+    prototypes: Vec<PrototypeCopies>,
+}
+
+impl CopyBindGroups {
+    fn new(
+        device: &wgpu::Device,
+        camera: &CameraController,
+        layout: CopyLayout,
+    ) -> Self {
+        let storage = |label, contents: &[u8]| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents,
+                usage: wgpu::BufferUsages::STORAGE,
+            })
+        };
+        let instances = storage(
+            "Model Copy Instances",
+            bytemuck::cast_slice(&layout.instances),
+        );
+        let bind_group = |runs: &[[u32; 2]]| {
+            let runs = storage("Model Copy Runs", bytemuck::cast_slice(runs));
+            camera.copies_bind_group(
+                device,
+                [instances.as_entire_binding(), runs.as_entire_binding()],
+            )
+        };
+        Self {
+            scene: bind_group(&layout.scene_runs),
+            prototypes: layout
+                .prototypes
+                .into_iter()
+                .map(|runs| PrototypeCopies {
+                    bind_group: bind_group(&[runs.run]),
+                    picking: bind_group(&[runs.picking]),
+                    runs,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// One prototype's backbone instances in the geometry pass.
+pub(crate) struct PrototypeDraw<'a> {
+    /// The prototype's span of the backbone chain ranges.
+    pub(crate) chains: Range<usize>,
+    /// Camera bind group whose model copies are every copy of every
+    /// instance.
+    pub(crate) camera: &'a wgpu::BindGroup,
+    /// Visibility across those copies.
+    pub(crate) cull: ChainCull,
+}
+
+/// One prototype's backbone instances in the picking pass.
+pub(crate) struct PickingPrototype<'a> {
+    /// The prototype's span of the backbone chain ranges.
+    pub(crate) chains: Range<usize>,
+    /// Camera bind group placing each instance, by instance index.
+    pub(crate) camera: &'a wgpu::BindGroup,
+    /// Number of instances.
+    pub(crate) instances: u32,
+}
+
+/// The scene in the geometry pass, with every copy.
+pub(crate) struct SceneDraw<'a> {
+    /// Camera bind group of the scene copies.
+    pub(crate) camera: &'a wgpu::BindGroup,
+    /// Visibility of the scene across its copies.
+    pub(crate) cull: ChainCull,
+    /// The backbone instances, one draw per prototype.
+    pub(crate) prototypes: Vec<PrototypeDraw<'a>>,
+}
+
+/// Copy transforms of the scene and their GPU buffers.
+#[derive(Default)]
+pub(crate) struct ModelCopies {
+    transforms: CopyTransforms,
+    /// `(entity_id, pick IDs)` runs of the current pick map.
+    pick_ids: Vec<(u32, Range<u32>)>,
+    /// Backbone instances the buffers were built for.
+    instances: Vec<BackboneInstance>,
+    /// `None` until built, and again whenever an input changes.
+    gpu: Option<CopyBindGroups>,
+}
+
+impl ModelCopies {
+    /// Replace the copy transforms.
+    pub(crate) fn set(&mut self, transforms: CopyTransforms) {
+        if transforms != self.transforms {
+            self.transforms = transforms;
+            self.gpu = None;
+        }
+    }
+
+    /// Track which entity owns each pick ID, for entities drawn with
+    /// copies of their own.
+    pub(crate) fn set_pick_ids(&mut self, pick_map: &PickMap) {
+        let pick_ids = pick_map.entity_ids();
+        if pick_ids != self.pick_ids {
+            self.pick_ids = pick_ids;
+            if !self.transforms.entities.is_empty() {
+                self.gpu = None;
+            }
+        }
+    }
+
+    /// Whether the scene is drawn as transformed copies.
+    pub(crate) fn is_active(&self) -> bool {
+        !self.transforms.shared.is_empty()
+    }
+
+    /// Upload the copies of the scene and of every backbone instance,
+    /// unless they are unchanged since the last call.
+    pub(crate) fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        camera: &CameraController,
+        instances: &[BackboneInstance],
+    ) {
+        if instances != self.instances {
+            self.instances = instances.to_vec();
+            self.gpu = None;
+        }
+        if self.gpu.is_none() {
+            let layout =
+                CopyLayout::new(&self.transforms, &self.pick_ids, instances);
+            self.gpu = Some(CopyBindGroups::new(device, camera, layout));
+        }
+    }
+
+    /// Most copies any entity is drawn with: the instance count of
+    /// every scene draw.
+    pub(crate) fn copies(&self) -> u32 {
+        self.transforms
+            .entities
+            .iter()
+            .map(|(_, models)| models.len())
+            .fold(self.transforms.shared().len(), usize::max) as u32
+    }
+
+    /// Every transform the scene is drawn with, each once.
+    pub(crate) fn scene_models(&self) -> Vec<Mat4> {
+        let mut models = self.transforms.shared().to_vec();
+        for (_, entity_models) in &self.transforms.entities {
+            for model in entity_models {
+                if !models.contains(model) {
+                    models.push(*model);
+                }
+            }
+        }
+        models
+    }
+
+    /// The scene draw of a frame drawn with `view_proj` from `eye`;
+    /// its camera is `main_camera` until [`Self::prepare`] has run.
+    pub(crate) fn draws<'a>(
+        &'a self,
+        main_camera: &'a wgpu::BindGroup,
+        view_proj: Mat4,
+        eye: Vec3,
+    ) -> SceneDraw<'a> {
+        let Some(gpu) = &self.gpu else {
+            return SceneDraw {
+                camera: main_camera,
+                cull: ChainCull::new(view_proj, eye, IDENTITY_RUN.to_vec(), 1),
+                prototypes: Vec::new(),
+            };
+        };
+        let prototypes = gpu
+            .prototypes
+            .iter()
+            .map(|prototype| PrototypeDraw {
+                chains: prototype.runs.chains.clone(),
+                camera: &prototype.bind_group,
+                cull: ChainCull::new(
+                    view_proj,
+                    eye,
+                    prototype.runs.models.clone(),
+                    prototype.runs.run[1],
+                ),
+            })
+            .collect();
+        SceneDraw {
+            camera: &gpu.scene,
+            cull: ChainCull::new(
+                view_proj,
+                eye,
+                self.scene_models(),
+                self.copies(),
+            ),
+            prototypes,
+        }
+    }
+
+    /// The backbone instances of each prototype, placed in the
+    /// untransformed scene for the picking pass.
+    pub(crate) fn picking_prototypes(&self) -> Vec<PickingPrototype<'_>> {
+        let Some(gpu) = &self.gpu else {
+            return Vec::new();
+        };
+        gpu.prototypes
+            .iter()
+            .map(|prototype| PickingPrototype {
+                chains: prototype.runs.chains.clone(),
+                camera: &prototype.picking,
+                instances: prototype.runs.picking[1],
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(entity: u32, x: f32, residue_offset: u32) -> BackboneInstance {
+        BackboneInstance {
+            entity,
+            model: Mat4::from_translation(Vec3::X * x),
+            residue_offset,
+            chains: 0..1,
+        }
+    }
+
+    #[test]
+    fn entities_with_own_copies_get_their_pick_ids_run() {
+        let shift = Mat4::from_translation(Vec3::Y);
+        let transforms = CopyTransforms {
+            shared: vec![Mat4::IDENTITY, shift],
+            entities: vec![(7, vec![shift])],
+        };
+        let pick_ids = vec![(3, 1..3), (7, 3..5)];
+        let layout = CopyLayout::new(&transforms, &pick_ids, &[]);
+        assert_eq!(layout.instances.len(), 3);
+        assert_eq!(
+            layout.scene_runs,
+            vec![[0, 2], [0, 2], [0, 2], [2, 1], [2, 1]]
+        );
+    }
+
+    #[test]
+    fn backbone_instances_share_their_prototype_run() {
+        let shift = Mat4::from_translation(Vec3::Y);
+        let transforms = CopyTransforms {
+            shared: vec![Mat4::IDENTITY, shift],
+            entities: vec![(7, Vec::new())],
+        };
+        let mut other = instance(9, 4.0, 30);
+        other.chains = 1..2;
+        let backbone = [instance(5, 2.0, 10), other, instance(7, 3.0, 20)];
+        let layout = CopyLayout::new(&transforms, &[], &backbone);
+        assert_eq!(layout.scene_runs, vec![[0, 2]]);
+
+        // Entity 7 has no copies, so its instance only gets picked.
+        let first = &layout.prototypes[0];
+        assert_eq!(first.chains, 0..1);
+        assert_eq!(first.run, [2, 2]);
+        assert_eq!(first.picking, [4, 2]);
+        assert_eq!(
+            first.models,
+            vec![backbone[0].model, shift * backbone[0].model]
+        );
+        assert_eq!(
+            layout.instances[5],
+            ModelInstance::new(backbone[2].model, 20)
+        );
+
+        let second = &layout.prototypes[1];
+        assert_eq!((second.run, second.picking), ([6, 2], [8, 1]));
+        assert_eq!(layout.instances.len(), 9);
+    }
+
+    #[test]
+    fn no_transforms_draw_one_identity_copy() {
+        let layout = CopyLayout::new(&CopyTransforms::default(), &[], &[]);
+        assert_eq!(layout.instances, vec![ModelInstance::IDENTITY]);
+        assert_eq!(layout.scene_runs, vec![[0, 1]]);
+    }
+}
@@ -5,18 +5,23 @@
 
 mod pick_map;
 mod pipeline;
+mod selection_buffer;
 pub(crate) mod state;
+mod surface;
 
 use molex::SSType;
 pub(crate) use pick_map::PickMap;
 pub use pick_map::PickTarget;
-pub(crate) use pipeline::{Picking, PickingGeometry, SelectionBuffer};
+pub(crate) use pipeline::{Picking, PickingGeometry, PickingInstance};
+pub(crate) use selection_buffer::SelectionBuffer;
 
 use self::state::PickingState;
+pub(crate) use self::surface::PickingSurface;
 use super::Renderers;
 use crate::gpu::residue_color::ResidueColorBuffer;
 use crate::gpu::{RenderContext, ShaderComposer};
 use crate::renderer::entity_topology::ProteinBackboneChain;
+use crate::renderer::model_copies::ModelCopies;
 
 /// GPU picking, selection, and per-residue color buffers grouped together.
 pub(crate) struct PickingSystem {
@@ -173,12 +178,51 @@ impl PickingSystem {
         true
     }
 
+    /// Select the flat residue indices `residues`. If `extend` is true
+    /// they are added to the existing selection; otherwise the selection
+    /// is replaced.
+    ///
+    /// Returns `true` if the selection changed.
+    pub(crate) fn select_residues(
+        &mut self,
+        residues: impl IntoIterator<Item = i32>,
+        extend: bool,
+    ) -> bool {
+        let selected = &mut self.picking.selected_residues;
+        let before = selected.clone();
+        if !extend {
+            selected.clear();
+        }
+        for residue in residues {
+            if !selected.contains(&residue) {
+                selected.push(residue);
+            }
+        }
+        *selected != before
+    }
+
     /// Build the picking geometry descriptor from current renderer state.
     pub(crate) fn build_geometry<'a>(
         &'a self,
         renderers: &'a Renderers,
+        copies: &'a ModelCopies,
         show_sidechains: bool,
     ) -> PickingGeometry<'a> {
+        let backbone = &renderers.backbone;
+        let backbone_instances = copies
+            .picking_prototypes()
+            .into_iter()
+            .filter_map(|prototype| {
+                let (tube, ribbon) =
+                    backbone.prototype_index_ranges(prototype.chains)?;
+                Some(PickingInstance {
+                    camera: prototype.camera,
+                    instances: prototype.instances,
+                    tube,
+                    ribbon,
+                })
+            })
+            .collect();
         PickingGeometry {
             backbone_vertex_buffer: renderers.backbone.vertex_buffer(),
             backbone_tube_index_buffer: renderers.backbone.tube_index_buffer(),
@@ -189,6 +233,7 @@ impl PickingSystem {
             backbone_ribbon_index_count: renderers
                 .backbone
                 .ribbon_index_count(),
+            backbone_instances,
             capsule_bind_group: self.groups.capsule.as_ref(),
             capsule_count: if show_sidechains {
                 renderers.sidechain.instance_count()
@@ -199,6 +244,15 @@ impl PickingSystem {
             bns_capsule_count: renderers.ball_and_stick.bond_count(),
             bns_sphere_bind_group: self.groups.bns_sphere.as_ref(),
             bns_sphere_count: renderers.ball_and_stick.sphere_count(),
+            surfaces: renderers
+                .isosurface
+                .pickable_meshes()
+                .map(|(vertices, indices, index_count)| PickingSurface {
+                    vertices,
+                    indices,
+                    index_count,
+                })
+                .collect(),
         }
     }
 
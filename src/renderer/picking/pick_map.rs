@@ -1,5 +1,7 @@
 //! Typed pick-target resolution from raw GPU pick IDs.
 
+use std::ops::Range;
+
 /// A typed pick target resolved from a raw GPU pick ID.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PickTarget {
@@ -47,6 +49,9 @@ impl PickTarget {
 pub(crate) struct PickMap {
     residue_count: u32,
     atom_entries: Vec<(u32, u32)>,
+    /// Owners of the residue IDs, in order: `(entity_id,
+    /// residue_count)`.
+    residue_entities: Vec<(u32, u32)>,
 }
 
 impl PickMap {
@@ -58,7 +63,39 @@ impl PickMap {
         Self {
             residue_count,
             atom_entries,
+            residue_entities: Vec::new(),
+        }
+    }
+
+    /// Record which entity each run of residue IDs belongs to:
+    /// `(entity_id, residue_count)` per entity, in order.
+    pub(crate) fn with_residue_entities(
+        mut self,
+        entities: Vec<(u32, u32)>,
+    ) -> Self {
+        self.residue_entities = entities;
+        self
+    }
+
+    /// Pick IDs of every entity's residues and atoms, as
+    /// `(entity_id, ids)` runs in ID order.
+    pub(crate) fn entity_ids(&self) -> Vec<(u32, Range<u32>)> {
+        let mut runs: Vec<(u32, Range<u32>)> = Vec::new();
+        let mut next = 1;
+        for &(entity_id, count) in &self.residue_entities {
+            runs.push((entity_id, next..next + count));
+            next += count;
         }
+        let atom_ids = self.residue_count + 1..;
+        for (id, &(entity_id, _)) in atom_ids.zip(&self.atom_entries) {
+            match runs.last_mut() {
+                Some((last, ids)) if *last == entity_id && ids.end == id => {
+                    ids.end += 1;
+                }
+                _ => runs.push((entity_id, id..id + 1)),
+            }
+        }
+        runs
     }
 
     /// Resolve a raw pick ID (as read from the GPU picking buffer) to a typed
@@ -121,6 +158,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn entity_ids_cover_residues_then_atoms() {
+        let map = PickMap::new(5, vec![(10, 0), (10, 1), (20, 0)])
+            .with_residue_entities(vec![(1, 3), (2, 2)]);
+        assert_eq!(
+            map.entity_ids(),
+            vec![(1, 1..4), (2, 4..6), (10, 6..8), (20, 8..9)]
+        );
+    }
+
     #[test]
     fn out_of_range_resolves_to_none() {
         let map = PickMap::new(2, vec![(0, 0)]);
@@ -4,129 +4,16 @@
 //! the pixel at the mouse position to determine which residue is under the
 //! cursor. This is exact - it matches exactly what's rendered on screen.
 
+use std::ops::Range;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-use wgpu::util::DeviceExt;
-
+use super::surface::{self, PickingSurface};
 use crate::error::VisoError;
 use crate::gpu::pipeline_helpers::read_only_storage_buffer;
 use crate::gpu::{RenderContext, Shader, ShaderComposer};
 use crate::renderer::geometry::backbone::backbone_vertex_buffer_layout;
 
-/// Selection buffer for GPU - stores selection state as a bit array
-pub(crate) struct SelectionBuffer {
-    buffer: wgpu::Buffer,
-    /// Bind group layout for the selection storage buffer.
-    pub(crate) layout: wgpu::BindGroupLayout,
-    /// Bind group referencing the selection storage buffer.
-    pub(crate) bind_group: wgpu::BindGroup,
-    /// Number of residues (for sizing)
-    capacity: usize,
-}
-
-impl SelectionBuffer {
-    /// Create a selection buffer sized for up to `max_residues` residues.
-    pub(crate) fn new(device: &wgpu::Device, max_residues: usize) -> Self {
-        // Round up to multiple of 32 bits
-        let num_words = max_residues.div_ceil(32);
-        let data = vec![0u32; num_words.max(1)];
-
-        let buffer =
-            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Selection Buffer"),
-                contents: bytemuck::cast_slice(&data),
-                usage: wgpu::BufferUsages::STORAGE
-                    | wgpu::BufferUsages::COPY_DST,
-            });
-
-        let layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Selection Bind Group Layout"),
-                entries: &[read_only_storage_buffer(0)],
-            });
-
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Selection Bind Group"),
-            layout: &layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: buffer.as_entire_binding(),
-            }],
-        });
-
-        Self {
-            buffer,
-            layout,
-            bind_group,
-            capacity: max_residues,
-        }
-    }
-
-    /// Update selection state from a list of selected residue indices
-    pub(crate) fn update(
-        &self,
-        queue: &wgpu::Queue,
-        selected_residues: &[i32],
-    ) {
-        let num_words = self.capacity.div_ceil(32);
-        let mut data = vec![0u32; num_words.max(1)];
-
-        for &idx in selected_residues {
-            if idx >= 0 && (idx as usize) < self.capacity {
-                let word_idx = idx as usize / 32;
-                let bit_idx = idx as usize % 32;
-                data[word_idx] |= 1u32 << bit_idx;
-            }
-        }
-
-        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&data));
-    }
-
-    /// Ensure the buffer has capacity for at least `required` residues.
-    /// Recreates the buffer and bind_group if current capacity is insufficient.
-    pub(crate) fn ensure_capacity(
-        &mut self,
-        device: &wgpu::Device,
-        required: usize,
-    ) {
-        if required <= self.capacity {
-            return;
-        }
-
-        // Need to grow - recreate buffer with new capacity
-        let new_capacity = required;
-        let num_words = new_capacity.div_ceil(32);
-        let data = vec![0u32; num_words.max(1)];
-
-        self.buffer =
-            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Selection Buffer"),
-                contents: bytemuck::cast_slice(&data),
-                usage: wgpu::BufferUsages::STORAGE
-                    | wgpu::BufferUsages::COPY_DST,
-            });
-
-        self.bind_group =
-            device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Selection Bind Group"),
-                layout: &self.layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: self.buffer.as_entire_binding(),
-                }],
-            });
-
-        self.capacity = new_capacity;
-    }
-
-    /// GPU buffer sizes: `(label, used_bytes, allocated_bytes)`.
-    pub(crate) fn buffer_info(&self) -> Vec<(&'static str, usize, usize)> {
-        let bytes = self.capacity.div_ceil(32).max(1) * 4;
-        vec![("Selection", bytes, bytes)]
-    }
-}
-
 /// Geometry buffers needed for the picking render pass.
 pub(crate) struct PickingGeometry<'a> {
     /// Backbone vertex buffer (shared by tube and ribbon passes).
@@ -139,6 +26,9 @@ pub(crate) struct PickingGeometry<'a> {
     pub(crate) backbone_ribbon_index_buffer: &'a wgpu::Buffer,
     /// Number of backbone ribbon indices to draw.
     pub(crate) backbone_ribbon_index_count: u32,
+    /// Rigid entity copies drawn with their prototype's backbone ranges,
+    /// one instanced draw per prototype.
+    pub(crate) backbone_instances: Vec<PickingInstance<'a>>,
     /// Sidechain capsule bind group for picking.
     pub(crate) capsule_bind_group: Option<&'a wgpu::BindGroup>,
     /// Number of sidechain capsule instances.
@@ -151,6 +41,23 @@ pub(crate) struct PickingGeometry<'a> {
     pub(crate) bns_sphere_bind_group: Option<&'a wgpu::BindGroup>,
     /// Number of ball-and-stick sphere instances.
     pub(crate) bns_sphere_count: u32,
+    /// Entity surface meshes, whose vertices carry residue pick IDs.
+    pub(crate) surfaces: Vec<PickingSurface<'a>>,
+}
+
+/// The instanced entity copies of one prototype in the picking pass:
+/// their camera bind group (each copy's transform and residue offset)
+/// and the prototype's index ranges.
+pub(crate) struct PickingInstance<'a> {
+    /// Camera bind group carrying each copy's model and residue offset,
+    /// indexed by instance.
+    pub(crate) camera: &'a wgpu::BindGroup,
+    /// Number of copies.
+    pub(crate) instances: u32,
+    /// Prototype tube index range.
+    pub(crate) tube: Range<u32>,
+    /// Prototype ribbon index range.
+    pub(crate) ribbon: Range<u32>,
 }
 
 /// Manages GPU-based residue picking via an offscreen R32Uint render pass.
@@ -165,6 +72,8 @@ pub(crate) struct Picking {
     staging_buffer: wgpu::Buffer,
     /// Pipeline for rendering tubes to picking buffer
     tube_pipeline: wgpu::RenderPipeline,
+    /// Pipeline for rendering entity surfaces to picking buffer
+    surface_pipeline: wgpu::RenderPipeline,
     /// Pipeline for rendering capsules to picking buffer
     capsule_pipeline: wgpu::RenderPipeline,
     /// Bind group layout for capsule storage buffer
@@ -209,7 +118,15 @@ impl Picking {
                 mapped_at_creation: false,
             });
 
-        let tube_pipeline = Self::create_tube_pipeline(
+        let tube_pipeline = Self::create_mesh_picking_pipeline(
+            context,
+            camera_bind_group_layout,
+            shader_composer,
+            Shader::PickingMesh,
+            backbone_vertex_buffer_layout(),
+            "Picking Tube",
+        )?;
+        let surface_pipeline = surface::create_surface_pipeline(
             context,
             camera_bind_group_layout,
             shader_composer,
@@ -234,6 +151,7 @@ impl Picking {
             depth_view,
             staging_buffer,
             tube_pipeline,
+            surface_pipeline,
             capsule_pipeline,
             capsule_bind_group_layout,
             sphere_pipeline,
@@ -246,17 +164,22 @@ impl Picking {
         })
     }
 
-    fn create_tube_pipeline(
+    /// Shared pipeline creation for vertex-buffer meshes (backbone,
+    /// entity surfaces).
+    pub(super) fn create_mesh_picking_pipeline(
         context: &RenderContext,
         camera_bind_group_layout: &wgpu::BindGroupLayout,
         shader_composer: &mut ShaderComposer,
+        shader_variant: Shader,
+        vertex_layout: wgpu::VertexBufferLayout<'static>,
+        label: &str,
     ) -> Result<wgpu::RenderPipeline, VisoError> {
         let shader =
-            shader_composer.compose(&context.device, Shader::PickingMesh)?;
+            shader_composer.compose(&context.device, shader_variant)?;
 
         let layout = context.device.create_pipeline_layout(
             &wgpu::PipelineLayoutDescriptor {
-                label: Some("Picking Tube Pipeline Layout"),
+                label: Some(&format!("{label} Pipeline Layout")),
                 bind_group_layouts: &[camera_bind_group_layout],
                 push_constant_ranges: &[],
             },
@@ -264,12 +187,12 @@ impl Picking {
 
         Ok(context.device.create_render_pipeline(
             &wgpu::RenderPipelineDescriptor {
-                label: Some("Picking Tube Pipeline"),
+                label: Some(&format!("{label} Pipeline")),
                 layout: Some(&layout),
                 vertex: wgpu::VertexState {
                     module: &shader,
                     entry_point: Some("vs_main"),
-                    buffers: &[backbone_vertex_buffer_layout()],
+                    buffers: &[vertex_layout],
                     compilation_options: Default::default(),
                 },
                 fragment: Some(wgpu::FragmentState {
@@ -724,6 +647,25 @@ fn draw_picking_geometry(
         );
     }
 
+    // Instanced entity copies: the prototype's ranges once per
+    // prototype, one instance per copy; the shader reads the copy's
+    // transform and residue offset (which turns the pick ID into the
+    // copy's) by instance index.
+    for instance in &geometry.backbone_instances {
+        render_pass.set_bind_group(0, instance.camera, &[]);
+        for (buffer, range) in [
+            (geometry.backbone_tube_index_buffer, &instance.tube),
+            (geometry.backbone_ribbon_index_buffer, &instance.ribbon),
+        ] {
+            if range.is_empty() {
+                continue;
+            }
+            render_pass
+                .set_index_buffer(buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(range.clone(), 0, 0..instance.instances);
+        }
+    }
+
     // Draw capsules (sidechains)
     if let Some(capsule_bg) = geometry.capsule_bind_group {
         if geometry.capsule_count > 0 {
@@ -753,4 +695,11 @@ fn draw_picking_geometry(
             render_pass.draw(0..6, 0..geometry.bns_sphere_count);
         }
     }
+
+    surface::draw_surfaces(
+        render_pass,
+        &picking.surface_pipeline,
+        camera_bind_group,
+        &geometry.surfaces,
+    );
 }
@@ -0,0 +1,118 @@
+//! Per-residue selection bits for the GPU.
+
+use wgpu::util::DeviceExt;
+
+use crate::gpu::pipeline_helpers::read_only_storage_buffer;
+
+/// Selection buffer for GPU - stores selection state as a bit array
+pub(crate) struct SelectionBuffer {
+    buffer: wgpu::Buffer,
+    /// Bind group layout for the selection storage buffer.
+    pub(crate) layout: wgpu::BindGroupLayout,
+    /// Bind group referencing the selection storage buffer.
+    pub(crate) bind_group: wgpu::BindGroup,
+    /// Number of residues (for sizing)
+    capacity: usize,
+}
+
+impl SelectionBuffer {
+    /// Create a selection buffer sized for up to `max_residues` residues.
+    pub(crate) fn new(device: &wgpu::Device, max_residues: usize) -> Self {
+        // Round up to multiple of 32 bits
+        let num_words = max_residues.div_ceil(32);
+        let data = vec![0u32; num_words.max(1)];
+
+        let buffer =
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Selection Buffer"),
+                contents: bytemuck::cast_slice(&data),
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_DST,
+            });
+
+        let layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Selection Bind Group Layout"),
+                entries: &[read_only_storage_buffer(0)],
+            });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Selection Bind Group"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            buffer,
+            layout,
+            bind_group,
+            capacity: max_residues,
+        }
+    }
+
+    /// Update selection state from a list of selected residue indices
+    pub(crate) fn update(
+        &self,
+        queue: &wgpu::Queue,
+        selected_residues: &[i32],
+    ) {
+        let num_words = self.capacity.div_ceil(32);
+        let mut data = vec![0u32; num_words.max(1)];
+
+        for &idx in selected_residues {
+            if idx >= 0 && (idx as usize) < self.capacity {
+                let word_idx = idx as usize / 32;
+                let bit_idx = idx as usize % 32;
+                data[word_idx] |= 1u32 << bit_idx;
+            }
+        }
+
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&data));
+    }
+
+    /// Ensure the buffer has capacity for at least `required` residues.
+    /// Recreates the buffer and bind_group if current capacity is insufficient.
+    pub(crate) fn ensure_capacity(
+        &mut self,
+        device: &wgpu::Device,
+        required: usize,
+    ) {
+        if required <= self.capacity {
+            return;
+        }
+
+        // Need to grow - recreate buffer with new capacity
+        let new_capacity = required;
+        let num_words = new_capacity.div_ceil(32);
+        let data = vec![0u32; num_words.max(1)];
+
+        self.buffer =
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Selection Buffer"),
+                contents: bytemuck::cast_slice(&data),
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_DST,
+            });
+
+        self.bind_group =
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Selection Bind Group"),
+                layout: &self.layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.buffer.as_entire_binding(),
+                }],
+            });
+
+        self.capacity = new_capacity;
+    }
+
+    /// GPU buffer sizes: `(label, used_bytes, allocated_bytes)`.
+    pub(crate) fn buffer_info(&self) -> Vec<(&'static str, usize, usize)> {
+        let bytes = self.capacity.div_ceil(32).max(1) * 4;
+        vec![("Selection", bytes, bytes)]
+    }
+}
@@ -0,0 +1,59 @@
+//! Picking of entity surfaces.
+//!
+//! Surface vertices carry the pick ID of their nearest residue, so a
+//! surface picks as the residue under the cursor. The shader skips
+//! untagged vertices, faint translucent surfaces and the cut side of
+//! cutaway surfaces, so picks reach what lies inside.
+
+use super::pipeline::Picking;
+use crate::error::VisoError;
+use crate::gpu::{RenderContext, Shader, ShaderComposer};
+use crate::renderer::geometry::isosurface::isosurface_vertex_layout;
+
+/// One entity surface mesh in the picking pass.
+pub(crate) struct PickingSurface<'a> {
+    /// Isosurface vertex buffer.
+    pub(crate) vertices: &'a wgpu::Buffer,
+    /// Triangle index buffer.
+    pub(crate) indices: &'a wgpu::Buffer,
+    /// Number of indices to draw.
+    pub(crate) index_count: u32,
+}
+
+/// Pipeline drawing entity surfaces into the picking buffer.
+pub(super) fn create_surface_pipeline(
+    context: &RenderContext,
+    camera_bind_group_layout: &wgpu::BindGroupLayout,
+    shader_composer: &mut ShaderComposer,
+) -> Result<wgpu::RenderPipeline, VisoError> {
+    Picking::create_mesh_picking_pipeline(
+        context,
+        camera_bind_group_layout,
+        shader_composer,
+        Shader::PickingSurface,
+        isosurface_vertex_layout(),
+        "Picking Surface",
+    )
+}
+
+/// Draw `surfaces` with the surface picking pipeline.
+pub(super) fn draw_surfaces(
+    render_pass: &mut wgpu::RenderPass<'_>,
+    pipeline: &wgpu::RenderPipeline,
+    camera_bind_group: &wgpu::BindGroup,
+    surfaces: &[PickingSurface],
+) {
+    if surfaces.is_empty() {
+        return;
+    }
+    render_pass.set_pipeline(pipeline);
+    render_pass.set_bind_group(0, camera_bind_group, &[]);
+    for surface in surfaces {
+        render_pass.set_vertex_buffer(0, surface.vertices.slice(..));
+        render_pass.set_index_buffer(
+            surface.indices.slice(..),
+            wgpu::IndexFormat::Uint32,
+        );
+        render_pass.draw_indexed(0..surface.index_count, 0, 0..1);
+    }
+}
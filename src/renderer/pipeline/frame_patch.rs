@@ -0,0 +1,217 @@
+//! Sub-range backbone vertex uploads for animation frames.
+//!
+//! Animation frames travel through a latest-wins triple buffer, so the
+//! main thread may never see some of them. A frame therefore cannot
+//! carry just its own changes. Instead:
+//!
+//! - every frame gets a sequence number, and the main thread publishes the last
+//!   one it applied through a shared atomic;
+//! - a frame is sent whole when its index buffers differ from the last frame's,
+//!   or when the last whole frame has not been applied yet;
+//! - otherwise it carries a [`VertexPatch`]: the union of the vertex spans
+//!   changed by every frame since the last applied one, with their current
+//!   bytes.
+//!
+//! Applying a patch on top of any frame at or after its
+//! [`VertexPatch::since`] yields the patched frame. The main thread
+//! drops patches it cannot apply (its buffer was overwritten by a full
+//! rebuild meanwhile) and resets the shared sequence, so the worker
+//! sends the next frame whole.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use super::prepared::BackboneMeshData;
+use crate::renderer::geometry::backbone::BackboneVertex;
+
+/// Vertex byte writes bringing the backbone vertex buffer from any
+/// applied frame at or after [`Self::since`] to the frame carrying it.
+#[derive(Clone, Debug)]
+pub(crate) struct VertexPatch {
+    /// Oldest applied frame the writes are valid on top of.
+    pub(crate) since: u64,
+    /// `(byte offset, bytes)` of each changed span.
+    pub(crate) writes: Vec<(usize, Vec<u8>)>,
+}
+
+/// Worker-side record of the frames sent since the last whole one.
+pub(super) struct PatchLog {
+    /// Last frame sequence the main thread applied (0 for none).
+    applied: Arc<AtomicU64>,
+    sequence: u64,
+    /// Last frame sent whole.
+    base: Option<u64>,
+    /// Index bytes of the last frame; patches require them unchanged.
+    tube_indices: Vec<u8>,
+    ribbon_indices: Vec<u8>,
+    vertex_bytes: usize,
+    /// Changed byte spans of each frame after `base`.
+    pending: Vec<(u64, Vec<Range<usize>>)>,
+}
+
+impl PatchLog {
+    pub(super) fn new(applied: Arc<AtomicU64>) -> Self {
+        Self {
+            applied,
+            sequence: 0,
+            base: None,
+            tube_indices: Vec::new(),
+            ribbon_indices: Vec::new(),
+            vertex_bytes: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Forget the sent frames, so the next one goes out whole.
+    pub(super) fn reset(&mut self) {
+        self.base = None;
+        self.pending.clear();
+    }
+
+    /// Number the next frame and, when the main thread can take one,
+    /// replace `mesh`'s vertex and index bytes with a patch. `dirty`
+    /// holds the vertex spans that differ from the previous frame.
+    ///
+    /// Returns the frame's sequence number.
+    pub(super) fn record(
+        &mut self,
+        mesh: &mut BackboneMeshData,
+        dirty: &[Range<u32>],
+    ) -> u64 {
+        self.sequence += 1;
+        let sequence = self.sequence;
+        let applied = self.applied.load(Ordering::Acquire);
+        let same_layout = mesh.vertices.len() == self.vertex_bytes
+            && mesh.tube_indices == self.tube_indices
+            && mesh.ribbon_indices == self.ribbon_indices;
+
+        if !same_layout || self.base.is_none_or(|base| applied < base) {
+            self.base = Some(sequence);
+            self.pending.clear();
+            self.vertex_bytes = mesh.vertices.len();
+            self.tube_indices.clone_from(&mesh.tube_indices);
+            self.ribbon_indices.clone_from(&mesh.ribbon_indices);
+            return sequence;
+        }
+
+        let stride = size_of::<BackboneVertex>();
+        self.pending.retain(|(seq, _)| *seq > applied);
+        self.pending.push((
+            sequence,
+            dirty
+                .iter()
+                .map(|r| r.start as usize * stride..r.end as usize * stride)
+                .collect(),
+        ));
+        let spans =
+            merge_spans(self.pending.iter().flat_map(|(_, s)| s.iter()));
+        let writes = spans
+            .into_iter()
+            .filter_map(|r| Some((r.start, mesh.vertices.get(r)?.to_vec())))
+            .collect();
+        mesh.vertex_patch = Some(VertexPatch {
+            since: applied,
+            writes,
+        });
+        mesh.vertices = Vec::new();
+        mesh.tube_indices = Vec::new();
+        mesh.ribbon_indices = Vec::new();
+        sequence
+    }
+}
+
+/// Sorted union of `spans`, with touching spans joined.
+fn merge_spans<'a>(
+    spans: impl Iterator<Item = &'a Range<usize>>,
+) -> Vec<Range<usize>> {
+    let mut sorted: Vec<Range<usize>> =
+        spans.filter(|r| !r.is_empty()).cloned().collect();
+    sorted.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(sorted.len());
+    for span in sorted {
+        match merged.last_mut() {
+            Some(last) if span.start <= last.end => {
+                last.end = last.end.max(span.end);
+            }
+            _ => merged.push(span),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    fn frame(vertices: usize, fill: u8) -> BackboneMeshData {
+        let stride = size_of::<BackboneVertex>();
+        BackboneMeshData {
+            vertices: vec![fill; vertices * stride],
+            tube_indices: vec![1, 2, 3, 4],
+            tube_index_count: 1,
+            ribbon_indices: Vec::new(),
+            ribbon_index_count: 0,
+            sheet_offsets: Vec::new(),
+            chain_ranges: Vec::new(),
+            coarse_instances: Vec::new(),
+            instances: Vec::new(),
+            instance_colors: None,
+            vertex_patch: None,
+        }
+    }
+
+    #[test]
+    fn spans_merge_when_overlapping_or_touching() {
+        let spans = [5..8, 0..2, 2..3, 7..10, 12..12];
+        assert_eq!(merge_spans(spans.iter()), vec![0..3, 5..10]);
+    }
+
+    #[test]
+    fn patches_cover_every_frame_since_the_applied_one() {
+        let applied = Arc::new(AtomicU64::new(0));
+        let mut log = PatchLog::new(Arc::clone(&applied));
+        let stride = size_of::<BackboneVertex>();
+
+        // Nothing applied yet: whole frames until the first one lands.
+        let mut first = frame(10, 0);
+        assert_eq!(log.record(&mut first, &[0..10]), 1);
+        assert!(first.vertex_patch.is_none());
+        applied.store(1, Ordering::Release);
+
+        // Frame 2 is dropped; frame 3 must still carry its span.
+        let mut second = frame(10, 2);
+        let _ = log.record(&mut second, &[1..2]);
+        let mut third = frame(10, 3);
+        assert_eq!(log.record(&mut third, &[6..7]), 3);
+        let patch = third.vertex_patch.unwrap();
+        assert_eq!(patch.since, 1);
+        assert!(third.vertices.is_empty());
+        let spans: Vec<(usize, usize)> = patch
+            .writes
+            .iter()
+            .map(|(offset, bytes)| (offset / stride, bytes.len() / stride))
+            .collect();
+        assert_eq!(spans, vec![(1, 1), (6, 1)]);
+        assert!(patch.writes.iter().all(|(_, b)| b.iter().all(|&x| x == 3)));
+
+        // Once frame 3 is applied, frame 4 only carries its own span.
+        applied.store(3, Ordering::Release);
+        let mut fourth = frame(10, 4);
+        let _ = log.record(&mut fourth, &[4..5]);
+        assert_eq!(fourth.vertex_patch.unwrap().writes.len(), 1);
+
+        // A layout change goes out whole.
+        let mut grown = frame(12, 5);
+        let _ = log.record(&mut grown, &[]);
+        assert!(grown.vertex_patch.is_none());
+        assert_eq!(grown.vertices.len(), 12 * stride);
+
+        // Main thread lost the buffer (applied reset): whole again.
+        applied.store(0, Ordering::Release);
+        let mut lost = frame(12, 6);
+        let _ = log.record(&mut lost, &[0..1]);
+        assert!(lost.vertex_patch.is_none());
+    }
+}
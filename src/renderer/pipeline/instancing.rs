@@ -0,0 +1,279 @@
+//! Rigid entity copies drawn as instances of one backbone mesh.
+//!
+//! Icosahedral capsids and helical filaments repeat the same chain
+//! hundreds of times. Entities whose topology, secondary structure and
+//! putty radii match an earlier entity, and whose coordinates differ
+//! from it only by a rigid transform, reuse that entity's backbone mesh:
+//! the copy is drawn with the transform instead of being tessellated
+//! again. Colors are not part of the match -- copies look theirs up
+//! per residue on the GPU.
+
+use glam::{Mat4, Vec3};
+use molex::entity::molecule::id::EntityId;
+use molex::SSType;
+use rustc_hash::FxHashMap;
+
+use super::prepared::FullRebuildEntity;
+use crate::options::DrawingMode;
+use crate::renderer::entity_topology::EntityTopology;
+use crate::renderer::geometry::backbone::SheetOffset;
+use crate::util::superpose;
+
+/// Largest per-atom deviation (angstroms) a copy may show after the
+/// fit and still share the prototype's mesh. Far below anything
+/// visible in a cartoon.
+const RIGID_TOLERANCE: f32 = 0.05;
+
+/// An entity drawn as a transformed instance of an earlier one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct RigidCopy {
+    /// Entity whose backbone mesh is reused.
+    pub(super) prototype: EntityId,
+    /// Maps the prototype's coordinates onto the copy's.
+    pub(super) transform: Mat4,
+}
+
+/// Find every entity that can be drawn as an instance of an earlier
+/// entity, keyed on the copy.
+///
+/// Only Cartoon-mode protein entities without per-entity option
+/// overrides take part. The prototype is always the first matching
+/// entity in `entities` order, so a copy's residues come after its
+/// prototype's in the flat residue numbering.
+pub(super) fn find_rigid_copies(
+    entities: &[FullRebuildEntity],
+    is_overridden: impl Fn(EntityId) -> bool,
+) -> FxHashMap<EntityId, RigidCopy> {
+    let mut copies = FxHashMap::default();
+    // Prototypes bucketed by atom count; shape is checked per pair.
+    let mut prototypes: FxHashMap<usize, Vec<&FullRebuildEntity>> =
+        FxHashMap::default();
+    for e in entities {
+        if e.drawing_mode != DrawingMode::Cartoon
+            || !e.topology.is_protein()
+            || e.positions.is_empty()
+            || is_overridden(e.id)
+        {
+            continue;
+        }
+        let bucket = prototypes.entry(e.positions.len()).or_default();
+        let matched = bucket.iter().find_map(|proto| {
+            if !same_shape(proto, e) {
+                return None;
+            }
+            rigid_transform(&proto.positions, &e.positions).map(|transform| {
+                RigidCopy {
+                    prototype: proto.id,
+                    transform,
+                }
+            })
+        });
+        match matched {
+            Some(copy) => {
+                let _ = copies.insert(e.id, copy);
+            }
+            None => bucket.push(e),
+        }
+    }
+    copies
+}
+
+/// Transform mapping `prototype` onto `copy` (paired by index), or
+/// `None` when any atom deviates by more than [`RIGID_TOLERANCE`] after
+/// the best rigid fit.
+pub(super) fn rigid_transform(
+    prototype: &[Vec3],
+    copy: &[Vec3],
+) -> Option<Mat4> {
+    let fit = superpose::fit(prototype, copy)?;
+    if fit.rmsd > RIGID_TOLERANCE {
+        return None;
+    }
+    let tolerance_sq = RIGID_TOLERANCE * RIGID_TOLERANCE;
+    let rigid = prototype
+        .iter()
+        .zip(copy)
+        .all(|(&p, &q)| fit.apply(p).distance_squared(q) <= tolerance_sq);
+    rigid.then(|| {
+        Mat4::from_rotation_translation(
+            fit.rotation,
+            fit.reference_center - fit.rotation * fit.mobile_center,
+        )
+    })
+}
+
+/// A prototype's sheet-surface offsets carried onto a copy: same
+/// (entity-local) residues, deltas rotated into the copy's frame.
+pub(super) fn transform_sheet_offsets(
+    offsets: &[SheetOffset],
+    transform: Mat4,
+) -> Vec<SheetOffset> {
+    offsets
+        .iter()
+        .map(|so| SheetOffset {
+            residue_idx: so.residue_idx,
+            offset: transform.transform_vector3(so.offset),
+        })
+        .collect()
+}
+
+/// Whether two entities tessellate to the same backbone mesh up to a
+/// rigid transform: same atoms, residues, backbone layout, secondary
+/// structure and putty radii.
+fn same_shape(a: &FullRebuildEntity, b: &FullRebuildEntity) -> bool {
+    let ss = |e: &FullRebuildEntity| -> Vec<SSType> {
+        e.ss_override
+            .clone()
+            .unwrap_or_else(|| e.topology.ss_types.clone())
+    };
+    same_topology(&a.topology, &b.topology)
+        && a.per_residue_radii == b.per_residue_radii
+        && (a.ss_override == b.ss_override || ss(a) == ss(b))
+}
+
+fn same_topology(a: &EntityTopology, b: &EntityTopology) -> bool {
+    if std::ptr::eq(a, b) {
+        return true;
+    }
+    a.atom_elements == b.atom_elements
+        && a.residue_names == b.residue_names
+        && a.residue_atom_ranges == b.residue_atom_ranges
+        && a.protein_backbone_layout.len() == b.protein_backbone_layout.len()
+        && a.protein_backbone_layout
+            .iter()
+            .zip(&b.protein_backbone_layout)
+            .all(|(x, y)| {
+                x.n == y.n && x.ca == y.ca && x.c == y.c && x.o == y.o
+            })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use glam::Quat;
+    use molex::entity::molecule::id::EntityIdAllocator;
+    use molex::{Element, MoleculeType};
+
+    use super::*;
+    use crate::renderer::entity_topology::{
+        ProteinBackboneIndices, SidechainLayout,
+    };
+
+    fn id(raw: u32) -> EntityId {
+        EntityIdAllocator::new().from_raw(raw)
+    }
+
+    /// Three residues of N/CA/C/O laid out along a gentle zigzag.
+    fn topology() -> EntityTopology {
+        let n = vec![0, 4, 8];
+        let ca = vec![1, 5, 9];
+        let c = vec![2, 6, 10];
+        let o = vec![3, 7, 11];
+        EntityTopology {
+            molecule_type: MoleculeType::Protein,
+            protein_backbone_layout: vec![ProteinBackboneIndices {
+                n,
+                ca,
+                c,
+                o,
+            }],
+            na_backbone_chain_layout: Vec::new(),
+            sidechain_layout: SidechainLayout::empty(),
+            ring_topology: Vec::new(),
+            na_residue_base_colors: Vec::new(),
+            na_guide_atom_indices: Vec::new(),
+            ss_types: vec![SSType::Coil; 3],
+            atom_elements: [Element::N, Element::C, Element::C, Element::O]
+                .repeat(3),
+            atom_residue_index: (0..12).map(|i| i / 4).collect(),
+            residue_names: vec![*b"GLY"; 3],
+            residue_atom_ranges: vec![0..4, 4..8, 8..12],
+            bonds: Vec::new(),
+        }
+    }
+
+    fn positions() -> Vec<Vec3> {
+        (0..12)
+            .map(|i| {
+                let i = i as f32;
+                Vec3::new(i * 1.2, (i * 0.9).sin(), (i * 0.4).cos() * 0.7)
+            })
+            .collect()
+    }
+
+    fn entity(raw: u32, positions: Vec<Vec3>) -> FullRebuildEntity {
+        FullRebuildEntity {
+            id: id(raw),
+            mesh_version: 0,
+            drawing_mode: DrawingMode::Cartoon,
+            topology: Arc::new(topology()),
+            positions,
+            ss_override: None,
+            per_residue_colors: None,
+            per_residue_radii: None,
+        }
+    }
+
+    fn moved(transform: Mat4) -> Vec<Vec3> {
+        positions()
+            .into_iter()
+            .map(|p| transform.transform_point3(p))
+            .collect()
+    }
+
+    fn operator() -> Mat4 {
+        Mat4::from_rotation_translation(
+            Quat::from_axis_angle(Vec3::new(1.0, 2.0, 0.5).normalize(), 1.1),
+            Vec3::new(30.0, -12.0, 4.0),
+        )
+    }
+
+    #[test]
+    fn rigid_copy_shares_first_entity() {
+        let entities = vec![
+            entity(1, positions()),
+            entity(2, moved(operator())),
+            entity(3, moved(operator().inverse())),
+        ];
+        let copies = find_rigid_copies(&entities, |_| false);
+        assert_eq!(copies.len(), 2);
+        for raw in [2, 3] {
+            let copy = copies[&id(raw)];
+            assert_eq!(copy.prototype, id(1));
+        }
+    }
+
+    #[test]
+    #[allow(clippy::panic)]
+    fn recovered_transform_maps_prototype_onto_copy() {
+        let target = moved(operator());
+        let Some(transform) = rigid_transform(&positions(), &target) else {
+            panic!("rigid copy should fit");
+        };
+        for (p, q) in positions().iter().zip(&target) {
+            assert!(transform.transform_point3(*p).distance(*q) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn perturbed_copy_is_meshed_on_its_own() {
+        let mut bent = moved(operator());
+        bent[5] += Vec3::new(0.0, 0.4, 0.0);
+        let entities = vec![entity(1, positions()), entity(2, bent)];
+        assert!(find_rigid_copies(&entities, |_| false).is_empty());
+    }
+
+    #[test]
+    fn differing_secondary_structure_or_overrides_are_not_shared() {
+        let mut helix = entity(2, moved(operator()));
+        helix.ss_override = Some(vec![SSType::Helix; 3]);
+        let entities = vec![entity(1, positions()), helix];
+        assert!(find_rigid_copies(&entities, |_| false).is_empty());
+
+        let entities =
+            vec![entity(1, positions()), entity(2, moved(operator()))];
+        let copies = find_rigid_copies(&entities, |e| e == id(2));
+        assert!(copies.is_empty());
+    }
+}
@@ -1,6 +1,15 @@
+use std::ops::Range;
+
+use rustc_hash::FxHashMap;
+
+use super::instancing::RigidCopy;
 use super::prepared::{
-    BackboneMeshData, BallAndStickInstances, CachedEntityMesh,
-    NucleicAcidInstances, PreparedRebuild,
+    BackboneInstance, BackboneMeshData, BallAndStickInstances, CachedBackbone,
+    CachedEntityMesh, CachedInstance, NucleicAcidInstances, PreparedRebuild,
+};
+use crate::renderer::culling::InstanceGroup;
+use crate::renderer::geometry::backbone::coarse::{
+    CoarseInstance, BLOB_RESIDUE,
 };
 use crate::renderer::geometry::backbone::{ChainRange, SheetOffset};
 use crate::renderer::picking::PickMap;
@@ -73,16 +82,172 @@ fn offset_bns_pick_ids(dst: &mut Vec<u8>, src: &[u8], patch: &BnsPickPatch) {
     }
 }
 
-/// Accumulator that merges per-entity cached meshes into combined buffers.
+/// Where a prototype's backbone landed in the concatenated buffers.
+struct PrototypeSpan {
+    residue_start: u32,
+    chains: Range<usize>,
+    sheets: Range<usize>,
+}
+
+/// Accumulator for concatenated backbone geometry plus the instances
+/// that reuse it. Shared by full rebuilds and animation frames.
 #[derive(Default)]
-struct MeshAccumulator {
-    // Backbone
-    backbone_verts: Vec<u8>,
-    backbone_tube_inds: Vec<u32>,
-    backbone_ribbon_inds: Vec<u32>,
-    backbone_vert_offset: u32,
+pub(super) struct BackboneAccumulator {
+    verts: Vec<u8>,
+    tube_inds: Vec<u32>,
+    ribbon_inds: Vec<u32>,
+    vert_offset: u32,
     sheet_offsets: Vec<SheetOffset>,
     chain_ranges: Vec<ChainRange>,
+    coarse: Vec<CoarseInstance>,
+    spans: FxHashMap<u32, PrototypeSpan>,
+    instances: Vec<BackboneInstance>,
+}
+
+impl BackboneAccumulator {
+    /// Append one entity's backbone, shifting its residue indices by
+    /// `residue_offset`.
+    pub(super) fn push(
+        &mut self,
+        entity_id: u32,
+        backbone: &CachedBackbone,
+        residue_offset: u32,
+    ) {
+        let chain_start = self.chain_ranges.len();
+        let sheet_start = self.sheet_offsets.len();
+        offset_vertex_residue_idx(
+            &mut self.verts,
+            &backbone.verts,
+            residue_offset,
+        );
+        for &idx in &backbone.tube_inds {
+            self.tube_inds.push(idx + self.vert_offset);
+        }
+        for &idx in &backbone.ribbon_inds {
+            self.ribbon_inds.push(idx + self.vert_offset);
+        }
+        for so in &backbone.sheet_offsets {
+            self.sheet_offsets.push(SheetOffset {
+                residue_idx: so.residue_idx + residue_offset,
+                offset: so.offset,
+            });
+        }
+        let tube_idx_offset =
+            self.tube_inds.len() as u32 - backbone.tube_inds.len() as u32;
+        let ribbon_idx_offset =
+            self.ribbon_inds.len() as u32 - backbone.ribbon_inds.len() as u32;
+        let coarse_offset = self.coarse.len() as u32;
+        self.coarse
+            .extend(backbone.coarse.iter().map(|c| CoarseInstance {
+                residue_idx: if c.residue_idx == BLOB_RESIDUE {
+                    BLOB_RESIDUE
+                } else {
+                    c.residue_idx + residue_offset
+                },
+                anchor: [
+                    c.anchor[0],
+                    c.anchor[1],
+                    c.anchor[2],
+                    c.anchor[3] + residue_offset as f32,
+                ],
+                ..*c
+            }));
+        for r in &backbone.chain_ranges {
+            let tube = r.tube();
+            let ribbon = r.ribbon();
+            let coarse = r.coarse();
+            self.chain_ranges.push(
+                ChainRange::new(
+                    tube.start + tube_idx_offset..tube.end + tube_idx_offset,
+                    ribbon.start + ribbon_idx_offset
+                        ..ribbon.end + ribbon_idx_offset,
+                    r.bounding_center,
+                    r.bounding_radius,
+                )
+                .with_coarse(
+                    coarse.start + coarse_offset..coarse.end + coarse_offset,
+                ),
+            );
+        }
+        self.vert_offset += backbone.vert_count;
+        let _ = self.spans.insert(
+            entity_id,
+            PrototypeSpan {
+                residue_start: residue_offset,
+                chains: chain_start..self.chain_ranges.len(),
+                sheets: sheet_start..self.sheet_offsets.len(),
+            },
+        );
+    }
+
+    /// Vertices pushed so far; the next push starts at this vertex.
+    pub(super) fn vertex_count(&self) -> u32 {
+        self.vert_offset
+    }
+
+    /// Record entity `entity_id`, whose residues start at
+    /// `residue_offset`, as an instance of its (already pushed)
+    /// prototype.
+    pub(super) fn push_instance(
+        &mut self,
+        entity_id: u32,
+        copy: &RigidCopy,
+        residue_offset: u32,
+    ) {
+        let Some(span) = self.spans.get(&copy.prototype.raw()) else {
+            return;
+        };
+        self.instances.push(BackboneInstance {
+            entity: entity_id,
+            model: copy.transform,
+            residue_offset: residue_offset - span.residue_start,
+            chains: span.chains.clone(),
+        });
+    }
+
+    /// A pushed entity's sheet offsets with entity-local residue
+    /// indices, as the sidechain adjustment of its copies expects.
+    pub(super) fn local_sheet_offsets(
+        &self,
+        entity_id: u32,
+    ) -> Vec<SheetOffset> {
+        self.spans.get(&entity_id).map_or_else(Vec::new, |span| {
+            self.sheet_offsets[span.sheets.clone()]
+                .iter()
+                .map(|so| SheetOffset {
+                    residue_idx: so.residue_idx - span.residue_start,
+                    offset: so.offset,
+                })
+                .collect()
+        })
+    }
+
+    pub(super) fn into_mesh_data(
+        self,
+        instance_colors: Option<Vec<[f32; 4]>>,
+    ) -> BackboneMeshData {
+        BackboneMeshData {
+            tube_index_count: self.tube_inds.len() as u32,
+            ribbon_index_count: self.ribbon_inds.len() as u32,
+            vertices: self.verts,
+            tube_indices: bytemuck::cast_slice(&self.tube_inds).to_vec(),
+            ribbon_indices: bytemuck::cast_slice(&self.ribbon_inds).to_vec(),
+            sheet_offsets: self.sheet_offsets,
+            chain_ranges: self.chain_ranges,
+            coarse_instances: bytemuck::cast_slice(&self.coarse).to_vec(),
+            instances: self.instances,
+            instance_colors,
+            vertex_patch: None,
+        }
+    }
+}
+
+/// Accumulator that merges per-entity cached meshes into combined buffers.
+#[derive(Default)]
+struct MeshAccumulator {
+    backbone: BackboneAccumulator,
+    /// Flat-residue colors of instanced copies; empty without copies.
+    instance_colors: Vec<[f32; 4]>,
     // Sidechain instances
     sidechain_bytes: Vec<u8>,
     sidechain_count: u32,
@@ -91,22 +256,38 @@ struct MeshAccumulator {
     bns_sphere_count: u32,
     bns_capsules: Vec<u8>,
     bns_capsule_count: u32,
+    bns_sphere_groups: Vec<InstanceGroup>,
+    bns_capsule_groups: Vec<InstanceGroup>,
     bns_pick_offset: u32,
     // Nucleic acid instances
     na_stem_bytes: Vec<u8>,
     na_stem_count: u32,
     na_ring_bytes: Vec<u8>,
     na_ring_count: u32,
+    na_stem_groups: Vec<InstanceGroup>,
+    na_ring_groups: Vec<InstanceGroup>,
     /// Pick-map entries per entity that produced BnS content:
     /// `(entity_id, atom_count)`.
     bns_pick_entities: Vec<(u32, u32)>,
+    /// Pick-map entries per entity with residues:
+    /// `(entity_id, residue_count)`.
+    residue_pick_entities: Vec<(u32, u32)>,
     // Residue tracking
     residue_offset: u32,
 }
 
 impl MeshAccumulator {
     fn push_entity(&mut self, mesh: &CachedEntityMesh) {
-        self.push_backbone(mesh);
+        self.backbone
+            .push(mesh.entity_id, &mesh.backbone, self.residue_offset);
+        if let Some(instance) = &mesh.instance {
+            self.backbone.push_instance(
+                mesh.entity_id,
+                &instance.copy,
+                self.residue_offset,
+            );
+            self.push_instance_colors(instance, mesh.residue_count);
+        }
 
         // Sidechain instances (self-contained)
         self.sidechain_bytes
@@ -116,6 +297,16 @@ impl MeshAccumulator {
         self.push_bns(mesh);
 
         // NA instances (self-contained)
+        push_groups(
+            &mut self.na_stem_groups,
+            &mesh.na.stem_groups,
+            self.na_stem_count,
+        );
+        push_groups(
+            &mut self.na_ring_groups,
+            &mesh.na.ring_groups,
+            self.na_ring_count,
+        );
         self.na_stem_bytes
             .extend_from_slice(&mesh.na.stem_instances);
         self.na_stem_count += mesh.na.stem_count;
@@ -127,49 +318,41 @@ impl MeshAccumulator {
             self.bns_pick_entities
                 .push((mesh.entity_id, mesh.bns_atom_count));
         }
+        if mesh.residue_count > 0 {
+            self.residue_pick_entities
+                .push((mesh.entity_id, mesh.residue_count));
+        }
 
         self.residue_offset += mesh.residue_count;
     }
 
-    fn push_backbone(&mut self, mesh: &CachedEntityMesh) {
-        offset_vertex_residue_idx(
-            &mut self.backbone_verts,
-            &mesh.backbone.verts,
-            self.residue_offset,
-        );
-        for &idx in &mesh.backbone.tube_inds {
-            self.backbone_tube_inds
-                .push(idx + self.backbone_vert_offset);
-        }
-        for &idx in &mesh.backbone.ribbon_inds {
-            self.backbone_ribbon_inds
-                .push(idx + self.backbone_vert_offset);
-        }
-        for so in &mesh.backbone.sheet_offsets {
-            self.sheet_offsets.push(SheetOffset {
-                residue_idx: so.residue_idx + self.residue_offset,
-                offset: so.offset,
-            });
-        }
-        let tube_idx_offset = self.backbone_tube_inds.len() as u32
-            - mesh.backbone.tube_inds.len() as u32;
-        let ribbon_idx_offset = self.backbone_ribbon_inds.len() as u32
-            - mesh.backbone.ribbon_inds.len() as u32;
-        for r in &mesh.backbone.chain_ranges {
-            let tube = r.tube();
-            let ribbon = r.ribbon();
-            self.chain_ranges.push(ChainRange::new(
-                tube.start + tube_idx_offset..tube.end + tube_idx_offset,
-                ribbon.start + ribbon_idx_offset
-                    ..ribbon.end + ribbon_idx_offset,
-                r.bounding_center,
-                r.bounding_radius,
-            ));
+    fn push_instance_colors(
+        &mut self,
+        instance: &CachedInstance,
+        residue_count: u32,
+    ) {
+        let Some(colors) = instance.colors.as_deref() else {
+            return;
+        };
+        let start = self.residue_offset as usize;
+        self.instance_colors
+            .resize(start + residue_count as usize, [0.0; 4]);
+        for (dst, c) in self.instance_colors[start..].iter_mut().zip(colors) {
+            *dst = [c[0], c[1], c[2], 1.0];
         }
-        self.backbone_vert_offset += mesh.backbone.vert_count;
     }
 
     fn push_bns(&mut self, mesh: &CachedEntityMesh) {
+        push_groups(
+            &mut self.bns_sphere_groups,
+            &mesh.bns.sphere_groups,
+            self.bns_sphere_count,
+        );
+        push_groups(
+            &mut self.bns_capsule_groups,
+            &mesh.bns.capsule_groups,
+            self.bns_capsule_count,
+        );
         // SphereInstance: 32 bytes, pick_id at byte 28 (color.w)
         offset_bns_pick_ids(
             &mut self.bns_spheres,
@@ -216,28 +399,15 @@ impl MeshAccumulator {
             }
         }
         PickMap::new(self.residue_offset, atom_entries)
+            .with_residue_entities(self.residue_pick_entities.clone())
     }
 
     fn into_prepared_rebuild(mut self) -> PreparedRebuild {
         self.finalize_bns_pick_ids();
         let pick_map = self.build_pick_map();
-        let tube_index_count = self.backbone_tube_inds.len() as u32;
-        let ribbon_index_count = self.backbone_ribbon_inds.len() as u32;
         PreparedRebuild {
             generation: 0,
-            backbone: BackboneMeshData {
-                vertices: self.backbone_verts,
-                tube_indices: bytemuck::cast_slice(&self.backbone_tube_inds)
-                    .to_vec(),
-                tube_index_count,
-                ribbon_indices: bytemuck::cast_slice(
-                    &self.backbone_ribbon_inds,
-                )
-                .to_vec(),
-                ribbon_index_count,
-                sheet_offsets: self.sheet_offsets,
-                chain_ranges: self.chain_ranges,
-            },
+            backbone: self.backbone.into_mesh_data(Some(self.instance_colors)),
             sidechain_instances: self.sidechain_bytes,
             sidechain_instance_count: self.sidechain_count,
             bns: BallAndStickInstances {
@@ -245,18 +415,32 @@ impl MeshAccumulator {
                 sphere_count: self.bns_sphere_count,
                 capsule_instances: self.bns_capsules,
                 capsule_count: self.bns_capsule_count,
+                sphere_groups: self.bns_sphere_groups,
+                capsule_groups: self.bns_capsule_groups,
             },
             na: NucleicAcidInstances {
                 stem_instances: self.na_stem_bytes,
                 stem_count: self.na_stem_count,
                 ring_instances: self.na_ring_bytes,
                 ring_count: self.na_ring_count,
+                stem_groups: self.na_stem_groups,
+                ring_groups: self.na_ring_groups,
             },
             pick_map,
         }
     }
 }
 
+/// Append an entity's culling groups, shifted past the `count`
+/// instances already accumulated.
+fn push_groups(
+    dst: &mut Vec<InstanceGroup>,
+    src: &[InstanceGroup],
+    count: u32,
+) {
+    dst.extend(src.iter().map(|group| group.offset(count)));
+}
+
 /// Patch f32 pick IDs in a raw byte buffer by adding `delta`.
 fn patch_pick_id_buffer(
     buf: &mut [u8],
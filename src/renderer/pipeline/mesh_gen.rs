@@ -1,3 +1,5 @@
+use std::ops::Range;
+use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 
 use glam::Vec3;
@@ -5,8 +7,11 @@ use molex::entity::molecule::id::EntityId;
 use molex::SSType;
 use rustc_hash::FxHashMap;
 
+use super::frame_patch::PatchLog;
+use super::instancing::{rigid_transform, transform_sheet_offsets, RigidCopy};
+use super::mesh_concat::BackboneAccumulator;
 use super::prepared::{
-    BackboneMeshData, BallAndStickInstances, CachedBackbone, CachedEntityMesh,
+    BallAndStickInstances, CachedBackbone, CachedEntityMesh, CachedInstance,
     FullRebuildEntity, NucleicAcidInstances, PreparedAnimationFrame,
 };
 use crate::engine::positions::EntityPositions;
@@ -14,8 +19,9 @@ use crate::options::{
     ChainLod, ColorOptions, DisplayOptions, DrawingMode, GeometryOptions,
     NaColorMode, SidechainColorMode,
 };
+use crate::renderer::culling::group_instances;
 use crate::renderer::entity_topology::{EntityTopology, SidechainLayout};
-use crate::renderer::geometry::backbone::SheetOffset;
+use crate::renderer::geometry::backbone::{BackboneMeshOutput, SheetOffset};
 use crate::renderer::geometry::sheet_adjust::{
     adjust_bonds_for_sheet, adjust_sidechains_for_sheet,
 };
@@ -23,6 +29,7 @@ use crate::renderer::geometry::{
     BackboneRenderer, BallAndStickRenderer, NucleicAcidRenderer,
     SidechainRenderer, SidechainView,
 };
+use crate::renderer::impostor::CapsuleInstance;
 
 // ---------------------------------------------------------------------------
 // Sidechain capsule instance helper
@@ -145,7 +152,7 @@ fn generate_non_backbone_bytes(
     } else {
         Vec::new()
     };
-    let (na_stems, na_rings) = NucleicAcidRenderer::generate_instances(&rings);
+    let na = NucleicAcidRenderer::generate_instances(&rings);
     let bns_atoms = if bns_spheres.is_empty() && bns_capsules.is_empty() {
         0
     } else {
@@ -157,12 +164,16 @@ fn generate_non_backbone_bytes(
             sphere_count: bns_spheres.len() as u32,
             capsule_instances: bytemuck::cast_slice(&bns_capsules).to_vec(),
             capsule_count: bns_capsules.len() as u32,
+            sphere_groups: group_instances(&bns_spheres, |_| ()),
+            capsule_groups: group_instances(&bns_capsules, |_| ()),
         },
         NucleicAcidInstances {
-            stem_instances: bytemuck::cast_slice(&na_stems).to_vec(),
-            stem_count: na_stems.len() as u32,
-            ring_instances: bytemuck::cast_slice(&na_rings).to_vec(),
-            ring_count: na_rings.len() as u32,
+            stem_instances: bytemuck::cast_slice(&na.stems).to_vec(),
+            stem_count: na.stems.len() as u32,
+            ring_instances: bytemuck::cast_slice(&na.rings).to_vec(),
+            ring_count: na.rings.len() as u32,
+            stem_groups: na.stem_groups,
+            ring_groups: na.ring_groups,
         },
         bns_atoms,
     )
@@ -172,85 +183,149 @@ fn generate_non_backbone_bytes(
 // Entity mesh generation
 // ---------------------------------------------------------------------------
 
+/// Per-entity inputs to backbone tessellation.
+struct BackboneSource<'a> {
+    topology: &'a EntityTopology,
+    positions: &'a [Vec3],
+    ss_override: Option<&'a [SSType]>,
+    per_residue_colors: Option<&'a [[f32; 3]]>,
+    per_residue_radii: Option<&'a [f32]>,
+    /// Color NA backbone by base (NDB colors).
+    na_base_colors: bool,
+}
+
+/// Tessellate one entity's cartoon backbone, patching `prev` (its
+/// previous tessellation) where only some residues moved.
+fn mesh_backbone(
+    source: &BackboneSource,
+    geometry: &GeometryOptions,
+    per_chain_lod: Option<&[ChainLod]>,
+    prev: Option<&BackboneMeshOutput>,
+) -> BackboneMeshOutput {
+    let topology = source.topology;
+    let positions = source.positions;
+    let is_na = topology.is_nucleic_acid();
+    let protein_chains = if is_na {
+        Vec::new()
+    } else {
+        topology.protein_backbone_chains(positions)
+    };
+    let na_chains = if is_na {
+        topology.na_backbone_chain_positions(positions)
+    } else {
+        Vec::new()
+    };
+
+    // Residue-parallel with the P-atom stream (built per residue,
+    // not per resolvable ring) so a skipped/modified base doesn't
+    // shift every later base's backbone color.
+    let na_base_colors: &[[f32; 3]] = if is_na && source.na_base_colors {
+        &topology.na_residue_base_colors
+    } else {
+        &[]
+    };
+    let na_colors_ref = (!na_base_colors.is_empty()).then_some(na_base_colors);
+
+    let na_seeds: Vec<Option<Vec3>> = if is_na {
+        topology.na_chain_seed_normals(positions)
+    } else {
+        Vec::new()
+    };
+    let na_seeds_ref = (!na_seeds.is_empty()).then_some(na_seeds.as_slice());
+
+    let na_guides: Vec<Vec3> = if is_na {
+        topology.na_residue_guide_dirs(positions)
+    } else {
+        Vec::new()
+    };
+    let na_guides_ref = (!na_guides.is_empty()).then_some(na_guides.as_slice());
+
+    let ss_slice = source
+        .ss_override
+        .or(Some(topology.ss_types.as_slice()))
+        .filter(|s| !s.is_empty());
+
+    BackboneRenderer::generate_mesh_colored(
+        &protein_chains,
+        &na_chains,
+        ss_slice,
+        source.per_residue_colors,
+        source.per_residue_radii,
+        geometry,
+        per_chain_lod,
+        na_colors_ref,
+        na_seeds_ref,
+        na_guides_ref,
+        prev,
+    )
+}
+
+/// Convert a tessellated backbone into its cached concatenation form.
+fn cached_backbone(mesh: &BackboneMeshOutput) -> CachedBackbone {
+    CachedBackbone {
+        verts: bytemuck::cast_slice(&mesh.vertices).to_vec(),
+        tube_inds: mesh.tube_indices.clone(),
+        ribbon_inds: mesh.ribbon_indices.clone(),
+        vert_count: mesh.vertices.len() as u32,
+        sheet_offsets: mesh.sheet_offsets.clone(),
+        chain_ranges: mesh.chain_ranges.clone(),
+        coarse: mesh.coarse.clone(),
+    }
+}
+
+/// Number of protein backbone residues an entity contributes to the
+/// flat residue numbering (nucleic acids contribute none).
+pub(super) fn protein_residue_count(topology: &EntityTopology) -> u32 {
+    if topology.is_protein() {
+        topology
+            .protein_backbone_layout
+            .iter()
+            .map(|seg| seg.ca.len() as u32)
+            .sum()
+    } else {
+        0
+    }
+}
+
 /// Generate mesh for a single entity.
+///
+/// With `instance` set the entity is a rigid copy: its backbone is not
+/// tessellated, and its sidechains are adjusted against the prototype's
+/// sheet offsets (entity-local) carried through the copy transform.
 pub(super) fn generate_entity_mesh(
     entity: &FullRebuildEntity,
     display: &DisplayOptions,
     colors: &ColorOptions,
     geometry: &GeometryOptions,
+    instance: Option<(&RigidCopy, &[SheetOffset])>,
 ) -> CachedEntityMesh {
     let skip_backbone = entity.drawing_mode != DrawingMode::Cartoon;
     let topology = &entity.topology;
 
-    let backbone_mesh = if skip_backbone {
-        BackboneRenderer::generate_mesh_colored(
-            &[],
-            &[],
-            None,
-            None,
-            geometry,
-            None,
-            None,
-            None,
-            None,
-        )
+    let backbone_mesh = if let Some((copy, prototype_sheets)) = instance {
+        BackboneMeshOutput {
+            sheet_offsets: transform_sheet_offsets(
+                prototype_sheets,
+                copy.transform,
+            ),
+            ..BackboneMeshOutput::default()
+        }
+    } else if skip_backbone {
+        BackboneMeshOutput::default()
     } else {
-        let is_na = topology.is_nucleic_acid();
-        let protein_chains = if is_na {
-            Vec::new()
-        } else {
-            topology.protein_backbone_chains(&entity.positions)
-        };
-        let na_chains = if is_na {
-            topology.na_backbone_chain_positions(&entity.positions)
-        } else {
-            Vec::new()
-        };
-
-        // Residue-parallel with the P-atom stream (built per residue,
-        // not per resolvable ring) so a skipped/modified base doesn't
-        // shift every later base's backbone color.
-        let na_base_colors: &[[f32; 3]] =
-            if is_na && display.na_color_mode() == NaColorMode::BaseColor {
-                &topology.na_residue_base_colors
-            } else {
-                &[]
-            };
-        let na_colors_ref =
-            (!na_base_colors.is_empty()).then_some(na_base_colors);
-
-        let na_seeds: Vec<Option<Vec3>> = if is_na {
-            topology.na_chain_seed_normals(&entity.positions)
-        } else {
-            Vec::new()
-        };
-        let na_seeds_ref =
-            (!na_seeds.is_empty()).then_some(na_seeds.as_slice());
-
-        let na_guides: Vec<Vec3> = if is_na {
-            topology.na_residue_guide_dirs(&entity.positions)
-        } else {
-            Vec::new()
-        };
-        let na_guides_ref =
-            (!na_guides.is_empty()).then_some(na_guides.as_slice());
-
-        let ss_slice = entity
-            .ss_override
-            .as_deref()
-            .or_else(|| Some(topology.ss_types.as_slice()))
-            .filter(|s| !s.is_empty());
-
-        BackboneRenderer::generate_mesh_colored(
-            &protein_chains,
-            &na_chains,
-            ss_slice,
-            entity.per_residue_colors.as_deref(),
+        mesh_backbone(
+            &BackboneSource {
+                topology,
+                positions: &entity.positions,
+                ss_override: entity.ss_override.as_deref(),
+                per_residue_colors: entity.per_residue_colors.as_deref(),
+                per_residue_radii: entity.per_residue_radii.as_deref(),
+                na_base_colors: display.na_color_mode()
+                    == NaColorMode::BaseColor,
+            },
             geometry,
             None,
-            na_colors_ref,
-            na_seeds_ref,
-            na_guides_ref,
+            None,
         )
     };
 
@@ -269,32 +344,19 @@ pub(super) fn generate_entity_mesh(
     let (bns, na, bns_atom_count) =
         generate_non_backbone_bytes(entity, display, colors);
 
-    let residue_count = if topology.is_protein() {
-        topology
-            .protein_backbone_layout
-            .iter()
-            .map(|seg| seg.ca.len() as u32)
-            .sum()
-    } else {
-        0
-    };
-
     CachedEntityMesh {
-        backbone: CachedBackbone {
-            verts: bytemuck::cast_slice(&backbone_mesh.vertices).to_vec(),
-            tube_inds: backbone_mesh.tube_indices,
-            ribbon_inds: backbone_mesh.ribbon_indices,
-            vert_count: backbone_mesh.vertices.len() as u32,
-            sheet_offsets: backbone_mesh.sheet_offsets,
-            chain_ranges: backbone_mesh.chain_ranges,
-        },
+        backbone: cached_backbone(&backbone_mesh),
         sidechain_instances,
         sidechain_instance_count,
         bns,
         na,
-        residue_count,
+        residue_count: protein_residue_count(topology),
         bns_atom_count,
         entity_id: *entity.id,
+        instance: instance.map(|(copy, _)| CachedInstance {
+            copy: *copy,
+            colors: entity.per_residue_colors.clone(),
+        }),
     }
 }
 
@@ -306,15 +368,12 @@ pub(super) fn generate_entity_mesh(
 pub(super) struct AnimationFrameCache {
     /// Per-entity topology snapshots (same Arcs the main thread holds).
     pub topologies: FxHashMap<EntityId, Arc<EntityTopology>>,
-    /// Per-entity drawing-mode + SS-override + sidechain color lookup.
+    /// Per-entity drawing-mode + SS-override + color lookup.
     pub entity_meta: FxHashMap<EntityId, EntityMetaSnapshot>,
-    /// Concatenated SS types for Cartoon-mode entities only (feeds the
-    /// backbone mesh).
-    pub cartoon_ss_types: Option<Vec<SSType>>,
-    /// Concatenated per-residue colors for Cartoon-mode entities only.
-    pub cartoon_per_residue_colors: Option<Vec<[f32; 3]>>,
-    /// Concatenated NA base colors for Cartoon-mode NA entities.
-    pub cartoon_na_base_colors: Option<Vec<[f32; 3]>>,
+    /// Rigid copies found at the last rebuild. Each frame re-fits them
+    /// against the interpolated positions; copies that no longer move
+    /// rigidly with their prototype are tessellated on their own.
+    pub rigid_copies: FxHashMap<EntityId, RigidCopy>,
     /// Hydrophobic / hydrophilic sidechain color pair for the
     /// Hydrophobicity sidechain mode. Captured from the global
     /// [`crate::options::ColorOptions`] at rebuild time.
@@ -327,11 +386,17 @@ pub(super) struct AnimationFrameCache {
 #[derive(Clone)]
 pub(super) struct EntityMetaSnapshot {
     pub drawing_mode: DrawingMode,
-    /// Per-residue colors for this entity (used by Backbone-mode
-    /// sidechain coloring during animation frames). Mirrors
+    /// Per-residue colors for this entity (backbone vertex colors and
+    /// Backbone-mode sidechain coloring). Mirrors
     /// [`super::prepared::FullRebuildEntity::per_residue_colors`] at the
     /// last rebuild.
     pub per_residue_colors: Option<Vec<[f32; 3]>>,
+    /// SS override at the last rebuild.
+    pub ss_override: Option<Vec<SSType>>,
+    /// Putty radius multipliers at the last rebuild.
+    pub per_residue_radii: Option<Vec<f32>>,
+    /// Whether NA backbone is colored by base.
+    pub na_base_colors: bool,
     /// Resolved sidechain color mode for this entity (per-entity
     /// appearance overrides applied).
     pub sidechain_color_mode: SidechainColorMode,
@@ -346,167 +411,234 @@ pub(super) struct AnimationFrameInput<'a> {
     pub include_sidechains: bool,
 }
 
+/// Worker-side state carried from one animation frame to the next.
+pub(super) struct AnimationFrameState {
+    /// Each tessellated entity's backbone from the previous frame, so
+    /// chains whose residues barely moved are patched, not rebuilt.
+    backbones: FxHashMap<EntityId, BackboneMeshOutput>,
+    /// Frames sent since the last whole one.
+    patches: PatchLog,
+}
+
+impl AnimationFrameState {
+    pub(super) fn new(applied: Arc<AtomicU64>) -> Self {
+        Self {
+            backbones: FxHashMap::default(),
+            patches: PatchLog::new(applied),
+        }
+    }
+
+    /// Forget the previous frame (after a rebuild replaced the scene).
+    pub(super) fn reset(&mut self) {
+        self.backbones.clear();
+        self.patches.reset();
+    }
+}
+
 /// Generate backbone + optional sidechain mesh for an animation frame
 /// using only derived state + interpolated positions.
+///
+/// Entities are tessellated one at a time in rebuild order, so residue
+/// indices, chain ranges and instances line up with the last rebuild.
+/// Each entity's backbone is remeshed against its previous frame in
+/// `state`, and the frame goes out as a vertex patch when the main
+/// thread can take one.
 pub(super) fn process_animation_frame(
     input: &AnimationFrameInput,
+    state: &mut AnimationFrameState,
     generation: u64,
 ) -> PreparedAnimationFrame {
-    let (protein_chains, na_chains, na_seeds, na_guides) =
-        collect_cartoon_chains(input);
+    let copies = refit_rigid_copies(input);
 
-    let total_residues: usize =
-        protein_chains.iter().map(|c| c.ca().len()).sum::<usize>()
-            + na_chains.iter().map(|c| c.p().len()).sum::<usize>();
+    let total_residues: usize = input
+        .cache
+        .entity_order
+        .iter()
+        .filter(|id| !copies.contains_key(id))
+        .filter_map(|id| {
+            let meta = input.cache.entity_meta.get(id)?;
+            let topology = input.cache.topologies.get(id)?;
+            (meta.drawing_mode == DrawingMode::Cartoon).then(|| {
+                topology
+                    .protein_backbone_layout
+                    .iter()
+                    .map(|s| s.ca.len())
+                    .sum::<usize>()
+                    + topology
+                        .na_backbone_chain_layout
+                        .iter()
+                        .map(Vec::len)
+                        .sum::<usize>()
+            })
+        })
+        .sum();
     let safe_geo = input.geometry.clamped_for_residues(total_residues);
 
-    let backbone_mesh = BackboneRenderer::generate_mesh_colored(
-        &protein_chains,
-        &na_chains,
-        input.cache.cartoon_ss_types.as_deref(),
-        input.cache.cartoon_per_residue_colors.as_deref(),
-        &safe_geo,
-        input.per_chain_lod,
-        input.cache.cartoon_na_base_colors.as_deref(),
-        (!na_seeds.is_empty()).then_some(na_seeds.as_slice()),
-        (!na_guides.is_empty()).then_some(na_guides.as_slice()),
-    );
-    let backbone_tube_index_count = backbone_mesh.tube_indices.len() as u32;
-    let backbone_ribbon_index_count = backbone_mesh.ribbon_indices.len() as u32;
-    let backbone_vertices =
-        bytemuck::cast_slice(&backbone_mesh.vertices).to_vec();
-    let backbone_tube_indices =
-        bytemuck::cast_slice(&backbone_mesh.tube_indices).to_vec();
-    let backbone_ribbon_indices =
-        bytemuck::cast_slice(&backbone_mesh.ribbon_indices).to_vec();
-
-    let (sidechain_instances, sidechain_instance_count) =
-        if input.include_sidechains {
-            generate_animation_sidechains(input, &backbone_mesh.sheet_offsets)
-        } else {
-            (None, 0)
-        };
+    let mut backbone = BackboneAccumulator::default();
+    let mut sidechains: Vec<u8> = Vec::new();
+    let mut sidechain_count: u32 = 0;
+    let mut residue_offset: u32 = 0;
+    let mut lod_cursor: usize = 0;
+    let mut backbones = FxHashMap::default();
+    let mut dirty: Vec<Range<u32>> = Vec::new();
 
-    PreparedAnimationFrame {
-        backbone: BackboneMeshData {
-            vertices: backbone_vertices,
-            tube_indices: backbone_tube_indices,
-            tube_index_count: backbone_tube_index_count,
-            ribbon_indices: backbone_ribbon_indices,
-            ribbon_index_count: backbone_ribbon_index_count,
-            sheet_offsets: backbone_mesh.sheet_offsets,
-            chain_ranges: backbone_mesh.chain_ranges,
-        },
-        sidechain_instances,
-        sidechain_instance_count,
-        generation,
-    }
-}
-
-/// Walk cached Cartoon-mode entities in their last rebuild order and
-/// resolve each entity's backbone positions from the current animator
-/// frame.
-fn collect_cartoon_chains(
-    input: &AnimationFrameInput,
-) -> (
-    Vec<crate::renderer::entity_topology::ProteinBackboneChain>,
-    Vec<crate::renderer::entity_topology::NaBackboneChain>,
-    Vec<Option<Vec3>>,
-    Vec<Vec3>,
-) {
-    let mut protein_chains = Vec::new();
-    let mut na_chains: Vec<crate::renderer::entity_topology::NaBackboneChain> =
-        Vec::new();
-    // Per-NA-chain RMF roll seed (index-parallel with `na_chains`) and
-    // per-residue C1'-P guide field (residue-parallel with the
-    // concatenated P stream `process_na_chains` walks), both recomputed
-    // from the *interpolated* positions each frame.
-    let mut na_seeds: Vec<Option<Vec3>> = Vec::new();
-    let mut na_guides: Vec<Vec3> = Vec::new();
     for id in &input.cache.entity_order {
-        let Some(meta) = input.cache.entity_meta.get(id) else {
-            continue;
-        };
-        if meta.drawing_mode != DrawingMode::Cartoon {
-            continue;
-        }
-        let Some(topology) = input.cache.topologies.get(id) else {
+        let (Some(meta), Some(topology)) = (
+            input.cache.entity_meta.get(id),
+            input.cache.topologies.get(id),
+        ) else {
             continue;
         };
-        let Some(positions) = input.positions.get(*id) else {
+        let residues = protein_residue_count(topology);
+        let positions = input.positions.get(*id);
+        let (Some(positions), DrawingMode::Cartoon) =
+            (positions, meta.drawing_mode)
+        else {
+            residue_offset += residues;
             continue;
         };
-        if topology.is_nucleic_acid() {
-            na_chains.extend(topology.na_backbone_chain_positions(positions));
-            na_seeds.extend(topology.na_chain_seed_normals(positions));
-            na_guides.extend(topology.na_residue_guide_dirs(positions));
+
+        let sheet_offsets = if let Some(copy) = copies.get(id) {
+            backbone.push_instance(id.raw(), copy, residue_offset);
+            let prototype_sheets =
+                backbone.local_sheet_offsets(copy.prototype.raw());
+            let sheets =
+                transform_sheet_offsets(&prototype_sheets, copy.transform);
+            backbone.push(
+                id.raw(),
+                &CachedBackbone {
+                    verts: Vec::new(),
+                    tube_inds: Vec::new(),
+                    ribbon_inds: Vec::new(),
+                    vert_count: 0,
+                    sheet_offsets: sheets.clone(),
+                    chain_ranges: Vec::new(),
+                    coarse: Vec::new(),
+                },
+                residue_offset,
+            );
+            sheets
         } else {
-            protein_chains.extend(topology.protein_backbone_chains(positions));
+            let lod = input
+                .per_chain_lod
+                .map(|lod| lod.get(lod_cursor..).unwrap_or_default());
+            let mesh = mesh_backbone(
+                &BackboneSource {
+                    topology,
+                    positions,
+                    ss_override: meta.ss_override.as_deref(),
+                    per_residue_colors: meta.per_residue_colors.as_deref(),
+                    per_residue_radii: meta.per_residue_radii.as_deref(),
+                    na_base_colors: meta.na_base_colors,
+                },
+                &safe_geo,
+                lod,
+                state.backbones.get(id),
+            );
+            lod_cursor += mesh.chain_ranges.len();
+            let base = backbone.vertex_count();
+            dirty.extend(
+                mesh.dirty.iter().map(|r| r.start + base..r.end + base),
+            );
+            let cached = cached_backbone(&mesh);
+            backbone.push(id.raw(), &cached, residue_offset);
+            let _ = backbones.insert(*id, mesh);
+            cached.sheet_offsets
+        };
+
+        if input.include_sidechains {
+            let insts = animation_sidechains(
+                input,
+                meta,
+                topology,
+                positions,
+                &sheet_offsets,
+            );
+            sidechain_count += insts.len() as u32;
+            sidechains.extend_from_slice(bytemuck::cast_slice(&insts));
         }
+        residue_offset += residues;
+    }
+
+    state.backbones = backbones;
+    let mut backbone = backbone.into_mesh_data(None);
+    let sequence = state.patches.record(&mut backbone, &dirty);
+
+    PreparedAnimationFrame {
+        backbone,
+        sidechain_instances: input.include_sidechains.then_some(sidechains),
+        sidechain_instance_count: sidechain_count,
+        generation,
+        sequence,
     }
-    (protein_chains, na_chains, na_seeds, na_guides)
 }
 
-/// Concatenate per-entity sidechain capsule bytes for the animation
-/// frame.
-fn generate_animation_sidechains(
+/// Re-fit the last rebuild's rigid copies against this frame's
+/// positions, keeping those still rigid with their prototype.
+fn refit_rigid_copies(
     input: &AnimationFrameInput,
-    sheet_offsets: &[SheetOffset],
-) -> (Option<Vec<u8>>, u32) {
-    let mut combined: Vec<u8> = Vec::new();
-    let mut total_count: u32 = 0;
+) -> FxHashMap<EntityId, RigidCopy> {
+    input
+        .cache
+        .rigid_copies
+        .iter()
+        .filter_map(|(id, copy)| {
+            let prototype = input.positions.get(copy.prototype)?;
+            let positions = input.positions.get(*id)?;
+            let transform = rigid_transform(prototype, positions)?;
+            Some((
+                *id,
+                RigidCopy {
+                    prototype: copy.prototype,
+                    transform,
+                },
+            ))
+        })
+        .collect()
+}
 
-    for id in &input.cache.entity_order {
-        let Some(meta) = input.cache.entity_meta.get(id) else {
-            continue;
-        };
-        if meta.drawing_mode != DrawingMode::Cartoon {
-            continue;
-        }
-        let Some(topology) = input.cache.topologies.get(id) else {
-            continue;
-        };
-        if topology.sidechain_layout.atom_indices.is_empty() {
-            continue;
-        }
-        let Some(positions) = input.positions.get(*id) else {
-            continue;
-        };
-        let layout = &topology.sidechain_layout;
-        let (sidechain_positions, backbone_bonds) =
-            resolve_sidechain_atoms(layout, positions);
-        let adjusted_positions = adjust_sidechains_for_sheet(
-            &sidechain_positions,
-            &layout.residue_indices,
-            sheet_offsets,
-        );
-        let adjusted_bonds = adjust_bonds_for_sheet(
-            &backbone_bonds,
-            &layout.residue_indices,
-            sheet_offsets,
-        );
-        let view = SidechainView {
-            positions: &adjusted_positions,
-            bonds: &layout.bonds,
-            backbone_bonds: &adjusted_bonds,
-            hydrophobicity: &layout.hydrophobicity,
-            residue_indices: &layout.residue_indices,
-        };
-        let backbone_colors = (meta.sidechain_color_mode
-            == SidechainColorMode::Backbone)
-            .then_some(meta.per_residue_colors.as_deref())
-            .flatten();
-        let insts = SidechainRenderer::generate_instances(
-            &view,
-            None,
-            Some(input.cache.sidechain_palette),
-            backbone_colors,
-        );
-        total_count += insts.len() as u32;
-        combined.extend_from_slice(bytemuck::cast_slice(&insts));
+/// Sidechain capsule instances of one entity for the animation frame,
+/// adjusted against the entity's own (local) sheet offsets.
+fn animation_sidechains(
+    input: &AnimationFrameInput,
+    meta: &EntityMetaSnapshot,
+    topology: &EntityTopology,
+    positions: &[Vec3],
+    sheet_offsets: &[SheetOffset],
+) -> Vec<CapsuleInstance> {
+    let layout = &topology.sidechain_layout;
+    if layout.atom_indices.is_empty() {
+        return Vec::new();
     }
-
-    (Some(combined), total_count)
+    let (sidechain_positions, backbone_bonds) =
+        resolve_sidechain_atoms(layout, positions);
+    let adjusted_positions = adjust_sidechains_for_sheet(
+        &sidechain_positions,
+        &layout.residue_indices,
+        sheet_offsets,
+    );
+    let adjusted_bonds = adjust_bonds_for_sheet(
+        &backbone_bonds,
+        &layout.residue_indices,
+        sheet_offsets,
+    );
+    let view = SidechainView {
+        positions: &adjusted_positions,
+        bonds: &layout.bonds,
+        backbone_bonds: &adjusted_bonds,
+        hydrophobicity: &layout.hydrophobicity,
+        residue_indices: &layout.residue_indices,
+    };
+    let backbone_colors = (meta.sidechain_color_mode
+        == SidechainColorMode::Backbone)
+        .then_some(meta.per_residue_colors.as_deref())
+        .flatten();
+    SidechainRenderer::generate_instances(
+        &view,
+        None,
+        Some(input.cache.sidechain_palette),
+        backbone_colors,
+    )
 }
 
 #[cfg(test)]
@@ -3,6 +3,8 @@
 //! Converts scene data into GPU-ready byte buffers on a background
 //! thread. The main thread only does GPU uploads and render passes.
 
+pub(crate) mod frame_patch;
+mod instancing;
 mod mesh_concat;
 mod mesh_gen;
 pub(crate) mod prepared;
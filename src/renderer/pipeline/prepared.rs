@@ -1,15 +1,20 @@
+use std::ops::Range;
 use std::sync::Arc;
 
-use glam::Vec3;
+use glam::{Mat4, Vec3};
 use molex::entity::molecule::id::EntityId;
 use molex::SSType;
 use rustc_hash::FxHashMap;
 
+use super::frame_patch::VertexPatch;
+use super::instancing::RigidCopy;
 use crate::engine::positions::EntityPositions;
 use crate::options::{
     ColorOptions, DisplayOptions, DrawingMode, GeometryOptions,
 };
+use crate::renderer::culling::InstanceGroup;
 use crate::renderer::entity_topology::EntityTopology;
+use crate::renderer::geometry::backbone::coarse::CoarseInstance;
 use crate::renderer::geometry::backbone::{ChainRange, SheetOffset};
 use crate::renderer::picking::PickMap;
 
@@ -34,6 +39,30 @@ pub(crate) struct BackboneMeshData {
     pub(crate) sheet_offsets: Vec<SheetOffset>,
     /// Per-chain index ranges and bounding spheres for frustum culling.
     pub(crate) chain_ranges: Vec<ChainRange>,
+    /// Coarse-LOD instance bytes (residue spheres and chain blobs).
+    pub(crate) coarse_instances: Vec<u8>,
+    /// Rigid entity copies drawn with another entity's chain ranges.
+    pub(crate) instances: Vec<BackboneInstance>,
+    /// Per-residue colors of instanced copies (flat residue index, alpha
+    /// 0 where no color applies). `None` keeps the uploaded colors.
+    pub(crate) instance_colors: Option<Vec<[f32; 4]>>,
+    /// Animation frames only: sub-range vertex writes replacing the
+    /// whole-buffer upload. When set, `vertices` and both index byte
+    /// buffers are empty and the index counts are unchanged.
+    pub(crate) vertex_patch: Option<VertexPatch>,
+}
+
+/// A rigid entity copy drawn with its prototype's backbone mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct BackboneInstance {
+    /// Raw id of the copy's entity.
+    pub(crate) entity: u32,
+    /// Transform from the prototype's coordinates to the copy's.
+    pub(crate) model: Mat4,
+    /// Added to the prototype's residue indices to give the copy's.
+    pub(crate) residue_offset: u32,
+    /// The prototype's span of [`BackboneMeshData::chain_ranges`].
+    pub(crate) chains: Range<usize>,
 }
 
 /// Ball-and-stick instance data (GPU-ready byte buffers).
@@ -43,10 +72,14 @@ pub(crate) struct BallAndStickInstances {
     pub(crate) sphere_instances: Vec<u8>,
     /// Number of spheres.
     pub(crate) sphere_count: u32,
+    /// Culling groups over the spheres.
+    pub(crate) sphere_groups: Vec<InstanceGroup>,
     /// Capsule (bond) instance bytes.
     pub(crate) capsule_instances: Vec<u8>,
     /// Number of capsules.
     pub(crate) capsule_count: u32,
+    /// Culling groups over the capsules.
+    pub(crate) capsule_groups: Vec<InstanceGroup>,
 }
 
 /// Nucleic acid instance data (GPU-ready byte buffers).
@@ -56,10 +89,14 @@ pub(crate) struct NucleicAcidInstances {
     pub(crate) stem_instances: Vec<u8>,
     /// Number of stem instances.
     pub(crate) stem_count: u32,
+    /// Per-chain culling groups over the stems.
+    pub(crate) stem_groups: Vec<InstanceGroup>,
     /// Ring polygon instance bytes.
     pub(crate) ring_instances: Vec<u8>,
     /// Number of ring instances.
     pub(crate) ring_count: u32,
+    /// Per-chain culling groups over the rings.
+    pub(crate) ring_groups: Vec<InstanceGroup>,
 }
 
 // ---------------------------------------------------------------------------
@@ -74,6 +111,7 @@ pub(super) struct CachedBackbone {
     pub vert_count: u32,
     pub sheet_offsets: Vec<SheetOffset>,
     pub chain_ranges: Vec<ChainRange>,
+    pub coarse: Vec<CoarseInstance>,
 }
 
 // ---------------------------------------------------------------------------
@@ -105,6 +143,9 @@ pub(crate) struct FullRebuildEntity {
     /// Per-residue vertex colors for Cartoon-mode protein entities.
     /// `None` when the current color scheme produces no per-residue colors.
     pub(crate) per_residue_colors: Option<Vec<[f32; 3]>>,
+    /// Per-residue tube radius multipliers for the putty cartoon style
+    /// (cartoon residue order). `None` renders at unit radius.
+    pub(crate) per_residue_radii: Option<Vec<f32>>,
 }
 
 /// Body of a full scene rebuild request, boxed on the enum variant to
@@ -185,6 +226,9 @@ pub(crate) struct PreparedAnimationFrame {
     pub(crate) sidechain_instance_count: u32,
     /// Rebuild generation this frame was produced for.
     pub(crate) generation: u64,
+    /// Frame sequence number, reported back once applied so later
+    /// frames can be sent as vertex patches.
+    pub(crate) sequence: u64,
 }
 
 // ---------------------------------------------------------------------------
@@ -211,4 +255,16 @@ pub(super) struct CachedEntityMesh {
     pub bns_atom_count: u32,
     /// Entity id, recorded per cached mesh for pick map reconstruction.
     pub entity_id: u32,
+    /// Set when the backbone is drawn as an instance of another entity's
+    /// mesh; `backbone` then only carries the copy's sheet offsets.
+    pub instance: Option<CachedInstance>,
+}
+
+/// Instancing record of a cached copy entity.
+pub(super) struct CachedInstance {
+    /// Prototype and transform.
+    pub copy: RigidCopy,
+    /// The copy's own per-residue colors, looked up by the GPU in place
+    /// of the prototype's baked vertex colors.
+    pub colors: Option<Vec<[f32; 3]>>,
 }
@@ -8,19 +8,23 @@
 //! being regenerated. Global settings changes (view mode, display,
 //! colors) clear the entire cache.
 
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{mpsc, Arc};
 
+use glam::Mat4;
 use molex::entity::molecule::id::EntityId;
-use molex::SSType;
 use rustc_hash::{FxHashMap, FxHashSet};
 
-use super::mesh_gen::{AnimationFrameCache, EntityMetaSnapshot};
+use super::instancing::RigidCopy;
+use super::mesh_gen::{
+    AnimationFrameCache, AnimationFrameState, EntityMetaSnapshot,
+};
 use super::prepared::{
     AnimationFrameBody, CachedEntityMesh, FullRebuildBody, FullRebuildEntity,
     PreparedAnimationFrame, PreparedRebuild, SceneRequest,
 };
 use crate::options::{
-    ColorOptions, DisplayOptions, DrawingMode, GeometryOptions, NaColorMode,
+    ColorOptions, DisplayOptions, GeometryOptions, NaColorMode,
 };
 
 // ---------------------------------------------------------------------------
@@ -85,6 +89,11 @@ pub(crate) struct SceneProcessor {
     /// consumption. While set, the backbone renderer's cached chains are
     /// stale — LOD must not read them.
     rebuild_pending: bool,
+    /// Sequence of the last animation frame whose backbone vertices the
+    /// main thread uploaded, 0 when its buffer holds anything else. The
+    /// worker reads it to decide whether a frame may be sent as a
+    /// vertex patch.
+    applied_frame: Arc<AtomicU64>,
 }
 
 impl SceneProcessor {
@@ -98,9 +107,16 @@ impl SceneProcessor {
         let (rebuild_input, rebuild_output) =
             triple_buffer::triple_buffer(&None);
         let (anim_input, anim_output) = triple_buffer::triple_buffer(&None);
+        let applied_frame = Arc::new(AtomicU64::new(0));
+        let worker_applied = Arc::clone(&applied_frame);
 
         let worker = spawn_background(move || {
-            Self::thread_loop(request_rx, rebuild_input, anim_input);
+            Self::thread_loop(
+                request_rx,
+                rebuild_input,
+                anim_input,
+                worker_applied,
+            );
         })?;
 
         Ok(Self {
@@ -110,6 +126,7 @@ impl SceneProcessor {
             worker,
             rebuild_generation: 0,
             rebuild_pending: false,
+            applied_frame,
         })
     }
 
@@ -181,6 +198,9 @@ impl SceneProcessor {
     /// (bumping `rebuild_generation`), all prior animation frames
     /// become stale — even before the rebuild result arrives on the
     /// main thread.
+    ///
+    /// Also discards vertex patches made against a frame the backbone
+    /// buffer no longer holds (see [`Self::invalidate_frames`]).
     pub(crate) fn try_recv_animation(
         &mut self,
     ) -> Option<PreparedAnimationFrame> {
@@ -194,9 +214,31 @@ impl SceneProcessor {
             );
             return None;
         }
+        if let Some(patch) = &prepared.backbone.vertex_patch {
+            let applied = self.applied_frame.load(Ordering::Acquire);
+            if applied < patch.since {
+                log::debug!(
+                    "Discarding vertex patch (since {} > applied {applied})",
+                    patch.since,
+                );
+                return None;
+            }
+        }
         Some(prepared)
     }
 
+    /// Report that animation frame `sequence` is now in the backbone
+    /// buffer, so later frames may be sent as patches on top of it.
+    pub(crate) fn mark_frame_applied(&self, sequence: u64) {
+        self.applied_frame.store(sequence, Ordering::Release);
+    }
+
+    /// Report that the backbone buffer was overwritten by something
+    /// other than an animation frame; the next frame is sent whole.
+    pub(crate) fn invalidate_frames(&self) {
+        self.applied_frame.store(0, Ordering::Release);
+    }
+
     /// Shut down the background thread and wait for it to finish.
     pub(crate) fn shutdown(&mut self) {
         let _ = self.request_tx.send(SceneRequest::Shutdown);
@@ -209,8 +251,10 @@ impl SceneProcessor {
         request_rx: mpsc::Receiver<SceneRequest>,
         mut rebuild_input: triple_buffer::Input<Option<PreparedRebuild>>,
         mut anim_input: triple_buffer::Input<Option<PreparedAnimationFrame>>,
+        applied_frame: Arc<AtomicU64>,
     ) {
         let mut cache = MeshCache::new();
+        let mut frames = AnimationFrameState::new(applied_frame);
         // Generation of the last FullRebuild processed on this thread.
         let mut last_rebuild_generation: u64 = 0;
 
@@ -229,18 +273,28 @@ impl SceneProcessor {
                         generation,
                     } = *body;
                     last_rebuild_generation = generation;
+                    frames.reset();
+                    let rigid_copies = detect_rigid_copies(
+                        &entities,
+                        &geometry,
+                        &entity_options,
+                    );
                     cache.cache_stable_data(
                         &entities,
                         &display,
                         &colors,
                         &entity_options,
+                        &rigid_copies,
                     );
                     let entity_meshes = cache.update(
                         &entities,
-                        &display,
-                        &colors,
-                        &geometry,
-                        &entity_options,
+                        &SceneSettings {
+                            display: &display,
+                            colors: &colors,
+                            geometry: &geometry,
+                            entity_options: &entity_options,
+                        },
+                        &rigid_copies,
                     );
                     let mut prepared =
                         super::mesh_concat::concatenate_meshes(&entity_meshes);
@@ -266,6 +320,7 @@ impl SceneProcessor {
                             per_chain_lod: per_chain_lod.as_deref(),
                             include_sidechains,
                         },
+                        &mut frames,
                         generation,
                     );
                     anim_input.write(Some(prepared));
@@ -281,13 +336,30 @@ impl Drop for SceneProcessor {
     }
 }
 
+/// Global and per-entity options a full rebuild meshes with.
+struct SceneSettings<'a> {
+    display: &'a DisplayOptions,
+    colors: &'a ColorOptions,
+    geometry: &'a GeometryOptions,
+    entity_options: &'a FxHashMap<u32, (DisplayOptions, GeometryOptions)>,
+}
+
+/// Cache key of one entity's mesh: its own `mesh_version`, plus the
+/// prototype id, version and transform when it is drawn as an instance
+/// (a copy's sidechains depend on the prototype's sheet offsets).
+#[derive(PartialEq)]
+struct MeshKey {
+    version: u64,
+    instance_of: Option<(EntityId, u64, Mat4)>,
+}
+
 /// Per-entity mesh cache with settings-based invalidation.
 ///
 /// Caches per-entity geometry keyed on [`EntityId`], plus an
 /// [`AnimationFrameCache`] snapshot so `AnimationFrame` requests can be
 /// regenerated using only derived state and interpolated positions.
 struct MeshCache {
-    meshes: FxHashMap<EntityId, (u64, CachedEntityMesh)>,
+    meshes: FxHashMap<EntityId, (MeshKey, CachedEntityMesh)>,
     last_display: Option<DisplayOptions>,
     last_colors: Option<ColorOptions>,
     last_geometry: Option<GeometryOptions>,
@@ -304,9 +376,7 @@ impl MeshCache {
             anim_cache: AnimationFrameCache {
                 topologies: FxHashMap::default(),
                 entity_meta: FxHashMap::default(),
-                cartoon_ss_types: None,
-                cartoon_per_residue_colors: None,
-                cartoon_na_base_colors: None,
+                rigid_copies: FxHashMap::default(),
                 sidechain_palette: ([1.0, 1.0, 1.0], [0.5, 0.5, 0.5]),
                 entity_order: Vec::new(),
             },
@@ -321,10 +391,12 @@ impl MeshCache {
         display: &DisplayOptions,
         colors: &ColorOptions,
         entity_options: &FxHashMap<u32, (DisplayOptions, GeometryOptions)>,
+        rigid_copies: &FxHashMap<EntityId, RigidCopy>,
     ) {
         self.anim_cache.topologies.clear();
         self.anim_cache.entity_meta.clear();
         self.anim_cache.entity_order.clear();
+        self.anim_cache.rigid_copies.clone_from(rigid_copies);
         self.anim_cache.sidechain_palette =
             (colors.hydrophobic_sidechain, colors.hydrophilic_sidechain);
         for e in entities {
@@ -339,79 +411,34 @@ impl MeshCache {
                 EntityMetaSnapshot {
                     drawing_mode: e.drawing_mode,
                     per_residue_colors: e.per_residue_colors.clone(),
+                    ss_override: e.ss_override.clone(),
+                    per_residue_radii: e.per_residue_radii.clone(),
+                    na_base_colors: entity_display.na_color_mode()
+                        == NaColorMode::BaseColor,
                     sidechain_color_mode: entity_display.sidechain_color_mode(),
                 },
             );
             self.anim_cache.entity_order.push(e.id);
         }
-
-        // SS types: only Cartoon-mode entities contribute, in entity
-        // order. Uses ss_override when present, falls back to
-        // topology.ss_types.
-        let mut ss: Vec<SSType> = Vec::new();
-        for e in entities {
-            if e.drawing_mode != DrawingMode::Cartoon {
-                continue;
-            }
-            if let Some(ovr) = e.ss_override.as_deref() {
-                ss.extend_from_slice(ovr);
-            } else {
-                ss.extend_from_slice(&e.topology.ss_types);
-            }
-        }
-        self.anim_cache.cartoon_ss_types =
-            if ss.is_empty() { None } else { Some(ss) };
-
-        // Per-residue colors: only Cartoon-mode entities contribute.
-        let mut colors: Vec<[f32; 3]> = Vec::new();
-        for e in entities {
-            if e.drawing_mode != DrawingMode::Cartoon {
-                continue;
-            }
-            if let Some(c) = e.per_residue_colors.as_deref() {
-                colors.extend_from_slice(c);
-            }
-        }
-        self.anim_cache.cartoon_per_residue_colors = if colors.is_empty() {
-            None
-        } else {
-            Some(colors)
-        };
-
-        // NA base colors: only Cartoon-mode NA entities contribute when
-        // BaseColor mode is active.
-        let mut na_colors: Vec<[f32; 3]> = Vec::new();
-        if display.na_color_mode() == NaColorMode::BaseColor {
-            for e in entities {
-                if e.drawing_mode != DrawingMode::Cartoon
-                    || !e.topology.is_nucleic_acid()
-                {
-                    continue;
-                }
-                na_colors.extend_from_slice(&e.topology.na_residue_base_colors);
-            }
-        }
-        self.anim_cache.cartoon_na_base_colors = if na_colors.is_empty() {
-            None
-        } else {
-            Some(na_colors)
-        };
     }
 
     /// Update cached meshes and return entity-ordered references for
     /// concatenation.
+    ///
+    /// Entities in `rigid_copies` skip backbone tessellation; their
+    /// prototypes always precede them in `entities`, so the prototype's
+    /// mesh is current by the time a copy needs its sheet offsets.
     fn update(
         &mut self,
         entities: &[FullRebuildEntity],
-        display: &DisplayOptions,
-        colors: &ColorOptions,
-        geometry: &GeometryOptions,
-        entity_options: &FxHashMap<u32, (DisplayOptions, GeometryOptions)>,
+        settings: &SceneSettings,
+        rigid_copies: &FxHashMap<EntityId, RigidCopy>,
     ) -> Vec<&CachedEntityMesh> {
         // Clamp geometry detail so the concatenated vertex buffer stays
-        // under the wgpu 256 MB max.
+        // under the wgpu 256 MB max. Instanced copies add no vertices.
         let total_residues: usize = entities
             .iter()
+            .filter(|e| !rigid_copies.contains_key(&e.id))
             .map(|e| {
                 let protein = e
                     .topology
@@ -428,43 +455,60 @@ impl MeshCache {
                 protein + na
             })
             .sum();
-        let geometry = geometry.clamped_for_residues(total_residues);
+        let geometry = settings.geometry.clamped_for_residues(total_residues);
 
         // Any settings change (geometry, display, or colors) clears the
         // entire cache because backbone colors are baked into vertex data.
         let settings_changed = self.last_geometry.as_ref() != Some(&geometry)
-            || self.last_display.as_ref() != Some(display)
-            || self.last_colors.as_ref() != Some(colors);
+            || self.last_display.as_ref() != Some(settings.display)
+            || self.last_colors.as_ref() != Some(settings.colors);
 
         if settings_changed {
             self.meshes.clear();
         }
-        self.last_display = Some(display.clone());
-        self.last_colors = Some(colors.clone());
+        self.last_display = Some(settings.display.clone());
+        self.last_colors = Some(settings.colors.clone());
         self.last_geometry = Some(geometry.clone());
 
+        let versions: FxHashMap<EntityId, u64> =
+            entities.iter().map(|e| (e.id, e.mesh_version)).collect();
+
         // Generate or reuse per-entity meshes.
         for e in entities {
-            let entity_u32 = *e.id;
-            let needs_regen = self
-                .meshes
-                .get(&e.id)
-                .is_none_or(|(v, _)| *v != e.mesh_version);
-            if needs_regen {
-                let (e_display, e_geometry) =
-                    if let Some((d, g)) = entity_options.get(&entity_u32) {
-                        (d, g.clamped_for_residues(total_residues))
-                    } else {
-                        (display, geometry.clone())
-                    };
-                let mesh = super::mesh_gen::generate_entity_mesh(
-                    e,
-                    e_display,
-                    colors,
-                    &e_geometry,
-                );
-                drop(self.meshes.insert(e.id, (e.mesh_version, mesh)));
+            let copy = rigid_copies.get(&e.id);
+            let key = MeshKey {
+                version: e.mesh_version,
+                instance_of: copy.map(|c| {
+                    (
+                        c.prototype,
+                        versions.get(&c.prototype).copied().unwrap_or(0),
+                        c.transform,
+                    )
+                }),
+            };
+            if self.meshes.get(&e.id).is_some_and(|(k, _)| *k == key) {
+                continue;
             }
+            let (e_display, e_geometry) = if let Some((d, g)) =
+                settings.entity_options.get(&e.id.raw())
+            {
+                (d, g.clamped_for_residues(total_residues))
+            } else {
+                (settings.display, geometry.clone())
+            };
+            let prototype_sheets = copy.and_then(|c| {
+                self.meshes
+                    .get(&c.prototype)
+                    .map(|(_, mesh)| mesh.backbone.sheet_offsets.clone())
+            });
+            let mesh = super::mesh_gen::generate_entity_mesh(
+                e,
+                e_display,
+                settings.colors,
+                &e_geometry,
+                copy.zip(prototype_sheets.as_deref()),
+            );
+            drop(self.meshes.insert(e.id, (key, mesh)));
         }
 
         // Evict removed entities.
@@ -480,6 +524,21 @@ impl MeshCache {
     }
 }
 
+/// Rigid copies to draw instanced, or none when instancing is off.
+/// Entities with per-entity option overrides always mesh on their own.
+fn detect_rigid_copies(
+    entities: &[FullRebuildEntity],
+    geometry: &GeometryOptions,
+    entity_options: &FxHashMap<u32, (DisplayOptions, GeometryOptions)>,
+) -> FxHashMap<EntityId, RigidCopy> {
+    if !geometry.instance_copies {
+        return FxHashMap::default();
+    }
+    super::instancing::find_rigid_copies(entities, |id| {
+        entity_options.contains_key(&id.raw())
+    })
+}
+
 /// Drain queued requests, keeping only the latest.
 ///
 /// Special case: a queued `AnimationFrame` does NOT replace a pending
@@ -9,5 +9,17 @@ struct CameraUniform {
     hovered_residue: i32,
     debug_mode: u32,
     time: f32,
-    _pad: f32,
+    // Look-at target; surface cutaways remove what lies between the
+    // camera and the plane through it facing the camera.
+    focus: vec3<f32>,
 };
+
+/// Transform a model-space point into world space.
+fn model_point(model: mat4x4<f32>, p: vec3<f32>) -> vec3<f32> {
+    return (model * vec4<f32>(p, 1.0)).xyz;
+}
+
+/// Transform a model-space direction into world space.
+fn model_dir(model: mat4x4<f32>, d: vec3<f32>) -> vec3<f32> {
+    return (model * vec4<f32>(d, 0.0)).xyz;
+}
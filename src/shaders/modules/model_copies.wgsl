@@ -0,0 +1,40 @@
+#define_import_path viso::model_copies
+
+// One drawn copy of some geometry.
+struct ModelInstance {
+    // Rigid transform placing the copy in the world: a symmetry
+    // operator (times the copy transform for instanced entities).
+    model: mat4x4<f32>,
+    // Added to mesh residue indices when a rigid entity copy is drawn
+    // with its prototype's backbone mesh; 0 otherwise.
+    residue_offset: u32,
+};
+
+// The copy transforms, one contiguous run per set of copies.
+@group(0) @binding(1) var<storage, read> model_instances: array<ModelInstance>;
+// `[first, count]` run of `model_instances` each pick ID is drawn with.
+// Entry 0 covers geometry without a pick ID and IDs past the end.
+@group(0) @binding(2) var<storage, read> copy_runs: array<vec2<u32>>;
+
+// Clip position outside the view volume, for vertices of copies their
+// geometry isn't drawn in.
+const CULLED_POSITION: vec4<f32> = vec4<f32>(2.0, 2.0, 2.0, 1.0);
+
+struct ModelCopy {
+    model: mat4x4<f32>,
+    residue_offset: u32,
+    // Whether the geometry is drawn in this copy at all.
+    drawn: bool,
+};
+
+/// Copy `copy` (the draw's instance) of geometry with 1-based pick ID
+/// `pick_id` (0 for none).
+fn model_copy(pick_id: u32, copy: u32) -> ModelCopy {
+    var run = copy_runs[0];
+    if (pick_id < arrayLength(&copy_runs)) {
+        run = copy_runs[pick_id];
+    }
+    let drawn = copy < run.y;
+    let instance = model_instances[run.x + select(0u, copy, drawn)];
+    return ModelCopy(instance.model, instance.residue_offset, drawn);
+}
@@ -1,7 +1,8 @@
 // Ray-marched capsule impostors for sidechain rendering
 // Capsules = cylinders with hemispherical caps
 
-#import viso::camera::CameraUniform
+#import viso::camera::{CameraUniform, model_point}
+#import viso::model_copies::{model_copy, CULLED_POSITION}
 #import viso::lighting::LightingUniform
 #import viso::ray::{intersect_capsule, capsule_normal}
 #import viso::selection::check_selection
@@ -52,13 +53,15 @@ fn vs_main(
         vec2(-1.0, 1.0), vec2(1.0, -1.0), vec2(1.0, 1.0)
     );
 
+    // Six vertices per copy of the instance.
     let cap = capsules[iidx];
-    let endpoint_a = cap.endpoint_a.xyz;
-    let endpoint_b = cap.endpoint_b.xyz;
-    let color_a = cap.color_a.xyz;
-    let color_b = cap.color_b.xyz;
     // Residue index packed in endpoint_b.w
     let residue_idx = u32(cap.endpoint_b.w);
+    let copy = model_copy(residue_idx + 1u, vidx / 6u);
+    let endpoint_a = model_point(copy.model, cap.endpoint_a.xyz);
+    let endpoint_b = model_point(copy.model, cap.endpoint_b.xyz);
+    let color_a = cap.color_a.xyz;
+    let color_b = cap.color_b.xyz;
 
     // Use radius from instance data (packed in endpoint_a.w)
     // Fall back to TUBE_RADIUS if instance radius is 0 (legacy compatibility)
@@ -93,12 +96,12 @@ fn vs_main(
     let half_width = radius * BILLBOARD_SCALE;
     let half_height = seg_length * 0.5 + radius * BILLBOARD_SCALE;
 
-    let local_uv = quad[vidx];
+    let local_uv = quad[vidx % 6u];
     let world_offset = right * local_uv.x * half_width + up * local_uv.y * half_height;
     let world_pos = center + world_offset;
 
     var out: VertexOutput;
-    out.clip_position = camera.view_proj * vec4<f32>(world_pos, 1.0);
+    out.clip_position = select(CULLED_POSITION, camera.view_proj * vec4<f32>(world_pos, 1.0), copy.drawn);
     out.world_pos = world_pos;
     out.endpoint_a = endpoint_a;
     out.endpoint_b = endpoint_b;
@@ -0,0 +1,188 @@
+// Coarse-grained chain stand-ins: one sphere per residue, or one blob
+// per chain. Ray-cast like the sphere impostor; each instance fades in
+// and out by its chain's camera distance with a screen-door dither, so
+// the geometry stays opaque and depth-correct while tiers cross-fade.
+
+#import viso::camera::{CameraUniform, model_point}
+#import viso::model_copies::{model_copy, CULLED_POSITION}
+#import viso::lighting::LightingUniform
+#import viso::ray::intersect_sphere
+#import viso::selection::check_selection
+#import viso::highlight::apply_highlight
+#import viso::shade::{shade_geometry, ShadingResult}
+#import viso::constants::{MAX_IBL_MIP, BILLBOARD_SCALE}
+
+// Must match the Rust `CoarseInstance` layout.
+struct CoarseInstance {
+    center: vec4<f32>,  // xyz=position, w=radius
+    color: vec3<f32>,
+    residue_idx: u32,   // 0xffffffff for chain blobs
+    anchor: vec4<f32>,  // xyz=chain center driving the fade, w=first residue
+    window: vec4<f32>,  // start, opaque, end of the distance window
+};
+
+const BLOB_RESIDUE: u32 = 0xffffffffu;
+
+fn is_selected(residue_idx: u32) -> bool {
+    if (residue_idx == BLOB_RESIDUE) {
+        return false;
+    }
+    return check_selection(residue_idx, arrayLength(&selection), selection[residue_idx / 32u]);
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_pos: vec3<f32>,
+    @location(1) sphere_center: vec3<f32>,
+    @location(2) radius: f32,
+    @location(3) color: vec3<f32>,
+    @location(4) @interpolate(flat) residue_idx: u32,
+    @location(5) @interpolate(flat) opacity: f32,
+};
+
+struct FragOut {
+    @builtin(frag_depth) depth: f32,
+    @location(0) color: vec4<f32>,
+    @location(1) normal: vec4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+@group(1) @binding(0) var<uniform> lighting: LightingUniform;
+@group(1) @binding(1) var irradiance_map: texture_cube<f32>;
+@group(1) @binding(2) var env_sampler: sampler;
+@group(1) @binding(3) var prefiltered_map: texture_cube<f32>;
+@group(1) @binding(4) var brdf_lut: texture_2d<f32>;
+@group(2) @binding(0) var<storage, read> selection: array<u32>;
+@group(3) @binding(0) var<storage, read> instances: array<CoarseInstance>;
+// Per-residue colors of instanced entity copies (alpha 0 = keep the
+// baked color), shared with the backbone pass.
+@group(3) @binding(1) var<storage, read> instance_colors: array<vec4<f32>>;
+
+fn instance_color(residue_idx: u32, residue_offset: u32, baked: vec3<f32>) -> vec3<f32> {
+    if (residue_offset == 0u || residue_idx >= arrayLength(&instance_colors)) {
+        return baked;
+    }
+    let c = instance_colors[residue_idx];
+    return select(baked, c.rgb, c.a > 0.0);
+}
+
+/// Opacity of an instance whose chain center is `distance` from the eye.
+fn window_opacity(window: vec4<f32>, distance: f32) -> f32 {
+    if (distance < window.x || distance >= window.z) {
+        return 0.0;
+    }
+    return clamp((distance - window.x) / max(window.y - window.x, 1e-3), 0.0, 1.0);
+}
+
+/// 4x4 ordered-dither threshold in (0, 1) for a pixel.
+fn dither_threshold(pixel: vec2<f32>) -> f32 {
+    let bayer = array<f32, 16>(
+        0.0, 8.0, 2.0, 10.0,
+        12.0, 4.0, 14.0, 6.0,
+        3.0, 11.0, 1.0, 9.0,
+        15.0, 7.0, 13.0, 5.0
+    );
+    let p = vec2<u32>(pixel) % 4u;
+    return (bayer[p.y * 4u + p.x] + 0.5) / 16.0;
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vidx: u32,
+    @builtin(instance_index) iidx: u32
+) -> VertexOutput {
+    let quad = array<vec2<f32>, 6>(
+        vec2(-1.0, -1.0), vec2(1.0, -1.0), vec2(-1.0, 1.0),
+        vec2(-1.0, 1.0), vec2(1.0, -1.0), vec2(1.0, 1.0)
+    );
+
+    // Six vertices per copy of the instance.
+    let inst = instances[iidx];
+    let copy = model_copy(u32(inst.anchor.w) + 1u, vidx / 6u);
+    let anchor = model_point(copy.model, inst.anchor.xyz);
+    let opacity = window_opacity(inst.window, distance(anchor, camera.position));
+
+    var out: VertexOutput;
+    if (opacity <= 0.0 || !copy.drawn) {
+        // Outside its window: collapse the quad off-screen.
+        out.clip_position = CULLED_POSITION;
+        out.opacity = 0.0;
+        return out;
+    }
+
+    let center = model_point(copy.model, inst.center.xyz);
+    let radius = inst.center.w;
+    var residue_idx = inst.residue_idx;
+    if (residue_idx != BLOB_RESIDUE) {
+        residue_idx = residue_idx + copy.residue_offset;
+    }
+
+    let to_camera = normalize(camera.position - center);
+    var right = cross(to_camera, vec3<f32>(0.0, 1.0, 0.0));
+    if (length(right) < 0.001) {
+        right = cross(to_camera, vec3<f32>(0.0, 0.0, 1.0));
+    }
+    right = normalize(right);
+    let up = normalize(cross(right, to_camera));
+
+    let half_size = radius * BILLBOARD_SCALE;
+    let local_uv = quad[vidx % 6u];
+    let world_pos = center + right * local_uv.x * half_size + up * local_uv.y * half_size;
+
+    out.clip_position = camera.view_proj * vec4<f32>(world_pos, 1.0);
+    out.world_pos = world_pos;
+    out.sphere_center = center;
+    out.radius = radius;
+    out.color = select(instance_color(residue_idx, copy.residue_offset, inst.color), inst.color,
+        residue_idx == BLOB_RESIDUE);
+    out.residue_idx = residue_idx;
+    out.opacity = opacity;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragOut {
+    if (in.opacity < dither_threshold(in.clip_position.xy)) {
+        discard;
+    }
+
+    let ray_origin = camera.position;
+    let ray_dir = normalize(in.world_pos - camera.position);
+    let t = intersect_sphere(ray_origin, ray_dir, in.sphere_center, in.radius);
+    if (t < 0.0) {
+        discard;
+    }
+
+    let world_hit = ray_origin + ray_dir * t;
+    let normal = normalize(world_hit - in.sphere_center);
+    let view_dir = normalize(camera.position - world_hit);
+
+    let hovered = in.residue_idx != BLOB_RESIDUE && camera.hovered_residue >= 0
+        && u32(camera.hovered_residue) == in.residue_idx;
+    let highlighted = apply_highlight(in.color, hovered, is_selected(in.residue_idx));
+    let base_color = highlighted.xyz;
+    let outline_factor = highlighted.w;
+
+    // Pre-sample IBL textures (modules cannot reference bindings)
+    let NdotV = max(dot(normal, view_dir), 0.0);
+    let R = reflect(-view_dir, normal);
+    let irradiance = textureSample(irradiance_map, env_sampler, normal).rgb;
+    let prefiltered = textureSampleLevel(prefiltered_map, env_sampler, R,
+        lighting.roughness * MAX_IBL_MIP).rgb;
+    let brdf = textureSample(brdf_lut, env_sampler, vec2<f32>(NdotV, lighting.roughness)).rg;
+
+    let result = shade_geometry(normal, view_dir, base_color, outline_factor,
+        lighting, irradiance, prefiltered, brdf);
+
+    let clip_pos = camera.view_proj * vec4<f32>(world_hit, 1.0);
+
+    var out: FragOut;
+    out.depth = clip_pos.z / clip_pos.w;
+    if (camera.debug_mode == 1u) {
+        out.color = vec4<f32>(normal * 0.5 + 0.5, 1.0);
+    } else {
+        out.color = vec4<f32>(result.color, 1.0);
+    }
+    out.normal = vec4<f32>(normal, result.ambient_ratio);
+    return out;
+}
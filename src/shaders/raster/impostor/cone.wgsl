@@ -1,7 +1,8 @@
 // Ray-marched cone impostor for pull arrow rendering
 // Cone points from base (atom) toward tip (mouse target)
 
-#import viso::camera::CameraUniform
+#import viso::camera::{CameraUniform, model_point}
+#import viso::model_copies::{model_copy, CULLED_POSITION}
 #import viso::lighting::LightingUniform
 #import viso::ray::{intersect_cone, cone_normal}
 #import viso::selection::check_selection
@@ -59,12 +60,14 @@ fn vs_main(
         vec2(-1.0, 1.0), vec2(1.0, -1.0), vec2(1.0, 1.0)
     );
 
+    // Six vertices per copy of the instance.
     let cone = cones[iidx];
-    let base = cone.base.xyz;
-    let tip = cone.tip.xyz;
+    let residue_idx = u32(cone.tip.w);
+    let copy = model_copy(residue_idx + 1u, vidx / 6u);
+    let base = model_point(copy.model, cone.base.xyz);
+    let tip = model_point(copy.model, cone.tip.xyz);
     let base_radius = cone.base.w;
     let color = cone.color.xyz;
-    let residue_idx = u32(cone.tip.w);
 
     let center = (base + tip) * 0.5;
     let axis = tip - base;
@@ -89,12 +92,12 @@ fn vs_main(
     let half_width = base_radius * BILLBOARD_SCALE;
     let half_height = height * 0.5 + base_radius * 0.5;
 
-    let local_uv = quad[vidx];
+    let local_uv = quad[vidx % 6u];
     let world_offset = right * local_uv.x * half_width + up * local_uv.y * half_height;
     let world_pos = center + world_offset;
 
     var out: VertexOutput;
-    out.clip_position = camera.view_proj * vec4<f32>(world_pos, 1.0);
+    out.clip_position = select(CULLED_POSITION, camera.view_proj * vec4<f32>(world_pos, 1.0), copy.drawn);
     out.world_pos = world_pos;
     out.base = base;
     out.tip = tip;
@@ -11,7 +11,8 @@
 //
 // draw(0..72, 0..instance_count)
 
-#import viso::camera::CameraUniform
+#import viso::camera::{CameraUniform, model_point, model_dir}
+#import viso::model_copies::{model_copy, CULLED_POSITION}
 #import viso::lighting::LightingUniform
 #import viso::selection::check_selection
 #import viso::highlight::apply_highlight
@@ -69,14 +70,17 @@ fn get_vertex(inst: PolygonInstance, i: u32) -> vec3<f32> {
 
 @vertex
 fn vs_main(
-    @builtin(vertex_index) vid: u32,
+    @builtin(vertex_index) vertex_idx: u32,
     @builtin(instance_index) iid: u32
 ) -> VertexOutput {
+    // 72 vertices per copy of the instance.
+    let vid = vertex_idx % 72u;
     let inst = polygons[iid];
     let n = u32(inst.v0.w);    // 5 or 6
     let half_t = inst.v1.w;
     let face_n = inst.normal.xyz;
     let residue_idx = u32(inst.normal.w);
+    let copy = model_copy(residue_idx + 1u, vertex_idx / 72u);
     let offset = face_n * half_t;
 
     // Centroid is precomputed CPU-side and packed into the v2/v3/v4 .w
@@ -150,8 +154,11 @@ fn vs_main(
         pos = pos + normal * 0.15;
     }
 
+    pos = model_point(copy.model, pos);
+    normal = model_dir(copy.model, normal);
+
     var out: VertexOutput;
-    out.clip_position = camera.view_proj * vec4<f32>(pos, 1.0);
+    out.clip_position = select(CULLED_POSITION, camera.view_proj * vec4<f32>(pos, 1.0), copy.drawn);
     out.world_pos = pos;
     out.world_normal = normal;
     out.base_color = inst.color.xyz;
@@ -1,7 +1,8 @@
 // Ray-marched sphere impostors for ball-and-stick rendering
 // Each sphere is a billboard quad with per-pixel ray-sphere intersection
 
-#import viso::camera::CameraUniform
+#import viso::camera::{CameraUniform, model_point}
+#import viso::model_copies::{model_copy, CULLED_POSITION}
 #import viso::lighting::LightingUniform
 #import viso::ray::intersect_sphere
 #import viso::selection::check_selection
@@ -48,11 +49,13 @@ fn vs_main(
         vec2(-1.0, 1.0), vec2(1.0, -1.0), vec2(1.0, 1.0)
     );
 
+    // Six vertices per copy of the instance.
     let sph = spheres[iidx];
-    let center = sph.center.xyz;
+    let entity_id = u32(sph.color.w);
+    let copy = model_copy(entity_id + 1u, vidx / 6u);
+    let center = model_point(copy.model, sph.center.xyz);
     let radius = sph.center.w;
     let color = sph.color.xyz;
-    let entity_id = u32(sph.color.w);
 
     let to_camera = normalize(camera.position - center);
 
@@ -66,12 +69,12 @@ fn vs_main(
 
     let half_size = radius * BILLBOARD_SCALE;
 
-    let local_uv = quad[vidx];
+    let local_uv = quad[vidx % 6u];
     let world_offset = right * local_uv.x * half_size + up * local_uv.y * half_size;
     let world_pos = center + world_offset;
 
     var out: VertexOutput;
-    out.clip_position = camera.view_proj * vec4<f32>(world_pos, 1.0);
+    out.clip_position = select(CULLED_POSITION, camera.view_proj * vec4<f32>(world_pos, 1.0), copy.drawn);
     out.world_pos = world_pos;
     out.sphere_center = center;
     out.radius = radius;
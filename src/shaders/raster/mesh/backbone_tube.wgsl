@@ -1,4 +1,5 @@
-#import viso::camera::CameraUniform
+#import viso::camera::{CameraUniform, model_point}
+#import viso::model_copies::{model_copy, CULLED_POSITION}
 #import viso::lighting::LightingUniform
 #import viso::selection::check_selection
 #import viso::highlight::apply_highlight
@@ -32,22 +33,39 @@ struct VertexOutput {
 @group(1) @binding(3) var prefiltered_map: texture_cube<f32>;
 @group(1) @binding(4) var brdf_lut: texture_2d<f32>;
 @group(2) @binding(0) var<storage, read> selection: array<u32>;
+// Per-residue colors of instanced entity copies (alpha 0 = keep the
+// baked vertex color).
+@group(3) @binding(0) var<storage, read> instance_colors: array<vec4<f32>>;
+
+fn instance_color(residue_idx: u32, residue_offset: u32, baked: vec3<f32>) -> vec3<f32> {
+    if (residue_offset == 0u || residue_idx >= arrayLength(&instance_colors)) {
+        return baked;
+    }
+    let c = instance_colors[residue_idx];
+    return select(baked, c.rgb, c.a > 0.0);
+}
 
 @vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
+fn vs_main(
+    in: VertexInput,
+    @builtin(instance_index) copy_idx: u32
+) -> VertexOutput {
     var out: VertexOutput;
+    let copy = model_copy(in.residue_idx + 1u, copy_idx);
+    let residue_idx = in.residue_idx + copy.residue_offset;
 
     // Expand selected residues outward along normal for 1.4x radius (matches Foldit)
     var position = in.position;
-    if (is_selected(in.residue_idx)) {
+    if (is_selected(residue_idx)) {
         position = position + in.normal * 0.24;  // ~1.4x expansion (tube radius ~0.6)
     }
+    position = model_point(copy.model, position);
 
-    out.clip_position = camera.view_proj * vec4<f32>(position, 1.0);
-    out.center_pos = in.center_pos;
+    out.clip_position = select(CULLED_POSITION, camera.view_proj * vec4<f32>(position, 1.0), copy.drawn);
+    out.center_pos = model_point(copy.model, in.center_pos);
     out.world_position = position;
-    out.vertex_color = in.color;
-    out.residue_idx = in.residue_idx;
+    out.vertex_color = instance_color(residue_idx, copy.residue_offset, in.color);
+    out.residue_idx = residue_idx;
     return out;
 }
 
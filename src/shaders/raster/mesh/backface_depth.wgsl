@@ -7,14 +7,18 @@
 //
 //     thickness = back_view_z - front_view_z
 //
-// which feeds Beer-Lambert absorption per-kind. No discard, no
-// conditionals — every back-face writes its view_z. For non-overlapping
+// which feeds Beer-Lambert absorption per-kind. Apart from the cut side
+// of cutaway meshes, every back-face writes its view_z. For non-overlapping
 // isosurfaces (the common case) every pixel where a back-face exists
 // has the correct thickness reference. For overlapping isosurfaces the
 // depth test (Less) keeps the nearest back-face; the visual artifact
 // for overlap regions is a thickness discontinuity which we accept.
 
-#import viso::camera::CameraUniform
+#import viso::camera::{CameraUniform, model_point}
+#import viso::model_copies::{model_copy, CULLED_POSITION}
+
+// Mirrors `isosurface_kind::CUTAWAY`.
+const ISO_CUTAWAY: u32 = 512u;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
@@ -22,6 +26,7 @@ struct VertexInput {
     @location(2) color: vec4<f32>,
     @location(3) kind: u32,
     @location(4) cavity_center: vec3<f32>,
+    @location(5) residue: u32,
 };
 
 struct VertexOutput {
@@ -29,20 +34,33 @@ struct VertexOutput {
     // Linear view-space depth (perpendicular distance to the camera
     // plane along camera.forward). Positive in front of the camera.
     @location(0) view_z: f32,
+    @location(1) world_position: vec3<f32>,
+    @location(2) @interpolate(flat) cutaway: u32,
 };
 
 @group(0) @binding(0) var<uniform> camera: CameraUniform;
 
 @vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
+fn vs_main(
+    in: VertexInput,
+    @builtin(instance_index) copy_idx: u32
+) -> VertexOutput {
     var out: VertexOutput;
-    out.clip_position = camera.view_proj * vec4<f32>(in.position, 1.0);
-    out.view_z = dot(in.position - camera.position, camera.forward);
+    let copy = model_copy(in.residue, copy_idx);
+    let pos = model_point(copy.model, in.position);
+    out.clip_position = select(CULLED_POSITION, camera.view_proj * vec4<f32>(pos, 1.0), copy.drawn);
+    out.view_z = dot(pos - camera.position, camera.forward);
+    out.world_position = pos;
+    out.cutaway = select(0u, 1u, (in.kind & ISO_CUTAWAY) != 0u);
     return out;
 }
 
 // Single R32Float color attachment.
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) f32 {
+    if (in.cutaway != 0u
+        && dot(in.world_position - camera.focus, camera.forward) < 0.0) {
+        discard;
+    }
     return in.view_z;
 }
@@ -1,4 +1,5 @@
-#import viso::camera::CameraUniform
+#import viso::camera::{CameraUniform, model_point, model_dir}
+#import viso::model_copies::{model_copy, CULLED_POSITION}
 #import viso::lighting::{LightingUniform, compute_rim}
 #import viso::shade::{shade_geometry, ShadingResult}
 #import viso::constants::MAX_IBL_MIP
@@ -7,6 +8,13 @@
 // Keep these in sync — they're the per-vertex source-kind discriminator.
 const ISO_KIND_SURFACE: u32 = 0u;
 const ISO_KIND_CAVITY: u32 = 1u;
+const ISO_KIND_WIRE: u32 = 2u;
+const ISO_KIND_DOT: u32 = 3u;
+// Flag bit on top of the kind: color the vertex by its residue.
+const ISO_RESIDUE_COLOR: u32 = 256u;
+// Flag bit: discard the side of the surface facing the camera.
+const ISO_CUTAWAY: u32 = 512u;
+const ISO_KIND_MASK: u32 = 255u;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
@@ -14,6 +22,7 @@ struct VertexInput {
     @location(2) color: vec4<f32>,
     @location(3) kind: u32,
     @location(4) cavity_center: vec3<f32>,
+    @location(5) residue: u32,
 };
 
 struct VertexOutput {
@@ -23,6 +32,7 @@ struct VertexOutput {
     @location(2) vertex_color: vec4<f32>,
     @location(3) @interpolate(flat) kind: u32,
     @location(4) view_z: f32,
+    @location(5) @interpolate(flat) cutaway: u32,
 };
 
 // ── Lava-lamp displacement helpers ────────────────────────────────────
@@ -123,26 +133,45 @@ fn cavity_displacement(
 @group(1) @binding(3) var prefiltered_map: texture_cube<f32>;
 @group(1) @binding(4) var brdf_lut: texture_2d<f32>;
 @group(2) @binding(0) var backface_depth_tex: texture_2d<f32>;
+// Live per-residue colors, indexed by pick ID - 1.
+@group(3) @binding(0) var<storage, read> residue_colors: array<vec4<f32>>;
+
+// Residue-colored vertices take their residue's current color (it
+// eases through scheme transitions), keeping the baked alpha.
+fn vertex_color(in: VertexInput) -> vec4<f32> {
+    if ((in.kind & ISO_RESIDUE_COLOR) == 0u || in.residue == 0u
+        || in.residue > arrayLength(&residue_colors)) {
+        return in.color;
+    }
+    return vec4<f32>(residue_colors[in.residue - 1u].rgb, in.color.a);
+}
 
 @vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
+fn vs_main(
+    in: VertexInput,
+    @builtin(instance_index) copy_idx: u32
+) -> VertexOutput {
     var out: VertexOutput;
+    let copy = model_copy(in.residue, copy_idx);
 
     // Lava-lamp displacement: bounded sinusoidal motion around the rest
     // position, gated on cavity kind. Surface meshes (SES / Gaussian /
     // density) are unaffected.
+    let kind = in.kind & ISO_KIND_MASK;
     var pos = in.position;
-    if (in.kind == ISO_KIND_CAVITY) {
+    if (kind == ISO_KIND_CAVITY) {
         pos = pos + cavity_displacement(
             in.position, in.normal, in.cavity_center, camera.time,
         );
     }
 
-    out.clip_position = camera.view_proj * vec4<f32>(pos, 1.0);
+    pos = model_point(copy.model, pos);
+    out.clip_position = select(CULLED_POSITION, camera.view_proj * vec4<f32>(pos, 1.0), copy.drawn);
     out.world_position = pos;
-    out.world_normal = in.normal;
-    out.vertex_color = in.color;
-    out.kind = in.kind;
+    out.world_normal = model_dir(copy.model, in.normal);
+    out.vertex_color = vertex_color(in);
+    out.kind = kind;
+    out.cutaway = select(0u, 1u, (in.kind & ISO_CUTAWAY) != 0u);
     out.view_z = dot(pos - camera.position, camera.forward);
     return out;
 }
@@ -153,8 +182,18 @@ struct FragOutput {
 };
 
 @fragment
-fn fs_main(in: VertexOutput) -> FragOutput {
-    let normal = normalize(in.world_normal);
+fn fs_main(
+    in: VertexOutput,
+    @builtin(front_facing) front_facing: bool,
+) -> FragOutput {
+    // Cutaway: drop everything between the camera and the focus point's
+    // view plane. Cut meshes are drawn two-sided, so the inner walls
+    // left behind show with their normals flipped toward the viewer.
+    if (in.cutaway != 0u
+        && dot(in.world_position - camera.focus, camera.forward) < 0.0) {
+        discard;
+    }
+    let normal = select(-1.0, 1.0, front_facing) * normalize(in.world_normal);
     let view_dir = normalize(camera.position - in.world_position);
 
     // Pre-sample IBL textures
@@ -197,6 +236,14 @@ fn fs_main(in: VertexOutput) -> FragOutput {
     let opacity = 1.0 - exp(-thickness * absorption);
     final_alpha = in.vertex_color.a * opacity;
 
+    // Chicken-wire lines and dots have no slab to absorb through: the
+    // back-face pre-pass skips them, so draw them at their own alpha.
+    // Neither does an opened shell, whose cut slab is gone.
+    if (in.kind == ISO_KIND_WIRE || in.kind == ISO_KIND_DOT
+        || in.cutaway != 0u) {
+        final_alpha = in.vertex_color.a;
+    }
+
     // Cavity-specific rim, layered over the PBR pass. At the silhouette
     // thickness ≈ 0 so the Beer-Lambert opacity also ≈ 0, which would
     // make the rim invisible after alpha blending. Use the rim
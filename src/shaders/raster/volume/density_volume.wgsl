@@ -0,0 +1,147 @@
+// Ray-marched direct volume rendering of a density map.
+//
+// The map's grid box is drawn as a cube whose back faces start one ray
+// per pixel. The ray is intersected with the box in texel-grid space
+// (grid axes z, y, x — the texture's u, v, w) and marched front to back
+// in fixed world-space steps. Each sample is mapped through the baked
+// transfer function: density across `value_range` indexes a 256-entry
+// RGBA lookup, whose alpha is opacity per Ångström and is corrected for
+// the step length. The gradient gives a headlight shading term so
+// shapes read in depth.
+//
+// Opaque geometry is respected by stopping the march where a sample's
+// projected depth reaches the scene depth buffer. Output is
+// premultiplied and blended over the HDR scene color.
+
+#import viso::camera::CameraUniform
+#import viso::ray::{Ray, ray_box_entry_exit}
+#import viso::volume::{world_to_uvw, sample_volume, volume_gradient}
+
+// Mirrors `renderer::geometry::volume::VolumeUniform` in Rust.
+struct VolumeUniform {
+    world_to_grid: mat4x4<f32>,
+    grid_to_world: mat4x4<f32>,
+    box_min: vec3<f32>,
+    step: f32,
+    box_max: vec3<f32>,
+    gradient_epsilon: f32,
+    value_range: vec2<f32>,
+    // 1 when the texel-grid axes are left-handed in world space, which
+    // mirrors the cube's winding.
+    mirrored: u32,
+    _pad: u32,
+};
+
+const MAX_STEPS: u32 = 768u;
+// Accumulated alpha past which further samples are invisible.
+const OPAQUE_ALPHA: f32 = 0.98;
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+@group(1) @binding(0) var<uniform> volume: VolumeUniform;
+@group(1) @binding(1) var volume_tex: texture_3d<f32>;
+@group(1) @binding(2) var volume_sampler: sampler;
+@group(1) @binding(3) var transfer_lut: texture_2d<f32>;
+@group(2) @binding(0) var scene_depth: texture_depth_2d;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+};
+
+// Unit-cube corners (bit 0 = u, bit 1 = v, bit 2 = w), two outward
+// counter-clockwise triangles per face.
+const CUBE_INDICES = array<u32, 36>(
+    0u, 4u, 6u, 0u, 6u, 2u,
+    1u, 3u, 7u, 1u, 7u, 5u,
+    0u, 1u, 5u, 0u, 5u, 4u,
+    2u, 6u, 7u, 2u, 7u, 3u,
+    0u, 2u, 3u, 0u, 3u, 1u,
+    4u, 5u, 7u, 4u, 7u, 6u,
+);
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    // Swap the last two vertices of each triangle for a mirrored grid
+    // so the faces stay counter-clockwise in world space.
+    var index = vertex_index;
+    let corner_in_tri = index % 3u;
+    if (volume.mirrored == 1u && corner_in_tri != 0u) {
+        index = index - corner_in_tri + 3u - corner_in_tri;
+    }
+    var indices = CUBE_INDICES;
+    let corner = indices[index];
+    let unit = vec3<f32>(
+        f32(corner & 1u),
+        f32((corner >> 1u) & 1u),
+        f32((corner >> 2u) & 1u),
+    );
+    let grid = mix(volume.box_min, volume.box_max, unit);
+    let world = (volume.grid_to_world * vec4<f32>(grid, 1.0)).xyz;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(world, 1.0);
+    out.world_position = world;
+    return out;
+}
+
+// Per-pixel start offset in [0, 1) steps, hiding slicing artifacts.
+fn jitter(pixel: vec2<i32>) -> f32 {
+    return fract(sin(dot(vec2<f32>(pixel), vec2<f32>(12.9898, 78.233))) * 43758.5453);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let dir = normalize(in.world_position - camera.position);
+    // The grid map is affine, so `t` along the transformed ray is still
+    // world-space distance.
+    let ray = Ray(
+        (volume.world_to_grid * vec4<f32>(camera.position, 1.0)).xyz,
+        (volume.world_to_grid * vec4<f32>(dir, 0.0)).xyz,
+    );
+    let span = ray_box_entry_exit(ray, volume.box_min, volume.box_max);
+    if (span.y <= span.x) {
+        discard;
+    }
+
+    let pixel = vec2<i32>(in.clip_position.xy);
+    let opaque_depth = textureLoad(scene_depth, pixel, 0);
+    let grid_extent = volume.box_max - volume.box_min;
+    let value_span = max(volume.value_range.y - volume.value_range.x, 1e-6);
+
+    var color = vec3<f32>(0.0);
+    var alpha = 0.0;
+    var t = span.x + volume.step * jitter(pixel);
+    for (var i = 0u; i < MAX_STEPS && t < span.y; i++) {
+        let world = camera.position + dir * t;
+        let clip = camera.view_proj * vec4<f32>(world, 1.0);
+        if (clip.z / clip.w >= opaque_depth) {
+            break;
+        }
+
+        let uvw = world_to_uvw(ray.origin + ray.direction * t, volume.box_min, volume.box_max);
+        let s = (sample_volume(volume_tex, volume_sampler, uvw) - volume.value_range.x) / value_span;
+        if (s > 0.0) {
+            let tf = textureSampleLevel(transfer_lut, volume_sampler, vec2<f32>(clamp(s, 0.0, 1.0), 0.5), 0.0);
+            let a = 1.0 - pow(1.0 - clamp(tf.a, 0.0, 0.999), volume.step);
+            if (a > 0.0) {
+                // Gradient per grid unit, against the ray in grid space.
+                let g = volume_gradient(volume_tex, volume_sampler, uvw, volume.gradient_epsilon) / grid_extent;
+                var shade = 1.0;
+                if (dot(g, g) > 1e-12) {
+                    shade = 0.55 + 0.45 * abs(dot(normalize(g), normalize(ray.direction)));
+                }
+                color += (1.0 - alpha) * a * tf.rgb * shade;
+                alpha += (1.0 - alpha) * a;
+                if (alpha >= OPAQUE_ALPHA) {
+                    break;
+                }
+            }
+        }
+        t += volume.step;
+    }
+
+    if (alpha <= 0.0) {
+        discard;
+    }
+    return vec4<f32>(color, alpha);
+}
@@ -0,0 +1,48 @@
+// Hi-Z pyramid build: each texel keeps the farthest depth of the block
+// below it. Level 0 reduces the depth buffer, every further level the
+// level before it. The last texel of a row or column also covers the
+// odd texel left over when the source size is odd, so no source texel
+// is ever skipped.
+
+@group(0) @binding(0) var depth_src: texture_depth_2d;
+@group(0) @binding(1) var level_src: texture_2d<f32>;
+@group(0) @binding(2) var dst: texture_storage_2d<r32float, write>;
+
+/// Source texels `[lo, hi]` covered by destination texel `id`.
+fn footprint(id: vec2<u32>, src_dims: vec2<u32>, dst_dims: vec2<u32>) -> array<vec2<u32>, 2> {
+    let lo = min(id * 2u, src_dims - 1u);
+    let last = select(id * 2u + 1u, src_dims - 1u, id == dst_dims - 1u);
+    return array<vec2<u32>, 2>(lo, max(min(last, src_dims - 1u), lo));
+}
+
+@compute @workgroup_size(8, 8)
+fn build_base(@builtin(global_invocation_id) id: vec3<u32>) {
+    let dst_dims = textureDimensions(dst);
+    if (any(id.xy >= dst_dims)) {
+        return;
+    }
+    let span = footprint(id.xy, textureDimensions(depth_src), dst_dims);
+    var farthest = 0.0;
+    for (var y = span[0].y; y <= span[1].y; y++) {
+        for (var x = span[0].x; x <= span[1].x; x++) {
+            farthest = max(farthest, textureLoad(depth_src, vec2<u32>(x, y), 0));
+        }
+    }
+    textureStore(dst, id.xy, vec4<f32>(farthest, 0.0, 0.0, 0.0));
+}
+
+@compute @workgroup_size(8, 8)
+fn build_level(@builtin(global_invocation_id) id: vec3<u32>) {
+    let dst_dims = textureDimensions(dst);
+    if (any(id.xy >= dst_dims)) {
+        return;
+    }
+    let span = footprint(id.xy, textureDimensions(level_src), dst_dims);
+    var farthest = 0.0;
+    for (var y = span[0].y; y <= span[1].y; y++) {
+        for (var x = span[0].x; x <= span[1].x; x++) {
+            farthest = max(farthest, textureLoad(level_src, vec2<u32>(x, y), 0).r);
+        }
+    }
+    textureStore(dst, id.xy, vec4<f32>(farthest, 0.0, 0.0, 0.0));
+}
@@ -0,0 +1,90 @@
+// Occlusion test of indirect draw slots against last frame's Hi-Z
+// pyramid. A slot whose bounding sphere lies behind the farthest depth
+// of every texel it covers has its instance count zeroed, so its
+// indirect draw emits nothing. Anything the test cannot bound
+// conservatively (spheres crossing the near plane or the screen edge,
+// slots without bounds) is left alone.
+
+// Must match the Rust `CullParams` layout.
+struct CullParams {
+    view_proj: mat4x4<f32>,
+    screen_size: vec2<f32>,
+    mip_count: u32,
+    slot_count: u32,
+    stride: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: CullParams;
+// xyz = world-space center, w = radius (negative = never cull)
+@group(0) @binding(1) var<storage, read> bounds: array<vec4<f32>>;
+@group(0) @binding(2) var<storage, read_write> args: array<u32>;
+@group(0) @binding(3) var hiz: texture_2d<f32>;
+
+fn occluded(sphere: vec4<f32>) -> bool {
+    if (sphere.w < 0.0) {
+        return false;
+    }
+
+    // Screen rectangle and nearest depth of the sphere's bounding box.
+    var uv_min = vec2<f32>(1e30);
+    var uv_max = vec2<f32>(-1e30);
+    var nearest = 1.0;
+    for (var i = 0u; i < 8u; i++) {
+        let corner = sphere.xyz + sphere.w * vec3<f32>(
+            select(-1.0, 1.0, (i & 1u) != 0u),
+            select(-1.0, 1.0, (i & 2u) != 0u),
+            select(-1.0, 1.0, (i & 4u) != 0u),
+        );
+        let clip = params.view_proj * vec4<f32>(corner, 1.0);
+        if (clip.w <= 0.0) {
+            return false;
+        }
+        let ndc = clip.xyz / clip.w;
+        let uv = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+        uv_min = min(uv_min, uv);
+        uv_max = max(uv_max, uv);
+        nearest = min(nearest, ndc.z);
+    }
+    if (nearest <= 0.0 || any(uv_min < vec2<f32>(0.0)) || any(uv_max > vec2<f32>(1.0))) {
+        return false;
+    }
+
+    // Depth-buffer pixels covered, then the coarsest level where they
+    // span at most 2x2 texels. Texel t of level L covers pixels
+    // [t, t + 1) << (L + 1); the last texel also covers the remainder.
+    let px_lo = vec2<u32>(clamp(uv_min * params.screen_size, vec2<f32>(0.0), params.screen_size - 1.0));
+    let px_hi = vec2<u32>(clamp(uv_max * params.screen_size, vec2<f32>(0.0), params.screen_size - 1.0));
+    let extent = f32(max(px_hi.x - px_lo.x, px_hi.y - px_lo.y));
+    var level = u32(max(ceil(log2(max(extent, 1.0))) - 1.0, 0.0));
+    level = min(level, params.mip_count - 1u);
+    loop {
+        let lo = px_lo >> vec2<u32>(level + 1u);
+        let hi = px_hi >> vec2<u32>(level + 1u);
+        if (all(hi - lo <= vec2<u32>(1u)) || level + 1u >= params.mip_count) {
+            break;
+        }
+        level += 1u;
+    }
+
+    let last = textureDimensions(hiz, level) - 1u;
+    let lo = min(px_lo >> vec2<u32>(level + 1u), last);
+    let hi = min(px_hi >> vec2<u32>(level + 1u), last);
+    var farthest = 0.0;
+    for (var y = lo.y; y <= hi.y; y++) {
+        for (var x = lo.x; x <= hi.x; x++) {
+            farthest = max(farthest, textureLoad(hiz, vec2<u32>(x, y), i32(level)).r);
+        }
+    }
+    return nearest > farthest;
+}
+
+@compute @workgroup_size(64)
+fn cull(@builtin(global_invocation_id) id: vec3<u32>) {
+    let slot = id.x;
+    if (slot >= params.slot_count) {
+        return;
+    }
+    if (occluded(bounds[slot])) {
+        args[slot * params.stride + 1u] = 0u;
+    }
+}
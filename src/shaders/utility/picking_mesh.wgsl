@@ -1,7 +1,8 @@
 // Picking shader - renders residue indices to a picking buffer
 // Uses the same geometry as backbone_tube.wgsl but outputs residue_idx as color
 
-#import viso::camera::CameraUniform
+#import viso::camera::{CameraUniform, model_point}
+#import viso::model_copies::model_copy
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
@@ -18,10 +19,17 @@ struct VertexOutput {
 @group(0) @binding(0) var<uniform> camera: CameraUniform;
 
 @vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
+fn vs_main(
+    in: VertexInput,
+    @builtin(instance_index) instance_idx: u32
+) -> VertexOutput {
+    // Instanced entity copies are drawn as instances of their
+    // prototype's mesh, each with its own transform and residue offset.
+    let copy = model_copy(0u, instance_idx);
     var out: VertexOutput;
-    out.clip_position = camera.view_proj * vec4<f32>(in.position, 1.0);
-    out.residue_idx = in.residue_idx;
+    out.clip_position = camera.view_proj
+        * vec4<f32>(model_point(copy.model, in.position), 1.0);
+    out.residue_idx = in.residue_idx + copy.residue_offset;
     return out;
 }
 
@@ -0,0 +1,53 @@
+// Picking shader for entity surfaces - renders the residue pick ID each
+// vertex was tagged with. Vertices tagged 0 (no residue) and faint,
+// translucent surfaces are skipped so picks reach what lies inside, as
+// is the cut side of cutaway surfaces.
+
+#import viso::camera::CameraUniform
+
+// Mirrors `isosurface_kind::CUTAWAY`.
+const ISO_CUTAWAY: u32 = 512u;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) kind: u32,
+    @location(4) cavity_center: vec3<f32>,
+    @location(5) residue: u32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) @interpolate(flat) residue: u32,
+    @location(1) alpha: f32,
+    @location(2) world_position: vec3<f32>,
+    @location(3) @interpolate(flat) cutaway: u32,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let pos = in.position;
+    out.clip_position = camera.view_proj * vec4<f32>(pos, 1.0);
+    out.residue = in.residue;
+    out.alpha = in.color.a;
+    out.world_position = pos;
+    out.cutaway = select(0u, 1u, (in.kind & ISO_CUTAWAY) != 0u);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) u32 {
+    if (in.residue == 0u || in.alpha < 0.5) {
+        discard;
+    }
+    if (in.cutaway != 0u
+        && dot(in.world_position - camera.focus, camera.forward) < 0.0) {
+        discard;
+    }
+    // Already a pick ID (residue index + 1)
+    return in.residue;
+}
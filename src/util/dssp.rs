@@ -0,0 +1,568 @@
+//! DSSP secondary-structure assignment (Kabsch & Sander, 1983).
+//!
+//! Backbone hydrogen bonds are detected from the electrostatic
+//! N-H···O=C energy, with the amide H placed along the bisector of the
+//! preceding C=O. From the H-bond pattern:
+//!
+//! 1. **n-turns** at residue i exist when `hbond(i, i+n)` for n = 3, 4, 5.
+//! 2. **Minimal helices** are two consecutive n-turns; they mark α (n=4), 3-10
+//!    (n=3) and π (n=5) helices.
+//! 3. **Bridges** between residues i and j are parallel or antiparallel H-bond
+//!    ladders rungs; consecutive bridges form ladders, and ladders joined
+//!    across a β-bulge form one sheet.
+//! 4. **Bends** mark CA-trace curvature above 70°.
+//!
+//! Classes are resolved with DSSP's priority H > B > E > G > I > T > S.
+
+use glam::{IVec3, Vec3};
+use molex::SSType;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Coulomb factor `q1 * q2 * f` of the Kabsch-Sander energy
+/// (0.42e × 0.20e × 332 kcal·Å/mol).
+const COUPLING: f32 = 0.084 * 332.0;
+/// An N-H···O=C pair is an H-bond below this energy (kcal/mol).
+const HBOND_MAX_ENERGY: f32 = -0.5;
+/// Floor reported for overlapping atoms (kcal/mol).
+const MIN_ENERGY: f32 = -9.9;
+/// Atom pairs closer than this saturate the energy at `MIN_ENERGY`.
+const MIN_DISTANCE: f32 = 0.5;
+/// Residues whose CAs are farther apart cannot H-bond.
+const MAX_CA_DISTANCE: f32 = 9.0;
+/// CA-trace angle (degrees) above which a residue is bent.
+const BEND_ANGLE: f32 = 70.0;
+
+/// Secondary-structure class assigned by DSSP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DsspCode {
+    /// α-helix (`H`).
+    AlphaHelix,
+    /// 3-10 helix (`G`).
+    Helix310,
+    /// π-helix (`I`).
+    PiHelix,
+    /// Extended strand in a ladder (`E`).
+    Strand,
+    /// Isolated β-bridge (`B`).
+    Bridge,
+    /// H-bonded turn (`T`).
+    Turn,
+    /// Bend (`S`).
+    Bend,
+    /// None of the above (`C`).
+    Coil,
+}
+
+impl DsspCode {
+    /// DSSP one-letter code (`C` for coil, where DSSP prints a blank).
+    #[must_use]
+    pub const fn letter(self) -> char {
+        match self {
+            Self::AlphaHelix => 'H',
+            Self::Helix310 => 'G',
+            Self::PiHelix => 'I',
+            Self::Strand => 'E',
+            Self::Bridge => 'B',
+            Self::Turn => 'T',
+            Self::Bend => 'S',
+            Self::Coil => 'C',
+        }
+    }
+
+    /// The three-state class the cartoon renders: helices collapse to
+    /// helix, ladders to sheet, everything else (including isolated
+    /// bridges) to coil.
+    #[must_use]
+    pub const fn ss_type(self) -> SSType {
+        match self {
+            Self::AlphaHelix | Self::Helix310 | Self::PiHelix => SSType::Helix,
+            Self::Strand => SSType::Sheet,
+            Self::Bridge | Self::Turn | Self::Bend | Self::Coil => SSType::Coil,
+        }
+    }
+}
+
+/// Backbone atoms of one residue.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BackboneResidue {
+    pub(crate) n: Vec3,
+    pub(crate) ca: Vec3,
+    pub(crate) c: Vec3,
+    pub(crate) o: Vec3,
+    /// Proline has no amide H and never donates.
+    pub(crate) proline: bool,
+}
+
+/// Assign DSSP classes to every residue of `segments` (continuous
+/// backbone runs; no H-bond pattern spans a segment boundary), in
+/// segment order.
+pub(crate) fn assign(segments: &[Vec<BackboneResidue>]) -> Vec<DsspCode> {
+    let residues: Vec<BackboneResidue> =
+        segments.iter().flatten().copied().collect();
+    let segment: Vec<usize> = segments
+        .iter()
+        .enumerate()
+        .flat_map(|(s, seg)| std::iter::repeat_n(s, seg.len()))
+        .collect();
+    let hbonds = backbone_hbonds(&residues, &segment);
+    let ca: Vec<Vec3> = residues.iter().map(|r| r.ca).collect();
+    classify(&Pattern {
+        hbonds: &hbonds,
+        segment: &segment,
+        ca: &ca,
+    })
+}
+
+/// Kabsch-Sander electrostatic energy of the H-bond from donor N-H to
+/// acceptor C=O, in kcal/mol.
+fn hbond_energy(n: Vec3, h: Vec3, c: Vec3, o: Vec3) -> f32 {
+    let d_on = o.distance(n);
+    let d_ch = c.distance(h);
+    let d_oh = o.distance(h);
+    let d_cn = c.distance(n);
+    if d_on.min(d_ch).min(d_oh).min(d_cn) < MIN_DISTANCE {
+        return MIN_ENERGY;
+    }
+    let energy = COUPLING * (1.0 / d_on + 1.0 / d_ch - 1.0 / d_oh - 1.0 / d_cn);
+    energy.max(MIN_ENERGY)
+}
+
+/// Backbone H-bonds as `(acceptor, donor)` pairs: the C=O of the first
+/// residue accepts from the N-H of the second. Like DSSP, only the two
+/// strongest bonds of each N-H and of each C=O count.
+fn backbone_hbonds(
+    residues: &[BackboneResidue],
+    segment: &[usize],
+) -> FxHashSet<(usize, usize)> {
+    // Amide H: 1 Å from N, opposite the preceding carbonyl O.
+    let hydrogens: Vec<Option<Vec3>> = (0..residues.len())
+        .map(|i| {
+            let r = &residues[i];
+            if i == 0 || r.proline || segment[i - 1] != segment[i] {
+                return None;
+            }
+            let prev = &residues[i - 1];
+            Some(r.n + (prev.c - prev.o).normalize_or_zero())
+        })
+        .collect();
+
+    // Bucket CAs into MAX_CA_DISTANCE cells so each donor only scans
+    // the 27 cells around it.
+    let cell = |p: Vec3| (p / MAX_CA_DISTANCE).floor().as_ivec3();
+    let mut grid: FxHashMap<IVec3, Vec<usize>> = FxHashMap::default();
+    for (i, r) in residues.iter().enumerate() {
+        grid.entry(cell(r.ca)).or_default().push(i);
+    }
+
+    let mut candidates = Vec::new();
+    for (donor, h) in hydrogens.iter().enumerate() {
+        let Some(h) = *h else { continue };
+        let d = &residues[donor];
+        let acceptors = neighbor_cells(cell(d.ca))
+            .filter_map(|c| grid.get(&c))
+            .flatten();
+        for &acceptor in acceptors {
+            // The donor's own carbonyl, and the preceding one its H was
+            // placed from, are excluded.
+            if acceptor == donor || acceptor + 1 == donor {
+                continue;
+            }
+            let a = &residues[acceptor];
+            if d.ca.distance(a.ca) >= MAX_CA_DISTANCE {
+                continue;
+            }
+            let energy = hbond_energy(d.n, h, a.c, a.o);
+            if energy < HBOND_MAX_ENERGY {
+                candidates.push((acceptor, donor, energy));
+            }
+        }
+    }
+    strongest_two(candidates)
+}
+
+/// Keep the `(acceptor, donor, energy)` bonds that rank among the two
+/// lowest-energy ones of both their donor and their acceptor.
+fn strongest_two(
+    mut candidates: Vec<(usize, usize, f32)>,
+) -> FxHashSet<(usize, usize)> {
+    candidates.sort_by(|a, b| a.2.total_cmp(&b.2));
+    let mut donated: FxHashMap<usize, u8> = FxHashMap::default();
+    let mut accepted: FxHashMap<usize, u8> = FxHashMap::default();
+    candidates
+        .into_iter()
+        .filter(|&(acceptor, donor, _)| {
+            let by_donor = donated.entry(donor).or_default();
+            let by_acceptor = accepted.entry(acceptor).or_default();
+            let keep = *by_donor < 2 && *by_acceptor < 2;
+            *by_donor = by_donor.saturating_add(1);
+            *by_acceptor = by_acceptor.saturating_add(1);
+            keep
+        })
+        .map(|(acceptor, donor, _)| (acceptor, donor))
+        .collect()
+}
+
+/// `home` and its 26 neighboring grid cells.
+fn neighbor_cells(home: IVec3) -> impl Iterator<Item = IVec3> {
+    (-1..=1).flat_map(move |dz| {
+        (-1..=1).flat_map(move |dy| {
+            (-1..=1).map(move |dx| home + IVec3::new(dx, dy, dz))
+        })
+    })
+}
+
+/// H-bond pattern plus chain topology the classifier walks.
+struct Pattern<'a> {
+    /// `(acceptor, donor)` backbone H-bonds.
+    hbonds: &'a FxHashSet<(usize, usize)>,
+    /// Segment id per residue.
+    segment: &'a [usize],
+    /// CA positions (for bends).
+    ca: &'a [Vec3],
+}
+
+impl Pattern<'_> {
+    fn len(&self) -> usize {
+        self.segment.len()
+    }
+
+    /// Residues `a..=b` exist and lie in one segment.
+    fn continuous(&self, a: usize, b: usize) -> bool {
+        b < self.len() && self.segment[a] == self.segment[b]
+    }
+
+    /// C=O of `i` accepts an H-bond from N-H of `j`.
+    fn hbond(&self, i: usize, j: usize) -> bool {
+        self.hbonds.contains(&(i, j))
+    }
+
+    /// n-turn starting at `i`.
+    fn turn(&self, n: usize, i: usize) -> bool {
+        self.continuous(i, i + n) && self.hbond(i, i + n)
+    }
+
+    /// Residues covered by minimal n-helices (two consecutive
+    /// n-turns at `i - 1` and `i` mark `i..i + n`).
+    fn helix(&self, n: usize) -> Vec<bool> {
+        let mut marked = vec![false; self.len()];
+        for i in 1..self.len() {
+            if self.turn(n, i - 1) && self.turn(n, i) {
+                for flag in &mut marked[i..i + n] {
+                    *flag = true;
+                }
+            }
+        }
+        marked
+    }
+
+    /// Bridge type between `i` and `j` (`i + 2 < j`), if any.
+    fn bridge(&self, i: usize, j: usize) -> Option<BridgeKind> {
+        if i == 0
+            || !self.continuous(i - 1, i + 1)
+            || !self.continuous(j - 1, j + 1)
+        {
+            return None;
+        }
+        let hb = |a, b| self.hbond(a, b);
+        if (hb(i - 1, j) && hb(j, i + 1)) || (hb(j - 1, i) && hb(i, j + 1)) {
+            Some(BridgeKind::Parallel)
+        } else if (hb(i, j) && hb(j, i))
+            || (hb(i - 1, j + 1) && hb(j - 1, i + 1))
+        {
+            Some(BridgeKind::Antiparallel)
+        } else {
+            None
+        }
+    }
+
+    /// Every β-bridge. Candidates are limited to residue pairs within
+    /// one position of an H-bond's two ends -- every bridge pattern
+    /// involves such a bond.
+    fn bridges(&self) -> Vec<Bridge> {
+        let mut candidates: FxHashSet<(usize, usize)> = FxHashSet::default();
+        for &(a, b) in self.hbonds {
+            let (lo, hi) = (a.min(b), a.max(b));
+            let pairs = (lo.saturating_sub(1)..=lo + 1).flat_map(|i| {
+                (hi.saturating_sub(1)..=hi + 1).map(move |j| (i, j))
+            });
+            candidates.extend(pairs.filter(|&(i, j)| i + 2 < j));
+        }
+        let mut bridges: Vec<Bridge> = candidates
+            .into_iter()
+            .filter_map(|(i, j)| {
+                self.bridge(i, j).map(|kind| Bridge { i, j, kind })
+            })
+            .collect();
+        bridges.sort_by_key(|b| (b.i, b.j));
+        bridges
+    }
+
+    /// Residues whose CA trace bends by more than [`BEND_ANGLE`].
+    fn bent(&self, i: usize) -> bool {
+        if i < 2 || !self.continuous(i - 2, i + 2) {
+            return false;
+        }
+        let before = self.ca[i] - self.ca[i - 2];
+        let after = self.ca[i + 2] - self.ca[i];
+        before.angle_between(after).to_degrees() > BEND_ANGLE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BridgeKind {
+    Parallel,
+    Antiparallel,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bridge {
+    i: usize,
+    j: usize,
+    kind: BridgeKind,
+}
+
+/// Consecutive bridges of one kind.
+struct Ladder {
+    kind: BridgeKind,
+    bridges: Vec<Bridge>,
+    /// Joined to another ladder across a β-bulge.
+    bulged: bool,
+}
+
+impl Ladder {
+    fn first(&self) -> Bridge {
+        self.bridges[0]
+    }
+
+    fn last(&self) -> Bridge {
+        self.bridges[self.bridges.len() - 1]
+    }
+
+    /// Whether `b` extends this ladder by one rung.
+    fn continues_with(&self, b: Bridge) -> bool {
+        let last = self.last();
+        b.kind == self.kind
+            && b.i == last.i + 1
+            && match self.kind {
+                BridgeKind::Parallel => b.j == last.j + 1,
+                BridgeKind::Antiparallel => b.j + 1 == last.j,
+            }
+    }
+}
+
+/// Group bridges into ladders, then link ladders separated by a
+/// β-bulge (a gap of at most 1 residue on one strand and 4 on the
+/// other). Returns the ladders plus the bulge gaps to fill.
+fn ladders(bridges: &[Bridge]) -> (Vec<Ladder>, Vec<(usize, usize)>) {
+    let mut ladders: Vec<Ladder> = Vec::new();
+    for &b in bridges {
+        match ladders.iter_mut().find(|l| l.continues_with(b)) {
+            Some(ladder) => ladder.bridges.push(b),
+            None => ladders.push(Ladder {
+                kind: b.kind,
+                bridges: vec![b],
+                bulged: false,
+            }),
+        }
+    }
+
+    let mut gaps = Vec::new();
+    for x in 0..ladders.len() {
+        for y in 0..ladders.len() {
+            let (a, b) = (&ladders[x], &ladders[y]);
+            let (a_end, b_start) = (a.last().i, b.first().i);
+            if x == y || a.kind != b.kind || b_start <= a_end {
+                continue;
+            }
+            let gap_i = b_start - a_end - 1;
+            let (j_lo, j_hi) = match a.kind {
+                BridgeKind::Parallel => (a.last().j, b.first().j),
+                BridgeKind::Antiparallel => (b.first().j, a.last().j),
+            };
+            if j_hi <= j_lo {
+                continue;
+            }
+            let gap_j = j_hi - j_lo - 1;
+            if (gap_i <= 1 && gap_j <= 4) || (gap_i <= 4 && gap_j <= 1) {
+                gaps.push((a.last().i, b.first().i));
+                gaps.push((j_lo, j_hi));
+                ladders[x].bulged = true;
+                ladders[y].bulged = true;
+            }
+        }
+    }
+    (ladders, gaps)
+}
+
+fn classify(pattern: &Pattern<'_>) -> Vec<DsspCode> {
+    let len = pattern.len();
+    let mut codes = vec![DsspCode::Coil; len];
+
+    for (i, code) in codes.iter_mut().enumerate() {
+        if pattern.bent(i) {
+            *code = DsspCode::Bend;
+        }
+    }
+    for n in 3..=5 {
+        for i in 0..len {
+            if pattern.turn(n, i) {
+                for code in &mut codes[i + 1..i + n] {
+                    *code = DsspCode::Turn;
+                }
+            }
+        }
+    }
+
+    // β-ladders and bridges.
+    let mut strand = vec![None; len];
+    let (ladders, gaps) = ladders(&pattern.bridges());
+    for ladder in &ladders {
+        let code = if ladder.bridges.len() > 1 || ladder.bulged {
+            DsspCode::Strand
+        } else {
+            DsspCode::Bridge
+        };
+        for b in &ladder.bridges {
+            for r in [b.i, b.j] {
+                if strand[r] != Some(DsspCode::Strand) {
+                    strand[r] = Some(code);
+                }
+            }
+        }
+    }
+    for (lo, hi) in gaps {
+        for slot in &mut strand[lo..=hi] {
+            *slot = Some(DsspCode::Strand);
+        }
+    }
+
+    // 3-10 and π helices only where nothing of higher priority sits.
+    let alpha = pattern.helix(4);
+    let free = |r: usize| !alpha[r] && strand[r].is_none();
+    for (n, code) in [(5, DsspCode::PiHelix), (3, DsspCode::Helix310)] {
+        for i in 1..len {
+            if pattern.turn(n, i - 1)
+                && pattern.turn(n, i)
+                && (i..i + n).all(free)
+            {
+                for slot in &mut codes[i..i + n] {
+                    *slot = code;
+                }
+            }
+        }
+    }
+
+    for (r, code) in codes.iter_mut().enumerate() {
+        if let Some(s) = strand[r] {
+            *code = s;
+        }
+        if alpha[r] {
+            *code = DsspCode::AlphaHelix;
+        }
+    }
+    codes
+}
+
+/// Collapse DSSP classes to the three-state cartoon classes. Isolated
+/// one-residue helix or strand runs render as coil.
+pub(crate) fn to_ss_types(codes: &[DsspCode]) -> Vec<SSType> {
+    let ss: Vec<SSType> = codes.iter().map(|c| c.ss_type()).collect();
+    molex::analysis::merge_short_segments(&ss)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify_bonds(len: usize, bonds: &[(usize, usize)]) -> String {
+        let hbonds: FxHashSet<(usize, usize)> = bonds.iter().copied().collect();
+        let segment = vec![0; len];
+        let ca: Vec<Vec3> =
+            (0..len).map(|i| Vec3::X * (i as f32 * 3.8)).collect();
+        classify(&Pattern {
+            hbonds: &hbonds,
+            segment: &segment,
+            ca: &ca,
+        })
+        .iter()
+        .map(|c| c.letter())
+        .collect()
+    }
+
+    #[test]
+    fn linear_hbond_is_strongly_favorable() {
+        let n = Vec3::ZERO;
+        let h = Vec3::X;
+        let o = Vec3::X * 2.9;
+        let c = Vec3::X * 4.13;
+        let e = hbond_energy(n, h, c, o);
+        assert!(e < -2.5 && e > -3.5, "energy {e}");
+        // Reversed dipole repels.
+        assert!(hbond_energy(n, h, o, c) > 0.0);
+    }
+
+    #[test]
+    fn only_the_two_strongest_bonds_per_group_count() {
+        // Donor 10 bonds to three acceptors; the weakest is dropped.
+        // Acceptor 0 then also accepts from 11 and 12: 12 is its third.
+        let kept = strongest_two(vec![
+            (3, 10, -1.0),
+            (0, 10, -3.0),
+            (5, 10, -2.0),
+            (0, 11, -2.5),
+            (0, 12, -0.8),
+        ]);
+        let mut kept: Vec<_> = kept.into_iter().collect();
+        kept.sort_unstable();
+        assert_eq!(kept, vec![(0, 10), (0, 11), (5, 10)]);
+    }
+
+    #[test]
+    fn i_to_i_plus_4_ladder_is_alpha_helix() {
+        let bonds: Vec<_> = (0..10).map(|i| (i, i + 4)).collect();
+        let codes = classify_bonds(16, &bonds);
+        assert_eq!(&codes[1..=12], "HHHHHHHHHHHH");
+        assert_ne!(&codes[0..1], "H");
+    }
+
+    #[test]
+    fn i_to_i_plus_3_pair_is_310_helix() {
+        let codes = classify_bonds(10, &[(2, 5), (3, 6)]);
+        assert_eq!(&codes[3..6], "GGG");
+    }
+
+    #[test]
+    fn hairpin_forms_antiparallel_strands() {
+        let mut bonds = Vec::new();
+        for (i, j) in [(1, 14), (3, 12), (5, 10)] {
+            bonds.push((i, j));
+            bonds.push((j, i));
+        }
+        let codes = classify_bonds(16, &bonds);
+        assert_eq!(&codes[1..=5], "EEEEE");
+        assert_eq!(&codes[10..=14], "EEEEE");
+    }
+
+    #[test]
+    fn lone_bridge_is_b() {
+        let codes = classify_bonds(12, &[(2, 9), (9, 2)]);
+        assert_eq!(codes.chars().nth(2), Some('B'));
+        assert_eq!(codes.chars().nth(9), Some('B'));
+        assert_eq!(to_ss_types(&[DsspCode::Bridge])[0], SSType::Coil);
+    }
+
+    #[test]
+    fn segment_breaks_block_turns() {
+        let hbonds: FxHashSet<(usize, usize)> =
+            [(0, 4), (1, 5)].into_iter().collect();
+        let segment = [0, 0, 0, 1, 1, 1, 1];
+        let ca = [Vec3::ZERO; 7];
+        let p = Pattern {
+            hbonds: &hbonds,
+            segment: &segment,
+            ca: &ca,
+        };
+        assert!(!p.turn(4, 0));
+        assert!(!p.turn(4, 1));
+    }
+}
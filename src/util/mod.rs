@@ -1,8 +1,14 @@
 //! Shared utilities for the rendering engine.
 
+/// DSSP secondary-structure assignment from backbone H-bond energies.
+pub(crate) mod dssp;
 /// Easing functions for smooth interpolation curves.
 pub(crate) mod easing;
 /// Pure geometric-math primitives (Newell normal, etc.).
 pub(crate) mod geom;
 /// Fast hashing helpers for change detection on Vec3 data.
 pub(crate) mod hash;
+/// Crystallographic space-group operators (Sohncke groups).
+pub(crate) mod space_group;
+/// Least-squares rigid superposition (quaternion Kabsch).
+pub(crate) mod superpose;
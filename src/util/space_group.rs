@@ -0,0 +1,420 @@
+//! Crystallographic space-group operators.
+//!
+//! Operators are kept in exact fractional form (integer rotation,
+//! translation in twelfths of a lattice vector), so closing a group
+//! from its generators never accumulates rounding. The built-in table
+//! covers the 65 Sohncke groups — the only ones a chiral
+//! macromolecular crystal can adopt — in their ITA standard settings,
+//! which is what PDB and mmCIF files use.
+
+use glam::{DMat3, DVec3};
+
+/// Translations are stored in twelfths: every crystallographic
+/// translation component (1/2, 1/3, 1/4, 1/6, ...) is a multiple.
+const DENOM: i32 = 12;
+
+/// Upper bound on group order (F432 with centering is 96); stops the
+/// closure on malformed generators.
+const MAX_ORDER: usize = 192;
+
+/// One symmetry operator `x' = R x + t` in fractional coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct SymOp {
+    /// Integer rotation part, row-major.
+    rot: [[i32; 3]; 3],
+    /// Translation in twelfths, reduced to `0..12`.
+    trans: [i32; 3],
+}
+
+impl SymOp {
+    /// The identity operator `x,y,z`.
+    pub(crate) const IDENTITY: Self = Self {
+        rot: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+        trans: [0, 0, 0],
+    };
+
+    /// Pure lattice translation by `t` twelfths.
+    const fn translation(t: [i32; 3]) -> Self {
+        Self {
+            rot: Self::IDENTITY.rot,
+            trans: t,
+        }
+    }
+
+    /// Parse an operator in the `x,y+1/2,-z` notation used by
+    /// `_space_group_symop.operation_xyz` and International Tables.
+    /// Case-insensitive; accepts `1/2+x` as well as `x+1/2`.
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().trim_matches(|c| c == '\'' || c == '"');
+        let mut parts = s.split(',');
+        let mut op = Self {
+            rot: [[0; 3]; 3],
+            trans: [0; 3],
+        };
+        for row in 0..3 {
+            let (r, t) = parse_component(parts.next()?)?;
+            op.rot[row] = r;
+            op.trans[row] = t;
+        }
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(op.reduced())
+    }
+
+    /// Round a real-valued fractional operator (e.g. a Cartesian
+    /// SMTRY record converted through the cell) to exact form.
+    /// Returns `None` if the rotation isn't integral.
+    pub(crate) fn from_fractional(rot: DMat3, trans: DVec3) -> Option<Self> {
+        let mut op = Self {
+            rot: [[0; 3]; 3],
+            trans: [0; 3],
+        };
+        for row in 0..3 {
+            for col in 0..3 {
+                let v = rot.col(col)[row];
+                let r = v.round();
+                if (v - r).abs() > 0.05 {
+                    return None;
+                }
+                op.rot[row][col] = r as i32;
+            }
+            op.trans[row] = (trans[row] * f64::from(DENOM)).round() as i32;
+        }
+        Some(op.reduced())
+    }
+
+    /// `self ∘ other`: apply `other`, then `self`.
+    #[must_use]
+    pub(crate) fn compose(&self, other: &Self) -> Self {
+        let mut out = Self {
+            rot: [[0; 3]; 3],
+            trans: self.trans,
+        };
+        for i in 0..3 {
+            for j in 0..3 {
+                out.rot[i][j] =
+                    (0..3).map(|k| self.rot[i][k] * other.rot[k][j]).sum();
+            }
+            out.trans[i] +=
+                (0..3).map(|k| self.rot[i][k] * other.trans[k]).sum::<i32>();
+        }
+        out.reduced()
+    }
+
+    /// Whether this is `x,y,z` (modulo whole lattice translations).
+    pub(crate) fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// Rotation part as a real matrix.
+    pub(crate) fn rotation(&self) -> DMat3 {
+        DMat3::from_cols_array_2d(&self.rot.map(|r| r.map(f64::from)))
+            .transpose()
+    }
+
+    /// Translation part as fractions of the lattice vectors, in `[0, 1)`.
+    pub(crate) fn translation_frac(&self) -> DVec3 {
+        DVec3::from_array(self.trans.map(f64::from)) / f64::from(DENOM)
+    }
+
+    /// Apply to a fractional coordinate.
+    pub(crate) fn apply(&self, frac: DVec3) -> DVec3 {
+        self.rotation() * frac + self.translation_frac()
+    }
+
+    fn reduced(mut self) -> Self {
+        for t in &mut self.trans {
+            *t = t.rem_euclid(DENOM);
+        }
+        self
+    }
+}
+
+/// Parse one `±x±1/2` style component into a rotation row and a
+/// translation in twelfths.
+fn parse_component(s: &str) -> Option<([i32; 3], i32)> {
+    let s: String = s
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if s.is_empty() {
+        return None;
+    }
+    let mut row = [0; 3];
+    let mut trans = 0;
+    let mut sign = 1;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '+' => sign = 1,
+            '-' => sign = -1,
+            'x' | 'y' | 'z' => {
+                row[(c as u8 - b'x') as usize] += sign;
+                sign = 1;
+            }
+            '0'..='9' | '.' => {
+                let mut num = String::from(c);
+                while let Some(&d) = chars
+                    .peek()
+                    .filter(|d| d.is_ascii_digit() || **d == '.' || **d == '/')
+                {
+                    num.push(d);
+                    let _ = chars.next();
+                }
+                trans += sign * parse_fraction(&num)?;
+                sign = 1;
+            }
+            _ => return None,
+        }
+    }
+    Some((row, trans))
+}
+
+/// `"1/2"` or `"0.5"` in twelfths.
+fn parse_fraction(s: &str) -> Option<i32> {
+    let value = match s.split_once('/') {
+        Some((n, d)) => n.parse::<f64>().ok()? / d.parse::<f64>().ok()?,
+        None => s.parse::<f64>().ok()?,
+    };
+    Some((value * f64::from(DENOM)).round() as i32)
+}
+
+/// Every operator generated by `generators` (the identity included),
+/// identity first.
+pub(crate) fn close(generators: &[SymOp]) -> Vec<SymOp> {
+    let mut ops = vec![SymOp::IDENTITY];
+    let mut frontier = vec![SymOp::IDENTITY];
+    while let Some(op) = frontier.pop() {
+        for g in generators {
+            let next = g.compose(&op);
+            if !ops.contains(&next) && ops.len() < MAX_ORDER {
+                ops.push(next);
+                frontier.push(next);
+            }
+        }
+    }
+    ops
+}
+
+/// Lattice centering translations (in twelfths) for a centering letter.
+fn centering(letter: char, rhombohedral_axes: bool) -> Vec<SymOp> {
+    let shifts: &[[i32; 3]] = match letter {
+        'C' => &[[6, 6, 0]],
+        'A' => &[[0, 6, 6]],
+        'B' => &[[6, 0, 6]],
+        'I' => &[[6, 6, 6]],
+        'F' => &[[0, 6, 6], [6, 0, 6], [6, 6, 0]],
+        'R' if !rhombohedral_axes => &[[8, 4, 4], [4, 8, 8]],
+        _ => &[],
+    };
+    shifts.iter().copied().map(SymOp::translation).collect()
+}
+
+/// Generators of the 65 Sohncke groups, keyed by the normalized
+/// Hermann–Mauguin symbol. The first letter is the centering.
+const SOHNCKE: &[(&str, &[&str])] = &[
+    ("P1", &[]),
+    ("P2", &["-x,y,-z"]),
+    ("P21", &["-x,y+1/2,-z"]),
+    ("C2", &["-x,y,-z"]),
+    ("P222", &["-x,-y,z", "-x,y,-z"]),
+    ("P2221", &["-x,-y,z+1/2", "-x,y,-z+1/2"]),
+    ("P21212", &["-x,-y,z", "-x+1/2,y+1/2,-z"]),
+    ("P212121", &["-x+1/2,-y,z+1/2", "-x,y+1/2,-z+1/2"]),
+    ("C2221", &["-x,-y,z+1/2", "-x,y,-z+1/2"]),
+    ("C222", &["-x,-y,z", "-x,y,-z"]),
+    ("F222", &["-x,-y,z", "-x,y,-z"]),
+    ("I222", &["-x,-y,z", "-x,y,-z"]),
+    ("I212121", &["-x+1/2,-y,z+1/2", "-x,y+1/2,-z+1/2"]),
+    ("P4", &["-y,x,z"]),
+    ("P41", &["-y,x,z+1/4"]),
+    ("P42", &["-y,x,z+1/2"]),
+    ("P43", &["-y,x,z+3/4"]),
+    ("I4", &["-y,x,z"]),
+    ("I41", &["-y,x+1/2,z+1/4"]),
+    ("P422", &["-y,x,z", "-x,y,-z"]),
+    ("P4212", &["-y+1/2,x+1/2,z", "-x+1/2,y+1/2,-z"]),
+    ("P4122", &["-y,x,z+1/4", "-x,y,-z"]),
+    ("P41212", &["-y+1/2,x+1/2,z+1/4", "-x+1/2,y+1/2,-z+1/4"]),
+    ("P4222", &["-y,x,z+1/2", "-x,y,-z"]),
+    ("P42212", &["-y+1/2,x+1/2,z+1/2", "-x+1/2,y+1/2,-z+1/2"]),
+    ("P4322", &["-y,x,z+3/4", "-x,y,-z"]),
+    ("P43212", &["-y+1/2,x+1/2,z+3/4", "-x+1/2,y+1/2,-z+3/4"]),
+    ("I422", &["-y,x,z", "-x,y,-z"]),
+    ("I4122", &["-y,x+1/2,z+1/4", "-x+1/2,y,-z+3/4"]),
+    ("P3", &["-y,x-y,z"]),
+    ("P31", &["-y,x-y,z+1/3"]),
+    ("P32", &["-y,x-y,z+2/3"]),
+    ("R3", &["-y,x-y,z"]),
+    ("P312", &["-y,x-y,z", "-y,-x,-z"]),
+    ("P321", &["-y,x-y,z", "y,x,-z"]),
+    ("P3112", &["-y,x-y,z+1/3", "-y,-x,-z+2/3"]),
+    ("P3121", &["-y,x-y,z+1/3", "y,x,-z"]),
+    ("P3212", &["-y,x-y,z+2/3", "-y,-x,-z+1/3"]),
+    ("P3221", &["-y,x-y,z+2/3", "y,x,-z"]),
+    ("R32", &["-y,x-y,z", "y,x,-z"]),
+    ("P6", &["x-y,x,z"]),
+    ("P61", &["x-y,x,z+1/6"]),
+    ("P65", &["x-y,x,z+5/6"]),
+    ("P62", &["x-y,x,z+1/3"]),
+    ("P64", &["x-y,x,z+2/3"]),
+    ("P63", &["x-y,x,z+1/2"]),
+    ("P622", &["x-y,x,z", "y,x,-z"]),
+    ("P6122", &["x-y,x,z+1/6", "y,x,-z+1/3"]),
+    ("P6522", &["x-y,x,z+5/6", "y,x,-z+2/3"]),
+    ("P6222", &["x-y,x,z+1/3", "y,x,-z+2/3"]),
+    ("P6422", &["x-y,x,z+2/3", "y,x,-z+1/3"]),
+    ("P6322", &["x-y,x,z+1/2", "y,x,-z"]),
+    ("P23", &["-x,-y,z", "-x,y,-z", "z,x,y"]),
+    ("F23", &["-x,-y,z", "-x,y,-z", "z,x,y"]),
+    ("I23", &["-x,-y,z", "-x,y,-z", "z,x,y"]),
+    ("P213", &["-x+1/2,-y,z+1/2", "-x,y+1/2,-z+1/2", "z,x,y"]),
+    ("I213", &["-x+1/2,-y,z+1/2", "-x,y+1/2,-z+1/2", "z,x,y"]),
+    ("P432", &["-x,-y,z", "-x,y,-z", "z,x,y", "y,x,-z"]),
+    (
+        "P4232",
+        &["-x,-y,z", "-x,y,-z", "z,x,y", "y+1/2,x+1/2,-z+1/2"],
+    ),
+    ("F432", &["-x,-y,z", "-x,y,-z", "z,x,y", "y,x,-z"]),
+    (
+        "F4132",
+        &[
+            "-x,-y+1/2,z+1/2",
+            "-x+1/2,y+1/2,-z",
+            "z,x,y",
+            "y+3/4,x+1/4,-z+3/4",
+        ],
+    ),
+    ("I432", &["-x,-y,z", "-x,y,-z", "z,x,y", "y,x,-z"]),
+    (
+        "P4332",
+        &[
+            "-x+1/2,-y,z+1/2",
+            "-x,y+1/2,-z+1/2",
+            "z,x,y",
+            "y+1/4,x+3/4,-z+3/4",
+        ],
+    ),
+    (
+        "P4132",
+        &[
+            "-x+1/2,-y,z+1/2",
+            "-x,y+1/2,-z+1/2",
+            "z,x,y",
+            "y+3/4,x+1/4,-z+1/4",
+        ],
+    ),
+    (
+        "I4132",
+        &[
+            "-x+1/2,-y,z+1/2",
+            "-x,y+1/2,-z+1/2",
+            "z,x,y",
+            "y+3/4,x+1/4,-z+1/4",
+        ],
+    ),
+];
+
+/// Normalize a Hermann–Mauguin symbol as written in PDB/mmCIF files
+/// (`"P 21 21 21"`, `"P 1 21 1"`, `"H 3"`) to the table's short form.
+pub(crate) fn normalize_symbol(symbol: &str) -> String {
+    let compact: String = symbol
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\'' && *c != '"')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    // Full monoclinic symbols put the unique b axis between two 1s.
+    let short = match compact.as_str() {
+        "P121" => "P2",
+        "P1211" => "P21",
+        "C121" => "C2",
+        "H3" => "R3",
+        "H32" => "R32",
+        other => other,
+    };
+    short.to_owned()
+}
+
+/// Every operator of space group `symbol` (identity first), including
+/// centering translations. `rhombohedral_axes` selects the primitive
+/// rhombohedral setting for `R3`/`R32` (cell with α = β = γ ≠ 90°)
+/// instead of the hexagonal one. `None` for groups outside the table.
+pub(crate) fn operators(
+    symbol: &str,
+    rhombohedral_axes: bool,
+) -> Option<Vec<SymOp>> {
+    let symbol = normalize_symbol(symbol);
+    let gens: Vec<&str> = match (symbol.as_str(), rhombohedral_axes) {
+        ("R3", true) => vec!["z,x,y"],
+        ("R32", true) => vec!["z,x,y", "-y,-x,-z"],
+        _ => SOHNCKE.iter().find(|(name, _)| *name == symbol)?.1.to_vec(),
+    };
+    let centering_letter = symbol.chars().next()?;
+    let mut generators: Vec<SymOp> = gens
+        .iter()
+        .map(|g| SymOp::parse(g))
+        .collect::<Option<_>>()?;
+    generators.extend(centering(centering_letter, rhombohedral_axes));
+    Some(close(&generators))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn order(symbol: &str) -> usize {
+        operators(symbol, false).unwrap().len()
+    }
+
+    #[test]
+    fn parses_operator_notation() {
+        let op = SymOp::parse("-x+1/2, y, 1/2-Z").unwrap();
+        let p = op.apply(DVec3::new(0.1, 0.2, 0.3));
+        assert!((p - DVec3::new(0.4, 0.2, 0.2)).length() < 1e-12);
+        assert!(SymOp::parse("x,y").is_none());
+        assert!(SymOp::parse("X,Y,Z").unwrap().is_identity());
+    }
+
+    #[test]
+    fn group_orders_match_tables() {
+        assert_eq!(order("P 1"), 1);
+        assert_eq!(order("P 1 21 1"), 2);
+        assert_eq!(order("C 1 2 1"), 4);
+        assert_eq!(order("P 21 21 21"), 4);
+        assert_eq!(order("P 43 21 2"), 8);
+        assert_eq!(order("P 61 2 2"), 12);
+        assert_eq!(order("H 3 2"), 18);
+        assert_eq!(order("I 41 3 2"), 48);
+        assert_eq!(order("F 4 3 2"), 96);
+        assert_eq!(operators("R 3 2", true).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn every_sohncke_group_closes() {
+        assert_eq!(SOHNCKE.len(), 65);
+        for (name, _) in SOHNCKE {
+            let ops = operators(name, false).unwrap();
+            assert!(ops[0].is_identity(), "{name}");
+            assert!(ops.len() < MAX_ORDER, "{name}");
+        }
+    }
+
+    #[test]
+    fn unknown_and_centrosymmetric_groups_are_absent() {
+        assert!(operators("P 21/c", false).is_none());
+        assert!(operators("", false).is_none());
+    }
+
+    #[test]
+    fn rounds_real_operators() {
+        let op = SymOp::from_fractional(
+            DMat3::from_cols(DVec3::NEG_X, DVec3::Y, DVec3::NEG_Z),
+            DVec3::new(0.0, 0.500_000_1, 0.0),
+        )
+        .unwrap();
+        assert_eq!(op, SymOp::parse("-x,y+1/2,-z").unwrap());
+    }
+}
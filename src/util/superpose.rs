@@ -0,0 +1,182 @@
+//! Optimal rigid-body superposition of paired point sets.
+//!
+//! Uses Horn's closed-form quaternion solution: the rotation minimizing
+//! the RMSD between two centered point sets is the eigenvector of the
+//! largest eigenvalue of a 4x4 symmetric matrix built from their
+//! cross-covariance. Equivalent to the Kabsch SVD solution, but never
+//! yields a reflection and needs only a small symmetric eigensolver.
+
+use glam::{DQuat, DVec3, Quat, Vec3};
+
+/// Maximum Jacobi sweeps before giving up on convergence.
+const MAX_SWEEPS: usize = 50;
+
+/// Rigid transform mapping a mobile point set onto a reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct RigidFit {
+    /// Rotation about the mobile centroid.
+    pub(crate) rotation: Quat,
+    /// Centroid of the mobile points.
+    pub(crate) mobile_center: Vec3,
+    /// Centroid of the reference points.
+    pub(crate) reference_center: Vec3,
+    /// Root-mean-square deviation after applying the fit, in the
+    /// input units.
+    pub(crate) rmsd: f32,
+}
+
+impl RigidFit {
+    /// Map a mobile-frame point into the reference frame.
+    pub(crate) fn apply(&self, p: Vec3) -> Vec3 {
+        self.rotation * (p - self.mobile_center) + self.reference_center
+    }
+}
+
+/// Least-squares rigid fit of `mobile` onto `reference` (paired by
+/// index). Returns `None` if the sets differ in length or are empty.
+pub(crate) fn fit(mobile: &[Vec3], reference: &[Vec3]) -> Option<RigidFit> {
+    if mobile.len() != reference.len() || mobile.is_empty() {
+        return None;
+    }
+    let n = mobile.len() as f64;
+    let mobile_center = mobile.iter().map(Vec3::as_dvec3).sum::<DVec3>() / n;
+    let reference_center =
+        reference.iter().map(Vec3::as_dvec3).sum::<DVec3>() / n;
+
+    // Cross-covariance S[i][j] = Σ a_i b_j and the summed squared norms.
+    let mut s = [[0.0f64; 3]; 3];
+    let mut norms = 0.0;
+    for (m, r) in mobile.iter().zip(reference) {
+        let a = m.as_dvec3() - mobile_center;
+        let b = r.as_dvec3() - reference_center;
+        norms += a.length_squared() + b.length_squared();
+        let (a, b) = (a.to_array(), b.to_array());
+        for i in 0..3 {
+            for j in 0..3 {
+                s[i][j] += a[i] * b[j];
+            }
+        }
+    }
+    let [[sxx, sxy, sxz], [syx, syy, syz], [szx, szy, szz]] = s;
+    let mut k = [
+        [sxx + syy + szz, syz - szy, szx - sxz, sxy - syx],
+        [syz - szy, sxx - syy - szz, sxy + syx, szx + sxz],
+        [szx - sxz, sxy + syx, -sxx + syy - szz, syz + szy],
+        [sxy - syx, szx + sxz, syz + szy, -sxx - syy + szz],
+    ];
+    let (eigenvalue, q) = largest_eigenpair(&mut k);
+
+    let rotation = DQuat::from_xyzw(q[1], q[2], q[3], q[0]).normalize();
+    let msd = ((norms - 2.0 * eigenvalue) / n).max(0.0);
+    Some(RigidFit {
+        rotation: rotation.as_quat(),
+        mobile_center: mobile_center.as_vec3(),
+        reference_center: reference_center.as_vec3(),
+        rmsd: msd.sqrt() as f32,
+    })
+}
+
+/// Largest eigenvalue and its eigenvector of a symmetric 4x4 matrix
+/// (cyclic Jacobi rotations; `m` is destroyed).
+fn largest_eigenpair(m: &mut [[f64; 4]; 4]) -> (f64, [f64; 4]) {
+    let mut v = [[0.0f64; 4]; 4];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+    for _ in 0..MAX_SWEEPS {
+        let off: f64 = (0..4)
+            .flat_map(|p| ((p + 1)..4).map(move |q| (p, q)))
+            .map(|(p, q)| m[p][q] * m[p][q])
+            .sum();
+        if off < 1e-22 {
+            break;
+        }
+        for p in 0..4 {
+            for q in (p + 1)..4 {
+                jacobi_rotate(m, &mut v, p, q);
+            }
+        }
+    }
+    let best = (0..4)
+        .max_by(|&a, &b| m[a][a].total_cmp(&m[b][b]))
+        .unwrap_or(0);
+    (
+        m[best][best],
+        [v[0][best], v[1][best], v[2][best], v[3][best]],
+    )
+}
+
+/// Zero `m[p][q]` with one Jacobi rotation, accumulating it into the
+/// eigenvector matrix `v` (eigenvectors are its columns).
+fn jacobi_rotate(
+    m: &mut [[f64; 4]; 4],
+    v: &mut [[f64; 4]; 4],
+    p: usize,
+    q: usize,
+) {
+    if m[p][q].abs() < 1e-300 {
+        return;
+    }
+    let theta = (m[q][q] - m[p][p]) / (2.0 * m[p][q]);
+    let t = theta.signum() / (theta.abs() + theta.mul_add(theta, 1.0).sqrt());
+    let c = 1.0 / t.mul_add(t, 1.0).sqrt();
+    let s = t * c;
+    for row in m.iter_mut() {
+        let (mkp, mkq) = (row[p], row[q]);
+        row[p] = c * mkp - s * mkq;
+        row[q] = s * mkp + c * mkq;
+    }
+    let (row_p, row_q) = (m[p], m[q]);
+    m[p] = std::array::from_fn(|k| c * row_p[k] - s * row_q[k]);
+    m[q] = std::array::from_fn(|k| s * row_p[k] + c * row_q[k]);
+    for row in v.iter_mut() {
+        let (vp, vq) = (row[p], row[q]);
+        row[p] = c * vp - s * vq;
+        row[q] = s * vp + c * vq;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn points() -> Vec<Vec3> {
+        vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.5, 0.0, 0.0),
+            Vec3::new(1.5, 2.0, 0.0),
+            Vec3::new(0.0, 2.0, 3.0),
+            Vec3::new(-1.0, 0.5, 1.0),
+        ]
+    }
+
+    #[test]
+    fn recovers_rigid_transform() {
+        let rot = Quat::from_euler(glam::EulerRot::XYZ, 0.7, -1.2, 2.5);
+        let shift = Vec3::new(10.0, -4.0, 3.0);
+        let reference = points();
+        let mobile: Vec<Vec3> =
+            reference.iter().map(|&p| rot * p + shift).collect();
+        let f = fit(&mobile, &reference).unwrap();
+        assert!(f.rmsd < 1e-3, "rmsd {}", f.rmsd);
+        for (m, r) in mobile.iter().zip(&reference) {
+            assert!((f.apply(*m) - *r).length() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn reports_residual_rmsd() {
+        let reference = points();
+        let mut mobile = reference.clone();
+        mobile[0].x += 1.0;
+        let f = fit(&mobile, &reference).unwrap();
+        assert!(f.rmsd > 0.1 && f.rmsd < 1.0 / 5.0_f32.sqrt() + 1e-4);
+    }
+
+    #[test]
+    fn mismatched_lengths_fail() {
+        assert!(fit(&points(), &points()[..2]).is_none());
+        assert!(fit(&[], &[]).is_none());
+    }
+}